@@ -1,92 +1,55 @@
-use std::{io::Read, time::Duration};
-
-use xcursor::{
-    parser::{parse_xcursor, Image},
-    CursorTheme,
+use std::time::Duration;
+
+use smithay::{
+    input::pointer::{
+        cursor_theme::{Cursor as ThemeCursor, CursorFrame, CursorTheme},
+        CursorIcon,
+    },
+    reexports::xcursor::parser::Image,
 };
 
 static FALLBACK_CURSOR_DATA: &[u8] = include_bytes!("../resources/cursor.rgba");
 
 pub struct Cursor {
-    icons: Vec<Image>,
-    size: u32,
+    theme: CursorTheme,
+    default: ThemeCursor,
 }
 
 impl Cursor {
     pub fn load(log: &::slog::Logger) -> Cursor {
-        let name = std::env::var("XCURSOR_THEME")
-            .ok()
-            .unwrap_or_else(|| "default".into());
-        let size = std::env::var("XCURSOR_SIZE")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(24);
-
-        let theme = CursorTheme::load(&name);
-        let icons = load_icon(&theme)
-            .map_err(|err| slog::warn!(log, "Unable to load xcursor: {}, using fallback cursor", err))
-            .unwrap_or_else(|_| {
-                vec![Image {
-                    size: 32,
-                    width: 64,
-                    height: 64,
-                    xhot: 1,
-                    yhot: 1,
-                    delay: 1,
-                    pixels_rgba: Vec::from(FALLBACK_CURSOR_DATA),
-                    pixels_argb: vec![], //unused
-                }]
-            });
-
-        Cursor { icons, size }
+        let mut theme = CursorTheme::load_from_env(log.clone());
+        let default = theme.get_cursor(CursorIcon::Default).unwrap_or_else(|| {
+            slog::warn!(
+                log,
+                "Unable to load default xcursor of theme {}, using fallback cursor",
+                theme.name()
+            );
+            let image = Image {
+                size: 32,
+                width: 64,
+                height: 64,
+                xhot: 1,
+                yhot: 1,
+                delay: 1,
+                pixels_rgba: Vec::from(FALLBACK_CURSOR_DATA),
+                pixels_argb: vec![], //unused
+            };
+            ThemeCursor::from_images(image.size, vec![image]).unwrap()
+        });
+
+        Cursor { theme, default }
     }
 
-    pub fn get_image(&self, scale: u32, time: Duration) -> Image {
-        let size = self.size * scale;
-        frame(time.as_millis() as u32, size, &self.icons)
+    /// Get the frame of the given cursor, falling back to the default cursor if the theme lacks it
+    pub fn get_frame(&mut self, icon: CursorIcon, scale: i32, time: Duration) -> CursorFrame {
+        let cursor = self
+            .theme
+            .get_cursor(icon)
+            .unwrap_or_else(|| self.default.clone());
+        cursor.frame(scale, time)
     }
-}
-
-fn nearest_images(size: u32, images: &[Image]) -> impl Iterator<Item = &Image> {
-    // Follow the nominal size of the cursor to choose the nearest
-    let nearest_image = images
-        .iter()
-        .min_by_key(|image| (size as i32 - image.size as i32).abs())
-        .unwrap();
-
-    images
-        .iter()
-        .filter(move |image| image.width == nearest_image.width && image.height == nearest_image.height)
-}
 
-fn frame(mut millis: u32, size: u32, images: &[Image]) -> Image {
-    let total = nearest_images(size, images).fold(0, |acc, image| acc + image.delay);
-    millis %= total;
-
-    for img in nearest_images(size, images) {
-        if millis < img.delay {
-            return img.clone();
-        }
-        millis -= img.delay;
+    pub fn get_image(&self, scale: i32, time: Duration) -> Image {
+        self.default.image(scale, time).clone()
     }
-
-    unreachable!()
-}
-
-#[derive(thiserror::Error, Debug)]
-enum Error {
-    #[error("Theme has no default cursor")]
-    NoDefaultCursor,
-    #[error("Error opening xcursor file: {0}")]
-    File(#[from] std::io::Error),
-    #[error("Failed to parse XCursor file")]
-    Parse,
-}
-
-fn load_icon(theme: &CursorTheme) -> Result<Vec<Image>, Error> {
-    let icon_path = theme.load_icon("default").ok_or(Error::NoDefaultCursor)?;
-    let mut cursor_file = std::fs::File::open(&icon_path)?;
-    let mut cursor_data = Vec::new();
-    cursor_file.read_to_end(&mut cursor_data)?;
-    parse_xcursor(&cursor_data).ok_or(Error::Parse)
 }
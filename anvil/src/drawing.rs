@@ -3,11 +3,11 @@
 use smithay::{
     backend::renderer::{
         element::{
+            memory::{MemoryRenderBuffer, MemoryRenderBufferRenderElement},
             surface::WaylandSurfaceRenderElement,
-            texture::{TextureBuffer, TextureRenderElement},
             AsRenderElements,
         },
-        ImportAll, Renderer, Texture,
+        ImportAll, ImportMem, Renderer,
     },
     input::pointer::CursorImageStatus,
     render_elements,
@@ -18,46 +18,47 @@ use smithay::{
     backend::renderer::{
         element::{Element, Id, RenderElement},
         utils::CommitCounter,
-        Frame,
+        Frame, Texture,
     },
     utils::{Buffer, Logical, Rectangle, Size, Transform},
 };
 
 pub static CLEAR_COLOR: [f32; 4] = [0.8, 0.8, 0.9, 1.0];
-pub struct PointerElement<T: Texture> {
-    texture: Option<TextureBuffer<T>>,
+pub struct PointerElement {
+    buffer: Option<MemoryRenderBuffer>,
     status: CursorImageStatus,
 }
 
-impl<T: Texture> Default for PointerElement<T> {
+impl Default for PointerElement {
     fn default() -> Self {
         Self {
-            texture: Default::default(),
+            buffer: Default::default(),
             status: CursorImageStatus::Default,
         }
     }
 }
 
-impl<T: Texture> PointerElement<T> {
+impl PointerElement {
     pub fn set_status(&mut self, status: CursorImageStatus) {
         self.status = status;
     }
 
-    pub fn set_texture(&mut self, texture: TextureBuffer<T>) {
-        self.texture = Some(texture);
+    pub fn set_buffer(&mut self, buffer: MemoryRenderBuffer) {
+        self.buffer = Some(buffer);
     }
 }
 
 render_elements! {
     pub PointerRenderElement<R> where
-        R: ImportAll;
+        R: ImportAll + ImportMem;
     Surface=WaylandSurfaceRenderElement<R>,
-    Texture=TextureRenderElement<<R as Renderer>::TextureId>,
+    Memory=MemoryRenderBufferRenderElement<R>,
 }
 
-impl<T: Texture + Clone + 'static, R> AsRenderElements<R> for PointerElement<T>
+impl<R> AsRenderElements<R> for PointerElement
 where
-    R: Renderer<TextureId = T> + ImportAll,
+    R: Renderer + ImportAll + ImportMem,
+    <R as Renderer>::TextureId: 'static,
 {
     type RenderElement = PointerRenderElement<R>;
     fn render_elements<E>(
@@ -71,18 +72,21 @@ where
     {
         match &self.status {
             CursorImageStatus::Hidden => vec![],
-            CursorImageStatus::Default => {
-                if let Some(texture) = self.texture.as_ref() {
-                    vec![
-                        PointerRenderElement::<R>::from(TextureRenderElement::from_texture_buffer(
+            CursorImageStatus::Default | CursorImageStatus::Named(_) => {
+                if let Some(buffer) = self.buffer.as_ref() {
+                    vec![PointerRenderElement::<R>::from(
+                        MemoryRenderBufferRenderElement::from_buffer(
+                            renderer,
                             location.to_f64(),
-                            texture,
+                            buffer,
+                            None,
                             None,
                             None,
                             None,
-                        ))
-                        .into(),
-                    ]
+                        )
+                        .expect("Failed to import cursor bitmap"),
+                    )
+                    .into()]
                 } else {
                     vec![]
                 }
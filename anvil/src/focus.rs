@@ -3,12 +3,16 @@ pub use smithay::{
     desktop::{LayerSurface, PopupKind},
     input::{
         keyboard::{KeyboardTarget, KeysymHandle, ModifiersState},
-        pointer::{AxisFrame, ButtonEvent, MotionEvent, PointerTarget, RelativeMotionEvent},
+        pointer::{
+            AxisFrame, ButtonEvent, GestureHoldBeginEvent, GestureHoldEndEvent, GesturePinchBeginEvent,
+            GesturePinchEndEvent, GesturePinchUpdateEvent, GestureSwipeBeginEvent, GestureSwipeEndEvent,
+            GestureSwipeUpdateEvent, MotionEvent, PointerTarget, RelativeMotionEvent,
+        },
         Seat,
     },
     reexports::wayland_server::{backend::ObjectId, protocol::wl_surface::WlSurface, Resource},
     utils::{IsAlive, Serial},
-    wayland::seat::WaylandFocus,
+    wayland::{seat::WaylandFocus, session_lock::LockSurface},
 };
 
 use crate::{
@@ -21,6 +25,7 @@ pub enum FocusTarget {
     Window(WindowElement),
     LayerSurface(LayerSurface),
     Popup(PopupKind),
+    LockSurface(LockSurface),
 }
 
 impl IsAlive for FocusTarget {
@@ -29,6 +34,7 @@ impl IsAlive for FocusTarget {
             FocusTarget::Window(w) => w.alive(),
             FocusTarget::LayerSurface(l) => l.alive(),
             FocusTarget::Popup(p) => p.alive(),
+            FocusTarget::LockSurface(s) => s.wl_surface().alive(),
         }
     }
 }
@@ -50,6 +56,7 @@ impl<BackendData: Backend> PointerTarget<AnvilState<BackendData>> for FocusTarge
             FocusTarget::Window(w) => PointerTarget::enter(w, seat, data, event),
             FocusTarget::LayerSurface(l) => PointerTarget::enter(l, seat, data, event),
             FocusTarget::Popup(p) => PointerTarget::enter(p.wl_surface(), seat, data, event),
+            FocusTarget::LockSurface(s) => PointerTarget::enter(s.wl_surface(), seat, data, event),
         }
     }
     fn motion(
@@ -62,6 +69,7 @@ impl<BackendData: Backend> PointerTarget<AnvilState<BackendData>> for FocusTarge
             FocusTarget::Window(w) => PointerTarget::motion(w, seat, data, event),
             FocusTarget::LayerSurface(l) => PointerTarget::motion(l, seat, data, event),
             FocusTarget::Popup(p) => PointerTarget::motion(p.wl_surface(), seat, data, event),
+            FocusTarget::LockSurface(s) => PointerTarget::motion(s.wl_surface(), seat, data, event),
         }
     }
     fn relative_motion(
@@ -74,6 +82,7 @@ impl<BackendData: Backend> PointerTarget<AnvilState<BackendData>> for FocusTarge
             FocusTarget::Window(w) => PointerTarget::relative_motion(w, seat, data, event),
             FocusTarget::LayerSurface(l) => PointerTarget::relative_motion(l.wl_surface(), seat, data, event),
             FocusTarget::Popup(p) => PointerTarget::relative_motion(p.wl_surface(), seat, data, event),
+            FocusTarget::LockSurface(s) => PointerTarget::relative_motion(s.wl_surface(), seat, data, event),
         }
     }
     fn button(
@@ -86,6 +95,7 @@ impl<BackendData: Backend> PointerTarget<AnvilState<BackendData>> for FocusTarge
             FocusTarget::Window(w) => PointerTarget::button(w, seat, data, event),
             FocusTarget::LayerSurface(l) => PointerTarget::button(l, seat, data, event),
             FocusTarget::Popup(p) => PointerTarget::button(p.wl_surface(), seat, data, event),
+            FocusTarget::LockSurface(s) => PointerTarget::button(s.wl_surface(), seat, data, event),
         }
     }
     fn axis(
@@ -98,6 +108,7 @@ impl<BackendData: Backend> PointerTarget<AnvilState<BackendData>> for FocusTarge
             FocusTarget::Window(w) => PointerTarget::axis(w, seat, data, frame),
             FocusTarget::LayerSurface(l) => PointerTarget::axis(l, seat, data, frame),
             FocusTarget::Popup(p) => PointerTarget::axis(p.wl_surface(), seat, data, frame),
+            FocusTarget::LockSurface(s) => PointerTarget::axis(s.wl_surface(), seat, data, frame),
         }
     }
     fn leave(
@@ -111,6 +122,125 @@ impl<BackendData: Backend> PointerTarget<AnvilState<BackendData>> for FocusTarge
             FocusTarget::Window(w) => PointerTarget::leave(w, seat, data, serial, time),
             FocusTarget::LayerSurface(l) => PointerTarget::leave(l, seat, data, serial, time),
             FocusTarget::Popup(p) => PointerTarget::leave(p.wl_surface(), seat, data, serial, time),
+            FocusTarget::LockSurface(s) => PointerTarget::leave(s.wl_surface(), seat, data, serial, time),
+        }
+    }
+    fn gesture_swipe_begin(
+        &self,
+        seat: &Seat<AnvilState<BackendData>>,
+        data: &mut AnvilState<BackendData>,
+        event: &GestureSwipeBeginEvent,
+    ) {
+        match self {
+            FocusTarget::Window(w) => PointerTarget::gesture_swipe_begin(w, seat, data, event),
+            FocusTarget::LayerSurface(l) => PointerTarget::gesture_swipe_begin(l, seat, data, event),
+            FocusTarget::Popup(p) => PointerTarget::gesture_swipe_begin(p.wl_surface(), seat, data, event),
+            FocusTarget::LockSurface(s) => {
+                PointerTarget::gesture_swipe_begin(s.wl_surface(), seat, data, event)
+            }
+        }
+    }
+    fn gesture_swipe_update(
+        &self,
+        seat: &Seat<AnvilState<BackendData>>,
+        data: &mut AnvilState<BackendData>,
+        event: &GestureSwipeUpdateEvent,
+    ) {
+        match self {
+            FocusTarget::Window(w) => PointerTarget::gesture_swipe_update(w, seat, data, event),
+            FocusTarget::LayerSurface(l) => PointerTarget::gesture_swipe_update(l, seat, data, event),
+            FocusTarget::Popup(p) => PointerTarget::gesture_swipe_update(p.wl_surface(), seat, data, event),
+            FocusTarget::LockSurface(s) => {
+                PointerTarget::gesture_swipe_update(s.wl_surface(), seat, data, event)
+            }
+        }
+    }
+    fn gesture_swipe_end(
+        &self,
+        seat: &Seat<AnvilState<BackendData>>,
+        data: &mut AnvilState<BackendData>,
+        event: &GestureSwipeEndEvent,
+    ) {
+        match self {
+            FocusTarget::Window(w) => PointerTarget::gesture_swipe_end(w, seat, data, event),
+            FocusTarget::LayerSurface(l) => PointerTarget::gesture_swipe_end(l, seat, data, event),
+            FocusTarget::Popup(p) => PointerTarget::gesture_swipe_end(p.wl_surface(), seat, data, event),
+            FocusTarget::LockSurface(s) => {
+                PointerTarget::gesture_swipe_end(s.wl_surface(), seat, data, event)
+            }
+        }
+    }
+    fn gesture_pinch_begin(
+        &self,
+        seat: &Seat<AnvilState<BackendData>>,
+        data: &mut AnvilState<BackendData>,
+        event: &GesturePinchBeginEvent,
+    ) {
+        match self {
+            FocusTarget::Window(w) => PointerTarget::gesture_pinch_begin(w, seat, data, event),
+            FocusTarget::LayerSurface(l) => PointerTarget::gesture_pinch_begin(l, seat, data, event),
+            FocusTarget::Popup(p) => PointerTarget::gesture_pinch_begin(p.wl_surface(), seat, data, event),
+            FocusTarget::LockSurface(s) => {
+                PointerTarget::gesture_pinch_begin(s.wl_surface(), seat, data, event)
+            }
+        }
+    }
+    fn gesture_pinch_update(
+        &self,
+        seat: &Seat<AnvilState<BackendData>>,
+        data: &mut AnvilState<BackendData>,
+        event: &GesturePinchUpdateEvent,
+    ) {
+        match self {
+            FocusTarget::Window(w) => PointerTarget::gesture_pinch_update(w, seat, data, event),
+            FocusTarget::LayerSurface(l) => PointerTarget::gesture_pinch_update(l, seat, data, event),
+            FocusTarget::Popup(p) => PointerTarget::gesture_pinch_update(p.wl_surface(), seat, data, event),
+            FocusTarget::LockSurface(s) => {
+                PointerTarget::gesture_pinch_update(s.wl_surface(), seat, data, event)
+            }
+        }
+    }
+    fn gesture_pinch_end(
+        &self,
+        seat: &Seat<AnvilState<BackendData>>,
+        data: &mut AnvilState<BackendData>,
+        event: &GesturePinchEndEvent,
+    ) {
+        match self {
+            FocusTarget::Window(w) => PointerTarget::gesture_pinch_end(w, seat, data, event),
+            FocusTarget::LayerSurface(l) => PointerTarget::gesture_pinch_end(l, seat, data, event),
+            FocusTarget::Popup(p) => PointerTarget::gesture_pinch_end(p.wl_surface(), seat, data, event),
+            FocusTarget::LockSurface(s) => {
+                PointerTarget::gesture_pinch_end(s.wl_surface(), seat, data, event)
+            }
+        }
+    }
+    fn gesture_hold_begin(
+        &self,
+        seat: &Seat<AnvilState<BackendData>>,
+        data: &mut AnvilState<BackendData>,
+        event: &GestureHoldBeginEvent,
+    ) {
+        match self {
+            FocusTarget::Window(w) => PointerTarget::gesture_hold_begin(w, seat, data, event),
+            FocusTarget::LayerSurface(l) => PointerTarget::gesture_hold_begin(l, seat, data, event),
+            FocusTarget::Popup(p) => PointerTarget::gesture_hold_begin(p.wl_surface(), seat, data, event),
+            FocusTarget::LockSurface(s) => {
+                PointerTarget::gesture_hold_begin(s.wl_surface(), seat, data, event)
+            }
+        }
+    }
+    fn gesture_hold_end(
+        &self,
+        seat: &Seat<AnvilState<BackendData>>,
+        data: &mut AnvilState<BackendData>,
+        event: &GestureHoldEndEvent,
+    ) {
+        match self {
+            FocusTarget::Window(w) => PointerTarget::gesture_hold_end(w, seat, data, event),
+            FocusTarget::LayerSurface(l) => PointerTarget::gesture_hold_end(l, seat, data, event),
+            FocusTarget::Popup(p) => PointerTarget::gesture_hold_end(p.wl_surface(), seat, data, event),
+            FocusTarget::LockSurface(s) => PointerTarget::gesture_hold_end(s.wl_surface(), seat, data, event),
         }
     }
 }
@@ -127,6 +257,7 @@ impl<BackendData: Backend> KeyboardTarget<AnvilState<BackendData>> for FocusTarg
             FocusTarget::Window(w) => KeyboardTarget::enter(w, seat, data, keys, serial),
             FocusTarget::LayerSurface(l) => KeyboardTarget::enter(l, seat, data, keys, serial),
             FocusTarget::Popup(p) => KeyboardTarget::enter(p.wl_surface(), seat, data, keys, serial),
+            FocusTarget::LockSurface(s) => KeyboardTarget::enter(s.wl_surface(), seat, data, keys, serial),
         }
     }
     fn leave(
@@ -139,6 +270,7 @@ impl<BackendData: Backend> KeyboardTarget<AnvilState<BackendData>> for FocusTarg
             FocusTarget::Window(w) => KeyboardTarget::leave(w, seat, data, serial),
             FocusTarget::LayerSurface(l) => KeyboardTarget::leave(l, seat, data, serial),
             FocusTarget::Popup(p) => KeyboardTarget::leave(p.wl_surface(), seat, data, serial),
+            FocusTarget::LockSurface(s) => KeyboardTarget::leave(s.wl_surface(), seat, data, serial),
         }
     }
     fn key(
@@ -156,6 +288,9 @@ impl<BackendData: Backend> KeyboardTarget<AnvilState<BackendData>> for FocusTarg
             FocusTarget::Popup(p) => {
                 KeyboardTarget::key(p.wl_surface(), seat, data, key, state, serial, time)
             }
+            FocusTarget::LockSurface(s) => {
+                KeyboardTarget::key(s.wl_surface(), seat, data, key, state, serial, time)
+            }
         }
     }
     fn modifiers(
@@ -169,6 +304,9 @@ impl<BackendData: Backend> KeyboardTarget<AnvilState<BackendData>> for FocusTarg
             FocusTarget::Window(w) => KeyboardTarget::modifiers(w, seat, data, modifiers, serial),
             FocusTarget::LayerSurface(l) => KeyboardTarget::modifiers(l, seat, data, modifiers, serial),
             FocusTarget::Popup(p) => KeyboardTarget::modifiers(p.wl_surface(), seat, data, modifiers, serial),
+            FocusTarget::LockSurface(s) => {
+                KeyboardTarget::modifiers(s.wl_surface(), seat, data, modifiers, serial)
+            }
         }
     }
 }
@@ -179,6 +317,8 @@ impl WaylandFocus for FocusTarget {
             FocusTarget::Window(w) => w.wl_surface(),
             FocusTarget::LayerSurface(l) => Some(l.wl_surface().clone()),
             FocusTarget::Popup(p) => Some(p.wl_surface().clone()),
+            FocusTarget::LockSurface(s) => Some(s.wl_surface().clone()),
+            FocusTarget::LockSurface(s) => Some(s.wl_surface().clone()),
         }
     }
     fn same_client_as(&self, object_id: &ObjectId) -> bool {
@@ -188,6 +328,8 @@ impl WaylandFocus for FocusTarget {
             FocusTarget::Window(WindowElement::X11(w)) => w.same_client_as(object_id),
             FocusTarget::LayerSurface(l) => l.wl_surface().id().same_client_as(object_id),
             FocusTarget::Popup(p) => p.wl_surface().id().same_client_as(object_id),
+            FocusTarget::LockSurface(s) => s.wl_surface().id().same_client_as(object_id),
+            FocusTarget::LockSurface(s) => s.wl_surface().id().same_client_as(object_id),
         }
     }
 }
@@ -209,3 +351,9 @@ impl From<PopupKind> for FocusTarget {
         FocusTarget::Popup(p)
     }
 }
+
+impl From<LockSurface> for FocusTarget {
+    fn from(s: LockSurface) -> Self {
+        FocusTarget::LockSurface(s)
+    }
+}
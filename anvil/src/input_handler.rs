@@ -4,6 +4,8 @@ use crate::{focus::FocusTarget, shell::FullscreenSurface, AnvilState};
 
 #[cfg(feature = "udev")]
 use crate::udev::UdevData;
+#[cfg(feature = "udev")]
+use std::time::Duration;
 
 use smithay::{
     backend::input::{
@@ -37,15 +39,21 @@ use crate::state::Backend;
 use smithay::{
     backend::{
         input::{
-            Device, DeviceCapability, PointerMotionEvent, ProximityState, TabletToolButtonEvent,
-            TabletToolEvent, TabletToolProximityEvent, TabletToolTipEvent, TabletToolTipState,
+            Device, DeviceCapability, GestureBeginEvent, GestureEndEvent, GesturePinchUpdateEvent as _,
+            GestureSwipeUpdateEvent as _, PointerMotionEvent, ProximityState, TabletPadButtonEvent,
+            TabletPadEvent, TabletPadRingEvent, TabletPadStripEvent, TabletToolButtonEvent, TabletToolEvent,
+            TabletToolProximityEvent, TabletToolTipEvent, TabletToolTipState,
         },
         session::Session,
     },
-    input::pointer::RelativeMotionEvent,
+    input::pointer::{
+        GestureHoldBeginEvent, GestureHoldEndEvent, GesturePinchBeginEvent, GesturePinchEndEvent,
+        GesturePinchUpdateEvent, GestureSwipeBeginEvent, GestureSwipeEndEvent, GestureSwipeUpdateEvent,
+        RelativeMotionEvent,
+    },
     wayland::{
         seat::WaylandFocus,
-        tablet_manager::{TabletDescriptor, TabletSeatTrait},
+        tablet_manager::{TabletDescriptor, TabletPadDescriptor, TabletSeatTrait},
     },
 };
 
@@ -109,6 +117,19 @@ impl<BackendData: Backend> AnvilState<BackendData> {
         let mut suppressed_keys = self.suppressed_keys.clone();
         let keyboard = self.seat.get_keyboard().unwrap();
 
+        if self.session_lock_state.is_locked() {
+            // all input goes to the lock surfaces, without triggering any keybindings
+            if keyboard.current_focus().is_none() {
+                if let Some((lock_surface, _)) = self.lock_surface_under_pointer() {
+                    keyboard.set_focus(self, Some(lock_surface.into()), serial);
+                }
+            }
+            keyboard.input::<(), _>(self, keycode, state, serial, time, |_, _, _| {
+                FilterResult::Forward
+            });
+            return KeyAction::None;
+        }
+
         for layer in self.layer_shell_state.layer_surfaces().rev() {
             let data = with_states(layer.wl_surface(), |states| {
                 *states.cached_state.current::<LayerSurfaceCachedState>()
@@ -219,6 +240,14 @@ impl<BackendData: Backend> AnvilState<BackendData> {
         // subsurface menus (for example firefox-wayland).
         // see here for a discussion about that issue:
         // https://gitlab.freedesktop.org/wayland/wayland/-/issues/294
+        if self.session_lock_state.is_locked() {
+            // only lock surfaces may receive the keyboard focus
+            if let Some((lock_surface, _)) = self.lock_surface_under_pointer() {
+                keyboard.set_focus(self, Some(lock_surface.into()), serial);
+            }
+            return;
+        }
+
         if !pointer.is_grabbed() && (!keyboard.is_grabbed() || input_method.keyboard_grabbed()) {
             let output = self.space.output_under(self.pointer_location).next().cloned();
             if let Some(output) = output.as_ref() {
@@ -301,6 +330,12 @@ impl<BackendData: Backend> AnvilState<BackendData> {
     }
 
     pub fn surface_under(&self) -> Option<(FocusTarget, Point<i32, Logical>)> {
+        if self.session_lock_state.is_locked() {
+            return self
+                .lock_surface_under_pointer()
+                .map(|(lock_surface, location)| (lock_surface.into(), location));
+        }
+
         let pos = self.pointer_location;
         let output = self.space.outputs().find(|o| {
             let geometry = self.space.output_geometry(o).unwrap();
@@ -343,22 +378,34 @@ impl<BackendData: Backend> AnvilState<BackendData> {
             .unwrap_or_else(|| evt.amount_discrete(input::Axis::Vertical).unwrap_or(0.0) * 3.0);
         let horizontal_amount_discrete = evt.amount_discrete(input::Axis::Horizontal);
         let vertical_amount_discrete = evt.amount_discrete(input::Axis::Vertical);
+        let horizontal_amount_v120 = evt.amount_v120(input::Axis::Horizontal);
+        let vertical_amount_v120 = evt.amount_v120(input::Axis::Vertical);
 
         {
             let mut frame = AxisFrame::new(evt.time_msec()).source(evt.source());
             if horizontal_amount != 0.0 {
-                frame = frame.value(Axis::Horizontal, horizontal_amount);
+                frame = frame
+                    .relative_direction(Axis::Horizontal, evt.relative_direction(input::Axis::Horizontal))
+                    .value(Axis::Horizontal, horizontal_amount);
                 if let Some(discrete) = horizontal_amount_discrete {
                     frame = frame.discrete(Axis::Horizontal, discrete as i32);
                 }
+                if let Some(v120) = horizontal_amount_v120 {
+                    frame = frame.v120(Axis::Horizontal, v120 as i32);
+                }
             } else if evt.source() == AxisSource::Finger {
                 frame = frame.stop(Axis::Horizontal);
             }
             if vertical_amount != 0.0 {
-                frame = frame.value(Axis::Vertical, vertical_amount);
+                frame = frame
+                    .relative_direction(Axis::Vertical, evt.relative_direction(input::Axis::Vertical))
+                    .value(Axis::Vertical, vertical_amount);
                 if let Some(discrete) = vertical_amount_discrete {
                     frame = frame.discrete(Axis::Vertical, discrete as i32);
                 }
+                if let Some(v120) = vertical_amount_v120 {
+                    frame = frame.v120(Axis::Vertical, v120 as i32);
+                }
             } else if evt.source() == AxisSource::Finger {
                 frame = frame.stop(Axis::Vertical);
             }
@@ -390,6 +437,7 @@ impl<Backend: crate::state::Backend> AnvilState<Backend> {
                     output.change_current_state(None, None, Some(Scale::Fractional(new_scale)), None);
 
                     crate::shell::fixup_positions(&mut self.space);
+                    self.output_management_state.update::<Self>(&self.display_handle);
                     self.backend_data.reset_buffers(&output);
                 }
 
@@ -406,6 +454,7 @@ impl<Backend: crate::state::Backend> AnvilState<Backend> {
                     output.change_current_state(None, None, Some(Scale::Fractional(new_scale)), None);
 
                     crate::shell::fixup_positions(&mut self.space);
+                    self.output_management_state.update::<Self>(&self.display_handle);
                     self.backend_data.reset_buffers(&output);
                 }
 
@@ -427,6 +476,7 @@ impl<Backend: crate::state::Backend> AnvilState<Backend> {
                     };
                     output.change_current_state(None, Some(new_transform), None, None);
                     crate::shell::fixup_positions(&mut self.space);
+                    self.output_management_state.update::<Self>(&self.display_handle);
                     self.backend_data.reset_buffers(&output);
                 }
 
@@ -470,7 +520,8 @@ impl<Backend: crate::state::Backend> AnvilState<Backend> {
         let serial = SCOUNTER.next_serial();
 
         let under = self.surface_under();
-        self.seat.get_pointer().unwrap().motion(
+        let pointer = self.seat.get_pointer().unwrap();
+        pointer.motion(
             self,
             under,
             &MotionEvent {
@@ -479,12 +530,28 @@ impl<Backend: crate::state::Backend> AnvilState<Backend> {
                 time: evt.time_msec(),
             },
         );
+        // an active pointer constraint might have kept the pointer in place
+        self.pointer_location = pointer.current_location();
     }
 }
 
 #[cfg(feature = "udev")]
 impl AnvilState<UdevData> {
     pub fn process_input_event<B: InputBackend>(&mut self, dh: &DisplayHandle, event: InputEvent<B>) {
+        // tablet input is not processed through the keyboard or pointer of the seat
+        if matches!(
+            event,
+            InputEvent::TabletToolAxis { .. }
+                | InputEvent::TabletToolProximity { .. }
+                | InputEvent::TabletToolTip { .. }
+                | InputEvent::TabletToolButton { .. }
+                | InputEvent::TabletPadButton { .. }
+                | InputEvent::TabletPadRing { .. }
+                | InputEvent::TabletPadStrip { .. }
+        ) {
+            self.idle_notifier_state.notify_activity(&self.seat);
+        }
+
         match event {
             InputEvent::Keyboard { event, .. } => match self.keyboard_key_to_action::<B>(event) {
                 #[cfg(feature = "udev")]
@@ -531,6 +598,7 @@ impl AnvilState<UdevData> {
                         self.pointer_location = output_location + pointer_output_location;
 
                         crate::shell::fixup_positions(&mut self.space);
+                        self.output_management_state.update::<Self>(&self.display_handle);
                         let under = self.surface_under();
                         if let Some(ptr) = self.seat.get_pointer() {
                             ptr.motion(
@@ -570,6 +638,7 @@ impl AnvilState<UdevData> {
                         self.pointer_location = output_location + pointer_output_location;
 
                         crate::shell::fixup_positions(&mut self.space);
+                        self.output_management_state.update::<Self>(&self.display_handle);
                         let under = self.surface_under();
                         if let Some(ptr) = self.seat.get_pointer() {
                             ptr.motion(
@@ -604,6 +673,7 @@ impl AnvilState<UdevData> {
                         };
                         output.change_current_state(None, Some(new_transform), None, None);
                         crate::shell::fixup_positions(&mut self.space);
+                        self.output_management_state.update::<Self>(&self.display_handle);
                         self.backend_data.reset_buffers(&output);
                     }
                 }
@@ -624,12 +694,38 @@ impl AnvilState<UdevData> {
             InputEvent::TabletToolProximity { event, .. } => self.on_tablet_tool_proximity::<B>(dh, event),
             InputEvent::TabletToolTip { event, .. } => self.on_tablet_tool_tip::<B>(event),
             InputEvent::TabletToolButton { event, .. } => self.on_tablet_button::<B>(event),
+            InputEvent::TabletPadButton { event, .. } => self.on_tablet_pad_button::<B>(event),
+            InputEvent::TabletPadRing { event, .. } => self.on_tablet_pad_ring::<B>(event),
+            InputEvent::TabletPadStrip { event, .. } => self.on_tablet_pad_strip::<B>(event),
+            InputEvent::GestureSwipeBegin { event, .. } => self.on_gesture_swipe_begin::<B>(event),
+            InputEvent::GestureSwipeUpdate { event, .. } => self.on_gesture_swipe_update::<B>(event),
+            InputEvent::GestureSwipeEnd { event, .. } => self.on_gesture_swipe_end::<B>(event),
+            InputEvent::GesturePinchBegin { event, .. } => self.on_gesture_pinch_begin::<B>(event),
+            InputEvent::GesturePinchUpdate { event, .. } => self.on_gesture_pinch_update::<B>(event),
+            InputEvent::GesturePinchEnd { event, .. } => self.on_gesture_pinch_end::<B>(event),
+            InputEvent::GestureHoldBegin { event, .. } => self.on_gesture_hold_begin::<B>(event),
+            InputEvent::GestureHoldEnd { event, .. } => self.on_gesture_hold_end::<B>(event),
             InputEvent::DeviceAdded { device } => {
                 if device.has_capability(DeviceCapability::TabletTool) {
                     self.seat
                         .tablet_seat()
                         .add_tablet::<Self>(dh, &TabletDescriptor::from(&device));
                 }
+                if device.has_capability(DeviceCapability::TabletPad) {
+                    let tablet_seat = self.seat.tablet_seat();
+                    // announce the pad device as the tablet the pad is attached to
+                    let tablet = tablet_seat.add_tablet::<Self>(dh, &TabletDescriptor::from(&device));
+                    let pad = tablet_seat.add_pad::<Self>(dh, &TabletPadDescriptor::from(&device), &tablet);
+
+                    // the pad follows the keyboard focus
+                    let focus = self
+                        .seat
+                        .get_keyboard()
+                        .and_then(|keyboard| keyboard.current_focus())
+                        .and_then(|focus| focus.wl_surface());
+                    let time = Duration::from(self.clock.now()).as_millis() as u32;
+                    pad.set_focus(focus, SCOUNTER.next_serial(), time);
+                }
             }
             InputEvent::DeviceRemoved { device } => {
                 if device.has_capability(DeviceCapability::TabletTool) {
@@ -642,6 +738,12 @@ impl AnvilState<UdevData> {
                         tablet_seat.clear_tools();
                     }
                 }
+                if device.has_capability(DeviceCapability::TabletPad) {
+                    let tablet_seat = self.seat.tablet_seat();
+
+                    tablet_seat.remove_pad(&TabletPadDescriptor::from(&device));
+                    tablet_seat.remove_tablet(&TabletDescriptor::from(&device));
+                }
             }
             _ => {
                 // other events are not handled in anvil (yet)
@@ -661,14 +763,17 @@ impl AnvilState<UdevData> {
         if let Some(ptr) = self.seat.get_pointer() {
             ptr.motion(
                 self,
-                under.clone(),
+                under,
                 &MotionEvent {
                     location: self.pointer_location,
                     serial,
                     time: evt.time_msec(),
                 },
             );
+            // an active pointer constraint might have kept the pointer in place
+            self.pointer_location = ptr.current_location();
 
+            let under = self.surface_under();
             ptr.relative_motion(
                 self,
                 under,
@@ -718,6 +823,8 @@ impl AnvilState<UdevData> {
                     time: evt.time_msec(),
                 },
             );
+            // an active pointer constraint might have kept the pointer in place
+            self.pointer_location = ptr.current_location();
         }
     }
 
@@ -842,6 +949,159 @@ impl AnvilState<UdevData> {
         }
     }
 
+    fn on_tablet_pad_button<B: InputBackend>(&mut self, evt: B::TabletPadButtonEvent) {
+        let pad = self
+            .seat
+            .tablet_seat()
+            .get_pad(&TabletPadDescriptor::from(&evt.device()));
+
+        if let Some(pad) = pad {
+            pad.mode_switch(
+                evt.mode_group(),
+                evt.mode(),
+                SCOUNTER.next_serial(),
+                evt.time_msec(),
+            );
+            pad.button(evt.button(), evt.button_state(), evt.time_msec());
+        }
+    }
+
+    fn on_tablet_pad_ring<B: InputBackend>(&mut self, evt: B::TabletPadRingEvent) {
+        let pad = self
+            .seat
+            .tablet_seat()
+            .get_pad(&TabletPadDescriptor::from(&evt.device()));
+
+        if let Some(pad) = pad {
+            pad.mode_switch(
+                evt.mode_group(),
+                evt.mode(),
+                SCOUNTER.next_serial(),
+                evt.time_msec(),
+            );
+            pad.ring(evt.number(), evt.position(), evt.source(), evt.time_msec());
+        }
+    }
+
+    fn on_tablet_pad_strip<B: InputBackend>(&mut self, evt: B::TabletPadStripEvent) {
+        let pad = self
+            .seat
+            .tablet_seat()
+            .get_pad(&TabletPadDescriptor::from(&evt.device()));
+
+        if let Some(pad) = pad {
+            pad.mode_switch(
+                evt.mode_group(),
+                evt.mode(),
+                SCOUNTER.next_serial(),
+                evt.time_msec(),
+            );
+            pad.strip(evt.number(), evt.position(), evt.source(), evt.time_msec());
+        }
+    }
+
+    fn on_gesture_swipe_begin<B: InputBackend>(&mut self, evt: B::GestureSwipeBeginEvent) {
+        let serial = SCOUNTER.next_serial();
+        let pointer = self.seat.get_pointer().unwrap();
+        pointer.gesture_swipe_begin(
+            self,
+            &GestureSwipeBeginEvent {
+                serial,
+                time: evt.time_msec(),
+                fingers: evt.fingers(),
+            },
+        );
+    }
+
+    fn on_gesture_swipe_update<B: InputBackend>(&mut self, evt: B::GestureSwipeUpdateEvent) {
+        let pointer = self.seat.get_pointer().unwrap();
+        pointer.gesture_swipe_update(
+            self,
+            &GestureSwipeUpdateEvent {
+                time: evt.time_msec(),
+                delta: evt.delta(),
+            },
+        );
+    }
+
+    fn on_gesture_swipe_end<B: InputBackend>(&mut self, evt: B::GestureSwipeEndEvent) {
+        let serial = SCOUNTER.next_serial();
+        let pointer = self.seat.get_pointer().unwrap();
+        pointer.gesture_swipe_end(
+            self,
+            &GestureSwipeEndEvent {
+                serial,
+                time: evt.time_msec(),
+                cancelled: evt.cancelled(),
+            },
+        );
+    }
+
+    fn on_gesture_pinch_begin<B: InputBackend>(&mut self, evt: B::GesturePinchBeginEvent) {
+        let serial = SCOUNTER.next_serial();
+        let pointer = self.seat.get_pointer().unwrap();
+        pointer.gesture_pinch_begin(
+            self,
+            &GesturePinchBeginEvent {
+                serial,
+                time: evt.time_msec(),
+                fingers: evt.fingers(),
+            },
+        );
+    }
+
+    fn on_gesture_pinch_update<B: InputBackend>(&mut self, evt: B::GesturePinchUpdateEvent) {
+        let pointer = self.seat.get_pointer().unwrap();
+        pointer.gesture_pinch_update(
+            self,
+            &GesturePinchUpdateEvent {
+                time: evt.time_msec(),
+                delta: evt.delta(),
+                scale: evt.scale(),
+                rotation: evt.rotation(),
+            },
+        );
+    }
+
+    fn on_gesture_pinch_end<B: InputBackend>(&mut self, evt: B::GesturePinchEndEvent) {
+        let serial = SCOUNTER.next_serial();
+        let pointer = self.seat.get_pointer().unwrap();
+        pointer.gesture_pinch_end(
+            self,
+            &GesturePinchEndEvent {
+                serial,
+                time: evt.time_msec(),
+                cancelled: evt.cancelled(),
+            },
+        );
+    }
+
+    fn on_gesture_hold_begin<B: InputBackend>(&mut self, evt: B::GestureHoldBeginEvent) {
+        let serial = SCOUNTER.next_serial();
+        let pointer = self.seat.get_pointer().unwrap();
+        pointer.gesture_hold_begin(
+            self,
+            &GestureHoldBeginEvent {
+                serial,
+                time: evt.time_msec(),
+                fingers: evt.fingers(),
+            },
+        );
+    }
+
+    fn on_gesture_hold_end<B: InputBackend>(&mut self, evt: B::GestureHoldEndEvent) {
+        let serial = SCOUNTER.next_serial();
+        let pointer = self.seat.get_pointer().unwrap();
+        pointer.gesture_hold_end(
+            self,
+            &GestureHoldEndEvent {
+                serial,
+                time: evt.time_msec(),
+                cancelled: evt.cancelled(),
+            },
+        );
+    }
+
     fn clamp_coords(&self, pos: Point<f64, Logical>) -> Point<f64, Logical> {
         if self.space.outputs().next().is_none() {
             return pos;
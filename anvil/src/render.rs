@@ -1,29 +1,33 @@
 use smithay::{
-    backend::renderer::{
-        damage::{DamageTrackedRenderer, DamageTrackedRendererError, DamageTrackedRendererMode},
-        element::{
-            surface::WaylandSurfaceRenderElement,
-            utils::{
-                ConstrainAlign, ConstrainScaleBehavior, CropRenderElement, RelocateRenderElement,
-                RescaleRenderElement,
+    backend::{
+        allocator::dmabuf::Dmabuf,
+        renderer::{
+            damage::{DamageTrackedRenderer, DamageTrackedRendererError, DamageTrackedRendererMode},
+            element::{
+                surface::{render_elements_from_surface_tree, WaylandSurfaceRenderElement},
+                utils::{
+                    ConstrainAlign, ConstrainScaleBehavior, CropRenderElement, RelocateRenderElement,
+                    RescaleRenderElement,
+                },
+                AsRenderElements, RenderElementStates,
             },
-            AsRenderElements, RenderElementStates,
+            Blit, ExportMem, ImportAll, ImportMem, Renderer,
         },
-        ImportAll, ImportMem, Renderer,
     },
     desktop::{
         self,
         space::{constrain_space_element, ConstrainBehavior, ConstrainReference, Space},
     },
     output::Output,
-    utils::{Physical, Point, Rectangle, Size},
+    utils::{Monotonic, Physical, Point, Rectangle, Size, Time},
+    wayland::screencopy::{self, Screencopy},
 };
 
 #[cfg(feature = "debug")]
 use crate::drawing::FpsElement;
 use crate::{
     drawing::{PointerRenderElement, CLEAR_COLOR},
-    shell::{FullscreenSurface, WindowElement, WindowRenderElement},
+    shell::{FullscreenSurface, SessionLockSurface, WindowElement, WindowRenderElement},
 };
 
 smithay::backend::renderer::element::render_elements! {
@@ -56,6 +60,7 @@ pub fn render_output<'a, R>(
     damage_tracked_renderer: &mut DamageTrackedRenderer,
     age: usize,
     show_window_preview: bool,
+    session_locked: bool,
     log: &slog::Logger,
 ) -> Result<(Option<Vec<Rectangle<i32, Physical>>>, RenderElementStates), DamageTrackedRendererError<R>>
 where
@@ -64,6 +69,37 @@ where
 {
     let output_scale = output.current_scale().fractional_scale().into();
 
+    if session_locked {
+        // nothing but the lock surface of the output may be shown while the session is locked
+        let lock_render_elements: Vec<CustomRenderElements<R>> = output
+            .user_data()
+            .get::<SessionLockSurface>()
+            .and_then(|lock_surface| lock_surface.get())
+            .map(|lock_surface| {
+                render_elements_from_surface_tree(
+                    renderer,
+                    lock_surface.wl_surface(),
+                    (0, 0),
+                    output_scale,
+                    log.clone(),
+                )
+            })
+            .unwrap_or_default();
+
+        let render_elements = custom_elements
+            .iter()
+            .chain(lock_render_elements.iter())
+            .collect::<Vec<_>>();
+
+        return damage_tracked_renderer.render_output(
+            renderer,
+            age,
+            &render_elements,
+            CLEAR_COLOR,
+            log.clone(),
+        );
+    }
+
     if let Some(window) = output
         .user_data()
         .get::<FullscreenSurface>()
@@ -152,3 +188,47 @@ where
         )
     }
 }
+
+/// Whether the pending screencopy frames of `output` need the output to be fully redrawn
+pub fn screencopy_pending(pending_screencopy: &[Screencopy], output: &Output) -> bool {
+    pending_screencopy
+        .iter()
+        .any(|frame| frame.output() == output && (!frame.with_damage() || !frame.damage().is_empty()))
+}
+
+/// Copies the framebuffer of `output` into its pending screencopy frames
+///
+/// Needs to be called after `render_output` while the framebuffer is still bound.
+/// `damage` is the damage returned by `render_output`.
+pub fn submit_screencopy<R>(
+    pending_screencopy: &mut Vec<Screencopy>,
+    output: &Output,
+    renderer: &mut R,
+    damage: Option<&[Rectangle<i32, Physical>]>,
+    time: Time<Monotonic>,
+    log: &slog::Logger,
+) where
+    R: ExportMem + Blit<Dmabuf>,
+{
+    // nothing was rendered, the framebuffer might not contain the current contents
+    let damage = match damage {
+        Some(damage) => damage,
+        None => return,
+    };
+    screencopy::damage_output(output, damage);
+
+    for frame in std::mem::take(pending_screencopy) {
+        if frame.output() != output || (frame.with_damage() && frame.damage().is_empty()) {
+            pending_screencopy.push(frame);
+            continue;
+        }
+
+        match frame.copy_framebuffer(renderer) {
+            Ok(()) => frame.submit(time),
+            Err(err) => {
+                warn!(log, "Failed to copy the output for screencopy: {}", err);
+                frame.failed();
+            }
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use std::cell::RefCell;
 
+#[cfg(feature = "udev")]
+use smithay::wayland::drm_syncobj::DrmSyncobjCachedState;
 #[cfg(feature = "xwayland")]
 use smithay::xwayland::X11Wm;
 use smithay::{
@@ -9,14 +11,22 @@ use smithay::{
         WindowSurfaceType,
     },
     output::Output,
-    reexports::wayland_server::protocol::{wl_buffer::WlBuffer, wl_output, wl_surface::WlSurface},
-    utils::{Logical, Point, Rectangle, Size},
+    reexports::{
+        calloop::Interest,
+        wayland_server::{
+            protocol::{wl_buffer::WlBuffer, wl_output, wl_surface::WlSurface},
+            Resource,
+        },
+    },
+    utils::{Logical, Point, Rectangle, Size, SERIAL_COUNTER},
     wayland::{
         buffer::BufferHandler,
         compositor::{
-            get_parent, is_sync_subsurface, with_states, with_surface_tree_upward, CompositorHandler,
-            CompositorState, TraversalAction,
+            add_blocker, get_parent, is_sync_subsurface, with_states, with_surface_tree_upward,
+            BufferAssignment, CompositorHandler, CompositorState, SurfaceAttributes, TraversalAction,
         },
+        dmabuf::get_dmabuf,
+        session_lock::{LockSurface, SessionLockHandler, SessionLockManagerState, SessionLocker},
         shell::{
             wlr_layer::{
                 Layer, LayerSurface as WlrLayerSurface, LayerSurfaceData, WlrLayerShellHandler,
@@ -80,6 +90,24 @@ impl FullscreenSurface {
     }
 }
 
+/// Lock surface of an output, while the session is locked
+#[derive(Default)]
+pub struct SessionLockSurface(RefCell<Option<LockSurface>>);
+
+impl SessionLockSurface {
+    pub fn set(&self, surface: LockSurface) {
+        *self.0.borrow_mut() = Some(surface);
+    }
+
+    pub fn get(&self) -> Option<LockSurface> {
+        self.0.borrow().clone()
+    }
+
+    pub fn clear(&self) -> Option<LockSurface> {
+        self.0.borrow_mut().take()
+    }
+}
+
 impl<BackendData: Backend> BufferHandler for AnvilState<BackendData> {
     fn buffer_destroyed(&mut self, _buffer: &WlBuffer) {}
 }
@@ -88,6 +116,56 @@ impl<BackendData: Backend> CompositorHandler for AnvilState<BackendData> {
     fn compositor_state(&mut self) -> &mut CompositorState {
         &mut self.compositor_state
     }
+    fn pre_commit(&mut self, surface: &WlSurface) {
+        let client = match self.display_handle.get_client(surface.id()) {
+            Ok(client) => client.id(),
+            Err(_) => return,
+        };
+
+        // wait for the client to finish rendering into an explicitly synchronized buffer
+        #[cfg(feature = "udev")]
+        {
+            let acquire_point = with_states(surface, |states| {
+                states
+                    .cached_state
+                    .pending::<DrmSyncobjCachedState>()
+                    .acquire_point
+                    .clone()
+            });
+            if let Some(acquire_point) = acquire_point {
+                if let Ok((blocker, source)) = acquire_point.generate_blocker() {
+                    let res = self.handle.insert_source(source, move |_, _, data| {
+                        let dh = data.state.display_handle.clone();
+                        CompositorState::blocker_cleared(&mut data.state, &dh, &client);
+                    });
+                    if res.is_ok() {
+                        add_blocker(surface, blocker);
+                    }
+                }
+                return;
+            }
+        }
+
+        // otherwise wait for the implicit fences of a newly attached dmabuf,
+        // so a slow client does not stall rendering
+        let maybe_dmabuf = with_states(surface, |states| {
+            match states.cached_state.pending::<SurfaceAttributes>().buffer.as_ref() {
+                Some(BufferAssignment::NewBuffer(buffer)) => get_dmabuf(buffer).ok(),
+                _ => None,
+            }
+        });
+        if let Some(dmabuf) = maybe_dmabuf {
+            if let Ok((blocker, source)) = dmabuf.generate_blocker(Interest::READ) {
+                let res = self.handle.insert_source(source, move |_, _, data| {
+                    let dh = data.state.display_handle.clone();
+                    CompositorState::blocker_cleared(&mut data.state, &dh, &client);
+                });
+                if res.is_ok() {
+                    add_blocker(surface, blocker);
+                }
+            }
+        }
+    }
     fn commit(&mut self, surface: &WlSurface) {
         #[cfg(feature = "xwayland")]
         X11Wm::commit_hook::<CalloopData<BackendData>>(surface);
@@ -131,7 +209,69 @@ impl<BackendData: Backend> WlrLayerShellHandler for AnvilState<BackendData> {
     }
 }
 
+impl<BackendData: Backend> SessionLockHandler for AnvilState<BackendData> {
+    fn lock_state(&mut self) -> &mut SessionLockManagerState {
+        &mut self.session_lock_state
+    }
+
+    fn lock(&mut self, confirmation: SessionLocker) {
+        // From now on only lock surfaces are rendered and receive input,
+        // so the contents of the outputs are hidden with the next frame.
+        confirmation.lock();
+
+        let keyboard = self.seat.get_keyboard().unwrap();
+        keyboard.set_focus(self, None, SERIAL_COUNTER.next_serial());
+    }
+
+    fn unlock(&mut self) {
+        for output in self.space.outputs() {
+            if let Some(lock_surface) = output.user_data().get::<SessionLockSurface>() {
+                lock_surface.clear();
+            }
+        }
+
+        let keyboard = self.seat.get_keyboard().unwrap();
+        keyboard.set_focus(self, None, SERIAL_COUNTER.next_serial());
+    }
+
+    fn new_surface(&mut self, surface: LockSurface, wl_output: wl_output::WlOutput) {
+        let output = match Output::from_resource(&wl_output) {
+            Some(output) => output,
+            None => return,
+        };
+        let output_geo = match self.space.output_geometry(&output) {
+            Some(geo) => geo,
+            None => return,
+        };
+
+        surface.with_pending_state(|state| {
+            state.size = Some((output_geo.size.w as u32, output_geo.size.h as u32).into());
+        });
+        surface.send_configure();
+
+        output.user_data().insert_if_missing(SessionLockSurface::default);
+        output
+            .user_data()
+            .get::<SessionLockSurface>()
+            .unwrap()
+            .set(surface.clone());
+
+        let keyboard = self.seat.get_keyboard().unwrap();
+        if keyboard.current_focus().is_none() {
+            keyboard.set_focus(self, Some(surface.into()), SERIAL_COUNTER.next_serial());
+        }
+    }
+}
+
 impl<BackendData: Backend> AnvilState<BackendData> {
+    /// The lock surface of the output under the pointer, while the session is locked
+    pub fn lock_surface_under_pointer(&self) -> Option<(LockSurface, Point<i32, Logical>)> {
+        let output = self.space.output_under(self.pointer_location).next()?;
+        let output_geo = self.space.output_geometry(output)?;
+        let lock_surface = output.user_data().get::<SessionLockSurface>()?.get()?;
+        Some((lock_surface, output_geo.loc))
+    }
+
     pub fn window_for_surface(&self, surface: &WlSurface) -> Option<WindowElement> {
         self.space
             .elements()
@@ -284,11 +424,15 @@ pub fn fixup_positions(space: &mut Space<WindowElement>) {
             .map(|geo| geo.size)
             .unwrap_or_else(|| Size::from((0, 0)));
         space.map_output(&output, offset);
+        output.change_current_state(None, None, None, Some(offset));
         layer_map_for_output(&output).arrange();
         offset.x += size.w;
     }
 
-    // fixup windows
+    fixup_window_positions(space);
+}
+
+pub fn fixup_window_positions(space: &mut Space<WindowElement>) {
     let mut orphaned_windows = Vec::new();
     let outputs = space
         .outputs()
@@ -1,17 +1,27 @@
-use std::cell::RefCell;
+use std::{cell::RefCell, os::unix::io::OwnedFd};
 
 use smithay::{
     desktop::space::SpaceElement,
     input::pointer::Focus,
+    reexports::wayland_server::protocol::wl_data_device_manager::DndAction,
     utils::{Logical, Rectangle, SERIAL_COUNTER},
-    wayland::compositor::with_states,
+    wayland::{
+        compositor::with_states,
+        data_device::{
+            clear_data_device_selection, request_data_device_client_selection, set_data_device_selection,
+            start_dnd, SourceMetadata,
+        },
+        primary_selection::{
+            clear_primary_selection, request_primary_client_selection, set_primary_selection,
+        },
+    },
     xwayland::{
-        xwm::{Reorder, ResizeEdge as X11ResizeEdge, XwmId},
+        xwm::{Reorder, ResizeEdge as X11ResizeEdge, SelectionTarget, XwmId},
         X11Surface, X11Wm, XwmHandler,
     },
 };
 
-use crate::{state::Backend, AnvilState, CalloopData};
+use crate::{focus::FocusTarget, state::Backend, AnvilState, CalloopData};
 
 use super::{
     place_new_window, FullscreenSurface, MoveSurfaceGrab, ResizeData, ResizeState, ResizeSurfaceGrab,
@@ -229,6 +239,66 @@ impl<BackendData: Backend> XwmHandler for CalloopData<BackendData> {
     fn move_request(&mut self, _xwm: XwmId, window: X11Surface, _button: u32) {
         self.state.move_request_x11(&window)
     }
+
+    fn allow_selection_access(&mut self, _xwm: XwmId, _selection: SelectionTarget) -> bool {
+        // only allow the focused X11 window to read the selection
+        let keyboard = self.state.seat.get_keyboard().unwrap();
+        matches!(
+            keyboard.current_focus(),
+            Some(FocusTarget::Window(WindowElement::X11(_)))
+        )
+    }
+
+    fn send_selection(&mut self, _xwm: XwmId, selection: SelectionTarget, mime_type: String, fd: OwnedFd) {
+        let res = match selection {
+            SelectionTarget::Clipboard => {
+                request_data_device_client_selection(&self.state.seat, mime_type, fd)
+            }
+            SelectionTarget::Primary => request_primary_client_selection(&self.state.seat, mime_type, fd),
+        };
+        if let Err(err) = res {
+            slog::warn!(
+                self.state.log,
+                "Failed to request current wayland selection: {}",
+                err
+            );
+        }
+    }
+
+    fn new_selection(&mut self, _xwm: XwmId, selection: SelectionTarget, mime_types: Vec<String>) {
+        let dh = &self.state.display_handle;
+        match selection {
+            SelectionTarget::Clipboard => set_data_device_selection(dh, &self.state.seat, mime_types),
+            SelectionTarget::Primary => set_primary_selection(dh, &self.state.seat, mime_types),
+        }
+    }
+
+    fn cleared_selection(&mut self, _xwm: XwmId, selection: SelectionTarget) {
+        let dh = &self.state.display_handle;
+        match selection {
+            SelectionTarget::Clipboard => clear_data_device_selection(dh, &self.state.seat),
+            SelectionTarget::Primary => clear_primary_selection(dh, &self.state.seat),
+        }
+    }
+
+    fn start_dnd(&mut self, _xwm: XwmId, mime_types: Vec<String>, actions: DndAction) {
+        // continue the drag of the X11 client with the currently held pointer grab
+        let pointer = self.state.seat.get_pointer().unwrap();
+        let Some(start_data) = pointer.grab_start_data() else { return };
+        let dh = self.state.display_handle.clone();
+        let seat = self.state.seat.clone();
+        start_dnd(
+            &dh,
+            &seat,
+            &mut self.state,
+            SERIAL_COUNTER.next_serial(),
+            start_data,
+            SourceMetadata {
+                mime_types,
+                dnd_action: actions,
+            },
+        );
+    }
 }
 
 impl<BackendData: Backend> AnvilState<BackendData> {
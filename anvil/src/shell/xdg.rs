@@ -2,8 +2,9 @@ use std::cell::RefCell;
 
 use smithay::{
     desktop::{
-        find_popup_root_surface, layer_map_for_output, space::SpaceElement, PopupKeyboardGrab, PopupKind,
-        PopupPointerGrab, PopupUngrabStrategy, Window, WindowSurfaceType,
+        find_popup_root_surface, get_popup_toplevel_coords, layer_map_for_output, space::SpaceElement,
+        PopupKeyboardGrab, PopupKind, PopupManager, PopupPointerGrab, PopupUngrabStrategy, Window,
+        WindowSurfaceType,
     },
     input::{pointer::Focus, Seat},
     output::Output,
@@ -53,14 +54,10 @@ impl<BackendData: Backend> XdgShellHandler for AnvilState<BackendData> {
         // of a xdg_surface has to be sent during the commit if
         // the surface is not already configured
 
-        // TODO: properly recompute the geometry with the whole of positioner state
         surface.with_pending_state(|state| {
-            // NOTE: This is not really necessary as the default geometry
-            // is already set the same way, but for demonstrating how
-            // to set the initial popup geometry this code is left as
-            // an example
             state.geometry = positioner.get_geometry();
         });
+        self.unconstrain_popup(&surface);
         if let Err(err) = self.popups.track_popup(PopupKind::from(surface)) {
             slog::warn!(self.log, "Failed to track popup: {}", err);
         }
@@ -68,14 +65,10 @@ impl<BackendData: Backend> XdgShellHandler for AnvilState<BackendData> {
 
     fn reposition_request(&mut self, surface: PopupSurface, positioner: PositionerState, token: u32) {
         surface.with_pending_state(|state| {
-            // NOTE: This is again a simplification, a proper compositor would
-            // calculate the geometry of the popup here. For simplicity we just
-            // use the default implementation here that does not take the
-            // window position and output constraints into account.
-            let geometry = positioner.get_geometry();
-            state.geometry = geometry;
+            state.geometry = positioner.get_geometry();
             state.positioner = positioner;
         });
+        self.unconstrain_popup(&surface);
         surface.send_repositioned(token);
     }
 
@@ -331,6 +324,43 @@ impl<BackendData: Backend> XdgShellHandler for AnvilState<BackendData> {
 }
 
 impl<BackendData: Backend> AnvilState<BackendData> {
+    fn unconstrain_popup(&self, popup: &PopupSurface) {
+        let kind = PopupKind::Xdg(popup.clone());
+        let root = match find_popup_root_surface(&kind) {
+            Ok(root) => root,
+            Err(_) => return,
+        };
+        let window = match self.window_for_surface(&root) {
+            Some(window) => window,
+            None => return,
+        };
+        let window_location = match self.space.element_location(&window) {
+            Some(location) => location,
+            None => return,
+        };
+
+        // fit the popup into the output its parent is on, as the union of
+        // all outputs may contain gaps not covered by any of them
+        let parent_location = window_location + get_popup_toplevel_coords(&kind);
+        let output = self
+            .space
+            .outputs()
+            .find(|output| {
+                self.space
+                    .output_geometry(output)
+                    .map(|geo| geo.contains(parent_location))
+                    .unwrap_or(false)
+            })
+            .cloned()
+            .or_else(|| self.space.outputs_for_element(&window).into_iter().next());
+        let target = match output.and_then(|output| self.space.output_geometry(&output)) {
+            Some(target) => target,
+            None => return,
+        };
+
+        PopupManager::unconstrain_popup(&kind, window_location, target);
+    }
+
     pub fn move_request_xdg(&mut self, surface: &ToplevelSurface, seat: &Seat<Self>, serial: Serial) {
         // TODO: touch move.
         let pointer = seat.get_pointer().unwrap();
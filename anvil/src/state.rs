@@ -1,4 +1,5 @@
 use std::{
+    cell::RefCell,
     os::unix::io::{AsRawFd, OwnedFd},
     sync::{atomic::AtomicBool, Arc, Mutex},
     time::Duration,
@@ -6,74 +7,116 @@ use std::{
 
 use smithay::{
     backend::renderer::element::{default_primary_scanout_output_compare, RenderElementStates},
-    delegate_compositor, delegate_data_device, delegate_fractional_scale, delegate_input_method_manager,
-    delegate_keyboard_shortcuts_inhibit, delegate_layer_shell, delegate_output, delegate_presentation,
-    delegate_primary_selection, delegate_relative_pointer, delegate_seat, delegate_shm,
-    delegate_tablet_manager, delegate_text_input_manager, delegate_viewporter,
-    delegate_virtual_keyboard_manager, delegate_xdg_activation, delegate_xdg_decoration, delegate_xdg_shell,
+    delegate_compositor, delegate_cursor_shape, delegate_data_control, delegate_data_device,
+    delegate_foreign_toplevel, delegate_fractional_scale, delegate_idle_inhibit, delegate_idle_notify,
+    delegate_input_method_manager, delegate_keyboard_shortcuts_inhibit, delegate_layer_shell,
+    delegate_output, delegate_output_management, delegate_pointer_constraints, delegate_pointer_gestures,
+    delegate_presentation, delegate_primary_selection, delegate_relative_pointer, delegate_screencopy,
+    delegate_seat, delegate_security_context, delegate_session_lock, delegate_shm, delegate_tablet_manager,
+    delegate_tearing_control, delegate_text_input_manager, delegate_viewporter,
+    delegate_virtual_keyboard_manager, delegate_xdg_activation, delegate_xdg_decoration,
+    delegate_xdg_foreign, delegate_xdg_shell,
     desktop::{
+        layer_map_for_output,
         utils::{
-            surface_presentation_feedback_flags_from_states, surface_primary_scanout_output,
-            update_surface_primary_scanout_output, OutputPresentationFeedback,
+            send_frames_surface_tree, surface_presentation_feedback_flags_from_states,
+            surface_primary_scanout_output, update_surface_primary_scanout_output,
+            OutputPresentationFeedback,
         },
         PopupManager, Space,
     },
-    input::{keyboard::XkbConfig, pointer::CursorImageStatus, Seat, SeatHandler, SeatState},
-    output::Output,
+    input::{
+        keyboard::XkbConfig,
+        pointer::{CursorImageStatus, PointerHandle},
+        Seat, SeatHandler, SeatState,
+    },
+    output::{Output, Scale},
     reexports::{
         calloop::{generic::Generic, Interest, LoopHandle, Mode, PostAction},
-        wayland_protocols::xdg::decoration::{
-            self as xdg_decoration, zv1::server::zxdg_toplevel_decoration_v1::Mode as DecorationMode,
+        wayland_protocols::{
+            wp::primary_selection::zv1::server::zwp_primary_selection_source_v1::ZwpPrimarySelectionSourceV1 as PrimarySource,
+            xdg::decoration::{
+                self as xdg_decoration, zv1::server::zxdg_toplevel_decoration_v1::Mode as DecorationMode,
+            },
         },
+        wayland_protocols_wlr::data_control::v1::server::zwlr_data_control_source_v1::ZwlrDataControlSourceV1,
         wayland_server::{
             backend::{ClientData, ClientId, DisconnectReason},
-            protocol::{wl_data_source::WlDataSource, wl_surface::WlSurface},
-            Display, DisplayHandle, Resource,
+            protocol::{
+                wl_data_source::WlDataSource, wl_output::WlOutput, wl_seat::WlSeat, wl_surface::WlSurface,
+            },
+            Client, Display, DisplayHandle, Resource,
         },
     },
-    utils::{Clock, Logical, Monotonic, Point},
+    utils::{Clock, Logical, Monotonic, Point, SERIAL_COUNTER},
     wayland::{
         compositor::{get_parent, with_states, CompositorState},
+        cursor_shape::CursorShapeManagerState,
+        data_control::{DataControlHandler, DataControlState},
         data_device::{
             set_data_device_focus, ClientDndGrabHandler, DataDeviceHandler, DataDeviceState,
             ServerDndGrabHandler,
         },
+        foreign_toplevel::{ForeignToplevelHandle, ForeignToplevelHandler, ForeignToplevelState},
         fractional_scale::{with_fractional_scale, FractionScaleHandler, FractionalScaleManagerState},
+        idle_inhibit::{IdleInhibitHandler, IdleInhibitManagerState},
+        idle_notify::{IdleNotifierHandler, IdleNotifierState},
         input_method::{InputMethodManagerState, InputMethodSeat},
         keyboard_shortcuts_inhibit::{
             KeyboardShortcutsInhibitHandler, KeyboardShortcutsInhibitState, KeyboardShortcutsInhibitor,
         },
         output::OutputManagerState,
+        output_management::{
+            ModeConfiguration, OutputConfiguration, OutputManagementHandler, OutputManagementState,
+        },
+        pointer_constraints::{with_pointer_constraint, PointerConstraintsHandler, PointerConstraintsState},
+        pointer_gestures::PointerGesturesState,
         presentation::PresentationState,
         primary_selection::{set_primary_focus, PrimarySelectionHandler, PrimarySelectionState},
         relative_pointer::RelativePointerManagerState,
+        screencopy::{Screencopy, ScreencopyHandler, ScreencopyState},
         seat::WaylandFocus,
+        security_context::{
+            SecurityContext, SecurityContextHandler, SecurityContextListenerSource, SecurityContextState,
+        },
+        session_lock::SessionLockManagerState,
         shell::{
             wlr_layer::WlrLayerShellState,
             xdg::{
                 decoration::{XdgDecorationHandler, XdgDecorationState},
-                ToplevelSurface, XdgShellState, XdgToplevelSurfaceData,
+                ToplevelSurface, XdgShellHandler, XdgShellState, XdgToplevelSurfaceData,
             },
         },
         shm::{ShmHandler, ShmState},
         socket::ListeningSocketSource,
         tablet_manager::TabletSeatTrait,
+        tearing_control::TearingControlState,
         text_input::TextInputManagerState,
         viewporter::ViewporterState,
         virtual_keyboard::VirtualKeyboardManagerState,
         xdg_activation::{
             XdgActivationHandler, XdgActivationState, XdgActivationToken, XdgActivationTokenData,
         },
+        xdg_foreign::{XdgForeignHandler, XdgForeignState},
     },
 };
 
 #[cfg(feature = "xwayland")]
 use crate::cursor::Cursor;
-use crate::{focus::FocusTarget, shell::WindowElement};
+use crate::{
+    focus::FocusTarget,
+    shell::{SessionLockSurface, WindowElement},
+};
 #[cfg(feature = "xwayland")]
 use smithay::{
+    reexports::wayland_server::protocol::wl_data_device_manager::DndAction,
     utils::Size,
-    xwayland::{X11Wm, XWayland, XWaylandEvent},
+    wayland::{
+        data_control::with_source_metadata as with_data_control_source_metadata,
+        data_device::{with_source_metadata, ExternalDndOffer},
+        primary_selection::with_source_metadata as with_primary_source_metadata,
+    },
+    xwayland::{xwm::SelectionTarget, X11Wm, XWayland, XWaylandEvent},
 };
 
 pub struct CalloopData<BackendData: Backend + 'static> {
@@ -82,7 +125,10 @@ pub struct CalloopData<BackendData: Backend + 'static> {
 }
 
 #[derive(Debug, Default)]
-pub struct ClientState;
+pub struct ClientState {
+    /// Security context of the socket the client connected to, if it is sandboxed
+    pub security_context: Option<SecurityContext>,
+}
 impl ClientData for ClientState {
     /// Notification that a client was initialized
     fn initialized(&self, _client_id: ClientId) {}
@@ -90,6 +136,13 @@ impl ClientData for ClientState {
     fn disconnected(&self, _client_id: ClientId, _reason: DisconnectReason) {}
 }
 
+/// Sandboxed clients are not allowed to use privileged protocols
+fn client_is_privileged(client: &Client) -> bool {
+    client
+        .get_data::<ClientState>()
+        .map_or(true, |data| data.security_context.is_none())
+}
+
 #[derive(Debug)]
 pub struct AnvilState<BackendData: Backend + 'static> {
     pub backend_data: BackendData,
@@ -104,21 +157,30 @@ pub struct AnvilState<BackendData: Backend + 'static> {
 
     // smithay state
     pub compositor_state: CompositorState,
+    pub data_control_state: DataControlState,
     pub data_device_state: DataDeviceState,
+    pub foreign_toplevel_state: ForeignToplevelState,
+    pub idle_inhibit_manager_state: IdleInhibitManagerState,
+    pub idle_notifier_state: IdleNotifierState<AnvilState<BackendData>>,
     pub layer_shell_state: WlrLayerShellState,
     pub output_manager_state: OutputManagerState,
+    pub output_management_state: OutputManagementState,
     pub primary_selection_state: PrimarySelectionState,
     pub seat_state: SeatState<AnvilState<BackendData>>,
+    pub session_lock_state: SessionLockManagerState,
     pub keyboard_shortcuts_inhibit_state: KeyboardShortcutsInhibitState,
     pub shm_state: ShmState,
     pub viewporter_state: ViewporterState,
+    pub tearing_control_state: TearingControlState,
     pub xdg_activation_state: XdgActivationState,
     pub xdg_decoration_state: XdgDecorationState,
+    pub xdg_foreign_state: XdgForeignState,
     pub xdg_shell_state: XdgShellState,
     pub presentation_state: PresentationState,
     pub fractional_scale_manager_state: FractionalScaleManagerState,
 
     pub dnd_icon: Option<WlSurface>,
+    pub pending_screencopy: Vec<Screencopy>,
     pub log: slog::Logger,
 
     // input-related fields
@@ -148,8 +210,27 @@ impl<BackendData: Backend> DataDeviceHandler for AnvilState<BackendData> {
     fn data_device_state(&self) -> &DataDeviceState {
         &self.data_device_state
     }
-    fn send_selection(&mut self, _mime_type: String, _fd: OwnedFd) {
-        unreachable!("Anvil doesn't do server-side selections");
+    #[cfg_attr(not(feature = "xwayland"), allow(unused_variables))]
+    fn new_selection(&mut self, source: Option<WlDataSource>) {
+        #[cfg(feature = "xwayland")]
+        if let Some(xwm) = self.xwm.as_mut() {
+            let mime_types = source.map(|source| {
+                with_source_metadata(&source, |metadata| metadata.mime_types.clone()).unwrap_or_default()
+            });
+            if let Err(err) = xwm.new_selection(SelectionTarget::Clipboard, mime_types) {
+                slog::warn!(self.log, "Failed to set Xwayland clipboard selection: {}", err);
+            }
+        }
+    }
+    #[cfg_attr(not(feature = "xwayland"), allow(unused_variables))]
+    fn send_selection(&mut self, mime_type: String, fd: OwnedFd) {
+        // server-side selections are only set for selections of X11 clients
+        #[cfg(feature = "xwayland")]
+        if let Some(xwm) = self.xwm.as_mut() {
+            if let Err(err) = xwm.send_selection(SelectionTarget::Clipboard, mime_type, fd) {
+                slog::warn!(self.log, "Failed to send clipboard (X11 -> Wayland): {}", err);
+            }
+        }
     }
 }
 impl<BackendData: Backend> ClientDndGrabHandler for AnvilState<BackendData> {
@@ -159,10 +240,89 @@ impl<BackendData: Backend> ClientDndGrabHandler for AnvilState<BackendData> {
     fn dropped(&mut self, _seat: Seat<Self>) {
         self.dnd_icon = None;
     }
+    #[cfg(feature = "xwayland")]
+    fn enter_external(
+        &mut self,
+        surface: &WlSurface,
+        offer: ExternalDndOffer,
+        location: Point<f64, Logical>,
+    ) -> bool {
+        // X11 windows receive drag'n'drop through XDND
+        match self.xwm.as_mut() {
+            Some(xwm) => xwm.dnd_enter(surface, offer, location).unwrap_or_else(|err| {
+                slog::warn!(self.log, "Failed to start drag'n'drop (Wayland -> X11): {}", err);
+                false
+            }),
+            None => false,
+        }
+    }
+    #[cfg(feature = "xwayland")]
+    fn motion_external(&mut self, _surface: &WlSurface, location: Point<f64, Logical>, time: u32) {
+        if let Some(xwm) = self.xwm.as_mut() {
+            if let Err(err) = xwm.dnd_motion(location, time) {
+                slog::warn!(self.log, "Failed to update drag'n'drop (Wayland -> X11): {}", err);
+            }
+        }
+    }
+    #[cfg(feature = "xwayland")]
+    fn leave_external(&mut self, _surface: &WlSurface) {
+        if let Some(xwm) = self.xwm.as_mut() {
+            if let Err(err) = xwm.dnd_leave() {
+                slog::warn!(self.log, "Failed to leave drag'n'drop (Wayland -> X11): {}", err);
+            }
+        }
+    }
+    #[cfg(feature = "xwayland")]
+    fn drop_external(&mut self, _surface: &WlSurface, time: u32) {
+        if let Some(xwm) = self.xwm.as_mut() {
+            if let Err(err) = xwm.dnd_drop(time) {
+                slog::warn!(self.log, "Failed to drop drag'n'drop (Wayland -> X11): {}", err);
+            }
+        }
+    }
 }
 impl<BackendData: Backend> ServerDndGrabHandler for AnvilState<BackendData> {
-    fn send(&mut self, _mime_type: String, _fd: OwnedFd) {
-        unreachable!("Anvil doesn't do server-side grabs");
+    // server-side drag'n'drop is only started for drags of X11 clients
+    #[cfg(feature = "xwayland")]
+    fn action(&mut self, action: DndAction) {
+        if let Some(xwm) = self.xwm.as_mut() {
+            if let Err(err) = xwm.set_dnd_action(action) {
+                slog::warn!(
+                    self.log,
+                    "Failed to set drag'n'drop action (X11 -> Wayland): {}",
+                    err
+                );
+            }
+        }
+    }
+    #[cfg_attr(not(feature = "xwayland"), allow(unused_variables))]
+    fn send(&mut self, mime_type: String, fd: OwnedFd) {
+        #[cfg(feature = "xwayland")]
+        if let Some(xwm) = self.xwm.as_mut() {
+            if let Err(err) = xwm.send_dnd_data(mime_type, fd) {
+                slog::warn!(
+                    self.log,
+                    "Failed to send drag'n'drop data (X11 -> Wayland): {}",
+                    err
+                );
+            }
+        }
+    }
+    #[cfg(feature = "xwayland")]
+    fn finished(&mut self) {
+        if let Some(xwm) = self.xwm.as_mut() {
+            if let Err(err) = xwm.finish_dnd(true) {
+                slog::warn!(self.log, "Failed to finish drag'n'drop (X11 -> Wayland): {}", err);
+            }
+        }
+    }
+    #[cfg(feature = "xwayland")]
+    fn cancelled(&mut self) {
+        if let Some(xwm) = self.xwm.as_mut() {
+            if let Err(err) = xwm.finish_dnd(false) {
+                slog::warn!(self.log, "Failed to cancel drag'n'drop (X11 -> Wayland): {}", err);
+            }
+        }
     }
 }
 delegate_data_device!(@<BackendData: Backend + 'static> AnvilState<BackendData>);
@@ -173,9 +333,69 @@ impl<BackendData: Backend> PrimarySelectionHandler for AnvilState<BackendData> {
     fn primary_selection_state(&self) -> &PrimarySelectionState {
         &self.primary_selection_state
     }
+    #[cfg_attr(not(feature = "xwayland"), allow(unused_variables))]
+    fn new_selection(&mut self, source: Option<PrimarySource>) {
+        #[cfg(feature = "xwayland")]
+        if let Some(xwm) = self.xwm.as_mut() {
+            let mime_types = source.map(|source| {
+                with_primary_source_metadata(&source, |metadata| metadata.mime_types.clone())
+                    .unwrap_or_default()
+            });
+            if let Err(err) = xwm.new_selection(SelectionTarget::Primary, mime_types) {
+                slog::warn!(self.log, "Failed to set Xwayland primary selection: {}", err);
+            }
+        }
+    }
+    #[cfg_attr(not(feature = "xwayland"), allow(unused_variables))]
+    fn send_selection(&mut self, mime_type: String, fd: OwnedFd) {
+        // server-side selections are only set for selections of X11 clients
+        #[cfg(feature = "xwayland")]
+        if let Some(xwm) = self.xwm.as_mut() {
+            if let Err(err) = xwm.send_selection(SelectionTarget::Primary, mime_type, fd) {
+                slog::warn!(
+                    self.log,
+                    "Failed to send primary selection (X11 -> Wayland): {}",
+                    err
+                );
+            }
+        }
+    }
 }
 delegate_primary_selection!(@<BackendData: Backend + 'static> AnvilState<BackendData>);
 
+impl<BackendData: Backend> DataControlHandler for AnvilState<BackendData> {
+    fn data_control_state(&self) -> &DataControlState {
+        &self.data_control_state
+    }
+    #[cfg_attr(not(feature = "xwayland"), allow(unused_variables))]
+    fn new_selection(&mut self, source: Option<ZwlrDataControlSourceV1>) {
+        #[cfg(feature = "xwayland")]
+        if let Some(xwm) = self.xwm.as_mut() {
+            let mime_types = source.map(|source| {
+                with_data_control_source_metadata(&source, |metadata| metadata.mime_types.clone())
+                    .unwrap_or_default()
+            });
+            if let Err(err) = xwm.new_selection(SelectionTarget::Clipboard, mime_types) {
+                slog::warn!(self.log, "Failed to set Xwayland clipboard selection: {}", err);
+            }
+        }
+    }
+    #[cfg_attr(not(feature = "xwayland"), allow(unused_variables))]
+    fn new_primary_selection(&mut self, source: Option<ZwlrDataControlSourceV1>) {
+        #[cfg(feature = "xwayland")]
+        if let Some(xwm) = self.xwm.as_mut() {
+            let mime_types = source.map(|source| {
+                with_data_control_source_metadata(&source, |metadata| metadata.mime_types.clone())
+                    .unwrap_or_default()
+            });
+            if let Err(err) = xwm.new_selection(SelectionTarget::Primary, mime_types) {
+                slog::warn!(self.log, "Failed to set Xwayland primary selection: {}", err);
+            }
+        }
+    }
+}
+delegate_data_control!(@<BackendData: Backend + 'static> AnvilState<BackendData>);
+
 impl<BackendData: Backend> ShmHandler for AnvilState<BackendData> {
     fn shm_state(&self) -> &ShmState {
         &self.shm_state
@@ -199,6 +419,13 @@ impl<BackendData: Backend> SeatHandler for AnvilState<BackendData> {
             .and_then(|s| dh.get_client(s.id()).ok());
         set_data_device_focus(dh, seat, focus.clone());
         set_primary_focus(dh, seat, focus);
+
+        // tablet pads follow the keyboard focus
+        let surface = target.and_then(WaylandFocus::wl_surface);
+        let time = Duration::from(self.clock.now()).as_millis() as u32;
+        for pad in seat.tablet_seat().pads() {
+            pad.set_focus(surface.clone(), SERIAL_COUNTER.next_serial(), time);
+        }
     }
     fn cursor_image(&mut self, _seat: &Seat<Self>, image: CursorImageStatus) {
         *self.cursor_status.lock().unwrap() = image;
@@ -229,8 +456,275 @@ delegate_virtual_keyboard_manager!(@<BackendData: Backend + 'static> AnvilState<
 
 delegate_relative_pointer!(@<BackendData: Backend + 'static> AnvilState<BackendData>);
 
+impl<BackendData: Backend> IdleNotifierHandler for AnvilState<BackendData> {
+    fn idle_notifier_state(&mut self) -> &mut IdleNotifierState<Self> {
+        &mut self.idle_notifier_state
+    }
+}
+delegate_idle_notify!(@<BackendData: Backend + 'static> AnvilState<BackendData>);
+
+impl<BackendData: Backend> IdleInhibitHandler for AnvilState<BackendData> {
+    fn idle_inhibit_manager_state(&mut self) -> &mut IdleInhibitManagerState {
+        &mut self.idle_inhibit_manager_state
+    }
+
+    fn inhibit(&mut self, _surface: WlSurface) {}
+
+    fn uninhibit(&mut self, _surface: WlSurface) {}
+}
+delegate_idle_inhibit!(@<BackendData: Backend + 'static> AnvilState<BackendData>);
+
+impl<BackendData: Backend> PointerConstraintsHandler for AnvilState<BackendData> {
+    fn new_constraint(&mut self, surface: &WlSurface, pointer: &PointerHandle<Self>) {
+        // activate the constraint right away, if the pointer is already inside of its region
+        let origin = match self.surface_under() {
+            Some((focus, origin)) if focus.wl_surface().as_ref() == Some(surface) => origin,
+            _ => return,
+        };
+        let location = self.pointer_location - origin.to_f64();
+        with_pointer_constraint(surface, pointer, |constraint| {
+            if let Some(constraint) = constraint {
+                if constraint.contains(location) {
+                    constraint.activate();
+                }
+            }
+        });
+    }
+}
+delegate_pointer_constraints!(@<BackendData: Backend + 'static> AnvilState<BackendData>);
+
+impl<BackendData: Backend> SecurityContextHandler for AnvilState<BackendData> {
+    fn context_created(&mut self, source: SecurityContextListenerSource, context: SecurityContext) {
+        self.handle
+            .insert_source(source, move |client_stream, _, data| {
+                let client_state = ClientState {
+                    security_context: Some(context.clone()),
+                };
+                if let Err(err) = data
+                    .display
+                    .handle()
+                    .insert_client(client_stream, Arc::new(client_state))
+                {
+                    slog::warn!(data.state.log, "Error adding wayland client: {}", err);
+                };
+            })
+            .expect("Failed to init the security context listener source");
+    }
+}
+delegate_security_context!(@<BackendData: Backend + 'static> AnvilState<BackendData>);
+
+delegate_cursor_shape!(@<BackendData: Backend + 'static> AnvilState<BackendData>);
+
+impl<BackendData: Backend> ScreencopyHandler for AnvilState<BackendData> {
+    fn frame(&mut self, frame: Screencopy) {
+        // copied the next time the output is rendered
+        self.pending_screencopy.push(frame);
+    }
+}
+delegate_screencopy!(@<BackendData: Backend + 'static> AnvilState<BackendData>);
+
+delegate_session_lock!(@<BackendData: Backend + 'static> AnvilState<BackendData>);
+
+delegate_pointer_gestures!(@<BackendData: Backend + 'static> AnvilState<BackendData>);
+
 delegate_viewporter!(@<BackendData: Backend + 'static> AnvilState<BackendData>);
 
+delegate_tearing_control!(@<BackendData: Backend + 'static> AnvilState<BackendData>);
+
+impl<BackendData: Backend> OutputManagementHandler for AnvilState<BackendData> {
+    fn output_management_state(&mut self) -> &mut OutputManagementState {
+        &mut self.output_management_state
+    }
+
+    fn test_configuration(&mut self, config: Vec<(Output, OutputConfiguration)>) -> bool {
+        // anvil can neither disable outputs nor change their modes at runtime
+        config.iter().all(|(output, config)| match config {
+            OutputConfiguration::Enabled {
+                mode, adaptive_sync, ..
+            } => {
+                let mode_supported = match mode {
+                    Some(ModeConfiguration::Mode(mode)) => output.current_mode() == Some(*mode),
+                    Some(ModeConfiguration::Custom { .. }) => false,
+                    None => true,
+                };
+                mode_supported && *adaptive_sync != Some(true)
+            }
+            OutputConfiguration::Disabled => false,
+        })
+    }
+
+    fn apply_configuration(&mut self, config: Vec<(Output, OutputConfiguration)>) -> bool {
+        if !self.test_configuration(config.clone()) {
+            return false;
+        }
+
+        for (output, config) in config {
+            if let OutputConfiguration::Enabled {
+                position,
+                transform,
+                scale,
+                ..
+            } = config
+            {
+                output.change_current_state(None, transform, scale.map(Scale::Fractional), position);
+                if let Some(position) = position {
+                    self.space.map_output(&output, position);
+                }
+                layer_map_for_output(&output).arrange();
+                self.backend_data.reset_buffers(&output);
+            }
+        }
+        crate::shell::fixup_window_positions(&mut self.space);
+
+        true
+    }
+}
+
+delegate_output_management!(@<BackendData: Backend + 'static> AnvilState<BackendData>);
+
+impl<BackendData: Backend> ForeignToplevelHandler for AnvilState<BackendData> {
+    fn foreign_toplevel_state(&mut self) -> &mut ForeignToplevelState {
+        &mut self.foreign_toplevel_state
+    }
+
+    fn activate(&mut self, toplevel: ForeignToplevelHandle, _seat: WlSeat) {
+        if self.session_lock_state.is_locked() {
+            return;
+        }
+
+        let window = match self.window_for_foreign_toplevel(&toplevel) {
+            Some(window) => window,
+            None => return,
+        };
+
+        self.space.raise_element(&window, true);
+        #[cfg(feature = "xwayland")]
+        if let WindowElement::X11(surface) = &window {
+            if let Some(xwm) = self.xwm.as_mut() {
+                let _ = xwm.raise_window(surface);
+            }
+        }
+        let keyboard = self.seat.get_keyboard().unwrap();
+        keyboard.set_focus(self, Some(window.into()), SERIAL_COUNTER.next_serial());
+    }
+
+    fn close(&mut self, toplevel: ForeignToplevelHandle) {
+        match self.window_for_foreign_toplevel(&toplevel) {
+            Some(WindowElement::Wayland(window)) => window.toplevel().send_close(),
+            #[cfg(feature = "xwayland")]
+            Some(WindowElement::X11(surface)) => {
+                let _ = surface.close();
+            }
+            None => {}
+        }
+    }
+
+    fn set_maximized(&mut self, toplevel: ForeignToplevelHandle) {
+        match self.window_for_foreign_toplevel(&toplevel) {
+            Some(WindowElement::Wayland(window)) => {
+                XdgShellHandler::maximize_request(self, window.toplevel().clone())
+            }
+            #[cfg(feature = "xwayland")]
+            Some(WindowElement::X11(surface)) => self.maximize_request_x11(&surface),
+            None => {}
+        }
+    }
+
+    fn unset_maximized(&mut self, toplevel: ForeignToplevelHandle) {
+        if let Some(WindowElement::Wayland(window)) = self.window_for_foreign_toplevel(&toplevel) {
+            XdgShellHandler::unmaximize_request(self, window.toplevel().clone())
+        }
+    }
+
+    fn set_fullscreen(&mut self, toplevel: ForeignToplevelHandle, output: Option<WlOutput>) {
+        if let Some(WindowElement::Wayland(window)) = self.window_for_foreign_toplevel(&toplevel) {
+            XdgShellHandler::fullscreen_request(self, window.toplevel().clone(), output)
+        }
+    }
+
+    fn unset_fullscreen(&mut self, toplevel: ForeignToplevelHandle) {
+        if let Some(WindowElement::Wayland(window)) = self.window_for_foreign_toplevel(&toplevel) {
+            XdgShellHandler::unfullscreen_request(self, window.toplevel().clone())
+        }
+    }
+}
+
+delegate_foreign_toplevel!(@<BackendData: Backend + 'static> AnvilState<BackendData>);
+
+impl<BackendData: Backend> AnvilState<BackendData> {
+    /// Update the foreign toplevel handles of all mapped windows
+    pub fn refresh_foreign_toplevels(&mut self) {
+        let mut mapped = Vec::new();
+        for window in self.space.elements() {
+            #[cfg(feature = "xwayland")]
+            if let WindowElement::X11(surface) = window {
+                if surface.is_override_redirect() {
+                    continue;
+                }
+            }
+
+            window
+                .user_data()
+                .insert_if_missing(|| RefCell::new(None::<ForeignToplevelHandle>));
+            let mut handle = window
+                .user_data()
+                .get::<RefCell<Option<ForeignToplevelHandle>>>()
+                .unwrap()
+                .borrow_mut();
+            let toplevel = match handle.as_ref().filter(|toplevel| !toplevel.is_closed()) {
+                Some(toplevel) => toplevel.clone(),
+                None => {
+                    let toplevel = self
+                        .foreign_toplevel_state
+                        .new_toplevel::<Self>(String::new(), String::new());
+                    *handle = Some(toplevel.clone());
+                    toplevel
+                }
+            };
+
+            match window {
+                WindowElement::Wayland(window) => toplevel.set_from_window(window),
+                #[cfg(feature = "xwayland")]
+                WindowElement::X11(surface) => toplevel.set_from_x11_surface(surface),
+            }
+            let window_outputs = self.space.outputs_for_element(window);
+            for output in self.space.outputs() {
+                if window_outputs.contains(output) {
+                    toplevel.output_enter(output);
+                } else {
+                    toplevel.output_leave(output);
+                }
+            }
+            toplevel.send_done();
+
+            mapped.push(toplevel);
+        }
+
+        let unmapped = self
+            .foreign_toplevel_state
+            .toplevels()
+            .filter(|toplevel| !mapped.contains(toplevel))
+            .cloned()
+            .collect::<Vec<_>>();
+        for toplevel in unmapped {
+            self.foreign_toplevel_state.remove_toplevel(&toplevel);
+        }
+    }
+
+    fn window_for_foreign_toplevel(&self, toplevel: &ForeignToplevelHandle) -> Option<WindowElement> {
+        self.space
+            .elements()
+            .find(|window| {
+                window
+                    .user_data()
+                    .get::<RefCell<Option<ForeignToplevelHandle>>>()
+                    .map(|handle| handle.borrow().as_ref() == Some(toplevel))
+                    .unwrap_or(false)
+            })
+            .cloned()
+    }
+}
+
 impl<BackendData: Backend> XdgActivationHandler for AnvilState<BackendData> {
     fn activation_state(&mut self) -> &mut XdgActivationState {
         &mut self.xdg_activation_state
@@ -269,6 +763,13 @@ impl<BackendData: Backend> XdgActivationHandler for AnvilState<BackendData> {
 }
 delegate_xdg_activation!(@<BackendData: Backend + 'static> AnvilState<BackendData>);
 
+impl<BackendData: Backend> XdgForeignHandler for AnvilState<BackendData> {
+    fn xdg_foreign_state(&mut self) -> &mut XdgForeignState {
+        &mut self.xdg_foreign_state
+    }
+}
+delegate_xdg_foreign!(@<BackendData: Backend + 'static> AnvilState<BackendData>);
+
 impl<BackendData: Backend> XdgDecorationHandler for AnvilState<BackendData> {
     fn new_decoration(&mut self, toplevel: ToplevelSurface) {
         use xdg_decoration::zv1::server::zxdg_toplevel_decoration_v1::Mode;
@@ -410,7 +911,7 @@ impl<BackendData: Backend + 'static> AnvilState<BackendData> {
                     if let Err(err) = data
                         .display
                         .handle()
-                        .insert_client(client_stream, Arc::new(ClientState))
+                        .insert_client(client_stream, Arc::new(ClientState::default()))
                     {
                         slog::warn!(data.state.log, "Error adding wayland client: {}", err);
                     };
@@ -438,25 +939,44 @@ impl<BackendData: Backend + 'static> AnvilState<BackendData> {
         // init globals
         let dh = display.handle();
         let compositor_state = CompositorState::new::<Self, _>(&dh, log.clone());
+        let data_control_state = DataControlState::new::<Self, _, _>(&dh, client_is_privileged, log.clone());
         let data_device_state = DataDeviceState::new::<Self, _>(&dh, log.clone());
+        let foreign_toplevel_state =
+            ForeignToplevelState::new_with_filter::<Self, _>(&dh, client_is_privileged);
+        let idle_inhibit_manager_state = IdleInhibitManagerState::new::<Self>(&dh);
+        let mut idle_notifier_state = IdleNotifierState::<Self>::new(&dh, handle.clone());
+        idle_notifier_state.set_idle_inhibitors(&idle_inhibit_manager_state);
         let layer_shell_state = WlrLayerShellState::new::<Self, _>(&dh, log.clone());
         let output_manager_state = OutputManagerState::new_with_xdg_output::<Self>(&dh);
+        let output_management_state =
+            OutputManagementState::new_with_filter::<Self, _>(&dh, client_is_privileged);
         let primary_selection_state = PrimarySelectionState::new::<Self, _>(&dh, log.clone());
         let mut seat_state = SeatState::new();
+        let session_lock_state = SessionLockManagerState::new::<Self>(&dh);
         let shm_state = ShmState::new::<Self, _>(&dh, vec![], log.clone());
         let viewporter_state = ViewporterState::new::<Self, _>(&dh, log.clone());
+        let tearing_control_state = TearingControlState::new::<Self, _>(&dh, log.clone());
         let xdg_activation_state = XdgActivationState::new::<Self, _>(&dh, log.clone());
         let xdg_decoration_state = XdgDecorationState::new::<Self, _>(&dh, log.clone());
+        let xdg_foreign_state = XdgForeignState::new::<Self>(&dh);
         let xdg_shell_state = XdgShellState::new::<Self, _>(&dh, log.clone());
         let presentation_state = PresentationState::new::<Self>(&dh, clock.id() as u32);
         let fractional_scale_manager_state = FractionalScaleManagerState::new::<Self, _>(&dh, log.clone());
         TextInputManagerState::new::<Self>(&dh);
         InputMethodManagerState::new::<Self>(&dh);
-        VirtualKeyboardManagerState::new::<Self, _>(&dh, |_client| true);
+        VirtualKeyboardManagerState::new::<Self, _>(&dh, client_is_privileged);
+        SecurityContextState::new::<Self, _>(&dh, client_is_privileged);
+        ScreencopyState::new_with_filter::<Self, _>(&dh, client_is_privileged);
+        CursorShapeManagerState::new::<Self>(&dh);
+        PointerConstraintsState::new::<Self>(&dh);
         // Expose global only if backend supports relative motion events
         if BackendData::HAS_RELATIVE_MOTION {
             RelativePointerManagerState::new::<Self>(&dh);
         }
+        // Expose global only if backend supports gesture events
+        if BackendData::HAS_GESTURES {
+            PointerGesturesState::new::<Self>(&dh);
+        }
 
         // init input
         let seat_name = backend_data.seat_name();
@@ -530,20 +1050,29 @@ impl<BackendData: Backend + 'static> AnvilState<BackendData> {
             space: Space::new(log.clone()),
             popups: PopupManager::new(log.clone()),
             compositor_state,
+            data_control_state,
             data_device_state,
+            foreign_toplevel_state,
+            idle_inhibit_manager_state,
+            idle_notifier_state,
             layer_shell_state,
             output_manager_state,
+            output_management_state,
             primary_selection_state,
             seat_state,
+            session_lock_state,
             keyboard_shortcuts_inhibit_state,
             shm_state,
             viewporter_state,
+            tearing_control_state,
             xdg_activation_state,
             xdg_decoration_state,
+            xdg_foreign_state,
             xdg_shell_state,
             presentation_state,
             fractional_scale_manager_state,
             dnd_icon: None,
+            pending_screencopy: Vec::new(),
             log,
             suppressed_keys: Vec::new(),
             pointer_location: (0.0, 0.0).into(),
@@ -614,6 +1143,15 @@ pub fn post_repaint(
 
         layer_surface.send_frame(output, time, throttle, surface_primary_scanout_output);
     }
+    if let Some(lock_surface) = output
+        .user_data()
+        .get::<SessionLockSurface>()
+        .and_then(|lock_surface| lock_surface.get())
+    {
+        send_frames_surface_tree(lock_surface.wl_surface(), output, time, throttle, |_, _| {
+            Some(output.clone())
+        });
+    }
 }
 
 pub fn take_presentation_feedback(
@@ -646,6 +1184,7 @@ pub fn take_presentation_feedback(
 
 pub trait Backend {
     const HAS_RELATIVE_MOTION: bool = false;
+    const HAS_GESTURES: bool = false;
     fn seat_name(&self) -> String;
     fn reset_buffers(&mut self, output: &Output);
     fn early_import(&mut self, surface: &WlSurface);
@@ -17,7 +17,7 @@ use slog::Logger;
 use crate::{
     drawing::*,
     render::*,
-    shell::WindowElement,
+    shell::{FullscreenSurface, WindowElement},
     state::{post_repaint, take_presentation_feedback, AnvilState, Backend, CalloopData},
 };
 #[cfg(feature = "debug")]
@@ -29,7 +29,7 @@ use smithay::{
         renderer::{ImportDma, ImportEgl},
     },
     delegate_dmabuf,
-    wayland::dmabuf::{DmabufGlobal, DmabufHandler, DmabufState, ImportError},
+    wayland::dmabuf::{DmabufFeedbackBuilder, DmabufGlobal, DmabufHandler, DmabufState, ImportError},
 };
 use smithay::{
     backend::{
@@ -41,7 +41,7 @@ use smithay::{
         libinput::{LibinputInputBackend, LibinputSessionInterface},
         renderer::{
             damage::{DamageTrackedRenderer, DamageTrackedRendererError},
-            element::{texture::TextureBuffer, AsRenderElements},
+            element::AsRenderElements,
             gles2::{Gles2Renderbuffer, Gles2Renderer},
             multigpu::{egl::EglGlesBackend, GpuManager, MultiRenderer, MultiTexture},
             Bind, Frame, Renderer,
@@ -50,11 +50,12 @@ use smithay::{
         udev::{all_gpus, primary_gpu, UdevBackend, UdevEvent},
         SwapBuffersError,
     },
+    delegate_drm_syncobj,
     desktop::{
         space::{Space, SurfaceTree},
         utils::OutputPresentationFeedback,
     },
-    input::pointer::{CursorImageAttributes, CursorImageStatus},
+    input::pointer::{cursor_theme::CursorFrame, CursorIcon, CursorImageAttributes, CursorImageStatus},
     output::{Mode, Output, PhysicalProperties, Subpixel},
     reexports::{
         calloop::{
@@ -79,7 +80,10 @@ use smithay::{
     utils::{Clock, DeviceFd, IsAlive, Logical, Monotonic, Point, Rectangle, Scale, Transform},
     wayland::{
         compositor,
+        drm_syncobj::{supports_syncobj_eventfd, DrmSyncobjHandler, DrmSyncobjState},
         input_method::{InputMethodHandle, InputMethodSeat},
+        screencopy::Screencopy,
+        tearing_control::{PresentationHint, TearingControlSurfaceCachedState},
     },
 };
 
@@ -97,11 +101,11 @@ pub struct UdevData {
     dh: DisplayHandle,
     #[cfg(feature = "egl")]
     dmabuf_state: Option<(DmabufState, DmabufGlobal)>,
+    syncobj_state: Option<DrmSyncobjState>,
     primary_gpu: DrmNode,
     gpus: GpuManager<EglGlesBackend<Gles2Renderer>>,
     backends: HashMap<DrmNode, BackendData>,
-    pointer_images: Vec<(xcursor::parser::Image, TextureBuffer<MultiTexture>)>,
-    pointer_element: PointerElement<MultiTexture>,
+    pointer_element: PointerElement,
     #[cfg(feature = "debug")]
     fps_texture: MultiTexture,
     pointer_image: crate::cursor::Cursor,
@@ -126,8 +130,16 @@ impl DmabufHandler for AnvilState<UdevData> {
 #[cfg(feature = "egl")]
 delegate_dmabuf!(AnvilState<UdevData>);
 
+impl DrmSyncobjHandler for AnvilState<UdevData> {
+    fn drm_syncobj_state(&mut self) -> &mut DrmSyncobjState {
+        self.backend_data.syncobj_state.as_mut().unwrap()
+    }
+}
+delegate_drm_syncobj!(AnvilState<UdevData>);
+
 impl Backend for UdevData {
     const HAS_RELATIVE_MOTION: bool = true;
+    const HAS_GESTURES: bool = true;
 
     fn seat_name(&self) -> String {
         self.session.seat()
@@ -222,10 +234,13 @@ pub fn run_udev(log: Logger) {
         if renderer.bind_wl_display(&display.handle()).is_ok() {
             info!(log, "EGL hardware-acceleration enabled");
             let dmabuf_formats = renderer.dmabuf_formats().cloned().collect::<Vec<_>>();
+            let default_feedback = DmabufFeedbackBuilder::new(primary_gpu.dev_id(), dmabuf_formats)
+                .build()
+                .unwrap();
             let mut state = DmabufState::new();
-            let global = state.create_global::<AnvilState<UdevData>, _>(
+            let global = state.create_global_with_default_feedback::<AnvilState<UdevData>, _>(
                 &display.handle(),
-                dmabuf_formats,
+                &default_feedback,
                 log.clone(),
             );
             Some((state, global))
@@ -238,12 +253,12 @@ pub fn run_udev(log: Logger) {
         dh: display.handle(),
         #[cfg(feature = "egl")]
         dmabuf_state,
+        syncobj_state: None,
         session,
         primary_gpu,
         gpus,
         backends: HashMap::new(),
         pointer_image: crate::cursor::Cursor::load(&log),
-        pointer_images: Vec::new(),
         pointer_element: PointerElement::default(),
         #[cfg(feature = "debug")]
         fps_texture,
@@ -323,6 +338,17 @@ pub fn run_udev(log: Logger) {
         state.device_added(&mut display, dev, path.into())
     }
 
+    // init explicit sync support, if the kernel supports it for our primary gpu
+    if let Some(backend) = state.backend_data.backends.get(&state.backend_data.primary_gpu) {
+        let import_device = backend.event_dispatcher.as_source_ref().device_fd();
+        if supports_syncobj_eventfd(&import_device) {
+            info!(log, "Enabling explicit synchronization via linux-drm-syncobj");
+            let syncobj_state =
+                DrmSyncobjState::new::<AnvilState<UdevData>>(&display.handle(), import_device);
+            state.backend_data.syncobj_state = Some(syncobj_state);
+        }
+    }
+
     event_loop
         .handle()
         .insert_source(udev_backend, move |event, _, data| match event {
@@ -360,6 +386,7 @@ pub fn run_udev(log: Logger) {
             state.running.store(false, Ordering::SeqCst);
         } else {
             state.space.refresh();
+            state.refresh_foreign_toplevels();
             state.popups.cleanup();
             display.flush_clients().unwrap();
         }
@@ -478,6 +505,17 @@ fn scan_connectors(
                     }
                 };
 
+            if std::env::var("ANVIL_VRR").is_ok() {
+                match gbm_surface.vrr_supported(connector_info.handle()) {
+                    Ok(true) => {
+                        if let Err(err) = gbm_surface.use_vrr(true) {
+                            warn!(logger, "Failed to enable variable refresh rate: {}", err);
+                        }
+                    }
+                    _ => info!(logger, "Variable refresh rate is not supported by this connector"),
+                }
+            }
+
             let size = mode.size();
             let mode = Mode {
                 size: (size.0 as i32, size.1 as i32).into(),
@@ -601,6 +639,10 @@ impl AnvilState<UdevData> {
             &self.backend_data.fps_texture,
             &self.log,
         )));
+        for output in self.space.outputs() {
+            self.output_management_state.add_head(output);
+        }
+        self.output_management_state.update::<Self>(&self.display_handle);
 
         let event_dispatcher =
             Dispatcher::new(
@@ -664,6 +706,7 @@ impl AnvilState<UdevData> {
                 .into_iter()
             {
                 self.space.unmap_output(&output);
+                self.output_management_state.remove_head(&output);
             }
 
             let source = backend_data.event_dispatcher.as_source_mut();
@@ -681,6 +724,10 @@ impl AnvilState<UdevData> {
 
             // fixup window coordinates
             crate::shell::fixup_positions(&mut self.space);
+            for output in self.space.outputs() {
+                self.output_management_state.add_head(output);
+            }
+            self.output_management_state.update::<Self>(&self.display_handle);
 
             for surface in backends.values() {
                 let logger = logger.clone();
@@ -715,8 +762,10 @@ impl AnvilState<UdevData> {
                 .into_iter()
             {
                 self.space.unmap_output(&output);
+                self.output_management_state.remove_head(&output);
             }
             crate::shell::fixup_positions(&mut self.space);
+            self.output_management_state.update::<Self>(&self.display_handle);
 
             self.handle.remove(backend_data.registration_token);
             let _device = backend_data.event_dispatcher.into_source_inner();
@@ -785,15 +834,18 @@ impl AnvilState<UdevData> {
                         (self.clock.now(), wp_presentation_feedback::Kind::Vsync)
                     };
 
-                    feedback.presented(
-                        clock,
+                    // the refresh duration is in nanoseconds and zero, if the refresh rate is not constant
+                    let refresh = if surface.surface.vrr_enabled() {
+                        0
+                    } else {
                         output
                             .current_mode()
-                            .map(|mode| mode.refresh as u32)
-                            .unwrap_or_default(),
-                        seq as u64,
-                        flags,
-                    );
+                            .filter(|mode| mode.refresh > 0)
+                            .map(|mode| (1_000_000_000_000u64 / mode.refresh as u64) as u32)
+                            .unwrap_or_default()
+                    };
+
+                    feedback.presented(clock, refresh, seq as u64, flags);
                 }
 
                 true
@@ -902,41 +954,22 @@ impl AnvilState<UdevData> {
             };
 
         for (&crtc, surface) in to_render_iter {
+            let icon = match *self.cursor_status.lock().unwrap() {
+                CursorImageStatus::Named(icon) => icon,
+                _ => CursorIcon::Default,
+            };
             // TODO get scale from the rendersurface when supporting HiDPI
-            let frame = self
-                .backend_data
-                .pointer_image
-                .get_image(1 /*scale*/, self.clock.now().try_into().unwrap());
+            let frame = self.backend_data.pointer_image.get_frame(
+                icon,
+                1, /*scale*/
+                self.clock.now().try_into().unwrap(),
+            );
             let primary_gpu = self.backend_data.primary_gpu;
             let mut renderer = self
                 .backend_data
                 .gpus
                 .renderer::<Gles2Renderbuffer>(&primary_gpu, &surface.borrow().render_node)
                 .unwrap();
-            let pointer_images = &mut self.backend_data.pointer_images;
-            let pointer_image = pointer_images
-                .iter()
-                .find_map(|(image, texture)| {
-                    if image == &frame {
-                        Some(texture.clone())
-                    } else {
-                        None
-                    }
-                })
-                .unwrap_or_else(|| {
-                    let texture = TextureBuffer::from_memory(
-                        &mut renderer,
-                        &frame.pixels_rgba,
-                        (frame.width as i32, frame.height as i32),
-                        false,
-                        1,
-                        Transform::Normal,
-                        None,
-                    )
-                    .expect("Failed to import cursor bitmap");
-                    pointer_images.push((frame, texture.clone()));
-                    texture
-                });
 
             let output = if let Some(output) = self.space.outputs().find(|o| {
                 o.user_data().get::<UdevOutputId>()
@@ -958,12 +991,14 @@ impl AnvilState<UdevData> {
                 &output,
                 self.seat.input_method().unwrap(),
                 self.pointer_location,
-                &pointer_image,
+                &frame,
                 &mut self.backend_data.pointer_element,
                 &self.dnd_icon,
                 &mut self.cursor_status.lock().unwrap(),
                 &self.clock,
+                &mut self.pending_screencopy,
                 self.show_window_preview,
+                self.session_lock_state.is_locked(),
                 &self.log,
             );
             let reschedule = match &result {
@@ -1021,18 +1056,25 @@ fn render_surface<'a>(
     output: &Output,
     input_method: &InputMethodHandle,
     pointer_location: Point<f64, Logical>,
-    pointer_image: &TextureBuffer<MultiTexture>,
-    pointer_element: &mut PointerElement<MultiTexture>,
+    pointer_frame: &CursorFrame,
+    pointer_element: &mut PointerElement,
     dnd_icon: &Option<wl_surface::WlSurface>,
     cursor_status: &mut CursorImageStatus,
     clock: &Clock<Monotonic>,
+    pending_screencopy: &mut Vec<Screencopy>,
     show_window_preview: bool,
+    session_locked: bool,
     logger: &slog::Logger,
 ) -> Result<bool, SwapBuffersError> {
     let output_geometry = space.output_geometry(output).unwrap();
     let scale = Scale::from(output.current_scale().fractional_scale());
 
     let (dmabuf, age) = surface.surface.next_buffer()?;
+    let age = if screencopy_pending(pending_screencopy, output) {
+        0
+    } else {
+        age
+    };
     renderer.bind(dmabuf)?;
 
     let mut elements: Vec<CustomRenderElements<_>> = Vec::new();
@@ -1063,13 +1105,13 @@ fn render_surface<'a>(
                     .hotspot
             })
         } else {
-            (0, 0).into()
+            pointer_frame.hotspot()
         };
         let cursor_pos = pointer_location - output_geometry.loc.to_f64() - cursor_hotspot.to_f64();
         let cursor_pos_scaled = cursor_pos.to_physical(scale).to_i32_round();
 
         // set cursor
-        pointer_element.set_texture(pointer_image.clone());
+        pointer_element.set_buffer(pointer_frame.buffer().clone());
 
         // draw the cursor as relevant
         {
@@ -1110,7 +1152,7 @@ fn render_surface<'a>(
     }
 
     // and draw to our buffer
-    let (rendered, states) = render_output(
+    let (damage, states) = render_output(
         output,
         space,
         &elements,
@@ -1118,21 +1160,50 @@ fn render_surface<'a>(
         &mut surface.damage_tracked_renderer,
         age.into(),
         show_window_preview,
+        session_locked,
         logger,
     )
-    .map(|(damage, states)| (damage.is_some(), states))
     .map_err(|err| match err {
         DamageTrackedRendererError::Rendering(err) => SwapBuffersError::from(err),
         _ => unreachable!(),
     })?;
+    let rendered = damage.is_some();
+
+    // the framebuffer is rendered by the renderer of the render node
+    let gles_renderer: &mut Gles2Renderer = renderer.as_mut();
+    submit_screencopy(
+        pending_screencopy,
+        output,
+        gles_renderer,
+        damage.as_deref(),
+        clock.now(),
+        logger,
+    );
 
     post_repaint(output, &states, space, clock.now());
 
     if rendered {
         let output_presentation_feedback = take_presentation_feedback(output, space, &states);
+        // fullscreen surfaces hinting async presentation are flipped without waiting for the vblank
+        let async_flip = !session_locked
+            && output
+                .user_data()
+                .get::<FullscreenSurface>()
+                .and_then(|fullscreen| fullscreen.get())
+                .and_then(|window| window.wl_surface())
+                .map(|wl_surface| {
+                    compositor::with_states(&wl_surface, |states| {
+                        states
+                            .cached_state
+                            .current::<TearingControlSurfaceCachedState>()
+                            .presentation_hint()
+                            == PresentationHint::Async
+                    })
+                })
+                .unwrap_or(false);
         surface
             .surface
-            .queue_buffer(Some(output_presentation_feedback))
+            .queue_buffer(Some(output_presentation_feedback), async_flip)
             .map_err(Into::<SwapBuffersError>::into)?;
     }
 
@@ -1179,7 +1250,7 @@ fn initial_render(
         .map_err(Into::<SwapBuffersError>::into)?;
     frame.clear(CLEAR_COLOR, &[Rectangle::from_loc_and_size((0, 0), (1, 1))])?;
     frame.finish().map_err(Into::<SwapBuffersError>::into)?;
-    surface.queue_buffer(None)?;
+    surface.queue_buffer(None, false)?;
     surface.reset_buffers();
     Ok(())
 }
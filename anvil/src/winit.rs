@@ -22,7 +22,7 @@ use smithay::{
         renderer::{
             damage::{DamageTrackedRenderer, DamageTrackedRendererError},
             element::AsRenderElements,
-            gles2::{Gles2Renderer, Gles2Texture},
+            gles2::Gles2Renderer,
         },
         winit::{self, WinitEvent, WinitGraphicsBackend},
         SwapBuffersError,
@@ -160,6 +160,10 @@ pub fn run_winit(log: Logger) {
     };
     let mut state = AnvilState::init(&mut display, event_loop.handle(), data, log.clone(), true);
     state.space.map_output(&output, (0, 0));
+    state.output_management_state.add_head(&output);
+    state
+        .output_management_state
+        .update::<AnvilState<WinitData>>(&state.display_handle);
 
     #[cfg(feature = "xwayland")]
     if let Err(e) = state.xwayland.start(
@@ -173,7 +177,7 @@ pub fn run_winit(log: Logger) {
 
     info!(log, "Initialization completed, starting the main loop.");
 
-    let mut pointer_element = PointerElement::<Gles2Texture>::default();
+    let mut pointer_element = PointerElement::default();
 
     while state.running.load(Ordering::SeqCst) {
         if winit
@@ -189,6 +193,9 @@ pub fn run_winit(log: Logger) {
                     output.change_current_state(Some(mode), None, None, None);
                     output.set_preferred(mode);
                     crate::shell::fixup_positions(&mut state.space);
+                    state
+                        .output_management_state
+                        .update::<AnvilState<WinitData>>(&state.display_handle);
                 }
                 WinitEvent::Input(event) => {
                     state.process_input_event_windowed(&display.handle(), event, OUTPUT_NAME)
@@ -230,6 +237,9 @@ pub fn run_winit(log: Logger) {
             let space = &mut state.space;
             let damage_tracked_renderer = &mut state.backend_data.damage_tracked_renderer;
             let show_window_preview = state.show_window_preview;
+            let session_locked = state.session_lock_state.is_locked();
+            let pending_screencopy = &mut state.pending_screencopy;
+            let clock = &state.clock;
 
             let input_method = state.seat.input_method().unwrap();
             let dnd_icon = state.dnd_icon.as_ref();
@@ -252,7 +262,7 @@ pub fn run_winit(log: Logger) {
             let cursor_pos_scaled = cursor_pos.to_physical(scale).to_i32_round();
 
             let render_res = backend.bind().and_then(|_| {
-                let age = if *full_redraw > 0 {
+                let age = if *full_redraw > 0 || screencopy_pending(pending_screencopy, &output) {
                     0
                 } else {
                     backend.buffer_age().unwrap_or(0)
@@ -294,7 +304,7 @@ pub fn run_winit(log: Logger) {
                 #[cfg(feature = "debug")]
                 elements.push(CustomRenderElements::Fps(fps_element.clone()));
 
-                render_output(
+                let (damage, states) = render_output(
                     &output,
                     space,
                     &elements,
@@ -302,12 +312,24 @@ pub fn run_winit(log: Logger) {
                     damage_tracked_renderer,
                     age,
                     show_window_preview,
+                    session_locked,
                     &log,
                 )
                 .map_err(|err| match err {
-                    DamageTrackedRendererError::Rendering(err) => err.into(),
+                    DamageTrackedRendererError::Rendering(err) => SwapBuffersError::from(err),
                     _ => unreachable!(),
-                })
+                })?;
+
+                submit_screencopy(
+                    pending_screencopy,
+                    &output,
+                    renderer,
+                    damage.as_deref(),
+                    clock.now(),
+                    &log,
+                );
+
+                Ok((damage, states))
             });
 
             match render_res {
@@ -354,6 +376,7 @@ pub fn run_winit(log: Logger) {
             state.running.store(false, Ordering::SeqCst);
         } else {
             state.space.refresh();
+            state.refresh_foreign_toplevels();
             state.popups.cleanup();
             display.flush_clients().unwrap();
         }
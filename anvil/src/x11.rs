@@ -192,6 +192,10 @@ pub fn run_x11(log: Logger) {
     let mut state = AnvilState::init(&mut display, event_loop.handle(), data, log.clone(), true);
 
     state.space.map_output(&output, (0, 0));
+    state.output_management_state.add_head(&output);
+    state
+        .output_management_state
+        .update::<AnvilState<X11Data>>(&state.display_handle);
 
     let output_clone = output.clone();
     event_loop
@@ -212,6 +216,9 @@ pub fn run_x11(log: Logger) {
                 output.change_current_state(Some(data.state.backend_data.mode), None, None, None);
                 output.set_preferred(data.state.backend_data.mode);
                 crate::shell::fixup_positions(&mut data.state.space);
+                data.state
+                    .output_management_state
+                    .update::<AnvilState<X11Data>>(&data.state.display_handle);
 
                 data.state.backend_data.render = true;
             }
@@ -331,6 +338,11 @@ pub fn run_x11(log: Logger) {
             #[cfg(feature = "debug")]
             elements.push(CustomRenderElements::Fps(fps_element.clone()));
 
+            let age = if screencopy_pending(&state.pending_screencopy, &output) {
+                0
+            } else {
+                age
+            };
             let render_res = render_output(
                 &output,
                 &state.space,
@@ -339,12 +351,21 @@ pub fn run_x11(log: Logger) {
                 &mut backend_data.damage_tracked_renderer,
                 age.into(),
                 state.show_window_preview,
+                state.session_lock_state.is_locked(),
                 &log,
             );
 
             match render_res {
                 Ok((damage, states)) => {
                     trace!(log, "Finished rendering");
+                    submit_screencopy(
+                        &mut state.pending_screencopy,
+                        &output,
+                        &mut backend_data.renderer,
+                        damage.as_deref(),
+                        state.clock.now(),
+                        &log,
+                    );
                     if let Err(err) = backend_data.surface.submit() {
                         backend_data.surface.reset_buffers();
                         warn!(log, "Failed to submit buffer: {}. Retrying", err);
@@ -413,6 +434,7 @@ pub fn run_x11(log: Logger) {
             state.running.store(false, Ordering::SeqCst);
         } else {
             state.space.refresh();
+            state.refresh_foreign_toplevels();
             state.popups.cleanup();
             display.flush_clients().unwrap();
         }
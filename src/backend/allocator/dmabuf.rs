@@ -16,6 +16,18 @@ use std::hash::{Hash, Hasher};
 use std::os::unix::io::{AsFd, BorrowedFd, OwnedFd};
 use std::sync::{Arc, Weak};
 
+#[cfg(feature = "wayland_frontend")]
+use crate::wayland::compositor::{Blocker, BlockerState};
+#[cfg(feature = "wayland_frontend")]
+use calloop::{
+    generic::Generic, EventSource, Interest, Mode, Poll, PostAction, Readiness, Token, TokenFactory,
+};
+#[cfg(feature = "wayland_frontend")]
+use std::{
+    os::unix::io::AsRawFd,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
 /// Maximum amount of planes this implementation supports
 pub const MAX_PLANES: usize = 4;
 
@@ -248,3 +260,151 @@ impl AsDmabuf for Dmabuf {
         Ok(self.clone())
     }
 }
+
+#[cfg(feature = "wayland_frontend")]
+impl Dmabuf {
+    /// Create a [`Blocker`] for this buffer, alongside an event source releasing it
+    ///
+    /// Usually used to delay applying a surface commit until the client is done rendering
+    /// into the attached buffer, see [`add_blocker`](crate::wayland::compositor::add_blocker).
+    /// The blocker is released once the implicit fences of all planes are signalled for the given
+    /// `interest`, e.g. [`Interest::READ`] waits for pending writes to the buffer.
+    ///
+    /// Insert the source into your event loop and call
+    /// [`CompositorState::blocker_cleared`](crate::wayland::compositor::CompositorState::blocker_cleared)
+    /// from its callback.
+    ///
+    /// Returns [`AlreadyReady`], if the buffer can be accessed without waiting.
+    pub fn generate_blocker(
+        &self,
+        interest: Interest,
+    ) -> Result<(DmabufBlocker, DmabufSource), AlreadyReady> {
+        let events = match (interest.readable, interest.writable) {
+            (true, true) => nix::poll::PollFlags::POLLIN | nix::poll::PollFlags::POLLOUT,
+            (false, true) => nix::poll::PollFlags::POLLOUT,
+            _ => nix::poll::PollFlags::POLLIN,
+        };
+
+        let planes = self
+            .handles()
+            .filter(|fd| {
+                // planes, we fail to poll, are not waited upon. Any remaining implicit fence
+                // is still respected by the renderer.
+                let mut fds = [nix::poll::PollFd::new(fd.as_raw_fd(), events)];
+                matches!(nix::poll::poll(&mut fds, 0), Ok(0))
+            })
+            .filter_map(|fd| fd.try_clone_to_owned().ok())
+            .map(|fd| DmabufPlaneSource {
+                source: Generic::new(fd, interest, Mode::OneShot),
+                ready: false,
+            })
+            .collect::<Vec<_>>();
+        if planes.is_empty() {
+            return Err(AlreadyReady);
+        }
+
+        let signaled = Arc::new(AtomicBool::new(false));
+        let blocker = DmabufBlocker {
+            signaled: signaled.clone(),
+        };
+        let source = DmabufSource { planes, signaled };
+        Ok((blocker, source))
+    }
+}
+
+/// The [`Dmabuf`] is ready already, no [`Blocker`] is necessary
+#[cfg(feature = "wayland_frontend")]
+#[derive(Debug, thiserror::Error)]
+#[error("The dmabuf is already ready")]
+pub struct AlreadyReady;
+
+/// [`Blocker`] waiting for the implicit fences of a [`Dmabuf`]
+#[cfg(feature = "wayland_frontend")]
+#[derive(Debug)]
+pub struct DmabufBlocker {
+    signaled: Arc<AtomicBool>,
+}
+
+#[cfg(feature = "wayland_frontend")]
+impl Blocker for DmabufBlocker {
+    fn state(&self) -> BlockerState {
+        if self.signaled.load(Ordering::Acquire) {
+            BlockerState::Released
+        } else {
+            BlockerState::Pending
+        }
+    }
+}
+
+#[cfg(feature = "wayland_frontend")]
+#[derive(Debug)]
+struct DmabufPlaneSource {
+    source: Generic<OwnedFd>,
+    ready: bool,
+}
+
+/// Event source releasing a [`DmabufBlocker`]
+///
+/// Generates a single event once all planes of the buffer are ready and removes itself afterwards.
+#[cfg(feature = "wayland_frontend")]
+#[derive(Debug)]
+pub struct DmabufSource {
+    planes: Vec<DmabufPlaneSource>,
+    signaled: Arc<AtomicBool>,
+}
+
+#[cfg(feature = "wayland_frontend")]
+impl EventSource for DmabufSource {
+    type Event = ();
+    type Metadata = ();
+    type Ret = ();
+    type Error = std::io::Error;
+
+    fn process_events<F>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        mut callback: F,
+    ) -> std::io::Result<PostAction>
+    where
+        F: FnMut(Self::Event, &mut Self::Metadata) -> Self::Ret,
+    {
+        for plane in self.planes.iter_mut().filter(|plane| !plane.ready) {
+            let ready = &mut plane.ready;
+            plane.source.process_events(readiness, token, |_, _| {
+                // the source is registered as oneshot, so it won't trigger again
+                *ready = true;
+                Ok(PostAction::Continue)
+            })?;
+        }
+
+        if self.planes.iter().all(|plane| plane.ready) {
+            self.signaled.store(true, Ordering::Release);
+            callback((), &mut ());
+            Ok(PostAction::Remove)
+        } else {
+            Ok(PostAction::Continue)
+        }
+    }
+
+    fn register(&mut self, poll: &mut Poll, token_factory: &mut TokenFactory) -> calloop::Result<()> {
+        for plane in self.planes.iter_mut() {
+            plane.source.register(poll, token_factory)?;
+        }
+        Ok(())
+    }
+
+    fn reregister(&mut self, poll: &mut Poll, token_factory: &mut TokenFactory) -> calloop::Result<()> {
+        for plane in self.planes.iter_mut() {
+            plane.source.reregister(poll, token_factory)?;
+        }
+        Ok(())
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> calloop::Result<()> {
+        for plane in self.planes.iter_mut() {
+            plane.source.unregister(poll)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,842 @@
+//! Composition on a drm surface with automatic plane assignment
+//!
+//! The [`DrmCompositor`] combines a [`DrmSurface`], a swapchain of gbm buffers for the
+//! primary plane (like a [`GbmBufferedSurface`](super::GbmBufferedSurface)) and a
+//! [`DamageTrackedRenderer`] into a single abstraction taking a list of [`RenderElement`]s.
+//!
+//! For every frame it tries to display elements directly on hardware planes, avoiding
+//! to composite them with the renderer:
+//!
+//! - An element covering the whole output can be directly scanned out on the primary plane,
+//!   skipping rendering entirely (e.g. fullscreen video or games).
+//! - Elements not overlapped by any element in front of them can be put on the cursor or
+//!   overlay planes of the crtc.
+//!
+//! Only elements backed by a dmabuf (see [`UnderlyingStorage`](crate::backend::renderer::element::UnderlyingStorage)) are considered for planes.
+//! Additionally the element has to be displayed without any scaling, cropping or transformation
+//! and the output needs to use [`Transform::Normal`]. Every assignment is verified
+//! by a test commit (see [`DrmSurface::test_state`]), so elements not supported by the hardware
+//! are composited on the primary plane as usual. All remaining elements are rendered into the
+//! buffers of the primary plane using damage tracking.
+//!
+//! Elements put on a plane are reported with
+//! [`RenderElementPresentationState::ZeroCopy`](crate::backend::renderer::element::RenderElementPresentationState::ZeroCopy)
+//! in the returned [`RenderElementStates`].
+//!
+//! Client buffers displayed directly are not released to the client, until the page flip
+//! replacing them has completed (see [`DrmCompositor::frame_submitted`]).
+//!
+//! The results of test commits are cached for the formats and positions of the involved buffers,
+//! so a static arrangement of planes is only tested once.
+//!
+//! ## Usage
+//!
+//! Create a [`DrmCompositor`] for every [`DrmSurface`] and [`Output`] and call
+//! [`render_frame`](DrmCompositor::render_frame) whenever the output needs to be redrawn.
+//! If the returned frame is not empty, queue it using [`queue_frame`](DrmCompositor::queue_frame)
+//! and call [`frame_submitted`](DrmCompositor::frame_submitted) once the matching
+//! [`DrmEvent::VBlank`](super::DrmEvent::VBlank) is received.
+
+#[cfg(feature = "wayland_frontend")]
+use std::os::unix::io::OwnedFd;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    os::unix::io::AsFd,
+    sync::Arc,
+};
+
+use drm::{
+    control::{connector, crtc, framebuffer, plane, Mode, PageFlipFlags},
+    Device as BasicDevice, DriverCapability,
+};
+use gbm::BufferObject;
+use slog::{debug, o, trace};
+
+use crate::{
+    backend::{
+        allocator::{
+            dmabuf::{AsDmabuf, Dmabuf, WeakDmabuf},
+            gbm::{GbmBufferFlags, GbmDevice},
+            Allocator, Buffer, Format, Slot, Swapchain,
+        },
+        renderer::{
+            damage::{DamageTrackedRenderer, DamageTrackedRendererError, OutputNoMode},
+            element::{Element, Id, RenderElement, RenderElementState, RenderElementStates},
+            utils::CommitCounter,
+            Bind, Renderer, Texture,
+        },
+    },
+    output::Output,
+    utils::{Physical, Rectangle, Scale, Size, Transform},
+};
+
+use super::{
+    surface::{
+        gbm::{attach_framebuffer, create_swapchain, Error, FbHandle},
+        PlaneConfig,
+    },
+    DrmSurface, Planes,
+};
+
+/// Number of test commit results cached, before the cache is reset
+const MAX_CACHED_TESTS: usize = 64;
+
+/// A buffer, that can be directly scanned out
+#[derive(Debug)]
+struct ScanoutBuffer {
+    fb: FbHandle,
+    format: Format,
+    _bo: BufferObject<()>,
+}
+
+/// Reference to the client buffer of a scanned out element
+#[cfg(feature = "wayland_frontend")]
+type ClientBuffer = crate::backend::renderer::utils::Buffer;
+#[cfg(not(feature = "wayland_frontend"))]
+type ClientBuffer = ();
+
+/// A buffer assigned to a plane
+#[derive(Debug, Clone)]
+struct PlaneBuffer {
+    scanout: Arc<ScanoutBuffer>,
+    /// Keeps the client buffer from being released, while it is displayed
+    _client: ClientBuffer,
+}
+
+#[derive(Debug)]
+enum PrimaryBuffer {
+    Swapchain(Slot<BufferObject<()>>),
+    Scanout(PlaneBuffer),
+}
+
+impl PrimaryBuffer {
+    fn fb(&self) -> framebuffer::Handle {
+        match self {
+            PrimaryBuffer::Swapchain(slot) => slot.userdata().get::<FbHandle>().unwrap().fb,
+            PrimaryBuffer::Scanout(buffer) => buffer.scanout.fb.fb,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct FrameState {
+    primary: PrimaryBuffer,
+    planes: Vec<(PlaneConfig, PlaneBuffer)>,
+}
+
+/// The parts of a plane configuration relevant for the result of a test commit
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct TestKey {
+    primary: Format,
+    planes: Vec<(plane::Handle, Format, (i32, i32), (u32, u32))>,
+}
+
+impl FrameState {
+    fn plane_configs(&self) -> Vec<PlaneConfig> {
+        self.planes.iter().map(|(config, _)| *config).collect()
+    }
+
+    #[cfg(feature = "wayland_frontend")]
+    fn plane_buffers(&self) -> impl Iterator<Item = &PlaneBuffer> {
+        let primary = match &self.primary {
+            PrimaryBuffer::Scanout(buffer) => Some(buffer),
+            PrimaryBuffer::Swapchain(_) => None,
+        };
+        primary
+            .into_iter()
+            .chain(self.planes.iter().map(|(_, buffer)| buffer))
+    }
+
+    /// Delays the release of the client buffers scanned out by this frame until the fence is signalled
+    #[cfg(feature = "wayland_frontend")]
+    fn add_release_fence(&self, fence: &OwnedFd) {
+        for buffer in self.plane_buffers() {
+            if let Ok(fence) = fence.try_clone() {
+                buffer._client.add_release_fence(fence);
+            }
+        }
+    }
+}
+
+/// Result of [`DrmCompositor::render_frame`]
+#[derive(Debug)]
+pub struct RenderFrameResult {
+    /// Whether the frame is identical to the last queued frame.
+    ///
+    /// Empty frames do not need to be queued.
+    pub is_empty: bool,
+    /// The damage rendered to the primary plane, if it was rendered to
+    pub damage: Option<Vec<Rectangle<i32, Physical>>>,
+    /// The render states of the provided elements
+    pub states: RenderElementStates,
+}
+
+/// Errors thrown by [`DrmCompositor::render_frame`]
+#[derive(thiserror::Error)]
+pub enum RenderFrameError<E: std::error::Error + Send + Sync + 'static, R: Renderer> {
+    /// Preparing the frame failed
+    #[error(transparent)]
+    PrepareFrame(#[from] Error<E>),
+    /// Rendering the frame failed
+    #[error(transparent)]
+    RenderFrame(DamageTrackedRendererError<R>),
+}
+
+impl<E: std::error::Error + Send + Sync + 'static, R: Renderer> fmt::Debug for RenderFrameError<E, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderFrameError::PrepareFrame(err) => fmt::Debug::fmt(err, f),
+            RenderFrameError::RenderFrame(err) => fmt::Debug::fmt(err, f),
+        }
+    }
+}
+
+impl<E: std::error::Error + Send + Sync + 'static, R: Renderer> From<OutputNoMode>
+    for RenderFrameError<E, R>
+{
+    fn from(err: OutputNoMode) -> Self {
+        RenderFrameError::RenderFrame(DamageTrackedRendererError::OutputNoMode(err))
+    }
+}
+
+/// Composites [`RenderElement`]s onto a [`DrmSurface`] making use of the available planes
+///
+/// See the [module-level documentation](self) for details.
+pub struct DrmCompositor<A, G, U>
+where
+    A: Allocator<Buffer = BufferObject<()>>,
+    G: AsFd + 'static,
+{
+    surface: Arc<DrmSurface>,
+    planes: Planes,
+    cursor_size: Size<i32, Physical>,
+    gbm: GbmDevice<G>,
+    swapchain: Swapchain<A>,
+    damage_tracked_renderer: DamageTrackedRenderer,
+    force_full_damage: bool,
+    scanout_buffers: HashMap<WeakDmabuf, Option<Arc<ScanoutBuffer>>>,
+    plane_commits: HashMap<Id, CommitCounter>,
+    test_results: HashMap<TestKey, bool>,
+
+    current_frame: Option<FrameState>,
+    pending_frame: Option<(FrameState, U)>,
+    /// The queued frame, its user data and whether it should be flipped asynchronously
+    queued_frame: Option<(FrameState, U, bool)>,
+    next_frame: Option<FrameState>,
+
+    logger: ::slog::Logger,
+}
+
+impl<A, G, U> fmt::Debug for DrmCompositor<A, G, U>
+where
+    A: Allocator<Buffer = BufferObject<()>>,
+    G: AsFd + 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DrmCompositor")
+            .field("surface", &self.surface)
+            .field("planes", &self.planes)
+            .field("damage_tracked_renderer", &self.damage_tracked_renderer)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<A, G, U> DrmCompositor<A, G, U>
+where
+    A: Allocator<Buffer = BufferObject<()>>,
+    A::Error: std::error::Error + Send + Sync,
+    G: AsFd + 'static,
+{
+    /// Create a new `DrmCompositor` for a given [`DrmSurface`] and [`Output`].
+    ///
+    /// The `allocator` is used to allocate the buffers of the primary plane, while the
+    /// `gbm` device is used to import client buffers for direct scan-out.
+    /// `renderer_formats` are the formats the used renderer is able to render into (see
+    /// [`GbmBufferedSurface::new`](super::GbmBufferedSurface::new)).
+    ///
+    /// If `planes` is `None`, all planes of the surface (see [`DrmSurface::planes`]) may be used.
+    pub fn new<L>(
+        output: &Output,
+        surface: DrmSurface,
+        planes: Option<Planes>,
+        allocator: A,
+        gbm: GbmDevice<G>,
+        renderer_formats: HashSet<Format>,
+        log: L,
+    ) -> Result<DrmCompositor<A, G, U>, Error<A::Error>>
+    where
+        L: Into<Option<::slog::Logger>>,
+    {
+        let surface = Arc::new(surface);
+        let log = crate::slog_or_fallback(log).new(o!("backend" => "drm_compositor"));
+
+        let planes = match planes {
+            Some(planes) => planes,
+            None => surface.planes()?,
+        };
+        let cursor_width = surface
+            .get_driver_capability(DriverCapability::CursorWidth)
+            .unwrap_or(64);
+        let cursor_height = surface
+            .get_driver_capability(DriverCapability::CursorHeight)
+            .unwrap_or(64);
+
+        let (current_fb, swapchain) = create_swapchain(&surface, allocator, renderer_formats, &log)?;
+        debug!(log, "Available planes: {:?}", planes);
+
+        Ok(DrmCompositor {
+            surface,
+            planes,
+            cursor_size: Size::from((cursor_width as i32, cursor_height as i32)),
+            gbm,
+            swapchain,
+            damage_tracked_renderer: DamageTrackedRenderer::from_output(output),
+            force_full_damage: false,
+            scanout_buffers: HashMap::new(),
+            plane_commits: HashMap::new(),
+            test_results: HashMap::new(),
+            current_frame: Some(FrameState {
+                primary: PrimaryBuffer::Swapchain(current_fb),
+                planes: Vec::new(),
+            }),
+            pending_frame: None,
+            queued_frame: None,
+            next_frame: None,
+            logger: log,
+        })
+    }
+
+    /// Render the next frame
+    ///
+    /// Elements are expected in front-to-back order, like for
+    /// [`DamageTrackedRenderer::render_output`]. Elements suitable for scan-out are assigned
+    /// to planes, the remaining elements are rendered into the next buffer of the primary plane.
+    ///
+    /// *Note*: The resulting frame needs to be queued with [`DrmCompositor::queue_frame`]
+    /// to be displayed. Calling this function again before doing so, discards the previous frame.
+    pub fn render_frame<R, E>(
+        &mut self,
+        renderer: &mut R,
+        elements: &[E],
+        clear_color: [f32; 4],
+    ) -> Result<RenderFrameResult, RenderFrameError<A::Error, R>>
+    where
+        R: Renderer + Bind<Dmabuf>,
+        <R as Renderer>::TextureId: Texture,
+        E: RenderElement<R>,
+    {
+        if self.next_frame.take().is_some() {
+            // the discarded frame might have been rendered with damage, that will never be displayed
+            self.force_full_damage = true;
+        }
+
+        let (output_size, output_scale, output_transform) =
+            self.damage_tracked_renderer.mode().clone().try_into()?;
+        let output_geo = Rectangle::from_loc_and_size((0, 0), output_transform.transform_size(output_size));
+
+        self.scanout_buffers.retain(|dmabuf, _| !dmabuf.is_gone());
+
+        let slot = self
+            .swapchain
+            .acquire()
+            .map_err(Error::<A::Error>::GbmError)?
+            .ok_or(Error::<A::Error>::NoFreeSlotsError)?;
+        if slot.userdata().get::<FbHandle>().is_none() {
+            let fb = attach_framebuffer::<A::Error>(&self.surface, &slot)?;
+            slot.userdata().insert_if_missing(|| fb);
+        }
+        let slot_fb = slot.userdata().get::<FbHandle>().unwrap().fb;
+        let slot_format = slot.format();
+
+        let use_planes = output_transform == Transform::Normal;
+        let mut free_cursor = self.planes.cursor;
+        let mut free_overlays = self.planes.overlay.clone();
+
+        let mut primary_elements: Vec<&E> = Vec::with_capacity(elements.len());
+        let mut plane_elements: Vec<(PlaneConfig, PlaneBuffer, &E)> = Vec::new();
+        let mut primary_scanout: Option<(PlaneBuffer, &E)> = None;
+
+        'elements: for (idx, element) in elements.iter().enumerate() {
+            let geometry = element.geometry(output_scale);
+            if !geometry.overlaps(output_geo) {
+                continue;
+            }
+
+            if use_planes {
+                // the primary plane can be replaced, if all elements in front are on other planes
+                // and the element hides everything behind it
+                if primary_elements.is_empty() && geometry == output_geo {
+                    // a translucent element is only displayed correctly, if nothing is behind it,
+                    // as the clear color is not rendered when replacing the primary plane
+                    let hides_background = is_opaque(element, output_scale)
+                        || (clear_color[3] == 0.0
+                            && elements[idx + 1..]
+                                .iter()
+                                .all(|element| !element.geometry(output_scale).overlaps(output_geo)));
+                    if hides_background {
+                        if let Some(buffer) = self.scanout_buffer(renderer, element, geometry) {
+                            let configs = plane_elements
+                                .iter()
+                                .map(|(config, buffer, _)| (*config, buffer.scanout.format))
+                                .collect::<Vec<_>>();
+                            if self.test_state((buffer.scanout.fb.fb, buffer.scanout.format), &configs)? {
+                                trace!(
+                                    self.logger,
+                                    "Assigning element {:?} to primary plane",
+                                    element.id()
+                                );
+                                primary_scanout = Some((buffer, element));
+                                break 'elements;
+                            }
+                        }
+                    }
+                }
+
+                // cursor and overlay planes are displayed on top of the primary plane,
+                // so no element in front of this one may overlap it
+                let overlapped = elements[..idx]
+                    .iter()
+                    .any(|element| element.geometry(output_scale).overlaps(geometry));
+                if !overlapped && output_geo.contains_rect(geometry) {
+                    let fits_cursor =
+                        geometry.size.w <= self.cursor_size.w && geometry.size.h <= self.cursor_size.h;
+                    let candidates = free_cursor
+                        .filter(|_| fits_cursor)
+                        .into_iter()
+                        .chain(free_overlays.iter().copied())
+                        .collect::<Vec<_>>();
+
+                    if !candidates.is_empty() {
+                        if let Some(buffer) = self.scanout_buffer(renderer, element, geometry) {
+                            let mut configs = plane_elements
+                                .iter()
+                                .map(|(config, buffer, _)| (*config, buffer.scanout.format))
+                                .collect::<Vec<_>>();
+                            for plane in candidates {
+                                let config = PlaneConfig {
+                                    handle: plane,
+                                    fb: buffer.scanout.fb.fb,
+                                    position: (geometry.loc.x, geometry.loc.y),
+                                    size: (geometry.size.w as u32, geometry.size.h as u32),
+                                };
+                                configs.push((config, buffer.scanout.format));
+                                if self.test_state((slot_fb, slot_format), &configs)? {
+                                    trace!(
+                                        self.logger,
+                                        "Assigning element {:?} to plane {:?}",
+                                        element.id(),
+                                        plane
+                                    );
+                                    if free_cursor == Some(plane) {
+                                        free_cursor = None;
+                                    }
+                                    free_overlays.retain(|overlay| *overlay != plane);
+                                    plane_elements.push((config, buffer, element));
+                                    continue 'elements;
+                                }
+                                configs.pop();
+                            }
+                        }
+                    }
+                }
+            }
+
+            primary_elements.push(element);
+        }
+
+        // check if the elements on planes changed since the last frame
+        let plane_commits = plane_elements
+            .iter()
+            .map(|(_, _, element)| element)
+            .chain(primary_scanout.as_ref().map(|(_, element)| element))
+            .map(|element| (element.id().clone(), element.current_commit()))
+            .collect::<HashMap<_, _>>();
+        let planes_damaged = plane_commits
+            .iter()
+            .any(|(id, commit)| self.plane_commits.get(id) != Some(commit));
+        self.plane_commits = plane_commits;
+
+        let configs = plane_elements
+            .iter()
+            .map(|(config, _, _)| *config)
+            .collect::<Vec<_>>();
+        let planes_changed = self
+            .last_frame()
+            .map(|frame| frame.plane_configs() != configs)
+            .unwrap_or(true);
+
+        let zero_copy_states = plane_elements
+            .iter()
+            .map(|(_, _, element)| element)
+            .chain(primary_scanout.as_ref().map(|(_, element)| element))
+            .map(|element| {
+                let geometry = element.geometry(output_scale);
+                (
+                    element.id().clone(),
+                    RenderElementState::zero_copy((geometry.size.w * geometry.size.h) as usize),
+                )
+            })
+            .collect::<Vec<_>>();
+        let planes = plane_elements
+            .into_iter()
+            .map(|(config, buffer, _)| (config, buffer))
+            .collect::<Vec<_>>();
+
+        if let Some((buffer, _)) = primary_scanout {
+            // the primary plane was not rendered to, so the damage tracking needs to start over
+            self.force_full_damage = true;
+
+            let mut states = RenderElementStates {
+                states: elements
+                    .iter()
+                    .map(|element| (element.id().clone(), RenderElementState::skipped()))
+                    .collect(),
+            };
+            states.states.extend(zero_copy_states);
+
+            let primary_changed = self
+                .last_frame()
+                .map(|frame| frame.primary.fb() != buffer.scanout.fb.fb)
+                .unwrap_or(true);
+            let is_empty = !primary_changed && !planes_changed && !planes_damaged;
+            if !is_empty {
+                self.next_frame = Some(FrameState {
+                    primary: PrimaryBuffer::Scanout(buffer),
+                    planes,
+                });
+            }
+
+            return Ok(RenderFrameResult {
+                is_empty,
+                damage: None,
+                states,
+            });
+        }
+
+        let age = if self.force_full_damage {
+            0
+        } else {
+            slot.age() as usize
+        };
+        let dmabuf = slot.export().map_err(Error::<A::Error>::AsDmabufError)?;
+        renderer
+            .bind(dmabuf)
+            .map_err(|err| RenderFrameError::RenderFrame(DamageTrackedRendererError::Rendering(err)))?;
+        let (damage, mut states) = self
+            .damage_tracked_renderer
+            .render_output(renderer, age, &primary_elements, clear_color, self.logger.clone())
+            .map_err(RenderFrameError::RenderFrame)?;
+        self.force_full_damage = false;
+        for (id, state) in zero_copy_states {
+            states.states.insert(id, state);
+        }
+
+        let is_empty = damage.is_none() && !planes_changed && !planes_damaged;
+        if !is_empty {
+            self.next_frame = Some(FrameState {
+                primary: PrimaryBuffer::Swapchain(slot),
+                planes,
+            });
+        }
+
+        Ok(RenderFrameResult {
+            is_empty,
+            damage,
+            states,
+        })
+    }
+
+    /// Queues the last rendered frame for display.
+    ///
+    /// *Note*: This function needs to be followed up with [`DrmCompositor::frame_submitted`]
+    /// when a vblank event is received, that denotes successful scanout of the frame.
+    /// Otherwise the underlying swapchain will eventually run out of buffers.
+    ///
+    /// `user_data` can be used to attach some data to a specific frame and later retrieved
+    /// with [`DrmCompositor::frame_submitted`]. If no frame was rendered or the last frame was
+    /// empty, this function does nothing.
+    ///
+    /// If `async_flip` is set, the frame is presented without waiting for the next vblank, which may cause tearing.
+    /// This falls back to a vblank synchronized flip, if the device does not support asynchronous page flips
+    /// (see [`DrmSurface::async_page_flip_supported`]), rejects the flip or the pending state requires a modeset.
+    /// Atomic drivers usually only accept asynchronous flips, if no other planes than the primary plane change.
+    pub fn queue_frame(&mut self, user_data: U, async_flip: bool) -> Result<(), Error<A::Error>> {
+        if let Some(frame) = self.next_frame.take() {
+            if let PrimaryBuffer::Swapchain(slot) = &frame.primary {
+                self.swapchain.submitted(slot);
+            }
+            self.queued_frame = Some((frame, user_data, async_flip));
+            if self.pending_frame.is_none() {
+                self.submit()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Marks the current frame as submitted.
+    ///
+    /// *Note*: Needs to be called, after the vblank event of the matching [`DrmDevice`](super::DrmDevice)
+    /// was received after calling [`DrmCompositor::queue_frame`].
+    /// Otherwise the underlying swapchain will run out of buffers eventually.
+    ///
+    /// Client buffers directly scanned out by the previous frame are released at this point.
+    ///
+    /// Returns the user data that was stored with [`DrmCompositor::queue_frame`] if a frame was pending,
+    /// otherwise `None` is returned.
+    pub fn frame_submitted(&mut self) -> Result<Option<U>, Error<A::Error>> {
+        if let Some((frame, user_data)) = self.pending_frame.take() {
+            self.current_frame = Some(frame);
+            if self.queued_frame.is_some() {
+                self.submit()?;
+            }
+            Ok(Some(user_data))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn submit(&mut self) -> Result<(), Error<A::Error>> {
+        let (frame, user_data, async_flip) = self.queued_frame.take().unwrap();
+
+        self.surface.set_planes(&frame.plane_configs())?;
+        // the out-fence of the commit signals, once the client buffers of the current frame are not scanned out anymore
+        #[cfg(feature = "wayland_frontend")]
+        let request_out_fence = self
+            .current_frame
+            .as_ref()
+            .map(|frame| frame.plane_buffers().next().is_some())
+            .unwrap_or(false);
+        #[cfg(feature = "wayland_frontend")]
+        if request_out_fence {
+            // legacy devices do not support out-fences, in which case the buffers
+            // are released without a fence once the frame is replaced
+            let _ = self.surface.request_out_fence();
+        }
+        let fbs = [(frame.primary.fb(), self.surface.plane())];
+        let flip = if self.surface.commit_pending() {
+            self.surface.commit(fbs.iter(), PageFlipFlags::EVENT)
+        } else if async_flip && self.surface.async_page_flip_supported() {
+            self.surface
+                .page_flip(fbs.iter(), PageFlipFlags::EVENT | PageFlipFlags::ASYNC)
+                .or_else(|_| {
+                    // the rejected flip consumed the out-fence request
+                    #[cfg(feature = "wayland_frontend")]
+                    if request_out_fence {
+                        let _ = self.surface.request_out_fence();
+                    }
+                    self.surface.page_flip(fbs.iter(), PageFlipFlags::EVENT)
+                })
+        } else {
+            self.surface.page_flip(fbs.iter(), PageFlipFlags::EVENT)
+        };
+        if flip.is_ok() {
+            #[cfg(feature = "wayland_frontend")]
+            if let (Some(fence), Some(current_frame)) =
+                (self.surface.take_out_fence(), self.current_frame.as_ref())
+            {
+                current_frame.add_release_fence(&fence);
+            }
+            self.pending_frame = Some((frame, user_data));
+        }
+        flip.map_err(Error::DrmError)
+    }
+
+    fn last_frame(&self) -> Option<&FrameState> {
+        self.queued_frame
+            .as_ref()
+            .map(|(frame, _, _)| frame)
+            .or_else(|| self.pending_frame.as_ref().map(|(frame, _)| frame))
+            .or(self.current_frame.as_ref())
+    }
+
+    fn scanout_buffer<R, E>(
+        &mut self,
+        renderer: &R,
+        element: &E,
+        geometry: Rectangle<i32, Physical>,
+    ) -> Option<PlaneBuffer>
+    where
+        R: Renderer,
+        E: RenderElement<R>,
+    {
+        let (dmabuf, client) = element_dmabuf(renderer, element)?;
+
+        // planes are used without any scaling, cropping or transformation
+        let size = dmabuf.size();
+        if element.transform() != Transform::Normal
+            || dmabuf.y_inverted()
+            || element.src() != Rectangle::from_loc_and_size((0.0, 0.0), size.to_f64())
+            || geometry.size != Size::from((size.w, size.h))
+        {
+            return None;
+        }
+
+        let gbm = &self.gbm;
+        let surface = &self.surface;
+        let logger = &self.logger;
+        self.scanout_buffers
+            .entry(dmabuf.weak())
+            .or_insert_with(|| {
+                let bo = match dmabuf.import_to::<_, ()>(gbm, GbmBufferFlags::SCANOUT) {
+                    Ok(bo) => bo,
+                    Err(err) => {
+                        trace!(logger, "Failed to import dmabuf for scan-out: {}", err);
+                        return None;
+                    }
+                };
+                match attach_framebuffer::<std::io::Error>(surface, &bo) {
+                    Ok(fb) => Some(Arc::new(ScanoutBuffer {
+                        fb,
+                        format: dmabuf.format(),
+                        _bo: bo,
+                    })),
+                    Err(err) => {
+                        trace!(logger, "Failed to create framebuffer for scan-out: {}", err);
+                        None
+                    }
+                }
+            })
+            .clone()
+            .map(|scanout| PlaneBuffer {
+                scanout,
+                _client: client,
+            })
+    }
+
+    /// Tests a plane configuration, re-using the result of previous tests of equivalent configurations
+    fn test_state(
+        &mut self,
+        primary: (framebuffer::Handle, Format),
+        planes: &[(PlaneConfig, Format)],
+    ) -> Result<bool, Error<A::Error>> {
+        let key = TestKey {
+            primary: primary.1,
+            planes: planes
+                .iter()
+                .map(|(config, format)| (config.handle, *format, config.position, config.size))
+                .collect(),
+        };
+        if let Some(result) = self.test_results.get(&key) {
+            return Ok(*result);
+        }
+
+        let configs = planes.iter().map(|(config, _)| *config).collect::<Vec<_>>();
+        let result = self
+            .surface
+            .test_state(primary.0, &configs)
+            .map_err(Error::<A::Error>::DrmError)?;
+        if self.test_results.len() >= MAX_CACHED_TESTS {
+            self.test_results.clear();
+        }
+        self.test_results.insert(key, result);
+        Ok(result)
+    }
+
+    /// Reset the age and contents of the buffers of the primary plane
+    ///
+    /// This also discards the cached results of previous test commits.
+    pub fn reset_buffers(&mut self) {
+        self.swapchain.reset_buffers();
+        self.force_full_damage = true;
+        self.test_results.clear();
+    }
+
+    /// Returns the underlying [`crtc`](drm::control::crtc) of this compositor
+    pub fn crtc(&self) -> crtc::Handle {
+        self.surface.crtc()
+    }
+
+    /// Returns the underlying primary [`plane`](drm::control::plane) of this compositor
+    pub fn plane(&self) -> plane::Handle {
+        self.surface.plane()
+    }
+
+    /// Returns the currently active [`Mode`](drm::control::Mode)
+    /// of the underlying [`crtc`](drm::control::crtc)
+    pub fn current_mode(&self) -> Mode {
+        self.surface.current_mode()
+    }
+
+    /// Returns the currently pending [`Mode`](drm::control::Mode)
+    /// to be used after the next commit.
+    pub fn pending_mode(&self) -> Mode {
+        self.surface.pending_mode()
+    }
+
+    /// Tries to set a new [`Mode`](drm::control::Mode)
+    /// to be used after the next commit.
+    ///
+    /// Fails if the mode is not compatible with the underlying
+    /// [`crtc`](drm::control::crtc) or any of the
+    /// pending [`connector`](drm::control::connector)s.
+    ///
+    /// *Note*: You also need to update the mode of the [`Output`] used to create this compositor.
+    pub fn use_mode(&mut self, mode: Mode) -> Result<(), Error<A::Error>> {
+        self.surface.use_mode(mode).map_err(Error::DrmError)?;
+        let (w, h) = mode.size();
+        self.swapchain.resize(w as _, h as _);
+        self.test_results.clear();
+        Ok(())
+    }
+
+    /// Returns true, if the given [`connector`](drm::control::connector) supports
+    /// variable refresh rate on this surface.
+    pub fn vrr_supported(&self, conn: connector::Handle) -> Result<bool, Error<A::Error>> {
+        self.surface.vrr_supported(conn).map_err(Error::DrmError)
+    }
+
+    /// Tries to enable or disable variable refresh rate
+    /// to be used after the next commit.
+    pub fn use_vrr(&self, vrr: bool) -> Result<(), Error<A::Error>> {
+        self.surface.use_vrr(vrr).map_err(Error::DrmError)
+    }
+
+    /// Returns true, if variable refresh rate is currently enabled
+    pub fn vrr_enabled(&self) -> bool {
+        self.surface.vrr_enabled()
+    }
+
+    /// Returns a reference to the underlying drm surface
+    pub fn surface(&self) -> &DrmSurface {
+        &self.surface
+    }
+}
+
+fn is_opaque<E: Element>(element: &E, scale: Scale<f64>) -> bool {
+    let size = element.geometry(scale).size;
+    element
+        .opaque_regions(scale)
+        .into_iter()
+        .fold(
+            vec![Rectangle::from_loc_and_size((0, 0), size)],
+            |rest, region| {
+                rest.into_iter()
+                    .flat_map(|rect| rect.subtract_rect(region))
+                    .collect::<Vec<_>>()
+            },
+        )
+        .is_empty()
+}
+
+#[cfg(feature = "wayland_frontend")]
+fn element_dmabuf<R: Renderer, E: RenderElement<R>>(
+    renderer: &R,
+    element: &E,
+) -> Option<(Dmabuf, ClientBuffer)> {
+    match element.underlying_storage(renderer)? {
+        crate::backend::renderer::element::UnderlyingStorage::Wayland(buffer) => {
+            let dmabuf = crate::wayland::dmabuf::get_dmabuf(&buffer).ok()?;
+            Some((dmabuf, buffer))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(not(feature = "wayland_frontend"))]
+fn element_dmabuf<R: Renderer, E: RenderElement<R>>(
+    _renderer: &R,
+    _element: &E,
+) -> Option<(Dmabuf, ClientBuffer)> {
+    None
+}
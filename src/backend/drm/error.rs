@@ -59,6 +59,15 @@ pub enum Error {
     /// Atomic Test failed for new properties
     #[error("Atomic Test failed for new properties on crtc ({0:?})")]
     TestFailed(crtc::Handle),
+    /// Explicit synchronization using fences is not available for use with legacy devices
+    #[error("Explicit synchronization is not available for use with legacy devices")]
+    ExplicitSyncUnsupported,
+    /// Variable refresh rate is not available for use with legacy devices
+    #[error("Variable refresh rate is not available for use with legacy devices")]
+    VrrUnsupported,
+    /// Asynchronous page flips are not supported by the device
+    #[error("Asynchronous page flips are not supported by the device")]
+    AsyncPageFlipUnsupported,
 }
 
 impl From<Error> for SwapBuffersError {
@@ -62,6 +62,10 @@
 //! Buffer management and details about the various types can be found in the [`allocator`-Module](crate::backend::allocator) and
 //! rendering abstractions, which can target these buffers can be found in the [`renderer`-Module](crate::backend::renderer).
 //!
+//! [`GbmBufferedSurface`] provides a simple way to render into the primary plane of a surface,
+//! while the [`DrmCompositor`](compositor::DrmCompositor) additionally makes use of overlay and cursor planes
+//! and direct scan-out of client buffers.
+//!
 //! ## [`DrmNode`]
 //!
 //! A drm node refers to a drm device and the capabilities that may be performed using the node.
@@ -69,6 +73,8 @@
 //! to allocate buffers for use in X11 or Wayland. If you need to do mode setting, you should use
 //! [`DrmDevice`] instead.
 
+#[cfg(feature = "backend_gbm")]
+pub mod compositor;
 pub(crate) mod device;
 pub(self) mod error;
 pub mod node;
@@ -81,7 +87,7 @@ pub use error::Error as DrmError;
 pub use node::{CreateDrmNodeError, DrmNode, NodeType};
 #[cfg(feature = "backend_gbm")]
 pub use surface::gbm::{Error as GbmBufferedSurfaceError, GbmBufferedSurface};
-pub use surface::DrmSurface;
+pub use surface::{DrmSurface, PlaneConfig};
 
 use drm::control::{crtc, plane, Device as ControlDevice, PlaneType};
 
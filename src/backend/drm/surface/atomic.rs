@@ -1,10 +1,12 @@
 use drm::control::atomic::AtomicModeReq;
 use drm::control::Device as ControlDevice;
 use drm::control::{
-    connector, crtc, dumbbuffer::DumbBuffer, framebuffer, plane, property, AtomicCommitFlags, Mode, PlaneType,
+    connector, crtc, dumbbuffer::DumbBuffer, framebuffer, plane, property, AtomicCommitFlags, Mode,
+    PageFlipFlags, PlaneType, ResourceHandle,
 };
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc, Mutex, RwLock,
@@ -18,6 +20,7 @@ use crate::{
             device::DrmDeviceInternal,
             error::Error,
             plane_type,
+            surface::PlaneConfig,
         },
     },
     utils::DevPath,
@@ -30,6 +33,7 @@ pub struct State {
     pub mode: Mode,
     pub blob: property::Value<'static>,
     pub connectors: HashSet<connector::Handle>,
+    pub vrr: bool,
 }
 
 impl State {
@@ -98,10 +102,20 @@ impl State {
                 }
             }
         }
+        // variable refresh rate might have been left enabled by a previous drm master
+        let current_vrr = prop_mapping
+            .1
+            .get(&crtc)
+            .and_then(|props| props.get("VRR_ENABLED"))
+            .and_then(|prop| prop_value(fd, crtc, *prop))
+            .map(|val| val != 0)
+            .unwrap_or(false);
+
         Ok(State {
             mode: current_mode,
             blob: current_blob,
             connectors: current_connectors,
+            vrr: current_vrr,
         })
     }
 }
@@ -113,6 +127,20 @@ pub struct PlaneInfo {
     y: i32,
     w: u32,
     h: u32,
+    fb: Option<framebuffer::Handle>,
+}
+
+impl From<&PlaneConfig> for PlaneInfo {
+    fn from(config: &PlaneConfig) -> Self {
+        PlaneInfo {
+            handle: config.handle,
+            x: config.position.0,
+            y: config.position.1,
+            w: config.size.0,
+            h: config.size.1,
+            fb: Some(config.fb),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -122,9 +150,13 @@ pub struct AtomicDrmSurface {
     crtc: crtc::Handle,
     plane: plane::Handle,
     additional_planes: Mutex<Vec<PlaneInfo>>,
+    cleared_planes: Mutex<Vec<plane::Handle>>,
     prop_mapping: RwLock<Mapping>,
     state: RwLock<State>,
     pending: RwLock<State>,
+    in_fences: Mutex<HashMap<plane::Handle, OwnedFd>>,
+    out_fence_requested: AtomicBool,
+    out_fence: Mutex<Option<OwnedFd>>,
     pub(crate) logger: ::slog::Logger,
 }
 
@@ -160,6 +192,7 @@ impl AtomicDrmSurface {
             mode,
             blob,
             connectors: connectors.iter().copied().collect(),
+            vrr: false,
         };
 
         let surface = AtomicDrmSurface {
@@ -168,9 +201,13 @@ impl AtomicDrmSurface {
             crtc,
             plane,
             additional_planes: Mutex::new(Vec::new()),
+            cleared_planes: Mutex::new(Vec::new()),
             prop_mapping: RwLock::new(prop_mapping),
             state: RwLock::new(state),
             pending: RwLock::new(pending),
+            in_fences: Mutex::new(HashMap::new()),
+            out_fence_requested: AtomicBool::new(false),
+            out_fence: Mutex::new(None),
             logger,
         };
 
@@ -287,6 +324,7 @@ impl AtomicDrmSurface {
                 &mut [].iter(),
                 self.plane,
                 &[],
+                &[],
                 Some([(test_buffer.fb, self.plane)].iter()),
                 Some(pending.mode),
                 Some(pending.blob),
@@ -327,6 +365,7 @@ impl AtomicDrmSurface {
             &mut [conn].iter(),
             self.plane,
             &[],
+            &[],
             Some([(test_buffer.fb, self.plane)].iter()),
             Some(pending.mode),
             Some(pending.blob),
@@ -369,6 +408,7 @@ impl AtomicDrmSurface {
             &mut removed,
             self.plane,
             &[],
+            &[],
             Some([(test_buffer.fb, self.plane)].iter()),
             Some(pending.mode),
             Some(pending.blob),
@@ -409,6 +449,7 @@ impl AtomicDrmSurface {
             &mut [].iter(),
             self.plane,
             &[],
+            &[],
             Some([(test_buffer.fb, self.plane)].iter()),
             Some(mode),
             Some(new_blob),
@@ -432,6 +473,60 @@ impl AtomicDrmSurface {
         Ok(())
     }
 
+    pub fn vrr_supported(&self, conn: connector::Handle) -> Result<bool, Error> {
+        self.ensure_props_known(&[conn])?;
+        let prop_mapping = self.prop_mapping.read().unwrap();
+
+        if crtc_prop_handle(&prop_mapping, self.crtc, "VRR_ENABLED").is_err() {
+            return Ok(false);
+        }
+        Ok(conn_prop_handle(&prop_mapping, conn, "vrr_capable")
+            .ok()
+            .and_then(|prop| prop_value(&*self.fd, conn, prop))
+            .map(|val| val != 0)
+            .unwrap_or(false))
+    }
+
+    pub fn use_vrr(&self, vrr: bool) -> Result<(), Error> {
+        if !self.active.load(Ordering::SeqCst) {
+            return Err(Error::DeviceInactive);
+        }
+
+        let mut pending = self.pending.write().unwrap();
+        if pending.vrr == vrr {
+            return Ok(());
+        }
+
+        // check if new config is supported
+        let test_buffer = self.create_test_buffer(pending.mode.size(), self.plane)?;
+        let mut req = self.build_request(
+            &mut pending.connectors.iter(),
+            &mut [].iter(),
+            self.plane,
+            &[],
+            &[],
+            Some([(test_buffer.fb, self.plane)].iter()),
+            Some(pending.mode),
+            Some(pending.blob),
+        )?;
+        self.add_vrr(&mut req, vrr)?;
+        self.fd
+            .atomic_commit(
+                AtomicCommitFlags::ALLOW_MODESET | AtomicCommitFlags::TEST_ONLY,
+                req,
+            )
+            .map_err(|_| Error::TestFailed(self.crtc))?;
+
+        // seems to be, lets change it
+        pending.vrr = vrr;
+
+        Ok(())
+    }
+
+    pub fn vrr_enabled(&self) -> bool {
+        self.state.read().unwrap().vrr
+    }
+
     pub fn use_plane(
         &self,
         plane: plane::Handle,
@@ -444,6 +539,7 @@ impl AtomicDrmSurface {
             y: position.1,
             w: size.0,
             h: size.1,
+            fb: None,
         };
 
         let mut planes = self.additional_planes.lock().unwrap();
@@ -466,6 +562,7 @@ impl AtomicDrmSurface {
             &mut [].iter(),
             self.plane,
             &new_planes,
+            &[],
             Some(
                 [(primary_test_buffer.fb, self.plane)].iter().chain(
                     additional_test_buffers
@@ -497,7 +594,7 @@ impl AtomicDrmSurface {
     pub fn commit<'a>(
         &self,
         framebuffers: impl Iterator<Item = &'a (framebuffer::Handle, plane::Handle)>,
-        event: bool,
+        flags: PageFlipFlags,
     ) -> Result<(), Error> {
         if !self.active.load(Ordering::SeqCst) {
             return Err(Error::DeviceInactive);
@@ -541,15 +638,17 @@ impl AtomicDrmSurface {
 
         // test the new config and return the request if it would be accepted by the driver.
         let req = {
-            let req = self.build_request(
+            let mut req = self.build_request(
                 &mut added,
                 &mut removed,
                 self.plane,
                 &self.additional_planes.lock().unwrap(),
+                &self.cleared_planes.lock().unwrap(),
                 Some(framebuffers),
                 Some(pending.mode),
                 Some(pending.blob),
             )?;
+            self.add_vrr(&mut req, pending.vrr)?;
 
             if let Err(err) = self
                 .fd
@@ -577,26 +676,24 @@ impl AtomicDrmSurface {
             }
         };
 
+        // fences are only added to the actual commit
+        let mut req = req;
+        let (_in_fences, out_fence) = self.add_fences(&mut req)?;
+
         debug!(self.logger, "Setting screen: {:?}", req);
+        // on the atomic api we can modeset and trigger a page_flip event on the same call!
+        //
+        // we also *should* not need to wait for completion, like with `set_crtc`,
+        // because we have tested this exact commit already, so we do not expect any errors later down the line.
+        //
+        // but there is always an exception and `amdgpu` can fail in interesting ways with this flag set...
+        // https://gitlab.freedesktop.org/drm/amd/-/issues?scope=all&utf8=%E2%9C%93&state=opened&search=drm_atomic_helper_wait_for_flip_done
+        //
+        // so we skip this flag:
+        // AtomicCommitFlags::Nonblock,
         let result = self
             .fd
-            .atomic_commit(
-                if event {
-                    // on the atomic api we can modeset and trigger a page_flip event on the same call!
-                    AtomicCommitFlags::PAGE_FLIP_EVENT | AtomicCommitFlags::ALLOW_MODESET
-                    // we also *should* not need to wait for completion, like with `set_crtc`,
-                    // because we have tested this exact commit already, so we do not expect any errors later down the line.
-                    //
-                    // but there is always an exception and `amdgpu` can fail in interesting ways with this flag set...
-                    // https://gitlab.freedesktop.org/drm/amd/-/issues?scope=all&utf8=%E2%9C%93&state=opened&search=drm_atomic_helper_wait_for_flip_done
-                    //
-                    // so we skip this flag:
-                    // AtomicCommitFlags::Nonblock,
-                } else {
-                    AtomicCommitFlags::ALLOW_MODESET
-                },
-                req,
-            )
+            .atomic_commit(AtomicCommitFlags::ALLOW_MODESET | commit_flags(flags), req)
             .map_err(|source| Error::Access {
                 errmsg: "Error setting crtc",
                 dev: self.fd.dev_path(),
@@ -605,6 +702,8 @@ impl AtomicDrmSurface {
 
         if result.is_ok() {
             *current = pending.clone();
+            self.cleared_planes.lock().unwrap().clear();
+            self.store_out_fence(out_fence);
         }
 
         result
@@ -613,45 +712,171 @@ impl AtomicDrmSurface {
     pub fn page_flip<'a>(
         &self,
         framebuffers: impl Iterator<Item = &'a (framebuffer::Handle, plane::Handle)>,
-        event: bool,
+        flags: PageFlipFlags,
     ) -> Result<(), Error> {
         if !self.active.load(Ordering::SeqCst) {
             return Err(Error::DeviceInactive);
         }
 
         // page flips work just like commits with fewer parameters..
-        let req = self.build_request(
+        let mut req = self.build_request(
             &mut [].iter(),
             &mut [].iter(),
             self.plane,
             &self.additional_planes.lock().unwrap(),
+            &self.cleared_planes.lock().unwrap(),
             Some(framebuffers),
             None,
             None,
         )?;
+        let (_in_fences, out_fence) = self.add_fences(&mut req)?;
 
         // .. and without `AtomicCommitFlags::AllowModeset`.
         // If we would set anything here, that would require a modeset, this would fail,
         // indicating a problem in our assumptions.
         trace!(self.logger, "Queueing page flip: {:?}", req);
         self.fd
-            .atomic_commit(
-                if event {
-                    AtomicCommitFlags::PAGE_FLIP_EVENT | AtomicCommitFlags::NONBLOCK
-                } else {
-                    AtomicCommitFlags::NONBLOCK
-                },
-                req,
-            )
+            .atomic_commit(AtomicCommitFlags::NONBLOCK | commit_flags(flags), req)
             .map_err(|source| Error::Access {
                 errmsg: "Page flip commit failed",
                 dev: self.fd.dev_path(),
                 source,
             })?;
+        self.cleared_planes.lock().unwrap().clear();
+        self.store_out_fence(out_fence);
 
         Ok(())
     }
 
+    pub fn set_in_fence(&self, plane: plane::Handle, fence: OwnedFd) {
+        self.in_fences.lock().unwrap().insert(plane, fence);
+    }
+
+    pub fn request_out_fence(&self) {
+        self.out_fence_requested.store(true, Ordering::SeqCst);
+    }
+
+    pub fn take_out_fence(&self) -> Option<OwnedFd> {
+        self.out_fence.lock().unwrap().take()
+    }
+
+    // Sets the VRR_ENABLED property of the crtc, if it is supported.
+    fn add_vrr(&self, req: &mut AtomicModeReq, vrr: bool) -> Result<(), Error> {
+        let prop_mapping = self.prop_mapping.read().unwrap();
+        match crtc_prop_handle(&prop_mapping, self.crtc, "VRR_ENABLED") {
+            Ok(prop) => {
+                req.add_property(self.crtc, prop, property::Value::Boolean(vrr));
+                Ok(())
+            }
+            // without the property variable refresh rate is always disabled
+            Err(_) if !vrr => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    // Adds the pending in-fences and a requested out-fence to the request.
+    // The returned fences and out-fence storage need to be kept alive until the request is committed.
+    #[allow(clippy::type_complexity)]
+    fn add_fences(&self, req: &mut AtomicModeReq) -> Result<(Vec<OwnedFd>, Option<Box<i32>>), Error> {
+        let prop_mapping = self.prop_mapping.read().unwrap();
+
+        let in_fences = std::mem::take(&mut *self.in_fences.lock().unwrap());
+        let mut fences = Vec::with_capacity(in_fences.len());
+        for (plane, fence) in in_fences {
+            req.add_property(
+                plane,
+                plane_prop_handle(&prop_mapping, plane, "IN_FENCE_FD")?,
+                property::Value::SignedRange(fence.as_raw_fd() as i64),
+            );
+            fences.push(fence);
+        }
+
+        let out_fence = if self.out_fence_requested.swap(false, Ordering::SeqCst) {
+            // the kernel writes the fence fd into the memory pointed to by the property
+            let mut out_fence = Box::new(-1i32);
+            req.add_property(
+                self.crtc,
+                crtc_prop_handle(&prop_mapping, self.crtc, "OUT_FENCE_PTR")?,
+                property::Value::UnsignedRange(&mut *out_fence as *mut i32 as u64),
+            );
+            Some(out_fence)
+        } else {
+            None
+        };
+
+        Ok((fences, out_fence))
+    }
+
+    fn store_out_fence(&self, out_fence: Option<Box<i32>>) {
+        if let Some(fd) = out_fence.filter(|fd| **fd >= 0) {
+            // SAFETY: the kernel created a new file descriptor for us
+            *self.out_fence.lock().unwrap() = Some(unsafe { OwnedFd::from_raw_fd(*fd) });
+        }
+    }
+
+    pub fn set_planes(&self, planes: &[PlaneConfig]) -> Result<(), Error> {
+        let mut additional_planes = self.additional_planes.lock().unwrap();
+        let mut cleared_planes = self.cleared_planes.lock().unwrap();
+
+        for info in additional_planes.iter() {
+            if !planes.iter().any(|config| config.handle == info.handle)
+                && !cleared_planes.contains(&info.handle)
+            {
+                cleared_planes.push(info.handle);
+            }
+        }
+        cleared_planes.retain(|plane| !planes.iter().any(|config| config.handle == *plane));
+        *additional_planes = planes.iter().map(PlaneInfo::from).collect();
+
+        Ok(())
+    }
+
+    pub fn test_state(&self, fb: framebuffer::Handle, planes: &[PlaneConfig]) -> Result<bool, Error> {
+        if !self.active.load(Ordering::SeqCst) {
+            return Err(Error::DeviceInactive);
+        }
+
+        let current = self.state.read().unwrap();
+        let pending = self.pending.read().unwrap();
+
+        let current_conns = current.connectors.clone();
+        let pending_conns = pending.connectors.clone();
+        let mut removed = current_conns.difference(&pending_conns);
+        let mut added = pending_conns.difference(&current_conns);
+
+        let plane_infos = planes.iter().map(PlaneInfo::from).collect::<Vec<_>>();
+        // planes currently in use, that are not part of the tested state, need to be disabled
+        let cleared_planes = self
+            .additional_planes
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|info| info.handle)
+            .chain(self.cleared_planes.lock().unwrap().iter().copied())
+            .filter(|plane| !planes.iter().any(|config| config.handle == *plane))
+            .collect::<Vec<_>>();
+
+        let req = self.build_request(
+            &mut added,
+            &mut removed,
+            self.plane,
+            &plane_infos,
+            &cleared_planes,
+            Some([(fb, self.plane)].iter()),
+            Some(pending.mode),
+            Some(pending.blob),
+        )?;
+
+        let result = self
+            .fd
+            .atomic_commit(
+                AtomicCommitFlags::ALLOW_MODESET | AtomicCommitFlags::TEST_ONLY,
+                req,
+            )
+            .is_ok();
+        Ok(result)
+    }
+
     pub fn test_buffer(&self, fb: framebuffer::Handle, mode: &Mode) -> Result<bool, Error> {
         if !self.active.load(Ordering::SeqCst) {
             return Err(Error::DeviceInactive);
@@ -679,6 +904,7 @@ impl AtomicDrmSurface {
             &mut removed,
             self.plane,
             &[],
+            &[],
             Some([(fb, self.plane)].iter()),
             Some(*mode),
             Some(blob),
@@ -716,7 +942,9 @@ impl AtomicDrmSurface {
                 y: position.1,
                 w: size.0,
                 h: size.1,
+                fb: None,
             }],
+            &[],
             Some([(fb, self.plane), (fb, plane)].iter()),
             Some(pending.mode),
             Some(pending.blob),
@@ -740,6 +968,7 @@ impl AtomicDrmSurface {
         removed_connectors: &mut dyn Iterator<Item = &connector::Handle>,
         primary: plane::Handle,
         planes: &[PlaneInfo],
+        cleared_planes: &[plane::Handle],
         framebuffers: Option<impl Iterator<Item = &'a (framebuffer::Handle, plane::Handle)>>,
         mode: Option<Mode>,
         blob: Option<property::Value<'static>>,
@@ -908,6 +1137,27 @@ impl AtomicDrmSurface {
             if let Ok(prop) = plane_prop_handle(&prop_mapping, plane_info.handle, "rotation") {
                 req.add_property(plane_info.handle, prop, property::Value::Bitmask(1u64));
             }
+            if let Some(fb) = plane_info.fb {
+                req.add_property(
+                    plane_info.handle,
+                    plane_prop_handle(&prop_mapping, plane_info.handle, "FB_ID")?,
+                    property::Value::Framebuffer(Some(fb)),
+                );
+            }
+        }
+
+        // planes, that are not used anymore, need to be disconnected
+        for plane in cleared_planes {
+            req.add_property(
+                *plane,
+                plane_prop_handle(&prop_mapping, *plane, "CRTC_ID")?,
+                property::Value::CRTC(None),
+            );
+            req.add_property(
+                *plane,
+                plane_prop_handle(&prop_mapping, *plane, "FB_ID")?,
+                property::Value::Framebuffer(None),
+            );
         }
 
         Ok(req)
@@ -1041,6 +1291,32 @@ impl Drop for AtomicDrmSurface {
     }
 }
 
+// reads the raw value of a property of a drm object
+fn prop_value<A: ControlDevice, H: ResourceHandle>(
+    fd: &A,
+    handle: H,
+    prop: property::Handle,
+) -> Option<property::RawValue> {
+    let props = fd.get_properties(handle).ok()?;
+    let (ids, vals) = props.as_props_and_values();
+    ids.iter()
+        .zip(vals.iter())
+        .find(|(id, _)| **id == prop)
+        .map(|(_, val)| *val)
+}
+
+// translates the legacy page flip flags into their atomic counterparts
+fn commit_flags(flags: PageFlipFlags) -> AtomicCommitFlags {
+    let mut commit_flags = AtomicCommitFlags::empty();
+    if flags.contains(PageFlipFlags::EVENT) {
+        commit_flags |= AtomicCommitFlags::PAGE_FLIP_EVENT;
+    }
+    if flags.contains(PageFlipFlags::ASYNC) {
+        commit_flags |= AtomicCommitFlags::PAGE_FLIP_ASYNC;
+    }
+    commit_flags
+}
+
 pub(crate) fn conn_prop_handle(
     prop_mapping: &Mapping,
     handle: connector::Handle,
@@ -1,7 +1,7 @@
 use std::collections::HashSet;
 use std::sync::Arc;
 
-use drm::control::{connector, crtc, framebuffer, plane, Device, Mode};
+use drm::control::{connector, crtc, framebuffer, plane, Device, Mode, PageFlipFlags};
 use gbm::BufferObject;
 
 use crate::backend::allocator::dmabuf::{AsDmabuf, Dmabuf};
@@ -21,7 +21,8 @@ use slog::{debug, error, o, trace, warn};
 pub struct GbmBufferedSurface<A: Allocator<Buffer = BufferObject<()>> + 'static, U> {
     current_fb: Slot<BufferObject<()>>,
     pending_fb: Option<(Slot<BufferObject<()>>, U)>,
-    queued_fb: Option<(Slot<BufferObject<()>>, U)>,
+    /// The queued buffer, its user data and whether it should be flipped asynchronously
+    queued_fb: Option<(Slot<BufferObject<()>>, U, bool)>,
     next_fb: Option<Slot<BufferObject<()>>>,
     swapchain: Swapchain<A>,
     drm: Arc<DrmSurface>,
@@ -58,150 +59,18 @@ where
     where
         L: Into<Option<::slog::Logger>>,
     {
-        let mut error = None;
         let drm = Arc::new(drm);
-
         let log = crate::slog_or_fallback(log).new(o!("backend" => "drm_render"));
-        for format in SUPPORTED_FORMATS {
-            debug!(log, "Testing color format: {}", format);
-            match Self::new_internal(
-                drm.clone(),
-                allocator,
-                renderer_formats.clone(),
-                *format,
-                log.clone(),
-            ) {
-                Ok((current_fb, swapchain)) => {
-                    return Ok(GbmBufferedSurface {
-                        current_fb,
-                        pending_fb: None,
-                        queued_fb: None,
-                        next_fb: None,
-                        swapchain,
-                        drm,
-                    });
-                }
-                Err((alloc, err)) => {
-                    warn!(log, "Preferred format {} not available: {:?}", format, err);
-                    allocator = alloc;
-                    error = Some(err);
-                }
-            }
-        }
-        Err(error.unwrap())
-    }
-
-    #[allow(clippy::type_complexity)]
-    fn new_internal(
-        drm: Arc<DrmSurface>,
-        allocator: A,
-        mut renderer_formats: HashSet<Format>,
-        code: Fourcc,
-        logger: slog::Logger,
-    ) -> Result<(Slot<BufferObject<()>>, Swapchain<A>), (A, Error<A::Error>)> {
-        // select a format
-        let mut plane_formats = match drm.supported_formats(drm.plane()) {
-            Ok(formats) => formats.iter().cloned().collect::<HashSet<_>>(),
-            Err(err) => return Err((allocator, err.into())),
-        };
-
-        if !plane_formats.iter().any(|fmt| fmt.code == code) {
-            return Err((allocator, Error::NoSupportedPlaneFormat));
-        }
-        plane_formats.retain(|fmt| fmt.code == code);
-        renderer_formats.retain(|fmt| fmt.code == code);
-
-        trace!(logger, "Plane formats: {:?}", plane_formats);
-        trace!(logger, "Renderer formats: {:?}", renderer_formats);
-        debug!(
-            logger,
-            "Remaining intersected formats: {:?}",
-            plane_formats
-                .intersection(&renderer_formats)
-                .collect::<HashSet<_>>()
-        );
-
-        if plane_formats.is_empty() {
-            return Err((allocator, Error::NoSupportedPlaneFormat));
-        } else if renderer_formats.is_empty() {
-            return Err((allocator, Error::NoSupportedRendererFormat));
-        }
-
-        let formats = {
-            // Special case: if a format supports explicit LINEAR (but no implicit Modifiers)
-            // and the other doesn't support any modifier, force Implicit.
-            // This should at least result in a working pipeline possibly with a linear buffer,
-            // but we cannot be sure.
-            if (plane_formats.len() == 1
-                && plane_formats.iter().next().unwrap().modifier == Modifier::Invalid
-                && renderer_formats.iter().all(|x| x.modifier != Modifier::Invalid)
-                && renderer_formats.iter().any(|x| x.modifier == Modifier::Linear))
-                || (renderer_formats.len() == 1
-                    && renderer_formats.iter().next().unwrap().modifier == Modifier::Invalid
-                    && plane_formats.iter().all(|x| x.modifier != Modifier::Invalid)
-                    && plane_formats.iter().any(|x| x.modifier == Modifier::Linear))
-            {
-                vec![Format {
-                    code,
-                    modifier: Modifier::Invalid,
-                }]
-            } else {
-                plane_formats
-                    .intersection(&renderer_formats)
-                    .cloned()
-                    .collect::<Vec<_>>()
-            }
-        };
-        debug!(logger, "Testing Formats: {:?}", formats);
-
-        let modifiers = formats.iter().map(|x| x.modifier).collect::<Vec<_>>();
-        let mode = drm.pending_mode();
-
-        let mut swapchain: Swapchain<A> = Swapchain::new(
-            allocator,
-            mode.size().0 as u32,
-            mode.size().1 as u32,
-            code,
-            modifiers,
-        );
-
-        // Test format
-        let buffer = match swapchain.acquire() {
-            Ok(buffer) => buffer.unwrap(),
-            Err(err) => return Err((swapchain.allocator, Error::GbmError(err))),
-        };
-        let format = Format {
-            code,
-            modifier: buffer.modifier().unwrap(), // no guarantee
-                                                  // that this is stable across allocations, but
-                                                  // we want to print that here for debugging proposes.
-                                                  // It has no further use.
-        };
-
-        let fb = match attach_framebuffer(&drm, &buffer) {
-            Ok(fb) => fb,
-            Err(err) => return Err((swapchain.allocator, err)),
-        };
-        match buffer.export() {
-            Ok(dmabuf) => dmabuf,
-            Err(err) => return Err((swapchain.allocator, err.into())),
-        };
-        let handle = fb.fb;
-        buffer.userdata().insert_if_missing(|| fb);
 
-        match drm.test_buffer(handle, &mode, true) {
-            Ok(_) => {
-                debug!(logger, "Choosen format: {:?}", format);
-                Ok((buffer, swapchain))
-            }
-            Err(err) => {
-                warn!(
-                    logger,
-                    "Mode-setting failed with automatically selected buffer format {:?}: {}", format, err
-                );
-                Err((swapchain.allocator, err.into()))
-            }
-        }
+        let (current_fb, swapchain) = create_swapchain(&drm, allocator, renderer_formats, &log)?;
+        Ok(GbmBufferedSurface {
+            current_fb,
+            pending_fb: None,
+            queued_fb: None,
+            next_fb: None,
+            swapchain,
+            drm,
+        })
     }
 
     /// Retrieves the next buffer to be rendered into and it's age.
@@ -236,10 +105,14 @@ where
     /// Otherwise the underlying swapchain will eventually run out of buffers.
     ///
     /// `user_data` can be used to attach some data to a specific buffer and later retrieved with [`GbmBufferedSurface::frame_submitted`]
-    pub fn queue_buffer(&mut self, user_data: U) -> Result<(), Error<A::Error>> {
+    ///
+    /// If `async_flip` is set, the buffer is presented without waiting for the next vblank, which may cause tearing.
+    /// This falls back to a vblank synchronized flip, if the device does not support asynchronous page flips
+    /// (see [`DrmSurface::async_page_flip_supported`]), rejects the flip or the pending state requires a modeset.
+    pub fn queue_buffer(&mut self, user_data: U, async_flip: bool) -> Result<(), Error<A::Error>> {
         self.queued_fb = self.next_fb.take().map(|fb| {
             self.swapchain.submitted(&fb);
-            (fb, user_data)
+            (fb, user_data, async_flip)
         });
         if self.pending_fb.is_none() && self.queued_fb.is_some() {
             self.submit()?;
@@ -269,13 +142,18 @@ where
 
     fn submit(&mut self) -> Result<(), Error<A::Error>> {
         // yes it does not look like it, but both of these lines should be safe in all cases.
-        let (slot, user_data) = self.queued_fb.take().unwrap();
+        let (slot, user_data, async_flip) = self.queued_fb.take().unwrap();
         let fb = slot.userdata().get::<FbHandle>().unwrap().fb;
+        let fbs = [(fb, self.drm.plane())];
 
         let flip = if self.drm.commit_pending() {
-            self.drm.commit([(fb, self.drm.plane())].iter(), true)
+            self.drm.commit(fbs.iter(), PageFlipFlags::EVENT)
+        } else if async_flip && self.drm.async_page_flip_supported() {
+            self.drm
+                .page_flip(fbs.iter(), PageFlipFlags::EVENT | PageFlipFlags::ASYNC)
+                .or_else(|_| self.drm.page_flip(fbs.iter(), PageFlipFlags::EVENT))
         } else {
-            self.drm.page_flip([(fb, self.drm.plane())].iter(), true)
+            self.drm.page_flip(fbs.iter(), PageFlipFlags::EVENT)
         };
         if flip.is_ok() {
             self.pending_fb = Some((slot, user_data));
@@ -366,16 +244,186 @@ where
         Ok(())
     }
 
+    /// Returns true, if the given [`connector`](drm::control::connector) supports
+    /// variable refresh rate on this surface.
+    pub fn vrr_supported(&self, conn: connector::Handle) -> Result<bool, Error<A::Error>> {
+        self.drm.vrr_supported(conn).map_err(Error::DrmError)
+    }
+
+    /// Tries to enable or disable variable refresh rate
+    /// to be used after the next commit.
+    pub fn use_vrr(&self, vrr: bool) -> Result<(), Error<A::Error>> {
+        self.drm.use_vrr(vrr).map_err(Error::DrmError)
+    }
+
+    /// Returns true, if variable refresh rate is currently enabled
+    pub fn vrr_enabled(&self) -> bool {
+        self.drm.vrr_enabled()
+    }
+
     /// Returns a reference to the underlying drm surface
     pub fn surface(&self) -> &DrmSurface {
         &self.drm
     }
 }
 
+/// Creates a swapchain for the given surface, selecting a format supported by the
+/// primary plane and the renderer.
+///
+/// Returns the swapchain together with the buffer used for testing the format.
+#[allow(clippy::type_complexity)]
+pub(crate) fn create_swapchain<A>(
+    drm: &Arc<DrmSurface>,
+    mut allocator: A,
+    renderer_formats: HashSet<Format>,
+    log: &slog::Logger,
+) -> Result<(Slot<BufferObject<()>>, Swapchain<A>), Error<A::Error>>
+where
+    A: Allocator<Buffer = BufferObject<()>>,
+    A::Error: std::error::Error + Send + Sync,
+{
+    let mut error = None;
+    for format in SUPPORTED_FORMATS {
+        debug!(log, "Testing color format: {}", format);
+        match create_swapchain_with_format(
+            drm.clone(),
+            allocator,
+            renderer_formats.clone(),
+            *format,
+            log.clone(),
+        ) {
+            Ok((current_fb, swapchain)) => return Ok((current_fb, swapchain)),
+            Err((alloc, err)) => {
+                warn!(log, "Preferred format {} not available: {:?}", format, err);
+                allocator = alloc;
+                error = Some(err);
+            }
+        }
+    }
+    Err(error.unwrap())
+}
+
+#[allow(clippy::type_complexity)]
+fn create_swapchain_with_format<A>(
+    drm: Arc<DrmSurface>,
+    allocator: A,
+    mut renderer_formats: HashSet<Format>,
+    code: Fourcc,
+    logger: slog::Logger,
+) -> Result<(Slot<BufferObject<()>>, Swapchain<A>), (A, Error<A::Error>)>
+where
+    A: Allocator<Buffer = BufferObject<()>>,
+    A::Error: std::error::Error + Send + Sync,
+{
+    // select a format
+    let mut plane_formats = match drm.supported_formats(drm.plane()) {
+        Ok(formats) => formats.iter().cloned().collect::<HashSet<_>>(),
+        Err(err) => return Err((allocator, err.into())),
+    };
+
+    if !plane_formats.iter().any(|fmt| fmt.code == code) {
+        return Err((allocator, Error::NoSupportedPlaneFormat));
+    }
+    plane_formats.retain(|fmt| fmt.code == code);
+    renderer_formats.retain(|fmt| fmt.code == code);
+
+    trace!(logger, "Plane formats: {:?}", plane_formats);
+    trace!(logger, "Renderer formats: {:?}", renderer_formats);
+    debug!(
+        logger,
+        "Remaining intersected formats: {:?}",
+        plane_formats
+            .intersection(&renderer_formats)
+            .collect::<HashSet<_>>()
+    );
+
+    if plane_formats.is_empty() {
+        return Err((allocator, Error::NoSupportedPlaneFormat));
+    } else if renderer_formats.is_empty() {
+        return Err((allocator, Error::NoSupportedRendererFormat));
+    }
+
+    let formats = {
+        // Special case: if a format supports explicit LINEAR (but no implicit Modifiers)
+        // and the other doesn't support any modifier, force Implicit.
+        // This should at least result in a working pipeline possibly with a linear buffer,
+        // but we cannot be sure.
+        if (plane_formats.len() == 1
+            && plane_formats.iter().next().unwrap().modifier == Modifier::Invalid
+            && renderer_formats.iter().all(|x| x.modifier != Modifier::Invalid)
+            && renderer_formats.iter().any(|x| x.modifier == Modifier::Linear))
+            || (renderer_formats.len() == 1
+                && renderer_formats.iter().next().unwrap().modifier == Modifier::Invalid
+                && plane_formats.iter().all(|x| x.modifier != Modifier::Invalid)
+                && plane_formats.iter().any(|x| x.modifier == Modifier::Linear))
+        {
+            vec![Format {
+                code,
+                modifier: Modifier::Invalid,
+            }]
+        } else {
+            plane_formats
+                .intersection(&renderer_formats)
+                .cloned()
+                .collect::<Vec<_>>()
+        }
+    };
+    debug!(logger, "Testing Formats: {:?}", formats);
+
+    let modifiers = formats.iter().map(|x| x.modifier).collect::<Vec<_>>();
+    let mode = drm.pending_mode();
+
+    let mut swapchain: Swapchain<A> = Swapchain::new(
+        allocator,
+        mode.size().0 as u32,
+        mode.size().1 as u32,
+        code,
+        modifiers,
+    );
+
+    // Test format
+    let buffer = match swapchain.acquire() {
+        Ok(buffer) => buffer.unwrap(),
+        Err(err) => return Err((swapchain.allocator, Error::GbmError(err))),
+    };
+    let format = Format {
+        code,
+        modifier: buffer.modifier().unwrap(), // no guarantee
+                                              // that this is stable across allocations, but
+                                              // we want to print that here for debugging proposes.
+                                              // It has no further use.
+    };
+
+    let fb = match attach_framebuffer(&drm, &buffer) {
+        Ok(fb) => fb,
+        Err(err) => return Err((swapchain.allocator, err)),
+    };
+    match buffer.export() {
+        Ok(dmabuf) => dmabuf,
+        Err(err) => return Err((swapchain.allocator, err.into())),
+    };
+    let handle = fb.fb;
+    buffer.userdata().insert_if_missing(|| fb);
+
+    match drm.test_buffer(handle, &mode, true) {
+        Ok(_) => {
+            debug!(logger, "Choosen format: {:?}", format);
+            Ok((buffer, swapchain))
+        }
+        Err(err) => {
+            warn!(
+                logger,
+                "Mode-setting failed with automatically selected buffer format {:?}: {}", format, err
+            );
+            Err((swapchain.allocator, err.into()))
+        }
+    }
+}
+
 #[derive(Debug)]
-struct FbHandle {
+pub(crate) struct FbHandle {
     drm: Arc<DrmSurface>,
-    fb: framebuffer::Handle,
+    pub(crate) fb: framebuffer::Handle,
 }
 
 impl Drop for FbHandle {
@@ -384,7 +432,10 @@ impl Drop for FbHandle {
     }
 }
 
-fn attach_framebuffer<E>(drm: &Arc<DrmSurface>, bo: &BufferObject<()>) -> Result<FbHandle, Error<E>>
+pub(crate) fn attach_framebuffer<E>(
+    drm: &Arc<DrmSurface>,
+    bo: &BufferObject<()>,
+) -> Result<FbHandle, Error<E>>
 where
     E: std::error::Error + Send + Sync,
 {
@@ -209,7 +209,7 @@ impl LegacyDrmSurface {
         *self.pending.read().unwrap() != *self.state.read().unwrap()
     }
 
-    pub fn commit(&self, framebuffer: framebuffer::Handle, event: bool) -> Result<(), Error> {
+    pub fn commit(&self, framebuffer: framebuffer::Handle, flags: PageFlipFlags) -> Result<(), Error> {
         if !self.active.load(Ordering::SeqCst) {
             return Err(Error::DeviceInactive);
         }
@@ -281,46 +281,38 @@ impl LegacyDrmSurface {
 
         *current = pending.clone();
 
-        if event {
+        if flags.contains(PageFlipFlags::EVENT) {
             // set crtc does not trigger page_flip events, so we immediately queue a flip
             // with the same framebuffer.
             // this will result in wasting a frame, because this flip will need to wait
             // for `set_crtc`, but is necessary to drive the event loop and thus provide
             // a more consistent api.
-            ControlDevice::page_flip(&*self.fd, self.crtc, framebuffer, PageFlipFlags::EVENT, None).map_err(
-                |source| Error::Access {
+            // an async flip does not have to wait, so we do not waste a frame in that case.
+            ControlDevice::page_flip(&*self.fd, self.crtc, framebuffer, flags, None).map_err(|source| {
+                Error::Access {
                     errmsg: "Failed to queue page flip",
                     dev: self.fd.dev_path(),
                     source,
-                },
-            )?;
+                }
+            })?;
         }
 
         Ok(())
     }
 
-    pub fn page_flip(&self, framebuffer: framebuffer::Handle, event: bool) -> Result<(), Error> {
-        trace!(self.logger, "Queueing Page flip");
+    pub fn page_flip(&self, framebuffer: framebuffer::Handle, flags: PageFlipFlags) -> Result<(), Error> {
+        trace!(self.logger, "Queueing Page flip: {:?}", flags);
 
         if !self.active.load(Ordering::SeqCst) {
             return Err(Error::DeviceInactive);
         }
 
-        ControlDevice::page_flip(
-            &*self.fd,
-            self.crtc,
-            framebuffer,
-            if event {
-                PageFlipFlags::EVENT
-            } else {
-                PageFlipFlags::empty()
-            },
-            None,
-        )
-        .map_err(|source| Error::Access {
-            errmsg: "Failed to page flip",
-            dev: self.fd.dev_path(),
-            source,
+        ControlDevice::page_flip(&*self.fd, self.crtc, framebuffer, flags, None).map_err(|source| {
+            Error::Access {
+                errmsg: "Failed to page flip",
+                dev: self.fd.dev_path(),
+                source,
+            }
         })
     }
 
@@ -1,9 +1,11 @@
 use std::collections::HashSet;
 use std::convert::TryFrom;
-use std::os::unix::io::{AsFd, BorrowedFd};
+use std::os::unix::io::{AsFd, BorrowedFd, OwnedFd};
 use std::sync::Arc;
 
-use drm::control::{connector, crtc, framebuffer, plane, property, Device as ControlDevice, Mode};
+use drm::control::{
+    connector, crtc, framebuffer, plane, property, Device as ControlDevice, Mode, PageFlipFlags,
+};
 use drm::{Device as BasicDevice, DriverCapability};
 
 use nix::libc::dev_t;
@@ -22,6 +24,21 @@ use legacy::LegacyDrmSurface;
 
 use slog::trace;
 
+/// Configuration of a cursor or overlay plane
+///
+/// See [`DrmSurface::set_planes`] and [`DrmSurface::test_state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaneConfig {
+    /// The plane to be used
+    pub handle: plane::Handle,
+    /// The framebuffer to be displayed on the plane
+    pub fb: framebuffer::Handle,
+    /// The position of the plane on the crtc
+    pub position: (i32, i32),
+    /// The size of the plane, needs to match the size of the framebuffer
+    pub size: (u32, u32),
+}
+
 /// An open crtc + plane combination that can be used for scan-out
 #[derive(Debug)]
 pub struct DrmSurface {
@@ -152,6 +169,38 @@ impl DrmSurface {
         }
     }
 
+    /// Returns true, if the given [`connector`](drm::control::connector) supports
+    /// variable refresh rate (also known as adaptive sync, FreeSync or G-Sync) on this surface.
+    ///
+    /// Always returns false for legacy devices.
+    pub fn vrr_supported(&self, conn: connector::Handle) -> Result<bool, Error> {
+        match &*self.internal {
+            DrmSurfaceInternal::Atomic(surf) => surf.vrr_supported(conn),
+            DrmSurfaceInternal::Legacy(_) => Ok(false),
+        }
+    }
+
+    /// Tries to enable or disable variable refresh rate
+    /// to be used after the next commit.
+    ///
+    /// Fails if the underlying [`crtc`](drm::control::crtc) does not support variable refresh rate
+    /// with the pending [`connector`](drm::control::connector)s, see [`DrmSurface::vrr_supported`].
+    pub fn use_vrr(&self, vrr: bool) -> Result<(), Error> {
+        match &*self.internal {
+            DrmSurfaceInternal::Atomic(surf) => surf.use_vrr(vrr),
+            DrmSurfaceInternal::Legacy(_) if vrr => Err(Error::VrrUnsupported),
+            DrmSurfaceInternal::Legacy(_) => Ok(()),
+        }
+    }
+
+    /// Returns true, if variable refresh rate is currently enabled
+    pub fn vrr_enabled(&self) -> bool {
+        match &*self.internal {
+            DrmSurfaceInternal::Atomic(surf) => surf.vrr_enabled(),
+            DrmSurfaceInternal::Legacy(_) => false,
+        }
+    }
+
     /// Tries to setup a cursor or overlay [`Plane`](drm::control::plane)
     /// to be set at the next commit/page_flip with the given position and size.
     ///
@@ -186,12 +235,32 @@ impl DrmSurface {
         }
     }
 
+    /// Replaces the set of cursor and overlay [`Plane`](drm::control::plane)s
+    /// to be used at the next commit/page_flip.
+    ///
+    /// In contrast to [`use_plane`](DrmSurface::use_plane) the planes are not tested and will
+    /// display the framebuffers of the provided configurations. Planes previously used,
+    /// that are not part of the given set, will be disabled with the next commit/page_flip.
+    /// Use [`test_state`](DrmSurface::test_state) to verify a configuration beforehand.
+    ///
+    /// Errors if the underlying implementation does not support the use of planes.
+    pub fn set_planes(&self, planes: &[PlaneConfig]) -> Result<(), Error> {
+        match &*self.internal {
+            DrmSurfaceInternal::Atomic(surf) => surf.set_planes(planes),
+            DrmSurfaceInternal::Legacy(_) => match planes.first() {
+                Some(config) => Err(Error::NonPrimaryPlane(config.handle)),
+                None => Ok(()),
+            },
+        }
+    }
+
     /// Returns true whenever any state changes are pending to be commited
     ///
     /// The following functions may trigger a pending commit:
     /// - [`add_connector`](DrmSurface::add_connector)
     /// - [`remove_connector`](DrmSurface::remove_connector)
     /// - [`use_mode`](DrmSurface::use_mode)
+    /// - [`use_vrr`](DrmSurface::use_vrr)
     pub fn commit_pending(&self) -> bool {
         match &*self.internal {
             DrmSurfaceInternal::Atomic(surf) => surf.commit_pending(),
@@ -206,22 +275,32 @@ impl DrmSurface {
     /// operation if a commit really is necessary using [`commit_pending`](DrmSurface::commit_pending).
     ///
     /// This operation is not necessarily blocking until the crtc is in the desired state,
-    /// but will trigger a `vblank` event once done.
+    /// but will trigger a `vblank` event once done, if [`PageFlipFlags::EVENT`] is set.
     /// Make sure to have the device registered in your event loop prior to invoking this, to not miss
     /// any generated event.
+    ///
+    /// [`PageFlipFlags::ASYNC`] requests the new framebuffer to be presented immediately
+    /// instead of waiting for the next vblank, which may cause tearing.
+    /// Drivers usually reject asynchronous flips for atomic commits requiring a modeset.
+    /// Fails with [`Error::AsyncPageFlipUnsupported`], if the device does not support asynchronous
+    /// flips at all (see [`async_page_flip_supported`](DrmSurface::async_page_flip_supported)).
     pub fn commit<'a>(
         &self,
         mut framebuffers: impl Iterator<Item = &'a (framebuffer::Handle, plane::Handle)>,
-        event: bool,
+        flags: PageFlipFlags,
     ) -> Result<(), Error> {
+        if flags.contains(PageFlipFlags::ASYNC) && !self.async_page_flip_supported() {
+            return Err(Error::AsyncPageFlipUnsupported);
+        }
+
         match &*self.internal {
-            DrmSurfaceInternal::Atomic(surf) => surf.commit(framebuffers, event),
+            DrmSurfaceInternal::Atomic(surf) => surf.commit(framebuffers, flags),
             DrmSurfaceInternal::Legacy(surf) => {
                 if let Some((fb, plane)) = framebuffers.next() {
                     if plane_type(self, *plane)? != PlaneType::Primary {
                         return Err(Error::NonPrimaryPlane(*plane));
                     }
-                    surf.commit(*fb, event)
+                    surf.commit(*fb, flags)
                 } else {
                     Ok(())
                 }
@@ -234,21 +313,33 @@ impl DrmSurface {
     ///
     /// This will not cause the crtc to modeset.
     ///
-    /// This operation is not blocking and will produce a `vblank` event once swapping is done.
+    /// This operation is not blocking and will produce a `vblank` event once swapping is done,
+    /// if [`PageFlipFlags::EVENT`] is set.
     /// Make sure to have the device registered in your event loop to not miss the event.
+    ///
+    /// [`PageFlipFlags::ASYNC`] requests the new framebuffer to be presented immediately
+    /// instead of waiting for the next vblank, which may cause tearing.
+    /// Fails with [`Error::AsyncPageFlipUnsupported`], if the device does not support asynchronous
+    /// flips (see [`async_page_flip_supported`](DrmSurface::async_page_flip_supported)).
+    /// Drivers may still reject asynchronous flips of some configurations, e.g. on atomic devices
+    /// if more than the framebuffer of the primary plane changes.
     pub fn page_flip<'a>(
         &self,
         mut framebuffers: impl Iterator<Item = &'a (framebuffer::Handle, plane::Handle)>,
-        event: bool,
+        flags: PageFlipFlags,
     ) -> Result<(), Error> {
+        if flags.contains(PageFlipFlags::ASYNC) && !self.async_page_flip_supported() {
+            return Err(Error::AsyncPageFlipUnsupported);
+        }
+
         match &*self.internal {
-            DrmSurfaceInternal::Atomic(surf) => surf.page_flip(framebuffers, event),
+            DrmSurfaceInternal::Atomic(surf) => surf.page_flip(framebuffers, flags),
             DrmSurfaceInternal::Legacy(surf) => {
                 if let Some((fb, plane)) = framebuffers.next() {
                     if plane_type(self, *plane)? != PlaneType::Primary {
                         return Err(Error::NonPrimaryPlane(*plane));
                     }
-                    surf.page_flip(*fb, event)
+                    surf.page_flip(*fb, flags)
                 } else {
                     Ok(())
                 }
@@ -256,6 +347,58 @@ impl DrmSurface {
         }
     }
 
+    /// Returns true, if the device supports asynchronous page flips via [`PageFlipFlags::ASYNC`]
+    ///
+    /// This queries the `DRM_CAP_ASYNC_PAGE_FLIP` capability of the device.
+    pub fn async_page_flip_supported(&self) -> bool {
+        matches!(self.get_driver_capability(DriverCapability::ASyncPageFlip), Ok(1))
+    }
+
+    /// Set a fence to be waited on before scanning out the framebuffer of a given plane.
+    ///
+    /// The fence is consumed by the next [`commit`](DrmSurface::commit) or
+    /// [`page_flip`](DrmSurface::page_flip), allowing to commit a framebuffer before rendering into it
+    /// has finished.
+    ///
+    /// Fails with [`Error::ExplicitSyncUnsupported`] on legacy devices.
+    pub fn set_in_fence(&self, plane: plane::Handle, fence: OwnedFd) -> Result<(), Error> {
+        match &*self.internal {
+            DrmSurfaceInternal::Atomic(surf) => {
+                surf.set_in_fence(plane, fence);
+                Ok(())
+            }
+            DrmSurfaceInternal::Legacy(_) => Err(Error::ExplicitSyncUnsupported),
+        }
+    }
+
+    /// Request an out-fence for the next [`commit`](DrmSurface::commit) or
+    /// [`page_flip`](DrmSurface::page_flip).
+    ///
+    /// Once the commit succeeded, the fence can be retrieved with [`take_out_fence`](DrmSurface::take_out_fence).
+    ///
+    /// Fails with [`Error::ExplicitSyncUnsupported`] on legacy devices.
+    pub fn request_out_fence(&self) -> Result<(), Error> {
+        match &*self.internal {
+            DrmSurfaceInternal::Atomic(surf) => {
+                surf.request_out_fence();
+                Ok(())
+            }
+            DrmSurfaceInternal::Legacy(_) => Err(Error::ExplicitSyncUnsupported),
+        }
+    }
+
+    /// Take the out-fence of the last [`commit`](DrmSurface::commit) or
+    /// [`page_flip`](DrmSurface::page_flip), if one was requested.
+    ///
+    /// The fence is signalled once the new framebuffers are being scanned out,
+    /// at which point the previous framebuffers are not in use anymore.
+    pub fn take_out_fence(&self) -> Option<OwnedFd> {
+        match &*self.internal {
+            DrmSurfaceInternal::Atomic(surf) => surf.take_out_fence(),
+            DrmSurfaceInternal::Legacy(_) => None,
+        }
+    }
+
     /// Returns a set of supported pixel formats for attached buffers
     pub fn supported_formats(&self, plane: plane::Handle) -> Result<HashSet<Format>, Error> {
         // get plane formats
@@ -440,6 +583,24 @@ impl DrmSurface {
         }
     }
 
+    /// Tests if a complete plane configuration can be used with this surface.
+    ///
+    /// # Arguments
+    ///
+    /// - `fb` - Framebuffer handle to be used on the primary plane
+    /// - `planes` - The cursor and overlay planes to be used alongside the primary plane.
+    ///     Planes currently in use, that are not part of `planes`, are tested as disabled.
+    ///
+    /// The test uses the pending mode and connectors of the surface.
+    /// If the test cannot be performed, this function returns false.
+    /// This is always the case for non-atomic surfaces.
+    pub fn test_state(&self, fb: framebuffer::Handle, planes: &[PlaneConfig]) -> Result<bool, Error> {
+        match &*self.internal {
+            DrmSurfaceInternal::Atomic(surf) => surf.test_state(fb, planes),
+            DrmSurfaceInternal::Legacy(_) => Ok(false), // There is no test-commiting with the legacy interface
+        }
+    }
+
     /// Re-evaluates the current state of the crtc.
     ///
     /// It is recommended to call this function after this used [`Session`]
@@ -0,0 +1,555 @@
+//! Headless backend
+//!
+//! This module provides a backend without any physical display or input devices attached.
+//! It creates virtual [`Output`]s, which are driven by a timer instead of actual vblanks,
+//! and can be rendered to using any renderer implementing [`Offscreen`].
+//!
+//! This is useful to run the complete compositor pipeline (including damage tracking and
+//! presentation feedback) in integration tests or for remote-desktop use cases, where no
+//! drm device, X11 server or wayland compositor is available.
+//!
+//! ## Usage
+//!
+//! The [`HeadlessBackend`] is a calloop [`EventSource`], that needs to be inserted into your
+//! event loop. Use it to create [`HeadlessOutput`]s, which manage the buffers of a single
+//! virtual output in a similar way to a [`GbmBufferedSurface`](crate::backend::drm::GbmBufferedSurface):
+//!
+//! - Call [`HeadlessOutput::next_buffer`] to get a buffer to render into, alongside its age.
+//! - Once rendering is done, submit the buffer with [`HeadlessOutput::queue_buffer`].
+//! - The [`HeadlessBackend`] will emit a [`HeadlessEvent::VBlank`] for the output at the
+//!   next (virtual) vblank. Call [`HeadlessOutput::frame_submitted`] at that point, to
+//!   retrieve the user data passed to `queue_buffer` (e.g. presentation feedback) and
+//!   to get the buffer displayed.
+//!
+//! The buffer currently "displayed" on an output can be accessed through
+//! [`HeadlessOutput::current_buffer`], e.g. to stream or inspect its contents.
+//!
+//! ```no_run
+//! # use smithay::backend::renderer::software::{SoftwareRenderer, SoftwareTexture};
+//! use smithay::backend::headless::{HeadlessBackend, HeadlessEvent};
+//! use smithay::output::{Mode, Scale};
+//! use smithay::utils::Transform;
+//!
+//! let mut event_loop = calloop::EventLoop::<()>::try_new().unwrap();
+//! let mut backend = HeadlessBackend::new(None).unwrap();
+//! let mut output = backend.create_output::<SoftwareTexture, ()>(
+//!     "HEADLESS-1".into(),
+//!     Mode { size: (1920, 1080).into(), refresh: 60_000 },
+//!     Transform::Normal,
+//!     Scale::Integer(1),
+//! );
+//! // make sure to map the output into your space and advertise its global to clients
+//!
+//! event_loop
+//!     .handle()
+//!     .insert_source(backend, |event, metadata, _| match event {
+//!         HeadlessEvent::VBlank(output) => {
+//!             // call `frame_submitted` on the matching `HeadlessOutput`
+//!             // and send out presentation feedback using `metadata`
+//!         }
+//!     })
+//!     .unwrap();
+//!
+//! let mut renderer = SoftwareRenderer::new(None);
+//! let (buffer, age) = output.next_buffer(&mut renderer).unwrap();
+//! // bind the buffer and render your contents
+//! output.queue_buffer(());
+//! ```
+
+use std::{
+    cell::RefCell,
+    io,
+    rc::{Rc, Weak},
+    time::{Duration, Instant},
+};
+
+use calloop::{
+    ping::{make_ping, Ping, PingSource},
+    timer::{TimeoutAction, Timer},
+    EventSource, Poll, PostAction, Readiness, Token, TokenFactory,
+};
+use slog::{debug, o, trace};
+
+use crate::{
+    backend::renderer::Offscreen,
+    output::{Mode, Output, PhysicalProperties, Scale, Subpixel},
+    utils::{Clock, Monotonic, Time, Transform},
+};
+
+/// Amount of buffers a [`HeadlessOutput`] may use at a time
+///
+/// One buffer each for the currently displayed, the pending, the queued
+/// and the next frame.
+const BUFFER_COUNT: usize = 4;
+
+/// Refresh rate used for modes not specifying any, in millihertz
+const FALLBACK_REFRESH_RATE: u32 = 60_000;
+
+/// Interval after which the timer is checked, while no output waits for a vblank
+const IDLE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Events generated by the [`HeadlessBackend`]
+#[derive(Debug)]
+pub enum HeadlessEvent {
+    /// A virtual vblank for the provided output has happened
+    ///
+    /// This is only emitted for outputs that had a buffer queued for display.
+    /// You should call [`HeadlessOutput::frame_submitted`] on the matching output.
+    VBlank(Output),
+}
+
+/// Timing metadata for virtual vblank events
+#[derive(Debug)]
+pub struct EventMetadata {
+    /// The time the vblank happened
+    pub time: Time<Monotonic>,
+    /// The sequence number of the frame
+    pub sequence: u32,
+    /// The refresh interval of the output
+    pub refresh: Duration,
+}
+
+#[derive(Debug)]
+struct VBlankState {
+    output: Output,
+    refresh: Duration,
+    next_vblank: Instant,
+    sequence: u32,
+    pending: bool,
+}
+
+impl VBlankState {
+    /// Advances the virtual vblank timing up to `now`.
+    ///
+    /// Returns `true` if at least one vblank has passed since the last call.
+    fn advance(&mut self, now: Instant) -> bool {
+        if self.next_vblank > now {
+            return false;
+        }
+
+        let refresh = self.refresh.as_nanos().max(1);
+        let missed = (now - self.next_vblank).as_nanos() / refresh + 1;
+        self.next_vblank += Duration::from_nanos((missed * refresh) as u64);
+        self.sequence = self.sequence.wrapping_add(missed as u32);
+        true
+    }
+}
+
+fn refresh_interval(mode: &Mode) -> Duration {
+    let refresh = if mode.refresh > 0 {
+        mode.refresh as u32
+    } else {
+        FALLBACK_REFRESH_RATE
+    };
+    Duration::from_nanos(1_000_000_000_000 / refresh as u64)
+}
+
+/// Backend providing virtual outputs
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct HeadlessBackend {
+    outputs: Vec<Weak<RefCell<VBlankState>>>,
+    timer: Timer,
+    ping: Ping,
+    ping_source: PingSource,
+    clock: Clock<Monotonic>,
+    logger: ::slog::Logger,
+}
+
+impl HeadlessBackend {
+    /// Create a new headless backend
+    pub fn new<L>(logger: L) -> io::Result<HeadlessBackend>
+    where
+        L: Into<Option<::slog::Logger>>,
+    {
+        let logger = crate::slog_or_fallback(logger).new(o!("smithay_module" => "backend_headless"));
+        let (ping, ping_source) = make_ping()?;
+        Ok(HeadlessBackend {
+            outputs: Vec::new(),
+            timer: Timer::from_duration(IDLE_INTERVAL),
+            ping,
+            ping_source,
+            clock: Clock::new()?,
+            logger,
+        })
+    }
+
+    /// Create a new virtual output
+    ///
+    /// The returned [`Output`] (accessible through [`HeadlessOutput::output`]) uses the given
+    /// `mode` as its preferred and current mode, as well as the given `transform` and `scale`.
+    /// The output will not be advertised to clients, you need to create a global for it
+    /// yourself, if desired.
+    ///
+    /// The vblanks of the output are driven by this backend, as long as it is registered
+    /// in an event loop.
+    pub fn create_output<T, U>(
+        &mut self,
+        name: String,
+        mode: Mode,
+        transform: Transform,
+        scale: Scale,
+    ) -> HeadlessOutput<T, U> {
+        let output = Output::new(
+            name,
+            PhysicalProperties {
+                size: (0, 0).into(),
+                subpixel: Subpixel::Unknown,
+                make: "Smithay".into(),
+                model: "Virtual Output".into(),
+            },
+            self.logger.clone(),
+        );
+        output.set_preferred(mode);
+        output.change_current_state(Some(mode), Some(transform), Some(scale), None);
+        debug!(
+            self.logger,
+            "Created virtual output {}: {:?}",
+            output.name(),
+            mode
+        );
+
+        let refresh = refresh_interval(&mode);
+        let state = Rc::new(RefCell::new(VBlankState {
+            output: output.clone(),
+            refresh,
+            next_vblank: Instant::now() + refresh,
+            sequence: 0,
+            pending: false,
+        }));
+        self.outputs.push(Rc::downgrade(&state));
+
+        HeadlessOutput {
+            output,
+            state,
+            ping: self.ping.clone(),
+            slots: Default::default(),
+            current: None,
+            next: None,
+            pending: None,
+            queued: None,
+        }
+    }
+
+    /// Returns the time the next timer deadline has to happen at, if any output waits for a vblank
+    fn next_deadline(&mut self, now: Instant) -> Option<Instant> {
+        self.outputs
+            .iter()
+            .filter_map(|state| state.upgrade())
+            .filter_map(|state| {
+                let mut state = state.borrow_mut();
+                if state.pending {
+                    state.advance(now);
+                    Some(state.next_vblank)
+                } else {
+                    None
+                }
+            })
+            .min()
+    }
+}
+
+impl EventSource for HeadlessBackend {
+    type Event = HeadlessEvent;
+    type Metadata = EventMetadata;
+    type Ret = ();
+    type Error = io::Error;
+
+    fn process_events<F>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        mut callback: F,
+    ) -> Result<PostAction, Self::Error>
+    where
+        F: FnMut(Self::Event, &mut Self::Metadata) -> Self::Ret,
+    {
+        self.outputs.retain(|state| state.strong_count() > 0);
+
+        let mut vblanks = Vec::new();
+        let outputs = &self.outputs;
+        let log = &self.logger;
+        self.timer.process_events(readiness, token, |_, _| {
+            let now = Instant::now();
+            let mut next_deadline = None;
+            for state in outputs.iter().filter_map(|state| state.upgrade()) {
+                let mut state = state.borrow_mut();
+                if state.advance(now) && state.pending {
+                    trace!(log, "Virtual vblank on output {}", state.output.name());
+                    state.pending = false;
+                    vblanks.push((state.output.clone(), state.sequence, state.refresh));
+                } else if state.pending {
+                    let deadline = state.next_vblank;
+                    next_deadline = Some(next_deadline.map_or(deadline, |next: Instant| next.min(deadline)));
+                }
+            }
+            TimeoutAction::ToInstant(next_deadline.unwrap_or(now + IDLE_INTERVAL))
+        })?;
+
+        self.ping_source
+            .process_events(readiness, token, |_, _| {})
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+        let time = self.clock.now();
+        for (output, sequence, refresh) in vblanks {
+            callback(
+                HeadlessEvent::VBlank(output),
+                &mut EventMetadata {
+                    time,
+                    sequence,
+                    refresh,
+                },
+            );
+        }
+
+        // newly queued buffers might need the timer to fire earlier
+        match self.next_deadline(Instant::now()) {
+            Some(deadline) if deadline < self.timer.current_deadline() => {
+                self.timer.set_deadline(deadline);
+                Ok(PostAction::Reregister)
+            }
+            _ => Ok(PostAction::Continue),
+        }
+    }
+
+    fn register(&mut self, poll: &mut Poll, token_factory: &mut TokenFactory) -> calloop::Result<()> {
+        self.timer.register(poll, token_factory)?;
+        self.ping_source.register(poll, token_factory)
+    }
+
+    fn reregister(&mut self, poll: &mut Poll, token_factory: &mut TokenFactory) -> calloop::Result<()> {
+        self.timer.reregister(poll, token_factory)?;
+        self.ping_source.reregister(poll, token_factory)
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> calloop::Result<()> {
+        self.timer.unregister(poll)?;
+        self.ping_source.unregister(poll)
+    }
+}
+
+#[derive(Debug)]
+struct Slot<T> {
+    buffer: T,
+    age: u8,
+    stale: bool,
+}
+
+/// A virtual output created by a [`HeadlessBackend`]
+///
+/// Manages the buffers used for rendering the contents of the output.
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct HeadlessOutput<T, U> {
+    output: Output,
+    state: Rc<RefCell<VBlankState>>,
+    ping: Ping,
+    slots: [Option<Slot<T>>; BUFFER_COUNT],
+    current: Option<usize>,
+    next: Option<usize>,
+    pending: Option<(usize, U)>,
+    queued: Option<(usize, U)>,
+}
+
+impl<T, U> HeadlessOutput<T, U> {
+    /// Returns the [`Output`] of this virtual output
+    pub fn output(&self) -> &Output {
+        &self.output
+    }
+
+    /// Returns the buffer currently displayed on this output, if any
+    pub fn current_buffer(&self) -> Option<&T> {
+        self.current
+            .and_then(|idx| self.slots[idx].as_ref())
+            .map(|slot| &slot.buffer)
+    }
+
+    fn in_use(&self, idx: usize) -> bool {
+        self.current == Some(idx)
+            || self.next == Some(idx)
+            || self.pending.as_ref().map(|(pending, _)| *pending) == Some(idx)
+            || self.queued.as_ref().map(|(queued, _)| *queued) == Some(idx)
+    }
+
+    /// Retrieves the next buffer to be rendered into and its age.
+    ///
+    /// *Note*: This function can be called multiple times and
+    /// will return the same buffer until it is queued (see [`HeadlessOutput::queue_buffer`]).
+    ///
+    /// New buffers are created using the provided renderer with the size of the
+    /// current mode of the output.
+    pub fn next_buffer<R>(&mut self, renderer: &mut R) -> Result<(T, u8), R::Error>
+    where
+        R: Offscreen<T>,
+        T: Clone,
+    {
+        if self.next.is_none() {
+            let free = (0..BUFFER_COUNT).filter(|idx| !self.in_use(*idx));
+            // prefer buffers that can be reused
+            let idx = free
+                .clone()
+                .find(|idx| matches!(self.slots[*idx], Some(Slot { stale: false, .. })))
+                .or_else(|| free.clone().next())
+                .expect("Not enough buffer slots");
+
+            if !matches!(self.slots[idx], Some(Slot { stale: false, .. })) {
+                let size = self
+                    .output
+                    .current_mode()
+                    .map(|mode| mode.size)
+                    .unwrap_or_default();
+                let buffer = renderer.create_buffer((size.w, size.h).into())?;
+                self.slots[idx] = Some(Slot {
+                    buffer,
+                    age: 0,
+                    stale: false,
+                });
+            }
+            self.next = Some(idx);
+        }
+
+        let slot = self.slots[self.next.unwrap()].as_ref().unwrap();
+        Ok((slot.buffer.clone(), slot.age))
+    }
+
+    /// Queues the current buffer for rendering.
+    ///
+    /// *Note*: This function needs to be followed up with [`HeadlessOutput::frame_submitted`]
+    /// when a vblank event is received, that denotes successful scanout of the buffer.
+    /// Otherwise the underlying swapchain will eventually run out of buffers.
+    ///
+    /// `user_data` can be used to attach some data to a specific buffer and later retrieved
+    /// with [`HeadlessOutput::frame_submitted`]
+    pub fn queue_buffer(&mut self, user_data: U) {
+        if let Some(idx) = self.next.take() {
+            for (i, slot) in self.slots.iter_mut().enumerate() {
+                if let Some(slot) = slot {
+                    if i == idx {
+                        slot.age = 1;
+                    } else if slot.age > 0 {
+                        slot.age = slot.age.saturating_add(1);
+                    }
+                }
+            }
+            self.queued = Some((idx, user_data));
+            if self.pending.is_none() {
+                self.submit();
+            }
+        }
+    }
+
+    /// Marks the current frame as submitted.
+    ///
+    /// *Note*: Needs to be called, after the vblank event for the matching output
+    /// was received after calling [`HeadlessOutput::queue_buffer`] on this surface.
+    /// Otherwise the underlying swapchain will run out of buffers eventually.
+    pub fn frame_submitted(&mut self) -> Option<U> {
+        if let Some((idx, user_data)) = self.pending.take() {
+            self.current = Some(idx);
+            if self.queued.is_some() {
+                self.submit();
+            }
+            Some(user_data)
+        } else {
+            None
+        }
+    }
+
+    fn submit(&mut self) {
+        self.pending = self.queued.take();
+        self.state.borrow_mut().pending = true;
+        self.ping.ping();
+    }
+
+    /// Changes the current mode of the virtual output.
+    ///
+    /// This resets all buffers, buffers returned by [`HeadlessOutput::next_buffer`]
+    /// afterwards will have the size of the new mode.
+    pub fn use_mode(&mut self, mode: Mode) {
+        self.output.add_mode(mode);
+        self.output.change_current_state(Some(mode), None, None, None);
+        self.state.borrow_mut().refresh = refresh_interval(&mode);
+        self.reset_buffers();
+    }
+
+    /// Reset the age and contents of the buffers.
+    ///
+    /// Buffers currently in use are kept until they are released.
+    pub fn reset_buffers(&mut self) {
+        for idx in 0..BUFFER_COUNT {
+            if self.in_use(idx) {
+                if let Some(slot) = self.slots[idx].as_mut() {
+                    slot.stale = true;
+                    slot.age = 0;
+                }
+            } else {
+                self.slots[idx] = None;
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "renderer_software"))]
+mod tests {
+    use super::*;
+    use crate::backend::renderer::software::{SoftwareRenderer, SoftwareTexture};
+    use calloop::EventLoop;
+
+    #[test]
+    fn vblank_after_queue() {
+        let mut event_loop = EventLoop::<Vec<(Output, u32)>>::try_new().unwrap();
+        let mut backend = HeadlessBackend::new(None).unwrap();
+        let mut output = backend.create_output::<SoftwareTexture, u32>(
+            "HEADLESS-1".into(),
+            Mode {
+                size: (4, 2).into(),
+                refresh: 1_000_000,
+            },
+            Transform::Normal,
+            Scale::Integer(1),
+        );
+        event_loop
+            .handle()
+            .insert_source(backend, |HeadlessEvent::VBlank(output), metadata, vblanks| {
+                vblanks.push((output, metadata.sequence))
+            })
+            .unwrap();
+
+        let mut renderer = SoftwareRenderer::new(None);
+        let (_, age) = output.next_buffer(&mut renderer).unwrap();
+        assert_eq!(age, 0);
+        output.queue_buffer(1);
+        assert!(output.current_buffer().is_none());
+
+        let mut vblanks = Vec::new();
+        while vblanks.is_empty() {
+            event_loop
+                .dispatch(Some(Duration::from_millis(100)), &mut vblanks)
+                .unwrap();
+        }
+        assert_eq!(vblanks.len(), 1);
+        assert_eq!(&vblanks[0].0, output.output());
+        assert_eq!(output.frame_submitted(), Some(1));
+        assert!(output.current_buffer().is_some());
+
+        // the displayed buffer cannot be reused, so a second one gets allocated
+        let (_, age) = output.next_buffer(&mut renderer).unwrap();
+        assert_eq!(age, 0);
+        output.queue_buffer(2);
+        let (_, age) = output.next_buffer(&mut renderer).unwrap();
+        assert_eq!(age, 0);
+
+        // once a buffer is released, it can be reused
+        vblanks.clear();
+        while vblanks.is_empty() {
+            event_loop
+                .dispatch(Some(Duration::from_millis(100)), &mut vblanks)
+                .unwrap();
+        }
+        assert_eq!(output.frame_submitted(), Some(2));
+        output.queue_buffer(3);
+        let (_, age) = output.next_buffer(&mut renderer).unwrap();
+        assert_eq!(age, 3);
+    }
+}
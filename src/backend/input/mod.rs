@@ -5,8 +5,10 @@ use std::path::PathBuf;
 mod tablet;
 
 pub use tablet::{
-    ProximityState, TabletToolAxisEvent, TabletToolButtonEvent, TabletToolCapabilitys, TabletToolDescriptor,
-    TabletToolEvent, TabletToolProximityEvent, TabletToolTipEvent, TabletToolTipState, TabletToolType,
+    ProximityState, TabletPadAxisSource, TabletPadButtonEvent, TabletPadEvent, TabletPadGroupLayout,
+    TabletPadLayout, TabletPadRingEvent, TabletPadStripEvent, TabletToolAxisEvent, TabletToolButtonEvent,
+    TabletToolCapabilitys, TabletToolDescriptor, TabletToolEvent, TabletToolProximityEvent,
+    TabletToolTipEvent, TabletToolTipState, TabletToolType,
 };
 
 use crate::utils::{Logical, Point, Raw, Size};
@@ -29,6 +31,13 @@ pub trait Device: PartialEq + Eq + std::hash::Hash {
     ///
     /// The path is an absolute path and includes the sys mount point.
     fn syspath(&self) -> Option<PathBuf>;
+
+    /// Returns the layout of the buttons, rings and strips of the device.
+    ///
+    /// Only devices with the [`DeviceCapability::TabletPad`] capability have a layout.
+    fn tablet_pad_layout(&self) -> Option<TabletPadLayout> {
+        None
+    }
 }
 
 /// Set of input types a device may provide
@@ -228,6 +237,16 @@ pub enum AxisSource {
     WheelTilt,
 }
 
+/// Relative direction of an axis when scrolling, compared to the physical motion
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AxisRelativeDirection {
+    /// Physical motion matches the axis direction
+    Identical,
+    /// Physical motion is inverted compared to the axis direction,
+    /// e.g. when natural scrolling is enabled
+    Inverted,
+}
+
 /// Trait for pointer events generated by scrolling on an axis.
 pub trait PointerAxisEvent<B: InputBackend>: Event<B> {
     /// Amount of scrolling in pixels on the given [`Axis`].
@@ -240,8 +259,22 @@ pub trait PointerAxisEvent<B: InputBackend>: Event<B> {
     /// Guaranteed to be `Some` when source returns either [`AxisSource::Wheel`] or [`AxisSource::WheelTilt`].
     fn amount_discrete(&self, axis: Axis) -> Option<f64>;
 
+    /// Amount of high-resolution scrolling on the given [`Axis`], in fractions of 120 per discrete step.
+    ///
+    /// Defaults to the discrete amount multiplied by 120 for backends without support
+    /// for high-resolution scroll wheels.
+    fn amount_v120(&self, axis: Axis) -> Option<f64> {
+        self.amount_discrete(axis).map(|amount| amount * 120.0)
+    }
+
     /// Source of the scroll event.
     fn source(&self) -> AxisSource;
+
+    /// Direction of the scrolling on the given [`Axis`] relative to the physical motion.
+    fn relative_direction(&self, axis: Axis) -> AxisRelativeDirection {
+        let _ = axis;
+        AxisRelativeDirection::Identical
+    }
 }
 
 impl<B: InputBackend> PointerAxisEvent<B> for UnusedEvent {
@@ -416,6 +449,120 @@ pub trait TouchFrameEvent<B: InputBackend>: Event<B> {}
 
 impl<B: InputBackend> TouchFrameEvent<B> for UnusedEvent {}
 
+/// Trait for gesture begin events
+pub trait GestureBeginEvent<B: InputBackend>: Event<B> {
+    /// Number of fingers used for this gesture
+    fn fingers(&self) -> u32;
+}
+
+impl<B: InputBackend> GestureBeginEvent<B> for UnusedEvent {
+    fn fingers(&self) -> u32 {
+        match *self {}
+    }
+}
+
+/// Trait for gesture end events
+pub trait GestureEndEvent<B: InputBackend>: Event<B> {
+    /// Whether the gesture was cancelled
+    fn cancelled(&self) -> bool;
+}
+
+impl<B: InputBackend> GestureEndEvent<B> for UnusedEvent {
+    fn cancelled(&self) -> bool {
+        match *self {}
+    }
+}
+
+/// Trait for swipe gesture begin events
+pub trait GestureSwipeBeginEvent<B: InputBackend>: GestureBeginEvent<B> {}
+impl<B: InputBackend> GestureSwipeBeginEvent<B> for UnusedEvent {}
+
+/// Trait for swipe gesture update events
+pub trait GestureSwipeUpdateEvent<B: InputBackend>: Event<B> {
+    /// Delta of the center of the gesture since the last update, in the same
+    /// coordinate space as pointer motion
+    fn delta(&self) -> Point<f64, Logical> {
+        (self.delta_x(), self.delta_y()).into()
+    }
+
+    /// Delta on the x axis of the center of the gesture since the last update
+    fn delta_x(&self) -> f64;
+
+    /// Delta on the y axis of the center of the gesture since the last update
+    fn delta_y(&self) -> f64;
+}
+
+impl<B: InputBackend> GestureSwipeUpdateEvent<B> for UnusedEvent {
+    fn delta_x(&self) -> f64 {
+        match *self {}
+    }
+
+    fn delta_y(&self) -> f64 {
+        match *self {}
+    }
+}
+
+/// Trait for swipe gesture end events
+pub trait GestureSwipeEndEvent<B: InputBackend>: GestureEndEvent<B> {}
+impl<B: InputBackend> GestureSwipeEndEvent<B> for UnusedEvent {}
+
+/// Trait for pinch gesture begin events
+pub trait GesturePinchBeginEvent<B: InputBackend>: GestureBeginEvent<B> {}
+impl<B: InputBackend> GesturePinchBeginEvent<B> for UnusedEvent {}
+
+/// Trait for pinch gesture update events
+pub trait GesturePinchUpdateEvent<B: InputBackend>: Event<B> {
+    /// Delta of the center of the gesture since the last update, in the same
+    /// coordinate space as pointer motion
+    fn delta(&self) -> Point<f64, Logical> {
+        (self.delta_x(), self.delta_y()).into()
+    }
+
+    /// Delta on the x axis of the center of the gesture since the last update
+    fn delta_x(&self) -> f64;
+
+    /// Delta on the y axis of the center of the gesture since the last update
+    fn delta_y(&self) -> f64;
+
+    /// Absolute scale compared to the begin of the gesture
+    ///
+    /// Starts at `1.0`, a value of `2.0` means the distance between the fingers doubled.
+    fn scale(&self) -> f64;
+
+    /// Relative angle in degrees clockwise since the last update
+    fn rotation(&self) -> f64;
+}
+
+impl<B: InputBackend> GesturePinchUpdateEvent<B> for UnusedEvent {
+    fn delta_x(&self) -> f64 {
+        match *self {}
+    }
+
+    fn delta_y(&self) -> f64 {
+        match *self {}
+    }
+
+    fn scale(&self) -> f64 {
+        match *self {}
+    }
+
+    fn rotation(&self) -> f64 {
+        match *self {}
+    }
+}
+
+/// Trait for pinch gesture end events
+pub trait GesturePinchEndEvent<B: InputBackend>: GestureEndEvent<B> {}
+impl<B: InputBackend> GesturePinchEndEvent<B> for UnusedEvent {}
+
+/// Trait for hold gesture begin events
+pub trait GestureHoldBeginEvent<B: InputBackend>: GestureBeginEvent<B> {}
+impl<B: InputBackend> GestureHoldBeginEvent<B> for UnusedEvent {}
+
+/// Trait for hold gesture end events
+pub trait GestureHoldEndEvent<B: InputBackend>: GestureEndEvent<B> {}
+impl<B: InputBackend> GestureHoldEndEvent<B> for UnusedEvent {}
+
 /// Trait that describes objects providing a source of input events. All input backends
 /// need to implement this and provide the same base guarantees about the precision of
 /// given events.
@@ -450,6 +597,28 @@ pub trait InputBackend: Sized {
     type TabletToolTipEvent: TabletToolTipEvent<Self>;
     /// Type representing button events on tablet tool devices
     type TabletToolButtonEvent: TabletToolButtonEvent<Self>;
+    /// Type representing button events on tablet pad devices
+    type TabletPadButtonEvent: TabletPadButtonEvent<Self>;
+    /// Type representing ring events on tablet pad devices
+    type TabletPadRingEvent: TabletPadRingEvent<Self>;
+    /// Type representing strip events on tablet pad devices
+    type TabletPadStripEvent: TabletPadStripEvent<Self>;
+    /// Type representing swipe gesture begin events
+    type GestureSwipeBeginEvent: GestureSwipeBeginEvent<Self>;
+    /// Type representing swipe gesture update events
+    type GestureSwipeUpdateEvent: GestureSwipeUpdateEvent<Self>;
+    /// Type representing swipe gesture end events
+    type GestureSwipeEndEvent: GestureSwipeEndEvent<Self>;
+    /// Type representing pinch gesture begin events
+    type GesturePinchBeginEvent: GesturePinchBeginEvent<Self>;
+    /// Type representing pinch gesture update events
+    type GesturePinchUpdateEvent: GesturePinchUpdateEvent<Self>;
+    /// Type representing pinch gesture end events
+    type GesturePinchEndEvent: GesturePinchEndEvent<Self>;
+    /// Type representing hold gesture begin events
+    type GestureHoldBeginEvent: GestureHoldBeginEvent<Self>;
+    /// Type representing hold gesture end events
+    type GestureHoldEndEvent: GestureHoldEndEvent<Self>;
 
     /// Special events that are custom to this backend
     type SpecialEvent;
@@ -546,6 +715,72 @@ pub enum InputEvent<B: InputBackend> {
         event: B::TabletToolButtonEvent,
     },
 
+    /// A tablet pad button was pressed or released
+    TabletPadButton {
+        /// The tablet pad button event
+        event: B::TabletPadButtonEvent,
+    },
+
+    /// A tablet pad ring changed its position
+    TabletPadRing {
+        /// The tablet pad ring event
+        event: B::TabletPadRingEvent,
+    },
+
+    /// A tablet pad strip changed its position
+    TabletPadStrip {
+        /// The tablet pad strip event
+        event: B::TabletPadStripEvent,
+    },
+
+    /// A swipe gesture started
+    GestureSwipeBegin {
+        /// The gesture event
+        event: B::GestureSwipeBeginEvent,
+    },
+
+    /// A swipe gesture was updated
+    GestureSwipeUpdate {
+        /// The gesture event
+        event: B::GestureSwipeUpdateEvent,
+    },
+
+    /// A swipe gesture ended
+    GestureSwipeEnd {
+        /// The gesture event
+        event: B::GestureSwipeEndEvent,
+    },
+
+    /// A pinch gesture started
+    GesturePinchBegin {
+        /// The gesture event
+        event: B::GesturePinchBeginEvent,
+    },
+
+    /// A pinch gesture was updated
+    GesturePinchUpdate {
+        /// The gesture event
+        event: B::GesturePinchUpdateEvent,
+    },
+
+    /// A pinch gesture ended
+    GesturePinchEnd {
+        /// The gesture event
+        event: B::GesturePinchEndEvent,
+    },
+
+    /// A hold gesture started
+    GestureHoldBegin {
+        /// The gesture event
+        event: B::GestureHoldBeginEvent,
+    },
+
+    /// A hold gesture ended
+    GestureHoldEnd {
+        /// The gesture event
+        event: B::GestureHoldEndEvent,
+    },
+
     /// Special event specific of this backend
     Special(B::SpecialEvent),
 }
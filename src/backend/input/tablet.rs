@@ -368,3 +368,135 @@ impl<B: InputBackend> TabletToolButtonEvent<B> for UnusedEvent {
         match *self {}
     }
 }
+
+/// Layout of the buttons, rings and strips of a tablet pad
+///
+/// Buttons, rings and strips are identified by their index, starting at 0.
+#[derive(Debug, Default, Clone, Hash, Eq, PartialEq)]
+pub struct TabletPadLayout {
+    /// Number of buttons on the pad
+    pub buttons: u32,
+    /// Number of rings on the pad
+    pub rings: u32,
+    /// Number of strips on the pad
+    pub strips: u32,
+    /// Mode groups of the pad
+    pub groups: Vec<TabletPadGroupLayout>,
+}
+
+/// A group of buttons, rings and strips of a tablet pad sharing the same mode
+#[derive(Debug, Default, Clone, Hash, Eq, PartialEq)]
+pub struct TabletPadGroupLayout {
+    /// Indices of the buttons in this group
+    pub buttons: Vec<u32>,
+    /// Indices of the rings in this group
+    pub rings: Vec<u32>,
+    /// Indices of the strips in this group
+    pub strips: Vec<u32>,
+    /// Number of modes the group can switch between
+    pub modes: u32,
+}
+
+/// Describes how a ring or strip event was physically generated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TabletPadAxisSource {
+    /// The source is not known
+    Unknown,
+    /// A finger touching the ring or strip
+    Finger,
+}
+
+/// Tablet pad event
+pub trait TabletPadEvent<B: InputBackend>: Event<B> {
+    /// Index of the mode group the button, ring or strip of this event belongs to
+    fn mode_group(&self) -> u32;
+
+    /// Current mode of the mode group at the time of this event
+    fn mode(&self) -> u32;
+}
+
+impl<B: InputBackend> TabletPadEvent<B> for UnusedEvent {
+    fn mode_group(&self) -> u32 {
+        match *self {}
+    }
+
+    fn mode(&self) -> u32 {
+        match *self {}
+    }
+}
+
+/// Signals that a button on a device with the `DeviceCapability::TabletPad` capability was pressed or released
+pub trait TabletPadButtonEvent<B: InputBackend>: TabletPadEvent<B> {
+    /// Index of the button that triggered this event
+    fn button(&self) -> u32;
+
+    /// Return the button state of the event.
+    fn button_state(&self) -> ButtonState;
+}
+
+impl<B: InputBackend> TabletPadButtonEvent<B> for UnusedEvent {
+    fn button(&self) -> u32 {
+        match *self {}
+    }
+
+    fn button_state(&self) -> ButtonState {
+        match *self {}
+    }
+}
+
+/// Signals that a ring on a device with the `DeviceCapability::TabletPad` capability changed its position
+pub trait TabletPadRingEvent<B: InputBackend>: TabletPadEvent<B> {
+    /// Index of the ring that triggered this event
+    fn number(&self) -> u32;
+
+    /// Current position of the ring in degrees clockwise from the logical north of the ring
+    ///
+    /// Returns `None` once the interaction with the ring terminated, e.g. when the finger was lifted.
+    fn position(&self) -> Option<f64>;
+
+    /// Source of the interaction with the ring
+    fn source(&self) -> TabletPadAxisSource;
+}
+
+impl<B: InputBackend> TabletPadRingEvent<B> for UnusedEvent {
+    fn number(&self) -> u32 {
+        match *self {}
+    }
+
+    fn position(&self) -> Option<f64> {
+        match *self {}
+    }
+
+    fn source(&self) -> TabletPadAxisSource {
+        match *self {}
+    }
+}
+
+/// Signals that a strip on a device with the `DeviceCapability::TabletPad` capability changed its position
+pub trait TabletPadStripEvent<B: InputBackend>: TabletPadEvent<B> {
+    /// Index of the strip that triggered this event
+    fn number(&self) -> u32;
+
+    /// Current position of the strip, normalized to the range `[0, 1]`
+    ///
+    /// 0 is the top-most or left-most position of the strip.
+    /// Returns `None` once the interaction with the strip terminated, e.g. when the finger was lifted.
+    fn position(&self) -> Option<f64>;
+
+    /// Source of the interaction with the strip
+    fn source(&self) -> TabletPadAxisSource;
+}
+
+impl<B: InputBackend> TabletPadStripEvent<B> for UnusedEvent {
+    fn number(&self) -> u32 {
+        match *self {}
+    }
+
+    fn position(&self) -> Option<f64> {
+        match *self {}
+    }
+
+    fn source(&self) -> TabletPadAxisSource {
+        match *self {}
+    }
+}
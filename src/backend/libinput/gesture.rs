@@ -0,0 +1,109 @@
+use crate::backend::input as backend;
+
+use input as libinput;
+use input::event::gesture::{
+    self, GestureEndEvent, GestureEventCoordinates, GestureEventTrait, GesturePinchEventTrait,
+};
+
+use super::LibinputInputBackend;
+
+macro_rules! gesture_event {
+    ($($event:ident),*) => {
+        $(
+            impl backend::Event<LibinputInputBackend> for gesture::$event {
+                fn time(&self) -> u64 {
+                    GestureEventTrait::time_usec(self)
+                }
+
+                fn device(&self) -> libinput::Device {
+                    input::event::EventTrait::device(self)
+                }
+            }
+        )*
+    };
+}
+
+gesture_event!(
+    GestureSwipeBeginEvent,
+    GestureSwipeUpdateEvent,
+    GestureSwipeEndEvent,
+    GesturePinchBeginEvent,
+    GesturePinchUpdateEvent,
+    GesturePinchEndEvent
+);
+#[cfg(feature = "backend_libinput_1_19")]
+gesture_event!(GestureHoldBeginEvent, GestureHoldEndEvent);
+
+macro_rules! gesture_begin_event {
+    ($($event:ident),*) => {
+        $(
+            impl backend::GestureBeginEvent<LibinputInputBackend> for gesture::$event {
+                fn fingers(&self) -> u32 {
+                    GestureEventTrait::finger_count(self) as u32
+                }
+            }
+        )*
+    };
+}
+
+gesture_begin_event!(GestureSwipeBeginEvent, GesturePinchBeginEvent);
+#[cfg(feature = "backend_libinput_1_19")]
+gesture_begin_event!(GestureHoldBeginEvent);
+
+macro_rules! gesture_end_event {
+    ($($event:ident),*) => {
+        $(
+            impl backend::GestureEndEvent<LibinputInputBackend> for gesture::$event {
+                fn cancelled(&self) -> bool {
+                    GestureEndEvent::cancelled(self)
+                }
+            }
+        )*
+    };
+}
+
+gesture_end_event!(GestureSwipeEndEvent, GesturePinchEndEvent);
+#[cfg(feature = "backend_libinput_1_19")]
+gesture_end_event!(GestureHoldEndEvent);
+
+impl backend::GestureSwipeBeginEvent<LibinputInputBackend> for gesture::GestureSwipeBeginEvent {}
+
+impl backend::GestureSwipeUpdateEvent<LibinputInputBackend> for gesture::GestureSwipeUpdateEvent {
+    fn delta_x(&self) -> f64 {
+        GestureEventCoordinates::dx(self)
+    }
+
+    fn delta_y(&self) -> f64 {
+        GestureEventCoordinates::dy(self)
+    }
+}
+
+impl backend::GestureSwipeEndEvent<LibinputInputBackend> for gesture::GestureSwipeEndEvent {}
+
+impl backend::GesturePinchBeginEvent<LibinputInputBackend> for gesture::GesturePinchBeginEvent {}
+
+impl backend::GesturePinchUpdateEvent<LibinputInputBackend> for gesture::GesturePinchUpdateEvent {
+    fn delta_x(&self) -> f64 {
+        GestureEventCoordinates::dx(self)
+    }
+
+    fn delta_y(&self) -> f64 {
+        GestureEventCoordinates::dy(self)
+    }
+
+    fn scale(&self) -> f64 {
+        GesturePinchEventTrait::scale(self)
+    }
+
+    fn rotation(&self) -> f64 {
+        gesture::GesturePinchUpdateEvent::angle_delta(self)
+    }
+}
+
+impl backend::GesturePinchEndEvent<LibinputInputBackend> for gesture::GesturePinchEndEvent {}
+
+#[cfg(feature = "backend_libinput_1_19")]
+impl backend::GestureHoldBeginEvent<LibinputInputBackend> for gesture::GestureHoldBeginEvent {}
+
+#[cfg(feature = "backend_libinput_1_19")]
+impl backend::GestureHoldEndEvent<LibinputInputBackend> for gesture::GestureHoldEndEvent {}
@@ -6,6 +6,8 @@ use crate::backend::session::{AsErrno, Session};
 use input as libinput;
 use input::event;
 
+#[cfg(feature = "backend_libinput_1_19")]
+use std::collections::HashMap;
 use std::{
     io,
     os::unix::io::{AsRawFd, RawFd},
@@ -21,7 +23,9 @@ use calloop::{EventSource, Interest, Mode, Poll, PostAction, Readiness, Token, T
 
 use slog::{info, o, trace};
 
+mod gesture;
 mod tablet;
+mod tablet_pad;
 
 /// Libinput based [`InputBackend`].
 ///
@@ -32,6 +36,8 @@ pub struct LibinputInputBackend {
     context: libinput::Libinput,
     logger: ::slog::Logger,
     token: Option<Token>,
+    #[cfg(feature = "backend_libinput_1_19")]
+    scroll_steps: HashMap<libinput::Device, ScrollSteps>,
 }
 
 impl LibinputInputBackend {
@@ -47,6 +53,8 @@ impl LibinputInputBackend {
             context,
             logger: log,
             token: None,
+            #[cfg(feature = "backend_libinput_1_19")]
+            scroll_steps: HashMap::new(),
         }
     }
 
@@ -83,6 +91,10 @@ impl backend::Device for libinput::Device {
         #[cfg(not(feature = "udev"))]
         None
     }
+
+    fn tablet_pad_layout(&self) -> Option<backend::TabletPadLayout> {
+        tablet_pad::tablet_pad_layout(self)
+    }
 }
 
 impl From<backend::DeviceCapability> for libinput::DeviceCapability {
@@ -125,6 +137,147 @@ impl backend::KeyboardKeyEvent<LibinputInputBackend> for event::keyboard::Keyboa
     }
 }
 
+/// Scroll event of a libinput pointer device
+///
+/// Wraps the different kinds of scroll events emitted by libinput,
+/// which replace the deprecated `PointerAxisEvent`.
+#[cfg(feature = "backend_libinput_1_19")]
+#[derive(Debug)]
+pub enum PointerScrollAxis {
+    /// Scrolling using a (possibly high-resolution) scroll wheel
+    ///
+    /// Additionally contains the whole discrete steps on the horizontal and vertical axis,
+    /// accumulated from the high-resolution values of the wheel.
+    Wheel(event::pointer::PointerScrollWheelEvent, (f64, f64)),
+    /// Scrolling using fingers, e.g. on a touchpad
+    Finger(event::pointer::PointerScrollFingerEvent),
+    /// Continuous scrolling, e.g. button-based scrolling
+    Continuous(event::pointer::PointerScrollContinuousEvent),
+}
+
+/// Accumulates the high-resolution values of a scroll wheel into whole discrete steps
+#[cfg(feature = "backend_libinput_1_19")]
+#[derive(Debug, Default)]
+struct ScrollSteps {
+    v120: (f64, f64),
+}
+
+#[cfg(feature = "backend_libinput_1_19")]
+impl ScrollSteps {
+    fn accumulate(&mut self, event: &event::pointer::PointerScrollWheelEvent) -> (f64, f64) {
+        use input::event::pointer::{Axis as LibinputAxis, PointerScrollEvent};
+
+        let accumulate = |axis, v120: &mut f64| {
+            if !event.has_axis(axis) {
+                return 0.0;
+            }
+            let value = event.scroll_value_v120(axis);
+            // start over when the direction changes
+            if *v120 * value < 0.0 {
+                *v120 = 0.0;
+            }
+            *v120 += value;
+            let steps = (*v120 / 120.0).trunc();
+            *v120 -= steps * 120.0;
+            steps
+        };
+
+        (
+            accumulate(LibinputAxis::Horizontal, &mut self.v120.0),
+            accumulate(LibinputAxis::Vertical, &mut self.v120.1),
+        )
+    }
+}
+
+#[cfg(feature = "backend_libinput_1_19")]
+fn scroll_amount<E: event::pointer::PointerScrollEvent>(event: &E, axis: Axis) -> Option<f64> {
+    let axis = axis.into();
+    if event.has_axis(axis) {
+        Some(event.scroll_value(axis))
+    } else {
+        None
+    }
+}
+
+#[cfg(feature = "backend_libinput_1_19")]
+impl backend::Event<LibinputInputBackend> for PointerScrollAxis {
+    fn time(&self) -> u64 {
+        match self {
+            PointerScrollAxis::Wheel(event, _) => event::pointer::PointerEventTrait::time_usec(event),
+            PointerScrollAxis::Finger(event) => event::pointer::PointerEventTrait::time_usec(event),
+            PointerScrollAxis::Continuous(event) => event::pointer::PointerEventTrait::time_usec(event),
+        }
+    }
+
+    fn device(&self) -> libinput::Device {
+        match self {
+            PointerScrollAxis::Wheel(event, _) => event::EventTrait::device(event),
+            PointerScrollAxis::Finger(event) => event::EventTrait::device(event),
+            PointerScrollAxis::Continuous(event) => event::EventTrait::device(event),
+        }
+    }
+}
+
+#[cfg(feature = "backend_libinput_1_19")]
+impl backend::PointerAxisEvent<LibinputInputBackend> for PointerScrollAxis {
+    fn amount(&self, axis: Axis) -> Option<f64> {
+        match self {
+            PointerScrollAxis::Wheel(event, _) => scroll_amount(event, axis),
+            PointerScrollAxis::Finger(event) => scroll_amount(event, axis),
+            PointerScrollAxis::Continuous(event) => scroll_amount(event, axis),
+        }
+    }
+
+    fn amount_discrete(&self, axis: Axis) -> Option<f64> {
+        match self {
+            PointerScrollAxis::Wheel(event, steps) => {
+                if event::pointer::PointerScrollEvent::has_axis(event, axis.into()) {
+                    Some(match axis {
+                        Axis::Horizontal => steps.0,
+                        Axis::Vertical => steps.1,
+                    })
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn amount_v120(&self, axis: Axis) -> Option<f64> {
+        match self {
+            PointerScrollAxis::Wheel(event, _) => {
+                let axis = axis.into();
+                if event::pointer::PointerScrollEvent::has_axis(event, axis) {
+                    Some(event.scroll_value_v120(axis))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn source(&self) -> backend::AxisSource {
+        match self {
+            PointerScrollAxis::Wheel(..) => backend::AxisSource::Wheel,
+            PointerScrollAxis::Finger(_) => backend::AxisSource::Finger,
+            PointerScrollAxis::Continuous(_) => backend::AxisSource::Continuous,
+        }
+    }
+
+    fn relative_direction(&self, _axis: Axis) -> backend::AxisRelativeDirection {
+        // libinput does not report the direction per event, but inverts
+        // the scroll values if natural scrolling is enabled on the device
+        if backend::Event::device(self).config_scroll_natural_scroll_enabled() {
+            backend::AxisRelativeDirection::Inverted
+        } else {
+            backend::AxisRelativeDirection::Identical
+        }
+    }
+}
+
+#[cfg(not(feature = "backend_libinput_1_19"))]
 impl backend::Event<LibinputInputBackend> for event::pointer::PointerAxisEvent {
     fn time(&self) -> u64 {
         event::pointer::PointerEventTrait::time_usec(self)
@@ -135,6 +288,7 @@ impl backend::Event<LibinputInputBackend> for event::pointer::PointerAxisEvent {
     }
 }
 
+#[cfg(not(feature = "backend_libinput_1_19"))]
 impl backend::PointerAxisEvent<LibinputInputBackend> for event::pointer::PointerAxisEvent {
     fn amount(&self, axis: Axis) -> Option<f64> {
         let axis = axis.into();
@@ -157,6 +311,16 @@ impl backend::PointerAxisEvent<LibinputInputBackend> for event::pointer::Pointer
     fn source(&self) -> backend::AxisSource {
         self.axis_source().into()
     }
+
+    fn relative_direction(&self, _axis: Axis) -> backend::AxisRelativeDirection {
+        // libinput does not report the direction per event, but inverts
+        // the scroll values if natural scrolling is enabled on the device
+        if event::EventTrait::device(self).config_scroll_natural_scroll_enabled() {
+            backend::AxisRelativeDirection::Inverted
+        } else {
+            backend::AxisRelativeDirection::Identical
+        }
+    }
 }
 
 impl backend::Event<LibinputInputBackend> for event::pointer::PointerButtonEvent {
@@ -363,6 +527,9 @@ impl backend::TouchFrameEvent<LibinputInputBackend> for event::touch::TouchFrame
 impl InputBackend for LibinputInputBackend {
     type Device = libinput::Device;
     type KeyboardKeyEvent = event::keyboard::KeyboardKeyEvent;
+    #[cfg(feature = "backend_libinput_1_19")]
+    type PointerAxisEvent = PointerScrollAxis;
+    #[cfg(not(feature = "backend_libinput_1_19"))]
     type PointerAxisEvent = event::pointer::PointerAxisEvent;
     type PointerButtonEvent = event::pointer::PointerButtonEvent;
     type PointerMotionEvent = event::pointer::PointerMotionEvent;
@@ -376,6 +543,23 @@ impl InputBackend for LibinputInputBackend {
     type TabletToolProximityEvent = event::tablet_tool::TabletToolProximityEvent;
     type TabletToolTipEvent = event::tablet_tool::TabletToolTipEvent;
     type TabletToolButtonEvent = event::tablet_tool::TabletToolButtonEvent;
+    type TabletPadButtonEvent = event::tablet_pad::TabletPadButtonEvent;
+    type TabletPadRingEvent = event::tablet_pad::TabletPadRingEvent;
+    type TabletPadStripEvent = event::tablet_pad::TabletPadStripEvent;
+    type GestureSwipeBeginEvent = event::gesture::GestureSwipeBeginEvent;
+    type GestureSwipeUpdateEvent = event::gesture::GestureSwipeUpdateEvent;
+    type GestureSwipeEndEvent = event::gesture::GestureSwipeEndEvent;
+    type GesturePinchBeginEvent = event::gesture::GesturePinchBeginEvent;
+    type GesturePinchUpdateEvent = event::gesture::GesturePinchUpdateEvent;
+    type GesturePinchEndEvent = event::gesture::GesturePinchEndEvent;
+    #[cfg(feature = "backend_libinput_1_19")]
+    type GestureHoldBeginEvent = event::gesture::GestureHoldBeginEvent;
+    #[cfg(feature = "backend_libinput_1_19")]
+    type GestureHoldEndEvent = event::gesture::GestureHoldEndEvent;
+    #[cfg(not(feature = "backend_libinput_1_19"))]
+    type GestureHoldBeginEvent = backend::UnusedEvent;
+    #[cfg(not(feature = "backend_libinput_1_19"))]
+    type GestureHoldEndEvent = backend::UnusedEvent;
 
     type SpecialEvent = backend::UnusedEvent;
 }
@@ -490,6 +674,9 @@ impl EventSource for LibinputInputBackend {
 
                             info!(self.logger, "Removed device {:?}", removed.sysname(),);
 
+                            #[cfg(feature = "backend_libinput_1_19")]
+                            self.scroll_steps.remove(&removed);
+
                             callback(InputEvent::DeviceRemoved { device: removed }, &mut ());
                         }
                         _ => {
@@ -536,6 +723,43 @@ impl EventSource for LibinputInputBackend {
                                 &mut (),
                             );
                         }
+                        #[cfg(feature = "backend_libinput_1_19")]
+                        event::PointerEvent::ScrollWheel(event) => {
+                            let steps = self
+                                .scroll_steps
+                                .entry(event::EventTrait::device(&event))
+                                .or_default()
+                                .accumulate(&event);
+                            callback(
+                                InputEvent::PointerAxis {
+                                    event: PointerScrollAxis::Wheel(event, steps),
+                                },
+                                &mut (),
+                            );
+                        }
+                        #[cfg(feature = "backend_libinput_1_19")]
+                        event::PointerEvent::ScrollFinger(event) => {
+                            callback(
+                                InputEvent::PointerAxis {
+                                    event: PointerScrollAxis::Finger(event),
+                                },
+                                &mut (),
+                            );
+                        }
+                        #[cfg(feature = "backend_libinput_1_19")]
+                        event::PointerEvent::ScrollContinuous(event) => {
+                            callback(
+                                InputEvent::PointerAxis {
+                                    event: PointerScrollAxis::Continuous(event),
+                                },
+                                &mut (),
+                            );
+                        }
+                        #[cfg(feature = "backend_libinput_1_19")]
+                        event::PointerEvent::Axis(_) => {
+                            // deprecated since libinput 1.19, superseded by the scroll events above
+                        }
+                        #[cfg(not(feature = "backend_libinput_1_19"))]
                         event::PointerEvent::Axis(axis_event) => {
                             callback(InputEvent::PointerAxis { event: axis_event }, &mut ());
                         }
@@ -563,6 +787,51 @@ impl EventSource for LibinputInputBackend {
                             trace!(self.logger, "Unknown libinput tablet event");
                         }
                     },
+                    libinput::Event::TabletPad(tablet_pad_event) => match tablet_pad_event {
+                        event::TabletPadEvent::Button(event) => {
+                            callback(InputEvent::TabletPadButton { event }, &mut ());
+                        }
+                        event::TabletPadEvent::Ring(event) => {
+                            callback(InputEvent::TabletPadRing { event }, &mut ());
+                        }
+                        event::TabletPadEvent::Strip(event) => {
+                            callback(InputEvent::TabletPadStrip { event }, &mut ());
+                        }
+                        _ => {
+                            trace!(self.logger, "Unknown libinput tablet pad event");
+                        }
+                    },
+                    libinput::Event::Gesture(gesture_event) => match gesture_event {
+                        event::GestureEvent::Swipe(event::gesture::GestureSwipeEvent::Begin(event)) => {
+                            callback(InputEvent::GestureSwipeBegin { event }, &mut ());
+                        }
+                        event::GestureEvent::Swipe(event::gesture::GestureSwipeEvent::Update(event)) => {
+                            callback(InputEvent::GestureSwipeUpdate { event }, &mut ());
+                        }
+                        event::GestureEvent::Swipe(event::gesture::GestureSwipeEvent::End(event)) => {
+                            callback(InputEvent::GestureSwipeEnd { event }, &mut ());
+                        }
+                        event::GestureEvent::Pinch(event::gesture::GesturePinchEvent::Begin(event)) => {
+                            callback(InputEvent::GesturePinchBegin { event }, &mut ());
+                        }
+                        event::GestureEvent::Pinch(event::gesture::GesturePinchEvent::Update(event)) => {
+                            callback(InputEvent::GesturePinchUpdate { event }, &mut ());
+                        }
+                        event::GestureEvent::Pinch(event::gesture::GesturePinchEvent::End(event)) => {
+                            callback(InputEvent::GesturePinchEnd { event }, &mut ());
+                        }
+                        #[cfg(feature = "backend_libinput_1_19")]
+                        event::GestureEvent::Hold(event::gesture::GestureHoldEvent::Begin(event)) => {
+                            callback(InputEvent::GestureHoldBegin { event }, &mut ());
+                        }
+                        #[cfg(feature = "backend_libinput_1_19")]
+                        event::GestureEvent::Hold(event::gesture::GestureHoldEvent::End(event)) => {
+                            callback(InputEvent::GestureHoldEnd { event }, &mut ());
+                        }
+                        _ => {
+                            trace!(self.logger, "Unknown libinput gesture event");
+                        }
+                    },
                     _ => {} //FIXME: What to do with the rest.
                 }
             }
@@ -0,0 +1,119 @@
+use crate::backend::input::{self as backend, TabletPadAxisSource, TabletPadGroupLayout, TabletPadLayout};
+
+use input as libinput;
+use input::event::{tablet_pad, EventTrait};
+
+use super::LibinputInputBackend;
+
+pub(super) fn tablet_pad_layout(device: &libinput::Device) -> Option<TabletPadLayout> {
+    if !device.has_capability(libinput::DeviceCapability::TabletPad) {
+        return None;
+    }
+
+    // libinput returns negative values on errors
+    let buttons = device.tablet_pad_number_of_buttons().max(0) as u32;
+    let rings = device.tablet_pad_number_of_rings().max(0) as u32;
+    let strips = device.tablet_pad_number_of_strips().max(0) as u32;
+    let num_groups = device.tablet_pad_number_of_mode_groups().max(0) as u32;
+
+    let groups = (0..num_groups)
+        .filter_map(|index| device.tablet_pad_mode_group(index))
+        .map(|group| TabletPadGroupLayout {
+            buttons: (0..buttons).filter(|button| group.has_button(*button)).collect(),
+            rings: (0..rings).filter(|ring| group.has_ring(*ring)).collect(),
+            strips: (0..strips).filter(|strip| group.has_strip(*strip)).collect(),
+            modes: group.number_of_modes() as u32,
+        })
+        .collect();
+
+    Some(TabletPadLayout {
+        buttons,
+        rings,
+        strips,
+        groups,
+    })
+}
+
+macro_rules! tablet_pad_event {
+    ($($event:ident),*) => {
+        $(
+            impl backend::Event<LibinputInputBackend> for tablet_pad::$event {
+                fn time(&self) -> u64 {
+                    tablet_pad::TabletPadEventTrait::time_usec(self)
+                }
+
+                fn device(&self) -> libinput::Device {
+                    EventTrait::device(self)
+                }
+            }
+
+            impl backend::TabletPadEvent<LibinputInputBackend> for tablet_pad::$event {
+                fn mode_group(&self) -> u32 {
+                    tablet_pad::TabletPadEventTrait::mode_group(self).index()
+                }
+
+                fn mode(&self) -> u32 {
+                    tablet_pad::TabletPadEventTrait::mode(self)
+                }
+            }
+        )*
+    };
+}
+
+tablet_pad_event!(TabletPadButtonEvent, TabletPadRingEvent, TabletPadStripEvent);
+
+impl backend::TabletPadButtonEvent<LibinputInputBackend> for tablet_pad::TabletPadButtonEvent {
+    fn button(&self) -> u32 {
+        tablet_pad::TabletPadButtonEvent::button_number(self)
+    }
+
+    fn button_state(&self) -> backend::ButtonState {
+        tablet_pad::TabletPadButtonEvent::button_state(self).into()
+    }
+}
+
+impl backend::TabletPadRingEvent<LibinputInputBackend> for tablet_pad::TabletPadRingEvent {
+    fn number(&self) -> u32 {
+        tablet_pad::TabletPadRingEvent::number(self)
+    }
+
+    fn position(&self) -> Option<f64> {
+        // libinput reports -1 when the finger was lifted
+        let position = tablet_pad::TabletPadRingEvent::position(self);
+        if position < 0.0 {
+            None
+        } else {
+            Some(position)
+        }
+    }
+
+    fn source(&self) -> TabletPadAxisSource {
+        match tablet_pad::TabletPadRingEvent::source(self) {
+            tablet_pad::RingAxisSource::Finger => TabletPadAxisSource::Finger,
+            _ => TabletPadAxisSource::Unknown,
+        }
+    }
+}
+
+impl backend::TabletPadStripEvent<LibinputInputBackend> for tablet_pad::TabletPadStripEvent {
+    fn number(&self) -> u32 {
+        tablet_pad::TabletPadStripEvent::number(self)
+    }
+
+    fn position(&self) -> Option<f64> {
+        // libinput reports -1 when the finger was lifted
+        let position = tablet_pad::TabletPadStripEvent::position(self);
+        if position < 0.0 {
+            None
+        } else {
+            Some(position)
+        }
+    }
+
+    fn source(&self) -> TabletPadAxisSource {
+        match tablet_pad::TabletPadStripEvent::source(self) {
+            tablet_pad::StripAxisSource::Finger => TabletPadAxisSource::Finger,
+            _ => TabletPadAxisSource::Unknown,
+        }
+    }
+}
@@ -75,6 +75,13 @@
 //! development and debugging. That backend is both a renderer and an input provider, and is
 //! accessible in the [`winit`] module, gated by the `backend_winit` cargo feature.
 //!
+//! ## Headless backend
+//!
+//! Smithay also provides a backend without any connection to a display, which renders into
+//! offscreen buffers of virtual outputs and drives their frame timing with a timer. This is
+//! useful for testing and remote-desktop setups. It is accessible in the [`headless`] module,
+//! gated by the `backend_headless` cargo feature.
+//!
 
 pub mod allocator;
 pub mod input;
@@ -84,6 +91,8 @@ pub mod renderer;
 pub mod drm;
 #[cfg(feature = "backend_egl")]
 pub mod egl;
+#[cfg(feature = "backend_headless")]
+pub mod headless;
 #[cfg(feature = "backend_libinput")]
 pub mod libinput;
 #[cfg(feature = "backend_session")]
@@ -21,7 +21,7 @@
 use std::{collections::HashMap, sync::Arc};
 
 #[cfg(feature = "wayland_frontend")]
-use wayland_server::{backend::ObjectId, protocol::wl_buffer, Resource};
+use wayland_server::{backend::ObjectId, Resource};
 
 use crate::{
     output::{Output, WeakOutput},
@@ -94,8 +94,10 @@ impl<R: Resource> From<&R> for Id {
 #[derive(Debug)]
 pub enum UnderlyingStorage<'a, R: Renderer> {
     /// A wayland buffer
+    ///
+    /// The buffer is not released to the client as long as this reference is kept alive.
     #[cfg(feature = "wayland_frontend")]
-    Wayland(wl_buffer::WlBuffer),
+    Wayland(super::utils::Buffer),
     /// A texture
     External(&'a R::TextureId),
 }
@@ -136,6 +138,13 @@ impl RenderElementState {
             presentation_state: RenderElementPresentationState::Rendering,
         }
     }
+
+    pub(crate) fn zero_copy(visible_area: usize) -> Self {
+        RenderElementState {
+            visible_area,
+            presentation_state: RenderElementPresentationState::ZeroCopy,
+        }
+    }
 }
 
 /// Holds the primary scanout output for a surface
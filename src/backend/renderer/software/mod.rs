@@ -0,0 +1,1065 @@
+//! Implementation of the rendering traits using the CPU
+//!
+//! The [`SoftwareRenderer`] does all its work on plain memory buffers and does not
+//! require any graphics hardware or driver support. It is considerably slower than
+//! a hardware-accelerated renderer like the [`Gles2Renderer`](super::gles2::Gles2Renderer),
+//! but it can be used on machines without EGL, in headless setups and as a reference
+//! implementation to compare the output of other renderers against.
+//!
+//! All textures and offscreen buffers of this renderer are stored as premultiplied
+//! `RGBA8` data in memory, which is also the format returned by [`ExportMem`].
+//!
+//! Linear single-plane dmabufs can be imported and bound as rendering targets by
+//! mapping them into memory.
+
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    fmt,
+    num::NonZeroUsize,
+    os::unix::io::{AsRawFd, BorrowedFd},
+    rc::Rc,
+};
+
+#[cfg(feature = "wayland_frontend")]
+use std::collections::HashMap;
+
+use nix::sys::mman;
+use slog::{o, trace, warn};
+
+use super::{
+    Bind, ExportMem, Frame, ImportDma, ImportMem, Offscreen, Renderer, Texture, TextureFilter,
+    TextureMapping, Unbind,
+};
+use crate::backend::{
+    allocator::{dmabuf::Dmabuf, Buffer, Format, Fourcc, Modifier},
+    SwapBuffersError,
+};
+use crate::utils::{Buffer as BufferCoord, Physical, Point, Rectangle, Size, Transform};
+
+#[cfg(all(feature = "wayland_frontend", feature = "use_system_lib"))]
+use super::{buffer_type, BufferType, ImportAll};
+#[cfg(feature = "wayland_frontend")]
+use super::{ImportDmaWl, ImportMemWl};
+#[cfg(feature = "wayland_frontend")]
+use wayland_server::protocol::{wl_buffer, wl_shm};
+
+crate::utils::ids::id_gen!(next_renderer_id, RENDERER_ID, RENDERER_IDS);
+
+/// Formats supported for dmabuf import and as dmabuf rendering targets
+const SUPPORTED_FOURCCS: [Fourcc; 4] = [
+    Fourcc::Abgr8888,
+    Fourcc::Xbgr8888,
+    Fourcc::Argb8888,
+    Fourcc::Xrgb8888,
+];
+
+/// Memory layout of pixel data handled by the [`SoftwareRenderer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PixelLayout {
+    /// Bytes are ordered `R, G, B, A`
+    Rgba,
+    /// Bytes are ordered `B, G, R, A`
+    Bgra,
+}
+
+impl PixelLayout {
+    fn from_fourcc(code: Fourcc) -> Option<(PixelLayout, bool)> {
+        match code {
+            Fourcc::Abgr8888 => Some((PixelLayout::Rgba, false)),
+            Fourcc::Xbgr8888 => Some((PixelLayout::Rgba, true)),
+            Fourcc::Argb8888 => Some((PixelLayout::Bgra, false)),
+            Fourcc::Xrgb8888 => Some((PixelLayout::Bgra, true)),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn read(self, px: &[u8], opaque: bool) -> [u8; 4] {
+        let a = if opaque { 255 } else { px[3] };
+        match self {
+            PixelLayout::Rgba => [px[0], px[1], px[2], a],
+            PixelLayout::Bgra => [px[2], px[1], px[0], a],
+        }
+    }
+
+    #[inline]
+    fn write(self, px: &mut [u8], color: [u8; 4]) {
+        match self {
+            PixelLayout::Rgba => px[..4].copy_from_slice(&color),
+            PixelLayout::Bgra => px[..4].copy_from_slice(&[color[2], color[1], color[0], color[3]]),
+        }
+    }
+}
+
+/// A handle to a texture of the [`SoftwareRenderer`]
+///
+/// Cloning the handle does not copy the underlying memory.
+#[derive(Debug, Clone)]
+pub struct SoftwareTexture(Rc<SoftwareTextureInternal>);
+
+#[derive(Debug)]
+struct SoftwareTextureInternal {
+    data: RefCell<Vec<u8>>,
+    size: Size<i32, BufferCoord>,
+    y_inverted: bool,
+}
+
+impl SoftwareTexture {
+    fn new(data: Vec<u8>, size: Size<i32, BufferCoord>, y_inverted: bool) -> SoftwareTexture {
+        SoftwareTexture(Rc::new(SoftwareTextureInternal {
+            data: RefCell::new(data),
+            size,
+            y_inverted,
+        }))
+    }
+
+    /// Access the premultiplied `RGBA8` contents of this texture.
+    ///
+    /// The data is laid out row by row without any padding, so the stride is always `width * 4`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the texture is currently bound and accessed by an ongoing rendering operation.
+    pub fn with_data<F, R>(&self, func: F) -> R
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        func(&self.0.data.borrow())
+    }
+}
+
+impl Texture for SoftwareTexture {
+    fn width(&self) -> u32 {
+        self.0.size.w as u32
+    }
+    fn height(&self) -> u32 {
+        self.0.size.h as u32
+    }
+    fn size(&self) -> Size<i32, BufferCoord> {
+        self.0.size
+    }
+}
+
+/// Texture mapping of a [`SoftwareRenderer`] texture or framebuffer
+#[derive(Debug)]
+pub struct SoftwareMapping {
+    data: Vec<u8>,
+    size: Size<i32, BufferCoord>,
+}
+
+impl Texture for SoftwareMapping {
+    fn width(&self) -> u32 {
+        self.size.w as u32
+    }
+    fn height(&self) -> u32 {
+        self.size.h as u32
+    }
+    fn size(&self) -> Size<i32, BufferCoord> {
+        self.size
+    }
+}
+
+impl TextureMapping for SoftwareMapping {
+    fn flipped(&self) -> bool {
+        false
+    }
+}
+
+/// Error returned during rendering using the [`SoftwareRenderer`]
+#[derive(thiserror::Error, Debug)]
+pub enum SoftwareError {
+    /// The given buffer has an unsupported pixel format
+    #[error("Unsupported pixel format: {0:?}")]
+    #[cfg(feature = "wayland_frontend")]
+    UnsupportedPixelFormat(wl_shm::Format),
+    /// The given dmabuf has an unsupported format or layout
+    #[error("Unsupported dmabuf format: {0:?}")]
+    UnsupportedDmabufFormat(Format),
+    /// The given buffer was not accessible
+    #[error("Error accessing the buffer ({0:?})")]
+    #[cfg(feature = "wayland_frontend")]
+    BufferAccessError(crate::wayland::shm::BufferAccessError),
+    /// The given dmabuf could not be mapped into memory
+    #[error("Failed to map the dmabuf")]
+    DmabufMappingError(#[source] nix::Error),
+    /// This rendering operation was called without a bound target
+    #[error("Call bind before doing any rendering operations")]
+    NoTarget,
+    /// The provided buffer's size did not match the requested one.
+    #[error("Error reading buffer, size is too small for the given dimensions")]
+    UnexpectedSize,
+    /// The requested region is out of bounds
+    #[error("The requested region is out of bounds")]
+    OutOfBounds,
+}
+
+impl From<SoftwareError> for SwapBuffersError {
+    fn from(err: SoftwareError) -> SwapBuffersError {
+        SwapBuffersError::TemporaryFailure(Box::new(err))
+    }
+}
+
+/// Minimal definitions of the dma-buf uapi needed to synchronize cpu access
+mod dma_buf {
+    pub const DMA_BUF_SYNC_READ: u64 = 1;
+    pub const DMA_BUF_SYNC_WRITE: u64 = 2;
+    pub const DMA_BUF_SYNC_RW: u64 = DMA_BUF_SYNC_READ | DMA_BUF_SYNC_WRITE;
+    pub const DMA_BUF_SYNC_START: u64 = 0;
+    pub const DMA_BUF_SYNC_END: u64 = 4;
+
+    #[repr(C)]
+    pub struct DmaBufSync {
+        pub flags: u64,
+    }
+
+    nix::ioctl_write_ptr!(dma_buf_ioctl_sync, b'b', 0, DmaBufSync);
+}
+
+/// Cpu mapping of the first plane of a linear dmabuf
+#[derive(Debug)]
+struct DmabufMapping {
+    dmabuf: Dmabuf,
+    ptr: *mut u8,
+    len: usize,
+    offset: usize,
+    stride: usize,
+    layout: PixelLayout,
+    opaque: bool,
+    sync_flags: u64,
+}
+
+impl DmabufMapping {
+    fn new(dmabuf: &Dmabuf, write: bool) -> Result<DmabufMapping, SoftwareError> {
+        let format = dmabuf.format();
+        let (layout, opaque) = PixelLayout::from_fourcc(format.code)
+            .filter(|_| dmabuf.num_planes() == 1 && format.modifier == Modifier::Linear)
+            .ok_or(SoftwareError::UnsupportedDmabufFormat(format))?;
+
+        let fd: BorrowedFd<'_> = dmabuf.handles().next().unwrap();
+        let offset = dmabuf.offsets().next().unwrap() as usize;
+        let stride = dmabuf.strides().next().unwrap() as usize;
+        let size = dmabuf.size();
+
+        if stride < size.w as usize * 4 {
+            return Err(SoftwareError::UnexpectedSize);
+        }
+        let len = offset + stride * size.h as usize;
+
+        let prot = if write {
+            mman::ProtFlags::PROT_READ | mman::ProtFlags::PROT_WRITE
+        } else {
+            mman::ProtFlags::PROT_READ
+        };
+        let ptr = unsafe {
+            mman::mmap(
+                None,
+                NonZeroUsize::new(len).ok_or(SoftwareError::UnexpectedSize)?,
+                prot,
+                mman::MapFlags::MAP_SHARED,
+                fd.as_raw_fd(),
+                0,
+            )
+        }
+        .map_err(SoftwareError::DmabufMappingError)? as *mut u8;
+
+        let sync_flags = if write {
+            dma_buf::DMA_BUF_SYNC_RW
+        } else {
+            dma_buf::DMA_BUF_SYNC_READ
+        };
+        // Not every exporter supports the sync ioctl, cpu access is still valid without it.
+        let _ = unsafe {
+            dma_buf::dma_buf_ioctl_sync(
+                fd.as_raw_fd(),
+                &dma_buf::DmaBufSync {
+                    flags: dma_buf::DMA_BUF_SYNC_START | sync_flags,
+                },
+            )
+        };
+
+        Ok(DmabufMapping {
+            dmabuf: dmabuf.clone(),
+            ptr,
+            len,
+            offset,
+            stride,
+            layout,
+            opaque,
+            sync_flags,
+        })
+    }
+
+    fn pixels(&self) -> &[u8] {
+        // SAFETY: the mapping is valid for `len` bytes until it is dropped
+        unsafe { &std::slice::from_raw_parts(self.ptr, self.len)[self.offset..] }
+    }
+
+    fn pixels_mut(&mut self) -> &mut [u8] {
+        // SAFETY: the mapping is valid for `len` bytes until it is dropped
+        unsafe { &mut std::slice::from_raw_parts_mut(self.ptr, self.len)[self.offset..] }
+    }
+}
+
+impl Drop for DmabufMapping {
+    fn drop(&mut self) {
+        if let Some(fd) = self.dmabuf.handles().next() {
+            let _ = unsafe {
+                dma_buf::dma_buf_ioctl_sync(
+                    fd.as_raw_fd(),
+                    &dma_buf::DmaBufSync {
+                        flags: dma_buf::DMA_BUF_SYNC_END | self.sync_flags,
+                    },
+                )
+            };
+        }
+        let _ = unsafe { mman::munmap(self.ptr as *mut _, self.len) };
+    }
+}
+
+#[derive(Debug)]
+enum SoftwareTarget {
+    Texture(SoftwareTexture),
+    Dmabuf(DmabufMapping),
+}
+
+impl SoftwareTarget {
+    fn size(&self) -> Size<i32, BufferCoord> {
+        match self {
+            SoftwareTarget::Texture(texture) => texture.size(),
+            SoftwareTarget::Dmabuf(mapping) => mapping.dmabuf.size(),
+        }
+    }
+
+    fn is_texture(&self, texture: &SoftwareTexture) -> bool {
+        matches!(self, SoftwareTarget::Texture(target) if Rc::ptr_eq(&target.0, &texture.0))
+    }
+
+    fn with_canvas<F, R>(&mut self, func: F) -> R
+    where
+        F: FnOnce(&mut Canvas<'_>) -> R,
+    {
+        match self {
+            SoftwareTarget::Texture(texture) => {
+                let size = texture.size();
+                let mut data = texture.0.data.borrow_mut();
+                func(&mut Canvas {
+                    data: &mut data,
+                    stride: size.w as usize * 4,
+                    size,
+                    layout: PixelLayout::Rgba,
+                    opaque: false,
+                })
+            }
+            SoftwareTarget::Dmabuf(mapping) => {
+                let size = mapping.dmabuf.size();
+                let stride = mapping.stride;
+                let layout = mapping.layout;
+                let opaque = mapping.opaque;
+                func(&mut Canvas {
+                    data: mapping.pixels_mut(),
+                    stride,
+                    size,
+                    layout,
+                    opaque,
+                })
+            }
+        }
+    }
+
+    fn read_region(&self, region: Rectangle<i32, BufferCoord>) -> Result<Vec<u8>, SoftwareError> {
+        match self {
+            SoftwareTarget::Texture(texture) => read_region(
+                &texture.0.data.borrow(),
+                texture.size().w as usize * 4,
+                texture.size(),
+                PixelLayout::Rgba,
+                false,
+                region,
+            ),
+            SoftwareTarget::Dmabuf(mapping) => read_region(
+                mapping.pixels(),
+                mapping.stride,
+                mapping.dmabuf.size(),
+                mapping.layout,
+                mapping.opaque,
+                region,
+            ),
+        }
+    }
+}
+
+/// Mutable view into the pixels of the currently bound target
+struct Canvas<'a> {
+    data: &'a mut [u8],
+    stride: usize,
+    size: Size<i32, BufferCoord>,
+    layout: PixelLayout,
+    opaque: bool,
+}
+
+impl<'a> Canvas<'a> {
+    #[inline]
+    fn offset(&self, loc: Point<i32, BufferCoord>) -> usize {
+        loc.y as usize * self.stride + loc.x as usize * 4
+    }
+
+    #[inline]
+    fn set(&mut self, loc: Point<i32, BufferCoord>, color: [u8; 4]) {
+        let offset = self.offset(loc);
+        let color = if self.opaque {
+            [color[0], color[1], color[2], 255]
+        } else {
+            color
+        };
+        self.layout.write(&mut self.data[offset..offset + 4], color);
+    }
+
+    #[inline]
+    fn blend(&mut self, loc: Point<i32, BufferCoord>, color: [f32; 4]) {
+        let offset = self.offset(loc);
+        let dst = self.layout.read(&self.data[offset..offset + 4], self.opaque);
+        let inv_alpha = 1.0 - color[3];
+        let mut out = [0u8; 4];
+        for (out, (src, dst)) in out.iter_mut().zip(color.iter().zip(dst.iter())) {
+            *out = (src * 255.0 + *dst as f32 * inv_alpha).round().clamp(0.0, 255.0) as u8;
+        }
+        self.set(loc, out);
+    }
+}
+
+fn read_region(
+    data: &[u8],
+    stride: usize,
+    size: Size<i32, BufferCoord>,
+    layout: PixelLayout,
+    opaque: bool,
+    region: Rectangle<i32, BufferCoord>,
+) -> Result<Vec<u8>, SoftwareError> {
+    if !Rectangle::from_loc_and_size((0, 0), size).contains_rect(region) {
+        return Err(SoftwareError::OutOfBounds);
+    }
+
+    let mut out = Vec::with_capacity(region.size.w as usize * region.size.h as usize * 4);
+    for y in region.loc.y..region.loc.y + region.size.h {
+        let row = y as usize * stride;
+        for x in region.loc.x..region.loc.x + region.size.w {
+            let offset = row + x as usize * 4;
+            out.extend_from_slice(&layout.read(&data[offset..offset + 4], opaque));
+        }
+    }
+    Ok(out)
+}
+
+/// A renderer drawing into plain memory using the CPU
+pub struct SoftwareRenderer {
+    id: usize,
+    target: Option<SoftwareTarget>,
+    dmabuf_formats: Vec<Format>,
+    min_filter: TextureFilter,
+    max_filter: TextureFilter,
+    logger: ::slog::Logger,
+}
+
+impl fmt::Debug for SoftwareRenderer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SoftwareRenderer")
+            .field("id", &self.id)
+            .field("target", &self.target)
+            .field("dmabuf_formats", &self.dmabuf_formats)
+            .field("min_filter", &self.min_filter)
+            .field("max_filter", &self.max_filter)
+            .field("logger", &self.logger)
+            .finish()
+    }
+}
+
+impl Drop for SoftwareRenderer {
+    fn drop(&mut self) {
+        RENDERER_IDS.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl SoftwareRenderer {
+    /// Creates a new software renderer.
+    ///
+    /// # Implementation details
+    ///
+    /// - Texture handles created by any `SoftwareRenderer` can be used with every other `SoftwareRenderer`.
+    /// - This renderer has no default framebuffer, use `Bind::bind` before rendering.
+    /// - Binding a new target, while another one is already bound, will replace the current target.
+    /// - Shm buffers and dmabufs can be released after a successful import, without the texture handle becoming invalid.
+    /// - Texture filtering starts with Linear-downscaling and Linear-upscaling
+    pub fn new<L>(logger: L) -> SoftwareRenderer
+    where
+        L: Into<Option<::slog::Logger>>,
+    {
+        let logger = crate::slog_or_fallback(logger).new(o!("smithay_module" => "renderer_software"));
+
+        SoftwareRenderer {
+            id: next_renderer_id(),
+            target: None,
+            dmabuf_formats: SUPPORTED_FOURCCS
+                .iter()
+                .map(|&code| Format {
+                    code,
+                    modifier: Modifier::Linear,
+                })
+                .collect(),
+            min_filter: TextureFilter::Linear,
+            max_filter: TextureFilter::Linear,
+            logger,
+        }
+    }
+}
+
+/// Handle to the currently rendered frame during [`SoftwareRenderer::render`](Renderer::render).
+///
+/// All operations are executed immediately, finishing or dropping the frame has no further effect.
+#[derive(Debug)]
+pub struct SoftwareFrame<'frame> {
+    renderer: &'frame mut SoftwareRenderer,
+    transform: Transform,
+    size: Size<i32, Physical>,
+}
+
+impl Renderer for SoftwareRenderer {
+    type Error = SoftwareError;
+    type TextureId = SoftwareTexture;
+    type Frame<'frame> = SoftwareFrame<'frame>;
+
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn downscale_filter(&mut self, filter: TextureFilter) -> Result<(), Self::Error> {
+        self.min_filter = filter;
+        Ok(())
+    }
+    fn upscale_filter(&mut self, filter: TextureFilter) -> Result<(), Self::Error> {
+        self.max_filter = filter;
+        Ok(())
+    }
+
+    fn render(
+        &mut self,
+        output_size: Size<i32, Physical>,
+        transform: Transform,
+    ) -> Result<SoftwareFrame<'_>, Self::Error> {
+        let target_size = self.target.as_ref().ok_or(SoftwareError::NoTarget)?.size();
+        if output_size.w > target_size.w || output_size.h > target_size.h {
+            return Err(SoftwareError::OutOfBounds);
+        }
+
+        Ok(SoftwareFrame {
+            renderer: self,
+            transform,
+            // Handle the width/height swap when the output is rotated by 90°/270°.
+            size: transform.transform_size(output_size),
+        })
+    }
+}
+
+impl<'frame> SoftwareFrame<'frame> {
+    /// Maps a pixel of the frame into the bound target applying the output transformation
+    #[inline]
+    fn to_target(&self, loc: Point<i32, Physical>) -> Point<i32, BufferCoord> {
+        let rect = self
+            .transform
+            .transform_rect_in(Rectangle::from_loc_and_size(loc, (1, 1)), &self.size);
+        (rect.loc.x, rect.loc.y).into()
+    }
+
+    fn frame_geometry(&self) -> Rectangle<i32, Physical> {
+        Rectangle::from_loc_and_size((0, 0), self.size)
+    }
+}
+
+impl<'frame> Frame for SoftwareFrame<'frame> {
+    type TextureId = SoftwareTexture;
+    type Error = SoftwareError;
+
+    fn id(&self) -> usize {
+        self.renderer.id()
+    }
+
+    fn clear(&mut self, color: [f32; 4], at: &[Rectangle<i32, Physical>]) -> Result<(), SoftwareError> {
+        let color = color.map(|c| (c * 255.0).round().clamp(0.0, 255.0) as u8);
+        let geometry = self.frame_geometry();
+        let rects = at
+            .iter()
+            .filter_map(|rect| rect.intersection(geometry))
+            .collect::<Vec<_>>();
+
+        let positions = rects
+            .iter()
+            .flat_map(|rect| {
+                (rect.loc.y..rect.loc.y + rect.size.h)
+                    .flat_map(move |y| (rect.loc.x..rect.loc.x + rect.size.w).map(move |x| (x, y)))
+            })
+            .map(|(x, y)| self.to_target((x, y).into()))
+            .collect::<Vec<_>>();
+
+        self.renderer
+            .target
+            .as_mut()
+            .ok_or(SoftwareError::NoTarget)?
+            .with_canvas(|canvas| {
+                for loc in positions {
+                    canvas.set(loc, color);
+                }
+            });
+
+        Ok(())
+    }
+
+    fn render_texture_from_to(
+        &mut self,
+        texture: &SoftwareTexture,
+        src: Rectangle<f64, BufferCoord>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+        src_transform: Transform,
+        alpha: f32,
+    ) -> Result<(), SoftwareError> {
+        let target = self.renderer.target.as_ref().ok_or(SoftwareError::NoTarget)?;
+
+        let tex_size = texture.size();
+        if src.size.w <= 0.
+            || src.size.h <= 0.
+            || dst.size.w <= 0
+            || dst.size.h <= 0
+            || tex_size.w <= 0
+            || tex_size.h <= 0
+        {
+            warn!(self.renderer.logger, "Texture/Src is zero sized");
+            return Ok(());
+        }
+
+        // Rendering a texture into itself, operate on a copy of the source.
+        let source = if target.is_texture(texture) {
+            Some(texture.0.data.borrow().clone())
+        } else {
+            None
+        };
+
+        // The src rectangle after applying the buffer transform
+        let logical_src_size = src_transform.transform_size(src.size);
+        let scale_x = logical_src_size.w / dst.size.w as f64;
+        let scale_y = logical_src_size.h / dst.size.h as f64;
+        let filter = if scale_x < 1.0 || scale_y < 1.0 {
+            self.renderer.max_filter
+        } else {
+            self.renderer.min_filter
+        };
+
+        let frame_geometry = self.frame_geometry();
+        let rects = damage
+            .iter()
+            .filter_map(|rect| {
+                let rect = Rectangle::from_loc_and_size(rect.loc + dst.loc, rect.size);
+                rect.intersection(dst)
+                    .and_then(|rect| rect.intersection(frame_geometry))
+            })
+            .collect::<Vec<_>>();
+
+        let tex_ref;
+        let tex_data: &[u8] = match source.as_deref() {
+            Some(source) => source,
+            None => {
+                tex_ref = texture.0.data.borrow();
+                &tex_ref
+            }
+        };
+        let sampler = Sampler {
+            data: tex_data,
+            size: tex_size,
+            y_inverted: texture.0.y_inverted,
+        };
+
+        let positions = rects
+            .iter()
+            .flat_map(|rect| {
+                (rect.loc.y..rect.loc.y + rect.size.h)
+                    .flat_map(move |y| (rect.loc.x..rect.loc.x + rect.size.w).map(move |x| (x, y)))
+            })
+            .map(|(x, y)| {
+                // sample at the pixel center
+                let local = Point::<f64, BufferCoord>::from((
+                    (x - dst.loc.x) as f64 + 0.5,
+                    (y - dst.loc.y) as f64 + 0.5,
+                ));
+                let logical = Point::<f64, BufferCoord>::from((local.x * scale_x, local.y * scale_y));
+                let buffer = src_transform.transform_point_in(logical, &logical_src_size);
+                (
+                    self.to_target((x, y).into()),
+                    (src.loc.x + buffer.x, src.loc.y + buffer.y),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        self.renderer.target.as_mut().unwrap().with_canvas(|canvas| {
+            for (target_loc, (u, v)) in positions {
+                let color = sampler.sample(u, v, filter);
+                if color[3] == 0.0 && color[0] == 0.0 && color[1] == 0.0 && color[2] == 0.0 {
+                    continue;
+                }
+                canvas.blend(target_loc, color.map(|c| c * alpha));
+            }
+        });
+
+        Ok(())
+    }
+
+    fn transformation(&self) -> Transform {
+        self.transform
+    }
+
+    fn finish(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Helper to read filtered values out of a texture
+struct Sampler<'a> {
+    data: &'a [u8],
+    size: Size<i32, BufferCoord>,
+    y_inverted: bool,
+}
+
+impl<'a> Sampler<'a> {
+    #[inline]
+    fn texel(&self, x: i32, y: i32) -> [f32; 4] {
+        let x = x.clamp(0, self.size.w - 1) as usize;
+        let y = y.clamp(0, self.size.h - 1);
+        let y = if self.y_inverted { self.size.h - 1 - y } else { y } as usize;
+        let offset = (y * self.size.w as usize + x) * 4;
+        let px = &self.data[offset..offset + 4];
+        [
+            px[0] as f32 / 255.0,
+            px[1] as f32 / 255.0,
+            px[2] as f32 / 255.0,
+            px[3] as f32 / 255.0,
+        ]
+    }
+
+    /// Sample the texture at the given position in buffer coordinates
+    fn sample(&self, u: f64, v: f64, filter: TextureFilter) -> [f32; 4] {
+        match filter {
+            TextureFilter::Nearest => self.texel(u.floor() as i32, v.floor() as i32),
+            TextureFilter::Linear => {
+                let u = u - 0.5;
+                let v = v - 0.5;
+                let x0 = u.floor();
+                let y0 = v.floor();
+                let fx = (u - x0) as f32;
+                let fy = (v - y0) as f32;
+                let (x0, y0) = (x0 as i32, y0 as i32);
+
+                let tl = self.texel(x0, y0);
+                let tr = self.texel(x0 + 1, y0);
+                let bl = self.texel(x0, y0 + 1);
+                let br = self.texel(x0 + 1, y0 + 1);
+
+                let mut out = [0.0; 4];
+                for (i, out) in out.iter_mut().enumerate() {
+                    let top = tl[i] + (tr[i] - tl[i]) * fx;
+                    let bottom = bl[i] + (br[i] - bl[i]) * fx;
+                    *out = top + (bottom - top) * fy;
+                }
+                out
+            }
+        }
+    }
+}
+
+impl ImportMem for SoftwareRenderer {
+    fn import_memory(
+        &mut self,
+        data: &[u8],
+        size: Size<i32, BufferCoord>,
+        flipped: bool,
+    ) -> Result<SoftwareTexture, SoftwareError> {
+        if size.w < 0 || size.h < 0 {
+            return Err(SoftwareError::UnexpectedSize);
+        }
+        let len = size.w as usize * size.h as usize * 4;
+        if data.len() < len {
+            return Err(SoftwareError::UnexpectedSize);
+        }
+
+        Ok(SoftwareTexture::new(data[..len].to_vec(), size, flipped))
+    }
+
+    fn update_memory(
+        &mut self,
+        texture: &<Self as Renderer>::TextureId,
+        data: &[u8],
+        region: Rectangle<i32, BufferCoord>,
+    ) -> Result<(), <Self as Renderer>::Error> {
+        let size = texture.size();
+        if !Rectangle::from_loc_and_size((0, 0), size).contains_rect(region) {
+            return Err(SoftwareError::OutOfBounds);
+        }
+
+        // The data is laid out like the whole texture, see `ImportMem::update_memory`
+        let stride = size.w as usize * 4;
+        let row_len = region.size.w as usize * 4;
+        if region.size.h > 0
+            && data.len()
+                < (region.loc.y + region.size.h - 1) as usize * stride + region.loc.x as usize * 4 + row_len
+        {
+            return Err(SoftwareError::UnexpectedSize);
+        }
+
+        let mut tex_data = texture.0.data.borrow_mut();
+        for y in region.loc.y..region.loc.y + region.size.h {
+            let offset = y as usize * stride + region.loc.x as usize * 4;
+            tex_data[offset..offset + row_len].copy_from_slice(&data[offset..offset + row_len]);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "wayland_frontend")]
+impl ImportMemWl for SoftwareRenderer {
+    fn import_shm_buffer(
+        &mut self,
+        buffer: &wl_buffer::WlBuffer,
+        surface: Option<&crate::wayland::compositor::SurfaceData>,
+        damage: &[Rectangle<i32, BufferCoord>],
+    ) -> Result<SoftwareTexture, SoftwareError> {
+        use crate::wayland::shm::with_buffer_contents;
+
+        // why not store a `SoftwareTexture`? because the user might do so.
+        // this is guaranteed a non-public internal type, so we are good.
+        type CacheMap = HashMap<usize, Rc<SoftwareTextureInternal>>;
+
+        with_buffer_contents(buffer, |slice, data| {
+            let offset = data.offset as usize;
+            let width = data.width;
+            let height = data.height;
+            let stride = data.stride as usize;
+            let size = Size::<i32, BufferCoord>::from((width, height));
+
+            let (layout, opaque) = match data.format {
+                wl_shm::Format::Abgr8888 => (PixelLayout::Rgba, false),
+                wl_shm::Format::Xbgr8888 => (PixelLayout::Rgba, true),
+                wl_shm::Format::Argb8888 => (PixelLayout::Bgra, false),
+                wl_shm::Format::Xrgb8888 => (PixelLayout::Bgra, true),
+                format => return Err(SoftwareError::UnsupportedPixelFormat(format)),
+            };
+
+            if width <= 0 || height <= 0 {
+                return Err(SoftwareError::UnexpectedSize);
+            }
+
+            // ensure consistency, the SHM handler of smithay should ensure this
+            assert!(offset + (height as usize - 1) * stride + width as usize * 4 <= slice.len());
+
+            let mut upload_full = false;
+
+            let id = self.id();
+            let texture = SoftwareTexture(
+                surface
+                    .and_then(|surface| {
+                        surface
+                            .data_map
+                            .insert_if_missing(|| Rc::new(RefCell::new(CacheMap::new())));
+                        surface
+                            .data_map
+                            .get::<Rc<RefCell<CacheMap>>>()
+                            .unwrap()
+                            .borrow()
+                            .get(&id)
+                            .cloned()
+                    })
+                    .filter(|texture| texture.size == size)
+                    .unwrap_or_else(|| {
+                        // new texture, upload in full
+                        upload_full = true;
+                        let new = Rc::new(SoftwareTextureInternal {
+                            data: RefCell::new(vec![0; width as usize * height as usize * 4]),
+                            size,
+                            y_inverted: false,
+                        });
+                        if let Some(surface) = surface {
+                            let copy = new.clone();
+                            surface
+                                .data_map
+                                .get::<Rc<RefCell<CacheMap>>>()
+                                .unwrap()
+                                .borrow_mut()
+                                .insert(id, copy);
+                        }
+                        new
+                    }),
+            );
+
+            let full = [Rectangle::from_loc_and_size((0, 0), size)];
+            let regions = if upload_full || damage.is_empty() {
+                trace!(self.logger, "Uploading shm texture for {:?}", buffer);
+                &full[..]
+            } else {
+                trace!(self.logger, "Uploading partial shm texture for {:?}", buffer);
+                damage
+            };
+
+            let mut tex_data = texture.0.data.borrow_mut();
+            for region in regions.iter().filter_map(|region| region.intersection(full[0])) {
+                for y in region.loc.y..region.loc.y + region.size.h {
+                    for x in region.loc.x..region.loc.x + region.size.w {
+                        let src = offset + y as usize * stride + x as usize * 4;
+                        let dst = (y as usize * width as usize + x as usize) * 4;
+                        tex_data[dst..dst + 4].copy_from_slice(&layout.read(&slice[src..src + 4], opaque));
+                    }
+                }
+            }
+            std::mem::drop(tex_data);
+
+            Ok(texture)
+        })
+        .map_err(SoftwareError::BufferAccessError)?
+    }
+
+    fn shm_formats(&self) -> &[wl_shm::Format] {
+        &[
+            wl_shm::Format::Abgr8888,
+            wl_shm::Format::Xbgr8888,
+            wl_shm::Format::Argb8888,
+            wl_shm::Format::Xrgb8888,
+        ]
+    }
+}
+
+impl ImportDma for SoftwareRenderer {
+    fn import_dmabuf(
+        &mut self,
+        buffer: &Dmabuf,
+        _damage: Option<&[Rectangle<i32, BufferCoord>]>,
+    ) -> Result<SoftwareTexture, SoftwareError> {
+        let mapping = DmabufMapping::new(buffer, false)?;
+        let size = buffer.size();
+        let data = read_region(
+            mapping.pixels(),
+            mapping.stride,
+            size,
+            mapping.layout,
+            mapping.opaque,
+            Rectangle::from_loc_and_size((0, 0), size),
+        )?;
+
+        Ok(SoftwareTexture::new(data, size, buffer.y_inverted()))
+    }
+
+    fn dmabuf_formats<'a>(&'a self) -> Box<dyn Iterator<Item = &'a Format> + 'a> {
+        Box::new(self.dmabuf_formats.iter())
+    }
+}
+
+#[cfg(feature = "wayland_frontend")]
+impl ImportDmaWl for SoftwareRenderer {}
+
+// The blanket implementation requires `ImportEgl` in this configuration,
+// which this renderer cannot provide.
+#[cfg(all(feature = "wayland_frontend", feature = "use_system_lib"))]
+impl ImportAll for SoftwareRenderer {
+    fn import_buffer(
+        &mut self,
+        buffer: &wl_buffer::WlBuffer,
+        surface: Option<&crate::wayland::compositor::SurfaceData>,
+        damage: &[Rectangle<i32, BufferCoord>],
+    ) -> Option<Result<SoftwareTexture, SoftwareError>> {
+        match buffer_type(buffer) {
+            Some(BufferType::Shm) => Some(self.import_shm_buffer(buffer, surface, damage)),
+            Some(BufferType::Dma) => Some(self.import_dma_buffer(buffer, surface, damage)),
+            _ => None,
+        }
+    }
+}
+
+impl ExportMem for SoftwareRenderer {
+    type TextureMapping = SoftwareMapping;
+
+    fn copy_framebuffer(
+        &mut self,
+        region: Rectangle<i32, BufferCoord>,
+    ) -> Result<Self::TextureMapping, Self::Error> {
+        let target = self.target.as_ref().ok_or(SoftwareError::NoTarget)?;
+        Ok(SoftwareMapping {
+            data: target.read_region(region)?,
+            size: region.size,
+        })
+    }
+
+    fn copy_texture(
+        &mut self,
+        texture: &Self::TextureId,
+        region: Rectangle<i32, BufferCoord>,
+    ) -> Result<Self::TextureMapping, Self::Error> {
+        let size = texture.size();
+        let data = read_region(
+            &texture.0.data.borrow(),
+            size.w as usize * 4,
+            size,
+            PixelLayout::Rgba,
+            false,
+            region,
+        )?;
+        Ok(SoftwareMapping {
+            data,
+            size: region.size,
+        })
+    }
+
+    fn map_texture<'a>(
+        &mut self,
+        texture_mapping: &'a Self::TextureMapping,
+    ) -> Result<&'a [u8], Self::Error> {
+        Ok(&texture_mapping.data)
+    }
+}
+
+impl Bind<SoftwareTexture> for SoftwareRenderer {
+    fn bind(&mut self, texture: SoftwareTexture) -> Result<(), SoftwareError> {
+        self.unbind()?;
+        self.target = Some(SoftwareTarget::Texture(texture));
+        Ok(())
+    }
+}
+
+impl Offscreen<SoftwareTexture> for SoftwareRenderer {
+    fn create_buffer(&mut self, size: Size<i32, BufferCoord>) -> Result<SoftwareTexture, SoftwareError> {
+        if size.w < 0 || size.h < 0 {
+            return Err(SoftwareError::UnexpectedSize);
+        }
+        Ok(SoftwareTexture::new(
+            vec![0; size.w as usize * size.h as usize * 4],
+            size,
+            false,
+        ))
+    }
+}
+
+impl Bind<Dmabuf> for SoftwareRenderer {
+    fn bind(&mut self, dmabuf: Dmabuf) -> Result<(), SoftwareError> {
+        self.unbind()?;
+        self.target = Some(SoftwareTarget::Dmabuf(DmabufMapping::new(&dmabuf, true)?));
+        Ok(())
+    }
+
+    fn supported_formats(&self) -> Option<HashSet<Format>> {
+        Some(self.dmabuf_formats.iter().copied().collect())
+    }
+}
+
+impl Unbind for SoftwareRenderer {
+    fn unbind(&mut self) -> Result<(), <Self as Renderer>::Error> {
+        self.target = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,156 @@
+use super::*;
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const GREEN: [u8; 4] = [0, 255, 0, 255];
+const CLEAR: [u8; 4] = [0, 0, 0, 0];
+
+fn pixels(data: &[u8]) -> Vec<[u8; 4]> {
+    data.chunks_exact(4)
+        .map(|px| [px[0], px[1], px[2], px[3]])
+        .collect()
+}
+
+fn renderer_with_target(size: (i32, i32)) -> (SoftwareRenderer, SoftwareTexture) {
+    let mut renderer = SoftwareRenderer::new(None);
+    let target: SoftwareTexture = renderer.create_buffer(size.into()).unwrap();
+    renderer.bind(target.clone()).unwrap();
+    (renderer, target)
+}
+
+#[test]
+fn render_without_target() {
+    let mut renderer = SoftwareRenderer::new(None);
+    assert!(matches!(
+        renderer.render((1, 1).into(), Transform::Normal),
+        Err(SoftwareError::NoTarget)
+    ));
+}
+
+#[test]
+fn clear_damaged_region() {
+    let (mut renderer, target) = renderer_with_target((2, 2));
+
+    let mut frame = renderer.render((2, 2).into(), Transform::Normal).unwrap();
+    frame
+        .clear(
+            [1.0, 0.0, 0.0, 1.0],
+            &[Rectangle::from_loc_and_size((1, 0), (1, 2))],
+        )
+        .unwrap();
+    frame.finish().unwrap();
+
+    target.with_data(|data| assert_eq!(pixels(data), vec![CLEAR, RED, CLEAR, RED]));
+}
+
+#[test]
+fn clear_applies_output_transform() {
+    let (mut renderer, target) = renderer_with_target((1, 2));
+
+    let mut frame = renderer.render((1, 2).into(), Transform::_90).unwrap();
+    // the frame is rotated and thus 2x1 pixels large
+    frame
+        .clear(
+            [1.0, 0.0, 0.0, 1.0],
+            &[Rectangle::from_loc_and_size((1, 0), (1, 1))],
+        )
+        .unwrap();
+    frame.finish().unwrap();
+
+    target.with_data(|data| assert_eq!(pixels(data), vec![CLEAR, RED]));
+}
+
+#[test]
+fn render_texture_scaled() {
+    let (mut renderer, target) = renderer_with_target((4, 2));
+    renderer.upscale_filter(TextureFilter::Nearest).unwrap();
+
+    let texture = renderer
+        .import_memory(&[RED, GREEN].concat(), (2, 1).into(), false)
+        .unwrap();
+
+    let mut frame = renderer.render((4, 2).into(), Transform::Normal).unwrap();
+    frame
+        .render_texture_from_to(
+            &texture,
+            Rectangle::from_loc_and_size((0.0, 0.0), (2.0, 1.0)),
+            Rectangle::from_loc_and_size((0, 0), (4, 2)),
+            &[Rectangle::from_loc_and_size((0, 0), (4, 2))],
+            Transform::Normal,
+            1.0,
+        )
+        .unwrap();
+    frame.finish().unwrap();
+
+    target.with_data(|data| assert_eq!(pixels(data), vec![RED, RED, GREEN, GREEN, RED, RED, GREEN, GREEN]));
+}
+
+#[test]
+fn render_texture_blends_premultiplied() {
+    let (mut renderer, target) = renderer_with_target((1, 1));
+
+    let texture = renderer
+        .import_memory(&[0, 128, 0, 128], (1, 1).into(), false)
+        .unwrap();
+
+    let mut frame = renderer.render((1, 1).into(), Transform::Normal).unwrap();
+    let full = [Rectangle::from_loc_and_size((0, 0), (1, 1))];
+    frame.clear([1.0, 0.0, 0.0, 1.0], &full).unwrap();
+    frame
+        .render_texture_at(&texture, (0, 0).into(), 1, 1.0, Transform::Normal, &full, 1.0)
+        .unwrap();
+    frame.finish().unwrap();
+
+    target.with_data(|data| assert_eq!(pixels(data), vec![[127, 128, 0, 255]]));
+}
+
+#[test]
+fn copy_framebuffer_region() {
+    let (mut renderer, _target) = renderer_with_target((2, 2));
+
+    let mut frame = renderer.render((2, 2).into(), Transform::Normal).unwrap();
+    frame
+        .clear(
+            [0.0, 1.0, 0.0, 1.0],
+            &[Rectangle::from_loc_and_size((0, 1), (2, 1))],
+        )
+        .unwrap();
+    frame.finish().unwrap();
+
+    let mapping = renderer
+        .copy_framebuffer(Rectangle::from_loc_and_size((1, 0), (1, 2)))
+        .unwrap();
+    assert_eq!(mapping.size(), (1, 2).into());
+    assert_eq!(
+        pixels(renderer.map_texture(&mapping).unwrap()),
+        vec![CLEAR, GREEN]
+    );
+
+    assert!(matches!(
+        renderer.copy_framebuffer(Rectangle::from_loc_and_size((1, 1), (2, 2))),
+        Err(SoftwareError::OutOfBounds)
+    ));
+}
+
+#[test]
+fn update_memory_region() {
+    let mut renderer = SoftwareRenderer::new(None);
+    let texture = renderer
+        .import_memory(&[CLEAR, CLEAR, CLEAR, CLEAR].concat(), (2, 2).into(), false)
+        .unwrap();
+
+    renderer
+        .update_memory(
+            &texture,
+            &[RED, GREEN, RED, GREEN].concat(),
+            Rectangle::from_loc_and_size((1, 0), (1, 2)),
+        )
+        .unwrap();
+
+    let mapping = renderer
+        .copy_texture(&texture, Rectangle::from_loc_and_size((0, 0), (2, 2)))
+        .unwrap();
+    assert_eq!(
+        pixels(renderer.map_texture(&mapping).unwrap()),
+        vec![CLEAR, GREEN, CLEAR, GREEN]
+    );
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "backend_drm")]
+use crate::wayland::drm_syncobj::{merge_sync_files, DrmSyncPoint, DrmSyncobjCachedState};
 use crate::{
     backend::renderer::{buffer_dimensions, buffer_has_alpha, element::RenderElement, ImportAll, Renderer},
     utils::{Buffer as BufferCoord, Coordinate, Logical, Physical, Point, Rectangle, Scale, Size, Transform},
@@ -14,12 +16,107 @@ use std::{
     any::TypeId,
     cell::RefCell,
     collections::{hash_map::Entry, HashMap},
+    sync::Arc,
+};
+#[cfg(feature = "backend_drm")]
+use std::{
+    os::unix::io::{AsFd, OwnedFd},
+    sync::Mutex,
 };
 
 use wayland_server::protocol::{wl_buffer::WlBuffer, wl_surface::WlSurface};
 
 use super::{CommitCounter, DamageTracker, SurfaceView};
 
+/// A reference to a [`WlBuffer`] used by the compositor
+///
+/// The buffer is released to the client once the last reference to it is dropped.
+/// Holding on to a [`Buffer`] prevents the client from re-using its contents, e.g. while
+/// the buffer is still scanned out after a newer buffer was committed to the surface.
+///
+/// If the commit of the buffer set an explicit release point (see [`drm_syncobj`](crate::wayland::drm_syncobj)),
+/// it is signalled once the last reference to the [`Buffer`] of this commit is dropped
+/// and all fences added with [`Buffer::add_release_fence`] are signalled.
+#[derive(Debug, Clone)]
+pub struct Buffer {
+    inner: Arc<InnerBuffer>,
+    #[cfg(feature = "backend_drm")]
+    release_point: Option<Arc<BufferReleasePoint>>,
+}
+
+#[derive(Debug)]
+struct InnerBuffer(WlBuffer);
+
+impl Drop for InnerBuffer {
+    fn drop(&mut self) {
+        self.0.release();
+    }
+}
+
+#[cfg(feature = "backend_drm")]
+#[derive(Debug)]
+struct BufferReleasePoint {
+    point: DrmSyncPoint,
+    fence: Mutex<Option<OwnedFd>>,
+}
+
+#[cfg(feature = "backend_drm")]
+impl Drop for BufferReleasePoint {
+    fn drop(&mut self) {
+        let imported = match self.fence.get_mut().unwrap().take() {
+            Some(fence) => self.point.import_sync_file(fence.as_fd()).is_ok(),
+            None => false,
+        };
+        // without a fence (or if importing it failed) nothing is using the buffer anymore
+        if !imported {
+            let _ = self.point.signal();
+        }
+    }
+}
+
+impl Buffer {
+    fn new(buffer: WlBuffer) -> Self {
+        Buffer {
+            inner: Arc::new(InnerBuffer(buffer)),
+            #[cfg(feature = "backend_drm")]
+            release_point: None,
+        }
+    }
+
+    /// Add a fence, that has to be signalled before the release point of this buffer is
+    ///
+    /// This should be used for any pending gpu or scanout work reading the buffer,
+    /// e.g. the out-fence of a drm commit replacing the buffer on a plane.
+    /// The fence is dropped, if the commit of this buffer did not set a release point,
+    /// in which case the buffer is released once the last reference to it is dropped.
+    #[cfg(feature = "backend_drm")]
+    pub fn add_release_fence(&self, fence: OwnedFd) {
+        if let Some(release_point) = self.release_point.as_ref() {
+            let mut current = release_point.fence.lock().unwrap();
+            let fence = match current.take() {
+                // if the fences cannot be merged, the later one is kept
+                Some(previous) => merge_sync_files(previous.as_fd(), fence.as_fd()).unwrap_or(fence),
+                None => fence,
+            };
+            *current = Some(fence);
+        }
+    }
+}
+
+impl std::ops::Deref for Buffer {
+    type Target = WlBuffer;
+
+    fn deref(&self) -> &WlBuffer {
+        &self.inner.0
+    }
+}
+
+impl PartialEq<WlBuffer> for Buffer {
+    fn eq(&self, other: &WlBuffer) -> bool {
+        self.inner.0 == *other
+    }
+}
+
 /// Type stored in WlSurface states data_map
 ///
 /// ```rs
@@ -37,7 +134,7 @@ pub struct RendererSurfaceState {
     pub(crate) buffer_transform: Transform,
     pub(crate) buffer_delta: Option<Point<i32, Logical>>,
     pub(crate) buffer_has_alpha: Option<bool>,
-    pub(crate) buffer: Option<WlBuffer>,
+    pub(crate) buffer: Option<Buffer>,
     pub(crate) damage: DamageTracker<i32, BufferCoord>,
     pub(crate) renderer_seen: HashMap<(TypeId, usize), CommitCounter>,
     pub(crate) textures: HashMap<(TypeId, usize), Box<dyn std::any::Any>>,
@@ -58,9 +155,21 @@ impl RendererSurfaceState {
 
         match attrs.buffer.take() {
             Some(BufferAssignment::NewBuffer(buffer)) => {
+                #[cfg(feature = "backend_drm")]
+                let release_point = states
+                    .cached_state
+                    .current::<DrmSyncobjCachedState>()
+                    .release_point
+                    .take();
+
                 // new contents
                 self.buffer_dimensions = buffer_dimensions(&buffer);
                 if self.buffer_dimensions.is_none() {
+                    // the buffer is not going to be used
+                    #[cfg(feature = "backend_drm")]
+                    if let Some(release_point) = release_point {
+                        let _ = release_point.signal();
+                    }
                     // This results in us rendering nothing (can happen e.g. for failed egl-buffer-calls),
                     // but it is better than crashing the compositor for a bad buffer
                     return;
@@ -69,11 +178,28 @@ impl RendererSurfaceState {
                 self.buffer_scale = attrs.buffer_scale;
                 self.buffer_transform = attrs.buffer_transform.into();
 
-                if let Some(old_buffer) = std::mem::replace(&mut self.buffer, Some(buffer)) {
-                    if &old_buffer != self.buffer.as_ref().unwrap() {
-                        old_buffer.release();
-                    }
+                // re-attaching the same buffer keeps the existing reference, so it is not released
+                #[allow(unused_mut)]
+                let mut new_buffer = match self.buffer.take() {
+                    Some(old) if old == buffer => Buffer {
+                        inner: old.inner,
+                        #[cfg(feature = "backend_drm")]
+                        release_point: None,
+                    },
+                    _ => Buffer::new(buffer),
+                };
+                // the release point of the previous commit is kept by the old reference, as it refers
+                // to the usage of the buffer by that commit, even if the same buffer was attached again.
+                #[cfg(feature = "backend_drm")]
+                {
+                    new_buffer.release_point = release_point.map(|point| {
+                        Arc::new(BufferReleasePoint {
+                            point,
+                            fence: Mutex::new(None),
+                        })
+                    });
                 }
+                self.buffer = Some(new_buffer);
                 self.textures.clear();
 
                 let surface_size = self
@@ -162,9 +288,7 @@ impl RendererSurfaceState {
             Some(BufferAssignment::Removed) => {
                 // remove the contents
                 self.buffer_dimensions = None;
-                if let Some(buffer) = self.buffer.take() {
-                    buffer.release();
-                };
+                self.buffer = None;
                 self.textures.clear();
                 self.damage.reset();
                 self.surface_view = None;
@@ -219,6 +343,13 @@ impl RendererSurfaceState {
     /// Get the attached buffer.
     /// Can be used to check if surface is mapped
     pub fn wl_buffer(&self) -> Option<&WlBuffer> {
+        self.buffer.as_deref()
+    }
+
+    /// Get a reference to the attached buffer
+    ///
+    /// The buffer is not released to the client, while the returned [`Buffer`] is kept alive.
+    pub fn buffer(&self) -> Option<&Buffer> {
         self.buffer.as_ref()
     }
 
@@ -297,12 +428,8 @@ pub fn on_commit_buffer_handler(surface: &WlSurface) {
         );
         for surf in &new_surfaces {
             add_destruction_hook(surf, |data| {
-                if let Some(buffer) = data
-                    .data_map
-                    .get::<RendererSurfaceStateUserData>()
-                    .and_then(|s| s.borrow_mut().buffer.take())
-                {
-                    buffer.release();
+                if let Some(state) = data.data_map.get::<RendererSurfaceStateUserData>() {
+                    state.borrow_mut().buffer = None;
                 }
             });
         }
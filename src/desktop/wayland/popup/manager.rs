@@ -1,6 +1,6 @@
 use crate::{
     input::{Seat, SeatHandler},
-    utils::{DeadResource, IsAlive, Logical, Point, Serial},
+    utils::{DeadResource, IsAlive, Logical, Point, Rectangle, Serial},
     wayland::{
         compositor::{get_role, with_states},
         seat::WaylandFocus,
@@ -189,6 +189,25 @@ impl PopupManager {
         })
     }
 
+    /// Recomputes the pending geometry of a popup, fitting it into `target`.
+    ///
+    /// `target` is given in the same coordinate space as `root_location`, the location of the window geometry
+    /// of the popup's root surface (see [`find_popup_root_surface`]), e.g. in global compositor space.
+    /// It is converted into the coordinate space of the popup's parent for [`PopupSurface::unconstrain`].
+    ///
+    /// [`PopupSurface::unconstrain`]: crate::wayland::shell::xdg::PopupSurface::unconstrain
+    pub fn unconstrain_popup(
+        popup: &PopupKind,
+        root_location: Point<i32, Logical>,
+        mut target: Rectangle<i32, Logical>,
+    ) {
+        target.loc -= root_location;
+        target.loc -= get_popup_toplevel_coords(popup);
+        match popup {
+            PopupKind::Xdg(surface) => surface.unconstrain(target),
+        }
+    }
+
     pub(crate) fn dismiss_popup(surface: &WlSurface, popup: &PopupKind) -> Result<(), DeadResource> {
         if !surface.alive() {
             return Err(DeadResource);
@@ -238,6 +257,47 @@ pub fn find_popup_root_surface(popup: &PopupKind) -> Result<WlSurface, DeadResou
     Ok(parent)
 }
 
+/// Computes the location of this popup's parent relative to its toplevel wl_surface's geometry.
+///
+/// This function will go up the parent stack and add up the relative locations. Useful for
+/// transforming a target rectangle given in toplevel coordinates into the parent coordinate
+/// space expected by [`PopupSurface::unconstrain`](crate::wayland::shell::xdg::PopupSurface::unconstrain).
+pub fn get_popup_toplevel_coords(popup: &PopupKind) -> Point<i32, Logical> {
+    let mut parent = match popup.parent() {
+        Some(parent) => parent,
+        None => return (0, 0).into(),
+    };
+
+    let mut offset: Point<i32, Logical> = (0, 0).into();
+    while get_role(&parent) == Some(XDG_POPUP_ROLE) {
+        offset += with_states(&parent, |states| {
+            states
+                .data_map
+                .get::<XdgPopupSurfaceData>()
+                .unwrap()
+                .lock()
+                .unwrap()
+                .current
+                .geometry
+                .loc
+        });
+        parent = with_states(&parent, |states| {
+            states
+                .data_map
+                .get::<XdgPopupSurfaceData>()
+                .unwrap()
+                .lock()
+                .unwrap()
+                .parent
+                .as_ref()
+                .cloned()
+                .unwrap()
+        });
+    }
+
+    offset
+}
+
 #[derive(Debug, Default, Clone)]
 struct PopupTree(Arc<Mutex<Vec<PopupNode>>>);
 
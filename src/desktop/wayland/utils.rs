@@ -350,6 +350,10 @@ impl OutputPresentationFeedback {
     ///
     /// The flags passed to this function will be combined with the stored
     /// per surface flags.
+    ///
+    /// `refresh` is the duration of a refresh cycle of the output in nanoseconds.
+    /// It should be zero, if the output has no constant refresh rate, e.g. with
+    /// variable refresh rate enabled.
     pub fn presented<T, Kind>(
         &mut self,
         time: T,
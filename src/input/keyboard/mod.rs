@@ -522,6 +522,11 @@ impl<D: SeatHandler + 'static> KeyboardHandle<D> {
         F: FnOnce(&mut D, &ModifiersState, KeysymHandle<'_>) -> FilterResult<T>,
     {
         trace!(self.arc.logger, "Handling keystroke"; "keycode" => keycode, "state" => format_args!("{:?}", state));
+        #[cfg(feature = "wayland_frontend")]
+        {
+            let seat = self.get_seat(data);
+            crate::wayland::idle_notify::seat_activity(data, &seat);
+        }
         let mut guard = self.arc.internal.lock().unwrap();
         let mods_changed = guard.key_input(keycode, state);
         let key_handle = KeysymHandle {
@@ -0,0 +1,166 @@
+use std::fmt;
+
+/// Named cursor icons
+///
+/// These are the cursors of the CSS `cursor` property, which are also used by the
+/// cursor-shape protocol and most xcursor themes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CursorIcon {
+    /// The platform-dependent default cursor, usually an arrow
+    #[default]
+    Default,
+    /// A context menu is available
+    ContextMenu,
+    /// Help information is available
+    Help,
+    /// The cursor is a pointer that indicates a link
+    Pointer,
+    /// The program is busy in the background, but the user can still interact with it
+    Progress,
+    /// The program is busy, and the user can not interact with it
+    Wait,
+    /// A cell or set of cells may be selected
+    Cell,
+    /// Cross cursor, often used to indicate selection in a bitmap
+    Crosshair,
+    /// Text may be selected
+    Text,
+    /// Vertical text may be selected
+    VerticalText,
+    /// An alias or shortcut is to be created
+    Alias,
+    /// Something is to be copied
+    Copy,
+    /// Something is to be moved
+    Move,
+    /// An item may not be dropped at the current location
+    NoDrop,
+    /// The requested action will not be carried out
+    NotAllowed,
+    /// Something can be grabbed
+    Grab,
+    /// Something is being grabbed
+    Grabbing,
+    /// The east border is to be moved
+    EResize,
+    /// The north border is to be moved
+    NResize,
+    /// The north-east corner is to be moved
+    NeResize,
+    /// The north-west corner is to be moved
+    NwResize,
+    /// The south border is to be moved
+    SResize,
+    /// The south-east corner is to be moved
+    SeResize,
+    /// The south-west corner is to be moved
+    SwResize,
+    /// The west border is to be moved
+    WResize,
+    /// The east and west borders are to be moved
+    EwResize,
+    /// The north and south borders are to be moved
+    NsResize,
+    /// The north-east and south-west corners are to be moved
+    NeswResize,
+    /// The north-west and south-east corners are to be moved
+    NwseResize,
+    /// The item or column can be resized horizontally
+    ColResize,
+    /// The item or row can be resized vertically
+    RowResize,
+    /// Something can be scrolled in any direction
+    AllScroll,
+    /// Something can be zoomed in
+    ZoomIn,
+    /// Something can be zoomed out
+    ZoomOut,
+}
+
+impl CursorIcon {
+    /// The name of the cursor as used by CSS and cursor themes
+    pub fn name(&self) -> &'static str {
+        match self {
+            CursorIcon::Default => "default",
+            CursorIcon::ContextMenu => "context-menu",
+            CursorIcon::Help => "help",
+            CursorIcon::Pointer => "pointer",
+            CursorIcon::Progress => "progress",
+            CursorIcon::Wait => "wait",
+            CursorIcon::Cell => "cell",
+            CursorIcon::Crosshair => "crosshair",
+            CursorIcon::Text => "text",
+            CursorIcon::VerticalText => "vertical-text",
+            CursorIcon::Alias => "alias",
+            CursorIcon::Copy => "copy",
+            CursorIcon::Move => "move",
+            CursorIcon::NoDrop => "no-drop",
+            CursorIcon::NotAllowed => "not-allowed",
+            CursorIcon::Grab => "grab",
+            CursorIcon::Grabbing => "grabbing",
+            CursorIcon::EResize => "e-resize",
+            CursorIcon::NResize => "n-resize",
+            CursorIcon::NeResize => "ne-resize",
+            CursorIcon::NwResize => "nw-resize",
+            CursorIcon::SResize => "s-resize",
+            CursorIcon::SeResize => "se-resize",
+            CursorIcon::SwResize => "sw-resize",
+            CursorIcon::WResize => "w-resize",
+            CursorIcon::EwResize => "ew-resize",
+            CursorIcon::NsResize => "ns-resize",
+            CursorIcon::NeswResize => "nesw-resize",
+            CursorIcon::NwseResize => "nwse-resize",
+            CursorIcon::ColResize => "col-resize",
+            CursorIcon::RowResize => "row-resize",
+            CursorIcon::AllScroll => "all-scroll",
+            CursorIcon::ZoomIn => "zoom-in",
+            CursorIcon::ZoomOut => "zoom-out",
+        }
+    }
+
+    /// Legacy names of the cursor, that older cursor themes might use instead of [`CursorIcon::name`]
+    pub fn alt_names(&self) -> &'static [&'static str] {
+        match self {
+            CursorIcon::Default => &["left_ptr", "arrow", "top_left_arrow", "left_arrow"],
+            CursorIcon::ContextMenu => &[],
+            CursorIcon::Help => &["question_arrow", "whats_this"],
+            CursorIcon::Pointer => &["hand2", "hand1", "hand", "pointing_hand"],
+            CursorIcon::Progress => &["left_ptr_watch", "half-busy"],
+            CursorIcon::Wait => &["watch", "clock"],
+            CursorIcon::Cell => &["plus"],
+            CursorIcon::Crosshair => &["cross"],
+            CursorIcon::Text => &["xterm", "ibeam"],
+            CursorIcon::VerticalText => &[],
+            CursorIcon::Alias => &["link"],
+            CursorIcon::Copy => &[],
+            CursorIcon::Move => &[],
+            CursorIcon::NoDrop => &["circle"],
+            CursorIcon::NotAllowed => &["crossed_circle", "forbidden"],
+            CursorIcon::Grab => &["openhand", "fleur"],
+            CursorIcon::Grabbing => &["closedhand"],
+            CursorIcon::EResize => &["right_side"],
+            CursorIcon::NResize => &["top_side"],
+            CursorIcon::NeResize => &["top_right_corner"],
+            CursorIcon::NwResize => &["top_left_corner"],
+            CursorIcon::SResize => &["bottom_side"],
+            CursorIcon::SeResize => &["bottom_right_corner"],
+            CursorIcon::SwResize => &["bottom_left_corner"],
+            CursorIcon::WResize => &["left_side"],
+            CursorIcon::EwResize => &["h_double_arrow", "size_hor"],
+            CursorIcon::NsResize => &["v_double_arrow", "size_ver"],
+            CursorIcon::NeswResize => &["fd_double_arrow", "size_bdiag"],
+            CursorIcon::NwseResize => &["bd_double_arrow", "size_fdiag"],
+            CursorIcon::ColResize => &["split_h", "h_double_arrow", "sb_h_double_arrow"],
+            CursorIcon::RowResize => &["split_v", "v_double_arrow", "sb_v_double_arrow"],
+            CursorIcon::AllScroll => &["size_all"],
+            CursorIcon::ZoomIn => &[],
+            CursorIcon::ZoomOut => &[],
+        }
+    }
+}
+
+impl fmt::Display for CursorIcon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
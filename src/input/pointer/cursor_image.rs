@@ -4,6 +4,8 @@ use wayland_server::protocol::wl_surface::WlSurface;
 use crate::utils::{Logical, Point};
 use std::sync::Mutex;
 
+use super::CursorIcon;
+
 /// The role representing a surface set as the pointer cursor
 #[derive(Debug, Default, Copy, Clone)]
 pub struct CursorImageAttributes {
@@ -33,6 +35,8 @@ pub enum CursorImageStatus {
     Hidden,
     /// The compositor should draw its cursor
     Default,
+    /// The compositor should draw the given cursor of its cursor theme
+    Named(CursorIcon),
 
     // TODO bitmap, dmabuf cursor? Or let the compositor handle everything through "Default"
     /// The cursor should be drawn using this surface as an image
@@ -0,0 +1,276 @@
+//! Server-side cursor themes
+//!
+//! This module loads cursors from xcursor themes, so the compositor can draw its default cursor
+//! and the cursors requested through [`CursorImageStatus::Named`](super::CursorImageStatus::Named)
+//! consistently at the right size, instead of every client loading its own theme.
+//!
+//! Cursor frames are provided as [`MemoryRenderBuffer`]s, which can be rendered using a
+//! [`MemoryRenderBufferRenderElement`](crate::backend::renderer::element::memory::MemoryRenderBufferRenderElement).
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use smithay::input::pointer::{cursor_theme::CursorTheme, CursorIcon};
+//!
+//! // Load the theme configured by `XCURSOR_THEME` and `XCURSOR_SIZE`
+//! let mut theme = CursorTheme::load_from_env(None);
+//!
+//! if let Some(cursor) = theme.get_cursor(CursorIcon::Pointer) {
+//!     // Get the frame to display at scale 1, 100ms into the animation
+//!     let frame = cursor.frame(1, Duration::from_millis(100));
+//!     // `frame.buffer()` is to be drawn at the pointer location minus `frame.hotspot()`
+//! }
+//! ```
+
+use std::{
+    collections::HashMap,
+    io::Read,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use slog::warn;
+use xcursor::parser::{parse_xcursor, Image};
+
+use crate::{
+    backend::renderer::element::memory::MemoryRenderBuffer,
+    utils::{Logical, Point, Transform},
+};
+
+use super::CursorIcon;
+
+/// A xcursor theme
+///
+/// Cursors are loaded lazily and cached by the theme.
+#[derive(Debug)]
+pub struct CursorTheme {
+    theme: xcursor::CursorTheme,
+    name: String,
+    size: u32,
+    cursors: HashMap<CursorIcon, Option<Cursor>>,
+    log: slog::Logger,
+}
+
+impl CursorTheme {
+    /// Load the xcursor theme with the given name
+    ///
+    /// `size` is the nominal size of the cursors at scale 1.
+    pub fn load<L>(name: &str, size: u32, logger: L) -> CursorTheme
+    where
+        L: Into<Option<::slog::Logger>>,
+    {
+        let log = crate::slog_or_fallback(logger).new(slog::o!("smithay_module" => "cursor_theme"));
+
+        CursorTheme {
+            theme: xcursor::CursorTheme::load(name),
+            name: name.to_owned(),
+            size,
+            cursors: HashMap::new(),
+            log,
+        }
+    }
+
+    /// Load the xcursor theme configured by the `XCURSOR_THEME` and `XCURSOR_SIZE` environment variables
+    ///
+    /// Falls back to the `default` theme and a size of 24, if they are unset.
+    pub fn load_from_env<L>(logger: L) -> CursorTheme
+    where
+        L: Into<Option<::slog::Logger>>,
+    {
+        let name = std::env::var("XCURSOR_THEME")
+            .ok()
+            .unwrap_or_else(|| "default".into());
+        let size = std::env::var("XCURSOR_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(24);
+
+        CursorTheme::load(&name, size, logger)
+    }
+
+    /// Name of the theme
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Nominal size of the cursors at scale 1
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Get the cursor for the given icon
+    ///
+    /// The cursor is looked up by the [name](CursorIcon::name) of the icon, and then by its
+    /// [legacy names](CursorIcon::alt_names). Returns `None` if the theme has no cursor for the icon.
+    pub fn get_cursor(&mut self, icon: CursorIcon) -> Option<Cursor> {
+        if let Some(cursor) = self.cursors.get(&icon) {
+            return cursor.clone();
+        }
+
+        let cursor = std::iter::once(icon.name())
+            .chain(icon.alt_names().iter().copied())
+            .find_map(|name| match load_images(&self.theme, name) {
+                Ok(images) => Cursor::from_images(self.size, images),
+                Err(Error::NotFound) => None,
+                Err(err) => {
+                    warn!(self.log, "Unable to load cursor {}: {}", name, err);
+                    None
+                }
+            });
+        self.cursors.insert(icon, cursor.clone());
+        cursor
+    }
+}
+
+/// A cursor of a theme, possibly animated
+///
+/// This is a cheap to clone handle, buffers of the cursor are shared between clones.
+#[derive(Debug, Clone)]
+pub struct Cursor(Arc<CursorInner>);
+
+#[derive(Debug)]
+struct CursorInner {
+    size: u32,
+    images: Vec<CursorImage>,
+}
+
+#[derive(Debug)]
+struct CursorImage {
+    image: Image,
+    buffers: Mutex<HashMap<i32, MemoryRenderBuffer>>,
+}
+
+impl Cursor {
+    /// Create a cursor from xcursor images
+    ///
+    /// `size` is the nominal size of the cursor at scale 1, images are chosen by
+    /// the nominal size closest to it. Returns `None` if `images` is empty.
+    pub fn from_images(size: u32, images: Vec<Image>) -> Option<Cursor> {
+        if images.is_empty() {
+            return None;
+        }
+
+        let images = images
+            .into_iter()
+            .map(|image| CursorImage {
+                image,
+                buffers: Mutex::new(HashMap::new()),
+            })
+            .collect();
+        Some(Cursor(Arc::new(CursorInner { size, images })))
+    }
+
+    /// Get the xcursor image to display at the given scale and animation time
+    ///
+    /// `time` is the time elapsed since the start of the animation.
+    pub fn image(&self, scale: i32, time: Duration) -> &Image {
+        &self.0.images[self.image_index(scale, time)].image
+    }
+
+    /// Get the frame to display at the given scale and animation time
+    ///
+    /// `time` is the time elapsed since the start of the animation.
+    pub fn frame(&self, scale: i32, time: Duration) -> CursorFrame {
+        let scale = scale.max(1);
+        let image = &self.0.images[self.image_index(scale, time)];
+
+        let buffer = image
+            .buffers
+            .lock()
+            .unwrap()
+            .entry(scale)
+            .or_insert_with(|| {
+                MemoryRenderBuffer::from_memory(
+                    &image.image.pixels_rgba,
+                    (image.image.width as i32, image.image.height as i32),
+                    scale,
+                    Transform::Normal,
+                    None,
+                )
+            })
+            .clone();
+
+        CursorFrame {
+            buffer,
+            hotspot: Point::from((image.image.xhot as i32 / scale, image.image.yhot as i32 / scale)),
+            delay: Duration::from_millis(image.image.delay as u64),
+        }
+    }
+
+    fn image_index(&self, scale: i32, time: Duration) -> usize {
+        let size = self.0.size * scale.max(1) as u32;
+
+        // Follow the nominal size of the cursor to choose the nearest
+        let nominal_size = self
+            .0
+            .images
+            .iter()
+            .map(|image| image.image.size)
+            .min_by_key(|nominal_size| (size as i64 - *nominal_size as i64).abs())
+            .unwrap();
+        let frames = || {
+            self.0
+                .images
+                .iter()
+                .enumerate()
+                .filter(move |(_, image)| image.image.size == nominal_size)
+        };
+
+        let total = frames().fold(0, |acc, (_, image)| acc + image.image.delay);
+        if total == 0 {
+            return frames().next().unwrap().0;
+        }
+
+        let mut millis = (time.as_millis() % total as u128) as u32;
+        for (idx, image) in frames() {
+            if millis < image.image.delay {
+                return idx;
+            }
+            millis -= image.image.delay;
+        }
+
+        unreachable!()
+    }
+}
+
+/// A single frame of a [`Cursor`]
+#[derive(Debug, Clone)]
+pub struct CursorFrame {
+    buffer: MemoryRenderBuffer,
+    hotspot: Point<i32, Logical>,
+    delay: Duration,
+}
+
+impl CursorFrame {
+    /// The buffer containing the image of this frame
+    pub fn buffer(&self) -> &MemoryRenderBuffer {
+        &self.buffer
+    }
+
+    /// Location of the hotspot relative to the top-left corner of the frame
+    pub fn hotspot(&self) -> Point<i32, Logical> {
+        self.hotspot
+    }
+
+    /// How long this frame is displayed, if the cursor is animated
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+enum Error {
+    #[error("Theme has no such cursor")]
+    NotFound,
+    #[error("Error opening xcursor file: {0}")]
+    File(#[from] std::io::Error),
+    #[error("Failed to parse XCursor file")]
+    Parse,
+}
+
+fn load_images(theme: &xcursor::CursorTheme, name: &str) -> Result<Vec<Image>, Error> {
+    let icon_path = theme.load_icon(name).ok_or(Error::NotFound)?;
+    let mut cursor_file = std::fs::File::open(icon_path)?;
+    let mut cursor_data = Vec::new();
+    cursor_file.read_to_end(&mut cursor_data)?;
+    parse_xcursor(&cursor_data).ok_or(Error::Parse)
+}
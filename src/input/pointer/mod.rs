@@ -6,15 +6,21 @@ use std::{
 };
 
 use crate::{
-    backend::input::{Axis, AxisSource, ButtonState},
+    backend::input::{Axis, AxisRelativeDirection, AxisSource, ButtonState},
     input::{Seat, SeatHandler},
     utils::Serial,
     utils::{IsAlive, Logical, Point},
 };
 
+mod cursor_icon;
+pub use cursor_icon::CursorIcon;
+
 mod cursor_image;
 pub use cursor_image::{CursorImageAttributes, CursorImageStatus, CursorImageSurfaceData};
 
+#[cfg(feature = "xcursor")]
+pub mod cursor_theme;
+
 mod grab;
 use grab::{DefaultGrab, GrabStatus};
 pub use grab::{GrabStartData, PointerGrab};
@@ -34,6 +40,14 @@ pub struct PointerHandle<D: SeatHandler> {
     pub(crate) known_pointers: Arc<Mutex<Vec<wayland_server::protocol::wl_pointer::WlPointer>>>,
     #[cfg(feature = "wayland_frontend")]
     pub(crate) known_relative_pointers: Arc<Mutex<Vec<wayland_protocols::wp::relative_pointer::zv1::server::zwp_relative_pointer_v1::ZwpRelativePointerV1>>>,
+    #[cfg(feature = "wayland_frontend")]
+    pub(crate) known_swipe_gestures: Arc<Mutex<Vec<wayland_protocols::wp::pointer_gestures::zv1::server::zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1>>>,
+    #[cfg(feature = "wayland_frontend")]
+    pub(crate) known_pinch_gestures: Arc<Mutex<Vec<wayland_protocols::wp::pointer_gestures::zv1::server::zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1>>>,
+    #[cfg(feature = "wayland_frontend")]
+    pub(crate) known_hold_gestures: Arc<Mutex<Vec<wayland_protocols::wp::pointer_gestures::zv1::server::zwp_pointer_gesture_hold_v1::ZwpPointerGestureHoldV1>>>,
+    #[cfg(feature = "wayland_frontend")]
+    pub(crate) constraint_focus: Arc<Mutex<crate::wayland::pointer_constraints::ConstraintFocus>>,
 }
 
 #[cfg(not(feature = "wayland_frontend"))]
@@ -58,6 +72,10 @@ where
             .field("inner", &self.inner)
             .field("known_pointers", &self.known_pointers)
             .field("known_relative_pointers", &self.known_relative_pointers)
+            .field("known_swipe_gestures", &self.known_swipe_gestures)
+            .field("known_pinch_gestures", &self.known_pinch_gestures)
+            .field("known_hold_gestures", &self.known_hold_gestures)
+            .field("constraint_focus", &self.constraint_focus)
             .finish()
     }
 }
@@ -70,6 +88,14 @@ impl<D: SeatHandler> Clone for PointerHandle<D> {
             known_pointers: self.known_pointers.clone(),
             #[cfg(feature = "wayland_frontend")]
             known_relative_pointers: self.known_relative_pointers.clone(),
+            #[cfg(feature = "wayland_frontend")]
+            known_swipe_gestures: self.known_swipe_gestures.clone(),
+            #[cfg(feature = "wayland_frontend")]
+            known_pinch_gestures: self.known_pinch_gestures.clone(),
+            #[cfg(feature = "wayland_frontend")]
+            known_hold_gestures: self.known_hold_gestures.clone(),
+            #[cfg(feature = "wayland_frontend")]
+            constraint_focus: self.constraint_focus.clone(),
         }
     }
 }
@@ -97,6 +123,22 @@ where
     fn axis(&self, seat: &Seat<D>, data: &mut D, frame: AxisFrame);
     /// A pointer of a given seat left this handler
     fn leave(&self, seat: &Seat<D>, data: &mut D, serial: Serial, time: u32);
+    /// A pointer of a given seat started a swipe gesture
+    fn gesture_swipe_begin(&self, seat: &Seat<D>, data: &mut D, event: &GestureSwipeBeginEvent);
+    /// A pointer of a given seat updated a swipe gesture
+    fn gesture_swipe_update(&self, seat: &Seat<D>, data: &mut D, event: &GestureSwipeUpdateEvent);
+    /// A pointer of a given seat ended a swipe gesture
+    fn gesture_swipe_end(&self, seat: &Seat<D>, data: &mut D, event: &GestureSwipeEndEvent);
+    /// A pointer of a given seat started a pinch gesture
+    fn gesture_pinch_begin(&self, seat: &Seat<D>, data: &mut D, event: &GesturePinchBeginEvent);
+    /// A pointer of a given seat updated a pinch gesture
+    fn gesture_pinch_update(&self, seat: &Seat<D>, data: &mut D, event: &GesturePinchUpdateEvent);
+    /// A pointer of a given seat ended a pinch gesture
+    fn gesture_pinch_end(&self, seat: &Seat<D>, data: &mut D, event: &GesturePinchEndEvent);
+    /// A pointer of a given seat started a hold gesture
+    fn gesture_hold_begin(&self, seat: &Seat<D>, data: &mut D, event: &GestureHoldBeginEvent);
+    /// A pointer of a given seat ended a hold gesture
+    fn gesture_hold_end(&self, seat: &Seat<D>, data: &mut D, event: &GestureHoldEndEvent);
 }
 
 impl<D: SeatHandler + 'static> PointerHandle<D> {
@@ -107,6 +149,14 @@ impl<D: SeatHandler + 'static> PointerHandle<D> {
             known_pointers: Arc::new(Mutex::new(Vec::new())),
             #[cfg(feature = "wayland_frontend")]
             known_relative_pointers: Arc::new(Mutex::new(Vec::new())),
+            #[cfg(feature = "wayland_frontend")]
+            known_swipe_gestures: Arc::new(Mutex::new(Vec::new())),
+            #[cfg(feature = "wayland_frontend")]
+            known_pinch_gestures: Arc::new(Mutex::new(Vec::new())),
+            #[cfg(feature = "wayland_frontend")]
+            known_hold_gestures: Arc::new(Mutex::new(Vec::new())),
+            #[cfg(feature = "wayland_frontend")]
+            constraint_focus: Arc::new(Mutex::new(Default::default())),
         }
     }
 
@@ -164,6 +214,11 @@ impl<D: SeatHandler + 'static> PointerHandle<D> {
     ///
     /// This will internally take care of notifying the appropriate client objects
     /// of enter/motion/leave events.
+    ///
+    /// If a [pointer constraint](crate::wayland::pointer_constraints) is active, the location
+    /// is clamped to the region of the constraint (or kept in place, if the pointer is locked)
+    /// and the focus is not changed. Use [`PointerHandle::current_location`] to get the resulting
+    /// location of the pointer.
     pub fn motion(
         &self,
         data: &mut D,
@@ -171,8 +226,19 @@ impl<D: SeatHandler + 'static> PointerHandle<D> {
         event: &MotionEvent,
     ) {
         let mut inner = self.inner.lock().unwrap();
+        #[cfg(feature = "wayland_frontend")]
+        let (focus, event) = &crate::wayland::pointer_constraints::constrain_motion(
+            self,
+            inner.location,
+            &inner.focus,
+            focus,
+            event,
+        );
         inner.pending_focus = focus.clone();
         let seat = self.get_seat(data);
+        #[cfg(feature = "wayland_frontend")]
+        crate::wayland::idle_notify::seat_activity(data, &seat);
+        let focus = focus.clone();
         inner.with_grab(&seat, move |mut handle, grab| {
             grab.motion(data, &mut handle, focus, event);
         });
@@ -192,6 +258,8 @@ impl<D: SeatHandler + 'static> PointerHandle<D> {
         let mut inner = self.inner.lock().unwrap();
         inner.pending_focus = focus.clone();
         let seat = self.get_seat(data);
+        #[cfg(feature = "wayland_frontend")]
+        crate::wayland::idle_notify::seat_activity(data, &seat);
         inner.with_grab(&seat, move |mut handle, grab| {
             grab.relative_motion(data, &mut handle, focus, event);
         });
@@ -212,6 +280,8 @@ impl<D: SeatHandler + 'static> PointerHandle<D> {
             }
         }
         let seat = self.get_seat(data);
+        #[cfg(feature = "wayland_frontend")]
+        crate::wayland::idle_notify::seat_activity(data, &seat);
         inner.with_grab(&seat, |mut handle, grab| {
             grab.button(data, &mut handle, event);
         });
@@ -222,11 +292,117 @@ impl<D: SeatHandler + 'static> PointerHandle<D> {
     /// A single frame will group multiple scroll events as if they happened in the same instance.
     pub fn axis(&self, data: &mut D, details: AxisFrame) {
         let seat = self.get_seat(data);
+        #[cfg(feature = "wayland_frontend")]
+        crate::wayland::idle_notify::seat_activity(data, &seat);
         self.inner.lock().unwrap().with_grab(&seat, |mut handle, grab| {
             grab.axis(data, &mut handle, details);
         });
     }
 
+    /// Notify that a swipe gesture started
+    ///
+    /// This will internally send the appropriate gesture event to the client
+    /// objects matching with the currently focused surface.
+    pub fn gesture_swipe_begin(&self, data: &mut D, event: &GestureSwipeBeginEvent) {
+        let seat = self.get_seat(data);
+        #[cfg(feature = "wayland_frontend")]
+        crate::wayland::idle_notify::seat_activity(data, &seat);
+        self.inner.lock().unwrap().with_grab(&seat, |mut handle, grab| {
+            grab.gesture_swipe_begin(data, &mut handle, event);
+        });
+    }
+
+    /// Notify that a swipe gesture was updated
+    ///
+    /// This will internally send the appropriate gesture event to the client
+    /// objects matching with the currently focused surface.
+    pub fn gesture_swipe_update(&self, data: &mut D, event: &GestureSwipeUpdateEvent) {
+        let seat = self.get_seat(data);
+        #[cfg(feature = "wayland_frontend")]
+        crate::wayland::idle_notify::seat_activity(data, &seat);
+        self.inner.lock().unwrap().with_grab(&seat, |mut handle, grab| {
+            grab.gesture_swipe_update(data, &mut handle, event);
+        });
+    }
+
+    /// Notify that a swipe gesture ended
+    ///
+    /// This will internally send the appropriate gesture event to the client
+    /// objects matching with the currently focused surface.
+    pub fn gesture_swipe_end(&self, data: &mut D, event: &GestureSwipeEndEvent) {
+        let seat = self.get_seat(data);
+        #[cfg(feature = "wayland_frontend")]
+        crate::wayland::idle_notify::seat_activity(data, &seat);
+        self.inner.lock().unwrap().with_grab(&seat, |mut handle, grab| {
+            grab.gesture_swipe_end(data, &mut handle, event);
+        });
+    }
+
+    /// Notify that a pinch gesture started
+    ///
+    /// This will internally send the appropriate gesture event to the client
+    /// objects matching with the currently focused surface.
+    pub fn gesture_pinch_begin(&self, data: &mut D, event: &GesturePinchBeginEvent) {
+        let seat = self.get_seat(data);
+        #[cfg(feature = "wayland_frontend")]
+        crate::wayland::idle_notify::seat_activity(data, &seat);
+        self.inner.lock().unwrap().with_grab(&seat, |mut handle, grab| {
+            grab.gesture_pinch_begin(data, &mut handle, event);
+        });
+    }
+
+    /// Notify that a pinch gesture was updated
+    ///
+    /// This will internally send the appropriate gesture event to the client
+    /// objects matching with the currently focused surface.
+    pub fn gesture_pinch_update(&self, data: &mut D, event: &GesturePinchUpdateEvent) {
+        let seat = self.get_seat(data);
+        #[cfg(feature = "wayland_frontend")]
+        crate::wayland::idle_notify::seat_activity(data, &seat);
+        self.inner.lock().unwrap().with_grab(&seat, |mut handle, grab| {
+            grab.gesture_pinch_update(data, &mut handle, event);
+        });
+    }
+
+    /// Notify that a pinch gesture ended
+    ///
+    /// This will internally send the appropriate gesture event to the client
+    /// objects matching with the currently focused surface.
+    pub fn gesture_pinch_end(&self, data: &mut D, event: &GesturePinchEndEvent) {
+        let seat = self.get_seat(data);
+        #[cfg(feature = "wayland_frontend")]
+        crate::wayland::idle_notify::seat_activity(data, &seat);
+        self.inner.lock().unwrap().with_grab(&seat, |mut handle, grab| {
+            grab.gesture_pinch_end(data, &mut handle, event);
+        });
+    }
+
+    /// Notify that a hold gesture started
+    ///
+    /// This will internally send the appropriate gesture event to the client
+    /// objects matching with the currently focused surface.
+    pub fn gesture_hold_begin(&self, data: &mut D, event: &GestureHoldBeginEvent) {
+        let seat = self.get_seat(data);
+        #[cfg(feature = "wayland_frontend")]
+        crate::wayland::idle_notify::seat_activity(data, &seat);
+        self.inner.lock().unwrap().with_grab(&seat, |mut handle, grab| {
+            grab.gesture_hold_begin(data, &mut handle, event);
+        });
+    }
+
+    /// Notify that a hold gesture ended
+    ///
+    /// This will internally send the appropriate gesture event to the client
+    /// objects matching with the currently focused surface.
+    pub fn gesture_hold_end(&self, data: &mut D, event: &GestureHoldEndEvent) {
+        let seat = self.get_seat(data);
+        #[cfg(feature = "wayland_frontend")]
+        crate::wayland::idle_notify::seat_activity(data, &seat);
+        self.inner.lock().unwrap().with_grab(&seat, |mut handle, grab| {
+            grab.gesture_hold_end(data, &mut handle, event);
+        });
+    }
+
     /// Access the current location of this pointer in the global space
     pub fn current_location(&self) -> Point<f64, Logical> {
         self.inner.lock().unwrap().location
@@ -365,6 +541,86 @@ impl<'a, D: SeatHandler + 'static> PointerInnerHandle<'a, D> {
             focused.axis(self.seat, data, details);
         }
     }
+
+    /// Notify that a swipe gesture started
+    ///
+    /// This will internally send the appropriate gesture event to the client
+    /// objects matching with the currently focused surface.
+    pub fn gesture_swipe_begin(&mut self, data: &mut D, event: &GestureSwipeBeginEvent) {
+        if let Some((focused, _)) = self.inner.focus.as_mut() {
+            focused.gesture_swipe_begin(self.seat, data, event);
+        }
+    }
+
+    /// Notify that a swipe gesture was updated
+    ///
+    /// This will internally send the appropriate gesture event to the client
+    /// objects matching with the currently focused surface.
+    pub fn gesture_swipe_update(&mut self, data: &mut D, event: &GestureSwipeUpdateEvent) {
+        if let Some((focused, _)) = self.inner.focus.as_mut() {
+            focused.gesture_swipe_update(self.seat, data, event);
+        }
+    }
+
+    /// Notify that a swipe gesture ended
+    ///
+    /// This will internally send the appropriate gesture event to the client
+    /// objects matching with the currently focused surface.
+    pub fn gesture_swipe_end(&mut self, data: &mut D, event: &GestureSwipeEndEvent) {
+        if let Some((focused, _)) = self.inner.focus.as_mut() {
+            focused.gesture_swipe_end(self.seat, data, event);
+        }
+    }
+
+    /// Notify that a pinch gesture started
+    ///
+    /// This will internally send the appropriate gesture event to the client
+    /// objects matching with the currently focused surface.
+    pub fn gesture_pinch_begin(&mut self, data: &mut D, event: &GesturePinchBeginEvent) {
+        if let Some((focused, _)) = self.inner.focus.as_mut() {
+            focused.gesture_pinch_begin(self.seat, data, event);
+        }
+    }
+
+    /// Notify that a pinch gesture was updated
+    ///
+    /// This will internally send the appropriate gesture event to the client
+    /// objects matching with the currently focused surface.
+    pub fn gesture_pinch_update(&mut self, data: &mut D, event: &GesturePinchUpdateEvent) {
+        if let Some((focused, _)) = self.inner.focus.as_mut() {
+            focused.gesture_pinch_update(self.seat, data, event);
+        }
+    }
+
+    /// Notify that a pinch gesture ended
+    ///
+    /// This will internally send the appropriate gesture event to the client
+    /// objects matching with the currently focused surface.
+    pub fn gesture_pinch_end(&mut self, data: &mut D, event: &GesturePinchEndEvent) {
+        if let Some((focused, _)) = self.inner.focus.as_mut() {
+            focused.gesture_pinch_end(self.seat, data, event);
+        }
+    }
+
+    /// Notify that a hold gesture started
+    ///
+    /// This will internally send the appropriate gesture event to the client
+    /// objects matching with the currently focused surface.
+    pub fn gesture_hold_begin(&mut self, data: &mut D, event: &GestureHoldBeginEvent) {
+        if let Some((focused, _)) = self.inner.focus.as_mut() {
+            focused.gesture_hold_begin(self.seat, data, event);
+        }
+    }
+
+    /// Notify that a hold gesture ended
+    ///
+    /// This will internally send the appropriate gesture event to the client
+    /// objects matching with the currently focused surface.
+    pub fn gesture_hold_end(&mut self, data: &mut D, event: &GestureHoldEndEvent) {
+        if let Some((focused, _)) = self.inner.focus.as_mut() {
+            focused.gesture_hold_end(self.seat, data, event);
+        }
+    }
 }
 
 pub(crate) struct PointerInternal<D: SeatHandler> {
@@ -585,6 +841,94 @@ pub struct ButtonEvent {
     pub state: ButtonState,
 }
 
+/// Swipe gesture begin event
+#[derive(Debug, Clone, Copy)]
+pub struct GestureSwipeBeginEvent {
+    /// Serial of the event
+    pub serial: Serial,
+    /// Timestamp of the event, with millisecond granularity
+    pub time: u32,
+    /// Number of fingers of the gesture
+    pub fingers: u32,
+}
+
+/// Swipe gesture update event
+#[derive(Debug, Clone, Copy)]
+pub struct GestureSwipeUpdateEvent {
+    /// Timestamp of the event, with millisecond granularity
+    pub time: u32,
+    /// Delta of the center of the gesture since the last update
+    pub delta: Point<f64, Logical>,
+}
+
+/// Swipe gesture end event
+#[derive(Debug, Clone, Copy)]
+pub struct GestureSwipeEndEvent {
+    /// Serial of the event
+    pub serial: Serial,
+    /// Timestamp of the event, with millisecond granularity
+    pub time: u32,
+    /// Whether the gesture was cancelled
+    pub cancelled: bool,
+}
+
+/// Pinch gesture begin event
+#[derive(Debug, Clone, Copy)]
+pub struct GesturePinchBeginEvent {
+    /// Serial of the event
+    pub serial: Serial,
+    /// Timestamp of the event, with millisecond granularity
+    pub time: u32,
+    /// Number of fingers of the gesture
+    pub fingers: u32,
+}
+
+/// Pinch gesture update event
+#[derive(Debug, Clone, Copy)]
+pub struct GesturePinchUpdateEvent {
+    /// Timestamp of the event, with millisecond granularity
+    pub time: u32,
+    /// Delta of the center of the gesture since the last update
+    pub delta: Point<f64, Logical>,
+    /// Absolute scale compared to the begin of the gesture
+    pub scale: f64,
+    /// Relative angle in degrees clockwise since the last update
+    pub rotation: f64,
+}
+
+/// Pinch gesture end event
+#[derive(Debug, Clone, Copy)]
+pub struct GesturePinchEndEvent {
+    /// Serial of the event
+    pub serial: Serial,
+    /// Timestamp of the event, with millisecond granularity
+    pub time: u32,
+    /// Whether the gesture was cancelled
+    pub cancelled: bool,
+}
+
+/// Hold gesture begin event
+#[derive(Debug, Clone, Copy)]
+pub struct GestureHoldBeginEvent {
+    /// Serial of the event
+    pub serial: Serial,
+    /// Timestamp of the event, with millisecond granularity
+    pub time: u32,
+    /// Number of fingers of the gesture
+    pub fingers: u32,
+}
+
+/// Hold gesture end event
+#[derive(Debug, Clone, Copy)]
+pub struct GestureHoldEndEvent {
+    /// Serial of the event
+    pub serial: Serial,
+    /// Timestamp of the event, with millisecond granularity
+    pub time: u32,
+    /// Whether the gesture was cancelled
+    pub cancelled: bool,
+}
+
 /// A frame of pointer axis events.
 /// Frames of axis events should be considers as one logical action.
 ///
@@ -607,6 +951,12 @@ pub struct AxisFrame {
     pub axis: (f64, f64),
     /// Discrete representation of scroll value per axis, if available
     pub discrete: Option<(i32, i32)>,
+    /// High-resolution representation of scroll value per axis, if available
+    ///
+    /// A value of 120 corresponds to one discrete step.
+    pub v120: Option<(i32, i32)>,
+    /// Direction of the scroll value per axis relative to the physical motion
+    pub relative_direction: (AxisRelativeDirection, AxisRelativeDirection),
     /// If the axis is considered having stoped movement
     ///
     /// Only useful in conjunction of AxisSource::Finger events
@@ -621,6 +971,8 @@ impl AxisFrame {
             time,
             axis: (0.0, 0.0),
             discrete: None,
+            v120: None,
+            relative_direction: (AxisRelativeDirection::Identical, AxisRelativeDirection::Identical),
             stop: (false, false),
         }
     }
@@ -655,6 +1007,39 @@ impl AxisFrame {
         self
     }
 
+    /// Specify high-resolution scrolling steps additionally to the computed value.
+    ///
+    /// A value of 120 corresponds to one discrete step, high-resolution scroll wheels
+    /// may send fractions of that. Clients binding version 8 or higher of `wl_pointer`
+    /// receive these values instead of the steps set with [`AxisFrame::discrete`].
+    pub fn v120(mut self, axis: Axis, value: i32) -> Self {
+        let v120 = self.v120.get_or_insert_with(Default::default);
+        match axis {
+            Axis::Horizontal => {
+                v120.0 = value;
+            }
+            Axis::Vertical => {
+                v120.1 = value;
+            }
+        };
+        self
+    }
+
+    /// Specify the direction of the scroll value relative to the physical motion.
+    ///
+    /// This event is optional, e.g. natural scrolling results in an inverted direction.
+    pub fn relative_direction(mut self, axis: Axis, direction: AxisRelativeDirection) -> Self {
+        match axis {
+            Axis::Horizontal => {
+                self.relative_direction.0 = direction;
+            }
+            Axis::Vertical => {
+                self.relative_direction.1 = direction;
+            }
+        };
+        self
+    }
+
     /// The actual scroll value. This event is the only required one, but can also
     /// be send multiple times. The values off one frame will be accumulated by the client.
     pub fn value(mut self, axis: Axis, value: f64) -> Self {
@@ -22,8 +22,10 @@ pub(crate) struct SealedFile {
 
 impl SealedFile {
     pub fn new(name: CString, contents: CString) -> Result<Self, std::io::Error> {
-        let contents = contents.as_bytes_with_nul();
+        Self::with_data(name, contents.as_bytes_with_nul())
+    }
 
+    pub fn with_data(name: CString, contents: &[u8]) -> Result<Self, std::io::Error> {
         let fd = nix::sys::memfd::memfd_create(
             &name,
             MemFdCreateFlag::MFD_CLOEXEC | MemFdCreateFlag::MFD_ALLOW_SEALING,
@@ -51,7 +53,7 @@ impl SealedFile {
         })
     }
 
-    // Only used in KeymapFile which is under the wayland_frontend feature
+    // Only used in KeymapFile and the dmabuf feedback, which are under the wayland_frontend feature
     pub fn size(&self) -> usize {
         self.size
     }
@@ -15,9 +15,12 @@ use wayland_server::{
     DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource, WEnum,
 };
 
-use crate::utils::{
-    alive_tracker::{AliveTracker, IsAlive},
-    Logical, Point,
+use crate::{
+    output::WeakOutput,
+    utils::{
+        alive_tracker::{AliveTracker, IsAlive},
+        Logical, Point,
+    },
 };
 
 use super::{
@@ -29,6 +32,20 @@ use super::{
 
 use slog::trace;
 
+pub(super) fn post_commit<D>(state: &mut D, handle: &DisplayHandle, surface: &WlSurface)
+where
+    D: CompositorHandler + 'static,
+{
+    PrivateSurfaceData::invoke_post_commit_hooks(handle, surface);
+
+    trace!(
+        state.compositor_state().log,
+        "Calling user implementation for wl_surface.commit"
+    );
+
+    state.commit(surface);
+}
+
 /*
  * wl_compositor
  */
@@ -80,6 +97,7 @@ where
                     id,
                     SurfaceUserData {
                         inner: PrivateSurfaceData::new(),
+                        outputs: Mutex::new(Vec::new()),
                         alive_tracker: Default::default(),
                     },
                 );
@@ -146,6 +164,8 @@ impl Cacheable for SurfaceAttributes {
 #[derive(Debug)]
 pub struct SurfaceUserData {
     pub(crate) inner: Mutex<PrivateSurfaceData>,
+    /// Outputs the surface was entered on using [`Output::enter`](crate::output::Output::enter)
+    pub(crate) outputs: Mutex<Vec<WeakOutput>>,
     alive_tracker: AliveTracker,
 }
 
@@ -243,16 +263,21 @@ where
             wl_surface::Request::Commit => {
                 PrivateSurfaceData::invoke_pre_commit_hooks(handle, surface);
 
-                PrivateSurfaceData::commit(surface, handle);
-
-                PrivateSurfaceData::invoke_post_commit_hooks(handle, surface);
-
-                trace!(
-                    state.compositor_state().log,
-                    "Calling user implementation for wl_surface.commit"
-                );
-
-                state.commit(surface);
+                state.pre_commit(surface);
+
+                match PrivateSurfaceData::commit(surface, handle) {
+                    Some(transaction) => {
+                        // the transaction might be blocked, in which case the commit is
+                        // finished once it is applied
+                        let compositor = state.compositor_state();
+                        let surfaces = compositor.queue_transaction(handle, surface, transaction);
+                        for surface in surfaces.iter().filter(|surface| surface.alive()) {
+                            post_commit(state, handle, surface);
+                        }
+                    }
+                    // sync subsurface, its state was cached
+                    None => post_commit(state, handle, surface),
+                }
             }
             wl_surface::Request::SetBufferTransform { transform } => {
                 if let WEnum::Value(transform) = transform {
@@ -294,13 +319,14 @@ where
     }
 
     fn destroyed(
-        _state: &mut D,
-        _client_id: wayland_server::backend::ClientId,
+        state: &mut D,
+        client_id: wayland_server::backend::ClientId,
         object_id: wayland_server::backend::ObjectId,
         data: &SurfaceUserData,
     ) {
         data.alive_tracker.destroy_notify();
         PrivateSurfaceData::cleanup(data, object_id);
+        state.compositor_state().surface_destroyed(&client_id);
     }
 }
 
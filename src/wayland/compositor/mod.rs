@@ -76,19 +76,24 @@
 //!    the [`add_pre_commit_hook`] function. They are typically used by protocol extensions that
 //!    add state to a surface and need to check on commit that client did not request an
 //!    illegal state before it is applied on commit.
-//! 2. The pending state is either applied and made current, or cached for later application
+//! 2. Your implementation of [`CompositorHandler::pre_commit`] is invoked, giving you the
+//!    opportunity to attach [`Blocker`]s to the commit using [`add_blocker`].
+//! 3. The pending state is either applied and made current, or cached for later application
 //!    is the surface is a synchronize subsurface. If the current state is applied, state
-//!    of the synchronized children subsurface are applied as well at this point.
-//! 3. Post Commit hooks registered to this surface are invoked. Such hooks can be registered using
+//!    of the synchronized children subsurface are applied as well at this point. If blockers
+//!    are attached to the commit, the state is only applied once all of them are released,
+//!    which you need to signal using [`CompositorState::blocker_cleared`]. The following steps
+//!    are delayed until then.
+//! 4. Post Commit hooks registered to this surface are invoked. Such hooks can be registered using
 //!    the [`add_post_commit_hook`] function. They are typically used by abstractions that further process
 //!    the state.
-//! 4. Your implementation of [`CompositorHandler::commit`] is invoked, so that you can access
+//! 5. Your implementation of [`CompositorHandler::commit`] is invoked, so that you can access
 //!    the new current state of the surface. The state of sync children subsurfaces of your
 //!    surface may have changed as well, so this is the place to check it, using functions
 //!    like [`with_surface_tree_upward`] or [`with_surface_tree_downward`]. On the other hand,
 //!    if the surface is a sync subsurface, its current state will note have changed as
 //!    the result of that commit. You can check if it is using [`is_sync_subsurface`].
-//! 5. If the surface is destroyed, destruction hooks are invoked. Such hooks can be registered
+//! 6. If the surface is destroyed, destruction hooks are invoked. Such hooks can be registered
 //!    using the [`add_destruction_hook`] function. They are typically used to cleanup associated
 //!    state.
 //!
@@ -107,10 +112,13 @@ mod tree;
 
 pub use self::cache::{Cacheable, MultiCache};
 pub use self::handlers::{RegionUserData, SubsurfaceCachedState, SubsurfaceUserData, SurfaceUserData};
+use self::transaction::TransactionQueue;
+pub use self::transaction::{Blocker, BlockerState};
 use self::tree::PrivateSurfaceData;
 pub use self::tree::{AlreadyHasRole, TraversalAction};
-use crate::utils::{user_data::UserDataMap, Buffer, Logical, Point, Rectangle};
-use wayland_server::backend::GlobalId;
+use crate::utils::{user_data::UserDataMap, Buffer, IsAlive, Logical, Point, Rectangle};
+use std::collections::HashMap;
+use wayland_server::backend::{ClientId, GlobalId};
 use wayland_server::protocol::wl_compositor::WlCompositor;
 use wayland_server::protocol::wl_subcompositor::WlSubcompositor;
 use wayland_server::protocol::{wl_buffer, wl_callback, wl_output, wl_region, wl_surface::WlSurface};
@@ -423,11 +431,33 @@ pub fn add_destruction_hook(surface: &WlSurface, hook: fn(&SurfaceData)) {
     PrivateSurfaceData::add_destruction_hook(surface, hook)
 }
 
+/// Add a blocker to the pending state of a surface
+///
+/// The state committed next by the client will not be applied before the blocker is released.
+/// If the blocker is cancelled, the state is discarded in favor of the following commit.
+///
+/// This function should be called from [`CompositorHandler::pre_commit`] or from a pre-commit hook.
+/// Once the blocker is released, you need to call [`CompositorState::blocker_cleared`] for the
+/// state to be applied.
+///
+/// A ready-made blocker waiting for the client to finish rendering into an attached dmabuf
+/// can be created using [`Dmabuf::generate_blocker`](crate::backend::allocator::dmabuf::Dmabuf::generate_blocker).
+pub fn add_blocker(surface: &WlSurface, blocker: impl Blocker + Send + 'static) {
+    PrivateSurfaceData::add_blocker(surface, blocker)
+}
+
 /// Handler trait for compositor
+#[allow(unused_variables)]
 pub trait CompositorHandler {
     /// [CompositorState] getter
     fn compositor_state(&mut self) -> &mut CompositorState;
 
+    /// Surface pre-commit handler
+    ///
+    /// Invoked after the pre-commit hooks of the surface, before its pending state is committed.
+    /// This is the place to attach [`Blocker`]s to the commit using [`add_blocker`].
+    fn pre_commit(&mut self, surface: &WlSurface) {}
+
     /// Surface commit handler
     fn commit(&mut self, surface: &WlSurface);
 }
@@ -438,6 +468,7 @@ pub struct CompositorState {
     log: slog::Logger,
     compositor: GlobalId,
     subcompositor: GlobalId,
+    transaction_queues: HashMap<ClientId, TransactionQueue>,
 }
 
 #[doc(hidden)]
@@ -461,6 +492,66 @@ impl CompositorState {
             log,
             compositor,
             subcompositor,
+            transaction_queues: HashMap::new(),
+        }
+    }
+
+    /// Notify the compositor, that a blocker of a client was released or cancelled
+    ///
+    /// All pending commits of the client that are now ready are applied, invoking the post-commit hooks
+    /// and [`CompositorHandler::commit`] for the committed surfaces.
+    pub fn blocker_cleared<D: CompositorHandler + 'static>(
+        state: &mut D,
+        dh: &DisplayHandle,
+        client: &ClientId,
+    ) {
+        let surfaces = {
+            let queues = &mut state.compositor_state().transaction_queues;
+            match queues.get_mut(client) {
+                Some(queue) => {
+                    let surfaces = queue.apply_ready(dh);
+                    if queue.is_empty() {
+                        queues.remove(client);
+                    }
+                    surfaces
+                }
+                None => return,
+            }
+        };
+
+        for surface in surfaces.iter().filter(|surface| surface.alive()) {
+            handlers::post_commit(state, dh, surface);
+        }
+    }
+
+    pub(crate) fn queue_transaction(
+        &mut self,
+        dh: &DisplayHandle,
+        surface: &WlSurface,
+        transaction: transaction::Transaction,
+    ) -> Vec<WlSurface> {
+        let client = match dh.get_client(surface.id()) {
+            Ok(client) => client.id(),
+            // the client is gone, nothing to apply
+            Err(_) => return Vec::new(),
+        };
+        let queue = self.transaction_queues.entry(client.clone()).or_default();
+        queue.append(surface.clone(), transaction);
+        let surfaces = queue.apply_ready(dh);
+        if queue.is_empty() {
+            self.transaction_queues.remove(&client);
+        }
+        surfaces
+    }
+
+    pub(crate) fn surface_destroyed(&mut self, client: &ClientId) {
+        // the transactions of a client blocked forever, e.g. on a fence that never signals,
+        // would otherwise outlive the client once all its surfaces are gone
+        if let Some(queue) = self.transaction_queues.get_mut(client) {
+            queue.cleanup_dead();
+            if queue.is_empty() {
+                self.transaction_queues.remove(client);
+            }
         }
     }
 
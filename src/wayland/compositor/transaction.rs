@@ -23,11 +23,11 @@
 // - Then, still on commit, if the surface is not a synchronized subsurface, its pending transaction is
 //   directly applied
 //
-// This last step will change once we have support for explicit synchronization (and further in the future,
-// of the wp_transaction protocol). Explicit synchronization introduces a notion of blockers: the transaction
-// cannot be applied before all blockers are released, and thus must wait for it to be the case.
+// This last step is subject to blockers (used for example for explicit synchronization, and further in the
+// future, for the wp_transaction protocol): the transaction cannot be applied before all blockers are released,
+// and thus must wait for it to be the case.
 //
-// For thoses situations, the (currently unused) `TransactionQueue` will come into play. It is a per-client
+// For thoses situations, the `TransactionQueue` comes into play. It is a per-client
 // queue of transactions, that stores and applies them by both respecting their topological order
 // (ensuring that for each surface, states are applied in the correct order) and that all transactions
 // wait befor all their blockers are resolved to be merged. If a blocker is cancelled, the whole transaction
@@ -35,13 +35,9 @@
 // associated state will be applied automatically when the next valid transaction is applied, ensuring
 // global coherence.
 
-// A significant part of the logic of this module is not yet used,
-// but will be once proper transaction & blockers support is
-// added to smithay
-#![allow(dead_code)]
-
 use std::{
     collections::HashSet,
+    fmt,
     sync::{Arc, Mutex},
 };
 
@@ -51,13 +47,24 @@ use crate::{utils::IsAlive, utils::Serial};
 
 use super::tree::PrivateSurfaceData;
 
+/// Types potentially blocking state changes of a surface
+///
+/// A blocker is attached to the pending transaction of a surface using
+/// [`add_blocker`](super::add_blocker). The transaction (and thus the state
+/// committed by the client) is only applied once all its blockers are released.
 pub trait Blocker {
+    /// Retrieve the current state of the blocker
     fn state(&self) -> BlockerState;
 }
 
+/// States of a [`Blocker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BlockerState {
+    /// The blocker is still pending, the transaction has to wait
     Pending,
+    /// The blocker has been released, the transaction may be applied
     Released,
+    /// The blocker has been cancelled, the transaction will be discarded
     Cancelled,
 }
 
@@ -197,6 +204,10 @@ impl Transaction {
 
     pub(crate) fn apply(self, dh: &DisplayHandle) {
         for (surface, id) in self.surfaces {
+            // the surface might have been destroyed while the transaction was blocked
+            if !surface.alive() {
+                continue;
+            }
             PrivateSurfaceData::with_states(&surface, |states| {
                 states.cached_state.apply_state(id, dh);
             })
@@ -207,17 +218,38 @@ impl Transaction {
 // This queue should be per-client
 #[derive(Default)]
 pub(crate) struct TransactionQueue {
-    transactions: Vec<Transaction>,
+    // the transactions, alongside the surface whose commit created them
+    transactions: Vec<(WlSurface, Transaction)>,
     // we keep the hashset around to reuse allocations
     seen_surfaces: HashSet<u32>,
 }
 
+impl fmt::Debug for TransactionQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransactionQueue")
+            .field("transactions", &self.transactions.len())
+            .finish()
+    }
+}
+
 impl TransactionQueue {
-    pub(crate) fn append(&mut self, t: Transaction) {
-        self.transactions.push(t);
+    pub(crate) fn append(&mut self, surface: WlSurface, t: Transaction) {
+        self.transactions.push((surface, t));
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    /// Drops the transactions that no longer have any alive surface to apply state to
+    pub(crate) fn cleanup_dead(&mut self) {
+        self.transactions
+            .retain(|(_, transaction)| transaction.surfaces.iter().any(|(s, _)| s.alive()));
     }
 
-    pub(crate) fn apply_ready<D: 'static>(&mut self, dh: &DisplayHandle) {
+    /// Applies all transactions that are ready, returning the surfaces whose commits were applied
+    pub(crate) fn apply_ready(&mut self, dh: &DisplayHandle) -> Vec<WlSurface> {
+        let mut applied = Vec::new();
         // this is a very non-optimized implementation
         // we just iterate over the queue of transactions, keeping track of which
         // surface we have seen as they encode transaction dependencies
@@ -226,10 +258,10 @@ impl TransactionQueue {
         let mut i = 0;
         // the loop will terminate, as at every iteration either i is incremented by 1
         // or the lenght of self.transactions is reduced by 1.
-        while i <= self.transactions.len() {
+        while i < self.transactions.len() {
             let mut skip = false;
             // does the transaction have any active blocker?
-            match self.transactions[i].state() {
+            match self.transactions[i].1.state() {
                 BlockerState::Cancelled => {
                     // this transaction is cancelled, remove it without further processing
                     self.transactions.remove(i);
@@ -242,7 +274,7 @@ impl TransactionQueue {
             }
             // if not, does this transaction depend on any previous transaction?
             if !skip {
-                for (s, _) in &self.transactions[i].surfaces {
+                for (s, _) in &self.transactions[i].1.surfaces {
                     // TODO: is this alive check still needed?
                     if !s.alive() {
                         continue;
@@ -257,7 +289,7 @@ impl TransactionQueue {
             if skip {
                 // this transaction is not yet ready and should be skipped, add its surfaces to our
                 // seen list
-                for (s, _) in &self.transactions[i].surfaces {
+                for (s, _) in &self.transactions[i].1.surfaces {
                     // TODO: is this alive check still needed?
                     if !s.alive() {
                         continue;
@@ -267,8 +299,11 @@ impl TransactionQueue {
                 i += 1;
             } else {
                 // this transaction is to be applied, yay!
-                self.transactions.remove(i).apply(dh);
+                let (surface, transaction) = self.transactions.remove(i);
+                transaction.apply(dh);
+                applied.push(surface);
             }
         }
+        applied
     }
 }
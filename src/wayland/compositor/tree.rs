@@ -3,7 +3,7 @@ use crate::{utils::Serial, wayland::compositor::SUBSURFACE_ROLE};
 use super::{
     cache::MultiCache,
     handlers::{is_effectively_sync, SurfaceUserData},
-    transaction::PendingTransaction,
+    transaction::{Blocker, PendingTransaction, Transaction},
     BufferAssignment, SurfaceAttributes, SurfaceData,
 };
 use std::{
@@ -219,7 +219,11 @@ impl PrivateSurfaceData {
         }
     }
 
-    pub fn commit(surface: &WlSurface, dh: &DisplayHandle) {
+    /// Commits the pending state of the surface
+    ///
+    /// Returns the finalized transaction if the surface is not synchronized, it needs
+    /// to be applied once all of its blockers are released.
+    pub fn commit(surface: &WlSurface, dh: &DisplayHandle) -> Option<Transaction> {
         let is_sync = is_effectively_sync(surface);
         let children = PrivateSurfaceData::get_children(surface);
         let my_data_mutex = &surface.data::<SurfaceUserData>().unwrap().inner;
@@ -251,15 +255,22 @@ impl PrivateSurfaceData {
             .pending_transaction
             .insert_state(surface.clone(), my_data.current_txid);
         if !is_sync {
-            // if we are not sync, apply the transaction immediately
+            // if we are not sync, the transaction is complete
             let tx = std::mem::take(&mut my_data.pending_transaction);
-            // release the mutex, as applying the transaction will try to lock it
+            // release the mutex, as finalizing the transaction will try to lock it
             std::mem::drop(my_data);
-            // apply the transaction
-            tx.finalize().apply(dh);
+            Some(tx.finalize())
+        } else {
+            None
         }
     }
 
+    pub fn add_blocker<B: Blocker + Send + 'static>(surface: &WlSurface, blocker: B) {
+        let my_data_mutex = &surface.data::<SurfaceUserData>().unwrap().inner;
+        let my_data = my_data_mutex.lock().unwrap();
+        my_data.pending_transaction.add_blocker(blocker);
+    }
+
     /// Checks if the first surface is an ancestor of the second
     pub fn is_ancestor(a: &WlSurface, b: &WlSurface) -> bool {
         let b_mutex = &b.data::<SurfaceUserData>().unwrap().inner;
@@ -0,0 +1,297 @@
+//! Utilities for the cursor shape protocol
+//!
+//! The cursor shape protocol allows clients to request a cursor from the compositor's
+//! cursor theme by name, instead of attaching a surface with a cursor image.
+//!
+//! Shapes requested for a pointer are reported through [`SeatHandler::cursor_image`] as
+//! [`CursorImageStatus::Named`], shapes requested for a tablet tool are given to the callback
+//! registered with [`TabletSeatHandle::add_tool`](crate::wayland::tablet_manager::TabletSeatHandle::add_tool).
+//! Like for `wl_pointer.set_cursor`, requests are only honored while a surface of the client is focused.
+//!
+//! The [`cursor_theme`](crate::input::pointer::cursor_theme) module can be used to load
+//! the cursors of a xcursor theme for drawing.
+//!
+//! ```
+//! extern crate wayland_server;
+//! extern crate smithay;
+//!
+//! use smithay::wayland::cursor_shape::CursorShapeManagerState;
+//! use smithay::delegate_cursor_shape;
+//! # use smithay::input::{Seat, SeatHandler, SeatState, pointer::CursorImageStatus};
+//! # use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+//!
+//! # struct State { seat_state: SeatState<State> }
+//! # impl SeatHandler for State {
+//! #     type KeyboardFocus = WlSurface;
+//! #     type PointerFocus = WlSurface;
+//! #     fn seat_state(&mut self) -> &mut SeatState<Self> { &mut self.seat_state }
+//! #     fn focus_changed(&mut self, seat: &Seat<Self>, focused: Option<&WlSurface>) {}
+//! #     fn cursor_image(&mut self, seat: &Seat<Self>, image: CursorImageStatus) {}
+//! # }
+//! # let mut display = wayland_server::Display::<State>::new().unwrap();
+//! let state = CursorShapeManagerState::new::<State>(&display.handle());
+//!
+//! delegate_cursor_shape!(State);
+//! ```
+
+use std::fmt;
+
+use wayland_protocols::wp::{
+    cursor_shape::v1::server::{
+        wp_cursor_shape_device_v1::{self, Shape, WpCursorShapeDeviceV1},
+        wp_cursor_shape_manager_v1::{self, WpCursorShapeManagerV1},
+    },
+    tablet::zv2::server::zwp_tablet_tool_v2::ZwpTabletToolV2,
+};
+use wayland_server::{
+    backend::GlobalId, Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource, WEnum,
+};
+
+use crate::{
+    input::{
+        pointer::{CursorIcon, CursorImageStatus, PointerHandle},
+        SeatHandler,
+    },
+    wayland::{
+        seat::{PointerUserData, WaylandFocus},
+        tablet_manager::TabletToolUserData,
+    },
+};
+
+const MANAGER_VERSION: u32 = 1;
+
+/// State of the cursor shape manager
+#[derive(Debug)]
+pub struct CursorShapeManagerState {
+    global: GlobalId,
+}
+
+impl CursorShapeManagerState {
+    /// Register new [WpCursorShapeManagerV1] global
+    pub fn new<D>(display: &DisplayHandle) -> Self
+    where
+        D: GlobalDispatch<WpCursorShapeManagerV1, ()>,
+        D: Dispatch<WpCursorShapeManagerV1, ()>,
+        D: Dispatch<WpCursorShapeDeviceV1, CursorShapeDeviceUserData<D>>,
+        D: SeatHandler,
+        D: 'static,
+    {
+        let global = display.create_global::<D, WpCursorShapeManagerV1, _>(MANAGER_VERSION, ());
+
+        Self { global }
+    }
+
+    /// [WpCursorShapeManagerV1] GlobalId getter
+    pub fn global(&self) -> GlobalId {
+        self.global.clone()
+    }
+}
+
+/// User data of WpCursorShapeDeviceV1 object
+pub enum CursorShapeDeviceUserData<D: SeatHandler> {
+    /// The device was created for a pointer
+    Pointer(Option<PointerHandle<D>>),
+    /// The device was created for a tablet tool
+    TabletTool(ZwpTabletToolV2),
+}
+
+impl<D: SeatHandler> fmt::Debug for CursorShapeDeviceUserData<D>
+where
+    <D as SeatHandler>::PointerFocus: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CursorShapeDeviceUserData::Pointer(handle) => f.debug_tuple("Pointer").field(handle).finish(),
+            CursorShapeDeviceUserData::TabletTool(tool) => f.debug_tuple("TabletTool").field(tool).finish(),
+        }
+    }
+}
+
+impl<D> GlobalDispatch<WpCursorShapeManagerV1, (), D> for CursorShapeManagerState
+where
+    D: GlobalDispatch<WpCursorShapeManagerV1, ()>
+        + Dispatch<WpCursorShapeManagerV1, ()>
+        + SeatHandler
+        + 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _dh: &DisplayHandle,
+        _client: &Client,
+        resource: New<WpCursorShapeManagerV1>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl<D> Dispatch<WpCursorShapeManagerV1, (), D> for CursorShapeManagerState
+where
+    D: Dispatch<WpCursorShapeManagerV1, ()>,
+    D: Dispatch<WpCursorShapeDeviceV1, CursorShapeDeviceUserData<D>>,
+    D: SeatHandler,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _manager: &WpCursorShapeManagerV1,
+        request: wp_cursor_shape_manager_v1::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            wp_cursor_shape_manager_v1::Request::GetPointer {
+                cursor_shape_device,
+                pointer,
+            } => {
+                let handle = pointer
+                    .data::<PointerUserData<D>>()
+                    .and_then(|data| data.handle.clone());
+                data_init.init(cursor_shape_device, CursorShapeDeviceUserData::Pointer(handle));
+            }
+            wp_cursor_shape_manager_v1::Request::GetTabletToolV2 {
+                cursor_shape_device,
+                tablet_tool,
+            } => {
+                data_init.init(
+                    cursor_shape_device,
+                    CursorShapeDeviceUserData::TabletTool(tablet_tool),
+                );
+            }
+            wp_cursor_shape_manager_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<WpCursorShapeDeviceV1, CursorShapeDeviceUserData<D>, D> for CursorShapeManagerState
+where
+    D: Dispatch<WpCursorShapeDeviceV1, CursorShapeDeviceUserData<D>>,
+    D: SeatHandler,
+    <D as SeatHandler>::PointerFocus: WaylandFocus,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        device: &WpCursorShapeDeviceV1,
+        request: wp_cursor_shape_device_v1::Request,
+        data: &CursorShapeDeviceUserData<D>,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            wp_cursor_shape_device_v1::Request::SetShape { shape, .. } => {
+                let icon = match shape {
+                    WEnum::Value(shape) => shape_to_cursor_icon(shape),
+                    WEnum::Unknown(_) => None,
+                };
+                let icon = match icon {
+                    Some(icon) => icon,
+                    None => {
+                        device.post_error(
+                            wp_cursor_shape_device_v1::Error::InvalidShape,
+                            "Unknown cursor shape.",
+                        );
+                        return;
+                    }
+                };
+
+                match data {
+                    CursorShapeDeviceUserData::Pointer(Some(handle)) => {
+                        // only allow setting the cursor icon if the current pointer focus
+                        // is of the same client
+                        let has_focus = handle
+                            .inner
+                            .lock()
+                            .unwrap()
+                            .focus
+                            .as_ref()
+                            .map(|(focus, _)| focus.same_client_as(&device.id()))
+                            .unwrap_or(false);
+                        if !has_focus {
+                            return;
+                        }
+
+                        let seat = state
+                            .seat_state()
+                            .seats
+                            .iter()
+                            .find(|seat| seat.get_pointer().map(|h| &h == handle).unwrap_or(false))
+                            .cloned();
+                        if let Some(seat) = seat {
+                            state.cursor_image(&seat, CursorImageStatus::Named(icon));
+                        }
+                    }
+                    CursorShapeDeviceUserData::Pointer(None) => {}
+                    CursorShapeDeviceUserData::TabletTool(tool) => {
+                        if let Some(tool_data) = tool.data::<TabletToolUserData>() {
+                            tool_data.set_named_cursor(tool, icon);
+                        }
+                    }
+                }
+            }
+            wp_cursor_shape_device_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn shape_to_cursor_icon(shape: Shape) -> Option<CursorIcon> {
+    Some(match shape {
+        Shape::Default => CursorIcon::Default,
+        Shape::ContextMenu => CursorIcon::ContextMenu,
+        Shape::Help => CursorIcon::Help,
+        Shape::Pointer => CursorIcon::Pointer,
+        Shape::Progress => CursorIcon::Progress,
+        Shape::Wait => CursorIcon::Wait,
+        Shape::Cell => CursorIcon::Cell,
+        Shape::Crosshair => CursorIcon::Crosshair,
+        Shape::Text => CursorIcon::Text,
+        Shape::VerticalText => CursorIcon::VerticalText,
+        Shape::Alias => CursorIcon::Alias,
+        Shape::Copy => CursorIcon::Copy,
+        Shape::Move => CursorIcon::Move,
+        Shape::NoDrop => CursorIcon::NoDrop,
+        Shape::NotAllowed => CursorIcon::NotAllowed,
+        Shape::Grab => CursorIcon::Grab,
+        Shape::Grabbing => CursorIcon::Grabbing,
+        Shape::EResize => CursorIcon::EResize,
+        Shape::NResize => CursorIcon::NResize,
+        Shape::NeResize => CursorIcon::NeResize,
+        Shape::NwResize => CursorIcon::NwResize,
+        Shape::SResize => CursorIcon::SResize,
+        Shape::SeResize => CursorIcon::SeResize,
+        Shape::SwResize => CursorIcon::SwResize,
+        Shape::WResize => CursorIcon::WResize,
+        Shape::EwResize => CursorIcon::EwResize,
+        Shape::NsResize => CursorIcon::NsResize,
+        Shape::NeswResize => CursorIcon::NeswResize,
+        Shape::NwseResize => CursorIcon::NwseResize,
+        Shape::ColResize => CursorIcon::ColResize,
+        Shape::RowResize => CursorIcon::RowResize,
+        Shape::AllScroll => CursorIcon::AllScroll,
+        Shape::ZoomIn => CursorIcon::ZoomIn,
+        Shape::ZoomOut => CursorIcon::ZoomOut,
+        _ => return None,
+    })
+}
+
+/// Macro to delegate implementation of the cursor shape protocol
+#[macro_export]
+macro_rules! delegate_cursor_shape {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::cursor_shape::v1::server::wp_cursor_shape_manager_v1::WpCursorShapeManagerV1: ()
+        ] => $crate::wayland::cursor_shape::CursorShapeManagerState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::cursor_shape::v1::server::wp_cursor_shape_manager_v1::WpCursorShapeManagerV1: ()
+        ] => $crate::wayland::cursor_shape::CursorShapeManagerState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::cursor_shape::v1::server::wp_cursor_shape_device_v1::WpCursorShapeDeviceV1: $crate::wayland::cursor_shape::CursorShapeDeviceUserData<Self>
+        ] => $crate::wayland::cursor_shape::CursorShapeManagerState);
+    };
+}
@@ -0,0 +1,121 @@
+use std::cell::RefCell;
+
+use wayland_protocols_wlr::data_control::v1::server::zwlr_data_control_device_v1::{
+    self, ZwlrDataControlDeviceV1,
+};
+use wayland_server::{
+    backend::{ClientId, ObjectId},
+    protocol::wl_seat::WlSeat,
+    Client, DataInit, Dispatch, DisplayHandle, Resource,
+};
+
+use crate::{
+    input::{Seat, SeatHandler},
+    wayland::{
+        data_device::{
+            seat_data::{SeatData as DataDeviceSeatData, Selection as DataDeviceSelection},
+            DataDeviceHandler,
+        },
+        primary_selection::{
+            seat_data::{SeatData as PrimarySeatData, Selection as PrimarySelection},
+            PrimarySelectionHandler,
+        },
+    },
+};
+
+use super::{DataControlHandler, DataControlSourceUserData, DataControlState};
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct DataControlDeviceUserData {
+    pub(crate) wl_seat: WlSeat,
+}
+
+impl<D> Dispatch<ZwlrDataControlDeviceV1, DataControlDeviceUserData, D> for DataControlState
+where
+    D: Dispatch<ZwlrDataControlDeviceV1, DataControlDeviceUserData>,
+    D: DataControlHandler,
+    D: DataDeviceHandler,
+    D: PrimarySelectionHandler,
+    D: SeatHandler,
+    D: 'static,
+{
+    fn request(
+        handler: &mut D,
+        _client: &Client,
+        resource: &ZwlrDataControlDeviceV1,
+        request: zwlr_data_control_device_v1::Request,
+        data: &DataControlDeviceUserData,
+        dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        // a source can only be used for a single selection
+        match &request {
+            zwlr_data_control_device_v1::Request::SetSelection { source: Some(source) }
+            | zwlr_data_control_device_v1::Request::SetPrimarySelection { source: Some(source) } => {
+                let source_data = source.data::<DataControlSourceUserData>().unwrap();
+                if !source_data.mark_used() {
+                    resource.post_error(
+                        zwlr_data_control_device_v1::Error::UsedSource,
+                        "source was already used for a selection",
+                    );
+                    return;
+                }
+            }
+            _ => {}
+        }
+
+        let seat = match Seat::<D>::from_resource(&data.wl_seat) {
+            Some(seat) => seat,
+            None => return,
+        };
+
+        match request {
+            zwlr_data_control_device_v1::Request::SetSelection { source } => {
+                seat.user_data()
+                    .insert_if_missing(|| RefCell::new(DataDeviceSeatData::new()));
+                let seat_data = seat.user_data().get::<RefCell<DataDeviceSeatData>>().unwrap();
+
+                DataControlHandler::new_selection(handler, source.clone());
+                // data control clients may set the selection regardless of keyboard focus
+                seat_data.borrow_mut().set_selection::<D>(
+                    dh,
+                    source
+                        .map(DataDeviceSelection::DataControl)
+                        .unwrap_or(DataDeviceSelection::Empty),
+                );
+            }
+            zwlr_data_control_device_v1::Request::SetPrimarySelection { source } => {
+                seat.user_data()
+                    .insert_if_missing(|| RefCell::new(PrimarySeatData::new()));
+                let seat_data = seat.user_data().get::<RefCell<PrimarySeatData>>().unwrap();
+
+                DataControlHandler::new_primary_selection(handler, source.clone());
+                seat_data.borrow_mut().set_selection::<D>(
+                    dh,
+                    source
+                        .map(PrimarySelection::DataControl)
+                        .unwrap_or(PrimarySelection::Empty),
+                );
+            }
+            zwlr_data_control_device_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(_state: &mut D, _client: ClientId, id: ObjectId, data: &DataControlDeviceUserData) {
+        // Clean up the known devices
+        if let Some(seat) = Seat::<D>::from_resource(&data.wl_seat) {
+            if let Some(seat_data) = seat.user_data().get::<RefCell<DataDeviceSeatData>>() {
+                seat_data
+                    .borrow_mut()
+                    .retain_data_control_devices(|device| device.id() != id);
+            }
+            if let Some(seat_data) = seat.user_data().get::<RefCell<PrimarySeatData>>() {
+                seat_data
+                    .borrow_mut()
+                    .retain_data_control_devices(|device| device.id() != id);
+            }
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//! Utilities for clipboard managers using the wlr data control protocol
+//!
+//! The data control protocol allows privileged clients to read and set the selection and the
+//! primary selection of a seat without having keyboard focus. This is what clipboard managers
+//! and tools like `wl-clipboard` rely on.
+//!
+//! This module is integrated with the [`data_device`](super::data_device) and
+//! [`primary_selection`](super::primary_selection) modules: selections set by any of them are
+//! offered to data control clients, and selections set by data control clients are offered to
+//! the focused client of the respective protocol. A compositor supporting this protocol thus
+//! also needs to support both of these.
+//!
+//! ## Initialization
+//!
+//! To initialize this implementation, create the [`DataControlState`], store it inside your `State` struct
+//! and implement the [`DataControlHandler`], as shown in this example:
+//!
+//! ```
+//! # extern crate wayland_server;
+//! # #[macro_use] extern crate smithay;
+//! use smithay::delegate_data_control;
+//! use smithay::wayland::data_control::{DataControlHandler, DataControlState};
+//! # use smithay::wayland::data_device::{ClientDndGrabHandler, DataDeviceHandler, DataDeviceState, ServerDndGrabHandler};
+//! # use smithay::wayland::primary_selection::{PrimarySelectionHandler, PrimarySelectionState};
+//! # use smithay::input::{Seat, SeatHandler, SeatState, pointer::CursorImageStatus};
+//! # use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+//!
+//! # struct State { data_control_state: DataControlState }
+//! # let mut display = wayland_server::Display::<State>::new().unwrap();
+//! // Create the data_control state, only allowing trusted clients to bind the global
+//! let data_control_state = DataControlState::new::<State, _, _>(
+//!     &display.handle(),
+//!     |_client| true,
+//!     None // We don't add a logger in this example
+//! );
+//!
+//! // insert the DataControlState into your state
+//! // ..
+//!
+//! // implement the necessary traits
+//! # impl SeatHandler for State {
+//! #     type KeyboardFocus = WlSurface;
+//! #     type PointerFocus = WlSurface;
+//! #     fn seat_state(&mut self) -> &mut SeatState<Self> { unimplemented!() }
+//! #     fn focus_changed(&mut self, seat: &Seat<Self>, focused: Option<&WlSurface>) { unimplemented!() }
+//! #     fn cursor_image(&mut self, seat: &Seat<Self>, image: CursorImageStatus) { unimplemented!() }
+//! # }
+//! # impl ClientDndGrabHandler for State {}
+//! # impl ServerDndGrabHandler for State {}
+//! # impl DataDeviceHandler for State {
+//! #     fn data_device_state(&self) -> &DataDeviceState { unimplemented!() }
+//! # }
+//! # impl PrimarySelectionHandler for State {
+//! #     fn primary_selection_state(&self) -> &PrimarySelectionState { unimplemented!() }
+//! # }
+//! impl DataControlHandler for State {
+//!     fn data_control_state(&self) -> &DataControlState { &self.data_control_state }
+//!     // ... override default implementations here to customize handling ...
+//! }
+//! delegate_data_control!(State);
+//!
+//! // You're now ready to go!
+//! ```
+
+use std::cell::RefCell;
+
+use wayland_protocols_wlr::data_control::v1::server::{
+    zwlr_data_control_device_v1::ZwlrDataControlDeviceV1,
+    zwlr_data_control_manager_v1::{self, ZwlrDataControlManagerV1},
+    zwlr_data_control_source_v1::ZwlrDataControlSourceV1,
+};
+use wayland_server::{
+    backend::GlobalId, Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+};
+
+use crate::{
+    input::{Seat, SeatHandler},
+    wayland::{
+        data_device::{seat_data::SeatData as DataDeviceSeatData, DataDeviceHandler},
+        primary_selection::{seat_data::SeatData as PrimarySeatData, PrimarySelectionHandler},
+    },
+};
+
+mod device;
+mod source;
+
+pub use device::DataControlDeviceUserData;
+pub use source::{with_source_metadata, DataControlSourceUserData, SourceMetadata};
+
+const MANAGER_VERSION: u32 = 2;
+
+/// Events that are generated by interactions of data control clients with the selections
+pub trait DataControlHandler: Sized {
+    /// [DataControlState] getter
+    fn data_control_state(&self) -> &DataControlState;
+
+    /// A data control client has set the selection
+    ///
+    /// Selections set through the data device are reported by
+    /// [`DataDeviceHandler::new_selection`] instead.
+    #[allow(unused_variables)]
+    fn new_selection(&mut self, source: Option<ZwlrDataControlSourceV1>) {}
+
+    /// A data control client has set the primary selection
+    ///
+    /// Selections set through the primary selection protocol are reported by
+    /// [`PrimarySelectionHandler::new_selection`] instead.
+    #[allow(unused_variables)]
+    fn new_primary_selection(&mut self, source: Option<ZwlrDataControlSourceV1>) {}
+}
+
+/// State of the data control protocol
+#[derive(Debug)]
+pub struct DataControlState {
+    log: slog::Logger,
+    manager_global: GlobalId,
+}
+
+/// Data associated with a data control manager global
+#[allow(missing_debug_implementations)]
+pub struct DataControlGlobalData {
+    filter: Box<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+}
+
+impl DataControlState {
+    /// Register new [ZwlrDataControlManagerV1] global, only visible to [privileged clients]
+    ///
+    /// [privileged clients]: crate::wayland::security_context#privileged-globals
+    pub fn new<D, F, L>(display: &DisplayHandle, filter: F, logger: L) -> Self
+    where
+        L: Into<Option<::slog::Logger>>,
+        D: GlobalDispatch<ZwlrDataControlManagerV1, DataControlGlobalData> + 'static,
+        D: DataControlHandler,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+    {
+        let log = crate::slog_or_fallback(logger).new(slog::o!("smithay_module" => "data_control_mgr"));
+
+        let data = DataControlGlobalData {
+            filter: Box::new(filter),
+        };
+        let manager_global = display.create_global::<D, ZwlrDataControlManagerV1, _>(MANAGER_VERSION, data);
+
+        Self { log, manager_global }
+    }
+
+    /// [ZwlrDataControlManagerV1] GlobalId getter
+    pub fn global(&self) -> GlobalId {
+        self.manager_global.clone()
+    }
+}
+
+impl<D> GlobalDispatch<ZwlrDataControlManagerV1, DataControlGlobalData, D> for DataControlState
+where
+    D: GlobalDispatch<ZwlrDataControlManagerV1, DataControlGlobalData>,
+    D: Dispatch<ZwlrDataControlManagerV1, ()>,
+    D: Dispatch<ZwlrDataControlDeviceV1, DataControlDeviceUserData>,
+    D: Dispatch<ZwlrDataControlSourceV1, DataControlSourceUserData>,
+    D: DataControlHandler,
+    D: 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<ZwlrDataControlManagerV1>,
+        _global_data: &DataControlGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+
+    fn can_view(client: Client, global_data: &DataControlGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<ZwlrDataControlManagerV1, (), D> for DataControlState
+where
+    D: Dispatch<ZwlrDataControlManagerV1, ()>,
+    D: Dispatch<ZwlrDataControlDeviceV1, DataControlDeviceUserData>,
+    D: Dispatch<ZwlrDataControlSourceV1, DataControlSourceUserData>,
+    D: DataControlHandler,
+    D: DataDeviceHandler,
+    D: PrimarySelectionHandler,
+    D: SeatHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        _resource: &ZwlrDataControlManagerV1,
+        request: zwlr_data_control_manager_v1::Request,
+        _data: &(),
+        dh: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let data_control_state = state.data_control_state();
+
+        match request {
+            zwlr_data_control_manager_v1::Request::CreateDataSource { id } => {
+                data_init.init(id, DataControlSourceUserData::new());
+            }
+            zwlr_data_control_manager_v1::Request::GetDataDevice { id, seat: wl_seat } => {
+                match Seat::<D>::from_resource(&wl_seat) {
+                    Some(seat) => {
+                        let device = data_init.init(id, DataControlDeviceUserData { wl_seat });
+
+                        seat.user_data()
+                            .insert_if_missing(|| RefCell::new(DataDeviceSeatData::new()));
+                        let seat_data = seat.user_data().get::<RefCell<DataDeviceSeatData>>().unwrap();
+                        seat_data
+                            .borrow_mut()
+                            .add_data_control_device::<D>(dh, device.clone());
+
+                        // the primary selection is only supported since version 2
+                        if device.version() >= 2 {
+                            seat.user_data()
+                                .insert_if_missing(|| RefCell::new(PrimarySeatData::new()));
+                            let seat_data = seat.user_data().get::<RefCell<PrimarySeatData>>().unwrap();
+                            seat_data.borrow_mut().add_data_control_device::<D>(dh, device);
+                        }
+                    }
+                    None => {
+                        slog::error!(
+                            &data_control_state.log,
+                            "Unmanaged seat given to a data control device."
+                        );
+                    }
+                }
+            }
+            zwlr_data_control_manager_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[allow(missing_docs)] // TODO
+#[macro_export]
+macro_rules! delegate_data_control {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::data_control::v1::server::zwlr_data_control_manager_v1::ZwlrDataControlManagerV1: $crate::wayland::data_control::DataControlGlobalData
+        ] => $crate::wayland::data_control::DataControlState);
+
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::data_control::v1::server::zwlr_data_control_manager_v1::ZwlrDataControlManagerV1: ()
+        ] => $crate::wayland::data_control::DataControlState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::data_control::v1::server::zwlr_data_control_device_v1::ZwlrDataControlDeviceV1: $crate::wayland::data_control::DataControlDeviceUserData
+        ] => $crate::wayland::data_control::DataControlState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::data_control::v1::server::zwlr_data_control_source_v1::ZwlrDataControlSourceV1: $crate::wayland::data_control::DataControlSourceUserData
+        ] => $crate::wayland::data_control::DataControlState);
+    };
+}
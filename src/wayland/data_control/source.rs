@@ -0,0 +1,100 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+use wayland_protocols_wlr::data_control::v1::server::zwlr_data_control_source_v1::{
+    self, ZwlrDataControlSourceV1,
+};
+use wayland_server::{
+    backend::{ClientId, ObjectId},
+    Dispatch, DisplayHandle, Resource,
+};
+
+use crate::utils::{alive_tracker::AliveTracker, IsAlive};
+
+use super::{DataControlHandler, DataControlState};
+
+/// The metadata describing a data control source
+#[derive(Debug, Default, Clone)]
+pub struct SourceMetadata {
+    /// The MIME types supported by this source
+    pub mime_types: Vec<String>,
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct DataControlSourceUserData {
+    inner: Mutex<SourceMetadata>,
+    used: AtomicBool,
+    alive_tracker: AliveTracker,
+}
+
+impl DataControlSourceUserData {
+    pub(super) fn new() -> Self {
+        Self {
+            inner: Default::default(),
+            used: AtomicBool::new(false),
+            alive_tracker: Default::default(),
+        }
+    }
+
+    /// Mark the source as used for a selection, returns `false` if it already was
+    pub(super) fn mark_used(&self) -> bool {
+        !self.used.swap(true, Ordering::SeqCst)
+    }
+}
+
+impl<D> Dispatch<ZwlrDataControlSourceV1, DataControlSourceUserData, D> for DataControlState
+where
+    D: Dispatch<ZwlrDataControlSourceV1, DataControlSourceUserData>,
+    D: DataControlHandler,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &wayland_server::Client,
+        resource: &ZwlrDataControlSourceV1,
+        request: zwlr_data_control_source_v1::Request,
+        data: &DataControlSourceUserData,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut wayland_server::DataInit<'_, D>,
+    ) {
+        match request {
+            zwlr_data_control_source_v1::Request::Offer { mime_type } => {
+                if data.used.load(Ordering::SeqCst) {
+                    resource.post_error(
+                        zwlr_data_control_source_v1::Error::InvalidOffer,
+                        "offer sent after the source was used for a selection",
+                    );
+                    return;
+                }
+                data.inner.lock().unwrap().mime_types.push(mime_type);
+            }
+            zwlr_data_control_source_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(_state: &mut D, _client: ClientId, _resource: ObjectId, data: &DataControlSourceUserData) {
+        data.alive_tracker.destroy_notify();
+    }
+}
+
+impl IsAlive for ZwlrDataControlSourceV1 {
+    fn alive(&self) -> bool {
+        let data: &DataControlSourceUserData = self.data().unwrap();
+        data.alive_tracker.alive()
+    }
+}
+
+/// Access the metadata of a data control source
+pub fn with_source_metadata<T, F: FnOnce(&SourceMetadata) -> T>(
+    source: &ZwlrDataControlSourceV1,
+    f: F,
+) -> Result<T, crate::utils::UnmanagedResource> {
+    match source.data::<DataControlSourceUserData>() {
+        Some(data) => Ok(f(&data.inner.lock().unwrap())),
+        None => Err(crate::utils::UnmanagedResource),
+    }
+}
@@ -18,7 +18,9 @@ use wayland_server::{
 use crate::{
     input::{
         pointer::{
-            AxisFrame, ButtonEvent, GrabStartData as PointerGrabStartData, MotionEvent, PointerGrab,
+            AxisFrame, ButtonEvent, GestureHoldBeginEvent, GestureHoldEndEvent, GesturePinchBeginEvent,
+            GesturePinchEndEvent, GesturePinchUpdateEvent, GestureSwipeBeginEvent, GestureSwipeEndEvent,
+            GestureSwipeUpdateEvent, GrabStartData as PointerGrabStartData, MotionEvent, PointerGrab,
             PointerInnerHandle, RelativeMotionEvent,
         },
         Seat, SeatHandler,
@@ -36,6 +38,7 @@ pub(crate) struct DnDGrab<D: SeatHandler> {
     current_focus: Option<WlSurface>,
     pending_offers: Vec<wl_data_offer::WlDataOffer>,
     offer_data: Option<Arc<Mutex<OfferData>>>,
+    external: bool,
     icon: Option<WlSurface>,
     origin: WlSurface,
     seat: Seat<D>,
@@ -57,6 +60,7 @@ impl<D: SeatHandler> DnDGrab<D> {
             current_focus: None,
             pending_offers: Vec::with_capacity(1),
             offer_data: None,
+            external: false,
             origin,
             icon,
             seat,
@@ -90,8 +94,16 @@ where
         if focus.as_ref().and_then(|&(ref s, _)| s.wl_surface()) != self.current_focus.clone() {
             // focus changed, we need to make a leave if appropriate
             if let Some(surface) = self.current_focus.take() {
-                // only leave if there is a data source or we are on the original client
-                if self.data_source.is_some() || self.origin.id().same_client_as(&surface.id()) {
+                if self.external {
+                    // the surface is not handled by a wayland data device
+                    data.leave_external(&surface);
+                    self.external = false;
+                    self.pending_offers.clear();
+                    if let Some(offer_data) = self.offer_data.take() {
+                        offer_data.lock().unwrap().active = false;
+                    }
+                } else if self.data_source.is_some() || self.origin.id().same_client_as(&surface.id()) {
+                    // only leave if there is a data source or we are on the original client
                     for device in seat_data.known_devices() {
                         if device.id().same_client_as(&surface.id()) {
                             device.leave();
@@ -124,10 +136,15 @@ where
                         accepted: true,
                         chosen_action: DndAction::empty(),
                     }));
+                    let external_offer = ExternalDndOffer {
+                        offer_data: offer_data.clone(),
+                        source: source.clone(),
+                    };
+                    self.external = data.enter_external(&surface, external_offer, (x, y).into());
                     for device in seat_data
                         .known_devices()
                         .iter()
-                        .filter(|d| d.id().same_client_as(&surface.id()))
+                        .filter(|d| !self.external && d.id().same_client_as(&surface.id()))
                     {
                         let handle = self.dh.backend_handle();
 
@@ -171,7 +188,9 @@ where
                 self.current_focus = Some(surface);
             } else {
                 // make a move
-                if self.data_source.is_some() || self.origin.id().same_client_as(&surface.id()) {
+                if self.external {
+                    data.motion_external(&surface, (x, y).into(), event.time);
+                } else if self.data_source.is_some() || self.origin.id().same_client_as(&surface.id()) {
                     for device in seat_data.known_devices() {
                         if device.id().same_client_as(&surface.id()) {
                             device.motion(event.time, x, y);
@@ -208,7 +227,11 @@ where
                 false
             };
             if let Some(ref surface) = self.current_focus {
-                if self.data_source.is_some() || self.origin.id().same_client_as(&surface.id()) {
+                if self.external {
+                    if validated {
+                        data.drop_external(surface, event.time);
+                    }
+                } else if self.data_source.is_some() || self.origin.id().same_client_as(&surface.id()) {
                     for device in seat_data.known_devices() {
                         if device.id().same_client_as(&surface.id()) && validated {
                             device.drop();
@@ -236,9 +259,15 @@ where
             // in all cases abandon the drop
             // no more buttons are pressed, release the grab
             if let Some(ref surface) = self.current_focus {
-                for device in seat_data.known_devices() {
-                    if device.id().same_client_as(&surface.id()) {
-                        device.leave();
+                if self.external {
+                    if !validated {
+                        data.leave_external(surface);
+                    }
+                } else {
+                    for device in seat_data.known_devices() {
+                        if device.id().same_client_as(&surface.id()) {
+                            device.leave();
+                        }
                     }
                 }
             }
@@ -251,6 +280,78 @@ where
         handle.axis(data, details);
     }
 
+    fn gesture_swipe_begin(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        event: &GestureSwipeBeginEvent,
+    ) {
+        handle.gesture_swipe_begin(data, event);
+    }
+
+    fn gesture_swipe_update(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        event: &GestureSwipeUpdateEvent,
+    ) {
+        handle.gesture_swipe_update(data, event);
+    }
+
+    fn gesture_swipe_end(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        event: &GestureSwipeEndEvent,
+    ) {
+        handle.gesture_swipe_end(data, event);
+    }
+
+    fn gesture_pinch_begin(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        event: &GesturePinchBeginEvent,
+    ) {
+        handle.gesture_pinch_begin(data, event);
+    }
+
+    fn gesture_pinch_update(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        event: &GesturePinchUpdateEvent,
+    ) {
+        handle.gesture_pinch_update(data, event);
+    }
+
+    fn gesture_pinch_end(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        event: &GesturePinchEndEvent,
+    ) {
+        handle.gesture_pinch_end(data, event);
+    }
+
+    fn gesture_hold_begin(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        event: &GestureHoldBeginEvent,
+    ) {
+        handle.gesture_hold_begin(data, event);
+    }
+
+    fn gesture_hold_end(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        event: &GestureHoldEndEvent,
+    ) {
+        handle.gesture_hold_end(data, event);
+    }
+
     fn start_data(&self) -> &PointerGrabStartData<D> {
         &self.start_data
     }
@@ -264,6 +365,89 @@ struct OfferData {
     chosen_action: DndAction,
 }
 
+/// Drag'n'drop offer of a client data source to a target, that is not a wayland data device
+///
+/// This is handed out by [`ClientDndGrabHandler::enter_external`] and allows the compositor
+/// (or e.g. the XWM) to translate the drag'n'drop into other protocols.
+#[derive(Debug, Clone)]
+pub struct ExternalDndOffer {
+    offer_data: Arc<Mutex<OfferData>>,
+    source: WlDataSource,
+}
+
+impl ExternalDndOffer {
+    /// Mime types offered by the data source
+    pub fn mime_types(&self) -> Vec<String> {
+        with_source_metadata(&self.source, |meta| meta.mime_types.clone()).unwrap_or_default()
+    }
+
+    /// Actions supported by the data source
+    pub fn source_actions(&self) -> DndAction {
+        with_source_metadata(&self.source, |meta| meta.dnd_action).unwrap_or_else(|_| DndAction::empty())
+    }
+
+    /// Whether the offer is still active
+    ///
+    /// An offer becomes inactive once the drag'n'drop left the target or was finished.
+    pub fn is_active(&self) -> bool {
+        self.offer_data.lock().unwrap().active && self.source.alive()
+    }
+
+    /// Indicate which mime type the target would accept, `None` if it does not accept the drop
+    pub fn accept(&self, mime_type: Option<String>) {
+        let mut data = self.offer_data.lock().unwrap();
+        if !data.active {
+            return;
+        }
+        data.accepted = match mime_type.as_ref() {
+            Some(mime_type) => with_source_metadata(&self.source, |meta| meta.mime_types.contains(mime_type))
+                .unwrap_or(false),
+            None => false,
+        };
+        self.source.target(mime_type);
+    }
+
+    /// Set the action chosen by the target
+    ///
+    /// Actions not supported by the data source are ignored.
+    pub fn set_action(&self, action: DndAction) {
+        let mut data = self.offer_data.lock().unwrap();
+        if !data.active {
+            return;
+        }
+        let action = if self.source_actions().contains(action) {
+            action
+        } else {
+            DndAction::empty()
+        };
+        if data.chosen_action != action {
+            data.chosen_action = action;
+            self.source.action(action);
+        }
+    }
+
+    /// Request the data of the given mime type to be written into `fd`
+    pub fn send(&self, mime_type: String, fd: OwnedFd) {
+        let data = self.offer_data.lock().unwrap();
+        let valid = with_source_metadata(&self.source, |meta| meta.mime_types.contains(&mime_type))
+            .unwrap_or(false)
+            && self.source.alive()
+            && data.active;
+        if valid {
+            self.source.send(mime_type, fd.as_raw_fd());
+        }
+    }
+
+    /// Notify the data source, that the target finished the drag'n'drop after the drop
+    pub fn finish(&self) {
+        let mut data = self.offer_data.lock().unwrap();
+        if data.active && data.dropped && data.accepted && !data.chosen_action.is_empty() {
+            self.source.dnd_finished();
+        }
+        data.active = false;
+    }
+}
+
 #[derive(Debug)]
 struct DndDataOffer {
     offer_data: Arc<Mutex<OfferData>>,
@@ -16,6 +16,8 @@
 //!
 //! - the freestanding function [`set_data_device_selection`]
 //!   allows you to set the contents of the selection for your clients
+//! - the freestanding function [`request_data_device_client_selection`] allows you to read
+//!   the contents of a selection set by a client
 //! - the freestanding function [`start_dnd`] allows you to initiate a drag'n'drop event from the compositor
 //!   itself and receive interactions of clients with it via an other dedicated callback.
 //!
@@ -64,7 +66,10 @@
 //! // You're now ready to go!
 //! ```
 
-use std::{cell::RefCell, os::unix::io::OwnedFd};
+use std::{
+    cell::RefCell,
+    os::unix::io::{AsRawFd, OwnedFd},
+};
 
 use wayland_server::{
     backend::GlobalId,
@@ -81,17 +86,18 @@ use crate::{
         pointer::{Focus, GrabStartData as PointerGrabStartData},
         Seat, SeatHandler,
     },
-    utils::Serial,
-    wayland::seat::WaylandFocus,
+    utils::{IsAlive, Logical, Point, Serial},
+    wayland::{data_control::with_source_metadata as with_data_control_source_metadata, seat::WaylandFocus},
 };
 
 mod device;
 mod dnd_grab;
-mod seat_data;
+pub(crate) mod seat_data;
 mod server_dnd_grab;
 mod source;
 
 pub use device::{DataDeviceUserData, DND_ICON_ROLE};
+pub use dnd_grab::ExternalDndOffer;
 pub use source::{with_source_metadata, DataSourceUserData, SourceMetadata};
 
 use seat_data::{SeatData, Selection};
@@ -138,6 +144,43 @@ pub trait ClientDndGrabHandler: SeatHandler + Sized {
     ///
     /// * `seat` - The seat on which the DnD action was finished.
     fn dropped(&mut self, seat: Seat<Self>) {}
+
+    /// The drag'n'drop entered a surface, that might not be handled by a wayland data device
+    ///
+    /// This allows to forward drag'n'drop operations to other protocols, e.g. to X11 windows
+    /// using the XDND support of the `xwayland` module.
+    ///
+    /// Return `true` if the surface is handled externally. In that case no wayland data device
+    /// events are sent for the surface, but the remaining `*_external` methods are called instead
+    /// and the state of the `offer` decides whether the drop succeeds.
+    ///
+    /// This is only called for drag'n'drop operations with a data source.
+    ///
+    /// * `surface` - The surface the pointer entered
+    /// * `offer` - The offer of the data source to the external target
+    /// * `location` - The pointer location relative to the surface
+    fn enter_external(
+        &mut self,
+        surface: &WlSurface,
+        offer: ExternalDndOffer,
+        location: Point<f64, Logical>,
+    ) -> bool {
+        false
+    }
+
+    /// The pointer moved over an externally handled surface
+    ///
+    /// * `location` - The pointer location relative to the surface
+    /// * `time` - The time of the motion event
+    fn motion_external(&mut self, surface: &WlSurface, location: Point<f64, Logical>, time: u32) {}
+
+    /// The drag'n'drop left an externally handled surface without being dropped
+    fn leave_external(&mut self, surface: &WlSurface) {}
+
+    /// The drag'n'drop was dropped on an externally handled surface
+    ///
+    /// This is only called if the [`ExternalDndOffer`] was accepted with a valid action.
+    fn drop_external(&mut self, surface: &WlSurface, time: u32) {}
 }
 
 /// Event generated by the interactions of clients with a server initiated drag'n'drop
@@ -251,12 +294,85 @@ where
     );
 }
 
+/// Clear the current selection for this seat
+pub fn clear_data_device_selection<D>(dh: &DisplayHandle, seat: &Seat<D>)
+where
+    D: SeatHandler + DataDeviceHandler + 'static,
+{
+    seat.user_data()
+        .insert_if_missing(|| RefCell::new(SeatData::new()));
+    let seat_data = seat.user_data().get::<RefCell<SeatData>>().unwrap();
+    seat_data.borrow_mut().set_selection::<D>(dh, Selection::Empty);
+}
+
+/// Errors happening when requesting the contents of a client provided selection
+#[derive(Debug, thiserror::Error)]
+pub enum SelectionRequestError {
+    /// Requested mime type is not available
+    #[error("Requested mime type is not available")]
+    InvalidMimetype,
+    /// Current selection is not provided by a client, but by the compositor
+    #[error("Current selection is server-side")]
+    ServerSideSelection,
+    /// There is no active selection
+    #[error("No active selection to read")]
+    NoSelection,
+}
+
+/// Request the contents of the current client-provided selection of this seat
+///
+/// The data will be written by the client owning the selection into the provided `fd`
+/// using the given `mime_type`. This is useful to bridge the selection to other
+/// consumers (e.g. X11 clients).
+pub fn request_data_device_client_selection<D>(
+    seat: &Seat<D>,
+    mime_type: String,
+    fd: OwnedFd,
+) -> Result<(), SelectionRequestError>
+where
+    D: SeatHandler + DataDeviceHandler + 'static,
+{
+    let seat_data = match seat.user_data().get::<RefCell<SeatData>>() {
+        Some(seat_data) => seat_data,
+        None => return Err(SelectionRequestError::NoSelection),
+    };
+    match seat_data.borrow().get_selection() {
+        Selection::Empty => Err(SelectionRequestError::NoSelection),
+        Selection::Compositor(_) => Err(SelectionRequestError::ServerSideSelection),
+        Selection::Client(source) => {
+            if !source.alive() {
+                return Err(SelectionRequestError::NoSelection);
+            }
+            let valid =
+                with_source_metadata(source, |meta| meta.mime_types.contains(&mime_type)).unwrap_or(false);
+            if !valid {
+                return Err(SelectionRequestError::InvalidMimetype);
+            }
+            source.send(mime_type, fd.as_raw_fd());
+            Ok(())
+        }
+        Selection::DataControl(source) => {
+            if !source.alive() {
+                return Err(SelectionRequestError::NoSelection);
+            }
+            let valid =
+                with_data_control_source_metadata(source, |meta| meta.mime_types.contains(&mime_type))
+                    .unwrap_or(false);
+            if !valid {
+                return Err(SelectionRequestError::InvalidMimetype);
+            }
+            source.send(mime_type, fd.as_raw_fd());
+            Ok(())
+        }
+    }
+}
+
 /// Start a drag'n'drop from a resource controlled by the compositor
 ///
 /// You'll receive events generated by the interaction of clients with your
 /// drag'n'drop in the provided callback. See [`ServerDndGrabHandler`] for details about
 /// which events can be generated and what response is expected from you to them.
-pub fn start_dnd<D, C>(
+pub fn start_dnd<D>(
     dh: &DisplayHandle,
     seat: &Seat<D>,
     data: &mut D,
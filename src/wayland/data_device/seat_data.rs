@@ -4,6 +4,11 @@ use std::{
 };
 
 use slog::debug;
+use wayland_protocols_wlr::data_control::v1::server::{
+    zwlr_data_control_device_v1::ZwlrDataControlDeviceV1,
+    zwlr_data_control_offer_v1::{self, ZwlrDataControlOfferV1},
+    zwlr_data_control_source_v1::ZwlrDataControlSourceV1,
+};
 use wayland_server::{
     backend::{protocol::Message, ClientId, Handle, ObjectData, ObjectId},
     protocol::{
@@ -14,18 +19,23 @@ use wayland_server::{
     Client, DisplayHandle, Resource,
 };
 
-use crate::utils::IsAlive;
+use crate::{
+    utils::IsAlive, wayland::data_control::with_source_metadata as with_data_control_source_metadata,
+};
 
 use super::{with_source_metadata, DataDeviceHandler, SourceMetadata};
 
+#[derive(Clone)]
 pub enum Selection {
     Empty,
     Client(WlDataSource),
+    DataControl(ZwlrDataControlSourceV1),
     Compositor(SourceMetadata),
 }
 
 pub struct SeatData {
     known_devices: Vec<WlDataDevice>,
+    known_data_control_devices: Vec<ZwlrDataControlDeviceV1>,
     selection: Selection,
     current_focus: Option<Client>,
 }
@@ -34,6 +44,7 @@ impl Default for SeatData {
     fn default() -> Self {
         Self {
             known_devices: Vec::new(),
+            known_data_control_devices: Vec::new(),
             selection: Selection::Empty,
             current_focus: None,
         }
@@ -60,6 +71,28 @@ impl SeatData {
         self.known_devices.retain(f)
     }
 
+    pub fn add_data_control_device<D>(&mut self, dh: &DisplayHandle, device: ZwlrDataControlDeviceV1)
+    where
+        D: DataDeviceHandler,
+        D: 'static,
+    {
+        // data control devices receive the current selection right away
+        self.cleanup_selection();
+        self.send_data_control_offer::<D>(dh, &device);
+        self.known_data_control_devices.push(device);
+    }
+
+    pub fn retain_data_control_devices<F>(&mut self, f: F)
+    where
+        F: FnMut(&ZwlrDataControlDeviceV1) -> bool,
+    {
+        self.known_data_control_devices.retain(f)
+    }
+
+    pub fn get_selection(&self) -> &Selection {
+        &self.selection
+    }
+
     pub fn set_selection<D>(&mut self, dh: &DisplayHandle, new_selection: Selection)
     where
         D: DataDeviceHandler,
@@ -73,8 +106,17 @@ impl SeatData {
                 }
             }
         }
+        if let Selection::DataControl(source) = &self.selection {
+            match &new_selection {
+                Selection::DataControl(new_source) if new_source == source => {}
+                _ => {
+                    source.cancelled();
+                }
+            }
+        }
         self.selection = new_selection;
         self.send_selection::<D>(dh);
+        self.send_data_control_selection::<D>(dh);
     }
 
     pub fn set_focus<D>(&mut self, dh: &DisplayHandle, new_focus: Option<Client>)
@@ -97,14 +139,7 @@ impl SeatData {
         };
         // first sanitize the selection, reseting it to null if the client holding
         // it dropped it
-        let cleanup = if let Selection::Client(ref data_source) = self.selection {
-            !data_source.alive()
-        } else {
-            false
-        };
-        if cleanup {
-            self.selection = Selection::Empty;
-        }
+        self.cleanup_selection();
 
         // then send it if appropriate
         match self.selection {
@@ -149,6 +184,37 @@ impl SeatData {
                     dd.selection(Some(&offer));
                 }
             }
+            Selection::DataControl(ref source) => {
+                for dd in &self.known_devices {
+                    // skip data devices not belonging to our client
+                    if dh.get_client(dd.id()).map(|c| &c != client).unwrap_or(true) {
+                        continue;
+                    }
+                    let source_clone = source.clone();
+
+                    let handle = dh.backend_handle();
+                    // create a data offer
+                    let offer = handle
+                        .create_object::<D>(
+                            client.id(),
+                            WlDataOffer::interface(),
+                            dd.version(),
+                            Arc::new(DataControlSelection { source: source_clone }),
+                        )
+                        .unwrap();
+                    let offer = WlDataOffer::from_id(dh, offer).unwrap();
+
+                    // advertize the offer to the client
+                    dd.data_offer(&offer);
+                    with_data_control_source_metadata(source, |meta| {
+                        for mime_type in meta.mime_types.iter().cloned() {
+                            offer.offer(mime_type);
+                        }
+                    })
+                    .unwrap();
+                    dd.selection(Some(&offer));
+                }
+            }
             Selection::Compositor(ref meta) => {
                 for dd in &self.known_devices {
                     // skip data devices not belonging to our client
@@ -180,6 +246,74 @@ impl SeatData {
             }
         }
     }
+
+    pub fn send_data_control_selection<D>(&mut self, dh: &DisplayHandle)
+    where
+        D: DataDeviceHandler,
+        D: 'static,
+    {
+        self.cleanup_selection();
+        for device in &self.known_data_control_devices {
+            self.send_data_control_offer::<D>(dh, device);
+        }
+    }
+
+    fn send_data_control_offer<D>(&self, dh: &DisplayHandle, device: &ZwlrDataControlDeviceV1)
+    where
+        D: DataDeviceHandler,
+        D: 'static,
+    {
+        let mime_types = match self.selection {
+            Selection::Empty => {
+                device.selection(None);
+                return;
+            }
+            Selection::Client(ref source) => {
+                with_source_metadata(source, |meta| meta.mime_types.clone()).unwrap_or_default()
+            }
+            Selection::DataControl(ref source) => {
+                with_data_control_source_metadata(source, |meta| meta.mime_types.clone()).unwrap_or_default()
+            }
+            Selection::Compositor(ref meta) => meta.mime_types.clone(),
+        };
+
+        let client = match dh.get_client(device.id()) {
+            Ok(client) => client,
+            Err(_) => return,
+        };
+        let handle = dh.backend_handle();
+        // create a data offer
+        let offer = match handle.create_object::<D>(
+            client.id(),
+            ZwlrDataControlOfferV1::interface(),
+            device.version(),
+            Arc::new(DataControlOffer {
+                selection: self.selection.clone(),
+            }),
+        ) {
+            Ok(offer) => offer,
+            Err(_) => return,
+        };
+        let offer = ZwlrDataControlOfferV1::from_id(dh, offer).unwrap();
+
+        // advertize the offer to the client
+        device.data_offer(&offer);
+        for mime_type in mime_types {
+            offer.offer(mime_type);
+        }
+        device.selection(Some(&offer));
+    }
+
+    fn cleanup_selection(&mut self) {
+        let cleanup = match self.selection {
+            Selection::Client(ref data_source) => !data_source.alive(),
+            Selection::DataControl(ref source) => !source.alive(),
+            _ => false,
+        };
+        if cleanup {
+            self.selection = Selection::Empty;
+        }
+    }
 }
 
 struct ClientSelection {
@@ -282,3 +416,97 @@ pub fn handle_server_selection<D>(
         }
     }
 }
+
+struct DataControlSelection {
+    source: ZwlrDataControlSourceV1,
+}
+
+impl<D> ObjectData<D> for DataControlSelection
+where
+    D: DataDeviceHandler,
+{
+    fn request(
+        self: Arc<Self>,
+        dh: &Handle,
+        handler: &mut D,
+        _client_id: ClientId,
+        msg: Message<ObjectId, OwnedFd>,
+    ) -> Option<Arc<dyn ObjectData<D>>> {
+        let dh = DisplayHandle::from(dh.clone());
+        if let Ok((_resource, wl_data_offer::Request::Receive { fd, mime_type })) =
+            WlDataOffer::parse_request(&dh, msg)
+        {
+            receive_selection(
+                handler,
+                &Selection::DataControl(self.source.clone()),
+                mime_type,
+                fd,
+            );
+        }
+
+        None
+    }
+
+    fn destroyed(&self, _data: &mut D, _client_id: ClientId, _object_id: ObjectId) {}
+}
+
+struct DataControlOffer {
+    selection: Selection,
+}
+
+impl<D> ObjectData<D> for DataControlOffer
+where
+    D: DataDeviceHandler,
+{
+    fn request(
+        self: Arc<Self>,
+        dh: &Handle,
+        handler: &mut D,
+        _client_id: ClientId,
+        msg: Message<ObjectId, OwnedFd>,
+    ) -> Option<Arc<dyn ObjectData<D>>> {
+        let dh = DisplayHandle::from(dh.clone());
+        if let Ok((_resource, zwlr_data_control_offer_v1::Request::Receive { mime_type, fd })) =
+            ZwlrDataControlOfferV1::parse_request(&dh, msg)
+        {
+            receive_selection(handler, &self.selection, mime_type, fd);
+        }
+
+        None
+    }
+
+    fn destroyed(&self, _data: &mut D, _client_id: ClientId, _object_id: ObjectId) {}
+}
+
+/// Forward a receive request of an offer to the source of the selection
+fn receive_selection<D>(handler: &mut D, selection: &Selection, mime_type: String, fd: OwnedFd)
+where
+    D: DataDeviceHandler,
+{
+    let valid = match selection {
+        Selection::Empty => false,
+        Selection::Client(source) => {
+            with_source_metadata(source, |meta| meta.mime_types.contains(&mime_type)).unwrap_or(false)
+        }
+        Selection::DataControl(source) => {
+            with_data_control_source_metadata(source, |meta| meta.mime_types.contains(&mime_type))
+                .unwrap_or(false)
+        }
+        Selection::Compositor(meta) => meta.mime_types.contains(&mime_type),
+    };
+    if !valid {
+        // deny the receive
+        debug!(
+            handler.data_device_state().log,
+            "Denying a selection receive with invalid source."
+        );
+        return;
+    }
+
+    match selection {
+        Selection::Empty => {}
+        Selection::Client(source) => source.send(mime_type, fd.as_raw_fd()),
+        Selection::DataControl(source) => source.send(mime_type, fd.as_raw_fd()),
+        Selection::Compositor(_) => handler.send_selection(mime_type, fd),
+    }
+}
@@ -1,18 +1,22 @@
 use std::sync::{atomic::AtomicBool, Mutex};
 
-use wayland_protocols::wp::linux_dmabuf::zv1::server::{zwp_linux_buffer_params_v1, zwp_linux_dmabuf_v1};
+use wayland_protocols::wp::linux_dmabuf::zv1::server::{
+    zwp_linux_buffer_params_v1, zwp_linux_dmabuf_feedback_v1, zwp_linux_dmabuf_v1,
+};
 use wayland_server::{
-    protocol::wl_buffer, Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+    backend::{ClientId, ObjectId},
+    protocol::wl_buffer,
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
 };
 
 use crate::{
     backend::allocator::dmabuf::{Dmabuf, Plane, MAX_PLANES},
-    wayland::buffer::BufferHandler,
+    wayland::{buffer::BufferHandler, compositor},
 };
 
 use super::{
-    DmabufData, DmabufGlobal, DmabufGlobalData, DmabufHandler, DmabufParamsData, DmabufState, ImportError,
-    Modifier,
+    DmabufData, DmabufFeedbackData, DmabufGlobal, DmabufGlobalData, DmabufHandler, DmabufParamsData,
+    DmabufState, ImportError, Modifier, SurfaceDmabufFeedbackState,
 };
 
 impl<D> Dispatch<wl_buffer::WlBuffer, Dmabuf, D> for DmabufState
@@ -42,10 +46,12 @@ impl<D> Dispatch<zwp_linux_dmabuf_v1::ZwpLinuxDmabufV1, DmabufData, D> for Dmabu
 where
     D: Dispatch<zwp_linux_dmabuf_v1::ZwpLinuxDmabufV1, DmabufData>
         + Dispatch<zwp_linux_buffer_params_v1::ZwpLinuxBufferParamsV1, DmabufParamsData>
+        + Dispatch<zwp_linux_dmabuf_feedback_v1::ZwpLinuxDmabufFeedbackV1, DmabufFeedbackData>
+        + DmabufHandler
         + 'static,
 {
     fn request(
-        _state: &mut D,
+        state: &mut D,
         _client: &Client,
         _resource: &zwp_linux_dmabuf_v1::ZwpLinuxDmabufV1,
         request: zwp_linux_dmabuf_v1::Request,
@@ -69,9 +75,45 @@ where
                 );
             }
 
-            zwp_linux_dmabuf_v1::Request::GetDefaultFeedback { id: _ } => unimplemented!("v4"),
+            zwp_linux_dmabuf_v1::Request::GetDefaultFeedback { id } => {
+                let feedback = data_init.init(id, DmabufFeedbackData { surface: None });
+
+                // Version 4 is only advertised for globals with a default feedback
+                if let Some(default_feedback) = data.default_feedback.as_ref() {
+                    default_feedback.send(&feedback);
+                }
+            }
 
-            zwp_linux_dmabuf_v1::Request::GetSurfaceFeedback { id: _, surface: _ } => unimplemented!("v4"),
+            zwp_linux_dmabuf_v1::Request::GetSurfaceFeedback { id, surface } => {
+                let feedback = data_init.init(
+                    id,
+                    DmabufFeedbackData {
+                        surface: Some(surface.clone()),
+                    },
+                );
+
+                let surface_feedback = compositor::with_states(&surface, |states| {
+                    let mut state = SurfaceDmabufFeedbackState::from_states(states).lock().unwrap();
+                    state.known_instances.push(feedback.clone());
+                    state.feedback.clone()
+                });
+                let surface_feedback = surface_feedback
+                    .or_else(|| {
+                        let feedback = state.new_surface_feedback(&surface, &DmabufGlobal { id: data.id })?;
+                        compositor::with_states(&surface, |states| {
+                            SurfaceDmabufFeedbackState::from_states(states)
+                                .lock()
+                                .unwrap()
+                                .feedback = Some(feedback.clone());
+                        });
+                        Some(feedback)
+                    })
+                    .or_else(|| data.default_feedback.clone());
+
+                if let Some(surface_feedback) = surface_feedback {
+                    surface_feedback.send(&feedback);
+                }
+            }
 
             _ => unreachable!(),
         }
@@ -95,6 +137,7 @@ where
     ) {
         let data = DmabufData {
             formats: global_data.formats.clone(),
+            default_feedback: global_data.default_feedback.clone(),
             id: global_data.id,
             logger: global_data.logger.clone(),
         };
@@ -123,6 +166,38 @@ where
     }
 }
 
+impl<D> Dispatch<zwp_linux_dmabuf_feedback_v1::ZwpLinuxDmabufFeedbackV1, DmabufFeedbackData, D>
+    for DmabufState
+where
+    D: Dispatch<zwp_linux_dmabuf_feedback_v1::ZwpLinuxDmabufFeedbackV1, DmabufFeedbackData>,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _resource: &zwp_linux_dmabuf_feedback_v1::ZwpLinuxDmabufFeedbackV1,
+        request: zwp_linux_dmabuf_feedback_v1::Request,
+        _data: &DmabufFeedbackData,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_linux_dmabuf_feedback_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(_state: &mut D, _client: ClientId, object_id: ObjectId, data: &DmabufFeedbackData) {
+        if let Some(surface) = data.surface.as_ref().filter(|surface| surface.is_alive()) {
+            compositor::with_states(surface, |states| {
+                SurfaceDmabufFeedbackState::from_states(states)
+                    .lock()
+                    .unwrap()
+                    .remove_instance(&object_id);
+            });
+        }
+    }
+}
+
 impl<D> Dispatch<zwp_linux_buffer_params_v1::ZwpLinuxBufferParamsV1, DmabufParamsData, D> for DmabufState
 where
     D: Dispatch<zwp_linux_buffer_params_v1::ZwpLinuxBufferParamsV1, DmabufParamsData>
@@ -0,0 +1,221 @@
+use std::{
+    ffi::CString,
+    io,
+    os::unix::io::AsRawFd,
+    sync::{Arc, Mutex},
+};
+
+use indexmap::IndexSet;
+use wayland_protocols::wp::linux_dmabuf::zv1::server::zwp_linux_dmabuf_feedback_v1;
+use wayland_server::{backend::ObjectId, protocol::wl_surface::WlSurface, Resource};
+
+use crate::{backend::allocator::Format, utils::sealed_file::SealedFile};
+
+/// Feedback for dmabuf allocation
+///
+/// Describes the device and the formats a compositor prefers clients to allocate their buffers with.
+/// The formats of a feedback are grouped into tranches, ordered by preference. Use a
+/// [`DmabufFeedbackBuilder`] to create a new feedback.
+///
+/// Cloning a feedback is cheap, the format table is shared between all clones.
+#[derive(Debug, Clone)]
+pub struct DmabufFeedback(Arc<DmabufFeedbackInner>);
+
+impl PartialEq for DmabufFeedback {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[derive(Debug)]
+struct DmabufFeedbackInner {
+    format_table: SealedFile,
+    formats: Vec<Format>,
+    main_device: libc::dev_t,
+    tranches: Vec<DmabufFeedbackTranche>,
+}
+
+#[derive(Debug)]
+struct DmabufFeedbackTranche {
+    target_device: libc::dev_t,
+    flags: zwp_linux_dmabuf_feedback_v1::TrancheFlags,
+    indices: Vec<u16>,
+}
+
+impl DmabufFeedback {
+    /// Returns the main device of this feedback
+    pub fn main_device(&self) -> libc::dev_t {
+        self.0.main_device
+    }
+
+    /// Returns all formats contained in this feedback
+    pub fn formats(&self) -> impl Iterator<Item = &Format> {
+        self.0.formats.iter()
+    }
+
+    pub(super) fn send(&self, feedback: &zwp_linux_dmabuf_feedback_v1::ZwpLinuxDmabufFeedbackV1) {
+        feedback.format_table(self.0.format_table.as_raw_fd(), self.0.format_table.size() as u32);
+        feedback.main_device(self.0.main_device.to_ne_bytes().to_vec());
+
+        for tranche in &self.0.tranches {
+            feedback.tranche_target_device(tranche.target_device.to_ne_bytes().to_vec());
+            feedback.tranche_flags(tranche.flags);
+            feedback.tranche_formats(
+                tranche
+                    .indices
+                    .iter()
+                    .flat_map(|index| index.to_ne_bytes())
+                    .collect::<Vec<u8>>(),
+            );
+            feedback.tranche_done();
+        }
+
+        feedback.done();
+    }
+}
+
+#[derive(Debug)]
+struct PreferenceTranche {
+    target_device: libc::dev_t,
+    flags: zwp_linux_dmabuf_feedback_v1::TrancheFlags,
+    formats: Vec<Format>,
+}
+
+/// Builder for a [`DmabufFeedback`]
+///
+/// ```no_run
+/// # use smithay::wayland::dmabuf::DmabufFeedbackBuilder;
+/// # use smithay::reexports::wayland_protocols::wp::linux_dmabuf::zv1::server::zwp_linux_dmabuf_feedback_v1::TrancheFlags;
+/// # let (main_device, scanout_device) = (0, 0);
+/// # let (renderer_formats, plane_formats) = (Vec::new(), Vec::new());
+/// let feedback = DmabufFeedbackBuilder::new(main_device, renderer_formats)
+///     // formats which can be directly scanned out are preferred
+///     .add_preference_tranche(scanout_device, Some(TrancheFlags::Scanout), plane_formats)
+///     .build()
+///     .expect("failed to create the format table");
+/// ```
+#[derive(Debug)]
+pub struct DmabufFeedbackBuilder {
+    main_device: libc::dev_t,
+    formats: Vec<Format>,
+    preferred_tranches: Vec<PreferenceTranche>,
+}
+
+impl DmabufFeedbackBuilder {
+    /// Create a new feedback builder
+    ///
+    /// The `main_device` is the device the compositor uses for importing buffers, e.g. the
+    /// render node of its renderer. The `formats` will be advertised as the last tranche,
+    /// which should contain all formats the compositor can import.
+    pub fn new(main_device: libc::dev_t, formats: impl IntoIterator<Item = Format>) -> Self {
+        DmabufFeedbackBuilder {
+            main_device,
+            formats: formats.into_iter().collect(),
+            preferred_tranches: Vec::new(),
+        }
+    }
+
+    /// Add a preference tranche to the feedback
+    ///
+    /// Preference tranches are advertised in the order they were added, before the main tranche.
+    /// Formats not contained in the main tranche are ignored.
+    pub fn add_preference_tranche(
+        mut self,
+        target_device: libc::dev_t,
+        flags: Option<zwp_linux_dmabuf_feedback_v1::TrancheFlags>,
+        formats: impl IntoIterator<Item = Format>,
+    ) -> Self {
+        let formats = formats
+            .into_iter()
+            .filter(|format| self.formats.contains(format))
+            .collect();
+        self.preferred_tranches.push(PreferenceTranche {
+            target_device,
+            flags: flags.unwrap_or_else(zwp_linux_dmabuf_feedback_v1::TrancheFlags::empty),
+            formats,
+        });
+        self
+    }
+
+    /// Build the feedback, writing the format table into a sealed memory file
+    pub fn build(self) -> io::Result<DmabufFeedback> {
+        let formats = self.formats.iter().copied().collect::<IndexSet<Format>>();
+        if formats.len() > u16::MAX as usize + 1 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "too many formats for a dmabuf feedback format table",
+            ));
+        }
+
+        // Each entry of the format table consists of the format (u32),
+        // 4 bytes of padding and the modifier (u64).
+        let mut table = Vec::with_capacity(formats.len() * 16);
+        for format in formats.iter() {
+            table.extend_from_slice(&(format.code as u32).to_ne_bytes());
+            table.extend_from_slice(&[0; 4]);
+            table.extend_from_slice(&Into::<u64>::into(format.modifier).to_ne_bytes());
+        }
+        let format_table = SealedFile::with_data(
+            CString::new("smithay-dmabuf-feedback-format-table").unwrap(),
+            &table,
+        )?;
+
+        let indices = |tranche_formats: &[Format]| {
+            tranche_formats
+                .iter()
+                .filter_map(|format| formats.get_index_of(format))
+                .map(|index| index as u16)
+                .collect::<Vec<_>>()
+        };
+
+        let mut tranches = self
+            .preferred_tranches
+            .iter()
+            .map(|tranche| DmabufFeedbackTranche {
+                target_device: tranche.target_device,
+                flags: tranche.flags,
+                indices: indices(&tranche.formats),
+            })
+            .collect::<Vec<_>>();
+        tranches.push(DmabufFeedbackTranche {
+            target_device: self.main_device,
+            flags: zwp_linux_dmabuf_feedback_v1::TrancheFlags::empty(),
+            indices: indices(&self.formats),
+        });
+
+        Ok(DmabufFeedback(Arc::new(DmabufFeedbackInner {
+            format_table,
+            formats: formats.into_iter().collect(),
+            main_device: self.main_device,
+            tranches,
+        })))
+    }
+}
+
+/// Data associated with a dmabuf feedback protocol object.
+#[derive(Debug)]
+pub struct DmabufFeedbackData {
+    pub(super) surface: Option<WlSurface>,
+}
+
+/// Per-surface feedback state, stored in the surface data
+#[derive(Debug, Default)]
+pub(super) struct SurfaceDmabufFeedbackState {
+    pub(super) feedback: Option<DmabufFeedback>,
+    pub(super) known_instances: Vec<zwp_linux_dmabuf_feedback_v1::ZwpLinuxDmabufFeedbackV1>,
+}
+
+impl SurfaceDmabufFeedbackState {
+    pub(super) fn from_states(
+        states: &crate::wayland::compositor::SurfaceData,
+    ) -> &Mutex<SurfaceDmabufFeedbackState> {
+        states
+            .data_map
+            .insert_if_missing_threadsafe(|| Mutex::new(SurfaceDmabufFeedbackState::default()));
+        states.data_map.get::<Mutex<Self>>().unwrap()
+    }
+
+    pub(super) fn remove_instance(&mut self, id: &ObjectId) {
+        self.known_instances.retain(|instance| &instance.id() != id);
+    }
+}
@@ -18,6 +18,17 @@
 //! Accessing a [`Dmabuf`] associated with a [`WlBuffer`](wayland_server::protocol::wl_buffer::WlBuffer)
 //! may be achieved using [`get_dmabuf`].
 //!
+//! ## Dmabuf feedback
+//!
+//! Version 4 of the protocol replaces the format list by [`DmabufFeedback`], which additionally tells
+//! clients which device to allocate buffers on and which formats are preferred, for example because
+//! they can be scanned out directly. Create the global with
+//! [`DmabufState::create_global_with_default_feedback`] to enable it.
+//!
+//! Clients may also request feedback for a single surface. These start out with the default feedback
+//! (or the one returned by [`DmabufHandler::new_surface_feedback`]) and can be updated at any time
+//! using [`DmabufState::set_surface_feedback`], e.g. once a surface could be scanned out on a plane.
+//!
 //! ```no_run
 //! # extern crate wayland_server;
 //! use smithay::{
@@ -90,6 +101,10 @@
 //! ```
 
 mod dispatch;
+mod feedback;
+
+use self::feedback::SurfaceDmabufFeedbackState;
+pub use self::feedback::{DmabufFeedback, DmabufFeedbackBuilder, DmabufFeedbackData};
 
 use std::{
     collections::HashMap,
@@ -104,7 +119,9 @@ use std::{
 use nix::unistd;
 use wayland_protocols::wp::linux_dmabuf::zv1::server::{zwp_linux_buffer_params_v1, zwp_linux_dmabuf_v1};
 use wayland_server::{
-    backend::GlobalId, protocol::wl_buffer, Client, DisplayHandle, GlobalDispatch, Resource, WEnum,
+    backend::GlobalId,
+    protocol::{wl_buffer, wl_surface::WlSurface},
+    Client, DisplayHandle, GlobalDispatch, Resource, WEnum,
 };
 
 use crate::{
@@ -113,6 +130,7 @@ use crate::{
         Format, Fourcc, Modifier,
     },
     utils::{ids::id_gen, UnmanagedResource},
+    wayland::compositor,
 };
 
 use super::buffer::BufferHandler;
@@ -152,6 +170,53 @@ impl DmabufState {
         self.create_global_with_filter::<D, _, L>(display, formats, |_| true, logger)
     }
 
+    /// Creates a dmabuf global with the specified default feedback.
+    ///
+    /// The global is advertised at version 4 and the supported formats are taken from the feedback.
+    pub fn create_global_with_default_feedback<D, L>(
+        &mut self,
+        display: &DisplayHandle,
+        default_feedback: &DmabufFeedback,
+        logger: L,
+    ) -> DmabufGlobal
+    where
+        D: GlobalDispatch<zwp_linux_dmabuf_v1::ZwpLinuxDmabufV1, DmabufGlobalData>
+            + BufferHandler
+            + DmabufHandler
+            + 'static,
+        L: Into<Option<::slog::Logger>>,
+    {
+        self.create_global_with_filter_and_default_feedback::<D, _, L>(
+            display,
+            default_feedback,
+            |_| true,
+            logger,
+        )
+    }
+
+    /// Creates a dmabuf global with the specified default feedback.
+    ///
+    /// This function unlike [`DmabufState::create_global_with_default_feedback`] also allows you to specify a
+    /// filter function to determine which clients may see this global.
+    pub fn create_global_with_filter_and_default_feedback<D, F, L>(
+        &mut self,
+        display: &DisplayHandle,
+        default_feedback: &DmabufFeedback,
+        filter: F,
+        logger: L,
+    ) -> DmabufGlobal
+    where
+        D: GlobalDispatch<zwp_linux_dmabuf_v1::ZwpLinuxDmabufV1, DmabufGlobalData>
+            + BufferHandler
+            + DmabufHandler
+            + 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+        L: Into<Option<::slog::Logger>>,
+    {
+        let formats = default_feedback.formats().copied().collect();
+        self.create_global_inner::<D, F, L>(display, formats, Some(default_feedback.clone()), filter, logger)
+    }
+
     /// Creates a dmabuf global with the specified supported formats.
     ///
     /// This function unlike [`DmabufState::create_global`] also allows you to specify a filter function to
@@ -164,6 +229,25 @@ impl DmabufState {
         filter: F,
         logger: L,
     ) -> DmabufGlobal
+    where
+        D: GlobalDispatch<zwp_linux_dmabuf_v1::ZwpLinuxDmabufV1, DmabufGlobalData>
+            + BufferHandler
+            + DmabufHandler
+            + 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+        L: Into<Option<::slog::Logger>>,
+    {
+        self.create_global_inner::<D, F, L>(display, formats, None, filter, logger)
+    }
+
+    fn create_global_inner<D, F, L>(
+        &mut self,
+        display: &DisplayHandle,
+        formats: Vec<Format>,
+        default_feedback: Option<DmabufFeedback>,
+        filter: F,
+        logger: L,
+    ) -> DmabufGlobal
     where
         D: GlobalDispatch<zwp_linux_dmabuf_v1::ZwpLinuxDmabufV1, DmabufGlobalData>
             + BufferHandler
@@ -176,15 +260,21 @@ impl DmabufState {
         let logger = crate::slog_or_fallback(logger)
             .new(slog::o!("smithay_module" => "wayland_dmabuf", "global" => id));
         let formats = Arc::new(formats);
+        // Version 4 clients only learn about the formats through the feedback
+        let version = if default_feedback.is_some() {
+            GLOBAL_VERSION
+        } else {
+            FORMATS_GLOBAL_VERSION
+        };
         let data = DmabufGlobalData {
             filter: Box::new(filter),
             formats,
+            default_feedback,
             id,
             logger,
         };
 
-        let global =
-            display.create_global::<D, zwp_linux_dmabuf_v1::ZwpLinuxDmabufV1, _>(GLOBAL_VERSION, data);
+        let global = display.create_global::<D, zwp_linux_dmabuf_v1::ZwpLinuxDmabufV1, _>(version, data);
         self.globals.insert(id, global);
 
         DmabufGlobal { id }
@@ -206,6 +296,34 @@ impl DmabufState {
             display.remove_global::<D>(self.globals.remove(&global.id).unwrap());
         }
     }
+
+    /// Sets the dmabuf feedback of a surface.
+    ///
+    /// The feedback is sent to all feedback objects the client created for this surface and will be used
+    /// for any feedback objects created later.
+    pub fn set_surface_feedback(&mut self, surface: &WlSurface, feedback: &DmabufFeedback) {
+        compositor::with_states(surface, |states| {
+            let mut state = SurfaceDmabufFeedbackState::from_states(states).lock().unwrap();
+            if state.feedback.as_ref() == Some(feedback) {
+                return;
+            }
+            state.feedback = Some(feedback.clone());
+            for instance in &state.known_instances {
+                feedback.send(instance);
+            }
+        })
+    }
+
+    /// Returns the dmabuf feedback set for a surface using [`DmabufState::set_surface_feedback`], if any.
+    pub fn surface_feedback(&self, surface: &WlSurface) -> Option<DmabufFeedback> {
+        compositor::with_states(surface, |states| {
+            SurfaceDmabufFeedbackState::from_states(states)
+                .lock()
+                .unwrap()
+                .feedback
+                .clone()
+        })
+    }
 }
 
 /// Data associated with a dmabuf global.
@@ -213,6 +331,7 @@ impl DmabufState {
 pub struct DmabufGlobalData {
     filter: Box<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
     formats: Arc<Vec<Format>>,
+    default_feedback: Option<DmabufFeedback>,
     id: usize,
     logger: slog::Logger,
 }
@@ -221,6 +340,7 @@ pub struct DmabufGlobalData {
 #[derive(Debug)]
 pub struct DmabufData {
     formats: Arc<Vec<Format>>,
+    default_feedback: Option<DmabufFeedback>,
     id: usize,
     logger: slog::Logger,
 }
@@ -267,6 +387,15 @@ pub trait DmabufHandler: BufferHandler {
     /// If the import fails due to an implementation specific reason, then [`ImportError::Failed`] should be
     /// returned.
     fn dmabuf_imported(&mut self, global: &DmabufGlobal, dmabuf: Dmabuf) -> Result<(), ImportError>;
+
+    /// A client requested dmabuf feedback for a surface, which has no feedback set yet.
+    ///
+    /// Returning `None` (the default) uses the default feedback of the `global`. Use
+    /// [`DmabufState::set_surface_feedback`] to update the feedback of the surface later on.
+    fn new_surface_feedback(&mut self, surface: &WlSurface, global: &DmabufGlobal) -> Option<DmabufFeedback> {
+        let _ = (surface, global);
+        None
+    }
 }
 
 /// Error that may occur when importing a [`Dmabuf`].
@@ -309,6 +438,8 @@ macro_rules! delegate_dmabuf {
             $crate::reexports::wayland_protocols::wp::linux_dmabuf::zv1::server::zwp_linux_dmabuf_v1::ZwpLinuxDmabufV1;
         type __ZwpLinuxBufferParamsV1 =
             $crate::reexports::wayland_protocols::wp::linux_dmabuf::zv1::server::zwp_linux_buffer_params_v1::ZwpLinuxBufferParamsV1;
+        type __ZwpLinuxDmabufFeedbackV1 =
+            $crate::reexports::wayland_protocols::wp::linux_dmabuf::zv1::server::zwp_linux_dmabuf_feedback_v1::ZwpLinuxDmabufFeedbackV1;
 
         $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
             __ZwpLinuxDmabufV1: $crate::wayland::dmabuf::DmabufGlobalData
@@ -320,13 +451,18 @@ macro_rules! delegate_dmabuf {
         $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
             __ZwpLinuxBufferParamsV1: $crate::wayland::dmabuf::DmabufParamsData
         ] => $crate::wayland::dmabuf::DmabufState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            __ZwpLinuxDmabufFeedbackV1: $crate::wayland::dmabuf::DmabufFeedbackData
+        ] => $crate::wayland::dmabuf::DmabufState);
         $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
             $crate::reexports::wayland_server::protocol::wl_buffer::WlBuffer: $crate::backend::allocator::dmabuf::Dmabuf
         ] => $crate::wayland::dmabuf::DmabufState);
     };
 }
 
-const GLOBAL_VERSION: u32 = 3;
+const GLOBAL_VERSION: u32 = 4;
+/// Version used for globals without a default feedback
+const FORMATS_GLOBAL_VERSION: u32 = 3;
 
 impl DmabufParamsData {
     /// Emits a protocol error if the params have already been used to create a dmabuf.
@@ -0,0 +1,481 @@
+//! Utilities for handling the `linux-drm-syncobj-v1` protocol
+//!
+//! This protocol allows clients to use explicit synchronization for their buffers,
+//! using timeline points of DRM synchronization objects.
+//!
+//! With every buffer a client attaches, it provides an acquire point, which is signalled
+//! once the client is done rendering into the buffer, and a release point, which has to be
+//! signalled by the compositor once it is done using the buffer.
+//!
+//! ## How to use it
+//!
+//! ### Initialization
+//!
+//! The timelines of the clients are imported into a DRM device, typically the one used by your
+//! renderer. Waiting on timeline points requires kernel support for syncobj eventfds (linux 6.6),
+//! which can be checked with [`supports_syncobj_eventfd`]. The global should not be advertised,
+//! if that is not the case.
+//!
+//! ```no_run
+//! # extern crate wayland_server;
+//! use smithay::delegate_drm_syncobj;
+//! use smithay::wayland::drm_syncobj::{supports_syncobj_eventfd, DrmSyncobjHandler, DrmSyncobjState};
+//! # use smithay::backend::drm::DrmDeviceFd;
+//!
+//! pub struct State {
+//!     drm_syncobj_state: Option<DrmSyncobjState>,
+//! }
+//!
+//! impl DrmSyncobjHandler for State {
+//!     fn drm_syncobj_state(&mut self) -> &mut DrmSyncobjState {
+//!         self.drm_syncobj_state.as_mut().unwrap()
+//!     }
+//! }
+//! delegate_drm_syncobj!(State);
+//!
+//! # let display = wayland_server::Display::<State>::new().unwrap();
+//! # let device: DrmDeviceFd = unimplemented!();
+//! let drm_syncobj_state = if supports_syncobj_eventfd(&device) {
+//!     Some(DrmSyncobjState::new::<State>(&display.handle(), device))
+//! } else {
+//!     None
+//! };
+//! ```
+//!
+//! ### Acquire points
+//!
+//! The state of a commit must not be applied before its acquire point is signalled. To achieve this,
+//! attach a [`Blocker`](crate::wayland::compositor::Blocker) for the pending acquire point in your
+//! [`CompositorHandler::pre_commit`](crate::wayland::compositor::CompositorHandler::pre_commit)
+//! implementation:
+//!
+//! ```no_compile
+//! fn pre_commit(&mut self, surface: &WlSurface) {
+//!     let acquire_point = with_states(surface, |states| {
+//!         states.cached_state.pending::<DrmSyncobjCachedState>().acquire_point.clone()
+//!     });
+//!     if let Some(acquire_point) = acquire_point {
+//!         if let Ok((blocker, source)) = acquire_point.generate_blocker() {
+//!             let client = self.display_handle.get_client(surface.id()).unwrap().id();
+//!             self.loop_handle.insert_source(source, move |_, _, state| {
+//!                 let dh = state.display_handle.clone();
+//!                 CompositorState::blocker_cleared(state, &dh, &client);
+//!             }).unwrap();
+//!             add_blocker(surface, blocker);
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! ### Release points
+//!
+//! The release point of a buffer is available through the [`DrmSyncobjCachedState`] of the surface.
+//! If you use [`on_commit_buffer_handler`](crate::backend::renderer::utils::on_commit_buffer_handler),
+//! the release point is attached to the [`Buffer`](crate::backend::renderer::utils::Buffer) of the commit
+//! and signalled once the last reference to it is dropped. Fences of pending gpu or scanout work using
+//! the buffer can be added with [`Buffer::add_release_fence`](crate::backend::renderer::utils::Buffer::add_release_fence),
+//! in which case the release point is only signalled after them.
+//! The [`DrmCompositor`](crate::backend::drm::compositor::DrmCompositor) does this for directly scanned out
+//! buffers on atomic devices.
+
+use std::{
+    os::unix::io::{AsFd, AsRawFd},
+    sync::Mutex,
+};
+
+use wayland_server::{
+    backend::GlobalId, protocol::wl_surface::WlSurface, Client, DataInit, Dispatch, DisplayHandle,
+    GlobalDispatch, New, Resource,
+};
+
+use crate::{
+    backend::drm::DrmDeviceFd,
+    utils::IsAlive,
+    wayland::{
+        compositor::{self, with_states, BufferAssignment, Cacheable, SurfaceAttributes},
+        dmabuf::get_dmabuf,
+    },
+};
+
+pub mod protocol;
+mod sync_point;
+
+pub(crate) use self::sync_point::merge_sync_files;
+pub use self::sync_point::{DrmSyncPoint, DrmSyncPointBlocker, DrmSyncPointSource, DrmTimeline};
+
+use self::protocol::{
+    wp_linux_drm_syncobj_manager_v1::{self, WpLinuxDrmSyncobjManagerV1},
+    wp_linux_drm_syncobj_surface_v1::{self, WpLinuxDrmSyncobjSurfaceV1},
+    wp_linux_drm_syncobj_timeline_v1::{self, WpLinuxDrmSyncobjTimelineV1},
+};
+
+/// Checks, if the given device supports waiting on syncobj timeline points using eventfds
+///
+/// This is required to implement the protocol and needs linux 6.6 or newer.
+pub fn supports_syncobj_eventfd(device: &DrmDeviceFd) -> bool {
+    sync_point::supports_syncobj_eventfd(device)
+}
+
+/// Handler trait for the linux-drm-syncobj protocol
+pub trait DrmSyncobjHandler {
+    /// [`DrmSyncobjState`] getter
+    fn drm_syncobj_state(&mut self) -> &mut DrmSyncobjState;
+}
+
+/// Double-buffered explicit synchronization state of a surface
+///
+/// Both points are set, if and only if a new buffer was attached by the commit.
+#[derive(Debug, Default)]
+pub struct DrmSyncobjCachedState {
+    /// Point to be signalled before the buffer may be accessed
+    pub acquire_point: Option<DrmSyncPoint>,
+    /// Point to be signalled once the compositor is done using the buffer
+    pub release_point: Option<DrmSyncPoint>,
+}
+
+impl Cacheable for DrmSyncobjCachedState {
+    fn commit(&mut self, _dh: &DisplayHandle) -> Self {
+        DrmSyncobjCachedState {
+            acquire_point: self.acquire_point.take(),
+            release_point: self.release_point.take(),
+        }
+    }
+
+    fn merge_into(self, into: &mut Self, _dh: &DisplayHandle) {
+        if self.acquire_point.is_some() {
+            into.acquire_point = self.acquire_point;
+        }
+        if self.release_point.is_some() {
+            // the buffer of the replaced state was never used
+            if let Some(release_point) = std::mem::replace(&mut into.release_point, self.release_point) {
+                let _ = release_point.signal();
+            }
+        }
+    }
+}
+
+/// State of the linux-drm-syncobj global
+#[derive(Debug)]
+pub struct DrmSyncobjState {
+    global: GlobalId,
+    import_device: DrmDeviceFd,
+}
+
+impl DrmSyncobjState {
+    /// Create a new `wp_linux_drm_syncobj_manager_v1` global
+    ///
+    /// Timelines of clients are imported into the given `import_device`.
+    pub fn new<D>(display: &DisplayHandle, import_device: DrmDeviceFd) -> Self
+    where
+        D: GlobalDispatch<WpLinuxDrmSyncobjManagerV1, ()>
+            + Dispatch<WpLinuxDrmSyncobjManagerV1, ()>
+            + Dispatch<WpLinuxDrmSyncobjTimelineV1, DrmTimeline>
+            + Dispatch<WpLinuxDrmSyncobjSurfaceV1, DrmSyncobjSurfaceData>
+            + DrmSyncobjHandler
+            + 'static,
+    {
+        let global = display.create_global::<D, WpLinuxDrmSyncobjManagerV1, _>(1, ());
+        DrmSyncobjState {
+            global,
+            import_device,
+        }
+    }
+
+    /// Returns the id of the global
+    pub fn global(&self) -> GlobalId {
+        self.global.clone()
+    }
+}
+
+/// User data of the `wp_linux_drm_syncobj_surface_v1` objects
+#[derive(Debug)]
+pub struct DrmSyncobjSurfaceData {
+    surface: WlSurface,
+}
+
+// The syncobj surface object currently associated with a surface
+struct SyncobjSurfaceMarker(Mutex<Option<WpLinuxDrmSyncobjSurfaceV1>>);
+
+fn surface_marker(states: &compositor::SurfaceData) -> &SyncobjSurfaceMarker {
+    states
+        .data_map
+        .insert_if_missing_threadsafe(|| SyncobjSurfaceMarker(Mutex::new(None)));
+    states.data_map.get::<SyncobjSurfaceMarker>().unwrap()
+}
+
+fn commit_hook(_dh: &DisplayHandle, surface: &WlSurface) {
+    with_states(surface, |states| {
+        let syncobj_surface = surface_marker(states).0.lock().unwrap();
+        let syncobj_surface = match &*syncobj_surface {
+            Some(syncobj_surface) => syncobj_surface,
+            None => return,
+        };
+
+        let state = states.cached_state.pending::<DrmSyncobjCachedState>();
+        let attributes = states.cached_state.pending::<SurfaceAttributes>();
+        match &attributes.buffer {
+            Some(BufferAssignment::NewBuffer(buffer)) => {
+                if get_dmabuf(buffer).is_err() {
+                    syncobj_surface.post_error(
+                        wp_linux_drm_syncobj_surface_v1::Error::UnsupportedBuffer,
+                        "explicit synchronization is only supported for dmabufs",
+                    );
+                    return;
+                }
+                let (acquire_point, release_point) = match (&state.acquire_point, &state.release_point) {
+                    (Some(acquire_point), Some(release_point)) => (acquire_point, release_point),
+                    (None, _) => {
+                        syncobj_surface.post_error(
+                            wp_linux_drm_syncobj_surface_v1::Error::NoAcquirePoint,
+                            "no acquire point set for the attached buffer",
+                        );
+                        return;
+                    }
+                    (_, None) => {
+                        syncobj_surface.post_error(
+                            wp_linux_drm_syncobj_surface_v1::Error::NoReleasePoint,
+                            "no release point set for the attached buffer",
+                        );
+                        return;
+                    }
+                };
+                if acquire_point.timeline == release_point.timeline
+                    && acquire_point.point >= release_point.point
+                {
+                    syncobj_surface.post_error(
+                        wp_linux_drm_syncobj_surface_v1::Error::ConflictingPoints,
+                        format!(
+                            "release point {} is not after acquire point {} on the same timeline",
+                            release_point.point, acquire_point.point
+                        ),
+                    );
+                }
+            }
+            _ => {
+                if state.acquire_point.is_some() || state.release_point.is_some() {
+                    syncobj_surface.post_error(
+                        wp_linux_drm_syncobj_surface_v1::Error::NoBuffer,
+                        "timeline points set without attaching a buffer",
+                    );
+                }
+            }
+        }
+    });
+}
+
+impl<D> GlobalDispatch<WpLinuxDrmSyncobjManagerV1, (), D> for DrmSyncobjState
+where
+    D: GlobalDispatch<WpLinuxDrmSyncobjManagerV1, ()>
+        + Dispatch<WpLinuxDrmSyncobjManagerV1, ()>
+        + Dispatch<WpLinuxDrmSyncobjTimelineV1, DrmTimeline>
+        + Dispatch<WpLinuxDrmSyncobjSurfaceV1, DrmSyncobjSurfaceData>
+        + DrmSyncobjHandler
+        + 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<WpLinuxDrmSyncobjManagerV1>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl<D> Dispatch<WpLinuxDrmSyncobjManagerV1, (), D> for DrmSyncobjState
+where
+    D: Dispatch<WpLinuxDrmSyncobjManagerV1, ()>
+        + Dispatch<WpLinuxDrmSyncobjTimelineV1, DrmTimeline>
+        + Dispatch<WpLinuxDrmSyncobjSurfaceV1, DrmSyncobjSurfaceData>
+        + DrmSyncobjHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &WpLinuxDrmSyncobjManagerV1,
+        request: wp_linux_drm_syncobj_manager_v1::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            wp_linux_drm_syncobj_manager_v1::Request::GetSurface { id, surface } => {
+                let (already_exists, new_surface) = with_states(&surface, |states| {
+                    let new_surface = states
+                        .data_map
+                        .insert_if_missing_threadsafe(|| SyncobjSurfaceMarker(Mutex::new(None)));
+                    (surface_marker(states).0.lock().unwrap().is_some(), new_surface)
+                });
+                if already_exists {
+                    resource.post_error(
+                        wp_linux_drm_syncobj_manager_v1::Error::SurfaceExists,
+                        "the surface already has a syncobj surface object associated",
+                    );
+                    return;
+                }
+
+                // the marker outlives destroyed syncobj surfaces, so the hook is only added once per surface
+                if new_surface {
+                    compositor::add_pre_commit_hook(&surface, commit_hook);
+                }
+
+                let syncobj_surface = data_init.init(
+                    id,
+                    DrmSyncobjSurfaceData {
+                        surface: surface.clone(),
+                    },
+                );
+                with_states(&surface, |states| {
+                    *surface_marker(states).0.lock().unwrap() = Some(syncobj_surface);
+                });
+            }
+            wp_linux_drm_syncobj_manager_v1::Request::ImportTimeline { id, fd } => {
+                match DrmTimeline::new(&state.drm_syncobj_state().import_device, fd.as_fd()) {
+                    Ok(timeline) => {
+                        data_init.init(id, timeline);
+                    }
+                    Err(err) => {
+                        resource.post_error(
+                            wp_linux_drm_syncobj_manager_v1::Error::InvalidTimeline,
+                            format!(
+                                "failed to import syncobj timeline (fd {}): {}",
+                                fd.as_raw_fd(),
+                                err
+                            ),
+                        );
+                    }
+                }
+            }
+            wp_linux_drm_syncobj_manager_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<WpLinuxDrmSyncobjTimelineV1, DrmTimeline, D> for DrmSyncobjState
+where
+    D: Dispatch<WpLinuxDrmSyncobjTimelineV1, DrmTimeline>,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _resource: &WpLinuxDrmSyncobjTimelineV1,
+        request: wp_linux_drm_syncobj_timeline_v1::Request,
+        _data: &DrmTimeline,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            // points already set keep a reference to the timeline
+            wp_linux_drm_syncobj_timeline_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<WpLinuxDrmSyncobjSurfaceV1, DrmSyncobjSurfaceData, D> for DrmSyncobjState
+where
+    D: Dispatch<WpLinuxDrmSyncobjSurfaceV1, DrmSyncobjSurfaceData>,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        resource: &WpLinuxDrmSyncobjSurfaceV1,
+        request: wp_linux_drm_syncobj_surface_v1::Request,
+        data: &DrmSyncobjSurfaceData,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        let surface = &data.surface;
+        match request {
+            wp_linux_drm_syncobj_surface_v1::Request::Destroy => {
+                if surface.alive() {
+                    with_states(surface, |states| {
+                        surface_marker(states).0.lock().unwrap().take();
+                        // points set since the last commit are discarded
+                        *states.cached_state.pending::<DrmSyncobjCachedState>() = Default::default();
+                    });
+                }
+            }
+            wp_linux_drm_syncobj_surface_v1::Request::SetAcquirePoint {
+                timeline,
+                point_hi,
+                point_lo,
+            } => {
+                if !surface.alive() {
+                    resource.post_error(
+                        wp_linux_drm_syncobj_surface_v1::Error::NoSurface,
+                        "the wl_surface was destroyed",
+                    );
+                    return;
+                }
+                let timeline = match timeline.data::<DrmTimeline>() {
+                    Some(timeline) => timeline.clone(),
+                    // the timeline failed to import, the client was already killed
+                    None => return,
+                };
+                let point = DrmSyncPoint {
+                    timeline,
+                    point: ((point_hi as u64) << 32) + (point_lo as u64),
+                };
+                with_states(surface, |states| {
+                    states
+                        .cached_state
+                        .pending::<DrmSyncobjCachedState>()
+                        .acquire_point = Some(point);
+                });
+            }
+            wp_linux_drm_syncobj_surface_v1::Request::SetReleasePoint {
+                timeline,
+                point_hi,
+                point_lo,
+            } => {
+                if !surface.alive() {
+                    resource.post_error(
+                        wp_linux_drm_syncobj_surface_v1::Error::NoSurface,
+                        "the wl_surface was destroyed",
+                    );
+                    return;
+                }
+                let timeline = match timeline.data::<DrmTimeline>() {
+                    Some(timeline) => timeline.clone(),
+                    // the timeline failed to import, the client was already killed
+                    None => return,
+                };
+                let point = DrmSyncPoint {
+                    timeline,
+                    point: ((point_hi as u64) << 32) + (point_lo as u64),
+                };
+                with_states(surface, |states| {
+                    states
+                        .cached_state
+                        .pending::<DrmSyncobjCachedState>()
+                        .release_point = Some(point);
+                });
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Macro to delegate implementation of the linux-drm-syncobj protocol
+#[macro_export]
+macro_rules! delegate_drm_syncobj {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::wayland::drm_syncobj::protocol::wp_linux_drm_syncobj_manager_v1::WpLinuxDrmSyncobjManagerV1: ()
+        ] => $crate::wayland::drm_syncobj::DrmSyncobjState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::wayland::drm_syncobj::protocol::wp_linux_drm_syncobj_manager_v1::WpLinuxDrmSyncobjManagerV1: ()
+        ] => $crate::wayland::drm_syncobj::DrmSyncobjState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::wayland::drm_syncobj::protocol::wp_linux_drm_syncobj_timeline_v1::WpLinuxDrmSyncobjTimelineV1: $crate::wayland::drm_syncobj::DrmTimeline
+        ] => $crate::wayland::drm_syncobj::DrmSyncobjState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::wayland::drm_syncobj::protocol::wp_linux_drm_syncobj_surface_v1::WpLinuxDrmSyncobjSurfaceV1: $crate::wayland::drm_syncobj::DrmSyncobjSurfaceData
+        ] => $crate::wayland::drm_syncobj::DrmSyncobjState);
+    };
+}
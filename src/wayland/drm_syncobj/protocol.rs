@@ -0,0 +1,23 @@
+//! Server-side bindings of the `linux-drm-syncobj-v1` staging protocol
+//!
+//! The protocol is not yet part of the `wayland-protocols` version used by smithay,
+//! so the bindings are generated from the protocol definition shipped alongside this module.
+
+#![allow(
+    non_upper_case_globals,
+    non_camel_case_types,
+    unused_imports,
+    missing_docs,
+    clippy::all
+)]
+
+use wayland_server;
+use wayland_server::protocol::*;
+
+pub mod __interfaces {
+    use wayland_server::protocol::__interfaces::*;
+    wayland_scanner::generate_interfaces!("src/wayland/drm_syncobj/linux-drm-syncobj-v1.xml");
+}
+use self::__interfaces::*;
+
+wayland_scanner::generate_server_code!("src/wayland/drm_syncobj/linux-drm-syncobj-v1.xml");
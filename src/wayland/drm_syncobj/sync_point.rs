@@ -0,0 +1,356 @@
+use std::{
+    io,
+    os::unix::io::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use calloop::{
+    generic::Generic, EventSource, Interest, Mode, Poll, PostAction, Readiness, Token, TokenFactory,
+};
+use nix::sys::eventfd::{eventfd, EfdFlags};
+
+use crate::{
+    backend::drm::DrmDeviceFd,
+    wayland::compositor::{Blocker, BlockerState},
+};
+
+/// Minimal definitions of the drm syncobj uapi
+mod ioctl {
+    #![allow(non_camel_case_types)]
+
+    pub const DRM_SYNCOBJ_FD_TO_HANDLE_FLAGS_IMPORT_SYNC_FILE: u32 = 1;
+    pub const DRM_SYNCOBJ_HANDLE_TO_FD_FLAGS_EXPORT_SYNC_FILE: u32 = 1;
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct drm_syncobj_create {
+        pub handle: u32,
+        pub flags: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct drm_syncobj_destroy {
+        pub handle: u32,
+        pub pad: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct drm_syncobj_handle {
+        pub handle: u32,
+        pub flags: u32,
+        pub fd: i32,
+        pub pad: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct drm_syncobj_transfer {
+        pub src_handle: u32,
+        pub dst_handle: u32,
+        pub src_point: u64,
+        pub dst_point: u64,
+        pub flags: u32,
+        pub pad: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct drm_syncobj_timeline_array {
+        pub handles: u64,
+        pub points: u64,
+        pub count_handles: u32,
+        pub flags: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct drm_syncobj_eventfd {
+        pub handle: u32,
+        pub flags: u32,
+        pub point: u64,
+        pub fd: i32,
+        pub pad: u32,
+    }
+
+    nix::ioctl_readwrite!(syncobj_create, b'd', 0xBF, drm_syncobj_create);
+    nix::ioctl_readwrite!(syncobj_destroy, b'd', 0xC0, drm_syncobj_destroy);
+    nix::ioctl_readwrite!(syncobj_handle_to_fd, b'd', 0xC1, drm_syncobj_handle);
+    nix::ioctl_readwrite!(syncobj_fd_to_handle, b'd', 0xC2, drm_syncobj_handle);
+    nix::ioctl_readwrite!(syncobj_transfer, b'd', 0xCC, drm_syncobj_transfer);
+    nix::ioctl_readwrite!(syncobj_timeline_signal, b'd', 0xCD, drm_syncobj_timeline_array);
+    nix::ioctl_readwrite!(syncobj_eventfd, b'd', 0xCF, drm_syncobj_eventfd);
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct sync_merge_data {
+        pub name: [u8; 32],
+        pub fd2: i32,
+        pub fence: i32,
+        pub flags: u32,
+        pub pad: u32,
+    }
+
+    nix::ioctl_readwrite!(sync_merge, b'>', 3, sync_merge_data);
+}
+
+#[derive(Debug)]
+struct Syncobj {
+    device: DrmDeviceFd,
+    handle: u32,
+}
+
+impl Syncobj {
+    fn create(device: &DrmDeviceFd) -> io::Result<Syncobj> {
+        let mut args = ioctl::drm_syncobj_create::default();
+        unsafe { ioctl::syncobj_create(device.as_raw_fd(), &mut args) }?;
+        Ok(Syncobj {
+            device: device.clone(),
+            handle: args.handle,
+        })
+    }
+
+    fn import(device: &DrmDeviceFd, fd: BorrowedFd<'_>) -> io::Result<Syncobj> {
+        let mut args = ioctl::drm_syncobj_handle {
+            fd: fd.as_raw_fd(),
+            ..Default::default()
+        };
+        unsafe { ioctl::syncobj_fd_to_handle(device.as_raw_fd(), &mut args) }?;
+        Ok(Syncobj {
+            device: device.clone(),
+            handle: args.handle,
+        })
+    }
+
+    fn transfer(&self, src_point: u64, dst: &Syncobj, dst_point: u64) -> io::Result<()> {
+        let mut args = ioctl::drm_syncobj_transfer {
+            src_handle: self.handle,
+            dst_handle: dst.handle,
+            src_point,
+            dst_point,
+            ..Default::default()
+        };
+        unsafe { ioctl::syncobj_transfer(self.device.as_raw_fd(), &mut args) }?;
+        Ok(())
+    }
+}
+
+impl Drop for Syncobj {
+    fn drop(&mut self) {
+        let mut args = ioctl::drm_syncobj_destroy {
+            handle: self.handle,
+            pad: 0,
+        };
+        let _ = unsafe { ioctl::syncobj_destroy(self.device.as_raw_fd(), &mut args) };
+    }
+}
+
+pub(super) fn supports_syncobj_eventfd(device: &DrmDeviceFd) -> bool {
+    // the ioctl fails with ENOENT for the invalid handle, if it is supported at all
+    let mut args = ioctl::drm_syncobj_eventfd {
+        handle: 0,
+        flags: 0,
+        point: 0,
+        fd: -1,
+        pad: 0,
+    };
+    let res = unsafe { ioctl::syncobj_eventfd(device.as_raw_fd(), &mut args) };
+    matches!(res, Err(nix::errno::Errno::ENOENT))
+}
+
+/// Merge two sync_files into a new one, that is signalled once both fences are
+pub(crate) fn merge_sync_files(first: BorrowedFd<'_>, second: BorrowedFd<'_>) -> io::Result<OwnedFd> {
+    let mut args = ioctl::sync_merge_data {
+        fd2: second.as_raw_fd(),
+        fence: -1,
+        ..Default::default()
+    };
+    unsafe { ioctl::sync_merge(first.as_raw_fd(), &mut args) }?;
+    // SAFETY: the kernel returned a new file descriptor, we are its only owner
+    Ok(unsafe { OwnedFd::from_raw_fd(args.fence) })
+}
+
+/// A DRM syncobj timeline imported from a client
+#[derive(Debug, Clone)]
+pub struct DrmTimeline(Arc<Syncobj>);
+
+impl PartialEq for DrmTimeline {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl DrmTimeline {
+    /// Import a timeline from a syncobj file descriptor into the given device
+    pub fn new(device: &DrmDeviceFd, fd: BorrowedFd<'_>) -> io::Result<Self> {
+        Syncobj::import(device, fd).map(|syncobj| DrmTimeline(Arc::new(syncobj)))
+    }
+}
+
+/// A point on a [`DrmTimeline`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrmSyncPoint {
+    pub(super) timeline: DrmTimeline,
+    pub(super) point: u64,
+}
+
+impl DrmSyncPoint {
+    /// The timeline this point belongs to
+    pub fn timeline(&self) -> &DrmTimeline {
+        &self.timeline
+    }
+
+    /// The value of this point on its timeline
+    pub fn point(&self) -> u64 {
+        self.point
+    }
+
+    /// Signal the point from the cpu
+    pub fn signal(&self) -> io::Result<()> {
+        let syncobj = &*self.timeline.0;
+        let mut args = ioctl::drm_syncobj_timeline_array {
+            handles: &syncobj.handle as *const u32 as u64,
+            points: &self.point as *const u64 as u64,
+            count_handles: 1,
+            flags: 0,
+        };
+        unsafe { ioctl::syncobj_timeline_signal(syncobj.device.as_raw_fd(), &mut args) }?;
+        Ok(())
+    }
+
+    /// Create an eventfd, that becomes readable once the point is signalled
+    pub fn eventfd(&self) -> io::Result<OwnedFd> {
+        let syncobj = &*self.timeline.0;
+        let fd = eventfd(0, EfdFlags::EFD_CLOEXEC | EfdFlags::EFD_NONBLOCK)?;
+        // SAFETY: eventfd returned a new file descriptor, we are its only owner
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        let mut args = ioctl::drm_syncobj_eventfd {
+            handle: syncobj.handle,
+            flags: 0,
+            point: self.point,
+            fd: fd.as_raw_fd(),
+            pad: 0,
+        };
+        unsafe { ioctl::syncobj_eventfd(syncobj.device.as_raw_fd(), &mut args) }?;
+        Ok(fd)
+    }
+
+    /// Export the fence of this point as a sync_file
+    ///
+    /// This fails, if no fence was submitted for the point yet.
+    pub fn export_sync_file(&self) -> io::Result<OwnedFd> {
+        let syncobj = &*self.timeline.0;
+        // sync_files can only be exported from binary syncobjs,
+        // so the point is transferred to a temporary one first.
+        let tmp = Syncobj::create(&syncobj.device)?;
+        syncobj.transfer(self.point, &tmp, 0)?;
+        let mut args = ioctl::drm_syncobj_handle {
+            handle: tmp.handle,
+            flags: ioctl::DRM_SYNCOBJ_HANDLE_TO_FD_FLAGS_EXPORT_SYNC_FILE,
+            fd: -1,
+            pad: 0,
+        };
+        unsafe { ioctl::syncobj_handle_to_fd(syncobj.device.as_raw_fd(), &mut args) }?;
+        // SAFETY: the kernel returned a new file descriptor, we are its only owner
+        Ok(unsafe { OwnedFd::from_raw_fd(args.fd) })
+    }
+
+    /// Set the fence of this point from a sync_file
+    ///
+    /// The point will be signalled once the fence is, which is e.g. useful to signal
+    /// a release point using an out-fence of the rendering or scanout of a buffer.
+    pub fn import_sync_file(&self, sync_file: BorrowedFd<'_>) -> io::Result<()> {
+        let syncobj = &*self.timeline.0;
+        let tmp = Syncobj::create(&syncobj.device)?;
+        let mut args = ioctl::drm_syncobj_handle {
+            handle: tmp.handle,
+            flags: ioctl::DRM_SYNCOBJ_FD_TO_HANDLE_FLAGS_IMPORT_SYNC_FILE,
+            fd: sync_file.as_raw_fd(),
+            pad: 0,
+        };
+        unsafe { ioctl::syncobj_fd_to_handle(syncobj.device.as_raw_fd(), &mut args) }?;
+        tmp.transfer(0, syncobj, self.point)
+    }
+
+    /// Create a [`Blocker`] for this point, alongside an event source releasing it
+    ///
+    /// The blocker is released once the point is signalled. Insert the source into your
+    /// event loop and call [`CompositorState::blocker_cleared`](crate::wayland::compositor::CompositorState::blocker_cleared)
+    /// from its callback.
+    pub fn generate_blocker(&self) -> io::Result<(DrmSyncPointBlocker, DrmSyncPointSource)> {
+        let signaled = Arc::new(AtomicBool::new(false));
+        let blocker = DrmSyncPointBlocker {
+            signaled: signaled.clone(),
+        };
+        let source = DrmSyncPointSource {
+            source: Generic::new(self.eventfd()?, Interest::READ, Mode::Level),
+            signaled,
+        };
+        Ok((blocker, source))
+    }
+}
+
+/// [`Blocker`] waiting for a [`DrmSyncPoint`] to be signalled
+#[derive(Debug)]
+pub struct DrmSyncPointBlocker {
+    signaled: Arc<AtomicBool>,
+}
+
+impl Blocker for DrmSyncPointBlocker {
+    fn state(&self) -> BlockerState {
+        if self.signaled.load(Ordering::Acquire) {
+            BlockerState::Released
+        } else {
+            BlockerState::Pending
+        }
+    }
+}
+
+/// Event source releasing a [`DrmSyncPointBlocker`]
+///
+/// Generates a single event once the point is signalled and removes itself afterwards.
+#[derive(Debug)]
+pub struct DrmSyncPointSource {
+    source: Generic<OwnedFd>,
+    signaled: Arc<AtomicBool>,
+}
+
+impl EventSource for DrmSyncPointSource {
+    type Event = ();
+    type Metadata = ();
+    type Ret = ();
+    type Error = io::Error;
+
+    fn process_events<F>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        mut callback: F,
+    ) -> io::Result<PostAction>
+    where
+        F: FnMut(Self::Event, &mut Self::Metadata) -> Self::Ret,
+    {
+        let signaled = &self.signaled;
+        self.source.process_events(readiness, token, |_, _| {
+            signaled.store(true, Ordering::Release);
+            callback((), &mut ());
+            Ok(PostAction::Remove)
+        })
+    }
+
+    fn register(&mut self, poll: &mut Poll, token_factory: &mut TokenFactory) -> calloop::Result<()> {
+        self.source.register(poll, token_factory)
+    }
+
+    fn reregister(&mut self, poll: &mut Poll, token_factory: &mut TokenFactory) -> calloop::Result<()> {
+        self.source.reregister(poll, token_factory)
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> calloop::Result<()> {
+        self.source.unregister(poll)
+    }
+}
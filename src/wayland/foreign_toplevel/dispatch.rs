@@ -0,0 +1,248 @@
+use wayland_protocols::ext::foreign_toplevel_list::v1::server::{
+    ext_foreign_toplevel_handle_v1::{self, ExtForeignToplevelHandleV1},
+    ext_foreign_toplevel_list_v1::{self, ExtForeignToplevelListV1},
+};
+use wayland_protocols_wlr::foreign_toplevel::v1::server::{
+    zwlr_foreign_toplevel_handle_v1::{self, ZwlrForeignToplevelHandleV1},
+    zwlr_foreign_toplevel_manager_v1::{self, ZwlrForeignToplevelManagerV1},
+};
+use wayland_server::{
+    backend::{ClientId, ObjectId},
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+};
+
+use crate::utils::Rectangle;
+
+use super::{
+    ForeignToplevelGlobalData, ForeignToplevelHandler, ForeignToplevelState, WeakForeignToplevelHandle,
+};
+
+impl<D> GlobalDispatch<ZwlrForeignToplevelManagerV1, ForeignToplevelGlobalData, D> for ForeignToplevelState
+where
+    D: GlobalDispatch<ZwlrForeignToplevelManagerV1, ForeignToplevelGlobalData>
+        + Dispatch<ZwlrForeignToplevelManagerV1, ()>
+        + Dispatch<ZwlrForeignToplevelHandleV1, WeakForeignToplevelHandle>
+        + ForeignToplevelHandler
+        + 'static,
+{
+    fn bind(
+        state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<ZwlrForeignToplevelManagerV1>,
+        _global_data: &ForeignToplevelGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let manager = data_init.init(resource, ());
+
+        let toplevel_state = state.foreign_toplevel_state();
+        for toplevel in &toplevel_state.toplevels {
+            toplevel.init_wlr_instance::<D>(&manager);
+        }
+        toplevel_state.wlr_managers.push(manager);
+    }
+
+    fn can_view(client: Client, global_data: &ForeignToplevelGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> GlobalDispatch<ExtForeignToplevelListV1, ForeignToplevelGlobalData, D> for ForeignToplevelState
+where
+    D: GlobalDispatch<ExtForeignToplevelListV1, ForeignToplevelGlobalData>
+        + Dispatch<ExtForeignToplevelListV1, ()>
+        + Dispatch<ExtForeignToplevelHandleV1, WeakForeignToplevelHandle>
+        + ForeignToplevelHandler
+        + 'static,
+{
+    fn bind(
+        state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<ExtForeignToplevelListV1>,
+        _global_data: &ForeignToplevelGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let list = data_init.init(resource, ());
+
+        let toplevel_state = state.foreign_toplevel_state();
+        for toplevel in &toplevel_state.toplevels {
+            toplevel.init_ext_instance::<D>(&list);
+        }
+        toplevel_state.ext_lists.push(list);
+    }
+
+    fn can_view(client: Client, global_data: &ForeignToplevelGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<ZwlrForeignToplevelManagerV1, (), D> for ForeignToplevelState
+where
+    D: Dispatch<ZwlrForeignToplevelManagerV1, ()> + ForeignToplevelHandler + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        manager: &ZwlrForeignToplevelManagerV1,
+        request: zwlr_foreign_toplevel_manager_v1::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwlr_foreign_toplevel_manager_v1::Request::Stop => {
+                state
+                    .foreign_toplevel_state()
+                    .wlr_managers
+                    .retain(|instance| instance != manager);
+                manager.finished();
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(state: &mut D, _client: ClientId, id: ObjectId, _data: &()) {
+        state
+            .foreign_toplevel_state()
+            .wlr_managers
+            .retain(|manager| manager.id() != id);
+    }
+}
+
+impl<D> Dispatch<ExtForeignToplevelListV1, (), D> for ForeignToplevelState
+where
+    D: Dispatch<ExtForeignToplevelListV1, ()> + ForeignToplevelHandler + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        list: &ExtForeignToplevelListV1,
+        request: ext_foreign_toplevel_list_v1::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_foreign_toplevel_list_v1::Request::Stop => {
+                let toplevel_state = state.foreign_toplevel_state();
+                if toplevel_state.ext_lists.contains(list) {
+                    toplevel_state.ext_lists.retain(|instance| instance != list);
+                    list.finished();
+                }
+            }
+            ext_foreign_toplevel_list_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(state: &mut D, _client: ClientId, id: ObjectId, _data: &()) {
+        state
+            .foreign_toplevel_state()
+            .ext_lists
+            .retain(|list| list.id() != id);
+    }
+}
+
+impl<D> Dispatch<ZwlrForeignToplevelHandleV1, WeakForeignToplevelHandle, D> for ForeignToplevelState
+where
+    D: Dispatch<ZwlrForeignToplevelHandleV1, WeakForeignToplevelHandle> + ForeignToplevelHandler + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        handle: &ZwlrForeignToplevelHandleV1,
+        request: zwlr_foreign_toplevel_handle_v1::Request,
+        data: &WeakForeignToplevelHandle,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        if let zwlr_foreign_toplevel_handle_v1::Request::SetRectangle { width, height, .. } = request {
+            if width < 0 || height < 0 {
+                handle.post_error(
+                    zwlr_foreign_toplevel_handle_v1::Error::InvalidRectangle,
+                    "The rectangle has a negative size",
+                );
+                return;
+            }
+        }
+
+        // requests to closed toplevels are ignored
+        let toplevel = match data.upgrade().filter(|toplevel| !toplevel.is_closed()) {
+            Some(toplevel) => toplevel,
+            None => return,
+        };
+
+        match request {
+            zwlr_foreign_toplevel_handle_v1::Request::SetMaximized => state.set_maximized(toplevel),
+            zwlr_foreign_toplevel_handle_v1::Request::UnsetMaximized => state.unset_maximized(toplevel),
+            zwlr_foreign_toplevel_handle_v1::Request::SetMinimized => state.set_minimized(toplevel),
+            zwlr_foreign_toplevel_handle_v1::Request::UnsetMinimized => state.unset_minimized(toplevel),
+            zwlr_foreign_toplevel_handle_v1::Request::Activate { seat } => state.activate(toplevel, seat),
+            zwlr_foreign_toplevel_handle_v1::Request::Close => state.close(toplevel),
+            zwlr_foreign_toplevel_handle_v1::Request::SetRectangle {
+                surface,
+                x,
+                y,
+                width,
+                height,
+            } => {
+                state.set_rectangle(
+                    toplevel,
+                    surface,
+                    Rectangle::from_loc_and_size((x, y), (width, height)),
+                );
+            }
+            zwlr_foreign_toplevel_handle_v1::Request::SetFullscreen { output } => {
+                state.set_fullscreen(toplevel, output)
+            }
+            zwlr_foreign_toplevel_handle_v1::Request::UnsetFullscreen => state.unset_fullscreen(toplevel),
+            zwlr_foreign_toplevel_handle_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(_state: &mut D, _client: ClientId, id: ObjectId, data: &WeakForeignToplevelHandle) {
+        if let Some(toplevel) = data.upgrade() {
+            toplevel
+                .0
+                .data
+                .lock()
+                .unwrap()
+                .wlr_instances
+                .retain(|instance| instance.id() != id);
+        }
+    }
+}
+
+impl<D> Dispatch<ExtForeignToplevelHandleV1, WeakForeignToplevelHandle, D> for ForeignToplevelState
+where
+    D: Dispatch<ExtForeignToplevelHandleV1, WeakForeignToplevelHandle> + ForeignToplevelHandler + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _handle: &ExtForeignToplevelHandleV1,
+        request: ext_foreign_toplevel_handle_v1::Request,
+        _data: &WeakForeignToplevelHandle,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_foreign_toplevel_handle_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(_state: &mut D, _client: ClientId, id: ObjectId, data: &WeakForeignToplevelHandle) {
+        if let Some(toplevel) = data.upgrade() {
+            toplevel
+                .0
+                .data
+                .lock()
+                .unwrap()
+                .ext_instances
+                .retain(|instance| instance.id() != id);
+        }
+    }
+}
@@ -0,0 +1,643 @@
+//! Utilities for handling the `wlr-foreign-toplevel-management` and `ext-foreign-toplevel-list` protocols
+//!
+//! These protocols allow privileged clients like taskbars, docks or window switchers to
+//! list the toplevel windows of all clients. `wlr-foreign-toplevel-management` additionally lets
+//! those clients request to activate, close, maximize, minimize or fullscreen a toplevel.
+//!
+//! Both protocols are served from the same [`ForeignToplevelHandle`]s. Create a handle for every
+//! mapped window using [`ForeignToplevelState::new_toplevel`], keep its properties up to date and
+//! call [`ForeignToplevelHandle::send_done`] after changing them. Once the window is unmapped, remove
+//! it using [`ForeignToplevelState::remove_toplevel`].
+//!
+//! The properties of [`Window`](crate::desktop::Window)s and
+//! [`X11Surface`](crate::xwayland::X11Surface)s can be copied using
+//! [`ForeignToplevelHandle::set_from_window`] and [`ForeignToplevelHandle::set_from_x11_surface`],
+//! while the outputs of a toplevel need to be tracked by your compositor using
+//! [`ForeignToplevelHandle::output_enter`] and [`ForeignToplevelHandle::output_leave`].
+//!
+//! ```no_run
+//! # extern crate wayland_server;
+//! use smithay::delegate_foreign_toplevel;
+//! use smithay::wayland::foreign_toplevel::{
+//!     ForeignToplevelHandle, ForeignToplevelHandler, ForeignToplevelState,
+//! };
+//!
+//! pub struct State {
+//!     foreign_toplevel_state: ForeignToplevelState,
+//! }
+//!
+//! impl ForeignToplevelHandler for State {
+//!     fn foreign_toplevel_state(&mut self) -> &mut ForeignToplevelState {
+//!         &mut self.foreign_toplevel_state
+//!     }
+//!
+//!     fn close(&mut self, toplevel: ForeignToplevelHandle) {
+//!         // look up the window of the handle, e.g. through `toplevel.user_data()`, and close it
+//!     }
+//! }
+//!
+//! delegate_foreign_toplevel!(State);
+//!
+//! # let mut display = wayland_server::Display::<State>::new().unwrap();
+//! # let display_handle = display.handle();
+//! let mut foreign_toplevel_state = ForeignToplevelState::new::<State>(&display_handle);
+//!
+//! // once a window is mapped
+//! let handle = foreign_toplevel_state.new_toplevel::<State>("Title", "app-id");
+//! handle.set_activated(true);
+//! handle.send_done();
+//! ```
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex, Weak,
+};
+
+use wayland_protocols::ext::foreign_toplevel_list::v1::server::{
+    ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1,
+    ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1,
+};
+use wayland_protocols_wlr::foreign_toplevel::v1::server::{
+    zwlr_foreign_toplevel_handle_v1::{self, ZwlrForeignToplevelHandleV1},
+    zwlr_foreign_toplevel_manager_v1::ZwlrForeignToplevelManagerV1,
+};
+use wayland_server::{
+    backend::GlobalId,
+    protocol::{wl_output::WlOutput, wl_seat::WlSeat, wl_surface::WlSurface},
+    Client, Dispatch, DisplayHandle, GlobalDispatch, Resource,
+};
+
+use crate::{
+    output::Output,
+    utils::{user_data::UserDataMap, Logical, Rectangle},
+};
+
+mod dispatch;
+
+const WLR_MANAGER_VERSION: u32 = 3;
+
+static NEXT_IDENTIFIER: AtomicU64 = AtomicU64::new(0);
+
+/// Data associated with the foreign toplevel globals
+#[allow(missing_debug_implementations)]
+#[derive(Clone)]
+pub struct ForeignToplevelGlobalData {
+    filter: Arc<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+}
+
+/// State of the foreign toplevel globals
+#[derive(Debug)]
+pub struct ForeignToplevelState {
+    wlr_global: GlobalId,
+    ext_global: GlobalId,
+    dh: DisplayHandle,
+    wlr_managers: Vec<ZwlrForeignToplevelManagerV1>,
+    ext_lists: Vec<ExtForeignToplevelListV1>,
+    toplevels: Vec<ForeignToplevelHandle>,
+}
+
+impl ForeignToplevelState {
+    /// Create new `zwlr_foreign_toplevel_manager_v1` and `ext_foreign_toplevel_list_v1` globals
+    pub fn new<D>(display: &DisplayHandle) -> ForeignToplevelState
+    where
+        D: GlobalDispatch<ZwlrForeignToplevelManagerV1, ForeignToplevelGlobalData>
+            + GlobalDispatch<ExtForeignToplevelListV1, ForeignToplevelGlobalData>
+            + Dispatch<ZwlrForeignToplevelManagerV1, ()>
+            + Dispatch<ZwlrForeignToplevelHandleV1, WeakForeignToplevelHandle>
+            + Dispatch<ExtForeignToplevelListV1, ()>
+            + Dispatch<ExtForeignToplevelHandleV1, WeakForeignToplevelHandle>
+            + ForeignToplevelHandler
+            + 'static,
+    {
+        Self::new_with_filter::<D, _>(display, |_| true)
+    }
+
+    /// Create new foreign toplevel globals, only visible to [privileged clients]
+    ///
+    /// [privileged clients]: crate::wayland::security_context#privileged-globals
+    pub fn new_with_filter<D, F>(display: &DisplayHandle, filter: F) -> ForeignToplevelState
+    where
+        D: GlobalDispatch<ZwlrForeignToplevelManagerV1, ForeignToplevelGlobalData>
+            + GlobalDispatch<ExtForeignToplevelListV1, ForeignToplevelGlobalData>
+            + Dispatch<ZwlrForeignToplevelManagerV1, ()>
+            + Dispatch<ZwlrForeignToplevelHandleV1, WeakForeignToplevelHandle>
+            + Dispatch<ExtForeignToplevelListV1, ()>
+            + Dispatch<ExtForeignToplevelHandleV1, WeakForeignToplevelHandle>
+            + ForeignToplevelHandler
+            + 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+    {
+        let data = ForeignToplevelGlobalData {
+            filter: Arc::new(filter),
+        };
+        let wlr_global =
+            display.create_global::<D, ZwlrForeignToplevelManagerV1, _>(WLR_MANAGER_VERSION, data.clone());
+        let ext_global = display.create_global::<D, ExtForeignToplevelListV1, _>(1, data);
+
+        ForeignToplevelState {
+            wlr_global,
+            ext_global,
+            dh: display.clone(),
+            wlr_managers: Vec::new(),
+            ext_lists: Vec::new(),
+            toplevels: Vec::new(),
+        }
+    }
+
+    /// Returns the id of the `zwlr_foreign_toplevel_manager_v1` global
+    pub fn wlr_global(&self) -> GlobalId {
+        self.wlr_global.clone()
+    }
+
+    /// Returns the id of the `ext_foreign_toplevel_list_v1` global
+    pub fn ext_global(&self) -> GlobalId {
+        self.ext_global.clone()
+    }
+
+    /// Announce a new toplevel to clients
+    ///
+    /// Further properties of the toplevel are sent on the next call to
+    /// [`ForeignToplevelHandle::send_done`].
+    pub fn new_toplevel<D>(
+        &mut self,
+        title: impl Into<String>,
+        app_id: impl Into<String>,
+    ) -> ForeignToplevelHandle
+    where
+        D: Dispatch<ZwlrForeignToplevelHandleV1, WeakForeignToplevelHandle>
+            + Dispatch<ExtForeignToplevelHandleV1, WeakForeignToplevelHandle>
+            + 'static,
+    {
+        let properties = ToplevelProperties {
+            title: title.into(),
+            app_id: app_id.into(),
+            ..Default::default()
+        };
+        let handle = ForeignToplevelHandle(Arc::new(ForeignToplevelInner {
+            identifier: format!("{:016x}", NEXT_IDENTIFIER.fetch_add(1, Ordering::SeqCst)),
+            dh: self.dh.clone(),
+            data: Mutex::new(ToplevelData {
+                current: properties.clone(),
+                pending: properties,
+                closed: false,
+                wlr_instances: Vec::new(),
+                ext_instances: Vec::new(),
+            }),
+            user_data: UserDataMap::new(),
+        }));
+
+        for manager in &self.wlr_managers {
+            handle.init_wlr_instance::<D>(manager);
+        }
+        for list in &self.ext_lists {
+            handle.init_ext_instance::<D>(list);
+        }
+
+        self.toplevels.push(handle.clone());
+        handle
+    }
+
+    /// Remove a toplevel, e.g. because its window was unmapped
+    ///
+    /// This notifies clients, that the toplevel was closed.
+    pub fn remove_toplevel(&mut self, toplevel: &ForeignToplevelHandle) {
+        self.toplevels.retain(|handle| handle != toplevel);
+        toplevel.close();
+    }
+
+    /// Returns all currently announced toplevels
+    pub fn toplevels(&self) -> impl Iterator<Item = &ForeignToplevelHandle> {
+        self.toplevels.iter()
+    }
+}
+
+/// Handler trait for the foreign toplevel protocols
+///
+/// All requests are optional and ignored by default.
+#[allow(unused_variables)]
+pub trait ForeignToplevelHandler {
+    /// [`ForeignToplevelState`] getter
+    fn foreign_toplevel_state(&mut self) -> &mut ForeignToplevelState;
+
+    /// A client requested to activate the toplevel on the given seat
+    fn activate(&mut self, toplevel: ForeignToplevelHandle, seat: WlSeat) {}
+
+    /// A client requested to close the toplevel
+    fn close(&mut self, toplevel: ForeignToplevelHandle) {}
+
+    /// A client requested to maximize the toplevel
+    fn set_maximized(&mut self, toplevel: ForeignToplevelHandle) {}
+
+    /// A client requested to unmaximize the toplevel
+    fn unset_maximized(&mut self, toplevel: ForeignToplevelHandle) {}
+
+    /// A client requested to minimize the toplevel
+    fn set_minimized(&mut self, toplevel: ForeignToplevelHandle) {}
+
+    /// A client requested to unminimize the toplevel
+    fn unset_minimized(&mut self, toplevel: ForeignToplevelHandle) {}
+
+    /// A client requested to make the toplevel fullscreen, optionally on a specific output
+    fn set_fullscreen(&mut self, toplevel: ForeignToplevelHandle, output: Option<WlOutput>) {}
+
+    /// A client requested the toplevel to leave fullscreen
+    fn unset_fullscreen(&mut self, toplevel: ForeignToplevelHandle) {}
+
+    /// A client hinted where it represents the toplevel relative to one of its surfaces
+    ///
+    /// This can be used e.g. as the target of a minimize animation.
+    fn set_rectangle(
+        &mut self,
+        toplevel: ForeignToplevelHandle,
+        surface: WlSurface,
+        rectangle: Rectangle<i32, Logical>,
+    ) {
+    }
+}
+
+#[derive(Debug)]
+struct ForeignToplevelInner {
+    identifier: String,
+    dh: DisplayHandle,
+    data: Mutex<ToplevelData>,
+    user_data: UserDataMap,
+}
+
+#[derive(Debug)]
+struct ToplevelData {
+    /// Properties last sent to clients
+    current: ToplevelProperties,
+    pending: ToplevelProperties,
+    closed: bool,
+    wlr_instances: Vec<ZwlrForeignToplevelHandleV1>,
+    ext_instances: Vec<ExtForeignToplevelHandleV1>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct ToplevelProperties {
+    title: String,
+    app_id: String,
+    maximized: bool,
+    minimized: bool,
+    activated: bool,
+    fullscreen: bool,
+    outputs: Vec<Output>,
+    parent: Option<WeakForeignToplevelHandle>,
+}
+
+impl ToplevelProperties {
+    fn wlr_states(&self) -> Vec<u8> {
+        [
+            (self.maximized, zwlr_foreign_toplevel_handle_v1::State::Maximized),
+            (self.minimized, zwlr_foreign_toplevel_handle_v1::State::Minimized),
+            (self.activated, zwlr_foreign_toplevel_handle_v1::State::Activated),
+            (
+                self.fullscreen,
+                zwlr_foreign_toplevel_handle_v1::State::Fullscreen,
+            ),
+        ]
+        .into_iter()
+        .filter(|(set, _)| *set)
+        .flat_map(|(_, state)| (state as u32).to_ne_bytes())
+        .collect()
+    }
+}
+
+/// A toplevel window advertised through the foreign toplevel protocols
+#[derive(Debug, Clone)]
+pub struct ForeignToplevelHandle(Arc<ForeignToplevelInner>);
+
+impl PartialEq for ForeignToplevelHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl ForeignToplevelHandle {
+    /// Stable identifier of this toplevel, as sent to `ext_foreign_toplevel_list_v1` clients
+    pub fn identifier(&self) -> &str {
+        &self.0.identifier
+    }
+
+    /// Returns the title of this toplevel
+    pub fn title(&self) -> String {
+        self.0.data.lock().unwrap().pending.title.clone()
+    }
+
+    /// Returns the app id of this toplevel
+    pub fn app_id(&self) -> String {
+        self.0.data.lock().unwrap().pending.app_id.clone()
+    }
+
+    /// Set the title of this toplevel
+    pub fn set_title(&self, title: impl Into<String>) {
+        self.0.data.lock().unwrap().pending.title = title.into();
+    }
+
+    /// Set the app id of this toplevel
+    pub fn set_app_id(&self, app_id: impl Into<String>) {
+        self.0.data.lock().unwrap().pending.app_id = app_id.into();
+    }
+
+    /// Set whether this toplevel is maximized
+    pub fn set_maximized(&self, maximized: bool) {
+        self.0.data.lock().unwrap().pending.maximized = maximized;
+    }
+
+    /// Set whether this toplevel is minimized
+    pub fn set_minimized(&self, minimized: bool) {
+        self.0.data.lock().unwrap().pending.minimized = minimized;
+    }
+
+    /// Set whether this toplevel is activated
+    pub fn set_activated(&self, activated: bool) {
+        self.0.data.lock().unwrap().pending.activated = activated;
+    }
+
+    /// Set whether this toplevel is fullscreen
+    pub fn set_fullscreen(&self, fullscreen: bool) {
+        self.0.data.lock().unwrap().pending.fullscreen = fullscreen;
+    }
+
+    /// Notify clients, that this toplevel became visible on an output
+    pub fn output_enter(&self, output: &Output) {
+        let mut data = self.0.data.lock().unwrap();
+        if !data.pending.outputs.contains(output) {
+            data.pending.outputs.push(output.clone());
+        }
+    }
+
+    /// Notify clients, that this toplevel is no longer visible on an output
+    pub fn output_leave(&self, output: &Output) {
+        self.0
+            .data
+            .lock()
+            .unwrap()
+            .pending
+            .outputs
+            .retain(|o| o != output);
+    }
+
+    /// Set the parent of this toplevel, e.g. for dialogs
+    pub fn set_parent(&self, parent: Option<&ForeignToplevelHandle>) {
+        let parent = parent
+            .filter(|parent| *parent != self)
+            .map(|parent| parent.downgrade());
+        self.0.data.lock().unwrap().pending.parent = parent;
+    }
+
+    /// Copy the title, app id and states of a [`Window`](crate::desktop::Window)
+    ///
+    /// The minimized state is not part of the xdg-shell protocol and needs to be
+    /// set using [`ForeignToplevelHandle::set_minimized`].
+    #[cfg(feature = "desktop")]
+    pub fn set_from_window(&self, window: &crate::desktop::Window) {
+        use crate::wayland::{compositor::with_states, shell::xdg::XdgToplevelSurfaceData};
+        use wayland_protocols::xdg::shell::server::xdg_toplevel;
+
+        let toplevel = window.toplevel();
+        let (title, app_id) = with_states(toplevel.wl_surface(), |states| {
+            states
+                .data_map
+                .get::<XdgToplevelSurfaceData>()
+                .map(|data| {
+                    let data = data.lock().unwrap();
+                    (data.title.clone(), data.app_id.clone())
+                })
+                .unwrap_or_default()
+        });
+        let states = toplevel.current_state().states;
+
+        let mut data = self.0.data.lock().unwrap();
+        data.pending.title = title.unwrap_or_default();
+        data.pending.app_id = app_id.unwrap_or_default();
+        data.pending.maximized = states.contains(xdg_toplevel::State::Maximized);
+        data.pending.activated = states.contains(xdg_toplevel::State::Activated);
+        data.pending.fullscreen = states.contains(xdg_toplevel::State::Fullscreen);
+    }
+
+    /// Copy the title, class and states of an [`X11Surface`](crate::xwayland::X11Surface)
+    #[cfg(feature = "xwayland")]
+    pub fn set_from_x11_surface(&self, surface: &crate::xwayland::X11Surface) {
+        let mut data = self.0.data.lock().unwrap();
+        data.pending.title = surface.title();
+        data.pending.app_id = surface.class();
+        data.pending.maximized = surface.is_maximized();
+        data.pending.minimized = surface.is_minimized();
+        data.pending.activated = surface.is_activated();
+        data.pending.fullscreen = surface.is_fullscreen();
+    }
+
+    /// Send all changed properties to clients
+    pub fn send_done(&self) {
+        let mut data = self.0.data.lock().unwrap();
+        if data.closed || data.pending == data.current {
+            return;
+        }
+        let current = &data.current;
+        let pending = &data.pending;
+
+        for instance in &data.wlr_instances {
+            let client = match self.0.dh.get_client(instance.id()) {
+                Ok(client) => client,
+                Err(_) => continue,
+            };
+
+            if pending.title != current.title {
+                instance.title(pending.title.clone());
+            }
+            if pending.app_id != current.app_id {
+                instance.app_id(pending.app_id.clone());
+            }
+            for output in pending.outputs.iter().filter(|o| !current.outputs.contains(o)) {
+                for wl_output in output.client_outputs(&client) {
+                    instance.output_enter(&wl_output);
+                }
+            }
+            for output in current.outputs.iter().filter(|o| !pending.outputs.contains(o)) {
+                for wl_output in output.client_outputs(&client) {
+                    instance.output_leave(&wl_output);
+                }
+            }
+            if pending.wlr_states() != current.wlr_states() {
+                instance.state(pending.wlr_states());
+            }
+            if instance.version() >= 3 && pending.parent != current.parent {
+                instance.parent(wlr_parent_instance(pending, instance).as_ref());
+            }
+            instance.done();
+        }
+
+        if pending.title != current.title || pending.app_id != current.app_id {
+            for instance in &data.ext_instances {
+                if pending.title != current.title {
+                    instance.title(pending.title.clone());
+                }
+                if pending.app_id != current.app_id {
+                    instance.app_id(pending.app_id.clone());
+                }
+                instance.done();
+            }
+        }
+
+        data.current = data.pending.clone();
+    }
+
+    /// Returns whether this toplevel was closed
+    pub fn is_closed(&self) -> bool {
+        self.0.data.lock().unwrap().closed
+    }
+
+    /// Returns the [`UserDataMap`] of this toplevel
+    ///
+    /// This can e.g. be used to store the window this toplevel represents.
+    pub fn user_data(&self) -> &UserDataMap {
+        &self.0.user_data
+    }
+
+    /// Create a weak reference to this toplevel
+    pub fn downgrade(&self) -> WeakForeignToplevelHandle {
+        WeakForeignToplevelHandle(Arc::downgrade(&self.0))
+    }
+
+    fn close(&self) {
+        let mut data = self.0.data.lock().unwrap();
+        if data.closed {
+            return;
+        }
+        data.closed = true;
+
+        for instance in data.wlr_instances.drain(..) {
+            instance.closed();
+        }
+        for instance in data.ext_instances.drain(..) {
+            instance.closed();
+        }
+    }
+
+    fn init_wlr_instance<D>(&self, manager: &ZwlrForeignToplevelManagerV1)
+    where
+        D: Dispatch<ZwlrForeignToplevelHandleV1, WeakForeignToplevelHandle> + 'static,
+    {
+        let dh = &self.0.dh;
+        let client = match dh.get_client(manager.id()) {
+            Ok(client) => client,
+            Err(_) => return,
+        };
+        let instance = match client.create_resource::<ZwlrForeignToplevelHandleV1, _, D>(
+            dh,
+            manager.version(),
+            self.downgrade(),
+        ) {
+            Ok(instance) => instance,
+            Err(_) => return,
+        };
+        manager.toplevel(&instance);
+
+        let mut data = self.0.data.lock().unwrap();
+        let current = &data.current;
+        instance.title(current.title.clone());
+        instance.app_id(current.app_id.clone());
+        for output in &current.outputs {
+            for wl_output in output.client_outputs(&client) {
+                instance.output_enter(&wl_output);
+            }
+        }
+        instance.state(current.wlr_states());
+        if instance.version() >= 3 {
+            if let Some(parent) = wlr_parent_instance(current, &instance) {
+                instance.parent(Some(&parent));
+            }
+        }
+        instance.done();
+
+        data.wlr_instances.push(instance);
+    }
+
+    fn init_ext_instance<D>(&self, list: &ExtForeignToplevelListV1)
+    where
+        D: Dispatch<ExtForeignToplevelHandleV1, WeakForeignToplevelHandle> + 'static,
+    {
+        let dh = &self.0.dh;
+        let client = match dh.get_client(list.id()) {
+            Ok(client) => client,
+            Err(_) => return,
+        };
+        let instance = match client.create_resource::<ExtForeignToplevelHandleV1, _, D>(
+            dh,
+            list.version(),
+            self.downgrade(),
+        ) {
+            Ok(instance) => instance,
+            Err(_) => return,
+        };
+        list.toplevel(&instance);
+
+        let mut data = self.0.data.lock().unwrap();
+        instance.identifier(self.0.identifier.clone());
+        instance.title(data.current.title.clone());
+        instance.app_id(data.current.app_id.clone());
+        instance.done();
+
+        data.ext_instances.push(instance);
+    }
+}
+
+/// Find the handle of the parent of a toplevel, that belongs to the same client as `instance`
+fn wlr_parent_instance(
+    properties: &ToplevelProperties,
+    instance: &ZwlrForeignToplevelHandleV1,
+) -> Option<ZwlrForeignToplevelHandleV1> {
+    let parent = properties.parent.as_ref()?.upgrade()?;
+    let parent_data = parent.0.data.lock().unwrap();
+    parent_data
+        .wlr_instances
+        .iter()
+        .find(|parent_instance| parent_instance.id().same_client_as(&instance.id()))
+        .cloned()
+}
+
+/// Weak reference to a [`ForeignToplevelHandle`]
+#[derive(Debug, Clone)]
+pub struct WeakForeignToplevelHandle(Weak<ForeignToplevelInner>);
+
+impl PartialEq for WeakForeignToplevelHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Weak::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl WeakForeignToplevelHandle {
+    /// Try to upgrade to a strong reference, returns `None` if the toplevel was dropped
+    pub fn upgrade(&self) -> Option<ForeignToplevelHandle> {
+        self.0.upgrade().map(ForeignToplevelHandle)
+    }
+}
+
+/// Macro to delegate implementation of the foreign toplevel protocols to [`ForeignToplevelState`].
+///
+/// You must also implement [`ForeignToplevelHandler`] to use this.
+#[macro_export]
+macro_rules! delegate_foreign_toplevel {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::foreign_toplevel::v1::server::zwlr_foreign_toplevel_manager_v1::ZwlrForeignToplevelManagerV1: $crate::wayland::foreign_toplevel::ForeignToplevelGlobalData
+        ] => $crate::wayland::foreign_toplevel::ForeignToplevelState);
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::ext::foreign_toplevel_list::v1::server::ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1: $crate::wayland::foreign_toplevel::ForeignToplevelGlobalData
+        ] => $crate::wayland::foreign_toplevel::ForeignToplevelState);
+
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::foreign_toplevel::v1::server::zwlr_foreign_toplevel_manager_v1::ZwlrForeignToplevelManagerV1: ()
+        ] => $crate::wayland::foreign_toplevel::ForeignToplevelState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::foreign_toplevel::v1::server::zwlr_foreign_toplevel_handle_v1::ZwlrForeignToplevelHandleV1: $crate::wayland::foreign_toplevel::WeakForeignToplevelHandle
+        ] => $crate::wayland::foreign_toplevel::ForeignToplevelState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::ext::foreign_toplevel_list::v1::server::ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1: ()
+        ] => $crate::wayland::foreign_toplevel::ForeignToplevelState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::ext::foreign_toplevel_list::v1::server::ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1: $crate::wayland::foreign_toplevel::WeakForeignToplevelHandle
+        ] => $crate::wayland::foreign_toplevel::ForeignToplevelState);
+    };
+}
@@ -0,0 +1,267 @@
+//! Utilities for handling the `zwp_idle_inhibit_manager_v1` protocol
+//!
+//! This protocol allows clients (like video players) to prevent the compositor from
+//! going idle while one of their surfaces is visible.
+//!
+//! An inhibitor only counts while its surface is visible, which is the case if the surface was
+//! entered on at least one output using [`Output::enter`](crate::output::Output::enter).
+//! When using the [`desktop`](crate::desktop) abstractions this is taken care of by the output
+//! update logic of the [`Space`](crate::desktop::Space).
+//!
+//! The inhibitors can be passed to the [`idle_notify`](crate::wayland::idle_notify) protocol using
+//! [`IdleNotifierState::set_idle_inhibitors`](crate::wayland::idle_notify::IdleNotifierState::set_idle_inhibitors),
+//! which then stops sending idle notifications while an inhibitor is active. Otherwise compositors are
+//! expected to check [`IdleInhibitManagerState::is_inhibited`] regularly, as the visibility of surfaces
+//! changes outside of protocol requests.
+//!
+//! ```
+//! extern crate wayland_server;
+//! extern crate smithay;
+//!
+//! use smithay::delegate_idle_inhibit;
+//! use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+//! use smithay::wayland::idle_inhibit::{IdleInhibitHandler, IdleInhibitManagerState};
+//!
+//! # struct State { idle_inhibit_manager_state: IdleInhibitManagerState }
+//! # let mut display = wayland_server::Display::<State>::new().unwrap();
+//! // Create the idle inhibit manager state
+//! let idle_inhibit_manager_state = IdleInhibitManagerState::new::<State>(&display.handle());
+//!
+//! impl IdleInhibitHandler for State {
+//!     fn idle_inhibit_manager_state(&mut self) -> &mut IdleInhibitManagerState {
+//!         &mut self.idle_inhibit_manager_state
+//!     }
+//!
+//!     fn inhibit(&mut self, surface: WlSurface) {
+//!         // a new inhibitor was created for `surface`
+//!     }
+//!
+//!     fn uninhibit(&mut self, surface: WlSurface) {
+//!         // the inhibitor for `surface` was destroyed
+//!     }
+//! }
+//!
+//! delegate_idle_inhibit!(State);
+//! ```
+
+use std::sync::{Arc, Mutex};
+
+use wayland_protocols::wp::idle_inhibit::zv1::server::{
+    zwp_idle_inhibit_manager_v1::{self, ZwpIdleInhibitManagerV1},
+    zwp_idle_inhibitor_v1::{self, ZwpIdleInhibitorV1},
+};
+use wayland_server::{
+    backend::{ClientId, GlobalId, ObjectId},
+    protocol::wl_surface::WlSurface,
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+};
+
+use crate::wayland::compositor::SurfaceUserData;
+
+const MANAGER_VERSION: u32 = 1;
+
+/// Handler trait for idle inhibition
+pub trait IdleInhibitHandler {
+    /// [`IdleInhibitManagerState`] getter
+    fn idle_inhibit_manager_state(&mut self) -> &mut IdleInhibitManagerState;
+
+    /// A client created an inhibitor for the given surface
+    fn inhibit(&mut self, surface: WlSurface);
+
+    /// The inhibitor of the given surface was destroyed
+    fn uninhibit(&mut self, surface: WlSurface);
+}
+
+/// User data of the [`ZwpIdleInhibitorV1`] objects
+#[derive(Debug)]
+pub struct IdleInhibitorUserData {
+    surface: WlSurface,
+}
+
+/// State of the idle inhibit protocol
+#[derive(Debug)]
+pub struct IdleInhibitManagerState {
+    global: GlobalId,
+    inhibitors: IdleInhibitors,
+}
+
+impl IdleInhibitManagerState {
+    /// Register new [ZwpIdleInhibitManagerV1] global
+    pub fn new<D>(display: &DisplayHandle) -> Self
+    where
+        D: GlobalDispatch<ZwpIdleInhibitManagerV1, ()>,
+        D: Dispatch<ZwpIdleInhibitManagerV1, ()>,
+        D: Dispatch<ZwpIdleInhibitorV1, IdleInhibitorUserData>,
+        D: IdleInhibitHandler,
+        D: 'static,
+    {
+        let global = display.create_global::<D, ZwpIdleInhibitManagerV1, _>(MANAGER_VERSION, ());
+
+        Self {
+            global,
+            inhibitors: IdleInhibitors::default(),
+        }
+    }
+
+    /// Returns whether any inhibitor is currently active
+    ///
+    /// An inhibitor is active while its surface is alive and visible on at least one output.
+    pub fn is_inhibited(&self) -> bool {
+        self.inhibitors.is_inhibited()
+    }
+
+    /// Returns the surfaces of all active inhibitors
+    pub fn inhibiting_surfaces(&self) -> Vec<WlSurface> {
+        self.inhibitors
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|inhibitor| inhibitor.data::<IdleInhibitorUserData>().unwrap().surface.clone())
+            .filter(|surface| surface.is_alive() && is_visible(surface))
+            .collect()
+    }
+
+    /// [ZwpIdleInhibitManagerV1] GlobalId getter
+    pub fn global(&self) -> GlobalId {
+        self.global.clone()
+    }
+
+    pub(crate) fn inhibitors(&self) -> IdleInhibitors {
+        self.inhibitors.clone()
+    }
+}
+
+/// Inhibitors of an [`IdleInhibitManagerState`], shared with the idle notifier
+#[derive(Debug, Clone, Default)]
+pub(crate) struct IdleInhibitors(Arc<Mutex<Vec<ZwpIdleInhibitorV1>>>);
+
+impl IdleInhibitors {
+    pub(crate) fn is_inhibited(&self) -> bool {
+        self.0.lock().unwrap().iter().any(|inhibitor| {
+            let surface = &inhibitor.data::<IdleInhibitorUserData>().unwrap().surface;
+            surface.is_alive() && is_visible(surface)
+        })
+    }
+}
+
+fn is_visible(surface: &WlSurface) -> bool {
+    surface
+        .data::<SurfaceUserData>()
+        .map(|data| {
+            data.outputs
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|output| output.upgrade().is_some())
+        })
+        .unwrap_or(false)
+}
+
+impl<D> GlobalDispatch<ZwpIdleInhibitManagerV1, (), D> for IdleInhibitManagerState
+where
+    D: GlobalDispatch<ZwpIdleInhibitManagerV1, ()>,
+    D: Dispatch<ZwpIdleInhibitManagerV1, ()>,
+    D: Dispatch<ZwpIdleInhibitorV1, IdleInhibitorUserData>,
+    D: IdleInhibitHandler,
+    D: 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<ZwpIdleInhibitManagerV1>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl<D> Dispatch<ZwpIdleInhibitManagerV1, (), D> for IdleInhibitManagerState
+where
+    D: Dispatch<ZwpIdleInhibitManagerV1, ()>,
+    D: Dispatch<ZwpIdleInhibitorV1, IdleInhibitorUserData>,
+    D: IdleInhibitHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        _manager: &ZwpIdleInhibitManagerV1,
+        request: zwp_idle_inhibit_manager_v1::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_idle_inhibit_manager_v1::Request::CreateInhibitor { id, surface } => {
+                let inhibitor = data_init.init(
+                    id,
+                    IdleInhibitorUserData {
+                        surface: surface.clone(),
+                    },
+                );
+                state
+                    .idle_inhibit_manager_state()
+                    .inhibitors
+                    .0
+                    .lock()
+                    .unwrap()
+                    .push(inhibitor);
+                state.inhibit(surface);
+            }
+            zwp_idle_inhibit_manager_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ZwpIdleInhibitorV1, IdleInhibitorUserData, D> for IdleInhibitManagerState
+where
+    D: Dispatch<ZwpIdleInhibitorV1, IdleInhibitorUserData>,
+    D: IdleInhibitHandler,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _inhibitor: &ZwpIdleInhibitorV1,
+        request: zwp_idle_inhibitor_v1::Request,
+        _data: &IdleInhibitorUserData,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_idle_inhibitor_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(state: &mut D, _: ClientId, object_id: ObjectId, data: &IdleInhibitorUserData) {
+        state
+            .idle_inhibit_manager_state()
+            .inhibitors
+            .0
+            .lock()
+            .unwrap()
+            .retain(|inhibitor| inhibitor.id() != object_id);
+        state.uninhibit(data.surface.clone());
+    }
+}
+
+/// Macro to delegate implementation of the idle inhibit protocol
+#[macro_export]
+macro_rules! delegate_idle_inhibit {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::idle_inhibit::zv1::server::zwp_idle_inhibit_manager_v1::ZwpIdleInhibitManagerV1: ()
+        ] => $crate::wayland::idle_inhibit::IdleInhibitManagerState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::idle_inhibit::zv1::server::zwp_idle_inhibit_manager_v1::ZwpIdleInhibitManagerV1: ()
+        ] => $crate::wayland::idle_inhibit::IdleInhibitManagerState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::idle_inhibit::zv1::server::zwp_idle_inhibitor_v1::ZwpIdleInhibitorV1: $crate::wayland::idle_inhibit::IdleInhibitorUserData
+        ] => $crate::wayland::idle_inhibit::IdleInhibitManagerState);
+    };
+}
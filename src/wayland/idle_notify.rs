@@ -0,0 +1,403 @@
+//! Utilities for handling the `ext-idle-notify` protocol
+//!
+//! This protocol allows clients (like screen lockers or screen blankers) to be notified
+//! when the user has been idle on a given seat for a certain amount of time.
+//!
+//! Idle timers are tracked per seat. Any input processed through the [`KeyboardHandle`](crate::input::keyboard::KeyboardHandle)
+//! or [`PointerHandle`](crate::input::pointer::PointerHandle) of a [`Seat`] automatically resets the
+//! timers of that seat. Input the seat handles do not know about (e.g. touch or tablet input) has to be
+//! reported by calling [`IdleNotifierState::notify_activity`].
+//!
+//! Idle notifications can be inhibited using [`IdleNotifierState::set_is_inhibited`], or by passing the
+//! inhibitors of the [`idle_inhibit`](crate::wayland::idle_inhibit) protocol to
+//! [`IdleNotifierState::set_idle_inhibitors`].
+//!
+//! The idle timers are registered on an event loop, which does not need to use your state as its data.
+//!
+//! ```
+//! extern crate wayland_server;
+//! extern crate smithay;
+//!
+//! use smithay::delegate_idle_notify;
+//! use smithay::input::{SeatHandler, SeatState};
+//! use smithay::reexports::calloop::EventLoop;
+//! use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+//! use smithay::wayland::idle_notify::{IdleNotifierHandler, IdleNotifierState};
+//!
+//! struct State {
+//!     idle_notifier_state: IdleNotifierState<Self>,
+//! #   seat_state: SeatState<Self>,
+//!     // ...
+//! }
+//! # impl SeatHandler for State {
+//! #     type KeyboardFocus = WlSurface;
+//! #     type PointerFocus = WlSurface;
+//! #
+//! #     fn seat_state(&mut self) -> &mut SeatState<Self> {
+//! #         &mut self.seat_state
+//! #     }
+//! # }
+//!
+//! impl IdleNotifierHandler for State {
+//!     fn idle_notifier_state(&mut self) -> &mut IdleNotifierState<Self> {
+//!         &mut self.idle_notifier_state
+//!     }
+//! }
+//!
+//! delegate_idle_notify!(State);
+//!
+//! # let mut display = wayland_server::Display::<State>::new().unwrap();
+//! # let event_loop = EventLoop::<State>::try_new().unwrap();
+//! let idle_notifier_state = IdleNotifierState::<State>::new(&display.handle(), event_loop.handle());
+//! ```
+
+use std::{
+    fmt,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use calloop::{
+    timer::{TimeoutAction, Timer},
+    LoopHandle, RegistrationToken,
+};
+use wayland_protocols::ext::idle_notify::v1::server::{
+    ext_idle_notification_v1::{self, ExtIdleNotificationV1},
+    ext_idle_notifier_v1::{self, ExtIdleNotifierV1},
+};
+use wayland_server::{
+    backend::{ClientId, GlobalId, ObjectId},
+    protocol::wl_seat::WlSeat,
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+};
+
+use crate::{
+    input::{Seat, SeatHandler},
+    wayland::idle_inhibit::{IdleInhibitManagerState, IdleInhibitors},
+};
+
+const NOTIFIER_VERSION: u32 = 1;
+
+/// Handler trait for ext-idle-notify
+pub trait IdleNotifierHandler: SeatHandler + Sized {
+    /// [`IdleNotifierState`] getter
+    fn idle_notifier_state(&mut self) -> &mut IdleNotifierState<Self>;
+}
+
+/// User data of the [`ExtIdleNotificationV1`] objects
+#[derive(Debug)]
+pub struct IdleNotificationUserData {
+    seat: WlSeat,
+    timeout: Duration,
+    is_idle: AtomicBool,
+    last_activity: Mutex<Instant>,
+    timer_token: Mutex<Option<RegistrationToken>>,
+}
+
+impl IdleNotificationUserData {
+    fn take_timer(&self) -> Option<RegistrationToken> {
+        self.timer_token.lock().unwrap().take()
+    }
+}
+
+/// Hook stored in the seat's user data, called on input processed through the seat
+struct IdleActivityHook<D>(fn(&mut D, &Seat<D>));
+
+/// Reset the idle timers of `seat`, if the idle notifier is in use
+pub(crate) fn seat_activity<D: SeatHandler + 'static>(data: &mut D, seat: &Seat<D>) {
+    let hook = seat.user_data().get::<IdleActivityHook<D>>().map(|hook| hook.0);
+    if let Some(hook) = hook {
+        hook(data, seat);
+    }
+}
+
+type TimerCallback = Box<dyn FnMut() -> TimeoutAction>;
+
+/// Event loop the idle timers are registered on, independent of the type of its data
+trait TimerLoop {
+    fn insert_timer(&self, timeout: Duration, callback: TimerCallback) -> Option<RegistrationToken>;
+    fn remove(&self, token: RegistrationToken);
+}
+
+impl<L: 'static> TimerLoop for LoopHandle<'static, L> {
+    fn insert_timer(&self, timeout: Duration, mut callback: TimerCallback) -> Option<RegistrationToken> {
+        self.insert_source(Timer::from_duration(timeout), move |_, _, _| callback())
+            .ok()
+    }
+
+    fn remove(&self, token: RegistrationToken) {
+        LoopHandle::remove(self, token)
+    }
+}
+
+/// Sources of idle inhibition, shared with the idle timers
+#[derive(Debug, Clone, Default)]
+struct Inhibition {
+    is_inhibited: Arc<AtomicBool>,
+    inhibitors: Option<IdleInhibitors>,
+}
+
+impl Inhibition {
+    fn is_inhibited(&self) -> bool {
+        self.is_inhibited.load(Ordering::SeqCst)
+            || self
+                .inhibitors
+                .as_ref()
+                .map(|inhibitors| inhibitors.is_inhibited())
+                .unwrap_or(false)
+    }
+}
+
+/// State of the ext-idle-notify protocol
+pub struct IdleNotifierState<D> {
+    global: GlobalId,
+    notifications: Vec<ExtIdleNotificationV1>,
+    timer_loop: Box<dyn TimerLoop>,
+    inhibition: Inhibition,
+    _state: PhantomData<D>,
+}
+
+impl<D> fmt::Debug for IdleNotifierState<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdleNotifierState")
+            .field("global", &self.global)
+            .field("notifications", &self.notifications)
+            .field("inhibition", &self.inhibition)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<D: IdleNotifierHandler + 'static> IdleNotifierState<D> {
+    /// Register new [ExtIdleNotifierV1] global
+    ///
+    /// The `loop_handle` is used to register the idle timers, its data type does not need to match `D`.
+    pub fn new<L: 'static>(display: &DisplayHandle, loop_handle: LoopHandle<'static, L>) -> Self
+    where
+        D: GlobalDispatch<ExtIdleNotifierV1, ()>,
+        D: Dispatch<ExtIdleNotifierV1, ()>,
+        D: Dispatch<ExtIdleNotificationV1, IdleNotificationUserData>,
+    {
+        let global = display.create_global::<D, ExtIdleNotifierV1, _>(NOTIFIER_VERSION, ());
+
+        Self {
+            global,
+            notifications: Vec::new(),
+            timer_loop: Box::new(loop_handle),
+            inhibition: Inhibition::default(),
+            _state: PhantomData,
+        }
+    }
+
+    /// Notify the protocol of user activity on a given seat
+    ///
+    /// This resets the idle timers of all notifications of that seat and sends
+    /// the `resumed` event to notifications that were idle.
+    ///
+    /// Input processed through the keyboard and pointer handles of the seat calls this automatically.
+    pub fn notify_activity(&mut self, seat: &Seat<D>) {
+        let now = Instant::now();
+        for notification in self.notifications.iter() {
+            let data = notification.data::<IdleNotificationUserData>().unwrap();
+            if !seat.owns(&data.seat) {
+                continue;
+            }
+
+            // a running timer picks up the new activity once it expires
+            *data.last_activity.lock().unwrap() = now;
+            if data.is_idle.swap(false, Ordering::SeqCst) {
+                notification.resumed();
+                self.insert_timer(notification);
+            }
+        }
+    }
+
+    /// Inhibit or allow idle notifications
+    ///
+    /// While inhibited no `idled` events are sent. Allowing idle notifications again
+    /// restarts the idle timers of all notifications.
+    pub fn set_is_inhibited(&mut self, is_inhibited: bool) {
+        if self.inhibition.is_inhibited.swap(is_inhibited, Ordering::SeqCst) == is_inhibited {
+            return;
+        }
+
+        if !is_inhibited {
+            let now = Instant::now();
+            for notification in self.notifications.iter() {
+                let data = notification.data::<IdleNotificationUserData>().unwrap();
+                *data.last_activity.lock().unwrap() = now;
+            }
+        }
+    }
+
+    /// Inhibit idle notifications while any inhibitor of the idle inhibit protocol is active
+    ///
+    /// This is checked whenever an idle timer expires, in addition to [`IdleNotifierState::set_is_inhibited`].
+    pub fn set_idle_inhibitors(&mut self, idle_inhibit_manager_state: &IdleInhibitManagerState) {
+        self.inhibition.inhibitors = Some(idle_inhibit_manager_state.inhibitors());
+    }
+
+    /// Returns whether idle notifications are currently inhibited
+    pub fn is_inhibited(&self) -> bool {
+        self.inhibition.is_inhibited()
+    }
+
+    /// [ExtIdleNotifierV1] GlobalId getter
+    pub fn global(&self) -> GlobalId {
+        self.global.clone()
+    }
+
+    fn insert_timer(&self, notification: &ExtIdleNotificationV1) {
+        let data = notification.data::<IdleNotificationUserData>().unwrap();
+        if let Some(token) = data.take_timer() {
+            self.timer_loop.remove(token);
+        }
+
+        let timeout = data.timeout;
+        let inhibition = self.inhibition.clone();
+        let notification = notification.clone();
+        let token = self.timer_loop.insert_timer(
+            timeout,
+            Box::new(move || {
+                if !notification.is_alive() {
+                    return TimeoutAction::Drop;
+                }
+
+                let data = notification.data::<IdleNotificationUserData>().unwrap();
+                let idle_time = data.last_activity.lock().unwrap().elapsed();
+                if idle_time < timeout {
+                    return TimeoutAction::ToDuration(timeout - idle_time);
+                }
+
+                if inhibition.is_inhibited() {
+                    return TimeoutAction::ToDuration(timeout);
+                }
+
+                if !data.is_idle.swap(true, Ordering::SeqCst) {
+                    notification.idled();
+                }
+                data.take_timer();
+
+                TimeoutAction::Drop
+            }),
+        );
+
+        *data.timer_token.lock().unwrap() = token;
+    }
+}
+
+impl<D> GlobalDispatch<ExtIdleNotifierV1, (), D> for IdleNotifierState<D>
+where
+    D: GlobalDispatch<ExtIdleNotifierV1, ()>,
+    D: Dispatch<ExtIdleNotifierV1, ()>,
+    D: Dispatch<ExtIdleNotificationV1, IdleNotificationUserData>,
+    D: IdleNotifierHandler,
+    D: 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<ExtIdleNotifierV1>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl<D> Dispatch<ExtIdleNotifierV1, (), D> for IdleNotifierState<D>
+where
+    D: Dispatch<ExtIdleNotifierV1, ()>,
+    D: Dispatch<ExtIdleNotificationV1, IdleNotificationUserData>,
+    D: IdleNotifierHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        _notifier: &ExtIdleNotifierV1,
+        request: ext_idle_notifier_v1::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_idle_notifier_v1::Request::GetIdleNotification { id, timeout, seat } => {
+                if let Some(seat) = Seat::<D>::from_resource(&seat) {
+                    seat.user_data().insert_if_missing(|| {
+                        IdleActivityHook::<D>(|state, seat| state.idle_notifier_state().notify_activity(seat))
+                    });
+                }
+
+                let notification = data_init.init(
+                    id,
+                    IdleNotificationUserData {
+                        seat,
+                        timeout: Duration::from_millis(timeout as u64),
+                        is_idle: AtomicBool::new(false),
+                        last_activity: Mutex::new(Instant::now()),
+                        timer_token: Mutex::new(None),
+                    },
+                );
+
+                let idle_notifier_state = state.idle_notifier_state();
+                idle_notifier_state.insert_timer(&notification);
+                idle_notifier_state.notifications.push(notification);
+            }
+            ext_idle_notifier_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ExtIdleNotificationV1, IdleNotificationUserData, D> for IdleNotifierState<D>
+where
+    D: Dispatch<ExtIdleNotificationV1, IdleNotificationUserData>,
+    D: IdleNotifierHandler,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _notification: &ExtIdleNotificationV1,
+        request: ext_idle_notification_v1::Request,
+        _data: &IdleNotificationUserData,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_idle_notification_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(state: &mut D, _: ClientId, object_id: ObjectId, data: &IdleNotificationUserData) {
+        let idle_notifier_state = state.idle_notifier_state();
+        if let Some(token) = data.take_timer() {
+            idle_notifier_state.timer_loop.remove(token);
+        }
+        idle_notifier_state
+            .notifications
+            .retain(|notification| notification.id() != object_id);
+    }
+}
+
+/// Macro to delegate implementation of the ext idle notify protocol
+#[macro_export]
+macro_rules! delegate_idle_notify {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::ext::idle_notify::v1::server::ext_idle_notifier_v1::ExtIdleNotifierV1: ()
+        ] => $crate::wayland::idle_notify::IdleNotifierState<$ty>);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::ext::idle_notify::v1::server::ext_idle_notifier_v1::ExtIdleNotifierV1: ()
+        ] => $crate::wayland::idle_notify::IdleNotifierState<$ty>);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::ext::idle_notify::v1::server::ext_idle_notification_v1::ExtIdleNotificationV1: $crate::wayland::idle_notify::IdleNotificationUserData
+        ] => $crate::wayland::idle_notify::IdleNotifierState<$ty>);
+    };
+}
@@ -47,21 +47,36 @@
 
 pub mod buffer;
 pub mod compositor;
+pub mod cursor_shape;
+pub mod data_control;
 pub mod data_device;
 pub mod dmabuf;
+#[cfg(feature = "backend_drm")]
+pub mod drm_syncobj;
+pub mod foreign_toplevel;
 pub mod fractional_scale;
+pub mod idle_inhibit;
+pub mod idle_notify;
 pub mod input_method;
 pub mod keyboard_shortcuts_inhibit;
 pub mod output;
+pub mod output_management;
+pub mod pointer_constraints;
+pub mod pointer_gestures;
 pub mod presentation;
 pub mod primary_selection;
 pub mod relative_pointer;
+pub mod screencopy;
 pub mod seat;
+pub mod security_context;
+pub mod session_lock;
 pub mod shell;
 pub mod shm;
 pub mod socket;
 pub mod tablet_manager;
+pub mod tearing_control;
 pub mod text_input;
 pub mod viewporter;
 pub mod virtual_keyboard;
 pub mod xdg_activation;
+pub mod xdg_foreign;
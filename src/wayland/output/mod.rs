@@ -74,6 +74,7 @@ use wayland_server::{
 };
 
 use crate::utils::{Logical, Point};
+use crate::wayland::compositor::SurfaceUserData;
 
 pub use self::handlers::XdgOutputUserData;
 
@@ -253,6 +254,15 @@ impl Output {
     /// Sends `wl_surface.enter` for the provided surface
     /// with the matching client output
     pub fn enter(&self, surface: &wl_surface::WlSurface) {
+        if let Some(data) = surface.data::<SurfaceUserData>() {
+            let mut outputs = data.outputs.lock().unwrap();
+            outputs.retain(|o| o.upgrade().is_some());
+            let weak = self.downgrade();
+            if !outputs.contains(&weak) {
+                outputs.push(weak);
+            }
+        }
+
         let client = self
             .inner
             .0
@@ -272,6 +282,14 @@ impl Output {
     /// Sends `wl_surface.leave` for the provided surface
     /// with the matching client output
     pub fn leave(&self, surface: &wl_surface::WlSurface) {
+        if let Some(data) = surface.data::<SurfaceUserData>() {
+            let weak = self.downgrade();
+            data.outputs
+                .lock()
+                .unwrap()
+                .retain(|o| o != &weak && o.upgrade().is_some());
+        }
+
         let client = self
             .inner
             .0
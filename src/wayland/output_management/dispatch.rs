@@ -0,0 +1,454 @@
+use std::sync::Mutex;
+
+use wayland_protocols_wlr::output_management::v1::server::{
+    zwlr_output_configuration_head_v1::{self, ZwlrOutputConfigurationHeadV1},
+    zwlr_output_configuration_v1::{self, ZwlrOutputConfigurationV1},
+    zwlr_output_head_v1::{self, AdaptiveSyncState, ZwlrOutputHeadV1},
+    zwlr_output_manager_v1::{self, ZwlrOutputManagerV1},
+    zwlr_output_mode_v1::{self, ZwlrOutputModeV1},
+};
+use wayland_server::{
+    backend::{ClientId, ObjectId},
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource, WEnum,
+};
+
+use crate::output::{Mode, Output};
+
+use super::{
+    send_head, ConfigurationInner, ModeConfiguration, OutputConfiguration, OutputConfigurationData,
+    OutputConfigurationHeadData, OutputHeadData, OutputManagementGlobalData, OutputManagementHandler,
+    OutputManagementState, PendingHeadConfiguration,
+};
+
+impl<D> GlobalDispatch<ZwlrOutputManagerV1, OutputManagementGlobalData, D> for OutputManagementState
+where
+    D: GlobalDispatch<ZwlrOutputManagerV1, OutputManagementGlobalData>
+        + Dispatch<ZwlrOutputManagerV1, ()>
+        + Dispatch<ZwlrOutputHeadV1, OutputHeadData>
+        + Dispatch<ZwlrOutputModeV1, Mode>
+        + Dispatch<ZwlrOutputConfigurationV1, OutputConfigurationData>
+        + Dispatch<ZwlrOutputConfigurationHeadV1, OutputConfigurationHeadData>
+        + OutputManagementHandler
+        + 'static,
+{
+    fn bind(
+        state: &mut D,
+        handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<ZwlrOutputManagerV1>,
+        _global_data: &OutputManagementGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let manager = data_init.init(resource, ());
+
+        let management_state = state.output_management_state();
+        for head in &mut management_state.heads {
+            // heads, that were not advertised yet, are sent on the next update
+            if let Some(head_state) = head.advertised.clone() {
+                send_head::<D>(handle, &manager, head, &head_state);
+            }
+        }
+        manager.done(management_state.serial);
+
+        management_state.managers.push(manager);
+    }
+
+    fn can_view(client: Client, global_data: &OutputManagementGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<ZwlrOutputManagerV1, (), D> for OutputManagementState
+where
+    D: Dispatch<ZwlrOutputManagerV1, ()>
+        + Dispatch<ZwlrOutputConfigurationV1, OutputConfigurationData>
+        + OutputManagementHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        manager: &ZwlrOutputManagerV1,
+        request: zwlr_output_manager_v1::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwlr_output_manager_v1::Request::CreateConfiguration { id, serial } => {
+                data_init.init(
+                    id,
+                    OutputConfigurationData {
+                        serial,
+                        manager: manager.clone(),
+                        inner: Mutex::new(ConfigurationInner::default()),
+                    },
+                );
+            }
+            zwlr_output_manager_v1::Request::Stop => {
+                state
+                    .output_management_state()
+                    .managers
+                    .retain(|instance| instance != manager);
+                manager.finished();
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(state: &mut D, _client: ClientId, id: ObjectId, _data: &()) {
+        state
+            .output_management_state()
+            .managers
+            .retain(|manager| manager.id() != id);
+    }
+}
+
+impl<D> Dispatch<ZwlrOutputHeadV1, OutputHeadData, D> for OutputManagementState
+where
+    D: Dispatch<ZwlrOutputHeadV1, OutputHeadData> + OutputManagementHandler + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _head: &ZwlrOutputHeadV1,
+        request: zwlr_output_head_v1::Request,
+        _data: &OutputHeadData,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwlr_output_head_v1::Request::Release => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(state: &mut D, _client: ClientId, id: ObjectId, _data: &OutputHeadData) {
+        for head in &mut state.output_management_state().heads {
+            head.instances.retain(|instance| instance.id() != id);
+        }
+    }
+}
+
+impl<D> Dispatch<ZwlrOutputModeV1, Mode, D> for OutputManagementState
+where
+    D: Dispatch<ZwlrOutputModeV1, Mode> + OutputManagementHandler + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _mode: &ZwlrOutputModeV1,
+        request: zwlr_output_mode_v1::Request,
+        _data: &Mode,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwlr_output_mode_v1::Request::Release => {}
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ZwlrOutputConfigurationV1, OutputConfigurationData, D> for OutputManagementState
+where
+    D: Dispatch<ZwlrOutputHeadV1, OutputHeadData>
+        + Dispatch<ZwlrOutputModeV1, Mode>
+        + Dispatch<ZwlrOutputConfigurationV1, OutputConfigurationData>
+        + Dispatch<ZwlrOutputConfigurationHeadV1, OutputConfigurationHeadData>
+        + OutputManagementHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        configuration: &ZwlrOutputConfigurationV1,
+        request: zwlr_output_configuration_v1::Request,
+        data: &OutputConfigurationData,
+        dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let mut inner = data.inner.lock().unwrap();
+
+        match request {
+            zwlr_output_configuration_v1::Request::EnableHead { id, head } => {
+                let config_head = data_init.init(
+                    id,
+                    OutputConfigurationHeadData {
+                        head: head.clone(),
+                        pending: Mutex::new(PendingHeadConfiguration::default()),
+                    },
+                );
+                if !check_configurable(configuration, &inner, &head) {
+                    return;
+                }
+                inner.heads.push((head, Some(config_head)));
+            }
+            zwlr_output_configuration_v1::Request::DisableHead { head } => {
+                if !check_configurable(configuration, &inner, &head) {
+                    return;
+                }
+                inner.heads.push((head, None));
+            }
+            zwlr_output_configuration_v1::Request::Apply => {
+                if let Some(config) = take_configuration(state, configuration, data, &mut inner) {
+                    if state.apply_configuration(config.clone()) {
+                        configuration.succeeded();
+
+                        let management_state = state.output_management_state();
+                        for (output, output_config) in config {
+                            match output_config {
+                                OutputConfiguration::Enabled { adaptive_sync, .. } => {
+                                    management_state.set_head_enabled(&output, true);
+                                    if let Some(adaptive_sync) = adaptive_sync {
+                                        management_state.set_adaptive_sync(&output, adaptive_sync);
+                                    }
+                                }
+                                OutputConfiguration::Disabled => {
+                                    management_state.set_head_enabled(&output, false);
+                                }
+                            }
+                        }
+                        management_state.update::<D>(dhandle);
+                    } else {
+                        configuration.failed();
+                    }
+                }
+            }
+            zwlr_output_configuration_v1::Request::Test => {
+                if let Some(config) = take_configuration(state, configuration, data, &mut inner) {
+                    if state.test_configuration(config) {
+                        configuration.succeeded();
+                    } else {
+                        configuration.failed();
+                    }
+                }
+            }
+            zwlr_output_configuration_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Check that a head may be added to a configuration, posting an error otherwise
+fn check_configurable(
+    configuration: &ZwlrOutputConfigurationV1,
+    inner: &ConfigurationInner,
+    head: &ZwlrOutputHeadV1,
+) -> bool {
+    if inner.used {
+        configuration.post_error(
+            zwlr_output_configuration_v1::Error::AlreadyUsed,
+            "The configuration was already applied or tested",
+        );
+        return false;
+    }
+    if inner.heads.iter().any(|(configured, _)| configured == head) {
+        configuration.post_error(
+            zwlr_output_configuration_v1::Error::AlreadyConfiguredHead,
+            "The head was already configured",
+        );
+        return false;
+    }
+    true
+}
+
+/// Collect the configuration of all heads for testing or applying it
+///
+/// Returns `None` if the configuration is invalid or was cancelled.
+fn take_configuration<D>(
+    state: &mut D,
+    configuration: &ZwlrOutputConfigurationV1,
+    data: &OutputConfigurationData,
+    inner: &mut ConfigurationInner,
+) -> Option<Vec<(Output, OutputConfiguration)>>
+where
+    D: OutputManagementHandler,
+{
+    if inner.used {
+        configuration.post_error(
+            zwlr_output_configuration_v1::Error::AlreadyUsed,
+            "The configuration was already applied or tested",
+        );
+        return None;
+    }
+    inner.used = true;
+
+    let management_state = state.output_management_state();
+
+    // the client has outdated information about the heads, which might be
+    // missing some of them, so this has to be checked before the unconfigured heads
+    if data.serial != management_state.serial {
+        configuration.cancelled();
+        return None;
+    }
+
+    let unconfigured = management_state
+        .heads
+        .iter()
+        .flat_map(|head| head.instances.iter())
+        .filter(|instance| {
+            instance
+                .data::<OutputHeadData>()
+                .map(|head_data| head_data.manager == data.manager)
+                .unwrap_or(false)
+        })
+        .any(|instance| !inner.heads.iter().any(|(head, _)| head == instance));
+    if unconfigured {
+        configuration.post_error(
+            zwlr_output_configuration_v1::Error::UnconfiguredHead,
+            "Not all heads were configured",
+        );
+        return None;
+    }
+
+    let mut config = Vec::with_capacity(inner.heads.len());
+    for (head, config_head) in &inner.heads {
+        let output = match head
+            .data::<OutputHeadData>()
+            .and_then(|head_data| head_data.output.upgrade())
+        {
+            Some(output) => output,
+            None => {
+                configuration.cancelled();
+                return None;
+            }
+        };
+
+        let output_config = match config_head
+            .as_ref()
+            .and_then(|config_head| config_head.data::<OutputConfigurationHeadData>())
+        {
+            Some(config_head_data) => {
+                let pending = config_head_data.pending.lock().unwrap();
+                OutputConfiguration::Enabled {
+                    mode: pending.mode,
+                    position: pending.position,
+                    transform: pending.transform,
+                    scale: pending.scale,
+                    adaptive_sync: pending.adaptive_sync,
+                }
+            }
+            None => OutputConfiguration::Disabled,
+        };
+        config.push((output, output_config));
+    }
+
+    Some(config)
+}
+
+impl<D> Dispatch<ZwlrOutputConfigurationHeadV1, OutputConfigurationHeadData, D> for OutputManagementState
+where
+    D: Dispatch<ZwlrOutputConfigurationHeadV1, OutputConfigurationHeadData>
+        + OutputManagementHandler
+        + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        config_head: &ZwlrOutputConfigurationHeadV1,
+        request: zwlr_output_configuration_head_v1::Request,
+        data: &OutputConfigurationHeadData,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        let mut pending = data.pending.lock().unwrap();
+
+        match request {
+            zwlr_output_configuration_head_v1::Request::SetMode { mode } => {
+                if pending.mode.is_some() {
+                    already_set(config_head, "mode");
+                    return;
+                }
+                let belongs_to_head = data
+                    .head
+                    .data::<OutputHeadData>()
+                    .map(|head_data| head_data.modes.lock().unwrap().contains(&mode))
+                    .unwrap_or(false);
+                match mode.data::<Mode>() {
+                    Some(mode) if belongs_to_head => pending.mode = Some(ModeConfiguration::Mode(*mode)),
+                    _ => config_head.post_error(
+                        zwlr_output_configuration_head_v1::Error::InvalidMode,
+                        "The mode doesn't belong to the head",
+                    ),
+                }
+            }
+            zwlr_output_configuration_head_v1::Request::SetCustomMode {
+                width,
+                height,
+                refresh,
+            } => {
+                if pending.mode.is_some() {
+                    already_set(config_head, "mode");
+                    return;
+                }
+                if width <= 0 || height <= 0 || refresh < 0 {
+                    config_head.post_error(
+                        zwlr_output_configuration_head_v1::Error::InvalidCustomMode,
+                        "Invalid custom mode",
+                    );
+                    return;
+                }
+                pending.mode = Some(ModeConfiguration::Custom {
+                    size: (width, height).into(),
+                    refresh: Some(refresh).filter(|refresh| *refresh > 0),
+                });
+            }
+            zwlr_output_configuration_head_v1::Request::SetPosition { x, y } => {
+                if pending.position.is_some() {
+                    already_set(config_head, "position");
+                    return;
+                }
+                pending.position = Some((x, y).into());
+            }
+            zwlr_output_configuration_head_v1::Request::SetTransform { transform } => {
+                if pending.transform.is_some() {
+                    already_set(config_head, "transform");
+                    return;
+                }
+                match transform {
+                    WEnum::Value(transform) => pending.transform = Some(transform.into()),
+                    WEnum::Unknown(_) => config_head.post_error(
+                        zwlr_output_configuration_head_v1::Error::InvalidTransform,
+                        "Invalid transform",
+                    ),
+                }
+            }
+            zwlr_output_configuration_head_v1::Request::SetScale { scale } => {
+                if pending.scale.is_some() {
+                    already_set(config_head, "scale");
+                    return;
+                }
+                if scale <= 0.0 {
+                    config_head.post_error(
+                        zwlr_output_configuration_head_v1::Error::InvalidScale,
+                        "Scale must be positive",
+                    );
+                    return;
+                }
+                pending.scale = Some(scale);
+            }
+            zwlr_output_configuration_head_v1::Request::SetAdaptiveSync { state } => {
+                if pending.adaptive_sync.is_some() {
+                    already_set(config_head, "adaptive sync");
+                    return;
+                }
+                match state {
+                    WEnum::Value(state) => pending.adaptive_sync = Some(state == AdaptiveSyncState::Enabled),
+                    WEnum::Unknown(_) => config_head.post_error(
+                        zwlr_output_configuration_head_v1::Error::InvalidAdaptiveSyncState,
+                        "Invalid adaptive sync state",
+                    ),
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn already_set(config_head: &ZwlrOutputConfigurationHeadV1, property: &str) {
+    config_head.post_error(
+        zwlr_output_configuration_head_v1::Error::AlreadySet,
+        format!("The {} was already set", property),
+    );
+}
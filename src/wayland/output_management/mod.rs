@@ -0,0 +1,586 @@
+//! Utilities for handling the `wlr-output-management` protocol
+//!
+//! This protocol allows privileged clients (like kanshi or wdisplays) to inspect the outputs
+//! of the compositor and to change their configuration at runtime.
+//!
+//! Every [`Output`] that should be configurable needs to be registered as a head using
+//! [`OutputManagementState::add_head`]. Modes, the current mode, position, transform and scale
+//! of the head are derived from the [`Output`] itself, while whether the head is enabled and
+//! whether it uses adaptive sync is tracked by the [`OutputManagementState`]
+//! (see [`OutputManagementState::set_head_enabled`] and [`OutputManagementState::set_adaptive_sync`]).
+//! Clients are only notified about changes once [`OutputManagementState::update`] is called,
+//! so make sure to call it after modifying your outputs or adding and removing heads.
+//!
+//! Configurations requested by clients are passed as a list of [`OutputConfiguration`]s to
+//! [`OutputManagementHandler::test_configuration`] and [`OutputManagementHandler::apply_configuration`].
+//! Configurations with an outdated serial are cancelled without calling your handler.
+//!
+//! ```no_run
+//! # extern crate wayland_server;
+//! use smithay::delegate_output_management;
+//! use smithay::output::Output;
+//! use smithay::wayland::output_management::{
+//!     OutputConfiguration, OutputManagementHandler, OutputManagementState,
+//! };
+//!
+//! pub struct State {
+//!     output_management_state: OutputManagementState,
+//! }
+//!
+//! impl OutputManagementHandler for State {
+//!     fn output_management_state(&mut self) -> &mut OutputManagementState {
+//!         &mut self.output_management_state
+//!     }
+//!
+//!     fn test_configuration(&mut self, config: Vec<(Output, OutputConfiguration)>) -> bool {
+//!         // check if the configuration could be applied
+//!         true
+//!     }
+//!
+//!     fn apply_configuration(&mut self, config: Vec<(Output, OutputConfiguration)>) -> bool {
+//!         // apply the configuration, e.g. using `Output::change_current_state`
+//!         true
+//!     }
+//! }
+//!
+//! delegate_output_management!(State);
+//!
+//! # let mut display = wayland_server::Display::<State>::new().unwrap();
+//! # let display_handle = display.handle();
+//! let mut output_management_state = OutputManagementState::new::<State>(&display_handle);
+//! # let output: Output = todo!();
+//!
+//! // advertise an output to clients
+//! output_management_state.add_head(&output);
+//! output_management_state.update::<State>(&display_handle);
+//! ```
+
+use std::sync::Mutex;
+
+use wayland_protocols_wlr::output_management::v1::server::{
+    zwlr_output_configuration_head_v1::ZwlrOutputConfigurationHeadV1,
+    zwlr_output_configuration_v1::ZwlrOutputConfigurationV1,
+    zwlr_output_head_v1::{AdaptiveSyncState, ZwlrOutputHeadV1},
+    zwlr_output_manager_v1::ZwlrOutputManagerV1,
+    zwlr_output_mode_v1::ZwlrOutputModeV1,
+};
+use wayland_server::{backend::GlobalId, Client, Dispatch, DisplayHandle, GlobalDispatch, Resource};
+
+use crate::{
+    output::{Mode, Output, WeakOutput},
+    utils::{Logical, Physical, Point, Size, Transform},
+};
+
+mod dispatch;
+
+const MANAGER_VERSION: u32 = 4;
+
+/// Data associated with a output manager global
+#[allow(missing_debug_implementations)]
+pub struct OutputManagementGlobalData {
+    filter: Box<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+}
+
+/// State of the wlr-output-management global
+#[derive(Debug)]
+pub struct OutputManagementState {
+    global: GlobalId,
+    serial: u32,
+    dirty: bool,
+    managers: Vec<ZwlrOutputManagerV1>,
+    heads: Vec<Head>,
+}
+
+#[derive(Debug)]
+struct Head {
+    output: Output,
+    enabled: bool,
+    adaptive_sync: bool,
+    /// The state last advertised to clients, `None` if the head wasn't advertised yet
+    advertised: Option<HeadState>,
+    instances: Vec<ZwlrOutputHeadV1>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct HeadState {
+    enabled: bool,
+    modes: Vec<Mode>,
+    current_mode: Option<Mode>,
+    preferred_mode: Option<Mode>,
+    position: Point<i32, Logical>,
+    transform: Transform,
+    scale: f64,
+    adaptive_sync: bool,
+}
+
+impl HeadState {
+    fn new(head: &Head) -> HeadState {
+        let output = &head.output;
+        HeadState {
+            enabled: head.enabled,
+            modes: output.modes(),
+            current_mode: output.current_mode(),
+            preferred_mode: output.preferred_mode(),
+            position: output.current_location(),
+            transform: output.current_transform(),
+            scale: output.current_scale().fractional_scale(),
+            adaptive_sync: head.adaptive_sync,
+        }
+    }
+}
+
+impl OutputManagementState {
+    /// Create a new `zwlr_output_manager_v1` global
+    pub fn new<D>(display: &DisplayHandle) -> OutputManagementState
+    where
+        D: GlobalDispatch<ZwlrOutputManagerV1, OutputManagementGlobalData>
+            + Dispatch<ZwlrOutputManagerV1, ()>
+            + Dispatch<ZwlrOutputHeadV1, OutputHeadData>
+            + Dispatch<ZwlrOutputModeV1, Mode>
+            + Dispatch<ZwlrOutputConfigurationV1, OutputConfigurationData>
+            + Dispatch<ZwlrOutputConfigurationHeadV1, OutputConfigurationHeadData>
+            + OutputManagementHandler
+            + 'static,
+    {
+        Self::new_with_filter::<D, _>(display, |_| true)
+    }
+
+    /// Create a new `zwlr_output_manager_v1` global, only visible to [privileged clients]
+    ///
+    /// [privileged clients]: crate::wayland::security_context#privileged-globals
+    pub fn new_with_filter<D, F>(display: &DisplayHandle, filter: F) -> OutputManagementState
+    where
+        D: GlobalDispatch<ZwlrOutputManagerV1, OutputManagementGlobalData>
+            + Dispatch<ZwlrOutputManagerV1, ()>
+            + Dispatch<ZwlrOutputHeadV1, OutputHeadData>
+            + Dispatch<ZwlrOutputModeV1, Mode>
+            + Dispatch<ZwlrOutputConfigurationV1, OutputConfigurationData>
+            + Dispatch<ZwlrOutputConfigurationHeadV1, OutputConfigurationHeadData>
+            + OutputManagementHandler
+            + 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+    {
+        let data = OutputManagementGlobalData {
+            filter: Box::new(filter),
+        };
+        let global = display.create_global::<D, ZwlrOutputManagerV1, _>(MANAGER_VERSION, data);
+
+        OutputManagementState {
+            global,
+            serial: 0,
+            dirty: false,
+            managers: Vec::new(),
+            heads: Vec::new(),
+        }
+    }
+
+    /// Returns the id of the `zwlr_output_manager_v1` global
+    pub fn global(&self) -> GlobalId {
+        self.global.clone()
+    }
+
+    /// Register an output as a configurable head
+    ///
+    /// The head starts out enabled with adaptive sync disabled.
+    /// It is advertised to clients on the next call to [`OutputManagementState::update`].
+    pub fn add_head(&mut self, output: &Output) {
+        if self.heads.iter().any(|head| &head.output == output) {
+            return;
+        }
+
+        self.heads.push(Head {
+            output: output.clone(),
+            enabled: true,
+            adaptive_sync: false,
+            advertised: None,
+            instances: Vec::new(),
+        });
+    }
+
+    /// Remove the head of an output, e.g. because the monitor was unplugged
+    pub fn remove_head(&mut self, output: &Output) {
+        let pos = match self.heads.iter().position(|head| &head.output == output) {
+            Some(pos) => pos,
+            None => return,
+        };
+
+        let head = self.heads.remove(pos);
+        for instance in head.instances {
+            if let Some(data) = instance.data::<OutputHeadData>() {
+                for mode in data.modes.lock().unwrap().drain(..) {
+                    mode.finished();
+                }
+            }
+            instance.finished();
+        }
+        if head.advertised.is_some() {
+            self.dirty = true;
+        }
+    }
+
+    /// Set whether the head of an output is enabled
+    ///
+    /// Heads disabled by a client are updated automatically after
+    /// [`OutputManagementHandler::apply_configuration`] succeeded.
+    pub fn set_head_enabled(&mut self, output: &Output, enabled: bool) {
+        if let Some(head) = self.heads.iter_mut().find(|head| &head.output == output) {
+            head.enabled = enabled;
+        }
+    }
+
+    /// Set whether the head of an output uses adaptive sync
+    ///
+    /// Like the enabled state, this is updated automatically after
+    /// [`OutputManagementHandler::apply_configuration`] succeeded.
+    pub fn set_adaptive_sync(&mut self, output: &Output, enabled: bool) {
+        if let Some(head) = self.heads.iter_mut().find(|head| &head.output == output) {
+            head.adaptive_sync = enabled;
+        }
+    }
+
+    /// Returns whether the head of an output is enabled
+    ///
+    /// Returns `None` if the output isn't registered as a head.
+    pub fn head_enabled(&self, output: &Output) -> Option<bool> {
+        self.heads
+            .iter()
+            .find(|head| &head.output == output)
+            .map(|head| head.enabled)
+    }
+
+    /// Notify clients about changed heads
+    ///
+    /// This advertises newly added heads and sends all changes of the registered outputs
+    /// since the last call, followed by a new configuration serial.
+    pub fn update<D>(&mut self, dh: &DisplayHandle)
+    where
+        D: Dispatch<ZwlrOutputHeadV1, OutputHeadData> + Dispatch<ZwlrOutputModeV1, Mode> + 'static,
+    {
+        let mut changed = std::mem::take(&mut self.dirty);
+
+        for head in &mut self.heads {
+            let current = HeadState::new(head);
+            match head.advertised.take() {
+                None => {
+                    for manager in &self.managers {
+                        send_head::<D>(dh, manager, head, &current);
+                    }
+                    changed = true;
+                }
+                Some(previous) if previous != current => {
+                    for instance in &head.instances {
+                        update_head::<D>(dh, instance, &previous, &current);
+                    }
+                    changed = true;
+                }
+                Some(_) => {}
+            }
+            head.advertised = Some(current);
+        }
+
+        if changed {
+            self.serial = self.serial.wrapping_add(1);
+            for manager in &self.managers {
+                manager.done(self.serial);
+            }
+        }
+    }
+}
+
+/// Handler trait for the wlr-output-management protocol
+pub trait OutputManagementHandler {
+    /// [`OutputManagementState`] getter
+    fn output_management_state(&mut self) -> &mut OutputManagementState;
+
+    /// A client wants to know, whether a configuration could be applied
+    ///
+    /// The configuration contains every head known to the client.
+    /// Return `false` if the configuration is invalid or unsupported.
+    fn test_configuration(&mut self, config: Vec<(Output, OutputConfiguration)>) -> bool;
+
+    /// A client wants to apply a configuration
+    ///
+    /// The configuration contains every head known to the client.
+    /// Return `false` if it could not be applied, in which case any partially applied changes
+    /// should be reverted.
+    ///
+    /// On success the enabled and adaptive sync state of the heads is updated and
+    /// all changes are sent to clients, so make sure to change the state of the
+    /// outputs (e.g. using [`Output::change_current_state`]) before returning.
+    fn apply_configuration(&mut self, config: Vec<(Output, OutputConfiguration)>) -> bool;
+}
+
+/// Configuration of a single head requested by a client
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputConfiguration {
+    /// The head should be enabled
+    ///
+    /// Properties, that are `None`, were not changed by the client.
+    Enabled {
+        /// The requested mode
+        mode: Option<ModeConfiguration>,
+        /// The requested position in the global compositor space
+        position: Option<Point<i32, Logical>>,
+        /// The requested transform
+        transform: Option<Transform>,
+        /// The requested scale
+        scale: Option<f64>,
+        /// Whether adaptive sync should be enabled
+        adaptive_sync: Option<bool>,
+    },
+    /// The head should be disabled
+    Disabled,
+}
+
+/// Mode requested for a head
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeConfiguration {
+    /// One of the advertised modes of the output
+    Mode(Mode),
+    /// A custom mode
+    Custom {
+        /// The size of the mode, in pixels
+        size: Size<i32, Physical>,
+        /// The refresh rate in millihertz, `None` if unspecified
+        refresh: Option<i32>,
+    },
+}
+
+/// User data of a `zwlr_output_head_v1`
+#[derive(Debug)]
+pub struct OutputHeadData {
+    output: WeakOutput,
+    manager: ZwlrOutputManagerV1,
+    modes: Mutex<Vec<ZwlrOutputModeV1>>,
+}
+
+impl OutputHeadData {
+    fn mode(&self, mode: Mode) -> Option<ZwlrOutputModeV1> {
+        self.modes
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|instance| instance.data::<Mode>() == Some(&mode))
+            .cloned()
+    }
+}
+
+/// User data of a `zwlr_output_configuration_v1`
+#[derive(Debug)]
+pub struct OutputConfigurationData {
+    serial: u32,
+    manager: ZwlrOutputManagerV1,
+    inner: Mutex<ConfigurationInner>,
+}
+
+#[derive(Debug, Default)]
+struct ConfigurationInner {
+    used: bool,
+    /// Configured heads, with the configuration head object if the head is enabled
+    heads: Vec<(ZwlrOutputHeadV1, Option<ZwlrOutputConfigurationHeadV1>)>,
+}
+
+/// User data of a `zwlr_output_configuration_head_v1`
+#[derive(Debug)]
+pub struct OutputConfigurationHeadData {
+    head: ZwlrOutputHeadV1,
+    pending: Mutex<PendingHeadConfiguration>,
+}
+
+#[derive(Debug, Default)]
+struct PendingHeadConfiguration {
+    mode: Option<ModeConfiguration>,
+    position: Option<Point<i32, Logical>>,
+    transform: Option<Transform>,
+    scale: Option<f64>,
+    adaptive_sync: Option<bool>,
+}
+
+fn send_head<D>(dh: &DisplayHandle, manager: &ZwlrOutputManagerV1, head: &mut Head, state: &HeadState)
+where
+    D: Dispatch<ZwlrOutputHeadV1, OutputHeadData> + Dispatch<ZwlrOutputModeV1, Mode> + 'static,
+{
+    let client = match dh.get_client(manager.id()) {
+        Ok(client) => client,
+        Err(_) => return,
+    };
+    let data = OutputHeadData {
+        output: head.output.downgrade(),
+        manager: manager.clone(),
+        modes: Mutex::new(Vec::new()),
+    };
+    let instance = match client.create_resource::<ZwlrOutputHeadV1, _, D>(dh, manager.version(), data) {
+        Ok(instance) => instance,
+        Err(_) => return,
+    };
+    manager.head(&instance);
+
+    instance.name(head.output.name());
+    instance.description(head.output.description());
+    let physical = head.output.physical_properties();
+    if physical.size.w > 0 && physical.size.h > 0 {
+        instance.physical_size(physical.size.w, physical.size.h);
+    }
+    for mode in &state.modes {
+        send_mode::<D>(dh, &client, &instance, *mode, state.preferred_mode == Some(*mode));
+    }
+    instance.enabled(state.enabled as i32);
+    if state.enabled {
+        send_current_state(&instance, None, state);
+    }
+    if instance.version() >= 2 {
+        instance.make(physical.make);
+        instance.model(physical.model);
+    }
+    if instance.version() >= 4 {
+        instance.adaptive_sync(adaptive_sync_state(state.adaptive_sync));
+    }
+
+    head.instances.push(instance);
+}
+
+fn update_head<D>(dh: &DisplayHandle, instance: &ZwlrOutputHeadV1, previous: &HeadState, current: &HeadState)
+where
+    D: Dispatch<ZwlrOutputHeadV1, OutputHeadData> + Dispatch<ZwlrOutputModeV1, Mode> + 'static,
+{
+    let client = match dh.get_client(instance.id()) {
+        Ok(client) => client,
+        Err(_) => return,
+    };
+    let data = instance.data::<OutputHeadData>().unwrap();
+
+    data.modes.lock().unwrap().retain(|mode| {
+        let keep = mode
+            .data::<Mode>()
+            .map(|mode| current.modes.contains(mode))
+            .unwrap_or(false);
+        if !keep {
+            mode.finished();
+        }
+        keep
+    });
+    for mode in current
+        .modes
+        .iter()
+        .filter(|mode| !previous.modes.contains(*mode))
+    {
+        send_mode::<D>(
+            dh,
+            &client,
+            instance,
+            *mode,
+            current.preferred_mode == Some(*mode),
+        );
+    }
+    if previous.preferred_mode != current.preferred_mode {
+        // newly created modes were already marked as preferred
+        if let Some(mode) = current
+            .preferred_mode
+            .filter(|mode| previous.modes.contains(mode))
+            .and_then(|mode| data.mode(mode))
+        {
+            mode.preferred();
+        }
+    }
+
+    if previous.enabled != current.enabled {
+        instance.enabled(current.enabled as i32);
+    }
+    if current.enabled {
+        send_current_state(instance, Some(previous).filter(|state| state.enabled), current);
+    }
+    if instance.version() >= 4 && previous.adaptive_sync != current.adaptive_sync {
+        instance.adaptive_sync(adaptive_sync_state(current.adaptive_sync));
+    }
+}
+
+fn send_mode<D>(dh: &DisplayHandle, client: &Client, head: &ZwlrOutputHeadV1, mode: Mode, preferred: bool)
+where
+    D: Dispatch<ZwlrOutputModeV1, Mode> + 'static,
+{
+    let instance = match client.create_resource::<ZwlrOutputModeV1, _, D>(dh, head.version(), mode) {
+        Ok(instance) => instance,
+        Err(_) => return,
+    };
+    head.mode(&instance);
+
+    instance.size(mode.size.w, mode.size.h);
+    if mode.refresh > 0 {
+        instance.refresh(mode.refresh);
+    }
+    if preferred {
+        instance.preferred();
+    }
+
+    head.data::<OutputHeadData>()
+        .unwrap()
+        .modes
+        .lock()
+        .unwrap()
+        .push(instance);
+}
+
+/// Send the properties of an enabled head, that changed since `previous`
+fn send_current_state(instance: &ZwlrOutputHeadV1, previous: Option<&HeadState>, current: &HeadState) {
+    let data = instance.data::<OutputHeadData>().unwrap();
+
+    if previous
+        .map(|state| state.current_mode != current.current_mode)
+        .unwrap_or(true)
+    {
+        if let Some(mode) = current.current_mode.and_then(|mode| data.mode(mode)) {
+            instance.current_mode(&mode);
+        }
+    }
+    if previous
+        .map(|state| state.position != current.position)
+        .unwrap_or(true)
+    {
+        instance.position(current.position.x, current.position.y);
+    }
+    if previous
+        .map(|state| state.transform != current.transform)
+        .unwrap_or(true)
+    {
+        instance.transform(current.transform.into());
+    }
+    if previous.map(|state| state.scale != current.scale).unwrap_or(true) {
+        instance.scale(current.scale);
+    }
+}
+
+fn adaptive_sync_state(enabled: bool) -> AdaptiveSyncState {
+    if enabled {
+        AdaptiveSyncState::Enabled
+    } else {
+        AdaptiveSyncState::Disabled
+    }
+}
+
+/// Macro to delegate implementation of the wlr-output-management protocol to [`OutputManagementState`].
+///
+/// You must also implement [`OutputManagementHandler`] to use this.
+#[macro_export]
+macro_rules! delegate_output_management {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::output_management::v1::server::zwlr_output_manager_v1::ZwlrOutputManagerV1: $crate::wayland::output_management::OutputManagementGlobalData
+        ] => $crate::wayland::output_management::OutputManagementState);
+
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::output_management::v1::server::zwlr_output_manager_v1::ZwlrOutputManagerV1: ()
+        ] => $crate::wayland::output_management::OutputManagementState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::output_management::v1::server::zwlr_output_head_v1::ZwlrOutputHeadV1: $crate::wayland::output_management::OutputHeadData
+        ] => $crate::wayland::output_management::OutputManagementState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::output_management::v1::server::zwlr_output_mode_v1::ZwlrOutputModeV1: $crate::output::Mode
+        ] => $crate::wayland::output_management::OutputManagementState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::output_management::v1::server::zwlr_output_configuration_v1::ZwlrOutputConfigurationV1: $crate::wayland::output_management::OutputConfigurationData
+        ] => $crate::wayland::output_management::OutputManagementState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::output_management::v1::server::zwlr_output_configuration_head_v1::ZwlrOutputConfigurationHeadV1: $crate::wayland::output_management::OutputConfigurationHeadData
+        ] => $crate::wayland::output_management::OutputManagementState);
+    };
+}
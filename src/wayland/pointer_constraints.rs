@@ -0,0 +1,741 @@
+//! Utilities for pointer constraints support
+//!
+//! This protocol allows clients to lock the pointer in place or to confine it to a region
+//! of one of their surfaces. It is usually used together with the [relative pointer](super::relative_pointer)
+//! protocol, e.g. by games or 3D modelling tools.
+//!
+//! Constraints are created per surface and pointer. A constraint is activated once the pointer
+//! enters its region on the surface and deactivated once the surface loses the pointer focus, which
+//! is handled automatically when the focus of the [`PointerHandle`] is a [`WlSurface`](wayland_server::protocol::wl_surface::WlSurface).
+//! [`PointerConstraintsHandler::new_constraint`] is called for every new constraint and can be used
+//! to activate the constraint right away, if the surface is already focused.
+//!
+//! While a constraint is active:
+//!
+//! - A locked pointer does not receive absolute motion events anymore, but relative motion events are still
+//!   delivered. [`PointerHandle::motion`] keeps the pointer at its current location. The client may provide a
+//!   [hint](LockedPointer::cursor_position_hint) to where the cursor should be placed after the lock ends.
+//! - A confined pointer must not leave the [region](PointerConstraint::region) of the constraint.
+//!   [`PointerHandle::motion`] clamps the pointer location to the region.
+//!
+//! In both cases the pointer keeps its focus, so your compositor should read back the resulting location
+//! with [`PointerHandle::current_location`] after calling [`PointerHandle::motion`].
+//!
+//! The bounds of the constrained surface are taken from its
+//! [`RendererSurfaceState`](crate::backend::renderer::utils::RendererSurfaceState), so your compositor needs
+//! to call [`on_commit_buffer_handler`](crate::backend::renderer::utils::on_commit_buffer_handler)
+//! for the constrained surfaces.
+//!
+//! Use [`with_pointer_constraint`] to access the constraint of a surface.
+//!
+//! ```
+//! extern crate wayland_server;
+//! extern crate smithay;
+//!
+//! use smithay::delegate_pointer_constraints;
+//! use smithay::input::{pointer::PointerHandle, SeatHandler, SeatState};
+//! use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+//! use smithay::wayland::pointer_constraints::{
+//!     with_pointer_constraint, PointerConstraintsHandler, PointerConstraintsState,
+//! };
+//!
+//! # struct State {
+//! #     seat_state: SeatState<Self>,
+//! # };
+//! # impl SeatHandler for State {
+//! #     type KeyboardFocus = WlSurface;
+//! #     type PointerFocus = WlSurface;
+//! #
+//! #     fn seat_state(&mut self) -> &mut SeatState<Self> {
+//! #         &mut self.seat_state
+//! #     }
+//! # }
+//! impl PointerConstraintsHandler for State {
+//!     fn new_constraint(&mut self, surface: &WlSurface, pointer: &PointerHandle<Self>) {
+//!         if pointer.current_focus().as_ref() == Some(surface) {
+//!             with_pointer_constraint(surface, pointer, |constraint| {
+//!                 if let Some(constraint) = constraint {
+//!                     constraint.activate();
+//!                 }
+//!             });
+//!         }
+//!     }
+//! }
+//!
+//! # let mut display = wayland_server::Display::<State>::new().unwrap();
+//! let state = PointerConstraintsState::new::<State>(&display.handle());
+//!
+//! delegate_pointer_constraints!(State);
+//! ```
+
+use std::{fmt, sync::Mutex};
+
+use wayland_protocols::wp::pointer_constraints::zv1::server::{
+    zwp_confined_pointer_v1::{self, ZwpConfinedPointerV1},
+    zwp_locked_pointer_v1::{self, ZwpLockedPointerV1},
+    zwp_pointer_constraints_v1::{self, Lifetime, ZwpPointerConstraintsV1},
+};
+use wayland_server::{
+    backend::{protocol::ProtocolError, ClientId, DisconnectReason, GlobalId, ObjectId},
+    protocol::{wl_display, wl_region::WlRegion, wl_surface::WlSurface},
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource, WEnum,
+};
+
+use crate::{
+    backend::renderer::utils::RendererSurfaceStateUserData,
+    input::{
+        pointer::{MotionEvent, PointerHandle},
+        SeatHandler,
+    },
+    utils::{IsAlive, Logical, Point, Rectangle, Size},
+    wayland::{
+        compositor::{self, RegionAttributes},
+        seat::PointerUserData,
+    },
+};
+
+const MANAGER_VERSION: u32 = 1;
+
+/// Handler trait for the pointer constraints protocol
+pub trait PointerConstraintsHandler: SeatHandler {
+    /// A client created a new constraint for the given surface and pointer
+    ///
+    /// Use [`with_pointer_constraint`] to access the constraint.
+    fn new_constraint(&mut self, surface: &WlSurface, pointer: &PointerHandle<Self>);
+}
+
+/// The resource of a pointer constraint
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ConstraintResource {
+    Locked(ZwpLockedPointerV1),
+    Confined(ZwpConfinedPointerV1),
+}
+
+/// Constraint of a pointer, either locking or confining it
+#[derive(Debug)]
+pub enum PointerConstraint {
+    /// The pointer is locked in place
+    Locked(LockedPointer),
+    /// The pointer is confined to a region
+    Confined(ConfinedPointer),
+}
+
+/// State shared between locked and confined pointers
+#[derive(Debug)]
+struct ConstraintState {
+    resource: ConstraintResource,
+    /// Size of the constrained surface, updated whenever the constraint is accessed
+    surface_size: Option<Size<i32, Logical>>,
+    region: Option<RegionAttributes>,
+    pending_region: Option<Option<RegionAttributes>>,
+    lifetime: Lifetime,
+    active: bool,
+    defunct: bool,
+}
+
+/// A locked pointer
+#[derive(Debug)]
+pub struct LockedPointer {
+    state: ConstraintState,
+    cursor_position_hint: Option<Point<f64, Logical>>,
+    pending_cursor_position_hint: Option<Point<f64, Logical>>,
+}
+
+impl LockedPointer {
+    /// The underlying `zwp_locked_pointer_v1` object
+    pub fn wl_locked_pointer(&self) -> &ZwpLockedPointerV1 {
+        match &self.state.resource {
+            ConstraintResource::Locked(locked) => locked,
+            ConstraintResource::Confined(_) => unreachable!(),
+        }
+    }
+
+    /// Location in surface-local coordinates, where the client expects the cursor to be
+    /// placed after the lock ends
+    pub fn cursor_position_hint(&self) -> Option<Point<f64, Logical>> {
+        self.cursor_position_hint
+    }
+}
+
+/// A confined pointer
+#[derive(Debug)]
+pub struct ConfinedPointer {
+    state: ConstraintState,
+}
+
+impl ConfinedPointer {
+    /// The underlying `zwp_confined_pointer_v1` object
+    pub fn wl_confined_pointer(&self) -> &ZwpConfinedPointerV1 {
+        match &self.state.resource {
+            ConstraintResource::Confined(confined) => confined,
+            ConstraintResource::Locked(_) => unreachable!(),
+        }
+    }
+}
+
+impl PointerConstraint {
+    fn state(&self) -> &ConstraintState {
+        match self {
+            PointerConstraint::Locked(locked) => &locked.state,
+            PointerConstraint::Confined(confined) => &confined.state,
+        }
+    }
+
+    fn state_mut(&mut self) -> &mut ConstraintState {
+        match self {
+            PointerConstraint::Locked(locked) => &mut locked.state,
+            PointerConstraint::Confined(confined) => &mut confined.state,
+        }
+    }
+
+    /// Returns whether the constraint is currently active
+    pub fn is_active(&self) -> bool {
+        self.state().active
+    }
+
+    /// The region of the constraint in surface-local coordinates
+    ///
+    /// `None` means the whole surface.
+    pub fn region(&self) -> Option<&RegionAttributes> {
+        self.state().region.as_ref()
+    }
+
+    /// Returns whether the given surface-local location is inside the region of the constraint
+    ///
+    /// The region is always limited to the bounds of the surface.
+    pub fn contains(&self, location: Point<f64, Logical>) -> bool {
+        let location = location.to_i32_floor();
+        let in_surface = self
+            .state()
+            .surface_size
+            .map(|size| Rectangle::from_loc_and_size((0, 0), size).contains(location))
+            .unwrap_or(false);
+        in_surface
+            && self
+                .region()
+                .map(|region| region.contains(location))
+                .unwrap_or(true)
+    }
+
+    /// The lifetime of this constraint
+    ///
+    /// A [`Lifetime::Oneshot`] constraint can not be activated again after it was deactivated.
+    pub fn lifetime(&self) -> Lifetime {
+        self.state().lifetime
+    }
+
+    /// Activate the constraint
+    ///
+    /// Does nothing, if the constraint is already active or a oneshot constraint
+    /// was already deactivated.
+    pub fn activate(&mut self) {
+        let state = self.state_mut();
+        if state.active || state.defunct {
+            return;
+        }
+        state.active = true;
+        match &state.resource {
+            ConstraintResource::Locked(locked) => locked.locked(),
+            ConstraintResource::Confined(confined) => confined.confined(),
+        }
+    }
+
+    /// Deactivate the constraint
+    ///
+    /// Does nothing, if the constraint is not active.
+    pub fn deactivate(&mut self) {
+        let state = self.state_mut();
+        if !state.active {
+            return;
+        }
+        state.active = false;
+        if state.lifetime == Lifetime::Oneshot {
+            state.defunct = true;
+        }
+        match &state.resource {
+            ConstraintResource::Locked(locked) => locked.unlocked(),
+            ConstraintResource::Confined(confined) => confined.unconfined(),
+        }
+    }
+
+    fn commit(&mut self) {
+        if let PointerConstraint::Locked(locked) = self {
+            if let Some(hint) = locked.pending_cursor_position_hint.take() {
+                locked.cursor_position_hint = Some(hint);
+            }
+        }
+        let state = self.state_mut();
+        if let Some(region) = state.pending_region.take() {
+            state.region = region;
+        }
+    }
+}
+
+/// Constraints of a surface, stored in its data map
+struct PointerConstraintData<D: SeatHandler> {
+    constraints: Vec<(PointerHandle<D>, PointerConstraint)>,
+}
+
+impl<D: SeatHandler> PointerConstraintData<D> {
+    fn get_mut(&mut self, pointer: &PointerHandle<D>) -> Option<&mut PointerConstraint> {
+        self.constraints
+            .iter_mut()
+            .find(|(handle, _)| handle == pointer)
+            .map(|(_, constraint)| constraint)
+    }
+
+    fn get_by_resource(&mut self, resource: &ConstraintResource) -> Option<&mut PointerConstraint> {
+        self.constraints
+            .iter_mut()
+            .map(|(_, constraint)| constraint)
+            .find(|constraint| &constraint.state().resource == resource)
+    }
+}
+
+/// Access the constraint of the given pointer on a surface, if any
+pub fn with_pointer_constraint<D, T, F>(surface: &WlSurface, pointer: &PointerHandle<D>, f: F) -> T
+where
+    D: SeatHandler + 'static,
+    F: FnOnce(Option<&mut PointerConstraint>) -> T,
+{
+    compositor::with_states(surface, |states| {
+        let data = match states.data_map.get::<Mutex<PointerConstraintData<D>>>() {
+            Some(data) => data,
+            None => return f(None),
+        };
+        let mut data = data.lock().unwrap();
+        let mut constraint = data.get_mut(pointer);
+        if let Some(constraint) = constraint.as_mut() {
+            let surface_size = states
+                .data_map
+                .get::<RendererSurfaceStateUserData>()
+                .and_then(|state| state.borrow().surface_size());
+            constraint.state_mut().surface_size = surface_size;
+        }
+        f(constraint)
+    })
+}
+
+/// Surface focused by a [`PointerHandle`], used to apply its constraint to motion events
+#[derive(Debug, Default)]
+pub(crate) struct ConstraintFocus {
+    /// Location of the last motion event in the global compositor space
+    location: Point<f64, Logical>,
+    /// The focused surface and the location of its origin in the global compositor space
+    surface: Option<(WlSurface, Point<f64, Logical>)>,
+}
+
+/// Tracks the surface focused by the pointer
+///
+/// `location` is the surface-local location of the current motion event,
+/// or `None` if the surface lost the pointer focus.
+pub(crate) fn update_constraint_focus<D: SeatHandler + 'static>(
+    pointer: &PointerHandle<D>,
+    surface: &WlSurface,
+    location: Option<Point<f64, Logical>>,
+) {
+    let mut constraint_focus = pointer.constraint_focus.lock().unwrap();
+    match location {
+        Some(location) => {
+            let origin = constraint_focus.location - location;
+            constraint_focus.surface = Some((surface.clone(), origin));
+        }
+        None => {
+            if constraint_focus
+                .surface
+                .as_ref()
+                .map(|(focused, _)| focused == surface)
+                .unwrap_or(false)
+            {
+                constraint_focus.surface = None;
+            }
+        }
+    }
+}
+
+/// Applies the active constraint of the pointer to a motion event
+///
+/// A locked pointer keeps its current location, a confined pointer is kept inside the region
+/// of the constraint. In both cases the pointer keeps its current focus.
+#[allow(clippy::type_complexity)]
+pub(crate) fn constrain_motion<D: SeatHandler + 'static>(
+    pointer: &PointerHandle<D>,
+    current_location: Point<f64, Logical>,
+    current_focus: &Option<(<D as SeatHandler>::PointerFocus, Point<i32, Logical>)>,
+    focus: Option<(<D as SeatHandler>::PointerFocus, Point<i32, Logical>)>,
+    event: &MotionEvent,
+) -> (
+    Option<(<D as SeatHandler>::PointerFocus, Point<i32, Logical>)>,
+    MotionEvent,
+) {
+    let focused = pointer.constraint_focus.lock().unwrap().surface.clone();
+    let constrained_location = focused.and_then(|(surface, origin)| {
+        if !surface.alive() {
+            return None;
+        }
+        with_pointer_constraint(&surface, pointer, |constraint| {
+            let constraint = constraint.filter(|constraint| constraint.is_active())?;
+            match constraint {
+                PointerConstraint::Locked(_) => Some(current_location),
+                PointerConstraint::Confined(_) => {
+                    // slide along the border of the region, if the pointer would leave it
+                    let candidates: [Point<f64, Logical>; 3] = [
+                        event.location,
+                        (event.location.x, current_location.y).into(),
+                        (current_location.x, event.location.y).into(),
+                    ];
+                    Some(
+                        candidates
+                            .into_iter()
+                            .find(|location| constraint.contains(*location - origin))
+                            .unwrap_or(current_location),
+                    )
+                }
+            }
+        })
+    });
+
+    let mut event = event.clone();
+    let mut constraint_focus = pointer.constraint_focus.lock().unwrap();
+    match constrained_location {
+        Some(location) => {
+            event.location = location;
+            constraint_focus.location = location;
+            (current_focus.clone(), event)
+        }
+        None => {
+            constraint_focus.location = event.location;
+            (focus, event)
+        }
+    }
+}
+
+fn commit_hook<D: SeatHandler + 'static>(_dh: &DisplayHandle, surface: &WlSurface) {
+    compositor::with_states(surface, |states| {
+        if let Some(data) = states.data_map.get::<Mutex<PointerConstraintData<D>>>() {
+            for (_, constraint) in data.lock().unwrap().constraints.iter_mut() {
+                constraint.commit();
+            }
+        }
+    });
+}
+
+/// User data of `zwp_locked_pointer_v1` and `zwp_confined_pointer_v1` objects
+pub struct PointerConstraintUserData<D: SeatHandler> {
+    surface: WlSurface,
+    handle: Option<PointerHandle<D>>,
+}
+
+impl<D: SeatHandler> fmt::Debug for PointerConstraintUserData<D>
+where
+    <D as SeatHandler>::PointerFocus: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PointerConstraintUserData")
+            .field("surface", &self.surface)
+            .field("handle", &self.handle)
+            .finish()
+    }
+}
+
+impl<D: SeatHandler + 'static> PointerConstraintUserData<D> {
+    fn with_constraint<T>(
+        &self,
+        resource: &ConstraintResource,
+        f: impl FnOnce(&mut PointerConstraint) -> T,
+    ) -> Option<T> {
+        compositor::with_states(&self.surface, |states| {
+            let data = states.data_map.get::<Mutex<PointerConstraintData<D>>>()?;
+            let mut data = data.lock().unwrap();
+            data.get_by_resource(resource).map(f)
+        })
+    }
+
+    fn destroy(&self, id: &ObjectId) {
+        if !self.surface.is_alive() {
+            return;
+        }
+        compositor::with_states(&self.surface, |states| {
+            if let Some(data) = states.data_map.get::<Mutex<PointerConstraintData<D>>>() {
+                data.lock().unwrap().constraints.retain(|(_, constraint)| {
+                    let resource_id = match &constraint.state().resource {
+                        ConstraintResource::Locked(locked) => locked.id(),
+                        ConstraintResource::Confined(confined) => confined.id(),
+                    };
+                    &resource_id != id
+                });
+            }
+        });
+    }
+}
+
+/// State of the pointer constraints global
+#[derive(Debug)]
+pub struct PointerConstraintsState {
+    global: GlobalId,
+}
+
+impl PointerConstraintsState {
+    /// Register new [ZwpPointerConstraintsV1] global
+    pub fn new<D>(display: &DisplayHandle) -> Self
+    where
+        D: GlobalDispatch<ZwpPointerConstraintsV1, ()>,
+        D: Dispatch<ZwpPointerConstraintsV1, ()>,
+        D: Dispatch<ZwpLockedPointerV1, PointerConstraintUserData<D>>,
+        D: Dispatch<ZwpConfinedPointerV1, PointerConstraintUserData<D>>,
+        D: PointerConstraintsHandler,
+        D: 'static,
+    {
+        let global = display.create_global::<D, ZwpPointerConstraintsV1, _>(MANAGER_VERSION, ());
+
+        Self { global }
+    }
+
+    /// [ZwpPointerConstraintsV1] GlobalId getter
+    pub fn global(&self) -> GlobalId {
+        self.global.clone()
+    }
+}
+
+impl<D> GlobalDispatch<ZwpPointerConstraintsV1, (), D> for PointerConstraintsState
+where
+    D: GlobalDispatch<ZwpPointerConstraintsV1, ()>
+        + Dispatch<ZwpPointerConstraintsV1, ()>
+        + Dispatch<ZwpLockedPointerV1, PointerConstraintUserData<D>>
+        + Dispatch<ZwpConfinedPointerV1, PointerConstraintUserData<D>>
+        + PointerConstraintsHandler
+        + 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _dh: &DisplayHandle,
+        _client: &Client,
+        resource: New<ZwpPointerConstraintsV1>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl<D> Dispatch<ZwpPointerConstraintsV1, (), D> for PointerConstraintsState
+where
+    D: Dispatch<ZwpPointerConstraintsV1, ()>,
+    D: Dispatch<ZwpLockedPointerV1, PointerConstraintUserData<D>>,
+    D: Dispatch<ZwpConfinedPointerV1, PointerConstraintUserData<D>>,
+    D: PointerConstraintsHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        client: &Client,
+        constraints: &ZwpPointerConstraintsV1,
+        request: zwp_pointer_constraints_v1::Request,
+        _data: &(),
+        dh: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let (surface, pointer, region, lifetime) = match &request {
+            zwp_pointer_constraints_v1::Request::LockPointer {
+                surface,
+                pointer,
+                region,
+                lifetime,
+                ..
+            }
+            | zwp_pointer_constraints_v1::Request::ConfinePointer {
+                surface,
+                pointer,
+                region,
+                lifetime,
+                ..
+            } => (surface.clone(), pointer, region, lifetime),
+            zwp_pointer_constraints_v1::Request::Destroy => return,
+            _ => unreachable!(),
+        };
+
+        let lifetime = match lifetime {
+            WEnum::Value(lifetime) => *lifetime,
+            WEnum::Unknown(lifetime) => {
+                // the protocol has no error for this, so treat it like any other invalid argument
+                dh.backend_handle().kill_client(
+                    client.id(),
+                    DisconnectReason::ProtocolError(ProtocolError {
+                        code: wl_display::Error::InvalidMethod.into(),
+                        object_id: constraints.id().protocol_id(),
+                        object_interface: ZwpPointerConstraintsV1::interface().name.to_string(),
+                        message: format!("invalid constraint lifetime {}", lifetime),
+                    }),
+                );
+                return;
+            }
+        };
+        let region = region.as_ref().map(compositor::get_region_attributes);
+        let handle = pointer.data::<PointerUserData<D>>().unwrap().handle.clone();
+
+        if let Some(handle) = handle.as_ref() {
+            if with_pointer_constraint(&surface, handle, |constraint| constraint.is_some()) {
+                constraints.post_error(
+                    zwp_pointer_constraints_v1::Error::AlreadyConstrained,
+                    "The surface is already constrained for this pointer",
+                );
+                return;
+            }
+        }
+
+        let user_data = PointerConstraintUserData {
+            surface: surface.clone(),
+            handle: handle.clone(),
+        };
+        let resource = match request {
+            zwp_pointer_constraints_v1::Request::LockPointer { id, .. } => {
+                ConstraintResource::Locked(data_init.init(id, user_data))
+            }
+            zwp_pointer_constraints_v1::Request::ConfinePointer { id, .. } => {
+                ConstraintResource::Confined(data_init.init(id, user_data))
+            }
+            _ => unreachable!(),
+        };
+
+        // a constraint on a pointer without a handle can never be activated
+        let handle = match handle {
+            Some(handle) => handle,
+            None => return,
+        };
+
+        let constraint_state = ConstraintState {
+            resource: resource.clone(),
+            surface_size: None,
+            region,
+            pending_region: None,
+            lifetime,
+            active: false,
+            defunct: false,
+        };
+        let constraint = match resource {
+            ConstraintResource::Locked(_) => PointerConstraint::Locked(LockedPointer {
+                state: constraint_state,
+                cursor_position_hint: None,
+                pending_cursor_position_hint: None,
+            }),
+            ConstraintResource::Confined(_) => PointerConstraint::Confined(ConfinedPointer {
+                state: constraint_state,
+            }),
+        };
+
+        compositor::with_states(&surface, |states| {
+            let inserted = states.data_map.insert_if_missing_threadsafe(|| {
+                Mutex::new(PointerConstraintData::<D> {
+                    constraints: Vec::new(),
+                })
+            });
+            if inserted {
+                compositor::add_pre_commit_hook(&surface, commit_hook::<D>);
+            }
+            states
+                .data_map
+                .get::<Mutex<PointerConstraintData<D>>>()
+                .unwrap()
+                .lock()
+                .unwrap()
+                .constraints
+                .push((handle.clone(), constraint));
+        });
+
+        state.new_constraint(&surface, &handle);
+    }
+}
+
+fn pending_region(region: Option<WlRegion>) -> Option<Option<RegionAttributes>> {
+    Some(region.as_ref().map(compositor::get_region_attributes))
+}
+
+impl<D> Dispatch<ZwpLockedPointerV1, PointerConstraintUserData<D>, D> for PointerConstraintsState
+where
+    D: Dispatch<ZwpLockedPointerV1, PointerConstraintUserData<D>>,
+    D: SeatHandler,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        locked_pointer: &ZwpLockedPointerV1,
+        request: zwp_locked_pointer_v1::Request,
+        data: &PointerConstraintUserData<D>,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        let resource = ConstraintResource::Locked(locked_pointer.clone());
+        match request {
+            zwp_locked_pointer_v1::Request::SetCursorPositionHint { surface_x, surface_y } => {
+                data.with_constraint(&resource, |constraint| {
+                    if let PointerConstraint::Locked(locked) = constraint {
+                        locked.pending_cursor_position_hint = Some((surface_x, surface_y).into());
+                    }
+                });
+            }
+            zwp_locked_pointer_v1::Request::SetRegion { region } => {
+                data.with_constraint(&resource, |constraint| {
+                    constraint.state_mut().pending_region = pending_region(region);
+                });
+            }
+            zwp_locked_pointer_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(_state: &mut D, _: ClientId, object_id: ObjectId, data: &PointerConstraintUserData<D>) {
+        data.destroy(&object_id);
+    }
+}
+
+impl<D> Dispatch<ZwpConfinedPointerV1, PointerConstraintUserData<D>, D> for PointerConstraintsState
+where
+    D: Dispatch<ZwpConfinedPointerV1, PointerConstraintUserData<D>>,
+    D: SeatHandler,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        confined_pointer: &ZwpConfinedPointerV1,
+        request: zwp_confined_pointer_v1::Request,
+        data: &PointerConstraintUserData<D>,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        let resource = ConstraintResource::Confined(confined_pointer.clone());
+        match request {
+            zwp_confined_pointer_v1::Request::SetRegion { region } => {
+                data.with_constraint(&resource, |constraint| {
+                    constraint.state_mut().pending_region = pending_region(region);
+                });
+            }
+            zwp_confined_pointer_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(_state: &mut D, _: ClientId, object_id: ObjectId, data: &PointerConstraintUserData<D>) {
+        data.destroy(&object_id);
+    }
+}
+
+/// Macro to delegate implementation of the pointer constraints protocol
+#[macro_export]
+macro_rules! delegate_pointer_constraints {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::pointer_constraints::zv1::server::zwp_pointer_constraints_v1::ZwpPointerConstraintsV1: ()
+        ] => $crate::wayland::pointer_constraints::PointerConstraintsState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::pointer_constraints::zv1::server::zwp_pointer_constraints_v1::ZwpPointerConstraintsV1: ()
+        ] => $crate::wayland::pointer_constraints::PointerConstraintsState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::pointer_constraints::zv1::server::zwp_locked_pointer_v1::ZwpLockedPointerV1: $crate::wayland::pointer_constraints::PointerConstraintUserData<Self>
+        ] => $crate::wayland::pointer_constraints::PointerConstraintsState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::pointer_constraints::zv1::server::zwp_confined_pointer_v1::ZwpConfinedPointerV1: $crate::wayland::pointer_constraints::PointerConstraintUserData<Self>
+        ] => $crate::wayland::pointer_constraints::PointerConstraintsState);
+    };
+}
@@ -0,0 +1,295 @@
+//! Utilities for pointer gestures support
+//!
+//! This protocol allows clients to receive touchpad gestures (swipe, pinch and hold).
+//!
+//! Gestures are forwarded to clients by calling the gesture methods of the [`PointerHandle`], e.g.
+//! [`PointerHandle::gesture_swipe_begin`], which will send the events to any gesture objects
+//! the focused client created for this pointer. Gesture events are usually generated by
+//! the [`InputEvent::GestureSwipeBegin`](crate::backend::input::InputEvent::GestureSwipeBegin)
+//! and related events of your input backend.
+//!
+//! ```
+//! extern crate wayland_server;
+//! extern crate smithay;
+//!
+//! use smithay::delegate_pointer_gestures;
+//! use smithay::input::{SeatHandler, SeatState};
+//! use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+//! use smithay::wayland::pointer_gestures::PointerGesturesState;
+//!
+//! # struct State {
+//! #     seat_state: SeatState<Self>,
+//! # };
+//! # impl SeatHandler for State {
+//! #     type KeyboardFocus = WlSurface;
+//! #     type PointerFocus = WlSurface;
+//! #
+//! #     fn seat_state(&mut self) -> &mut SeatState<Self> {
+//! #         &mut self.seat_state
+//! #     }
+//! # }
+//! # let mut display = wayland_server::Display::<State>::new().unwrap();
+//! let state = PointerGesturesState::new::<State>(&display.handle());
+//!
+//! delegate_pointer_gestures!(State);
+//! ```
+
+use std::fmt;
+
+use wayland_protocols::wp::pointer_gestures::zv1::server::{
+    zwp_pointer_gesture_hold_v1::{self, ZwpPointerGestureHoldV1},
+    zwp_pointer_gesture_pinch_v1::{self, ZwpPointerGesturePinchV1},
+    zwp_pointer_gesture_swipe_v1::{self, ZwpPointerGestureSwipeV1},
+    zwp_pointer_gestures_v1::{self, ZwpPointerGesturesV1},
+};
+use wayland_server::{
+    backend::{ClientId, GlobalId, ObjectId},
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+};
+
+use crate::{
+    input::{pointer::PointerHandle, SeatHandler},
+    wayland::seat::PointerUserData,
+};
+
+const MANAGER_VERSION: u32 = 3;
+
+/// User data of the gesture objects
+pub struct PointerGestureUserData<D: SeatHandler> {
+    handle: Option<PointerHandle<D>>,
+}
+
+impl<D: SeatHandler> fmt::Debug for PointerGestureUserData<D>
+where
+    <D as SeatHandler>::PointerFocus: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PointerGestureUserData")
+            .field("handle", &self.handle)
+            .finish()
+    }
+}
+
+/// State of the pointer gestures
+#[derive(Debug)]
+pub struct PointerGesturesState {
+    global: GlobalId,
+}
+
+impl PointerGesturesState {
+    /// Register new [ZwpPointerGesturesV1] global
+    pub fn new<D>(display: &DisplayHandle) -> Self
+    where
+        D: GlobalDispatch<ZwpPointerGesturesV1, ()>,
+        D: Dispatch<ZwpPointerGesturesV1, ()>,
+        D: Dispatch<ZwpPointerGestureSwipeV1, PointerGestureUserData<D>>,
+        D: Dispatch<ZwpPointerGesturePinchV1, PointerGestureUserData<D>>,
+        D: Dispatch<ZwpPointerGestureHoldV1, PointerGestureUserData<D>>,
+        D: SeatHandler,
+        D: 'static,
+    {
+        let global = display.create_global::<D, ZwpPointerGesturesV1, _>(MANAGER_VERSION, ());
+
+        Self { global }
+    }
+
+    /// [ZwpPointerGesturesV1] GlobalId getter
+    pub fn global(&self) -> GlobalId {
+        self.global.clone()
+    }
+}
+
+impl<D> GlobalDispatch<ZwpPointerGesturesV1, (), D> for PointerGesturesState
+where
+    D: GlobalDispatch<ZwpPointerGesturesV1, ()>
+        + Dispatch<ZwpPointerGesturesV1, ()>
+        + Dispatch<ZwpPointerGestureSwipeV1, PointerGestureUserData<D>>
+        + Dispatch<ZwpPointerGesturePinchV1, PointerGestureUserData<D>>
+        + Dispatch<ZwpPointerGestureHoldV1, PointerGestureUserData<D>>
+        + SeatHandler
+        + 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _dh: &DisplayHandle,
+        _client: &Client,
+        resource: New<ZwpPointerGesturesV1>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl<D> Dispatch<ZwpPointerGesturesV1, (), D> for PointerGesturesState
+where
+    D: Dispatch<ZwpPointerGesturesV1, ()>,
+    D: Dispatch<ZwpPointerGestureSwipeV1, PointerGestureUserData<D>>,
+    D: Dispatch<ZwpPointerGesturePinchV1, PointerGestureUserData<D>>,
+    D: Dispatch<ZwpPointerGestureHoldV1, PointerGestureUserData<D>>,
+    D: SeatHandler,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _pointer_gestures: &ZwpPointerGesturesV1,
+        request: zwp_pointer_gestures_v1::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_pointer_gestures_v1::Request::GetSwipeGesture { id, pointer } => {
+                let handle = &pointer.data::<PointerUserData<D>>().unwrap().handle;
+                let user_data = PointerGestureUserData {
+                    handle: handle.clone(),
+                };
+                let gesture = data_init.init(id, user_data);
+                if let Some(handle) = handle {
+                    handle.known_swipe_gestures.lock().unwrap().push(gesture);
+                }
+            }
+            zwp_pointer_gestures_v1::Request::GetPinchGesture { id, pointer } => {
+                let handle = &pointer.data::<PointerUserData<D>>().unwrap().handle;
+                let user_data = PointerGestureUserData {
+                    handle: handle.clone(),
+                };
+                let gesture = data_init.init(id, user_data);
+                if let Some(handle) = handle {
+                    handle.known_pinch_gestures.lock().unwrap().push(gesture);
+                }
+            }
+            zwp_pointer_gestures_v1::Request::GetHoldGesture { id, pointer } => {
+                let handle = &pointer.data::<PointerUserData<D>>().unwrap().handle;
+                let user_data = PointerGestureUserData {
+                    handle: handle.clone(),
+                };
+                let gesture = data_init.init(id, user_data);
+                if let Some(handle) = handle {
+                    handle.known_hold_gestures.lock().unwrap().push(gesture);
+                }
+            }
+            zwp_pointer_gestures_v1::Request::Release => {}
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ZwpPointerGestureSwipeV1, PointerGestureUserData<D>, D> for PointerGesturesState
+where
+    D: Dispatch<ZwpPointerGestureSwipeV1, PointerGestureUserData<D>>,
+    D: SeatHandler,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _gesture: &ZwpPointerGestureSwipeV1,
+        request: zwp_pointer_gesture_swipe_v1::Request,
+        _data: &PointerGestureUserData<D>,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_pointer_gesture_swipe_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(_state: &mut D, _: ClientId, object_id: ObjectId, data: &PointerGestureUserData<D>) {
+        if let Some(ref handle) = data.handle {
+            handle
+                .known_swipe_gestures
+                .lock()
+                .unwrap()
+                .retain(|p| p.id() != object_id);
+        }
+    }
+}
+
+impl<D> Dispatch<ZwpPointerGesturePinchV1, PointerGestureUserData<D>, D> for PointerGesturesState
+where
+    D: Dispatch<ZwpPointerGesturePinchV1, PointerGestureUserData<D>>,
+    D: SeatHandler,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _gesture: &ZwpPointerGesturePinchV1,
+        request: zwp_pointer_gesture_pinch_v1::Request,
+        _data: &PointerGestureUserData<D>,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_pointer_gesture_pinch_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(_state: &mut D, _: ClientId, object_id: ObjectId, data: &PointerGestureUserData<D>) {
+        if let Some(ref handle) = data.handle {
+            handle
+                .known_pinch_gestures
+                .lock()
+                .unwrap()
+                .retain(|p| p.id() != object_id);
+        }
+    }
+}
+
+impl<D> Dispatch<ZwpPointerGestureHoldV1, PointerGestureUserData<D>, D> for PointerGesturesState
+where
+    D: Dispatch<ZwpPointerGestureHoldV1, PointerGestureUserData<D>>,
+    D: SeatHandler,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _gesture: &ZwpPointerGestureHoldV1,
+        request: zwp_pointer_gesture_hold_v1::Request,
+        _data: &PointerGestureUserData<D>,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_pointer_gesture_hold_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(_state: &mut D, _: ClientId, object_id: ObjectId, data: &PointerGestureUserData<D>) {
+        if let Some(ref handle) = data.handle {
+            handle
+                .known_hold_gestures
+                .lock()
+                .unwrap()
+                .retain(|p| p.id() != object_id);
+        }
+    }
+}
+
+/// Macro to delegate implementation of the pointer gestures protocol
+#[macro_export]
+macro_rules! delegate_pointer_gestures {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::pointer_gestures::zv1::server::zwp_pointer_gestures_v1::ZwpPointerGesturesV1: ()
+        ] => $crate::wayland::pointer_gestures::PointerGesturesState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::pointer_gestures::zv1::server::zwp_pointer_gestures_v1::ZwpPointerGesturesV1: ()
+        ] => $crate::wayland::pointer_gestures::PointerGesturesState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::pointer_gestures::zv1::server::zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1: $crate::wayland::pointer_gestures::PointerGestureUserData<Self>
+        ] => $crate::wayland::pointer_gestures::PointerGesturesState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::pointer_gestures::zv1::server::zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1: $crate::wayland::pointer_gestures::PointerGestureUserData<Self>
+        ] => $crate::wayland::pointer_gestures::PointerGesturesState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::pointer_gestures::zv1::server::zwp_pointer_gesture_hold_v1::ZwpPointerGestureHoldV1: $crate::wayland::pointer_gestures::PointerGestureUserData<Self>
+        ] => $crate::wayland::pointer_gestures::PointerGesturesState);
+    };
+}
@@ -15,6 +15,8 @@
 //!
 //! - the freestanding function [`set_primary_selection`]
 //!   allows you to set the contents of the selection for your clients
+//! - the freestanding function [`request_primary_client_selection`] allows you to read
+//!   the contents of a selection set by a client
 //! - the `PrimarySelectionHandle` gives you the option to inspect new selections
 //!   by overriding [`PrimarySelectionHandler::new_selection].
 //!
@@ -59,7 +61,10 @@
 //! // You're now ready to go!
 //! ```
 
-use std::{cell::RefCell, os::unix::io::OwnedFd};
+use std::{
+    cell::RefCell,
+    os::unix::io::{AsRawFd, OwnedFd},
+};
 
 use wayland_protocols::wp::primary_selection::zv1::server::{
     zwp_primary_selection_device_manager_v1::ZwpPrimarySelectionDeviceManagerV1 as PrimaryDeviceManager,
@@ -67,15 +72,21 @@ use wayland_protocols::wp::primary_selection::zv1::server::{
 };
 use wayland_server::{backend::GlobalId, Client, DisplayHandle, GlobalDispatch};
 
-use crate::input::{Seat, SeatHandler};
+use crate::{
+    input::{Seat, SeatHandler},
+    utils::IsAlive,
+    wayland::data_control::with_source_metadata as with_data_control_source_metadata,
+};
 
 mod device;
-mod seat_data;
+pub(crate) mod seat_data;
 mod source;
 
 pub use device::PrimaryDeviceUserData;
 pub use source::{with_source_metadata, PrimarySourceUserData, SourceMetadata};
 
+pub use crate::wayland::data_device::SelectionRequestError;
+
 use seat_data::{SeatData, Selection};
 
 /// Events that are generated by interactions of the clients with the data device
@@ -152,6 +163,65 @@ where
         .set_selection::<D>(dh, Selection::Compositor(SourceMetadata { mime_types }));
 }
 
+/// Clear the current primary selection for this seat
+pub fn clear_primary_selection<D>(dh: &DisplayHandle, seat: &Seat<D>)
+where
+    D: SeatHandler + PrimarySelectionHandler + 'static,
+{
+    seat.user_data()
+        .insert_if_missing(|| RefCell::new(SeatData::new()));
+    let seat_data = seat.user_data().get::<RefCell<SeatData>>().unwrap();
+    seat_data.borrow_mut().set_selection::<D>(dh, Selection::Empty);
+}
+
+/// Request the contents of the current client-provided primary selection of this seat
+///
+/// The data will be written by the client owning the selection into the provided `fd`
+/// using the given `mime_type`. This is useful to bridge the selection to other
+/// consumers (e.g. X11 clients).
+pub fn request_primary_client_selection<D>(
+    seat: &Seat<D>,
+    mime_type: String,
+    fd: OwnedFd,
+) -> Result<(), SelectionRequestError>
+where
+    D: SeatHandler + PrimarySelectionHandler + 'static,
+{
+    let seat_data = match seat.user_data().get::<RefCell<SeatData>>() {
+        Some(seat_data) => seat_data,
+        None => return Err(SelectionRequestError::NoSelection),
+    };
+    match seat_data.borrow().get_selection() {
+        Selection::Empty => Err(SelectionRequestError::NoSelection),
+        Selection::Compositor(_) => Err(SelectionRequestError::ServerSideSelection),
+        Selection::Client(source) => {
+            if !source.alive() {
+                return Err(SelectionRequestError::NoSelection);
+            }
+            let valid =
+                with_source_metadata(source, |meta| meta.mime_types.contains(&mime_type)).unwrap_or(false);
+            if !valid {
+                return Err(SelectionRequestError::InvalidMimetype);
+            }
+            source.send(mime_type, fd.as_raw_fd());
+            Ok(())
+        }
+        Selection::DataControl(source) => {
+            if !source.alive() {
+                return Err(SelectionRequestError::NoSelection);
+            }
+            let valid =
+                with_data_control_source_metadata(source, |meta| meta.mime_types.contains(&mime_type))
+                    .unwrap_or(false);
+            if !valid {
+                return Err(SelectionRequestError::InvalidMimetype);
+            }
+            source.send(mime_type, fd.as_raw_fd());
+            Ok(())
+        }
+    }
+}
+
 mod handlers {
     use std::cell::RefCell;
 
@@ -0,0 +1,163 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use wayland_protocols_wlr::screencopy::v1::server::{
+    zwlr_screencopy_frame_v1::{self, ZwlrScreencopyFrameV1},
+    zwlr_screencopy_manager_v1::{self, ZwlrScreencopyManagerV1},
+};
+use wayland_server::{Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource};
+
+use crate::{output::Output, utils::Rectangle};
+
+use super::{
+    buffer_matches, track_damage, FrameInfo, Screencopy, ScreencopyFrameData, ScreencopyGlobalData,
+    ScreencopyHandler, ScreencopyState, DMABUF_FORMAT, SHM_FORMAT,
+};
+
+impl<D> GlobalDispatch<ZwlrScreencopyManagerV1, ScreencopyGlobalData, D> for ScreencopyState
+where
+    D: GlobalDispatch<ZwlrScreencopyManagerV1, ScreencopyGlobalData>
+        + Dispatch<ZwlrScreencopyManagerV1, ()>
+        + Dispatch<ZwlrScreencopyFrameV1, ScreencopyFrameData>
+        + ScreencopyHandler
+        + 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<ZwlrScreencopyManagerV1>,
+        _global_data: &ScreencopyGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+
+    fn can_view(client: Client, global_data: &ScreencopyGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<ZwlrScreencopyManagerV1, (), D> for ScreencopyState
+where
+    D: Dispatch<ZwlrScreencopyManagerV1, ()>
+        + Dispatch<ZwlrScreencopyFrameV1, ScreencopyFrameData>
+        + ScreencopyHandler
+        + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        manager: &ZwlrScreencopyManagerV1,
+        request: zwlr_screencopy_manager_v1::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let (frame, overlay_cursor, output, region) = match request {
+            zwlr_screencopy_manager_v1::Request::CaptureOutput {
+                frame,
+                overlay_cursor,
+                output,
+            } => (frame, overlay_cursor, output, None),
+            zwlr_screencopy_manager_v1::Request::CaptureOutputRegion {
+                frame,
+                overlay_cursor,
+                output,
+                x,
+                y,
+                width,
+                height,
+            } => (
+                frame,
+                overlay_cursor,
+                output,
+                Some(Rectangle::from_loc_and_size((x, y), (width, height))),
+            ),
+            zwlr_screencopy_manager_v1::Request::Destroy => return,
+            _ => unreachable!(),
+        };
+
+        let info = Output::from_resource(&output)
+            .and_then(|output| FrameInfo::new(output, overlay_cursor != 0, region));
+        let frame = data_init.init(
+            frame,
+            ScreencopyFrameData {
+                manager: manager.clone(),
+                info: info.clone(),
+                used: AtomicBool::new(false),
+            },
+        );
+
+        // the output is gone, has no mode or the region is not on the output
+        let info = match info {
+            Some(info) => info,
+            None => {
+                frame.failed();
+                return;
+            }
+        };
+
+        let size = info.buffer_region.size;
+        frame.buffer(SHM_FORMAT, size.w as u32, size.h as u32, size.w as u32 * 4);
+        if frame.version() >= zwlr_screencopy_frame_v1::EVT_LINUX_DMABUF_SINCE {
+            frame.linux_dmabuf(DMABUF_FORMAT as u32, size.w as u32, size.h as u32);
+        }
+        if frame.version() >= zwlr_screencopy_frame_v1::EVT_BUFFER_DONE_SINCE {
+            frame.buffer_done();
+        }
+    }
+}
+
+impl<D> Dispatch<ZwlrScreencopyFrameV1, ScreencopyFrameData, D> for ScreencopyState
+where
+    D: Dispatch<ZwlrScreencopyFrameV1, ScreencopyFrameData> + ScreencopyHandler + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        frame: &ZwlrScreencopyFrameV1,
+        request: zwlr_screencopy_frame_v1::Request,
+        data: &ScreencopyFrameData,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        let (buffer, with_damage) = match request {
+            zwlr_screencopy_frame_v1::Request::Copy { buffer } => (buffer, false),
+            zwlr_screencopy_frame_v1::Request::CopyWithDamage { buffer } => (buffer, true),
+            zwlr_screencopy_frame_v1::Request::Destroy => return,
+            _ => unreachable!(),
+        };
+
+        if data.used.swap(true, Ordering::SeqCst) {
+            frame.post_error(
+                zwlr_screencopy_frame_v1::Error::AlreadyUsed,
+                "The frame was already used to copy a buffer",
+            );
+            return;
+        }
+
+        let info = match &data.info {
+            Some(info) => info.clone(),
+            // the frame already failed, when it was created
+            None => return,
+        };
+
+        if !buffer_matches(&buffer, info.buffer_region.size) {
+            frame.post_error(
+                zwlr_screencopy_frame_v1::Error::InvalidBuffer,
+                "The buffer does not match the advertised parameters",
+            );
+            return;
+        }
+
+        track_damage(&info.output, &data.manager, info.region);
+        state.frame(Screencopy {
+            manager: data.manager.clone(),
+            frame: frame.clone(),
+            buffer,
+            info,
+            with_damage,
+            done: false,
+        });
+    }
+}
@@ -0,0 +1,498 @@
+//! Utilities for handling the `wlr-screencopy` protocol
+//!
+//! This protocol allows clients like screenshot tools or screen recorders to request a copy of
+//! the contents of an output (or a region of it) into a client provided shm or dmabuf buffer.
+//!
+//! Whenever a client issues a copy request, [`ScreencopyHandler::frame`] is called with a
+//! [`Screencopy`] object. Your compositor is then expected to copy the contents of the requested output
+//! into the buffer of the frame, e.g. after the next time the output was rendered, and submit it.
+//!
+//! Frames requested with `copy_with_damage` ([`Screencopy::with_damage`]) should only be answered
+//! after the contents of the output changed. For this the damage of every rendered frame
+//! (as returned by [`DamageTrackedRenderer::render_output`](crate::backend::renderer::damage::DamageTrackedRenderer::render_output))
+//! has to be passed to [`damage_output`]. The damage is accumulated for every client since its last copy,
+//! which is then available through [`Screencopy::damage`] and sent to the client on [`Screencopy::submit`].
+//!
+//! ```no_run
+//! # extern crate wayland_server;
+//! use smithay::delegate_screencopy;
+//! use smithay::wayland::screencopy::{Screencopy, ScreencopyHandler, ScreencopyState};
+//!
+//! pub struct State {
+//!     pending_screencopy: Vec<Screencopy>,
+//! }
+//!
+//! impl ScreencopyHandler for State {
+//!     fn frame(&mut self, frame: Screencopy) {
+//!         // copy into the buffer the next time `frame.output()` is rendered
+//!         self.pending_screencopy.push(frame);
+//!     }
+//! }
+//!
+//! delegate_screencopy!(State);
+//!
+//! # let mut display = wayland_server::Display::<State>::new().unwrap();
+//! # let display_handle = display.handle();
+//! let screencopy_state = ScreencopyState::new::<State>(&display_handle);
+//! ```
+//!
+//! After rendering an output, the pending frames can be copied from the bound framebuffer:
+//!
+//! ```no_run
+//! # use smithay::backend::renderer::{damage::DamageTrackedRenderer, gles2::Gles2Renderer};
+//! # use smithay::backend::renderer::element::surface::WaylandSurfaceRenderElement;
+//! # use smithay::utils::{Clock, Monotonic};
+//! # use smithay::output::Output;
+//! # use smithay::wayland::screencopy::{self, Screencopy};
+//! # let mut renderer: Gles2Renderer = todo!();
+//! # let mut damage_tracked_renderer: DamageTrackedRenderer = todo!();
+//! # let elements: Vec<WaylandSurfaceRenderElement<Gles2Renderer>> = todo!();
+//! # let clock: Clock<Monotonic> = todo!();
+//! # let output: Output = todo!();
+//! # let mut pending_screencopy: Vec<Screencopy> = todo!();
+//! # let age = 0;
+//! let (damage, _) = damage_tracked_renderer
+//!     .render_output(&mut renderer, age, &elements, [0.0, 0.0, 0.0, 1.0], None)
+//!     .unwrap();
+//! if let Some(damage) = damage {
+//!     screencopy::damage_output(&output, &damage);
+//! }
+//!
+//! for frame in std::mem::take(&mut pending_screencopy) {
+//!     if frame.with_damage() && frame.damage().is_empty() {
+//!         // wait for the contents of the output to change
+//!         pending_screencopy.push(frame);
+//!         continue;
+//!     }
+//!
+//!     match frame.copy_framebuffer(&mut renderer) {
+//!         Ok(()) => frame.submit(clock.now()),
+//!         Err(_) => frame.failed(),
+//!     }
+//! }
+//! ```
+
+use std::{
+    sync::{atomic::AtomicBool, Mutex},
+    time::Duration,
+};
+
+use wayland_protocols_wlr::screencopy::v1::server::{
+    zwlr_screencopy_frame_v1::{self, ZwlrScreencopyFrameV1},
+    zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1,
+};
+use wayland_server::{
+    backend::GlobalId,
+    protocol::{wl_buffer::WlBuffer, wl_shm},
+    Client, Dispatch, DisplayHandle, GlobalDispatch, Resource,
+};
+
+use crate::{
+    backend::{
+        allocator::{dmabuf::Dmabuf, Buffer as _, Fourcc},
+        renderer::{Blit, ExportMem, TextureFilter, TextureMapping},
+    },
+    output::Output,
+    utils::{Buffer, Logical, Monotonic, Physical, Rectangle, Size, Time},
+    wayland::{dmabuf::get_dmabuf, shm},
+};
+
+mod dispatch;
+
+const MANAGER_VERSION: u32 = 3;
+
+/// The shm format offered to clients
+const SHM_FORMAT: wl_shm::Format = wl_shm::Format::Xrgb8888;
+/// The dmabuf format offered to clients
+const DMABUF_FORMAT: Fourcc = Fourcc::Xrgb8888;
+/// Amount of damage rectangles accumulated per client, before falling back to damaging the whole output
+const MAX_DAMAGE_RECTS: usize = 32;
+
+/// State of the wlr-screencopy global
+#[derive(Debug)]
+pub struct ScreencopyState {
+    global: GlobalId,
+}
+
+impl ScreencopyState {
+    /// Create a new `zwlr_screencopy_manager_v1` global
+    pub fn new<D>(display: &DisplayHandle) -> ScreencopyState
+    where
+        D: GlobalDispatch<ZwlrScreencopyManagerV1, ScreencopyGlobalData>
+            + Dispatch<ZwlrScreencopyManagerV1, ()>
+            + Dispatch<ZwlrScreencopyFrameV1, ScreencopyFrameData>
+            + ScreencopyHandler
+            + 'static,
+    {
+        Self::new_with_filter::<D, _>(display, |_| true)
+    }
+
+    /// Create a new `zwlr_screencopy_manager_v1` global, only visible to [privileged clients]
+    ///
+    /// [privileged clients]: crate::wayland::security_context#privileged-globals
+    pub fn new_with_filter<D, F>(display: &DisplayHandle, filter: F) -> ScreencopyState
+    where
+        D: GlobalDispatch<ZwlrScreencopyManagerV1, ScreencopyGlobalData>
+            + Dispatch<ZwlrScreencopyManagerV1, ()>
+            + Dispatch<ZwlrScreencopyFrameV1, ScreencopyFrameData>
+            + ScreencopyHandler
+            + 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+    {
+        let data = ScreencopyGlobalData {
+            filter: Box::new(filter),
+        };
+        let global = display.create_global::<D, ZwlrScreencopyManagerV1, _>(MANAGER_VERSION, data);
+
+        ScreencopyState { global }
+    }
+
+    /// Returns the id of the `zwlr_screencopy_manager_v1` global
+    pub fn global(&self) -> GlobalId {
+        self.global.clone()
+    }
+}
+
+/// Data associated with a screencopy manager global
+#[allow(missing_debug_implementations)]
+pub struct ScreencopyGlobalData {
+    filter: Box<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+}
+
+/// Handler trait for the wlr-screencopy protocol
+pub trait ScreencopyHandler {
+    /// A client requested a copy of an output into a buffer
+    ///
+    /// The buffer of the frame has already been validated to match the
+    /// advertised parameters. If the frame is dropped without being submitted,
+    /// the client is notified about the failed copy.
+    fn frame(&mut self, frame: Screencopy);
+}
+
+/// Parameters of a screencopy frame
+#[derive(Debug, Clone)]
+struct FrameInfo {
+    output: Output,
+    overlay_cursor: bool,
+    /// Captured region in the physical coordinate space of the output
+    region: Rectangle<i32, Physical>,
+    /// Captured region in the buffer coordinate space of the output framebuffer
+    buffer_region: Rectangle<i32, Buffer>,
+}
+
+impl FrameInfo {
+    fn new(
+        output: Output,
+        overlay_cursor: bool,
+        region: Option<Rectangle<i32, Logical>>,
+    ) -> Option<FrameInfo> {
+        let transform = output.current_transform();
+        let scale = output.current_scale().fractional_scale();
+
+        let output_geo = output_geometry(&output)?;
+        let region = match region {
+            Some(region) => region.to_physical_precise_round(scale).intersection(output_geo)?,
+            None => output_geo,
+        };
+        if region.is_empty() {
+            return None;
+        }
+
+        let buffer_region = transform.transform_rect_in(region, &output_geo.size);
+        let buffer_region = Rectangle::from_loc_and_size(
+            (buffer_region.loc.x, buffer_region.loc.y),
+            (buffer_region.size.w, buffer_region.size.h),
+        );
+
+        Some(FrameInfo {
+            output,
+            overlay_cursor,
+            region,
+            buffer_region,
+        })
+    }
+}
+
+/// Physical geometry of the output in its own coordinate space
+fn output_geometry(output: &Output) -> Option<Rectangle<i32, Physical>> {
+    let mode = output.current_mode()?;
+    Some(Rectangle::from_loc_and_size(
+        (0, 0),
+        output.current_transform().transform_size(mode.size),
+    ))
+}
+
+/// Damage of an output accumulated for every screencopy manager and captured region
+/// since the last copy with damage
+#[derive(Debug, Default)]
+struct OutputDamage(Mutex<Vec<TrackedDamage>>);
+
+#[derive(Debug)]
+struct TrackedDamage {
+    manager: ZwlrScreencopyManagerV1,
+    region: Rectangle<i32, Physical>,
+    damage: Vec<Rectangle<i32, Physical>>,
+}
+
+impl OutputDamage {
+    fn get(output: &Output) -> &OutputDamage {
+        output
+            .user_data()
+            .insert_if_missing_threadsafe(OutputDamage::default);
+        output.user_data().get::<OutputDamage>().unwrap()
+    }
+}
+
+/// Adds damage to an output
+///
+/// This needs to be called with the damage of every frame rendered for the output
+/// (in the physical coordinate space of the output) for frames requested with damage
+/// to be completed.
+pub fn damage_output(output: &Output, damage: &[Rectangle<i32, Physical>]) {
+    if damage.is_empty() {
+        return;
+    }
+
+    let mut tracked = OutputDamage::get(output).0.lock().unwrap();
+    tracked.retain(|tracked| tracked.manager.is_alive());
+    for tracked in tracked.iter_mut() {
+        tracked
+            .damage
+            .extend(damage.iter().filter_map(|rect| rect.intersection(tracked.region)));
+        if tracked.damage.len() > MAX_DAMAGE_RECTS {
+            tracked.damage.clear();
+            tracked.damage.push(tracked.region);
+        }
+    }
+}
+
+/// Starts tracking the damage of `region` of `output` for `manager`, if not done already
+///
+/// The whole region is considered damaged for the first copy.
+fn track_damage(output: &Output, manager: &ZwlrScreencopyManagerV1, region: Rectangle<i32, Physical>) {
+    let mut tracked = OutputDamage::get(output).0.lock().unwrap();
+    if !tracked
+        .iter()
+        .any(|tracked| tracked.manager == *manager && tracked.region == region)
+    {
+        tracked.push(TrackedDamage {
+            manager: manager.clone(),
+            region,
+            damage: vec![region],
+        });
+    }
+}
+
+/// User data of a `zwlr_screencopy_frame_v1`
+#[derive(Debug)]
+pub struct ScreencopyFrameData {
+    manager: ZwlrScreencopyManagerV1,
+    info: Option<FrameInfo>,
+    used: AtomicBool,
+}
+
+/// Errors thrown by [`Screencopy::copy_framebuffer`]
+#[derive(Debug, thiserror::Error)]
+pub enum CopyError<E: std::error::Error> {
+    /// The renderer failed to copy the framebuffer
+    #[error(transparent)]
+    Rendering(E),
+    /// The shm buffer of the client could not be accessed
+    #[error(transparent)]
+    BufferAccess(#[from] shm::BufferAccessError),
+}
+
+/// A pending screencopy request
+///
+/// Dropping this object without calling [`Screencopy::submit`] notifies the client
+/// about the failed copy.
+#[derive(Debug)]
+pub struct Screencopy {
+    manager: ZwlrScreencopyManagerV1,
+    frame: ZwlrScreencopyFrameV1,
+    buffer: WlBuffer,
+    info: FrameInfo,
+    with_damage: bool,
+    done: bool,
+}
+
+impl Screencopy {
+    /// The output to be copied
+    pub fn output(&self) -> &Output {
+        &self.info.output
+    }
+
+    /// The requested region in the physical coordinate space of the output
+    ///
+    /// For a full output capture this covers the whole output.
+    pub fn region(&self) -> Rectangle<i32, Physical> {
+        self.info.region
+    }
+
+    /// The requested region in the buffer coordinate space of the framebuffer of the output
+    ///
+    /// This takes the transformation of the output into account and matches the size of
+    /// [`Screencopy::buffer`].
+    pub fn buffer_region(&self) -> Rectangle<i32, Buffer> {
+        self.info.buffer_region
+    }
+
+    /// Whether the client wants the cursor to be included in the copy
+    pub fn overlay_cursor(&self) -> bool {
+        self.info.overlay_cursor
+    }
+
+    /// Whether the client requested to wait for damage before the copy (`copy_with_damage`)
+    pub fn with_damage(&self) -> bool {
+        self.with_damage
+    }
+
+    /// The damage of the requested region since the last copy with damage of the client
+    ///
+    /// The damage is in the physical coordinate space of the output, as passed to [`damage_output`].
+    /// For the first copy of a region by a client this covers the whole region.
+    pub fn damage(&self) -> Vec<Rectangle<i32, Physical>> {
+        let tracked = OutputDamage::get(&self.info.output).0.lock().unwrap();
+        tracked
+            .iter()
+            .find(|tracked| tracked.manager == self.manager && tracked.region == self.info.region)
+            .map(|tracked| tracked.damage.clone())
+            .unwrap_or_default()
+    }
+
+    /// The buffer provided by the client
+    ///
+    /// This is either a shm buffer or a dmabuf of the size of [`Screencopy::buffer_region`].
+    pub fn buffer(&self) -> &WlBuffer {
+        &self.buffer
+    }
+
+    /// Copies the requested region of the currently bound framebuffer into the client buffer
+    ///
+    /// The bound framebuffer is expected to contain the contents of [`Screencopy::output`],
+    /// as rendered by a [`DamageTrackedRenderer`](crate::backend::renderer::damage::DamageTrackedRenderer).
+    /// Shm buffers are filled using [`ExportMem`], dmabufs using [`Blit`].
+    pub fn copy_framebuffer<R>(&self, renderer: &mut R) -> Result<(), CopyError<R::Error>>
+    where
+        R: ExportMem + Blit<Dmabuf>,
+    {
+        if let Ok(dmabuf) = get_dmabuf(&self.buffer) {
+            let src = self.info.buffer_region;
+            return renderer
+                .blit_to(
+                    dmabuf,
+                    Rectangle::from_loc_and_size((src.loc.x, src.loc.y), (src.size.w, src.size.h)),
+                    Rectangle::from_loc_and_size((0, 0), (src.size.w, src.size.h)),
+                    TextureFilter::Nearest,
+                )
+                .map_err(CopyError::Rendering);
+        }
+
+        let mapping = renderer
+            .copy_framebuffer(self.info.buffer_region)
+            .map_err(CopyError::Rendering)?;
+        let flipped = mapping.flipped();
+        let pixels = renderer.map_texture(&mapping).map_err(CopyError::Rendering)?;
+
+        shm::with_buffer_contents_mut(&self.buffer, |slice, data| {
+            let width = data.width as usize;
+            let height = data.height as usize;
+            for y in 0..height {
+                let src_row = if flipped { height - 1 - y } else { y };
+                let src = &pixels[src_row * width * 4..(src_row + 1) * width * 4];
+                let offset = data.offset as usize + y * data.stride as usize;
+                let dst = &mut slice[offset..offset + width * 4];
+                // the mapping is RGBA8, while the shm format is BGRX in memory
+                for (dst, src) in dst.chunks_exact_mut(4).zip(src.chunks_exact(4)) {
+                    dst.copy_from_slice(&[src[2], src[1], src[0], src[3]]);
+                }
+            }
+        })?;
+
+        Ok(())
+    }
+
+    /// Marks the copy as completed
+    ///
+    /// If the frame was requested with damage, the damage since the last copy with damage
+    /// ([`Screencopy::damage`]) is sent to the client. `timestamp` should denote the time the copied contents were presented.
+    pub fn submit(mut self, timestamp: Time<Monotonic>) {
+        if self.with_damage {
+            // plain copies do not consume the damage, so it is still reported to the next copy with damage
+            let damage = {
+                let mut tracked = OutputDamage::get(&self.info.output).0.lock().unwrap();
+                tracked
+                    .iter_mut()
+                    .find(|tracked| tracked.manager == self.manager && tracked.region == self.info.region)
+                    .map(|tracked| std::mem::take(&mut tracked.damage))
+                    .unwrap_or_default()
+            };
+            let output_size = output_geometry(&self.info.output)
+                .map(|geo| geo.size)
+                .unwrap_or_default();
+            let transform = self.info.output.current_transform();
+            for rect in damage {
+                let rect = transform.transform_rect_in(rect, &output_size);
+                self.frame.damage(
+                    (rect.loc.x - self.info.buffer_region.loc.x) as u32,
+                    (rect.loc.y - self.info.buffer_region.loc.y) as u32,
+                    rect.size.w as u32,
+                    rect.size.h as u32,
+                );
+            }
+        }
+
+        let time = Duration::from(timestamp);
+        let tv_sec = time.as_secs();
+        self.frame.flags(zwlr_screencopy_frame_v1::Flags::empty());
+        self.frame
+            .ready((tv_sec >> 32) as u32, tv_sec as u32, time.subsec_nanos());
+        self.done = true;
+    }
+
+    /// Marks the copy as failed
+    pub fn failed(self) {
+        // the failed event is sent on drop
+    }
+}
+
+impl Drop for Screencopy {
+    fn drop(&mut self) {
+        if !self.done {
+            self.frame.failed();
+        }
+    }
+}
+
+fn buffer_matches(buffer: &WlBuffer, size: Size<i32, Buffer>) -> bool {
+    if let Ok(dmabuf) = get_dmabuf(buffer) {
+        return dmabuf.size() == size && dmabuf.format().code == DMABUF_FORMAT;
+    }
+
+    shm::with_buffer_contents(buffer, |_, data| {
+        data.format == SHM_FORMAT
+            && data.width == size.w
+            && data.height == size.h
+            && data.stride >= size.w * 4
+    })
+    .unwrap_or(false)
+}
+
+/// Macro to delegate implementation of the wlr-screencopy protocol to [`ScreencopyState`].
+///
+/// You must also implement [`ScreencopyHandler`] to use this.
+#[macro_export]
+macro_rules! delegate_screencopy {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::screencopy::v1::server::zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1: $crate::wayland::screencopy::ScreencopyGlobalData
+        ] => $crate::wayland::screencopy::ScreencopyState);
+
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::screencopy::v1::server::zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1: ()
+        ] => $crate::wayland::screencopy::ScreencopyState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::screencopy::v1::server::zwlr_screencopy_frame_v1::ZwlrScreencopyFrameV1: $crate::wayland::screencopy::ScreencopyFrameData
+        ] => $crate::wayland::screencopy::ScreencopyState);
+    };
+}
@@ -165,7 +165,7 @@ impl<D: SeatHandler + 'static> SeatState<D> {
     {
         let Seat { arc } = self.new_seat(name, logger);
 
-        let global_id = display.create_global::<D, _, _>(7, SeatGlobalData { arc: arc.clone() });
+        let global_id = display.create_global::<D, _, _>(9, SeatGlobalData { arc: arc.clone() });
         arc.inner.lock().unwrap().global = Some(global_id);
 
         Seat { arc }
@@ -1,12 +1,19 @@
 use std::{fmt, sync::Mutex};
 
-use wayland_protocols::wp::relative_pointer::zv1::server::zwp_relative_pointer_v1::ZwpRelativePointerV1;
+use wayland_protocols::wp::{
+    pointer_gestures::zv1::server::{
+        zwp_pointer_gesture_hold_v1::ZwpPointerGestureHoldV1,
+        zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1,
+        zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1,
+    },
+    relative_pointer::zv1::server::zwp_relative_pointer_v1::ZwpRelativePointerV1,
+};
 use wayland_server::{
     backend::{ClientId, ObjectId},
     protocol::{
         wl_pointer::{
-            self, Axis as WlAxis, AxisSource as WlAxisSource, ButtonState as WlButtonState, Request,
-            WlPointer,
+            self, Axis as WlAxis, AxisRelativeDirection as WlAxisRelativeDirection,
+            AxisSource as WlAxisSource, ButtonState as WlButtonState, Request, WlPointer,
         },
         wl_surface::WlSurface,
     },
@@ -14,16 +21,21 @@ use wayland_server::{
 };
 
 use crate::{
-    backend::input::{Axis, AxisSource, ButtonState},
+    backend::input::{Axis, AxisRelativeDirection, AxisSource, ButtonState},
     input::{
         pointer::{
-            AxisFrame, ButtonEvent, CursorImageAttributes, CursorImageStatus, MotionEvent, PointerHandle,
-            PointerInternal, PointerTarget, RelativeMotionEvent,
+            AxisFrame, ButtonEvent, CursorImageAttributes, CursorImageStatus, GestureHoldBeginEvent,
+            GestureHoldEndEvent, GesturePinchBeginEvent, GesturePinchEndEvent, GesturePinchUpdateEvent,
+            GestureSwipeBeginEvent, GestureSwipeEndEvent, GestureSwipeUpdateEvent, MotionEvent,
+            PointerHandle, PointerInternal, PointerTarget, RelativeMotionEvent,
         },
         Seat,
     },
-    utils::Serial,
-    wayland::compositor,
+    utils::{Logical, Point, Serial},
+    wayland::{
+        compositor,
+        pointer_constraints::{update_constraint_focus, with_pointer_constraint, PointerConstraint},
+    },
 };
 
 use super::{SeatHandler, SeatState, WaylandFocus};
@@ -73,12 +85,86 @@ fn for_each_focused_relative_pointers<D: SeatHandler + 'static>(
     }
 }
 
+fn for_each_focused_swipe_gestures<D: SeatHandler + 'static>(
+    seat: &Seat<D>,
+    surface: &WlSurface,
+    mut f: impl FnMut(ZwpPointerGestureSwipeV1),
+) {
+    if let Some(pointer) = seat.get_pointer() {
+        let inner = pointer.known_swipe_gestures.lock().unwrap();
+        for gesture in &*inner {
+            if gesture.id().same_client_as(&surface.id()) {
+                f(gesture.clone())
+            }
+        }
+    }
+}
+
+fn for_each_focused_pinch_gestures<D: SeatHandler + 'static>(
+    seat: &Seat<D>,
+    surface: &WlSurface,
+    mut f: impl FnMut(ZwpPointerGesturePinchV1),
+) {
+    if let Some(pointer) = seat.get_pointer() {
+        let inner = pointer.known_pinch_gestures.lock().unwrap();
+        for gesture in &*inner {
+            if gesture.id().same_client_as(&surface.id()) {
+                f(gesture.clone())
+            }
+        }
+    }
+}
+
+fn for_each_focused_hold_gestures<D: SeatHandler + 'static>(
+    seat: &Seat<D>,
+    surface: &WlSurface,
+    mut f: impl FnMut(ZwpPointerGestureHoldV1),
+) {
+    if let Some(pointer) = seat.get_pointer() {
+        let inner = pointer.known_hold_gestures.lock().unwrap();
+        for gesture in &*inner {
+            if gesture.id().same_client_as(&surface.id()) {
+                f(gesture.clone())
+            }
+        }
+    }
+}
+
+/// Activates or deactivates the pointer constraint of the surface for the pointer of the seat
+///
+/// `location` is the surface-local pointer location, or `None` if the surface lost the pointer focus.
+/// Returns whether the pointer is currently locked.
+fn update_pointer_constraint<D: SeatHandler + 'static>(
+    seat: &Seat<D>,
+    surface: &WlSurface,
+    location: Option<Point<f64, Logical>>,
+) -> bool {
+    let pointer = match seat.get_pointer() {
+        Some(pointer) => pointer,
+        None => return false,
+    };
+    update_constraint_focus(&pointer, surface, location);
+    with_pointer_constraint(surface, &pointer, |constraint| {
+        let constraint = match constraint {
+            Some(constraint) => constraint,
+            None => return false,
+        };
+        match location {
+            Some(location) if constraint.contains(location) => constraint.activate(),
+            Some(_) => {}
+            None => constraint.deactivate(),
+        }
+        constraint.is_active() && matches!(constraint, PointerConstraint::Locked(_))
+    })
+}
+
 #[cfg(feature = "wayland_frontend")]
 impl<D> PointerTarget<D> for WlSurface
 where
     D: SeatHandler + 'static,
 {
     fn enter(&self, seat: &Seat<D>, _data: &mut D, event: &MotionEvent) {
+        update_pointer_constraint(seat, self, Some(event.location));
         for_each_focused_pointers(seat, self, |ptr| {
             ptr.enter(event.serial.into(), self, event.location.x, event.location.y);
             if ptr.version() >= 5 {
@@ -87,6 +173,7 @@ where
         })
     }
     fn leave(&self, seat: &Seat<D>, _data: &mut D, serial: Serial, _time: u32) {
+        update_pointer_constraint(seat, self, None);
         for_each_focused_pointers(seat, self, |ptr| {
             ptr.leave(serial.into(), self);
             if ptr.version() >= 5 {
@@ -95,6 +182,10 @@ where
         })
     }
     fn motion(&self, seat: &Seat<D>, _data: &mut D, event: &MotionEvent) {
+        // locked pointers only receive relative motion
+        if update_pointer_constraint(seat, self, Some(event.location)) {
+            return;
+        }
         for_each_focused_pointers(seat, self, |ptr| {
             ptr.motion(event.time, event.location.x, event.location.y);
             if ptr.version() >= 5 {
@@ -126,6 +217,18 @@ where
     }
     fn axis(&self, seat: &Seat<D>, _data: &mut D, details: AxisFrame) {
         for_each_focused_pointers(seat, self, |ptr| {
+            // axis relative direction
+            if ptr.version() >= wl_pointer::EVT_AXIS_RELATIVE_DIRECTION_SINCE {
+                if details.axis.0 != 0.0 {
+                    ptr.axis_relative_direction(
+                        WlAxis::HorizontalScroll,
+                        details.relative_direction.0.into(),
+                    );
+                }
+                if details.axis.1 != 0.0 {
+                    ptr.axis_relative_direction(WlAxis::VerticalScroll, details.relative_direction.1.into());
+                }
+            }
             // axis
             if details.axis.0 != 0.0 {
                 ptr.axis(details.time, WlAxis::HorizontalScroll, details.axis.0);
@@ -149,8 +252,21 @@ where
                     .into();
                     ptr.axis_source(source);
                 }
-                // axis discrete
-                if let Some((x, y)) = details.discrete {
+                if ptr.version() >= wl_pointer::EVT_AXIS_VALUE120_SINCE {
+                    // axis value120, replacing axis discrete
+                    let v120 = details
+                        .v120
+                        .or_else(|| details.discrete.map(|(x, y)| (x * 120, y * 120)));
+                    if let Some((x, y)) = v120 {
+                        if x != 0 {
+                            ptr.axis_value120(WlAxis::HorizontalScroll, x);
+                        }
+                        if y != 0 {
+                            ptr.axis_value120(WlAxis::VerticalScroll, y);
+                        }
+                    }
+                } else if let Some((x, y)) = details.discrete {
+                    // axis discrete
                     if x != 0 {
                         ptr.axis_discrete(WlAxis::HorizontalScroll, x);
                     }
@@ -170,6 +286,52 @@ where
             }
         })
     }
+    fn gesture_swipe_begin(&self, seat: &Seat<D>, _data: &mut D, event: &GestureSwipeBeginEvent) {
+        for_each_focused_swipe_gestures(seat, self, |gesture| {
+            gesture.begin(event.serial.into(), event.time, self, event.fingers)
+        })
+    }
+    fn gesture_swipe_update(&self, seat: &Seat<D>, _data: &mut D, event: &GestureSwipeUpdateEvent) {
+        for_each_focused_swipe_gestures(seat, self, |gesture| {
+            gesture.update(event.time, event.delta.x, event.delta.y)
+        })
+    }
+    fn gesture_swipe_end(&self, seat: &Seat<D>, _data: &mut D, event: &GestureSwipeEndEvent) {
+        for_each_focused_swipe_gestures(seat, self, |gesture| {
+            gesture.end(event.serial.into(), event.time, event.cancelled as i32)
+        })
+    }
+    fn gesture_pinch_begin(&self, seat: &Seat<D>, _data: &mut D, event: &GesturePinchBeginEvent) {
+        for_each_focused_pinch_gestures(seat, self, |gesture| {
+            gesture.begin(event.serial.into(), event.time, self, event.fingers)
+        })
+    }
+    fn gesture_pinch_update(&self, seat: &Seat<D>, _data: &mut D, event: &GesturePinchUpdateEvent) {
+        for_each_focused_pinch_gestures(seat, self, |gesture| {
+            gesture.update(
+                event.time,
+                event.delta.x,
+                event.delta.y,
+                event.scale,
+                event.rotation,
+            )
+        })
+    }
+    fn gesture_pinch_end(&self, seat: &Seat<D>, _data: &mut D, event: &GesturePinchEndEvent) {
+        for_each_focused_pinch_gestures(seat, self, |gesture| {
+            gesture.end(event.serial.into(), event.time, event.cancelled as i32)
+        })
+    }
+    fn gesture_hold_begin(&self, seat: &Seat<D>, _data: &mut D, event: &GestureHoldBeginEvent) {
+        for_each_focused_hold_gestures(seat, self, |gesture| {
+            gesture.begin(event.serial.into(), event.time, self, event.fingers)
+        })
+    }
+    fn gesture_hold_end(&self, seat: &Seat<D>, _data: &mut D, event: &GestureHoldEndEvent) {
+        for_each_focused_hold_gestures(seat, self, |gesture| {
+            gesture.end(event.serial.into(), event.time, event.cancelled as i32)
+        })
+    }
 }
 
 /// User data for pointer
@@ -321,6 +483,15 @@ impl From<AxisSource> for WlAxisSource {
     }
 }
 
+impl From<AxisRelativeDirection> for WlAxisRelativeDirection {
+    fn from(direction: AxisRelativeDirection) -> WlAxisRelativeDirection {
+        match direction {
+            AxisRelativeDirection::Identical => WlAxisRelativeDirection::Identical,
+            AxisRelativeDirection::Inverted => WlAxisRelativeDirection::Inverted,
+        }
+    }
+}
+
 impl From<ButtonState> for WlButtonState {
     fn from(state: ButtonState) -> WlButtonState {
         match state {
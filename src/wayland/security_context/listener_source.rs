@@ -0,0 +1,94 @@
+use std::{
+    io,
+    os::unix::{
+        io::OwnedFd,
+        net::{UnixListener, UnixStream},
+    },
+};
+
+use calloop::{
+    generic::Generic, EventSource, Interest, Mode, Poll, PostAction, Readiness, Token, TokenFactory,
+};
+
+/// Event source accepting the clients of a security context
+///
+/// Yields a [`UnixStream`] for every client connecting to the socket of the security context.
+/// You need to create the client using this stream by calling
+/// [`DisplayHandle::insert_client`](wayland_server::DisplayHandle::insert_client).
+///
+/// The source removes itself from the event loop, once the sandbox engine closes
+/// the `close_fd` it provided for the security context.
+#[derive(Debug)]
+pub struct SecurityContextListenerSource {
+    listen_fd: Generic<UnixListener>,
+    close_fd: Generic<OwnedFd>,
+}
+
+impl SecurityContextListenerSource {
+    /// Create the source from a non-blocking listening socket and the fd signalling its end
+    pub(super) fn new(listener: UnixListener, close_fd: OwnedFd) -> SecurityContextListenerSource {
+        SecurityContextListenerSource {
+            listen_fd: Generic::new(listener, Interest::READ, Mode::Level),
+            close_fd: Generic::new(close_fd, Interest::READ, Mode::Level),
+        }
+    }
+}
+
+impl EventSource for SecurityContextListenerSource {
+    /// A stream to the new client.
+    ///
+    /// You must register the client using the stream by calling
+    /// [`DisplayHandle::insert_client`](wayland_server::DisplayHandle::insert_client).
+    type Event = UnixStream;
+    type Metadata = ();
+    type Ret = ();
+    type Error = io::Error;
+
+    fn process_events<F>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        mut callback: F,
+    ) -> io::Result<PostAction>
+    where
+        F: FnMut(Self::Event, &mut Self::Metadata) -> Self::Ret,
+    {
+        // Any event on the close_fd means the other end was closed (hang-up),
+        // so the socket should not accept any more clients.
+        let mut closed = false;
+        self.close_fd.process_events(readiness, token, |_, _| {
+            closed = true;
+            Ok(PostAction::Remove)
+        })?;
+        if closed {
+            return Ok(PostAction::Remove);
+        }
+
+        self.listen_fd.process_events(readiness, token, |_, listener| {
+            loop {
+                match listener.accept() {
+                    Ok((stream, _)) => callback(stream, &mut ()),
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                    Err(err) => return Err(err),
+                }
+            }
+
+            Ok(PostAction::Continue)
+        })
+    }
+
+    fn register(&mut self, poll: &mut Poll, token_factory: &mut TokenFactory) -> calloop::Result<()> {
+        self.listen_fd.register(poll, token_factory)?;
+        self.close_fd.register(poll, token_factory)
+    }
+
+    fn reregister(&mut self, poll: &mut Poll, token_factory: &mut TokenFactory) -> calloop::Result<()> {
+        self.listen_fd.reregister(poll, token_factory)?;
+        self.close_fd.reregister(poll, token_factory)
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> calloop::Result<()> {
+        self.listen_fd.unregister(poll)?;
+        self.close_fd.unregister(poll)
+    }
+}
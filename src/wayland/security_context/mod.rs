@@ -0,0 +1,338 @@
+//! Utilities for the security context protocol
+//!
+//! The security context protocol allows sandbox engines like Flatpak to create dedicated listening
+//! sockets for the applications they run. Clients connecting to such a socket are tagged with
+//! metadata describing the sandbox, which allows the compositor to restrict what these clients can
+//! access, e.g. by hiding privileged globals like [`screencopy`](super::screencopy),
+//! [`data_control`](super::data_control) or [`virtual_keyboard`](super::virtual_keyboard) from them.
+//!
+//! Once a sandbox engine commits a security context, [`SecurityContextHandler::context_created`] is
+//! called with a [`SecurityContextListenerSource`] and the [`SecurityContext`] metadata. The compositor
+//! is expected to insert the source into its event loop and to store the metadata in the
+//! [`ClientData`](wayland_server::backend::ClientData) of every client inserted from it. The filters
+//! of the privileged globals can then look up the metadata of a client to decide whether it may see them.
+//!
+//! # Privileged globals
+//!
+//! Some globals give their clients power over more than their own surfaces:
+//! [`screencopy`](super::screencopy) reads the contents of any output,
+//! [`data_control`](super::data_control) reads the clipboard at any time,
+//! [`output_management`](super::output_management) reconfigures all outputs,
+//! [`foreign_toplevel`](super::foreign_toplevel) exposes and controls the windows of all clients and
+//! [`virtual_keyboard`](super::virtual_keyboard) injects input. Their states take a `filter` deciding
+//! which clients can see the global, which should only let trusted clients through, e.g. by hiding the
+//! global from every client that is part of a security context.
+//!
+//! As nested security contexts are not allowed, the filter of the [`SecurityContextState`] should
+//! hide the global from clients that are already part of a security context.
+//!
+//! ```no_run
+//! # extern crate wayland_server;
+//! use std::sync::Arc;
+//! use smithay::delegate_security_context;
+//! use smithay::reexports::calloop::LoopHandle;
+//! use smithay::reexports::wayland_server::{
+//!     backend::{ClientData, ClientId, DisconnectReason},
+//!     Client, DisplayHandle,
+//! };
+//! use smithay::wayland::security_context::{
+//!     SecurityContext, SecurityContextHandler, SecurityContextListenerSource, SecurityContextState,
+//! };
+//!
+//! struct ClientState {
+//!     security_context: Option<SecurityContext>,
+//! }
+//!
+//! impl ClientData for ClientState {
+//!     fn initialized(&self, _client_id: ClientId) {}
+//!     fn disconnected(&self, _client_id: ClientId, _reason: DisconnectReason) {}
+//! }
+//!
+//! /// Clients inside of a sandbox are not allowed to see privileged globals
+//! fn is_privileged(client: &Client) -> bool {
+//!     client
+//!         .get_data::<ClientState>()
+//!         .map_or(true, |data| data.security_context.is_none())
+//! }
+//!
+//! struct State {
+//!     display_handle: DisplayHandle,
+//!     loop_handle: LoopHandle<'static, State>,
+//! }
+//!
+//! impl SecurityContextHandler for State {
+//!     fn context_created(&mut self, source: SecurityContextListenerSource, context: SecurityContext) {
+//!         self.loop_handle
+//!             .insert_source(source, move |client_stream, _, state| {
+//!                 let client_state = ClientState {
+//!                     security_context: Some(context.clone()),
+//!                 };
+//!                 let _ = state
+//!                     .display_handle
+//!                     .insert_client(client_stream, Arc::new(client_state));
+//!             })
+//!             .expect("Failed to init the security context listener source");
+//!     }
+//! }
+//!
+//! delegate_security_context!(State);
+//!
+//! # let mut display = wayland_server::Display::<State>::new().unwrap();
+//! let security_context_state = SecurityContextState::new::<State, _>(&display.handle(), is_privileged);
+//! ```
+
+use std::{
+    os::unix::{
+        io::{AsRawFd, OwnedFd},
+        net::UnixListener,
+    },
+    sync::Mutex,
+};
+
+use nix::sys::socket::{getsockopt, sockopt};
+use wayland_protocols::wp::security_context::v1::server::{
+    wp_security_context_manager_v1::{self, WpSecurityContextManagerV1},
+    wp_security_context_v1::{self, WpSecurityContextV1},
+};
+use wayland_server::{
+    backend::GlobalId, Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+};
+
+mod listener_source;
+
+pub use listener_source::SecurityContextListenerSource;
+
+const MANAGER_VERSION: u32 = 1;
+
+/// Metadata attached to a security context by the sandbox engine
+///
+/// All fields are optional, the sandbox engine decides which of them it sets.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct SecurityContext {
+    /// Name of the sandbox engine, e.g. `org.flatpak`
+    pub sandbox_engine: Option<String>,
+    /// Application ID of the sandboxed application
+    pub app_id: Option<String>,
+    /// Identifier of the running instance of the application
+    pub instance_id: Option<String>,
+}
+
+/// Handler trait for the security context protocol
+pub trait SecurityContextHandler {
+    /// A sandbox engine committed a new security context
+    ///
+    /// `source` yields the clients connecting to the socket of the security context,
+    /// which should be inserted with the `context` metadata attached to their client data.
+    fn context_created(&mut self, source: SecurityContextListenerSource, context: SecurityContext);
+}
+
+/// State of the security context protocol
+#[derive(Debug)]
+pub struct SecurityContextState {
+    global: GlobalId,
+}
+
+/// Data associated with a security context manager global
+#[allow(missing_debug_implementations)]
+pub struct SecurityContextGlobalData {
+    filter: Box<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+}
+
+impl SecurityContextState {
+    /// Register new [WpSecurityContextManagerV1] global
+    ///
+    /// The `filter` should hide the global from clients that are already
+    /// part of a security context, as these must not create nested ones.
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> SecurityContextState
+    where
+        D: GlobalDispatch<WpSecurityContextManagerV1, SecurityContextGlobalData>
+            + Dispatch<WpSecurityContextManagerV1, ()>
+            + Dispatch<WpSecurityContextV1, SecurityContextUserData>
+            + SecurityContextHandler
+            + 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+    {
+        let data = SecurityContextGlobalData {
+            filter: Box::new(filter),
+        };
+        let global = display.create_global::<D, WpSecurityContextManagerV1, _>(MANAGER_VERSION, data);
+
+        SecurityContextState { global }
+    }
+
+    /// [WpSecurityContextManagerV1] GlobalId getter
+    pub fn global(&self) -> GlobalId {
+        self.global.clone()
+    }
+}
+
+/// User data of a `wp_security_context_v1`
+#[derive(Debug)]
+pub struct SecurityContextUserData {
+    inner: Mutex<Option<PendingSecurityContext>>,
+}
+
+/// A security context, that has not been committed yet
+#[derive(Debug)]
+struct PendingSecurityContext {
+    listener: UnixListener,
+    close_fd: OwnedFd,
+    context: SecurityContext,
+}
+
+impl<D> GlobalDispatch<WpSecurityContextManagerV1, SecurityContextGlobalData, D> for SecurityContextState
+where
+    D: GlobalDispatch<WpSecurityContextManagerV1, SecurityContextGlobalData>
+        + Dispatch<WpSecurityContextManagerV1, ()>
+        + Dispatch<WpSecurityContextV1, SecurityContextUserData>
+        + SecurityContextHandler
+        + 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<WpSecurityContextManagerV1>,
+        _global_data: &SecurityContextGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+
+    fn can_view(client: Client, global_data: &SecurityContextGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<WpSecurityContextManagerV1, (), D> for SecurityContextState
+where
+    D: Dispatch<WpSecurityContextManagerV1, ()>
+        + Dispatch<WpSecurityContextV1, SecurityContextUserData>
+        + SecurityContextHandler
+        + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        manager: &WpSecurityContextManagerV1,
+        request: wp_security_context_manager_v1::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            wp_security_context_manager_v1::Request::CreateListener {
+                id,
+                listen_fd,
+                close_fd,
+            } => {
+                // the socket has to be bound and listening already
+                if !matches!(getsockopt(listen_fd.as_raw_fd(), sockopt::AcceptConn), Ok(true)) {
+                    manager.post_error(
+                        wp_security_context_manager_v1::Error::InvalidListenFd,
+                        format!("fd {} is not a listening socket", listen_fd.as_raw_fd()),
+                    );
+                    return;
+                }
+                let listener = UnixListener::from(listen_fd);
+                if let Err(err) = listener.set_nonblocking(true) {
+                    manager.post_error(
+                        wp_security_context_manager_v1::Error::InvalidListenFd,
+                        format!("failed to setup the listening socket: {}", err),
+                    );
+                    return;
+                }
+
+                data_init.init(
+                    id,
+                    SecurityContextUserData {
+                        inner: Mutex::new(Some(PendingSecurityContext {
+                            listener,
+                            close_fd,
+                            context: SecurityContext::default(),
+                        })),
+                    },
+                );
+            }
+            wp_security_context_manager_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<WpSecurityContextV1, SecurityContextUserData, D> for SecurityContextState
+where
+    D: Dispatch<WpSecurityContextV1, SecurityContextUserData> + SecurityContextHandler + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        security_context: &WpSecurityContextV1,
+        request: wp_security_context_v1::Request,
+        data: &SecurityContextUserData,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        if let wp_security_context_v1::Request::Destroy = request {
+            return;
+        }
+
+        let mut guard = data.inner.lock().unwrap();
+        let pending = match guard.as_mut() {
+            Some(pending) => pending,
+            None => {
+                security_context.post_error(
+                    wp_security_context_v1::Error::AlreadyUsed,
+                    "the security context was already committed",
+                );
+                return;
+            }
+        };
+
+        let (field, value) = match request {
+            wp_security_context_v1::Request::SetSandboxEngine { name } => {
+                (&mut pending.context.sandbox_engine, name)
+            }
+            wp_security_context_v1::Request::SetAppId { app_id } => (&mut pending.context.app_id, app_id),
+            wp_security_context_v1::Request::SetInstanceId { instance_id } => {
+                (&mut pending.context.instance_id, instance_id)
+            }
+            wp_security_context_v1::Request::Commit => {
+                let pending = guard.take().unwrap();
+                // don't hold the lock while calling into the compositor
+                std::mem::drop(guard);
+
+                let source = SecurityContextListenerSource::new(pending.listener, pending.close_fd);
+                state.context_created(source, pending.context);
+                return;
+            }
+            _ => unreachable!(),
+        };
+
+        if field.is_some() {
+            security_context.post_error(
+                wp_security_context_v1::Error::AlreadySet,
+                "the metadata was already set",
+            );
+            return;
+        }
+        *field = Some(value);
+    }
+}
+
+/// Macro to delegate implementation of the security context protocol
+#[macro_export]
+macro_rules! delegate_security_context {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::security_context::v1::server::wp_security_context_manager_v1::WpSecurityContextManagerV1: $crate::wayland::security_context::SecurityContextGlobalData
+        ] => $crate::wayland::security_context::SecurityContextState);
+
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::security_context::v1::server::wp_security_context_manager_v1::WpSecurityContextManagerV1: ()
+        ] => $crate::wayland::security_context::SecurityContextState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::security_context::v1::server::wp_security_context_v1::WpSecurityContextV1: $crate::wayland::security_context::SecurityContextUserData
+        ] => $crate::wayland::security_context::SecurityContextState);
+    };
+}
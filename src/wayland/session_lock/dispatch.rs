@@ -0,0 +1,287 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+use wayland_protocols::ext::session_lock::v1::server::{
+    ext_session_lock_manager_v1::{self, ExtSessionLockManagerV1},
+    ext_session_lock_surface_v1::{self, ExtSessionLockSurfaceV1},
+    ext_session_lock_v1::{self, ExtSessionLockV1},
+};
+use wayland_server::{
+    backend::{ClientId, ObjectId},
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+};
+
+use crate::{
+    utils::Serial,
+    wayland::compositor::{self, BufferAssignment, SurfaceAttributes},
+};
+
+use super::{
+    LockSurface, LockSurfaceAttributes, LockSurfaceData, SessionLockData, SessionLockHandler,
+    SessionLockManagerState, SessionLocker, LOCK_SURFACE_ROLE,
+};
+
+impl<D> GlobalDispatch<ExtSessionLockManagerV1, (), D> for SessionLockManagerState
+where
+    D: GlobalDispatch<ExtSessionLockManagerV1, ()>
+        + Dispatch<ExtSessionLockManagerV1, ()>
+        + Dispatch<ExtSessionLockV1, SessionLockData>
+        + Dispatch<ExtSessionLockSurfaceV1, LockSurfaceData>
+        + SessionLockHandler
+        + 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<ExtSessionLockManagerV1>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl<D> Dispatch<ExtSessionLockManagerV1, (), D> for SessionLockManagerState
+where
+    D: Dispatch<ExtSessionLockManagerV1, ()>
+        + Dispatch<ExtSessionLockV1, SessionLockData>
+        + Dispatch<ExtSessionLockSurfaceV1, LockSurfaceData>
+        + SessionLockHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        _manager: &ExtSessionLockManagerV1,
+        request: ext_session_lock_manager_v1::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_session_lock_manager_v1::Request::Lock { id } => {
+                let lock = data_init.init(
+                    id,
+                    SessionLockData {
+                        locked: AtomicBool::new(false),
+                        outputs: Mutex::new(Vec::new()),
+                    },
+                );
+
+                let lock_state = state.lock_state();
+                {
+                    let mut current_lock = lock_state.lock.lock().unwrap();
+                    // only a single client may hold the lock at a time
+                    if current_lock.is_some() {
+                        lock.finished();
+                        return;
+                    }
+                    *current_lock = Some(lock.clone());
+                }
+
+                let locker = SessionLocker {
+                    lock,
+                    locked: lock_state.locked.clone(),
+                    current_lock: lock_state.lock.clone(),
+                    done: false,
+                };
+                state.lock(locker);
+            }
+            ext_session_lock_manager_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ExtSessionLockV1, SessionLockData, D> for SessionLockManagerState
+where
+    D: Dispatch<ExtSessionLockV1, SessionLockData>
+        + Dispatch<ExtSessionLockSurfaceV1, LockSurfaceData>
+        + SessionLockHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        lock: &ExtSessionLockV1,
+        request: ext_session_lock_v1::Request,
+        data: &SessionLockData,
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_session_lock_v1::Request::GetLockSurface { id, surface, output } => {
+                let mut outputs = data.outputs.lock().unwrap();
+                if outputs.contains(&output) {
+                    lock.post_error(
+                        ext_session_lock_v1::Error::DuplicateOutput,
+                        "The output already has a lock surface",
+                    );
+                    return;
+                }
+
+                let has_buffer = compositor::with_states(&surface, |states| {
+                    states
+                        .cached_state
+                        .pending::<SurfaceAttributes>()
+                        .buffer
+                        .is_some()
+                        || states
+                            .cached_state
+                            .current::<SurfaceAttributes>()
+                            .buffer
+                            .is_some()
+                });
+                if has_buffer {
+                    lock.post_error(
+                        ext_session_lock_v1::Error::AlreadyConstructed,
+                        "The surface already has a buffer attached",
+                    );
+                    return;
+                }
+
+                if compositor::give_role(&surface, LOCK_SURFACE_ROLE).is_err() {
+                    lock.post_error(ext_session_lock_v1::Error::Role, "Surface already has a role.");
+                    return;
+                }
+                outputs.push(output.clone());
+                drop(outputs);
+
+                let lock_surface = data_init.init(
+                    id,
+                    LockSurfaceData {
+                        lock: lock.clone(),
+                        output: output.clone(),
+                        surface: surface.clone(),
+                    },
+                );
+
+                compositor::with_states(&surface, |states| {
+                    states.data_map.insert_if_missing_threadsafe(|| {
+                        Mutex::new(LockSurfaceAttributes::new(lock_surface.clone()))
+                    });
+                });
+                compositor::add_pre_commit_hook(&surface, |_dh, surface| {
+                    compositor::with_states(surface, |states| {
+                        let mut attributes = states
+                            .data_map
+                            .get::<Mutex<LockSurfaceAttributes>>()
+                            .unwrap()
+                            .lock()
+                            .unwrap();
+
+                        if !attributes.configured {
+                            attributes.surface.post_error(
+                                ext_session_lock_surface_v1::Error::CommitBeforeFirstAck,
+                                "Committed before the first ack_configure",
+                            );
+                            return;
+                        }
+
+                        let pending = states.cached_state.pending::<SurfaceAttributes>();
+                        if matches!(pending.buffer, Some(BufferAssignment::Removed)) {
+                            attributes.surface.post_error(
+                                ext_session_lock_surface_v1::Error::NullBuffer,
+                                "Lock surfaces must always have a buffer attached",
+                            );
+                            return;
+                        }
+
+                        if let Some(state) = attributes.last_acked.clone() {
+                            attributes.current = state;
+                        }
+                    });
+                });
+
+                let handle = LockSurface {
+                    surface: lock_surface,
+                    wl_surface: surface,
+                };
+                state.new_surface(handle, output);
+            }
+            ext_session_lock_v1::Request::UnlockAndDestroy => {
+                if !data.locked.load(Ordering::SeqCst) {
+                    lock.post_error(
+                        ext_session_lock_v1::Error::InvalidUnlock,
+                        "The session was never locked",
+                    );
+                    return;
+                }
+
+                let lock_state = state.lock_state();
+                lock_state.locked.store(false, Ordering::SeqCst);
+                *lock_state.lock.lock().unwrap() = None;
+                state.unlock();
+            }
+            ext_session_lock_v1::Request::Destroy => {
+                if data.locked.load(Ordering::SeqCst) {
+                    lock.post_error(
+                        ext_session_lock_v1::Error::InvalidDestroy,
+                        "A locked session must be unlocked with unlock_and_destroy",
+                    );
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(state: &mut D, _client: ClientId, id: ObjectId, _data: &SessionLockData) {
+        // the session stays locked, if the locking client goes away without unlocking it
+        let mut current_lock = state.lock_state().lock.lock().unwrap();
+        if current_lock.as_ref().map(|lock| lock.id() == id).unwrap_or(false) {
+            *current_lock = None;
+        }
+    }
+}
+
+impl<D> Dispatch<ExtSessionLockSurfaceV1, LockSurfaceData, D> for SessionLockManagerState
+where
+    D: Dispatch<ExtSessionLockSurfaceV1, LockSurfaceData> + SessionLockHandler + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        lock_surface: &ExtSessionLockSurfaceV1,
+        request: ext_session_lock_surface_v1::Request,
+        data: &LockSurfaceData,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_session_lock_surface_v1::Request::AckConfigure { serial } => {
+                let serial = Serial::from(serial);
+                let configure = compositor::with_states(&data.surface, |states| {
+                    states
+                        .data_map
+                        .get::<Mutex<LockSurfaceAttributes>>()
+                        .unwrap()
+                        .lock()
+                        .unwrap()
+                        .ack_configure(serial)
+                });
+
+                if configure.is_none() {
+                    lock_surface.post_error(
+                        ext_session_lock_surface_v1::Error::InvalidSerial,
+                        format!("wrong configure serial: {}", <u32>::from(serial)),
+                    );
+                }
+            }
+            ext_session_lock_surface_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(_state: &mut D, _client: ClientId, _id: ObjectId, data: &LockSurfaceData) {
+        if let Some(lock_data) = data.lock.data::<SessionLockData>() {
+            lock_data
+                .outputs
+                .lock()
+                .unwrap()
+                .retain(|output| output != &data.output);
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! Utilities for handling the `ext-session-lock` protocol
+//!
+//! This protocol allows a privileged client (like swaylock) to lock the session, hiding
+//! the contents of all outputs and redirecting all input to the surfaces of the locking client.
+//!
+//! When a client requests to lock the session, [`SessionLockHandler::lock`] is called with a
+//! [`SessionLocker`]. Once your compositor has stopped displaying anything but the lock surfaces
+//! and blanked all outputs, it confirms the lock using [`SessionLocker::lock`].
+//! Dropping the [`SessionLocker`] instead rejects the request.
+//!
+//! The locking client then creates a [`LockSurface`] for every output, which is announced through
+//! [`SessionLockHandler::new_surface`] and needs to be configured with the size of the output.
+//!
+//! While the session is locked (see [`SessionLockManagerState::is_locked`]) your compositor
+//! must not render anything but lock surfaces (or a solid color on outputs without one) and must
+//! only send input events to lock surfaces. The session stays locked until the locking client
+//! explicitly unlocks it, which is signaled by [`SessionLockHandler::unlock`]. If the locking client
+//! crashes, the session stays locked and a new client may take over the lock.
+//!
+//! ```no_run
+//! # extern crate wayland_server;
+//! use smithay::delegate_session_lock;
+//! use smithay::reexports::wayland_server::protocol::wl_output::WlOutput;
+//! use smithay::wayland::session_lock::{
+//!     LockSurface, SessionLockHandler, SessionLockManagerState, SessionLocker,
+//! };
+//!
+//! pub struct State {
+//!     session_lock_state: SessionLockManagerState,
+//! }
+//!
+//! impl SessionLockHandler for State {
+//!     fn lock_state(&mut self) -> &mut SessionLockManagerState {
+//!         &mut self.session_lock_state
+//!     }
+//!
+//!     fn lock(&mut self, confirmation: SessionLocker) {
+//!         // hide the contents of all outputs, then confirm the lock
+//!         confirmation.lock();
+//!     }
+//!
+//!     fn unlock(&mut self) {
+//!         // show the regular contents of the outputs again
+//!     }
+//!
+//!     fn new_surface(&mut self, surface: LockSurface, output: WlOutput) {
+//!         surface.with_pending_state(|state| {
+//!             state.size = Some((1920, 1080).into());
+//!         });
+//!         surface.send_configure();
+//!     }
+//! }
+//!
+//! delegate_session_lock!(State);
+//!
+//! # let mut display = wayland_server::Display::<State>::new().unwrap();
+//! # let display_handle = display.handle();
+//! let session_lock_state = SessionLockManagerState::new::<State>(&display_handle);
+//! ```
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+
+use wayland_protocols::ext::session_lock::v1::server::{
+    ext_session_lock_manager_v1::ExtSessionLockManagerV1,
+    ext_session_lock_surface_v1::ExtSessionLockSurfaceV1, ext_session_lock_v1::ExtSessionLockV1,
+};
+use wayland_server::{
+    backend::GlobalId,
+    protocol::{wl_output::WlOutput, wl_surface::WlSurface},
+    Dispatch, DisplayHandle, GlobalDispatch, Resource,
+};
+
+mod dispatch;
+mod surface;
+
+pub use surface::{LockSurface, LockSurfaceAttributes, LockSurfaceConfigure, LockSurfaceState};
+
+/// The role of a session lock surface
+pub const LOCK_SURFACE_ROLE: &str = "ext_session_lock_surface_v1";
+
+/// State of the ext-session-lock global
+#[derive(Debug)]
+pub struct SessionLockManagerState {
+    global: GlobalId,
+    locked: Arc<AtomicBool>,
+    lock: Arc<Mutex<Option<ExtSessionLockV1>>>,
+}
+
+impl SessionLockManagerState {
+    /// Create a new `ext_session_lock_manager_v1` global
+    pub fn new<D>(display: &DisplayHandle) -> SessionLockManagerState
+    where
+        D: GlobalDispatch<ExtSessionLockManagerV1, ()>
+            + Dispatch<ExtSessionLockManagerV1, ()>
+            + Dispatch<ExtSessionLockV1, SessionLockData>
+            + Dispatch<ExtSessionLockSurfaceV1, LockSurfaceData>
+            + SessionLockHandler
+            + 'static,
+    {
+        let global = display.create_global::<D, ExtSessionLockManagerV1, _>(1, ());
+
+        SessionLockManagerState {
+            global,
+            locked: Arc::new(AtomicBool::new(false)),
+            lock: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Returns the id of the `ext_session_lock_manager_v1` global
+    pub fn global(&self) -> GlobalId {
+        self.global.clone()
+    }
+
+    /// Returns whether the session is currently locked
+    ///
+    /// The session is locked from the moment the lock was confirmed with [`SessionLocker::lock`],
+    /// until the locking client unlocks it again. This includes the time after a crash
+    /// of the locking client.
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::SeqCst)
+    }
+}
+
+/// Handler trait for the ext-session-lock protocol
+pub trait SessionLockHandler {
+    /// [`SessionLockManagerState`] getter
+    fn lock_state(&mut self) -> &mut SessionLockManagerState;
+
+    /// A client requested to lock the session
+    ///
+    /// Call [`SessionLocker::lock`] once nothing but lock surfaces is displayed anymore.
+    /// Dropping the `confirmation` denies the request.
+    fn lock(&mut self, confirmation: SessionLocker);
+
+    /// The locking client unlocked the session
+    fn unlock(&mut self);
+
+    /// The locking client created a new lock surface for an output
+    ///
+    /// The surface needs to be configured with the size of the output using
+    /// [`LockSurface::with_pending_state`] and [`LockSurface::send_configure`].
+    fn new_surface(&mut self, surface: LockSurface, output: WlOutput);
+}
+
+/// A pending request to lock the session
+///
+/// Dropping this object without calling [`SessionLocker::lock`] denies the request.
+#[derive(Debug)]
+pub struct SessionLocker {
+    lock: ExtSessionLockV1,
+    locked: Arc<AtomicBool>,
+    current_lock: Arc<Mutex<Option<ExtSessionLockV1>>>,
+    done: bool,
+}
+
+impl SessionLocker {
+    /// Confirm that the session is locked
+    ///
+    /// This must only be called after the contents of all outputs have been hidden.
+    pub fn lock(mut self) {
+        self.done = true;
+        // the client destroyed the lock before it was confirmed
+        if !self.lock.is_alive() {
+            return;
+        }
+
+        self.locked.store(true, Ordering::SeqCst);
+        if let Some(data) = self.lock.data::<SessionLockData>() {
+            data.locked.store(true, Ordering::SeqCst);
+        }
+        self.lock.locked();
+    }
+
+    /// Returns the `ext_session_lock_v1` object of this request
+    pub fn ext_session_lock(&self) -> &ExtSessionLockV1 {
+        &self.lock
+    }
+}
+
+impl Drop for SessionLocker {
+    fn drop(&mut self) {
+        if !self.done {
+            self.lock.finished();
+
+            // allow other clients to lock the session again
+            let mut current_lock = self.current_lock.lock().unwrap();
+            if current_lock.as_ref() == Some(&self.lock) {
+                *current_lock = None;
+            }
+        }
+    }
+}
+
+/// User data of an `ext_session_lock_v1`
+#[derive(Debug)]
+pub struct SessionLockData {
+    /// Whether the locked event was sent
+    locked: AtomicBool,
+    /// Outputs having a lock surface assigned
+    outputs: Mutex<Vec<WlOutput>>,
+}
+
+/// User data of an `ext_session_lock_surface_v1`
+#[derive(Debug)]
+pub struct LockSurfaceData {
+    lock: ExtSessionLockV1,
+    output: WlOutput,
+    surface: WlSurface,
+}
+
+/// Macro to delegate implementation of the ext-session-lock protocol to [`SessionLockManagerState`].
+///
+/// You must also implement [`SessionLockHandler`] to use this.
+#[macro_export]
+macro_rules! delegate_session_lock {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::ext::session_lock::v1::server::ext_session_lock_manager_v1::ExtSessionLockManagerV1: ()
+        ] => $crate::wayland::session_lock::SessionLockManagerState);
+
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::ext::session_lock::v1::server::ext_session_lock_manager_v1::ExtSessionLockManagerV1: ()
+        ] => $crate::wayland::session_lock::SessionLockManagerState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::ext::session_lock::v1::server::ext_session_lock_v1::ExtSessionLockV1: $crate::wayland::session_lock::SessionLockData
+        ] => $crate::wayland::session_lock::SessionLockManagerState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::ext::session_lock::v1::server::ext_session_lock_surface_v1::ExtSessionLockSurfaceV1: $crate::wayland::session_lock::LockSurfaceData
+        ] => $crate::wayland::session_lock::SessionLockManagerState);
+    };
+}
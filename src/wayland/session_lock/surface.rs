@@ -0,0 +1,165 @@
+use std::sync::Mutex;
+
+use wayland_protocols::ext::session_lock::v1::server::ext_session_lock_surface_v1::ExtSessionLockSurfaceV1;
+use wayland_server::protocol::wl_surface::WlSurface;
+
+use crate::{
+    utils::{Logical, Serial, Size, SERIAL_COUNTER},
+    wayland::compositor,
+};
+
+/// State of a lock surface, as set by the compositor
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LockSurfaceState {
+    /// The size of the surface, should match the size of the output
+    pub size: Option<Size<u32, Logical>>,
+}
+
+/// A configure message sent to a lock surface
+#[derive(Debug, Clone)]
+pub struct LockSurfaceConfigure {
+    /// The state associated with this configure
+    pub state: LockSurfaceState,
+    /// The serial of this configure
+    pub serial: Serial,
+}
+
+/// Attributes of a lock surface
+///
+/// Stored in the data map of the [`WlSurface`] of every lock surface.
+#[derive(Debug)]
+pub struct LockSurfaceAttributes {
+    pub(crate) surface: ExtSessionLockSurfaceV1,
+    /// Defines if the surface has received at least one configure
+    /// acknowledgement from the client
+    pub configured: bool,
+    /// Configures sent to the client and not yet acknowledged
+    pending_configures: Vec<LockSurfaceConfigure>,
+    /// Holds the pending state as set by the server
+    pub server_pending: Option<LockSurfaceState>,
+    /// Holds the last server_pending state that has been acknowledged
+    /// by the client. This state is applied on the next commit.
+    pub last_acked: Option<LockSurfaceState>,
+    /// Holds the current state of the lock surface after a successful commit
+    pub current: LockSurfaceState,
+}
+
+impl LockSurfaceAttributes {
+    pub(crate) fn new(surface: ExtSessionLockSurfaceV1) -> Self {
+        Self {
+            surface,
+            configured: false,
+            pending_configures: Vec::new(),
+            server_pending: None,
+            last_acked: None,
+            current: Default::default(),
+        }
+    }
+
+    pub(crate) fn ack_configure(&mut self, serial: Serial) -> Option<LockSurfaceConfigure> {
+        let configure = self
+            .pending_configures
+            .iter()
+            .find(|configure| configure.serial == serial)
+            .cloned()?;
+
+        self.last_acked = Some(configure.state.clone());
+        self.configured = true;
+        self.pending_configures
+            .retain(|configure| configure.serial > serial);
+
+        Some(configure)
+    }
+}
+
+/// Handle to a lock surface
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockSurface {
+    pub(crate) surface: ExtSessionLockSurfaceV1,
+    pub(crate) wl_surface: WlSurface,
+}
+
+impl LockSurface {
+    /// Access the underlying `wl_surface` of this lock surface
+    pub fn wl_surface(&self) -> &WlSurface {
+        &self.wl_surface
+    }
+
+    /// Access the underlying `ext_session_lock_surface_v1` of this lock surface
+    pub fn ext_session_lock_surface(&self) -> &ExtSessionLockSurfaceV1 {
+        &self.surface
+    }
+
+    /// Send a configure event to the lock surface with its pending state
+    ///
+    /// The serial of this configure will be tracked waiting for the client to ACK it.
+    /// No configure is sent, if the pending state did not change since the last configure.
+    pub fn send_configure(&self) {
+        let configure = compositor::with_states(&self.wl_surface, |states| {
+            let mut attributes = states
+                .data_map
+                .get::<Mutex<LockSurfaceAttributes>>()
+                .unwrap()
+                .lock()
+                .unwrap();
+
+            let state = attributes.server_pending.take()?;
+            let last_state = attributes
+                .pending_configures
+                .last()
+                .map(|configure| &configure.state)
+                .or(attributes.last_acked.as_ref());
+            if last_state == Some(&state) {
+                return None;
+            }
+
+            let configure = LockSurfaceConfigure {
+                serial: SERIAL_COUNTER.next_serial(),
+                state,
+            };
+            attributes.pending_configures.push(configure.clone());
+            Some(configure)
+        });
+
+        if let Some(configure) = configure {
+            let (width, height) = configure.state.size.unwrap_or_default().into();
+            self.surface.configure(configure.serial.into(), width, height);
+        }
+    }
+
+    /// Access the pending state of this lock surface
+    ///
+    /// The changes are sent to the client with the next [`LockSurface::send_configure`].
+    pub fn with_pending_state<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce(&mut LockSurfaceState) -> T,
+    {
+        compositor::with_states(&self.wl_surface, |states| {
+            let mut attributes = states
+                .data_map
+                .get::<Mutex<LockSurfaceAttributes>>()
+                .unwrap()
+                .lock()
+                .unwrap();
+            if attributes.server_pending.is_none() {
+                attributes.server_pending = Some(attributes.current.clone());
+            }
+
+            f(attributes.server_pending.as_mut().unwrap())
+        })
+    }
+
+    /// Gets a copy of the current state of this lock surface
+    pub fn current_state(&self) -> LockSurfaceState {
+        compositor::with_states(&self.wl_surface, |states| {
+            states
+                .data_map
+                .get::<Mutex<LockSurfaceAttributes>>()
+                .unwrap()
+                .lock()
+                .unwrap()
+                .current
+                .clone()
+        })
+    }
+}
@@ -8,7 +8,7 @@ use crate::{
     utils::{Rectangle, Serial},
     wayland::{
         compositor,
-        shell::xdg::{PopupState, XdgShellState, XDG_POPUP_ROLE, XDG_TOPLEVEL_ROLE},
+        shell::xdg::{PopupState, ToplevelState, XdgShellState, XDG_POPUP_ROLE, XDG_TOPLEVEL_ROLE},
     },
 };
 
@@ -93,7 +93,10 @@ where
 
                 compositor::with_states(surface, |states| {
                     states.data_map.insert_if_missing_threadsafe(|| {
-                        Mutex::new(XdgToplevelSurfaceRoleAttributes::default())
+                        Mutex::new(XdgToplevelSurfaceRoleAttributes {
+                            server_pending: Some(ToplevelState::default()),
+                            ..Default::default()
+                        })
                     })
                 });
 
@@ -121,6 +124,11 @@ where
 
                 let handle = make_toplevel_handle(&toplevel);
 
+                let capabilities = XdgShellHandler::capabilities(state, &handle);
+                handle.with_pending_state(|pending| {
+                    pending.capabilities = capabilities;
+                });
+
                 XdgShellHandler::new_toplevel(state, handle);
             }
             xdg_surface::Request::GetPopup {
@@ -191,8 +191,30 @@ where
     })
 }
 
-pub fn send_toplevel_configure(resource: &xdg_toplevel::XdgToplevel, configure: ToplevelConfigure) {
+pub fn send_toplevel_configure(
+    resource: &xdg_toplevel::XdgToplevel,
+    configure: ToplevelConfigure,
+    send_capabilities: bool,
+) {
     let data = resource.data::<XdgShellSurfaceUserData>().unwrap();
+
+    if let Some(bounds) = configure.state.bounds {
+        if resource.version() >= xdg_toplevel::EVT_CONFIGURE_BOUNDS_SINCE {
+            resource.configure_bounds(bounds.w, bounds.h);
+        }
+    }
+
+    if send_capabilities && resource.version() >= xdg_toplevel::EVT_WM_CAPABILITIES_SINCE {
+        // same conversion as for the states below
+        let capabilities = configure
+            .state
+            .capabilities
+            .capabilities()
+            .flat_map(|cap| (*cap as u32).to_ne_bytes())
+            .collect::<Vec<u8>>();
+        resource.wm_capabilities(capabilities);
+    }
+
     let (width, height) = configure.state.size.unwrap_or_default().into();
     // convert the Vec<State> (which is really a Vec<u32>) into Vec<u8>
     let states = {
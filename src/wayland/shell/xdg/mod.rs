@@ -127,6 +127,12 @@ pub const XDG_POPUP_ROLE: &str = "xdg_popup";
 /// Constant for toplevel state version checking
 const XDG_TOPLEVEL_STATE_TILED_SINCE: u32 = 2;
 
+/// Constant for toplevel state version checking
+const XDG_TOPLEVEL_STATE_SUSPENDED_SINCE: u32 = 6;
+
+/// Highest supported version of the `xdg_wm_base` global
+const XDG_WM_BASE_VERSION: u32 = 6;
+
 macro_rules! xdg_role {
     ($state:ty,
      $(#[$configure_meta:meta])* $configure_name:ident $({$($(#[$configure_field_meta:meta])* $configure_field_vis:vis$configure_field_name:ident:$configure_field_type:ty),*}),*,
@@ -560,8 +566,8 @@ impl PositionerState {
     /// The position is calculated according to the rules defined
     /// in the `xdg_shell` protocol.
     /// The `constraint_adjustment` will not be considered by this
-    /// implementation and the position and size should be re-calculated
-    /// in the compositor if the compositor implements `constraint_adjustment`
+    /// function, use [`PositionerState::get_unconstrained_geometry`]
+    /// to fit the popup into a given target area.
     pub fn get_geometry(&self) -> Rectangle<i32, Logical> {
         // From the `xdg_shell` prococol specification:
         //
@@ -619,6 +625,198 @@ impl PositionerState {
 
         geometry
     }
+
+    /// Get the geometry for a popup as defined by this positioner, after trying to fit
+    /// the popup into the provided target rectangle.
+    ///
+    /// The target rectangle is expected to be in the same coordinate space as the
+    /// geometry returned by [`PositionerState::get_geometry`], i.e. relative to the
+    /// parent surface `window_geometry`. Usually this is the area of the output the
+    /// parent is shown on, or the bounds of the parent window itself.
+    ///
+    /// The adjustments are applied in the order defined by the `xdg_shell` protocol:
+    /// flipping, then sliding, then resizing. An axis without a matching
+    /// `constraint_adjustment` stays constrained.
+    pub fn get_unconstrained_geometry(mut self, target: Rectangle<i32, Logical>) -> Rectangle<i32, Logical> {
+        let mut geo = self.get_geometry();
+        let (mut off_left, mut off_right, mut off_top, mut off_bottom) = compute_offsets(target, geo);
+
+        // Try to flip the popup on the x-axis, but only keep the result
+        // if it is no longer constrained afterwards
+        if (off_left > 0 || off_right > 0)
+            && self
+                .constraint_adjustment
+                .contains(xdg_positioner::ConstraintAdjustment::FlipX)
+        {
+            let old_anchor = self.anchor_edges;
+            let old_gravity = self.gravity;
+            self.anchor_edges = invert_anchor_x(self.anchor_edges);
+            self.gravity = invert_gravity_x(self.gravity);
+
+            let new_geo = self.get_geometry();
+            let (new_off_left, new_off_right, _, _) = compute_offsets(target, new_geo);
+            if new_off_left > 0 || new_off_right > 0 {
+                self.anchor_edges = old_anchor;
+                self.gravity = old_gravity;
+            } else {
+                geo = new_geo;
+                off_left = new_off_left;
+                off_right = new_off_right;
+            }
+        }
+
+        // Same for the y-axis
+        if (off_top > 0 || off_bottom > 0)
+            && self
+                .constraint_adjustment
+                .contains(xdg_positioner::ConstraintAdjustment::FlipY)
+        {
+            let old_anchor = self.anchor_edges;
+            let old_gravity = self.gravity;
+            self.anchor_edges = invert_anchor_y(self.anchor_edges);
+            self.gravity = invert_gravity_y(self.gravity);
+
+            let new_geo = self.get_geometry();
+            let (_, _, new_off_top, new_off_bottom) = compute_offsets(target, new_geo);
+            if new_off_top > 0 || new_off_bottom > 0 {
+                self.anchor_edges = old_anchor;
+                self.gravity = old_gravity;
+            } else {
+                geo = new_geo;
+                off_top = new_off_top;
+                off_bottom = new_off_bottom;
+            }
+        }
+
+        // Slide the popup along the x-axis. If it does not fit at all, the
+        // left edge is kept visible.
+        if (off_left > 0 || off_right > 0)
+            && self
+                .constraint_adjustment
+                .contains(xdg_positioner::ConstraintAdjustment::SlideX)
+        {
+            if off_left > 0 {
+                geo.loc.x += off_left;
+            } else if off_right > 0 {
+                geo.loc.x -= i32::min(off_right, -off_left);
+            }
+        }
+
+        // Slide the popup along the y-axis. If it does not fit at all, the
+        // top edge is kept visible.
+        if (off_top > 0 || off_bottom > 0)
+            && self
+                .constraint_adjustment
+                .contains(xdg_positioner::ConstraintAdjustment::SlideY)
+        {
+            if off_top > 0 {
+                geo.loc.y += off_top;
+            } else if off_bottom > 0 {
+                geo.loc.y -= i32::min(off_bottom, -off_top);
+            }
+        }
+
+        // Finally shrink the popup to whatever is still sticking out
+        let (off_left, off_right, off_top, off_bottom) = compute_offsets(target, geo);
+
+        if (off_left > 0 || off_right > 0)
+            && self
+                .constraint_adjustment
+                .contains(xdg_positioner::ConstraintAdjustment::ResizeX)
+        {
+            let mut new_geo = geo;
+            if off_left > 0 {
+                new_geo.loc.x += off_left;
+                new_geo.size.w -= off_left;
+            }
+            if off_right > 0 {
+                new_geo.size.w -= off_right;
+            }
+            // the popup has to keep a positive size, otherwise it stays constrained
+            if new_geo.size.w > 0 {
+                geo = new_geo;
+            }
+        }
+
+        if (off_top > 0 || off_bottom > 0)
+            && self
+                .constraint_adjustment
+                .contains(xdg_positioner::ConstraintAdjustment::ResizeY)
+        {
+            let mut new_geo = geo;
+            if off_top > 0 {
+                new_geo.loc.y += off_top;
+                new_geo.size.h -= off_top;
+            }
+            if off_bottom > 0 {
+                new_geo.size.h -= off_bottom;
+            }
+            if new_geo.size.h > 0 {
+                geo = new_geo;
+            }
+        }
+
+        geo
+    }
+}
+
+/// Returns how far `geo` sticks out of `target` on the left, right, top and bottom.
+///
+/// Negative values describe the free space between the edges.
+fn compute_offsets(target: Rectangle<i32, Logical>, geo: Rectangle<i32, Logical>) -> (i32, i32, i32, i32) {
+    let off_left = target.loc.x - geo.loc.x;
+    let off_right = (geo.loc.x + geo.size.w) - (target.loc.x + target.size.w);
+    let off_top = target.loc.y - geo.loc.y;
+    let off_bottom = (geo.loc.y + geo.size.h) - (target.loc.y + target.size.h);
+    (off_left, off_right, off_top, off_bottom)
+}
+
+fn invert_anchor_x(anchor: xdg_positioner::Anchor) -> xdg_positioner::Anchor {
+    match anchor {
+        xdg_positioner::Anchor::Left => xdg_positioner::Anchor::Right,
+        xdg_positioner::Anchor::Right => xdg_positioner::Anchor::Left,
+        xdg_positioner::Anchor::TopLeft => xdg_positioner::Anchor::TopRight,
+        xdg_positioner::Anchor::TopRight => xdg_positioner::Anchor::TopLeft,
+        xdg_positioner::Anchor::BottomLeft => xdg_positioner::Anchor::BottomRight,
+        xdg_positioner::Anchor::BottomRight => xdg_positioner::Anchor::BottomLeft,
+        x => x,
+    }
+}
+
+fn invert_anchor_y(anchor: xdg_positioner::Anchor) -> xdg_positioner::Anchor {
+    match anchor {
+        xdg_positioner::Anchor::Top => xdg_positioner::Anchor::Bottom,
+        xdg_positioner::Anchor::Bottom => xdg_positioner::Anchor::Top,
+        xdg_positioner::Anchor::TopLeft => xdg_positioner::Anchor::BottomLeft,
+        xdg_positioner::Anchor::BottomLeft => xdg_positioner::Anchor::TopLeft,
+        xdg_positioner::Anchor::TopRight => xdg_positioner::Anchor::BottomRight,
+        xdg_positioner::Anchor::BottomRight => xdg_positioner::Anchor::TopRight,
+        x => x,
+    }
+}
+
+fn invert_gravity_x(gravity: xdg_positioner::Gravity) -> xdg_positioner::Gravity {
+    match gravity {
+        xdg_positioner::Gravity::Left => xdg_positioner::Gravity::Right,
+        xdg_positioner::Gravity::Right => xdg_positioner::Gravity::Left,
+        xdg_positioner::Gravity::TopLeft => xdg_positioner::Gravity::TopRight,
+        xdg_positioner::Gravity::TopRight => xdg_positioner::Gravity::TopLeft,
+        xdg_positioner::Gravity::BottomLeft => xdg_positioner::Gravity::BottomRight,
+        xdg_positioner::Gravity::BottomRight => xdg_positioner::Gravity::BottomLeft,
+        x => x,
+    }
+}
+
+fn invert_gravity_y(gravity: xdg_positioner::Gravity) -> xdg_positioner::Gravity {
+    match gravity {
+        xdg_positioner::Gravity::Top => xdg_positioner::Gravity::Bottom,
+        xdg_positioner::Gravity::Bottom => xdg_positioner::Gravity::Top,
+        xdg_positioner::Gravity::TopLeft => xdg_positioner::Gravity::BottomLeft,
+        xdg_positioner::Gravity::BottomLeft => xdg_positioner::Gravity::TopLeft,
+        xdg_positioner::Gravity::TopRight => xdg_positioner::Gravity::BottomRight,
+        xdg_positioner::Gravity::BottomRight => xdg_positioner::Gravity::TopRight,
+        x => x,
+    }
 }
 
 /// State of a regular toplevel surface
@@ -635,6 +833,19 @@ pub struct ToplevelState {
 
     /// The xdg decoration mode of the surface
     pub decoration_mode: Option<zxdg_toplevel_decoration_v1::Mode>,
+
+    /// The suggested bounds of the surface
+    ///
+    /// This is usually the size of the output the surface is shown on,
+    /// minus any exclusive zones. Only sent to clients binding version 4
+    /// or higher of `xdg_wm_base`.
+    pub bounds: Option<Size<i32, Logical>>,
+
+    /// The capabilities of the compositor for this surface
+    ///
+    /// Only sent to clients binding version 5 or higher of `xdg_wm_base`,
+    /// and only if they changed since the last configure.
+    pub capabilities: WmCapabilitySet,
 }
 
 impl Clone for ToplevelState {
@@ -644,6 +855,8 @@ impl Clone for ToplevelState {
             states: self.states.clone(),
             size: self.size,
             decoration_mode: self.decoration_mode,
+            bounds: self.bounds,
+            capabilities: self.capabilities.clone(),
         }
     }
 }
@@ -693,34 +906,36 @@ impl ToplevelStateSet {
     /// Filter the states according to the provided version
     /// of the [`XdgToplevel`]
     pub(crate) fn into_filtered_states(self, version: u32) -> Vec<xdg_toplevel::State> {
-        // If the client version supports the tiled states
+        // If the client version supports all states
         // we can directly return the states which will save
         // us from allocating another vector
-        if version >= XDG_TOPLEVEL_STATE_TILED_SINCE {
+        if version >= XDG_TOPLEVEL_STATE_SUSPENDED_SINCE {
             return self.states;
         }
 
-        let is_tiled = |state: &xdg_toplevel::State| {
-            matches!(
-                state,
-                xdg_toplevel::State::TiledTop
-                    | xdg_toplevel::State::TiledBottom
-                    | xdg_toplevel::State::TiledLeft
-                    | xdg_toplevel::State::TiledRight
-            )
+        let is_unsupported = |state: &xdg_toplevel::State| match state {
+            xdg_toplevel::State::TiledTop
+            | xdg_toplevel::State::TiledBottom
+            | xdg_toplevel::State::TiledLeft
+            | xdg_toplevel::State::TiledRight => version < XDG_TOPLEVEL_STATE_TILED_SINCE,
+            xdg_toplevel::State::Suspended => version < XDG_TOPLEVEL_STATE_SUSPENDED_SINCE,
+            _ => false,
         };
 
-        let contains_tiled = self.states.iter().any(|state| is_tiled(state));
+        let contains_unsupported = self.states.iter().any(|state| is_unsupported(state));
 
-        // If the states do not contain a tiled state
+        // If the states do not contain an unsupported state
         // we can directly return the states which will save
         // us from allocating another vector
-        if !contains_tiled {
+        if !contains_unsupported {
             return self.states;
         }
 
         // We need to filter out the unsupported states
-        self.states.into_iter().filter(|state| !is_tiled(state)).collect()
+        self.states
+            .into_iter()
+            .filter(|state| !is_unsupported(state))
+            .collect()
     }
 }
 
@@ -739,6 +954,75 @@ impl From<ToplevelStateSet> for Vec<xdg_toplevel::State> {
     }
 }
 
+/// Container holding the [`xdg_toplevel::WmCapabilities`] for a toplevel
+///
+/// This container will prevent the toplevel from
+/// having the same capability multiple times.
+///
+/// Capabilities not contained in the set are advertised as unsupported
+/// to clients binding version 5 or higher of `xdg_wm_base`, which can
+/// then for example hide their minimize button.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct WmCapabilitySet {
+    capabilities: Vec<xdg_toplevel::WmCapabilities>,
+}
+
+impl WmCapabilitySet {
+    /// Returns `true` if the set contains a capability.
+    pub fn contains(&self, capability: xdg_toplevel::WmCapabilities) -> bool {
+        self.capabilities.iter().any(|c| *c == capability)
+    }
+
+    /// Adds a capability to the set.
+    ///
+    /// If the set did not have this capability present, `true` is returned.
+    ///
+    /// If the set did have this capability present, `false` is returned.
+    pub fn set(&mut self, capability: xdg_toplevel::WmCapabilities) -> bool {
+        if self.contains(capability) {
+            false
+        } else {
+            self.capabilities.push(capability);
+            true
+        }
+    }
+
+    /// Removes a capability from the set. Returns whether the capability was
+    /// present in the set.
+    pub fn unset(&mut self, capability: xdg_toplevel::WmCapabilities) -> bool {
+        if !self.contains(capability) {
+            false
+        } else {
+            self.capabilities.retain(|c| *c != capability);
+            true
+        }
+    }
+
+    /// Replaces the capabilities in the set.
+    pub fn replace(&mut self, capabilities: impl IntoIterator<Item = xdg_toplevel::WmCapabilities>) {
+        self.capabilities.clear();
+        for capability in capabilities {
+            self.set(capability);
+        }
+    }
+
+    /// Returns an iterator over the capabilities in the set.
+    pub fn capabilities(&self) -> impl Iterator<Item = &xdg_toplevel::WmCapabilities> {
+        self.capabilities.iter()
+    }
+}
+
+impl<T> From<T> for WmCapabilitySet
+where
+    T: IntoIterator<Item = xdg_toplevel::WmCapabilities>,
+{
+    fn from(capabilities: T) -> Self {
+        let mut set = WmCapabilitySet::default();
+        set.replace(capabilities);
+        set
+    }
+}
+
 /// Represents the client pending state
 #[derive(Debug, Default, Clone, Copy)]
 pub struct SurfaceCachedState {
@@ -789,8 +1073,21 @@ pub trait XdgShellHandler {
     ///
     /// You likely need to send a [`ToplevelConfigure`] to the surface, to hint the
     /// client as to how its window should be sized.
+    ///
+    /// The pending state of the surface already contains the capabilities returned by
+    /// [`XdgShellHandler::capabilities`], they can still be adjusted using
+    /// [`ToplevelSurface::with_pending_state`] before the initial configure is sent.
     fn new_toplevel(&mut self, surface: ToplevelSurface);
 
+    /// The capabilities to advertise to a new toplevel surface
+    ///
+    /// Called right before [`XdgShellHandler::new_toplevel`], the result is sent with the
+    /// initial configure. Defaults to the [default capabilities](XdgShellState::default_capabilities)
+    /// of the [`XdgShellState`].
+    fn capabilities(&mut self, surface: &ToplevelSurface) -> WmCapabilitySet {
+        self.xdg_shell_state().default_capabilities().clone()
+    }
+
     /// A new popup surface was created
     ///
     /// You likely need to send a [`PopupConfigure`] to the surface, to hint the
@@ -892,18 +1189,46 @@ pub(crate) struct InnerState {
 pub struct XdgShellState {
     inner: Arc<Mutex<InnerState>>,
     global: GlobalId,
+    default_capabilities: WmCapabilitySet,
     _log: slog::Logger,
 }
 
 impl XdgShellState {
     /// Create a new `xdg_shell` global
+    ///
+    /// New toplevels will advertise all [`xdg_toplevel::WmCapabilities`].
     pub fn new<D, L>(display: &DisplayHandle, logger: L) -> XdgShellState
     where
         L: Into<Option<::slog::Logger>>,
         D: GlobalDispatch<XdgWmBase, ()> + 'static,
+    {
+        Self::new_with_capabilities::<D, _, _>(
+            display,
+            [
+                xdg_toplevel::WmCapabilities::WindowMenu,
+                xdg_toplevel::WmCapabilities::Maximize,
+                xdg_toplevel::WmCapabilities::Fullscreen,
+                xdg_toplevel::WmCapabilities::Minimize,
+            ],
+            logger,
+        )
+    }
+
+    /// Create a new `xdg_shell` global with the given default capabilities
+    ///
+    /// The capabilities will be part of the initial pending state of every new toplevel.
+    pub fn new_with_capabilities<D, C, L>(
+        display: &DisplayHandle,
+        capabilities: C,
+        logger: L,
+    ) -> XdgShellState
+    where
+        C: Into<WmCapabilitySet>,
+        L: Into<Option<::slog::Logger>>,
+        D: GlobalDispatch<XdgWmBase, ()> + 'static,
     {
         let log = crate::slog_or_fallback(logger);
-        let global = display.create_global::<D, XdgWmBase, _>(3, ());
+        let global = display.create_global::<D, XdgWmBase, _>(XDG_WM_BASE_VERSION, ());
 
         XdgShellState {
             inner: Arc::new(Mutex::new(InnerState {
@@ -911,10 +1236,30 @@ impl XdgShellState {
                 known_popups: Vec::new(),
             })),
             global,
+            default_capabilities: capabilities.into(),
             _log: log.new(slog::o!("smithay_module" => "xdg_shell_handler")),
         }
     }
 
+    /// Replace the default capabilities of new toplevels
+    ///
+    /// This also updates the pending state of all known toplevels, you need to call
+    /// [`ToplevelSurface::send_configure`] for the changes to reach the clients.
+    pub fn replace_capabilities<C: Into<WmCapabilitySet>>(&mut self, capabilities: C) {
+        self.default_capabilities = capabilities.into();
+        let toplevels = self.inner.lock().unwrap().known_toplevels.clone();
+        for toplevel in toplevels {
+            toplevel.with_pending_state(|state| {
+                state.capabilities = self.default_capabilities.clone();
+            });
+        }
+    }
+
+    /// Returns the default capabilities of new toplevels
+    pub fn default_capabilities(&self) -> &WmCapabilitySet {
+        &self.default_capabilities
+    }
+
     /// Access all the shell surfaces known by this handler
     pub fn toplevel_surfaces<T, F: FnMut(&[ToplevelSurface]) -> T>(&self, mut cb: F) -> T {
         cb(&self.inner.lock().unwrap().known_toplevels)
@@ -1113,6 +1458,9 @@ impl ToplevelSurface {
                 // to the pending configures.
                 let decoration_mode_changed =
                     pending.decoration_mode != attributes.current_server_state().decoration_mode;
+                // Same for the capabilities, which always have to be part of the initial configure
+                let capabilities_changed = !attributes.initial_configure_sent
+                    || pending.capabilities != attributes.current_server_state().capabilities;
 
                 let configure = ToplevelConfigure {
                     serial: SERIAL_COUNTER.next_serial(),
@@ -1122,12 +1470,12 @@ impl ToplevelSurface {
                 attributes.pending_configures.push(configure.clone());
                 attributes.initial_configure_sent = true;
 
-                Some((configure, decoration_mode_changed))
+                Some((configure, decoration_mode_changed, capabilities_changed))
             } else {
                 None
             }
         });
-        if let Some((configure, decoration_mode_changed)) = configure {
+        if let Some((configure, decoration_mode_changed, capabilities_changed)) = configure {
             if decoration_mode_changed {
                 if let Some(data) = self.shell_surface.data::<XdgShellSurfaceUserData>() {
                     if let Some(decoration) = &*data.decoration.lock().unwrap() {
@@ -1142,7 +1490,7 @@ impl ToplevelSurface {
                 }
             }
 
-            self::handlers::send_toplevel_configure(&self.shell_surface, configure)
+            self::handlers::send_toplevel_configure(&self.shell_surface, configure, capabilities_changed)
         }
     }
 
@@ -1268,8 +1616,7 @@ impl ToplevelSurface {
             }
         }
 
-        // Unset the parent
-        handlers::set_parent(&self.shell_surface, None);
+        handlers::set_parent(&self.shell_surface, parent.cloned());
 
         true
     }
@@ -1555,6 +1902,19 @@ impl PopupSurface {
             f(server_pending)
         })
     }
+
+    /// Recompute the pending geometry of this popup, fitting it into the provided
+    /// target rectangle according to its positioner's `constraint_adjustment`.
+    ///
+    /// The target is given relative to the parent surface `window_geometry`,
+    /// see [`PositionerState::get_unconstrained_geometry`].
+    ///
+    /// The new geometry will be sent to the client when calling [`send_configure`](#method.send_configure).
+    pub fn unconstrain(&self, target: Rectangle<i32, Logical>) {
+        self.with_pending_state(|state| {
+            state.geometry = state.positioner.get_unconstrained_geometry(target);
+        });
+    }
 }
 
 /// Defines the possible configure variants
@@ -1607,3 +1967,137 @@ macro_rules! delegate_xdg_shell {
         ] => $crate::wayland::shell::xdg::XdgShellState);
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{xdg_positioner, PositionerState};
+    use crate::utils::{Logical, Rectangle};
+
+    fn target() -> Rectangle<i32, Logical> {
+        Rectangle::from_loc_and_size((0, 0), (100, 100))
+    }
+
+    /// A popup of the given width, placed to the right of the anchor rectangle at `anchor_x`
+    fn positioner(
+        anchor_x: i32,
+        width: i32,
+        constraint_adjustment: xdg_positioner::ConstraintAdjustment,
+    ) -> PositionerState {
+        PositionerState {
+            anchor_rect: Rectangle::from_loc_and_size((anchor_x, 10), (10, 10)),
+            anchor_edges: xdg_positioner::Anchor::Right,
+            gravity: xdg_positioner::Gravity::Right,
+            rect_size: (width, 10).into(),
+            constraint_adjustment,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn unconstrained_geometry_fits() {
+        let positioner = positioner(10, 30, xdg_positioner::ConstraintAdjustment::all());
+
+        assert_eq!(
+            Rectangle::from_loc_and_size((20, 10), (30, 10)),
+            positioner.get_unconstrained_geometry(target())
+        );
+    }
+
+    #[test]
+    fn unconstrained_geometry_no_adjustment() {
+        let positioner = positioner(80, 30, xdg_positioner::ConstraintAdjustment::empty());
+
+        assert_eq!(
+            positioner.get_geometry(),
+            positioner.get_unconstrained_geometry(target())
+        );
+    }
+
+    #[test]
+    fn unconstrained_geometry_flip() {
+        let positioner = positioner(80, 30, xdg_positioner::ConstraintAdjustment::FlipX);
+
+        assert_eq!(
+            Rectangle::from_loc_and_size((50, 10), (30, 10)),
+            positioner.get_unconstrained_geometry(target())
+        );
+    }
+
+    #[test]
+    fn unconstrained_geometry_flip_y() {
+        let positioner = PositionerState {
+            anchor_rect: Rectangle::from_loc_and_size((10, 80), (10, 10)),
+            anchor_edges: xdg_positioner::Anchor::Bottom,
+            gravity: xdg_positioner::Gravity::Bottom,
+            rect_size: (10, 30).into(),
+            constraint_adjustment: xdg_positioner::ConstraintAdjustment::FlipY,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Rectangle::from_loc_and_size((10, 50), (10, 30)),
+            positioner.get_unconstrained_geometry(target())
+        );
+    }
+
+    #[test]
+    fn unconstrained_geometry_slide() {
+        let positioner = positioner(80, 30, xdg_positioner::ConstraintAdjustment::SlideX);
+
+        assert_eq!(
+            Rectangle::from_loc_and_size((70, 10), (30, 10)),
+            positioner.get_unconstrained_geometry(target())
+        );
+    }
+
+    #[test]
+    fn unconstrained_geometry_resize() {
+        let positioner = positioner(80, 30, xdg_positioner::ConstraintAdjustment::ResizeX);
+
+        assert_eq!(
+            Rectangle::from_loc_and_size((90, 10), (10, 10)),
+            positioner.get_unconstrained_geometry(target())
+        );
+    }
+
+    #[test]
+    fn unconstrained_geometry_resize_keeps_size() {
+        // the popup is completely outside of the target, resizing would leave nothing of it
+        let positioner = positioner(150, 30, xdg_positioner::ConstraintAdjustment::ResizeX);
+
+        assert_eq!(
+            Rectangle::from_loc_and_size((160, 10), (30, 10)),
+            positioner.get_unconstrained_geometry(target())
+        );
+    }
+
+    #[test]
+    fn unconstrained_geometry_flip_falls_through_to_slide() {
+        // flipping would move the popup out of the left edge instead
+        let positioner = positioner(
+            40,
+            60,
+            xdg_positioner::ConstraintAdjustment::FlipX | xdg_positioner::ConstraintAdjustment::SlideX,
+        );
+
+        assert_eq!(
+            Rectangle::from_loc_and_size((40, 10), (60, 10)),
+            positioner.get_unconstrained_geometry(target())
+        );
+    }
+
+    #[test]
+    fn unconstrained_geometry_slide_falls_through_to_resize() {
+        // too wide for the target, sliding keeps the left edge visible
+        let positioner = positioner(
+            80,
+            150,
+            xdg_positioner::ConstraintAdjustment::SlideX | xdg_positioner::ConstraintAdjustment::ResizeX,
+        );
+
+        assert_eq!(
+            Rectangle::from_loc_and_size((0, 10), (100, 10)),
+            positioner.get_unconstrained_geometry(target())
+        );
+    }
+}
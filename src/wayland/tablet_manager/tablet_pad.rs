@@ -0,0 +1,445 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use wayland_protocols::wp::tablet::zv2::server::{
+    zwp_tablet_pad_group_v2::{self, ZwpTabletPadGroupV2},
+    zwp_tablet_pad_ring_v2::{self, ZwpTabletPadRingV2},
+    zwp_tablet_pad_strip_v2::{self, ZwpTabletPadStripV2},
+    zwp_tablet_pad_v2::{self, ZwpTabletPadV2},
+    zwp_tablet_seat_v2::ZwpTabletSeatV2,
+};
+use wayland_server::{
+    backend::{ClientId, ObjectId},
+    protocol::wl_surface::WlSurface,
+    Client, DataInit, Dispatch, DisplayHandle, Resource,
+};
+
+use crate::backend::input::{
+    ButtonState, Device, TabletPadAxisSource, TabletPadGroupLayout, TabletPadLayout,
+};
+use crate::utils::Serial;
+
+use super::tablet::TabletHandle;
+use super::TabletManagerState;
+
+/// Description of a graphics tablet pad device
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct TabletPadDescriptor {
+    /// Pad device name
+    pub name: String,
+    /// Path to the device
+    pub syspath: Option<PathBuf>,
+    /// Layout of the buttons, rings and strips of the pad
+    pub layout: TabletPadLayout,
+}
+
+impl<D: Device> From<&D> for TabletPadDescriptor {
+    fn from(device: &D) -> Self {
+        TabletPadDescriptor {
+            name: device.name(),
+            syspath: device.syspath(),
+            layout: device.tablet_pad_layout().unwrap_or_default(),
+        }
+    }
+}
+
+impl TabletPadDescriptor {
+    /// Groups announced to clients, every pad has to have at least one group
+    fn groups(&self) -> Vec<TabletPadGroupLayout> {
+        if self.layout.groups.is_empty() {
+            vec![TabletPadGroupLayout {
+                buttons: (0..self.layout.buttons).collect(),
+                rings: (0..self.layout.rings).collect(),
+                strips: (0..self.layout.strips).collect(),
+                modes: 1,
+            }]
+        } else {
+            self.layout.groups.clone()
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PadInstance {
+    pad: ZwpTabletPadV2,
+    groups: Vec<ZwpTabletPadGroupV2>,
+    rings: Vec<(u32, ZwpTabletPadRingV2)>,
+    strips: Vec<(u32, ZwpTabletPadStripV2)>,
+}
+
+#[derive(Debug)]
+struct TabletPad {
+    instances: Vec<PadInstance>,
+    tablet: TabletHandle,
+    focus: Option<WlSurface>,
+    /// Current mode of every group
+    modes: Vec<u32>,
+}
+
+impl TabletPad {
+    fn focused_instance(&self) -> Option<&PadInstance> {
+        let focus = self.focus.as_ref()?;
+        self.instances
+            .iter()
+            .find(|instance| instance.pad.id().same_client_as(&focus.id()))
+    }
+
+    fn set_focus(&mut self, focus: Option<WlSurface>, serial: Serial, time: u32) {
+        if self.focus == focus {
+            return;
+        }
+
+        if let Some(old_focus) = self.focus.as_ref() {
+            if let Some(instance) = self.focused_instance() {
+                instance.pad.leave(serial.into(), old_focus);
+            }
+        }
+
+        self.focus = focus;
+
+        if let Some(focus) = self.focus.as_ref() {
+            if let Some(instance) = self.focused_instance() {
+                self.tablet.with_focused_tablet(focus, |wl_tablet| {
+                    instance.pad.enter(serial.into(), wl_tablet, focus);
+                    // the current mode of every group has to follow the enter event
+                    for (group, mode) in instance.groups.iter().zip(self.modes.iter()) {
+                        group.mode_switch(time, serial.into(), *mode);
+                    }
+                });
+            }
+        }
+    }
+
+    fn button(&self, button: u32, state: ButtonState, time: u32) {
+        if let Some(instance) = self.focused_instance() {
+            instance.pad.button(time, button, state.into());
+        }
+    }
+
+    fn mode_switch(&mut self, group: u32, mode: u32, serial: Serial, time: u32) {
+        match self.modes.get_mut(group as usize) {
+            Some(current) if *current != mode => *current = mode,
+            _ => return,
+        }
+
+        if let Some(instance) = self.focused_instance() {
+            if let Some(wl_group) = instance.groups.get(group as usize) {
+                wl_group.mode_switch(time, serial.into(), mode);
+            }
+        }
+    }
+
+    fn ring(&self, ring: u32, position: Option<f64>, source: TabletPadAxisSource, time: u32) {
+        if let Some(instance) = self.focused_instance() {
+            if let Some((_, wl_ring)) = instance.rings.iter().find(|(number, _)| *number == ring) {
+                if source == TabletPadAxisSource::Finger {
+                    wl_ring.source(zwp_tablet_pad_ring_v2::Source::Finger);
+                }
+                match position {
+                    Some(degrees) => wl_ring.angle(degrees),
+                    None => wl_ring.stop(),
+                }
+                wl_ring.frame(time);
+            }
+        }
+    }
+
+    fn strip(&self, strip: u32, position: Option<f64>, source: TabletPadAxisSource, time: u32) {
+        if let Some(instance) = self.focused_instance() {
+            if let Some((_, wl_strip)) = instance.strips.iter().find(|(number, _)| *number == strip) {
+                if source == TabletPadAxisSource::Finger {
+                    wl_strip.source(zwp_tablet_pad_strip_v2::Source::Finger);
+                }
+                match position {
+                    Some(position) => wl_strip.position((position * 65535.0).round() as u32),
+                    None => wl_strip.stop(),
+                }
+                wl_strip.frame(time);
+            }
+        }
+    }
+}
+
+/// Handle to a tablet pad device
+///
+/// TabletPad represents the buttons, rings and strips of a graphics tablet,
+/// that are usually physically present on the tablet device itself.
+///
+/// Pad events are delivered to the client owning the focused surface of the pad,
+/// which usually follows the keyboard focus.
+#[derive(Debug, Clone)]
+pub struct TabletPadHandle {
+    inner: Arc<Mutex<TabletPad>>,
+}
+
+impl TabletPadHandle {
+    pub(super) fn new(desc: &TabletPadDescriptor, tablet: &TabletHandle) -> Self {
+        TabletPadHandle {
+            inner: Arc::new(Mutex::new(TabletPad {
+                instances: Vec::new(),
+                tablet: tablet.clone(),
+                focus: None,
+                modes: vec![0; desc.groups().len()],
+            })),
+        }
+    }
+
+    pub(super) fn new_instance<D>(
+        &mut self,
+        client: &Client,
+        dh: &DisplayHandle,
+        seat: &ZwpTabletSeatV2,
+        desc: &TabletPadDescriptor,
+    ) where
+        D: Dispatch<ZwpTabletPadV2, TabletPadUserData>,
+        D: Dispatch<ZwpTabletPadGroupV2, ()>,
+        D: Dispatch<ZwpTabletPadRingV2, ()>,
+        D: Dispatch<ZwpTabletPadStripV2, ()>,
+        D: 'static,
+    {
+        let wl_pad = client
+            .create_resource::<ZwpTabletPadV2, _, D>(
+                dh,
+                seat.version(),
+                TabletPadUserData { handle: self.clone() },
+            )
+            .unwrap();
+
+        seat.pad_added(&wl_pad);
+
+        let mut instance = PadInstance {
+            pad: wl_pad.clone(),
+            groups: Vec::new(),
+            rings: Vec::new(),
+            strips: Vec::new(),
+        };
+
+        for group in desc.groups() {
+            let wl_group = client
+                .create_resource::<ZwpTabletPadGroupV2, _, D>(dh, wl_pad.version(), ())
+                .unwrap();
+            wl_pad.group(&wl_group);
+
+            let buttons = group
+                .buttons
+                .iter()
+                .flat_map(|button| button.to_ne_bytes())
+                .collect();
+            wl_group.buttons(buttons);
+
+            for ring in group.rings {
+                let wl_ring = client
+                    .create_resource::<ZwpTabletPadRingV2, _, D>(dh, wl_group.version(), ())
+                    .unwrap();
+                wl_group.ring(&wl_ring);
+                instance.rings.push((ring, wl_ring));
+            }
+
+            for strip in group.strips {
+                let wl_strip = client
+                    .create_resource::<ZwpTabletPadStripV2, _, D>(dh, wl_group.version(), ())
+                    .unwrap();
+                wl_group.strip(&wl_strip);
+                instance.strips.push((strip, wl_strip));
+            }
+
+            if group.modes > 1 {
+                wl_group.modes(group.modes);
+            }
+
+            wl_group.done();
+            instance.groups.push(wl_group);
+        }
+
+        if let Some(syspath) = desc.syspath.as_ref().and_then(|p| p.to_str()) {
+            wl_pad.path(syspath.to_owned());
+        }
+
+        if desc.layout.buttons > 0 {
+            wl_pad.buttons(desc.layout.buttons);
+        }
+
+        wl_pad.done();
+
+        self.inner.lock().unwrap().instances.push(instance);
+    }
+
+    /// Notify clients that the pad was removed from the system and will send no further events
+    ///
+    /// As the pad objects of the clients keep the handle alive, this can't be done on drop.
+    pub(super) fn removed(&self) {
+        for instance in self.inner.lock().unwrap().instances.drain(..) {
+            instance.pad.removed();
+        }
+    }
+
+    /// Set the surface the pad is focused on.
+    ///
+    /// Takes care of sending the leave event to the previously focused surface and the enter event,
+    /// followed by the current mode of every group, to the newly focused one.
+    /// Usually the pad focus follows the keyboard focus.
+    pub fn set_focus(&self, focus: Option<WlSurface>, serial: Serial, time: u32) {
+        self.inner.lock().unwrap().set_focus(focus, serial, time);
+    }
+
+    /// Returns the surface the pad is focused on
+    pub fn focus(&self) -> Option<WlSurface> {
+        self.inner.lock().unwrap().focus.clone()
+    }
+
+    /// Button on the pad was pressed or released
+    pub fn button(&self, button: u32, state: ButtonState, time: u32) {
+        self.inner.lock().unwrap().button(button, state, time);
+    }
+
+    /// Switch the mode of a group of the pad
+    ///
+    /// Does nothing if the group is already in the given mode, so this can be called
+    /// with the mode reported by every pad event.
+    pub fn mode_switch(&self, group: u32, mode: u32, serial: Serial, time: u32) {
+        self.inner.lock().unwrap().mode_switch(group, mode, serial, time);
+    }
+
+    /// Ring on the pad changed its position
+    ///
+    /// `position` is the angle in degrees clockwise from the logical north of the ring,
+    /// `None` signals the end of the interaction with the ring.
+    pub fn ring(&self, ring: u32, position: Option<f64>, source: TabletPadAxisSource, time: u32) {
+        self.inner.lock().unwrap().ring(ring, position, source, time);
+    }
+
+    /// Strip on the pad changed its position
+    ///
+    /// `position` is normalized to the range `[0, 1]`,
+    /// `None` signals the end of the interaction with the strip.
+    pub fn strip(&self, strip: u32, position: Option<f64>, source: TabletPadAxisSource, time: u32) {
+        self.inner.lock().unwrap().strip(strip, position, source, time);
+    }
+}
+
+impl From<ButtonState> for zwp_tablet_pad_v2::ButtonState {
+    fn from(from: ButtonState) -> zwp_tablet_pad_v2::ButtonState {
+        match from {
+            ButtonState::Pressed => zwp_tablet_pad_v2::ButtonState::Pressed,
+            ButtonState::Released => zwp_tablet_pad_v2::ButtonState::Released,
+        }
+    }
+}
+
+/// User data of ZwpTabletPadV2 object
+#[derive(Debug)]
+pub struct TabletPadUserData {
+    handle: TabletPadHandle,
+}
+
+impl<D> Dispatch<ZwpTabletPadV2, TabletPadUserData, D> for TabletManagerState
+where
+    D: Dispatch<ZwpTabletPadV2, TabletPadUserData>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _pad: &ZwpTabletPadV2,
+        request: zwp_tablet_pad_v2::Request,
+        _data: &TabletPadUserData,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_tablet_pad_v2::Request::SetFeedback { .. } => {
+                // Feedback strings are not used
+            }
+            zwp_tablet_pad_v2::Request::Destroy => {
+                // Nothing to do
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(_state: &mut D, _client: ClientId, resource: ObjectId, data: &TabletPadUserData) {
+        data.handle
+            .inner
+            .lock()
+            .unwrap()
+            .instances
+            .retain(|i| i.pad.id() != resource);
+    }
+}
+
+impl<D> Dispatch<ZwpTabletPadGroupV2, (), D> for TabletManagerState
+where
+    D: Dispatch<ZwpTabletPadGroupV2, ()>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _group: &ZwpTabletPadGroupV2,
+        request: zwp_tablet_pad_group_v2::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_tablet_pad_group_v2::Request::Destroy => {
+                // Nothing to do
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ZwpTabletPadRingV2, (), D> for TabletManagerState
+where
+    D: Dispatch<ZwpTabletPadRingV2, ()>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _ring: &ZwpTabletPadRingV2,
+        request: zwp_tablet_pad_ring_v2::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_tablet_pad_ring_v2::Request::SetFeedback { .. } => {
+                // Feedback strings are not used
+            }
+            zwp_tablet_pad_ring_v2::Request::Destroy => {
+                // Nothing to do
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ZwpTabletPadStripV2, (), D> for TabletManagerState
+where
+    D: Dispatch<ZwpTabletPadStripV2, ()>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _strip: &ZwpTabletPadStripV2,
+        request: zwp_tablet_pad_strip_v2::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_tablet_pad_strip_v2::Request::SetFeedback { .. } => {
+                // Feedback strings are not used
+            }
+            zwp_tablet_pad_strip_v2::Request::Destroy => {
+                // Nothing to do
+            }
+            _ => unreachable!(),
+        }
+    }
+}
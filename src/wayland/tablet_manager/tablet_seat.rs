@@ -1,4 +1,8 @@
 use wayland_protocols::wp::tablet::zv2::server::{
+    zwp_tablet_pad_group_v2::ZwpTabletPadGroupV2,
+    zwp_tablet_pad_ring_v2::ZwpTabletPadRingV2,
+    zwp_tablet_pad_strip_v2::ZwpTabletPadStripV2,
+    zwp_tablet_pad_v2::ZwpTabletPadV2,
     zwp_tablet_seat_v2::{self, ZwpTabletSeatV2},
     zwp_tablet_tool_v2::ZwpTabletToolV2,
     zwp_tablet_v2::ZwpTabletV2,
@@ -13,6 +17,7 @@ use crate::input::pointer::CursorImageStatus;
 
 use super::{
     tablet::TabletUserData,
+    tablet_pad::{TabletPadDescriptor, TabletPadHandle, TabletPadUserData},
     tablet_tool::{TabletToolHandle, TabletToolUserData},
 };
 use super::{
@@ -30,6 +35,7 @@ pub(crate) struct TabletSeat {
     instances: Vec<ZwpTabletSeatV2>,
     tablets: HashMap<TabletDescriptor, TabletHandle>,
     tools: HashMap<TabletToolDescriptor, TabletToolHandle>,
+    pads: HashMap<TabletPadDescriptor, TabletPadHandle>,
 
     cursor_callback: Option<Box<dyn FnMut(&TabletToolDescriptor, CursorImageStatus) + Send>>,
 }
@@ -40,6 +46,7 @@ impl fmt::Debug for TabletSeat {
             .field("instances", &self.instances)
             .field("tablets", &self.tablets)
             .field("tools", &self.tools)
+            .field("pads", &self.pads)
             .field(
                 "cursor_callback",
                 if self.cursor_callback.is_some() {
@@ -67,6 +74,10 @@ impl TabletSeatHandle {
     where
         D: Dispatch<ZwpTabletV2, TabletUserData>,
         D: Dispatch<ZwpTabletToolV2, TabletToolUserData>,
+        D: Dispatch<ZwpTabletPadV2, TabletPadUserData>,
+        D: Dispatch<ZwpTabletPadGroupV2, ()>,
+        D: Dispatch<ZwpTabletPadRingV2, ()>,
+        D: Dispatch<ZwpTabletPadStripV2, ()>,
         D: 'static,
     {
         let mut inner = self.inner.lock().unwrap();
@@ -86,6 +97,11 @@ impl TabletSeatHandle {
             });
         }
 
+        // Notify new instance about available pads
+        for (desc, pad) in inner.pads.iter_mut() {
+            pad.new_instance::<D>(client, dh, seat, desc);
+        }
+
         inner.instances.push(seat.clone());
     }
 
@@ -209,6 +225,78 @@ impl TabletSeatHandle {
     pub fn clear_tools(&self) {
         self.inner.lock().unwrap().tools.clear();
     }
+
+    /// Add a new pad to a seat.
+    ///
+    /// Pad is usually added on [input::Event::DeviceAdded](crate::backend::input::InputEvent::DeviceAdded) event,
+    /// for devices with the [TabletPad](crate::backend::input::DeviceCapability::TabletPad) capability.
+    /// The `tablet` is the tablet the pad is attached to, it is announced to clients when the pad enters their surfaces.
+    ///
+    /// Returns new [TabletPadHandle] if pad was not know by this seat, if pad was already know it returns existing handle,
+    /// it allows you to send pad input events to clients.
+    pub fn add_pad<D>(
+        &self,
+        dh: &DisplayHandle,
+        pad_desc: &TabletPadDescriptor,
+        tablet: &TabletHandle,
+    ) -> TabletPadHandle
+    where
+        D: Dispatch<ZwpTabletPadV2, TabletPadUserData>,
+        D: Dispatch<ZwpTabletPadGroupV2, ()>,
+        D: Dispatch<ZwpTabletPadRingV2, ()>,
+        D: Dispatch<ZwpTabletPadStripV2, ()>,
+        D: 'static,
+    {
+        let inner = &mut *self.inner.lock().unwrap();
+
+        let pads = &mut inner.pads;
+        let instances = &inner.instances;
+
+        let pad = pads.entry(pad_desc.clone()).or_insert_with(|| {
+            let mut pad = TabletPadHandle::new(pad_desc, tablet);
+            // Create new pad instance for every seat instance
+            for seat in instances.iter() {
+                if let Ok(client) = dh.get_client(seat.id()) {
+                    pad.new_instance::<D>(&client, dh, seat, pad_desc);
+                }
+            }
+            pad
+        });
+
+        pad.clone()
+    }
+
+    /// Get a handle to a tablet pad
+    pub fn get_pad(&self, pad_desc: &TabletPadDescriptor) -> Option<TabletPadHandle> {
+        self.inner.lock().unwrap().pads.get(pad_desc).cloned()
+    }
+
+    /// Get handles to all tablet pads, e.g. to update their focus
+    pub fn pads(&self) -> Vec<TabletPadHandle> {
+        self.inner.lock().unwrap().pads.values().cloned().collect()
+    }
+
+    /// Count all tablet pad devices
+    pub fn count_pads(&self) -> usize {
+        self.inner.lock().unwrap().pads.len()
+    }
+
+    /// Remove tablet pad device
+    ///
+    /// Called when pad is no longer available
+    /// For example on [input::Event::DeviceRemoved](crate::backend::input::InputEvent::DeviceRemoved) event.
+    pub fn remove_pad(&self, pad_desc: &TabletPadDescriptor) {
+        if let Some(pad) = self.inner.lock().unwrap().pads.remove(pad_desc) {
+            pad.removed();
+        }
+    }
+
+    /// Remove all tablet pad devices
+    pub fn clear_pads(&self) {
+        for (_, pad) in self.inner.lock().unwrap().pads.drain() {
+            pad.removed();
+        }
+    }
 }
 
 /// User data of ZwpTabletSeatV2 object
@@ -2,7 +2,7 @@ use std::fmt;
 use std::sync::{Arc, Mutex};
 
 use crate::backend::input::{ButtonState, TabletToolCapabilitys, TabletToolDescriptor, TabletToolType};
-use crate::input::pointer::{CursorImageAttributes, CursorImageStatus};
+use crate::input::pointer::{CursorIcon, CursorImageAttributes, CursorImageStatus};
 use crate::utils::{Logical, Point};
 use crate::wayland::seat::CURSOR_IMAGE_ROLE;
 use wayland_protocols::wp::tablet::zv2::server::{
@@ -435,6 +435,19 @@ impl fmt::Debug for TabletToolUserData {
     }
 }
 
+impl TabletToolUserData {
+    /// Set a named cursor for the tool, if it is focused on a surface of the client owning `tool`
+    pub(crate) fn set_named_cursor(&self, tool: &ZwpTabletToolV2, icon: CursorIcon) {
+        let focus = self.handle.inner.lock().unwrap().focus.clone();
+
+        if let Some(focus) = focus {
+            if focus.id().same_client_as(&tool.id()) {
+                (self.cb.lock().unwrap())(&self.desc, CursorImageStatus::Named(icon));
+            }
+        }
+    }
+}
+
 impl<D> Dispatch<ZwpTabletToolV2, TabletToolUserData, D> for TabletManagerState
 where
     D: Dispatch<ZwpTabletToolV2, TabletToolUserData>,
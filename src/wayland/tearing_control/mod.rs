@@ -0,0 +1,266 @@
+//! Utilities for handling the `wp_tearing_control` protocol
+//!
+//! ## How to use it
+//!
+//! ### Initialization
+//!
+//! To initialize this implementation, create [`TearingControlState`], store it in your `State` struct and
+//! implement the required traits, as shown in this example:
+//!
+//! ```
+//! use smithay::wayland::tearing_control::TearingControlState;
+//! use smithay::delegate_tearing_control;
+//!
+//! # struct State;
+//! # let mut display = wayland_server::Display::<State>::new().unwrap();
+//!
+//! // Create the tearing control state:
+//! let tearing_control_state = TearingControlState::new::<State, _>(
+//!     &display.handle(), // the display
+//!     None // provide a logger, if you want
+//! );
+//!
+//! // implement Dispatch for the tearing control types
+//! delegate_tearing_control!(State);
+//!
+//! // You're now ready to go!
+//! ```
+//!
+//! ### Use the presentation hint
+//!
+//! The [`presentation hint`](TearingControlSurfaceCachedState) is double-buffered and
+//! can be accessed by using the [`with_states`] function
+//!
+//! ```no_compile
+//! let hint = with_states(surface, |states| {
+//!     states.cached_state.current::<TearingControlSurfaceCachedState>().presentation_hint()
+//! });
+//! ```
+//!
+//! A surface hinting [`PresentationHint::Async`] prefers to be presented immediately,
+//! even if this causes tearing. This is usually only honored for fullscreen surfaces, e.g. by
+//! passing `PageFlipFlags::ASYNC` to `DrmSurface::page_flip`.
+
+use std::cell::RefCell;
+
+use wayland_protocols::wp::tearing_control::v1::server::{
+    wp_tearing_control_manager_v1, wp_tearing_control_v1,
+};
+use wayland_server::{
+    backend::GlobalId, protocol::wl_surface, Dispatch, DisplayHandle, GlobalDispatch, Resource, WEnum, Weak,
+};
+
+use super::compositor::{with_states, Cacheable};
+
+pub use wp_tearing_control_v1::PresentationHint;
+
+/// State of the wp_tearing_control_manager_v1 Global
+#[derive(Debug)]
+pub struct TearingControlState {
+    global: GlobalId,
+}
+
+impl TearingControlState {
+    /// Create new [`wp_tearing_control_manager`](wayland_protocols::wp::tearing_control::v1::server::wp_tearing_control_manager_v1) global.
+    ///
+    /// It returns the tearing control state, which you can drop to remove these global from
+    /// the event loop in the future.
+    pub fn new<D, L>(display: &DisplayHandle, log: L) -> TearingControlState
+    where
+        D: GlobalDispatch<wp_tearing_control_manager_v1::WpTearingControlManagerV1, slog::Logger>
+            + Dispatch<wp_tearing_control_manager_v1::WpTearingControlManagerV1, slog::Logger>
+            + Dispatch<wp_tearing_control_v1::WpTearingControlV1, Weak<wl_surface::WlSurface>>
+            + 'static,
+        L: Into<Option<slog::Logger>>,
+    {
+        TearingControlState {
+            global: display
+                .create_global::<D, wp_tearing_control_manager_v1::WpTearingControlManagerV1, slog::Logger>(
+                    1,
+                    crate::slog_or_fallback(log)
+                        .new(slog::o!("smithay_module" => "wp_tearing_control_manager")),
+                ),
+        }
+    }
+
+    /// Returns the tearing control manager global.
+    pub fn global(&self) -> GlobalId {
+        self.global.clone()
+    }
+}
+
+impl<D> GlobalDispatch<wp_tearing_control_manager_v1::WpTearingControlManagerV1, slog::Logger, D>
+    for TearingControlState
+where
+    D: GlobalDispatch<wp_tearing_control_manager_v1::WpTearingControlManagerV1, slog::Logger>,
+    D: Dispatch<wp_tearing_control_manager_v1::WpTearingControlManagerV1, slog::Logger>,
+    D: Dispatch<wp_tearing_control_v1::WpTearingControlV1, Weak<wl_surface::WlSurface>>,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &wayland_server::Client,
+        resource: wayland_server::New<wp_tearing_control_manager_v1::WpTearingControlManagerV1>,
+        global_data: &slog::Logger,
+        data_init: &mut wayland_server::DataInit<'_, D>,
+    ) {
+        data_init.init(resource, global_data.clone());
+    }
+}
+
+impl<D> Dispatch<wp_tearing_control_manager_v1::WpTearingControlManagerV1, slog::Logger, D>
+    for TearingControlState
+where
+    D: GlobalDispatch<wp_tearing_control_manager_v1::WpTearingControlManagerV1, slog::Logger>,
+    D: Dispatch<wp_tearing_control_manager_v1::WpTearingControlManagerV1, slog::Logger>,
+    D: Dispatch<wp_tearing_control_v1::WpTearingControlV1, Weak<wl_surface::WlSurface>>,
+{
+    fn request(
+        _state: &mut D,
+        _client: &wayland_server::Client,
+        _resource: &wp_tearing_control_manager_v1::WpTearingControlManagerV1,
+        request: <wp_tearing_control_manager_v1::WpTearingControlManagerV1 as Resource>::Request,
+        data: &slog::Logger,
+        _dhandle: &DisplayHandle,
+        data_init: &mut wayland_server::DataInit<'_, D>,
+    ) {
+        match request {
+            wp_tearing_control_manager_v1::Request::GetTearingControl { id, surface } => {
+                let already_has_tearing_control = with_states(&surface, |states| {
+                    states
+                        .data_map
+                        .get::<RefCell<Option<TearingControlMarker>>>()
+                        .map(|v| v.borrow().is_some())
+                        .unwrap_or(false)
+                });
+
+                if already_has_tearing_control {
+                    surface.post_error(
+                        wp_tearing_control_manager_v1::Error::TearingControlExists as u32,
+                        "the surface already has a tearing_control object associated".to_string(),
+                    );
+                    return;
+                }
+
+                data_init.init(id, surface.downgrade());
+                slog::trace!(data, "Created tearing control for surface {:?}", surface.id());
+
+                with_states(&surface, |states| {
+                    states
+                        .data_map
+                        .insert_if_missing(|| RefCell::new(Option::<TearingControlMarker>::None));
+                    *states
+                        .data_map
+                        .get::<RefCell<Option<TearingControlMarker>>>()
+                        .unwrap()
+                        .borrow_mut() = Some(TearingControlMarker);
+                });
+            }
+            wp_tearing_control_manager_v1::Request::Destroy => {
+                // All is already handled by our destructor
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<wp_tearing_control_v1::WpTearingControlV1, Weak<wl_surface::WlSurface>, D>
+    for TearingControlState
+where
+    D: GlobalDispatch<wp_tearing_control_manager_v1::WpTearingControlManagerV1, slog::Logger>,
+    D: Dispatch<wp_tearing_control_manager_v1::WpTearingControlManagerV1, slog::Logger>,
+    D: Dispatch<wp_tearing_control_v1::WpTearingControlV1, Weak<wl_surface::WlSurface>>,
+{
+    fn request(
+        _state: &mut D,
+        _client: &wayland_server::Client,
+        _resource: &wp_tearing_control_v1::WpTearingControlV1,
+        request: <wp_tearing_control_v1::WpTearingControlV1 as Resource>::Request,
+        data: &Weak<wl_surface::WlSurface>,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut wayland_server::DataInit<'_, D>,
+    ) {
+        // the object becomes inert, if the surface is destroyed
+        let surface = match data.upgrade() {
+            Ok(surface) => surface,
+            Err(_) => return,
+        };
+
+        match request {
+            wp_tearing_control_v1::Request::SetPresentationHint { hint } => {
+                if let WEnum::Value(hint) = hint {
+                    with_states(&surface, |states| {
+                        states
+                            .cached_state
+                            .pending::<TearingControlSurfaceCachedState>()
+                            .presentation_hint = hint;
+                    });
+                }
+            }
+            wp_tearing_control_v1::Request::Destroy => {
+                // the surface reverts to the vsync hint on the next commit
+                with_states(&surface, |states| {
+                    states
+                        .data_map
+                        .get::<RefCell<Option<TearingControlMarker>>>()
+                        .unwrap()
+                        .borrow_mut()
+                        .take();
+                    *states.cached_state.pending::<TearingControlSurfaceCachedState>() =
+                        TearingControlSurfaceCachedState::default();
+                });
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+struct TearingControlMarker;
+
+/// Presentation hint of a [`WlSurface`](wl_surface::WlSurface)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TearingControlSurfaceCachedState {
+    presentation_hint: PresentationHint,
+}
+
+impl TearingControlSurfaceCachedState {
+    /// Presentation hint set by the client
+    pub fn presentation_hint(&self) -> PresentationHint {
+        self.presentation_hint
+    }
+}
+
+impl Default for TearingControlSurfaceCachedState {
+    fn default() -> Self {
+        TearingControlSurfaceCachedState {
+            presentation_hint: PresentationHint::Vsync,
+        }
+    }
+}
+
+impl Cacheable for TearingControlSurfaceCachedState {
+    fn commit(&mut self, _dh: &DisplayHandle) -> Self {
+        *self
+    }
+
+    fn merge_into(self, into: &mut Self, _dh: &DisplayHandle) {
+        *into = self;
+    }
+}
+
+#[allow(missing_docs)] // TODO
+#[macro_export]
+macro_rules! delegate_tearing_control {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::tearing_control::v1::server::wp_tearing_control_manager_v1::WpTearingControlManagerV1: slog::Logger
+        ] => $crate::wayland::tearing_control::TearingControlState);
+
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::tearing_control::v1::server::wp_tearing_control_manager_v1::WpTearingControlManagerV1: slog::Logger
+        ] => $crate::wayland::tearing_control::TearingControlState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::tearing_control::v1::server::wp_tearing_control_v1::WpTearingControlV1: $crate::reexports::wayland_server::Weak<$crate::reexports::wayland_server::protocol::wl_surface::WlSurface>
+        ] => $crate::wayland::tearing_control::TearingControlState);
+    };
+}
@@ -0,0 +1,247 @@
+use std::sync::Mutex;
+
+use wayland_protocols::xdg::foreign::zv2::server::{
+    zxdg_exported_v2::{self, ZxdgExportedV2},
+    zxdg_exporter_v2::{self, ZxdgExporterV2},
+    zxdg_imported_v2::{self, ZxdgImportedV2},
+    zxdg_importer_v2::{self, ZxdgImporterV2},
+};
+use wayland_server::{
+    backend::{ClientId, ObjectId},
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+};
+
+use crate::wayland::{compositor, shell::xdg::XDG_TOPLEVEL_ROLE};
+
+use super::{
+    toplevel_of, unset_parents, ExportedState, XdgExportedUserData, XdgForeignHandle, XdgForeignHandler,
+    XdgForeignState, XdgImportedUserData,
+};
+
+impl<D> GlobalDispatch<ZxdgExporterV2, (), D> for XdgForeignState
+where
+    D: GlobalDispatch<ZxdgExporterV2, ()>
+        + Dispatch<ZxdgExporterV2, ()>
+        + Dispatch<ZxdgExportedV2, XdgExportedUserData>
+        + XdgForeignHandler
+        + 'static,
+{
+    fn bind(
+        _: &mut D,
+        _: &DisplayHandle,
+        _: &Client,
+        resource: New<ZxdgExporterV2>,
+        _: &(),
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl<D> Dispatch<ZxdgExporterV2, (), D> for XdgForeignState
+where
+    D: Dispatch<ZxdgExporterV2, ()> + Dispatch<ZxdgExportedV2, XdgExportedUserData> + XdgForeignHandler,
+{
+    fn request(
+        state: &mut D,
+        _: &Client,
+        exporter: &ZxdgExporterV2,
+        request: zxdg_exporter_v2::Request,
+        _: &(),
+        _: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zxdg_exporter_v2::Request::ExportToplevel { id, surface } => {
+                if compositor::get_role(&surface) != Some(XDG_TOPLEVEL_ROLE) {
+                    exporter.post_error(
+                        zxdg_exporter_v2::Error::InvalidSurface,
+                        "Surface must be an xdg_toplevel",
+                    );
+                    return;
+                }
+
+                let handle = XdgForeignHandle::new();
+                let exported = data_init.init(
+                    id,
+                    XdgExportedUserData {
+                        handle: handle.clone(),
+                    },
+                );
+
+                state.xdg_foreign_state().exported.insert(
+                    handle.clone(),
+                    ExportedState {
+                        exported_surface: surface,
+                        imported_by: Vec::new(),
+                    },
+                );
+                exported.handle(handle.to_string());
+            }
+
+            zxdg_exporter_v2::Request::Destroy => {}
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ZxdgExportedV2, XdgExportedUserData, D> for XdgForeignState
+where
+    D: Dispatch<ZxdgExportedV2, XdgExportedUserData> + XdgForeignHandler,
+{
+    fn request(
+        _: &mut D,
+        _: &Client,
+        _: &ZxdgExportedV2,
+        request: zxdg_exported_v2::Request,
+        _: &XdgExportedUserData,
+        _: &DisplayHandle,
+        _: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zxdg_exported_v2::Request::Destroy => {}
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(state: &mut D, _: ClientId, _: ObjectId, data: &XdgExportedUserData) {
+        let exported_state = match state.xdg_foreign_state().exported.remove(&data.handle) {
+            Some(exported_state) => exported_state,
+            None => return,
+        };
+
+        // Invalidate all relationships set up through the imported handles
+        for imported in exported_state.imported_by {
+            let children = match imported.data::<XdgImportedUserData>() {
+                Some(imported_data) => std::mem::take(&mut *imported_data.children.lock().unwrap()),
+                None => Vec::new(),
+            };
+            unset_parents(state, children, &exported_state.exported_surface);
+            imported.destroyed();
+        }
+    }
+}
+
+impl<D> GlobalDispatch<ZxdgImporterV2, (), D> for XdgForeignState
+where
+    D: GlobalDispatch<ZxdgImporterV2, ()>
+        + Dispatch<ZxdgImporterV2, ()>
+        + Dispatch<ZxdgImportedV2, XdgImportedUserData>
+        + XdgForeignHandler
+        + 'static,
+{
+    fn bind(
+        _: &mut D,
+        _: &DisplayHandle,
+        _: &Client,
+        resource: New<ZxdgImporterV2>,
+        _: &(),
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl<D> Dispatch<ZxdgImporterV2, (), D> for XdgForeignState
+where
+    D: Dispatch<ZxdgImporterV2, ()> + Dispatch<ZxdgImportedV2, XdgImportedUserData> + XdgForeignHandler,
+{
+    fn request(
+        state: &mut D,
+        _: &Client,
+        _: &ZxdgImporterV2,
+        request: zxdg_importer_v2::Request,
+        _: &(),
+        _: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zxdg_importer_v2::Request::ImportToplevel { id, handle } => {
+                let handle = XdgForeignHandle::from(handle);
+                let imported = data_init.init(
+                    id,
+                    XdgImportedUserData {
+                        handle: handle.clone(),
+                        children: Mutex::new(Vec::new()),
+                    },
+                );
+
+                match state.xdg_foreign_state().exported.get_mut(&handle) {
+                    Some(exported_state) => exported_state.imported_by.push(imported),
+                    // The handle is unknown or was already destroyed
+                    None => imported.destroyed(),
+                }
+            }
+
+            zxdg_importer_v2::Request::Destroy => {}
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ZxdgImportedV2, XdgImportedUserData, D> for XdgForeignState
+where
+    D: Dispatch<ZxdgImportedV2, XdgImportedUserData> + XdgForeignHandler,
+{
+    fn request(
+        state: &mut D,
+        _: &Client,
+        imported: &ZxdgImportedV2,
+        request: zxdg_imported_v2::Request,
+        data: &XdgImportedUserData,
+        _: &DisplayHandle,
+        _: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zxdg_imported_v2::Request::SetParentOf { surface } => {
+                let toplevel = match toplevel_of(state, &surface) {
+                    Some(toplevel) => toplevel,
+                    None => {
+                        imported.post_error(
+                            zxdg_imported_v2::Error::InvalidSurface,
+                            "Surface must be an xdg_toplevel",
+                        );
+                        return;
+                    }
+                };
+
+                let exported_surface = match state.xdg_foreign_state().exported.get(&data.handle) {
+                    Some(exported_state) if exported_state.exported_surface.is_alive() => {
+                        exported_state.exported_surface.clone()
+                    }
+                    // The relationship was already invalidated
+                    _ => return,
+                };
+
+                if toplevel.set_parent(Some(&exported_surface)) {
+                    let mut children = data.children.lock().unwrap();
+                    if !children.contains(&surface) {
+                        children.push(surface);
+                    }
+                }
+            }
+
+            zxdg_imported_v2::Request::Destroy => {}
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(state: &mut D, _: ClientId, object_id: ObjectId, data: &XdgImportedUserData) {
+        let exported_surface = match state.xdg_foreign_state().exported.get_mut(&data.handle) {
+            Some(exported_state) => {
+                exported_state
+                    .imported_by
+                    .retain(|imported| imported.id() != object_id);
+                exported_state.exported_surface.clone()
+            }
+            None => return,
+        };
+
+        let children = std::mem::take(&mut *data.children.lock().unwrap());
+        unset_parents(state, children, &exported_surface);
+    }
+}
@@ -0,0 +1,225 @@
+//! Utilities for handling the `xdg-foreign` protocol
+//!
+//! This protocol allows a client to reference a toplevel surface of a different client.
+//! A client exports one of its toplevels and receives a unique handle for it, which it can pass
+//! to another client, e.g. over D-Bus. That client can then import the toplevel using the handle and
+//! make it the parent of one of its own toplevels. This is what allows out-of-process dialogs, like
+//! the file chooser of `xdg-desktop-portal`, to be attached to the window of the requesting application.
+//!
+//! Parents set through this protocol are applied using [`ToplevelSurface::set_parent`], so they
+//! can be queried with [`ToplevelSurface::parent`] like any other parent.
+//!
+//! [`ToplevelSurface::set_parent`]: crate::wayland::shell::xdg::ToplevelSurface::set_parent
+//! [`ToplevelSurface::parent`]: crate::wayland::shell::xdg::ToplevelSurface::parent
+//!
+//! ### Example
+//!
+//! ```no_run
+//! # extern crate wayland_server;
+//! #
+//! use smithay::{
+//!     delegate_xdg_foreign,
+//!     wayland::xdg_foreign::{XdgForeignHandler, XdgForeignState},
+//! };
+//! # use smithay::wayland::shell::xdg::{XdgShellHandler, XdgShellState, ToplevelSurface, PopupSurface, PositionerState};
+//! # use smithay::reexports::wayland_server::protocol::wl_seat::WlSeat;
+//! # use smithay::utils::Serial;
+//!
+//! pub struct State {
+//!     xdg_foreign_state: XdgForeignState,
+//! }
+//!
+//! impl XdgForeignHandler for State {
+//!     fn xdg_foreign_state(&mut self) -> &mut XdgForeignState {
+//!         &mut self.xdg_foreign_state
+//!     }
+//! }
+//!
+//! # impl XdgShellHandler for State {
+//! #     fn xdg_shell_state(&mut self) -> &mut XdgShellState { unimplemented!() }
+//! #     fn new_toplevel(&mut self, surface: ToplevelSurface) { unimplemented!() }
+//! #     fn new_popup(&mut self, surface: PopupSurface, positioner: PositionerState) { unimplemented!() }
+//! #     fn grab(&mut self, surface: PopupSurface, seat: WlSeat, serial: Serial) { unimplemented!() }
+//! # }
+//! // Delegate xdg foreign handling for State to XdgForeignState.
+//! delegate_xdg_foreign!(State);
+//!
+//! # let mut display = wayland_server::Display::<State>::new().unwrap();
+//! # let display_handle = display.handle();
+//! let state = State {
+//!     xdg_foreign_state: XdgForeignState::new::<State>(&display_handle),
+//! };
+//!
+//! // Rest of the compositor goes here...
+//! ```
+
+use std::{collections::HashMap, ops, sync::Mutex};
+
+use rand::distributions::{Alphanumeric, DistString};
+use wayland_protocols::xdg::foreign::zv2::server::{
+    zxdg_exporter_v2::ZxdgExporterV2, zxdg_imported_v2::ZxdgImportedV2, zxdg_importer_v2::ZxdgImporterV2,
+};
+use wayland_server::{
+    backend::GlobalId, protocol::wl_surface::WlSurface, Dispatch, DisplayHandle, GlobalDispatch,
+};
+
+use crate::wayland::shell::xdg::{ToplevelSurface, XdgShellHandler};
+
+mod dispatch;
+
+/// Contains the unique string handle of an exported toplevel
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct XdgForeignHandle(String);
+
+impl XdgForeignHandle {
+    fn new() -> Self {
+        Self(Alphanumeric.sample_string(&mut rand::thread_rng(), 32))
+    }
+
+    /// Extracts a string slice containing the entire handle.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl ops::Deref for XdgForeignHandle {
+    type Target = str;
+    #[inline]
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for XdgForeignHandle {
+    fn from(s: String) -> Self {
+        Self(s)
+    }
+}
+
+impl From<XdgForeignHandle> for String {
+    fn from(s: XdgForeignHandle) -> Self {
+        s.0
+    }
+}
+
+/// A trait implemented to handle the xdg foreign protocol.
+pub trait XdgForeignHandler: XdgShellHandler {
+    /// Returns the xdg foreign state.
+    fn xdg_foreign_state(&mut self) -> &mut XdgForeignState;
+}
+
+/// Tracks the toplevels exported using the xdg foreign protocol
+#[derive(Debug)]
+pub struct XdgForeignState {
+    exported: HashMap<XdgForeignHandle, ExportedState>,
+    exporter: GlobalId,
+    importer: GlobalId,
+}
+
+#[derive(Debug)]
+struct ExportedState {
+    exported_surface: WlSurface,
+    imported_by: Vec<ZxdgImportedV2>,
+}
+
+impl XdgForeignState {
+    /// Creates new xdg exporter and importer globals.
+    ///
+    /// In order to use this abstraction, your `D` type needs to implement [`XdgForeignHandler`].
+    pub fn new<D>(display: &DisplayHandle) -> XdgForeignState
+    where
+        D: GlobalDispatch<ZxdgExporterV2, ()>
+            + GlobalDispatch<ZxdgImporterV2, ()>
+            + Dispatch<ZxdgExporterV2, ()>
+            + Dispatch<ZxdgImporterV2, ()>
+            + XdgForeignHandler
+            + 'static,
+    {
+        let exporter = display.create_global::<D, ZxdgExporterV2, _>(1, ());
+        let importer = display.create_global::<D, ZxdgImporterV2, _>(1, ());
+
+        XdgForeignState {
+            exported: HashMap::new(),
+            exporter,
+            importer,
+        }
+    }
+
+    /// Returns the surface exported with the given handle, if it still exists
+    pub fn exported_surface(&self, handle: &str) -> Option<&WlSurface> {
+        self.exported
+            .get(&XdgForeignHandle::from(handle.to_owned()))
+            .map(|exported| &exported.exported_surface)
+    }
+
+    /// Returns the xdg exporter global.
+    pub fn exporter_global(&self) -> GlobalId {
+        self.exporter.clone()
+    }
+
+    /// Returns the xdg importer global.
+    pub fn importer_global(&self) -> GlobalId {
+        self.importer.clone()
+    }
+}
+
+/// User data of a `zxdg_exported_v2`
+#[derive(Debug)]
+pub struct XdgExportedUserData {
+    handle: XdgForeignHandle,
+}
+
+/// User data of a `zxdg_imported_v2`
+#[derive(Debug)]
+pub struct XdgImportedUserData {
+    handle: XdgForeignHandle,
+    /// Surfaces, whose parent was set to the imported toplevel
+    children: Mutex<Vec<WlSurface>>,
+}
+
+/// Looks up the toplevel of `surface` known to the xdg shell
+fn toplevel_of<D: XdgShellHandler>(state: &mut D, surface: &WlSurface) -> Option<ToplevelSurface> {
+    state.xdg_shell_state().toplevel_surfaces(|toplevels| {
+        toplevels
+            .iter()
+            .find(|toplevel| toplevel.wl_surface() == surface)
+            .cloned()
+    })
+}
+
+/// Unsets the parent of `children`, if it still is the `parent` set through the protocol
+fn unset_parents<D: XdgShellHandler>(state: &mut D, children: Vec<WlSurface>, parent: &WlSurface) {
+    for child in children {
+        if let Some(toplevel) = toplevel_of(state, &child) {
+            if toplevel.parent().as_ref() == Some(parent) {
+                toplevel.set_parent(None);
+            }
+        }
+    }
+}
+
+/// Macro to delegate implementation of the xdg foreign protocol
+#[macro_export]
+macro_rules! delegate_xdg_foreign {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::xdg::foreign::zv2::server::zxdg_exporter_v2::ZxdgExporterV2: ()
+        ] => $crate::wayland::xdg_foreign::XdgForeignState);
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::xdg::foreign::zv2::server::zxdg_importer_v2::ZxdgImporterV2: ()
+        ] => $crate::wayland::xdg_foreign::XdgForeignState);
+
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::xdg::foreign::zv2::server::zxdg_exporter_v2::ZxdgExporterV2: ()
+        ] => $crate::wayland::xdg_foreign::XdgForeignState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::xdg::foreign::zv2::server::zxdg_importer_v2::ZxdgImporterV2: ()
+        ] => $crate::wayland::xdg_foreign::XdgForeignState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::xdg::foreign::zv2::server::zxdg_exported_v2::ZxdgExportedV2: $crate::wayland::xdg_foreign::XdgExportedUserData
+        ] => $crate::wayland::xdg_foreign::XdgForeignState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::xdg::foreign::zv2::server::zxdg_imported_v2::ZxdgImportedV2: $crate::wayland::xdg_foreign::XdgImportedUserData
+        ] => $crate::wayland::xdg_foreign::XdgForeignState);
+    };
+}
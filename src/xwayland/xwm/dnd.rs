@@ -0,0 +1,608 @@
+//! Bridging of drag'n'drop operations between X11 and wayland clients using XDND
+//!
+//! Wayland drag'n'drop operations entering X11 windows are forwarded by the compositor
+//! to [`X11Wm::dnd_enter`] and related methods, e.g. from the `*_external` methods of
+//! [`ClientDndGrabHandler`](crate::wayland::data_device::ClientDndGrabHandler).
+//! The XWM then acts as the XDND source on behalf of the wayland client.
+//!
+//! X11 drag'n'drop operations leaving the X11 windows are received by a proxy window,
+//! which is advertised through the `XdndProxy` property of the root window, and are handed
+//! to the compositor through [`XwmHandler::start_dnd`].
+//!
+//! In both directions the data is transferred using the `XdndSelection`.
+
+use std::os::unix::io::OwnedFd;
+
+use wayland_server::protocol::{wl_data_device_manager::DndAction, wl_surface::WlSurface};
+use x11rb::{
+    connection::Connection as _,
+    errors::ReplyOrIdError,
+    protocol::xproto::{
+        Atom, AtomEnum, ClientMessageEvent, ConnectionExt as _, EventMask, PropMode, Screen,
+        Window as X11Window,
+    },
+    rust_connection::RustConnection,
+    wrapper::ConnectionExt as _,
+};
+
+use crate::{
+    utils::{Logical, Point},
+    wayland::data_device::ExternalDndOffer,
+};
+
+use super::{
+    selection::{atom_to_mime, mime_to_atom, SelectionKind, XWmSelection},
+    Atoms, X11Wm, XwmHandler, XwmId,
+};
+
+/// Version of the XDND protocol supported by the XWM
+const XDND_VERSION: u32 = 5;
+/// Oldest version of the XDND protocol we are able to talk to
+const XDND_MIN_VERSION: u32 = 3;
+
+/// X11 window receiving a wayland drag'n'drop
+#[derive(Debug)]
+struct DndTarget {
+    window: X11Window,
+    /// window receiving the XDND messages, differs from `window` if the target uses `XdndProxy`
+    proxy: X11Window,
+    version: u32,
+    /// location of the target window in root coordinates
+    origin: Point<i32, Logical>,
+    accepted: bool,
+    /// a new position is only sent once the target replied to the previous one
+    awaiting_status: bool,
+    pending_position: Option<(Point<i32, Logical>, u32)>,
+}
+
+/// Drag'n'drop of a wayland client hovering X11 windows
+#[derive(Debug)]
+struct WaylandDnd {
+    offer: ExternalDndOffer,
+    target: Option<DndTarget>,
+}
+
+/// Drag'n'drop of an X11 client hovering wayland surfaces
+#[derive(Debug)]
+struct X11Dnd {
+    source: X11Window,
+    version: u32,
+    /// action chosen by the wayland client
+    action: DndAction,
+    dropped: bool,
+}
+
+/// Drag'n'drop state of the XWM
+#[derive(Debug)]
+pub(super) struct XWmDnd {
+    pub(super) selection: XWmSelection,
+    wayland: Option<WaylandDnd>,
+    x11: Option<X11Dnd>,
+}
+
+impl XWmDnd {
+    pub(super) fn new(conn: &RustConnection, screen: &Screen, atoms: &Atoms) -> Result<Self, ReplyOrIdError> {
+        let selection = XWmSelection::new(conn, screen, atoms, SelectionKind::Dnd)?;
+
+        // drags hovering anything but X11 windows are redirected to our window
+        conn.change_property32(
+            PropMode::REPLACE,
+            selection.window,
+            atoms.XdndAware,
+            AtomEnum::ATOM,
+            &[XDND_VERSION],
+        )?;
+        conn.change_property32(
+            PropMode::REPLACE,
+            selection.window,
+            atoms.XdndProxy,
+            AtomEnum::WINDOW,
+            &[selection.window],
+        )?;
+        conn.change_property32(
+            PropMode::REPLACE,
+            screen.root,
+            atoms.XdndProxy,
+            AtomEnum::WINDOW,
+            &[selection.window],
+        )?;
+
+        Ok(XWmDnd {
+            selection,
+            wayland: None,
+            x11: None,
+        })
+    }
+
+    pub(super) fn wayland_offer(&self) -> Option<&ExternalDndOffer> {
+        self.wayland.as_ref().map(|dnd| &dnd.offer)
+    }
+}
+
+fn action_to_atom(atoms: &Atoms, action: DndAction) -> Atom {
+    if action.contains(DndAction::Copy) {
+        atoms.XdndActionCopy
+    } else if action.contains(DndAction::Move) {
+        atoms.XdndActionMove
+    } else if action.contains(DndAction::Ask) {
+        atoms.XdndActionAsk
+    } else {
+        x11rb::NONE
+    }
+}
+
+fn atom_to_action(atoms: &Atoms, atom: Atom) -> DndAction {
+    if atom == atoms.XdndActionCopy {
+        DndAction::Copy
+    } else if atom == atoms.XdndActionMove {
+        DndAction::Move
+    } else if atom == atoms.XdndActionAsk {
+        DndAction::Ask
+    } else {
+        DndAction::empty()
+    }
+}
+
+fn send_dnd_message(
+    conn: &RustConnection,
+    destination: X11Window,
+    window: X11Window,
+    type_: Atom,
+    data: [u32; 5],
+) -> Result<(), ReplyOrIdError> {
+    let event = ClientMessageEvent::new(32, window, type_, data);
+    conn.send_event(false, destination, EventMask::NO_EVENT, event)?;
+    Ok(())
+}
+
+/// Query the XDND version and the window receiving XDND messages for `window`
+fn dnd_aware(
+    conn: &RustConnection,
+    atoms: &Atoms,
+    window: X11Window,
+) -> Result<Option<(u32, X11Window)>, ReplyOrIdError> {
+    let proxy = conn
+        .get_property(false, window, atoms.XdndProxy, AtomEnum::WINDOW, 0, 1)?
+        .reply()?
+        .value32()
+        .and_then(|mut value| value.next())
+        .unwrap_or(window);
+    let version = conn
+        .get_property(false, proxy, atoms.XdndAware, AtomEnum::ATOM, 0, 1)?
+        .reply()?
+        .value32()
+        .and_then(|mut value| value.next());
+
+    Ok(version
+        .filter(|version| *version >= XDND_MIN_VERSION)
+        .map(|version| (version.min(XDND_VERSION), proxy)))
+}
+
+impl X11Wm {
+    /// A wayland drag'n'drop entered a surface
+    ///
+    /// This should be called from
+    /// [`ClientDndGrabHandler::enter_external`](crate::wayland::data_device::ClientDndGrabHandler::enter_external).
+    ///
+    /// Returns `true` if `surface` belongs to an X11 window of this XWM. In that case the
+    /// drag'n'drop is forwarded to the window using XDND and [`X11Wm::dnd_motion`],
+    /// [`X11Wm::dnd_leave`] and [`X11Wm::dnd_drop`] should be called for further events.
+    pub fn dnd_enter(
+        &mut self,
+        surface: &WlSurface,
+        offer: ExternalDndOffer,
+        location: Point<f64, Logical>,
+    ) -> Result<bool, ReplyOrIdError> {
+        let Some(window) = self
+            .windows
+            .iter()
+            .find(|window| window.wl_surface().as_ref() == Some(surface))
+            .cloned()
+        else {
+            return Ok(false);
+        };
+        let conn = self.conn.clone();
+        let atoms = self.atoms;
+        let dnd_window = self.dnd.selection.window;
+
+        // offer the wayland data as the XdndSelection
+        let mime_types = offer.mime_types();
+        let mut types = Vec::with_capacity(mime_types.len());
+        for mime_type in mime_types.iter() {
+            let atom = mime_to_atom(&conn, &atoms, mime_type)?;
+            if !types.contains(&atom) {
+                types.push(atom);
+            }
+        }
+        conn.change_property32(
+            PropMode::REPLACE,
+            dnd_window,
+            atoms.XdndTypeList,
+            AtomEnum::ATOM,
+            &types,
+        )?;
+        conn.set_selection_owner(dnd_window, atoms.XdndSelection, x11rb::CURRENT_TIME)?;
+        self.dnd.selection.owner = dnd_window;
+        self.dnd.selection.mime_types = mime_types;
+
+        let target = match dnd_aware(&conn, &atoms, window.window_id())? {
+            Some((version, proxy)) => {
+                let more_types = u32::from(types.len() > 3);
+                let mut data = [dnd_window, (version << 24) | more_types, 0, 0, 0];
+                for (slot, atom) in data[2..].iter_mut().zip(types.iter()) {
+                    *slot = *atom;
+                }
+                send_dnd_message(&conn, proxy, window.window_id(), atoms.XdndEnter, data)?;
+
+                Some(DndTarget {
+                    window: window.window_id(),
+                    proxy,
+                    version,
+                    origin: window.geometry().loc,
+                    accepted: false,
+                    awaiting_status: false,
+                    pending_position: None,
+                })
+            }
+            None => {
+                slog::debug!(
+                    self.log,
+                    "X11 window {} does not support XDND",
+                    window.window_id()
+                );
+                None
+            }
+        };
+        self.dnd.wayland = Some(WaylandDnd { offer, target });
+
+        self.dnd_motion(location, x11rb::CURRENT_TIME)?;
+        conn.flush()?;
+        Ok(true)
+    }
+
+    /// The pointer of a wayland drag'n'drop moved over an X11 window
+    ///
+    /// This should be called from
+    /// [`ClientDndGrabHandler::motion_external`](crate::wayland::data_device::ClientDndGrabHandler::motion_external).
+    pub fn dnd_motion(&mut self, location: Point<f64, Logical>, time: u32) -> Result<(), ReplyOrIdError> {
+        let Some(target) = self.dnd.wayland.as_mut().and_then(|dnd| dnd.target.as_mut()) else {
+            return Ok(());
+        };
+
+        let position = target.origin + location.to_i32_round();
+        if target.awaiting_status {
+            target.pending_position = Some((position, time));
+            return Ok(());
+        }
+
+        self.send_dnd_position(position, time)?;
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    fn send_dnd_position(&mut self, position: Point<i32, Logical>, time: u32) -> Result<(), ReplyOrIdError> {
+        let dnd_window = self.dnd.selection.window;
+        let Some(dnd) = self.dnd.wayland.as_mut() else {
+            return Ok(());
+        };
+        let Some(target) = dnd.target.as_mut() else {
+            return Ok(());
+        };
+
+        let coordinates = ((position.x as u32 & 0xffff) << 16) | (position.y as u32 & 0xffff);
+        let action = action_to_atom(&self.atoms, dnd.offer.source_actions());
+        send_dnd_message(
+            &self.conn,
+            target.proxy,
+            target.window,
+            self.atoms.XdndPosition,
+            [dnd_window, 0, coordinates, time, action],
+        )?;
+        target.awaiting_status = true;
+        Ok(())
+    }
+
+    /// A wayland drag'n'drop left an X11 window without being dropped
+    ///
+    /// This should be called from
+    /// [`ClientDndGrabHandler::leave_external`](crate::wayland::data_device::ClientDndGrabHandler::leave_external).
+    pub fn dnd_leave(&mut self) -> Result<(), ReplyOrIdError> {
+        let Some(dnd) = self.dnd.wayland.take() else {
+            return Ok(());
+        };
+
+        if let Some(target) = dnd.target {
+            send_dnd_message(
+                &self.conn,
+                target.proxy,
+                target.window,
+                self.atoms.XdndLeave,
+                [self.dnd.selection.window, 0, 0, 0, 0],
+            )?;
+        }
+        self.release_dnd_selection()?;
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// A wayland drag'n'drop was dropped onto an X11 window
+    ///
+    /// This should be called from
+    /// [`ClientDndGrabHandler::drop_external`](crate::wayland::data_device::ClientDndGrabHandler::drop_external).
+    ///
+    /// The drag'n'drop is finished once the X11 client has read the data.
+    pub fn dnd_drop(&mut self, time: u32) -> Result<(), ReplyOrIdError> {
+        let Some(target) = self.dnd.wayland.as_ref().and_then(|dnd| dnd.target.as_ref()) else {
+            return Ok(());
+        };
+
+        send_dnd_message(
+            &self.conn,
+            target.proxy,
+            target.window,
+            self.atoms.XdndDrop,
+            [self.dnd.selection.window, 0, time, 0, 0],
+        )?;
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    fn release_dnd_selection(&mut self) -> Result<(), ReplyOrIdError> {
+        let sel = &mut self.dnd.selection;
+        if sel.owner == sel.window {
+            sel.owner = x11rb::NONE;
+            sel.mime_types.clear();
+            self.conn
+                .set_selection_owner(x11rb::NONE, self.atoms.XdndSelection, x11rb::CURRENT_TIME)?;
+        }
+        Ok(())
+    }
+
+    /// Set the action chosen by the wayland client for the current X11 drag'n'drop
+    ///
+    /// This should be called from
+    /// [`ServerDndGrabHandler::action`](crate::wayland::data_device::ServerDndGrabHandler::action)
+    /// for drag'n'drop operations started because of [`XwmHandler::start_dnd`].
+    pub fn set_dnd_action(&mut self, action: DndAction) -> Result<(), ReplyOrIdError> {
+        let Some(dnd) = self.dnd.x11.as_mut() else {
+            return Ok(());
+        };
+
+        dnd.action = action;
+        send_dnd_message(
+            &self.conn,
+            dnd.source,
+            dnd.source,
+            self.atoms.XdndStatus,
+            [
+                self.dnd.selection.window,
+                // accept the drop and ask for position updates
+                u32::from(!action.is_empty()) | 0b10,
+                0,
+                0,
+                action_to_atom(&self.atoms, action),
+            ],
+        )?;
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// Request the data of the current X11 drag'n'drop
+    ///
+    /// This should be called from
+    /// [`ServerDndGrabHandler::send`](crate::wayland::data_device::ServerDndGrabHandler::send)
+    /// for drag'n'drop operations started because of [`XwmHandler::start_dnd`].
+    ///
+    /// The data will be written into `fd` asynchronously, after which `fd` is closed.
+    pub fn send_dnd_data(&mut self, mime_type: String, fd: OwnedFd) -> Result<(), ReplyOrIdError> {
+        let conn = self.conn.clone();
+        let atoms = self.atoms;
+
+        let sel = &mut self.dnd.selection;
+        if self.dnd.x11.is_none() || sel.owner == x11rb::NONE || sel.owner == sel.window {
+            slog::debug!(
+                self.log,
+                "Ignoring request for drag'n'drop data not provided by any X11 client"
+            );
+            return Ok(());
+        }
+
+        let target = mime_to_atom(&conn, &atoms, &mime_type)?;
+        sel.request(&conn, &atoms, target, fd)?;
+
+        conn.flush()?;
+        Ok(())
+    }
+
+    /// Finish the current X11 drag'n'drop
+    ///
+    /// This should be called with `performed` set to `true` from
+    /// [`ServerDndGrabHandler::finished`](crate::wayland::data_device::ServerDndGrabHandler::finished)
+    /// and with `false` from
+    /// [`ServerDndGrabHandler::cancelled`](crate::wayland::data_device::ServerDndGrabHandler::cancelled)
+    /// for drag'n'drop operations started because of [`XwmHandler::start_dnd`].
+    pub fn finish_dnd(&mut self, performed: bool) -> Result<(), ReplyOrIdError> {
+        let Some(dnd) = self.dnd.x11.take() else {
+            return Ok(());
+        };
+
+        let performed = performed && !dnd.action.is_empty();
+        let action = if performed {
+            action_to_atom(&self.atoms, dnd.action)
+        } else {
+            x11rb::NONE
+        };
+        // the status and action of the drop are only transmitted since version 5
+        let data = if dnd.version >= 5 {
+            [self.dnd.selection.window, u32::from(performed), action, 0, 0]
+        } else {
+            [self.dnd.selection.window, 0, 0, 0, 0]
+        };
+        send_dnd_message(&self.conn, dnd.source, dnd.source, self.atoms.XdndFinished, data)?;
+        self.conn.flush()?;
+        Ok(())
+    }
+}
+
+/// An XDND message was sent to the XWM's drag'n'drop window
+pub(super) fn handle_client_message<D: XwmHandler>(
+    state: &mut D,
+    xwm_id: XwmId,
+    msg: ClientMessageEvent,
+) -> Result<(), ReplyOrIdError> {
+    let xwm = state.xwm_state(xwm_id);
+    let conn = xwm.conn.clone();
+    let atoms = xwm.atoms;
+    let dnd_window = xwm.dnd.selection.window;
+    let data = msg.data.as_data32();
+
+    match msg.type_ {
+        // the X11 target of a wayland drag'n'drop replied to a position
+        x if x == atoms.XdndStatus => {
+            let Some(dnd) = xwm.dnd.wayland.as_mut() else {
+                return Ok(());
+            };
+            let Some(target) = dnd.target.as_mut().filter(|target| target.window == data[0]) else {
+                return Ok(());
+            };
+
+            target.awaiting_status = false;
+            let accepted = data[1] & 1 == 1;
+            if accepted != target.accepted {
+                target.accepted = accepted;
+                // XDND does not tell us which type will be requested
+                dnd.offer.accept(if accepted {
+                    dnd.offer.mime_types().into_iter().next()
+                } else {
+                    None
+                });
+            }
+            let action = if !accepted {
+                DndAction::empty()
+            } else if target.version >= 2 {
+                atom_to_action(&atoms, data[4])
+            } else {
+                DndAction::Copy
+            };
+            dnd.offer.set_action(action);
+
+            if let Some((position, time)) = target.pending_position.take() {
+                xwm.send_dnd_position(position, time)?;
+            }
+        }
+        // the X11 target of a wayland drag'n'drop is done with the data
+        x if x == atoms.XdndFinished => {
+            if xwm
+                .dnd
+                .wayland
+                .as_ref()
+                .and_then(|dnd| dnd.target.as_ref())
+                .map(|target| target.window == data[0])
+                .unwrap_or(false)
+            {
+                let dnd = xwm.dnd.wayland.take().unwrap();
+                dnd.offer.finish();
+                xwm.release_dnd_selection()?;
+            }
+        }
+        // an X11 drag'n'drop left the X11 windows
+        x if x == atoms.XdndEnter => {
+            let source = data[0];
+            let version = data[1] >> 24;
+            if version < XDND_MIN_VERSION {
+                slog::debug!(xwm.log, "Ignoring drag'n'drop using XDND version {}", version);
+                return Ok(());
+            }
+
+            let types = if data[1] & 1 == 1 {
+                conn.get_property(false, source, atoms.XdndTypeList, AtomEnum::ATOM, 0, 4096)?
+                    .reply()?
+                    .value32()
+                    .map(|types| types.collect::<Vec<_>>())
+                    .unwrap_or_default()
+            } else {
+                data[2..]
+                    .iter()
+                    .copied()
+                    .filter(|atom| *atom != x11rb::NONE)
+                    .collect()
+            };
+            let mut mime_types = Vec::with_capacity(types.len());
+            for atom in types {
+                if let Some(mime_type) = atom_to_mime(&conn, &atoms, atom)? {
+                    if !mime_types.contains(&mime_type) {
+                        mime_types.push(mime_type);
+                    }
+                }
+            }
+
+            let mut actions = conn
+                .get_property(false, source, atoms.XdndActionList, AtomEnum::ATOM, 0, 16)?
+                .reply()?
+                .value32()
+                .map(|list| {
+                    list.fold(DndAction::empty(), |actions, atom| {
+                        actions | atom_to_action(&atoms, atom)
+                    })
+                })
+                .unwrap_or_else(DndAction::empty);
+            if actions.is_empty() {
+                actions = DndAction::Copy | DndAction::Move;
+            }
+
+            xwm.dnd.x11 = Some(X11Dnd {
+                source,
+                version: version.min(XDND_VERSION),
+                action: DndAction::empty(),
+                dropped: false,
+            });
+            state.start_dnd(xwm_id, mime_types, actions);
+        }
+        x if x == atoms.XdndPosition => {
+            let Some(dnd) = xwm.dnd.x11.as_ref().filter(|dnd| dnd.source == data[0]) else {
+                return Ok(());
+            };
+            send_dnd_message(
+                &conn,
+                dnd.source,
+                dnd.source,
+                atoms.XdndStatus,
+                [
+                    dnd_window,
+                    u32::from(!dnd.action.is_empty()) | 0b10,
+                    0,
+                    0,
+                    action_to_atom(&atoms, dnd.action),
+                ],
+            )?;
+        }
+        x if x == atoms.XdndLeave => {
+            if xwm
+                .dnd
+                .x11
+                .as_ref()
+                .map(|dnd| dnd.source == data[0])
+                .unwrap_or(false)
+            {
+                xwm.dnd.x11 = None;
+            }
+        }
+        x if x == atoms.XdndDrop => {
+            let Some(dnd) = xwm.dnd.x11.as_mut().filter(|dnd| dnd.source == data[0]) else {
+                return Ok(());
+            };
+            if dnd.dropped {
+                return Ok(());
+            }
+            dnd.dropped = true;
+            if dnd.action.is_empty() {
+                // no wayland client accepted the drop
+                xwm.finish_dnd(false)?;
+            }
+        }
+        _ => {}
+    }
+
+    conn.flush()?;
+    Ok(())
+}
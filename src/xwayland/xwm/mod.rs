@@ -72,10 +72,13 @@ use calloop::LoopHandle;
 use std::{
     cmp::Reverse,
     collections::{BinaryHeap, HashMap},
-    os::unix::net::UnixStream,
+    os::unix::{io::OwnedFd, net::UnixStream},
     sync::Arc,
 };
-use wayland_server::{protocol::wl_surface::WlSurface, Client, DisplayHandle, Resource};
+use wayland_server::{
+    protocol::{wl_data_device_manager::DndAction, wl_surface::WlSurface},
+    Client, DisplayHandle, Resource,
+};
 
 use x11rb::{
     connection::Connection as _,
@@ -83,6 +86,7 @@ use x11rb::{
     protocol::{
         composite::{ConnectionExt as _, Redirect},
         render::{ConnectionExt, CreatePictureAux, PictureWrapper},
+        xfixes::ConnectionExt as _,
         xproto::{
             AtomEnum, ChangeWindowAttributesAux, ConfigWindow, ConfigureWindowAux, ConnectionExt as _,
             CreateGCAux, CreateWindowAux, CursorWrapper, EventMask, FontWrapper, GcontextWrapper,
@@ -95,7 +99,12 @@ use x11rb::{
     COPY_DEPTH_FROM_PARENT,
 };
 
+mod dnd;
+mod selection;
 mod surface;
+use self::dnd::XWmDnd;
+pub use self::selection::SelectionTarget;
+use self::selection::{SelectionKind, XWmSelection};
 pub use self::surface::*;
 use super::xserver::XWaylandClientData;
 
@@ -116,6 +125,31 @@ mod atoms {
 
             // data formats
             UTF8_STRING,
+            TEXT,
+
+            // selections
+            CLIPBOARD,
+            PRIMARY,
+            TARGETS,
+            TIMESTAMP,
+            INCR,
+            _WL_SELECTION,
+
+            // drag'n'drop
+            XdndAware,
+            XdndProxy,
+            XdndSelection,
+            XdndTypeList,
+            XdndActionList,
+            XdndEnter,
+            XdndPosition,
+            XdndStatus,
+            XdndLeave,
+            XdndDrop,
+            XdndFinished,
+            XdndActionCopy,
+            XdndActionMove,
+            XdndActionAsk,
 
             // client -> server
             WM_HINTS,
@@ -278,6 +312,46 @@ pub trait XwmHandler {
     ///
     /// The window will be holding a grab on the mouse button provided.
     fn move_request(&mut self, xwm: XwmId, window: X11Surface, button: u32);
+
+    /// An X11 client wants to read the current wayland selection.
+    ///
+    /// All X11 clients share a single wayland connection, so this is the place to restrict access
+    /// to the selection, e.g. to X11 windows having keyboard focus. Access is denied by default.
+    fn allow_selection_access(&mut self, xwm: XwmId, selection: SelectionTarget) -> bool {
+        let _ = (xwm, selection);
+        false
+    }
+    /// An X11 client requested the contents of the wayland selection.
+    ///
+    /// The data of the given `mime_type` should be written into `fd`, e.g. by using
+    /// [`request_data_device_client_selection`](crate::wayland::data_device::request_data_device_client_selection)
+    /// or [`request_primary_client_selection`](crate::wayland::primary_selection::request_primary_client_selection).
+    fn send_selection(&mut self, xwm: XwmId, selection: SelectionTarget, mime_type: String, fd: OwnedFd) {
+        let _ = (xwm, selection, mime_type, fd);
+    }
+    /// An X11 client set a new selection.
+    ///
+    /// The selection should be offered to wayland clients, e.g. by using
+    /// [`set_data_device_selection`](crate::wayland::data_device::set_data_device_selection)
+    /// or [`set_primary_selection`](crate::wayland::primary_selection::set_primary_selection).
+    /// Requests to read it can be forwarded using [`X11Wm::send_selection`].
+    fn new_selection(&mut self, xwm: XwmId, selection: SelectionTarget, mime_types: Vec<String>) {
+        let _ = (xwm, selection, mime_types);
+    }
+    /// The X11 client owning the selection cleared it.
+    fn cleared_selection(&mut self, xwm: XwmId, selection: SelectionTarget) {
+        let _ = (xwm, selection);
+    }
+
+    /// A drag'n'drop of an X11 client left the X11 windows.
+    ///
+    /// The drag'n'drop should be continued for wayland clients, e.g. by using
+    /// [`start_dnd`](crate::wayland::data_device::start_dnd) with the given mime types and actions.
+    /// Interactions of wayland clients with it should be forwarded using [`X11Wm::set_dnd_action`],
+    /// [`X11Wm::send_dnd_data`] and [`X11Wm::finish_dnd`].
+    fn start_dnd(&mut self, xwm: XwmId, mime_types: Vec<String>, actions: DndAction) {
+        let _ = (xwm, mime_types, actions);
+    }
 }
 
 /// The runtime state of an reparenting XWayland window manager.
@@ -294,6 +368,10 @@ pub struct X11Wm {
     unpaired_surfaces: HashMap<u32, X11Window>,
     sequences_to_ignore: BinaryHeap<Reverse<u16>>,
 
+    clipboard: XWmSelection,
+    primary: XWmSelection,
+    dnd: XWmDnd,
+
     windows: Vec<X11Surface>,
     // oldest mapped -> newest
     client_list: Vec<X11Window>,
@@ -306,6 +384,9 @@ impl Drop for X11Wm {
     fn drop(&mut self) {
         // TODO: Not really needed for Xwayland, but maybe cleanup set root properties?
         let _ = self.conn.destroy_window(self.wm_window);
+        let _ = self.conn.destroy_window(self.clipboard.window);
+        let _ = self.conn.destroy_window(self.primary.window);
+        let _ = self.conn.destroy_window(self.dnd.selection.window);
         XWM_IDS.lock().unwrap().remove(&self.id.0);
     }
 }
@@ -383,6 +464,8 @@ impl X11Wm {
         let stream = DefaultStream::from_unix_stream(connection)?;
         let conn = RustConnection::connect_to_stream(stream, screen)?;
         let atoms = Atoms::new(&conn)?.reply()?;
+        // XFixes is used to track selection owners
+        conn.xfixes_query_version(5, 0)?.reply()?;
 
         let screen = conn.setup().roots[0].clone();
 
@@ -437,6 +520,21 @@ impl X11Wm {
         conn.set_selection_owner(win, atoms._NET_WM_CM_S0, x11rb::CURRENT_TIME)?;
         conn.composite_redirect_subwindows(screen.root, Redirect::MANUAL)?;
 
+        // Windows used to own and receive the selections
+        let clipboard = XWmSelection::new(
+            &conn,
+            &screen,
+            &atoms,
+            SelectionKind::Selection(SelectionTarget::Clipboard),
+        )?;
+        let primary = XWmSelection::new(
+            &conn,
+            &screen,
+            &atoms,
+            SelectionKind::Selection(SelectionTarget::Primary),
+        )?;
+        let dnd = XWmDnd::new(&conn, &screen, &atoms)?;
+
         // Set some EWMH properties
         conn.change_property32(
             PropMode::REPLACE,
@@ -531,14 +629,18 @@ impl X11Wm {
             wl_client: client,
             unpaired_surfaces: Default::default(),
             sequences_to_ignore: Default::default(),
+            clipboard,
+            primary,
+            dnd,
             windows: Vec::new(),
             client_list: Vec::new(),
             client_list_stacking: Vec::new(),
             log: log.clone(),
         };
 
+        let loop_handle = handle.clone();
         handle.insert_source(source, move |event, _, data| {
-            if let Err(err) = handle_event(data, id, event) {
+            if let Err(err) = handle_event(data, &loop_handle, id, event) {
                 slog::warn!(log, "Failed to handle X11 event ({:?}): {}", id, err);
             }
         })?;
@@ -818,7 +920,12 @@ impl X11Wm {
     }
 }
 
-fn handle_event<D: XwmHandler>(state: &mut D, xwmid: XwmId, event: Event) -> Result<(), ReplyOrIdError> {
+fn handle_event<D: XwmHandler + 'static>(
+    state: &mut D,
+    loop_handle: &LoopHandle<'static, D>,
+    xwmid: XwmId,
+    event: Event,
+) -> Result<(), ReplyOrIdError> {
     let xwm = state.xwm_state(xwmid);
     let id = xwm.id;
     let conn = xwm.conn.clone();
@@ -852,7 +959,11 @@ fn handle_event<D: XwmHandler>(state: &mut D, xwmid: XwmId, event: Event) -> Res
 
     match event {
         Event::CreateNotify(n) => {
-            if n.window == xwm.wm_window {
+            if n.window == xwm.wm_window
+                || n.window == xwm.clipboard.window
+                || n.window == xwm.primary.window
+                || n.window == xwm.dnd.selection.window
+            {
                 return Ok(());
             }
 
@@ -1103,10 +1214,22 @@ fn handle_event<D: XwmHandler>(state: &mut D, xwmid: XwmId, event: Event) -> Res
             }
         }
         Event::PropertyNotify(n) => {
-            if let Some(surface) = xwm.windows.iter().find(|x| x.window_id() == n.window) {
-                surface.update_properties(Some(n.atom))?;
+            if !selection::handle_property_notify(state, loop_handle, id, &n)? {
+                let xwm = state.xwm_state(xwmid);
+                if let Some(surface) = xwm.windows.iter().find(|x| x.window_id() == n.window) {
+                    surface.update_properties(Some(n.atom))?;
+                }
             }
         }
+        Event::SelectionRequest(n) => {
+            selection::handle_selection_request(state, loop_handle, id, n)?;
+        }
+        Event::SelectionNotify(n) => {
+            selection::handle_selection_notify(state, loop_handle, id, n)?;
+        }
+        Event::XfixesSelectionNotify(n) => {
+            selection::handle_xfixes_selection_notify(state, loop_handle, id, n)?;
+        }
         Event::FocusIn(n) => {
             conn.change_property32(
                 PropMode::REPLACE,
@@ -1135,6 +1258,9 @@ fn handle_event<D: XwmHandler>(state: &mut D, xwmid: XwmId, event: Event) -> Res
                 );
             }
             match msg.type_ {
+                _ if msg.window == xwm.dnd.selection.window => {
+                    dnd::handle_client_message(state, id, msg)?;
+                }
                 x if x == xwm.atoms.WL_SURFACE_ID => {
                     let wid = msg.data.as_data32()[0];
                     slog::info!(
@@ -0,0 +1,780 @@
+//! Bridging of the `CLIPBOARD` and `PRIMARY` selections between X11 and wayland clients
+//!
+//! The [`X11Wm`] owns both X11 selections on behalf of wayland clients and
+//! tracks selections set by X11 clients using the XFixes extension.
+//!
+//! Transfers in both directions are performed asynchronously through the event loop,
+//! large payloads are split into chunks using the `INCR` mechanism of the ICCCM.
+//! The same machinery is used for the `XdndSelection` of drag'n'drop operations.
+
+use std::{
+    collections::VecDeque,
+    os::unix::io::{AsRawFd, FromRawFd, OwnedFd},
+    sync::{Arc, Mutex},
+};
+
+use calloop::{generic::Generic, Interest, LoopHandle, Mode, PostAction, RegistrationToken};
+use nix::{
+    errno::Errno,
+    fcntl::{self, OFlag},
+    unistd,
+};
+use x11rb::{
+    connection::Connection as _,
+    errors::ReplyOrIdError,
+    protocol::{
+        xfixes::{
+            ConnectionExt as _, SelectionEventMask, SelectionNotifyEvent as XfixesSelectionNotifyEvent,
+        },
+        xproto::{
+            Atom, AtomEnum, ChangeWindowAttributesAux, ConnectionExt as _, CreateWindowAux, EventMask,
+            PropMode, Property, PropertyNotifyEvent, Screen, SelectionNotifyEvent, SelectionRequestEvent,
+            Timestamp, Window as X11Window, WindowClass, SELECTION_NOTIFY_EVENT,
+        },
+    },
+    rust_connection::RustConnection,
+    wrapper::ConnectionExt as _,
+};
+
+use super::{Atoms, X11Wm, XwmHandler, XwmId, XWM_IDS};
+
+/// Maximum size of a property written at once, larger transfers use `INCR`
+const INCR_CHUNK_SIZE: usize = 64 * 1024;
+/// Maximum length of a property read at once (in 32-bit units)
+const MAX_PROPERTY_LENGTH: u32 = 0x1fffffff;
+
+/// Selection bridged between X11 and wayland clients
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SelectionTarget {
+    /// The X11 `PRIMARY` selection, see [`primary_selection`](crate::wayland::primary_selection)
+    Primary,
+    /// The X11 `CLIPBOARD` selection, see [`data_device`](crate::wayland::data_device)
+    Clipboard,
+}
+
+/// Kind of an X11 selection handled by the XWM
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum SelectionKind {
+    /// A selection bridged with the matching wayland selection
+    Selection(SelectionTarget),
+    /// The `XdndSelection` used to transfer the data of drag'n'drop operations
+    Dnd,
+}
+
+#[derive(Debug, Default)]
+struct TransferBuffer {
+    data: Vec<u8>,
+    finished: bool,
+}
+
+/// Transfer of X11 selection data to a wayland client
+#[derive(Debug)]
+struct IncomingTransfer {
+    target: Atom,
+    incr: bool,
+    fd: Option<OwnedFd>,
+    writer: Option<(RegistrationToken, Arc<Mutex<TransferBuffer>>)>,
+}
+
+impl IncomingTransfer {
+    fn push_data<D: 'static>(&mut self, loop_handle: &LoopHandle<'static, D>, data: &[u8], finished: bool) {
+        if self.writer.is_none() {
+            let Some(fd) = self.fd.take() else {
+                return;
+            };
+            if fcntl::fcntl(fd.as_raw_fd(), fcntl::F_SETFL(OFlag::O_NONBLOCK)).is_err() {
+                return;
+            }
+
+            let buffer = Arc::new(Mutex::new(TransferBuffer::default()));
+            let writer_buffer = buffer.clone();
+            let token =
+                loop_handle.insert_source(Generic::new(fd, Interest::WRITE, Mode::Level), move |_, fd, _| {
+                    let mut buffer = writer_buffer.lock().unwrap();
+                    while !buffer.data.is_empty() {
+                        match unistd::write(fd.as_raw_fd(), &buffer.data) {
+                            Ok(written) => {
+                                buffer.data.drain(..written);
+                            }
+                            Err(Errno::EINTR) => continue,
+                            Err(Errno::EAGAIN) => return Ok(PostAction::Continue),
+                            Err(_) => {
+                                // the reading side went away, abort the transfer
+                                buffer.data.clear();
+                                buffer.finished = true;
+                                return Ok(PostAction::Remove);
+                            }
+                        }
+                    }
+
+                    if buffer.finished {
+                        Ok(PostAction::Remove)
+                    } else {
+                        Ok(PostAction::Disable)
+                    }
+                });
+            match token {
+                Ok(token) => self.writer = Some((token, buffer)),
+                Err(_) => return,
+            }
+        }
+
+        let (token, buffer) = self.writer.as_ref().unwrap();
+        {
+            let mut buffer = buffer.lock().unwrap();
+            if buffer.finished {
+                return;
+            }
+            buffer.data.extend_from_slice(data);
+            buffer.finished = finished;
+        }
+        let _ = loop_handle.enable(token);
+    }
+
+    fn cancel<D: 'static>(mut self, loop_handle: &LoopHandle<'static, D>) {
+        // anything already received is still delivered, but the transfer ends here
+        if self.writer.is_some() {
+            self.push_data(loop_handle, &[], true);
+        }
+    }
+}
+
+/// Transfer of wayland selection data to an X11 client using `INCR`
+#[derive(Debug)]
+struct OutgoingTransfer {
+    requestor: X11Window,
+    property: Atom,
+    target: Atom,
+    data: Vec<u8>,
+    offset: usize,
+}
+
+/// State of a single X11 selection
+#[derive(Debug)]
+pub(super) struct XWmSelection {
+    kind: SelectionKind,
+    atom: Atom,
+    pub(super) window: X11Window,
+    /// current owner of the X11 selection, our `window` if we provide the wayland selection
+    pub(super) owner: X11Window,
+    /// mime types of the wayland selection
+    pub(super) mime_types: Vec<String>,
+    timestamp: Timestamp,
+
+    incoming: Option<IncomingTransfer>,
+    pending: VecDeque<(Atom, OwnedFd)>,
+    outgoing: Vec<OutgoingTransfer>,
+}
+
+impl XWmSelection {
+    pub(super) fn new(
+        conn: &RustConnection,
+        screen: &Screen,
+        atoms: &Atoms,
+        kind: SelectionKind,
+    ) -> Result<Self, ReplyOrIdError> {
+        let atom = match kind {
+            SelectionKind::Selection(SelectionTarget::Primary) => atoms.PRIMARY,
+            SelectionKind::Selection(SelectionTarget::Clipboard) => atoms.CLIPBOARD,
+            SelectionKind::Dnd => atoms.XdndSelection,
+        };
+
+        let window = conn.generate_id()?;
+        conn.create_window(
+            screen.root_depth,
+            window,
+            screen.root,
+            // x, y, width, height, border width
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            x11rb::COPY_FROM_PARENT,
+            &CreateWindowAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+        )?;
+        conn.xfixes_select_selection_input(
+            window,
+            atom,
+            SelectionEventMask::SET_SELECTION_OWNER
+                | SelectionEventMask::SELECTION_WINDOW_DESTROY
+                | SelectionEventMask::SELECTION_CLIENT_CLOSE,
+        )?;
+
+        Ok(XWmSelection {
+            kind,
+            atom,
+            window,
+            owner: x11rb::NONE,
+            mime_types: Vec::new(),
+            timestamp: x11rb::CURRENT_TIME,
+            incoming: None,
+            pending: VecDeque::new(),
+            outgoing: Vec::new(),
+        })
+    }
+
+    /// Queue a request for the contents of the selection as `target`, to be written into `fd`
+    pub(super) fn request(
+        &mut self,
+        conn: &RustConnection,
+        atoms: &Atoms,
+        target: Atom,
+        fd: OwnedFd,
+    ) -> Result<(), ReplyOrIdError> {
+        self.pending.push_back((target, fd));
+        self.start_next_transfer(conn, atoms)
+    }
+
+    fn start_next_transfer(&mut self, conn: &RustConnection, atoms: &Atoms) -> Result<(), ReplyOrIdError> {
+        if self.incoming.is_some() {
+            return Ok(());
+        }
+
+        if let Some((target, fd)) = self.pending.pop_front() {
+            conn.convert_selection(
+                self.window,
+                self.atom,
+                target,
+                atoms._WL_SELECTION,
+                x11rb::CURRENT_TIME,
+            )?;
+            self.incoming = Some(IncomingTransfer {
+                target,
+                incr: false,
+                fd: Some(fd),
+                writer: None,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl X11Wm {
+    fn selection_by_atom(&mut self, atom: Atom) -> Option<&mut XWmSelection> {
+        if self.clipboard.atom == atom {
+            Some(&mut self.clipboard)
+        } else if self.primary.atom == atom {
+            Some(&mut self.primary)
+        } else if self.dnd.selection.atom == atom {
+            Some(&mut self.dnd.selection)
+        } else {
+            None
+        }
+    }
+
+    /// Notify the XWM about a new wayland selection
+    ///
+    /// This should be called whenever a wayland client sets a new selection, e.g. in
+    /// [`DataDeviceHandler::new_selection`](crate::wayland::data_device::DataDeviceHandler::new_selection),
+    /// with the mime types offered by the client, or `None` if the selection was cleared.
+    ///
+    /// The XWM will take ownership of the matching X11 selection and forward any requests
+    /// of X11 clients to [`XwmHandler::send_selection`].
+    pub fn new_selection(
+        &mut self,
+        selection: SelectionTarget,
+        mime_types: Option<Vec<String>>,
+    ) -> Result<(), ReplyOrIdError> {
+        let conn = self.conn.clone();
+        let sel = match selection {
+            SelectionTarget::Primary => &mut self.primary,
+            SelectionTarget::Clipboard => &mut self.clipboard,
+        };
+
+        match mime_types {
+            Some(mime_types) => {
+                sel.mime_types = mime_types;
+                sel.owner = sel.window;
+                conn.set_selection_owner(sel.window, sel.atom, x11rb::CURRENT_TIME)?;
+            }
+            None => {
+                if sel.owner == sel.window {
+                    sel.mime_types.clear();
+                    sel.owner = x11rb::NONE;
+                    conn.set_selection_owner(x11rb::NONE, sel.atom, x11rb::CURRENT_TIME)?;
+                }
+            }
+        }
+
+        conn.flush()?;
+        Ok(())
+    }
+
+    /// Request the contents of a selection provided by an X11 client
+    ///
+    /// This should be called when a wayland client wants to read a selection, that was
+    /// set as a response to [`XwmHandler::new_selection`], e.g. in
+    /// [`DataDeviceHandler::send_selection`](crate::wayland::data_device::DataDeviceHandler::send_selection).
+    ///
+    /// The data will be written into `fd` asynchronously, after which `fd` is closed.
+    pub fn send_selection(
+        &mut self,
+        selection: SelectionTarget,
+        mime_type: String,
+        fd: OwnedFd,
+    ) -> Result<(), ReplyOrIdError> {
+        let conn = self.conn.clone();
+        let atoms = self.atoms;
+
+        let target = mime_to_atom(&conn, &atoms, &mime_type)?;
+        let sel = match selection {
+            SelectionTarget::Primary => &mut self.primary,
+            SelectionTarget::Clipboard => &mut self.clipboard,
+        };
+        if sel.owner == x11rb::NONE || sel.owner == sel.window {
+            slog::debug!(
+                self.log,
+                "Ignoring request for {:?} selection not owned by any X11 client",
+                selection
+            );
+            return Ok(());
+        }
+
+        sel.request(&conn, &atoms, target, fd)?;
+
+        conn.flush()?;
+        Ok(())
+    }
+}
+
+pub(super) fn mime_to_atom(
+    conn: &RustConnection,
+    atoms: &Atoms,
+    mime_type: &str,
+) -> Result<Atom, ReplyOrIdError> {
+    Ok(match mime_type {
+        "text/plain;charset=utf-8" => atoms.UTF8_STRING,
+        "text/plain" => atoms.TEXT,
+        mime_type => conn.intern_atom(false, mime_type.as_bytes())?.reply()?.atom,
+    })
+}
+
+pub(super) fn atom_to_mime(
+    conn: &RustConnection,
+    atoms: &Atoms,
+    atom: Atom,
+) -> Result<Option<String>, ReplyOrIdError> {
+    if atom == atoms.UTF8_STRING {
+        return Ok(Some(String::from("text/plain;charset=utf-8")));
+    }
+    if atom == atoms.TEXT || atom == u32::from(AtomEnum::STRING) {
+        return Ok(Some(String::from("text/plain")));
+    }
+
+    let name = conn.get_atom_name(atom)?.reply()?.name;
+    // skip special targets like TARGETS, TIMESTAMP or MULTIPLE
+    Ok(String::from_utf8(name).ok().filter(|name| name.contains('/')))
+}
+
+fn send_selection_notify(
+    conn: &RustConnection,
+    request: &SelectionRequestEvent,
+    property: Atom,
+) -> Result<(), ReplyOrIdError> {
+    conn.send_event(
+        false,
+        request.requestor,
+        EventMask::NO_EVENT,
+        SelectionNotifyEvent {
+            response_type: SELECTION_NOTIFY_EVENT,
+            sequence: 0,
+            time: request.time,
+            requestor: request.requestor,
+            selection: request.selection,
+            target: request.target,
+            property,
+        },
+    )?;
+    conn.flush()?;
+    Ok(())
+}
+
+/// An X11 client requests the contents of a selection we own
+pub(super) fn handle_selection_request<D: XwmHandler + 'static>(
+    state: &mut D,
+    loop_handle: &LoopHandle<'static, D>,
+    xwm_id: XwmId,
+    request: SelectionRequestEvent,
+) -> Result<(), ReplyOrIdError> {
+    let xwm = state.xwm_state(xwm_id);
+    let conn = xwm.conn.clone();
+    let atoms = xwm.atoms;
+    let log = xwm.log.clone();
+
+    let Some(sel) = xwm.selection_by_atom(request.selection) else {
+        return send_selection_notify(&conn, &request, x11rb::NONE);
+    };
+    if sel.owner != sel.window || request.owner != sel.window {
+        return send_selection_notify(&conn, &request, x11rb::NONE);
+    }
+
+    // obsolete clients might not specify a property
+    let property = if request.property == x11rb::NONE {
+        request.target
+    } else {
+        request.property
+    };
+
+    if request.target == atoms.TARGETS {
+        let mut targets = vec![atoms.TARGETS, atoms.TIMESTAMP];
+        for mime_type in sel.mime_types.iter() {
+            let atom = mime_to_atom(&conn, &atoms, mime_type)?;
+            if !targets.contains(&atom) {
+                targets.push(atom);
+            }
+        }
+        conn.change_property32(
+            PropMode::REPLACE,
+            request.requestor,
+            property,
+            AtomEnum::ATOM,
+            &targets,
+        )?;
+        return send_selection_notify(&conn, &request, property);
+    }
+
+    if request.target == atoms.TIMESTAMP {
+        conn.change_property32(
+            PropMode::REPLACE,
+            request.requestor,
+            property,
+            AtomEnum::INTEGER,
+            &[sel.timestamp],
+        )?;
+        return send_selection_notify(&conn, &request, property);
+    }
+
+    let candidates = if request.target == atoms.UTF8_STRING {
+        vec![
+            String::from("text/plain;charset=utf-8"),
+            String::from("text/plain"),
+        ]
+    } else if request.target == atoms.TEXT || request.target == u32::from(AtomEnum::STRING) {
+        vec![
+            String::from("text/plain"),
+            String::from("text/plain;charset=utf-8"),
+        ]
+    } else {
+        atom_to_mime(&conn, &atoms, request.target)?.into_iter().collect()
+    };
+    let Some(mime_type) = candidates
+        .into_iter()
+        .find(|mime_type| sel.mime_types.contains(mime_type))
+    else {
+        slog::debug!(
+            log,
+            "Refusing selection request for unavailable target {}",
+            request.target
+        );
+        return send_selection_notify(&conn, &request, x11rb::NONE);
+    };
+    let kind = sel.kind;
+
+    if let SelectionKind::Selection(selection) = kind {
+        if !state.allow_selection_access(xwm_id, selection) {
+            slog::debug!(log, "Denied access to the {:?} selection", selection);
+            return send_selection_notify(&conn, &request, x11rb::NONE);
+        }
+    }
+
+    let (read_fd, write_fd) = match unistd::pipe2(OFlag::O_CLOEXEC | OFlag::O_NONBLOCK) {
+        Ok((read_fd, write_fd)) => unsafe { (OwnedFd::from_raw_fd(read_fd), OwnedFd::from_raw_fd(write_fd)) },
+        Err(err) => {
+            slog::warn!(log, "Failed to create pipe for selection transfer: {}", err);
+            return send_selection_notify(&conn, &request, x11rb::NONE);
+        }
+    };
+    match kind {
+        SelectionKind::Selection(selection) => state.send_selection(xwm_id, selection, mime_type, write_fd),
+        SelectionKind::Dnd => match state.xwm_state(xwm_id).dnd.wayland_offer() {
+            // the drop target requests the data of a wayland drag'n'drop
+            Some(offer) => offer.send(mime_type, write_fd),
+            None => return send_selection_notify(&conn, &request, x11rb::NONE),
+        },
+    }
+
+    let mut data = Vec::new();
+    let ret = loop_handle.insert_source(
+        Generic::new(read_fd, Interest::READ, Mode::Level),
+        move |_, fd, state| {
+            if !XWM_IDS.lock().unwrap().contains(&xwm_id.0) {
+                // the xwm is gone
+                return Ok(PostAction::Remove);
+            }
+
+            let mut buf = [0u8; 4096];
+            loop {
+                match unistd::read(fd.as_raw_fd(), &mut buf) {
+                    Ok(0) => {
+                        let xwm = state.xwm_state(xwm_id);
+                        if let Err(err) = xwm.finish_outgoing(&request, property, std::mem::take(&mut data)) {
+                            slog::warn!(xwm.log, "Failed to transfer selection to X11 client: {}", err);
+                        }
+                        return Ok(PostAction::Remove);
+                    }
+                    Ok(len) => data.extend_from_slice(&buf[..len]),
+                    Err(Errno::EINTR) => continue,
+                    Err(Errno::EAGAIN) => return Ok(PostAction::Continue),
+                    Err(err) => {
+                        let xwm = state.xwm_state(xwm_id);
+                        slog::warn!(xwm.log, "Failed to read selection data: {}", err);
+                        let _ = send_selection_notify(&xwm.conn, &request, x11rb::NONE);
+                        return Ok(PostAction::Remove);
+                    }
+                }
+            }
+        },
+    );
+    if let Err(err) = ret {
+        slog::warn!(log, "Failed to insert selection transfer source: {}", err);
+        return send_selection_notify(&conn, &request, x11rb::NONE);
+    }
+
+    Ok(())
+}
+
+impl X11Wm {
+    fn finish_outgoing(
+        &mut self,
+        request: &SelectionRequestEvent,
+        property: Atom,
+        data: Vec<u8>,
+    ) -> Result<(), ReplyOrIdError> {
+        let conn = self.conn.clone();
+        let atoms = self.atoms;
+        let Some(sel) = self.selection_by_atom(request.selection) else {
+            return Ok(());
+        };
+
+        if data.len() > INCR_CHUNK_SIZE {
+            // we get notified, once the requestor is ready for the next chunk
+            conn.change_window_attributes(
+                request.requestor,
+                &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+            )?;
+            conn.change_property32(
+                PropMode::REPLACE,
+                request.requestor,
+                property,
+                atoms.INCR,
+                &[data.len() as u32],
+            )?;
+            sel.outgoing.push(OutgoingTransfer {
+                requestor: request.requestor,
+                property,
+                target: request.target,
+                data,
+                offset: 0,
+            });
+        } else {
+            conn.change_property8(
+                PropMode::REPLACE,
+                request.requestor,
+                property,
+                request.target,
+                &data,
+            )?;
+        }
+
+        send_selection_notify(&conn, request, property)
+    }
+}
+
+/// The X11 selection owner replied to one of our conversion requests
+pub(super) fn handle_selection_notify<D: XwmHandler + 'static>(
+    state: &mut D,
+    loop_handle: &LoopHandle<'static, D>,
+    xwm_id: XwmId,
+    notify: SelectionNotifyEvent,
+) -> Result<(), ReplyOrIdError> {
+    let xwm = state.xwm_state(xwm_id);
+    let conn = xwm.conn.clone();
+    let atoms = xwm.atoms;
+
+    let Some(sel) = xwm.selection_by_atom(notify.selection) else {
+        return Ok(());
+    };
+    if notify.requestor != sel.window {
+        return Ok(());
+    }
+
+    if notify.target == atoms.TARGETS {
+        let SelectionKind::Selection(selection) = sel.kind else {
+            return Ok(());
+        };
+        if notify.property == x11rb::NONE {
+            return Ok(());
+        }
+
+        let reply = conn
+            .get_property(true, sel.window, atoms._WL_SELECTION, AtomEnum::ATOM, 0, 4096)?
+            .reply()?;
+        let mut mime_types = Vec::new();
+        if let Some(targets) = reply.value32() {
+            for atom in targets {
+                if let Some(mime_type) = atom_to_mime(&conn, &atoms, atom)? {
+                    if !mime_types.contains(&mime_type) {
+                        mime_types.push(mime_type);
+                    }
+                }
+            }
+        }
+        state.new_selection(xwm_id, selection, mime_types);
+        return Ok(());
+    }
+
+    match sel.incoming.as_mut() {
+        Some(transfer) if transfer.target == notify.target => {
+            if notify.property == x11rb::NONE {
+                // the conversion failed, dropping the fd signals the wayland client
+                sel.incoming = None;
+            } else {
+                let reply = conn
+                    .get_property(
+                        true,
+                        sel.window,
+                        atoms._WL_SELECTION,
+                        AtomEnum::ANY,
+                        0,
+                        MAX_PROPERTY_LENGTH,
+                    )?
+                    .reply()?;
+                if reply.type_ == atoms.INCR {
+                    // deleting the property signals the owner to start sending chunks
+                    transfer.incr = true;
+                } else {
+                    transfer.push_data(loop_handle, &reply.value, true);
+                    sel.incoming = None;
+                }
+            }
+            sel.start_next_transfer(&conn, &atoms)?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// A property of a window changed, which might be part of an `INCR` transfer
+///
+/// Returns `true` if the event was handled
+pub(super) fn handle_property_notify<D: XwmHandler + 'static>(
+    state: &mut D,
+    loop_handle: &LoopHandle<'static, D>,
+    xwm_id: XwmId,
+    notify: &PropertyNotifyEvent,
+) -> Result<bool, ReplyOrIdError> {
+    let xwm = state.xwm_state(xwm_id);
+    let conn = xwm.conn.clone();
+    let atoms = xwm.atoms;
+
+    for sel in [&mut xwm.clipboard, &mut xwm.primary, &mut xwm.dnd.selection] {
+        // next chunk of an incoming transfer
+        if notify.window == sel.window {
+            if notify.atom == atoms._WL_SELECTION && notify.state == Property::NEW_VALUE {
+                if let Some(transfer) = sel.incoming.as_mut().filter(|transfer| transfer.incr) {
+                    let reply = conn
+                        .get_property(
+                            true,
+                            sel.window,
+                            atoms._WL_SELECTION,
+                            AtomEnum::ANY,
+                            0,
+                            MAX_PROPERTY_LENGTH,
+                        )?
+                        .reply()?;
+                    if reply.value.is_empty() {
+                        transfer.push_data(loop_handle, &[], true);
+                        sel.incoming = None;
+                        sel.start_next_transfer(&conn, &atoms)?;
+                    } else {
+                        transfer.push_data(loop_handle, &reply.value, false);
+                    }
+                }
+            }
+            return Ok(true);
+        }
+
+        // requestor is ready for the next chunk of an outgoing transfer
+        if notify.state == Property::DELETE {
+            if let Some(pos) = sel
+                .outgoing
+                .iter()
+                .position(|t| t.requestor == notify.window && t.property == notify.atom)
+            {
+                let transfer = &mut sel.outgoing[pos];
+                let end = (transfer.offset + INCR_CHUNK_SIZE).min(transfer.data.len());
+                conn.change_property8(
+                    PropMode::REPLACE,
+                    transfer.requestor,
+                    transfer.property,
+                    transfer.target,
+                    &transfer.data[transfer.offset..end],
+                )?;
+                // an empty chunk marks the end of the transfer
+                if transfer.offset == end {
+                    sel.outgoing.remove(pos);
+                } else {
+                    transfer.offset = end;
+                }
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+/// The owner of an X11 selection changed
+pub(super) fn handle_xfixes_selection_notify<D: XwmHandler + 'static>(
+    state: &mut D,
+    loop_handle: &LoopHandle<'static, D>,
+    xwm_id: XwmId,
+    notify: XfixesSelectionNotifyEvent,
+) -> Result<(), ReplyOrIdError> {
+    let xwm = state.xwm_state(xwm_id);
+    let conn = xwm.conn.clone();
+    let atoms = xwm.atoms;
+
+    let Some(sel) = xwm.selection_by_atom(notify.selection) else {
+        return Ok(());
+    };
+
+    if notify.owner == sel.window {
+        // we became the owner on behalf of a wayland client
+        sel.owner = notify.owner;
+        sel.timestamp = notify.selection_timestamp;
+        return Ok(());
+    }
+
+    let previous_owner = std::mem::replace(&mut sel.owner, notify.owner);
+    // any running transfers are from the previous owner
+    if let Some(transfer) = sel.incoming.take() {
+        transfer.cancel(loop_handle);
+    }
+    sel.pending.clear();
+    sel.mime_types.clear();
+
+    let SelectionKind::Selection(selection) = sel.kind else {
+        // drag'n'drop offers are announced through XDND client messages
+        return Ok(());
+    };
+    if notify.owner == x11rb::NONE {
+        if previous_owner != x11rb::NONE && previous_owner != sel.window {
+            state.cleared_selection(xwm_id, selection);
+        }
+    } else {
+        // ask the new owner for the available targets
+        conn.convert_selection(
+            sel.window,
+            sel.atom,
+            atoms.TARGETS,
+            atoms._WL_SELECTION,
+            notify.timestamp,
+        )?;
+    }
+
+    Ok(())
+}